@@ -0,0 +1,100 @@
+//! Alarm triage scenario
+//!
+//! Reads the active alarms and the recent history of every alarm category, ranks the
+//! alarm codes by how often they occurred and prints a triage report, as a maintenance
+//! engineer would before a shift hand-over. With `--reset`, the active alarms are reset
+//! afterwards and the status is read back to confirm it.
+//!
+//! Usage: `alarm_triage [--mock] [--reset] [host] [robot_port]`
+
+mod common;
+
+use common::{Error, Scenario};
+use log::info;
+use moto_hses_client::Alarm;
+use moto_hses_proto::{AlarmAttribute, AlarmCategory};
+use std::collections::BTreeMap;
+
+// History entries read per category
+const HISTORY_DEPTH: u16 = 20;
+
+const CATEGORIES: [AlarmCategory; 5] = [
+    AlarmCategory::MajorFailure,
+    AlarmCategory::MonitorAlarm,
+    AlarmCategory::UserAlarmSystem,
+    AlarmCategory::UserAlarmUser,
+    AlarmCategory::OfflineAlarm,
+];
+
+#[tokio::main]
+async fn main() -> Result<(), Error> {
+    env_logger::init();
+    let scenario = Scenario::start(|builder| {
+        builder
+            .with_alarm(alarm(4107, "OUT OF RANGE (ABSO DATA)", "2025/03/10 09:12"))
+            .with_alarm(alarm(1030, "MEMORY ERROR (PARAMETER FILE)", "2025/03/10 09:13"))
+            .with_alarm_history(
+                AlarmCategory::MonitorAlarm,
+                alarm(4107, "OUT OF RANGE (ABSO DATA)", "2025/03/09 22:40"),
+            )
+            .with_alarm_history(
+                AlarmCategory::MonitorAlarm,
+                alarm(4107, "OUT OF RANGE (ABSO DATA)", "2025/03/08 14:05"),
+            )
+            .with_alarm_history(
+                AlarmCategory::UserAlarmUser,
+                alarm(8001, "GRIPPER PRESSURE LOW", "2025/03/09 03:17"),
+            )
+    })
+    .await?;
+    let client = scenario.client().await?;
+
+    let snapshot = client.read_snapshot(&[1]).await?;
+    info!(
+        "Status: servo {}, {} mode, alarm {}",
+        if snapshot.status.is_servo_on() { "on" } else { "off" },
+        if snapshot.status.is_play_mode() { "play" } else { "teach" },
+        snapshot.status.has_alarm()
+    );
+
+    info!("Active alarms ({}):", snapshot.active_alarm_count);
+    let mut active = Vec::new();
+    for instance in 1..=u16::try_from(snapshot.active_alarm_count)? {
+        let alarm = client.read_alarm_data(instance, AlarmAttribute::All).await?;
+        info!("  {} {} [{}] {}", alarm.time, alarm.code, alarm.data, alarm.name);
+        active.push(alarm);
+    }
+
+    // Occurrences of each code in the history, with the alarm seen last
+    let mut occurrences: BTreeMap<u32, (usize, Alarm)> = BTreeMap::new();
+    for category in CATEGORIES {
+        let history = client.read_alarm_history_range(category, 0, HISTORY_DEPTH).await?;
+        info!("{category}: {} recent alarms", history.len());
+        for alarm in history {
+            occurrences
+                .entry(alarm.code)
+                .and_modify(|(count, _)| *count += 1)
+                .or_insert_with(|| (1, alarm));
+        }
+    }
+
+    let mut ranking: Vec<_> = occurrences.into_values().collect();
+    ranking.sort_by(|(a, _), (b, _)| b.cmp(a));
+    info!("Triage report, most frequent first:");
+    for (count, alarm) in &ranking {
+        let marker =
+            if active.iter().any(|active| active.code == alarm.code) { " (ACTIVE)" } else { "" };
+        info!("  {:>4} x{count}: {} - last {}{marker}", alarm.code, alarm.name, alarm.time);
+    }
+
+    if scenario.has_option("--reset") && !active.is_empty() {
+        client.reset_alarm().await?;
+        let status = client.read_status().await?;
+        info!("Alarms reset; alarm status now {}", status.has_alarm());
+    }
+    Ok(())
+}
+
+fn alarm(code: u32, name: &str, time: &str) -> Alarm {
+    Alarm::new(code, 0, 0, time.to_string(), name.to_string())
+}
@@ -0,0 +1,117 @@
+//! Backup tool scenario
+//!
+//! Saves the job and data files of a controller and a snapshot of its variables into a
+//! new local directory, receiving every file a second time to check that it did not change
+//! during the backup. A `MANIFEST.txt` lists the files with their size.
+//!
+//! Usage: `backup_tool [--mock] [host] [robot_port] [file_port]`
+
+mod common;
+
+use common::{Error, Scenario};
+use log::{info, warn};
+use moto_hses_client::{VariableProfile, VariableSnapshot};
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// File name patterns saved by the backup
+const PATTERNS: [&str; 2] = ["*.JBI", "*.DAT"];
+
+// Variables of each type saved by the backup
+const VARIABLE_COUNT: u16 = 16;
+
+#[tokio::main]
+async fn main() -> Result<(), Error> {
+    env_logger::init();
+    let scenario = Scenario::start(|builder| {
+        builder
+            .with_file("WELD-A.JBI", job("WELD-A", "MOVJ VJ=25.00\r\nARCON\r\nMOVL V=80\r\nARCOF"))
+            .with_file("PALLET.JBI", job("PALLET", "MOVJ VJ=50.00\r\nDOUT OT#(1) ON"))
+            .with_file("TOOL.DAT", b"//TOOL 0\r\n0.000,0.000,250.000,0.00,0.00,0.00\r\n".to_vec())
+    })
+    .await?;
+    let files = scenario.file_client().await?;
+    let robot = scenario.client().await?;
+
+    let stamp = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+    let directory = std::env::temp_dir().join(format!("hses-backup-{stamp}"));
+    std::fs::create_dir_all(&directory)?;
+    info!("Backing up {} to {}", scenario.robot_addr.ip(), directory.display());
+
+    let mut manifest = String::new();
+    let mut unverified = 0;
+    for pattern in PATTERNS {
+        for name in files.read_file_list(pattern).await? {
+            let content = files.receive_file(&name).await?;
+            let path = directory.join(&name);
+            std::fs::write(&path, &content)?;
+            match files.receive_file(&name).await {
+                Ok(again) if again == content => {
+                    info!("  {name}: {} bytes, verified", content.len());
+                }
+                Ok(again) => {
+                    return Err(format!(
+                        "{name} changed during the backup: saved {} bytes, controller {} bytes",
+                        content.len(),
+                        again.len()
+                    )
+                    .into());
+                }
+                Err(e) => {
+                    warn!("  {name}: {} bytes, not verified ({e})", content.len());
+                    unverified += 1;
+                }
+            }
+            writeln!(manifest, "{name} {}", content.len())?;
+        }
+    }
+
+    let snapshot = robot.snapshot_variables(&VariableProfile::uniform(VARIABLE_COUNT)).await?;
+    let variables = save_variables(&directory, &snapshot)?;
+    writeln!(manifest, "VARIABLES.txt {} variables", snapshot_len(&snapshot))?;
+    std::fs::write(directory.join("MANIFEST.txt"), manifest)?;
+    info!("Saved {} variables to {}", snapshot_len(&snapshot), variables.display());
+    if unverified > 0 {
+        warn!("{unverified} files could not be received again to verify them");
+    }
+    info!("Backup complete: {}", directory.display());
+    Ok(())
+}
+
+// Write the variables as one `name = value` line each
+fn save_variables(directory: &Path, snapshot: &VariableSnapshot) -> Result<PathBuf, Error> {
+    let mut text = String::new();
+    for (index, value) in snapshot.bytes.iter().enumerate() {
+        writeln!(text, "B{index:03} = {value}")?;
+    }
+    for (index, value) in snapshot.integers.iter().enumerate() {
+        writeln!(text, "I{index:03} = {value}")?;
+    }
+    for (index, value) in snapshot.doubles.iter().enumerate() {
+        writeln!(text, "D{index:03} = {value}")?;
+    }
+    for (index, value) in snapshot.reals.iter().enumerate() {
+        writeln!(text, "R{index:03} = {value}")?;
+    }
+    for (index, value) in snapshot.strings.iter().enumerate() {
+        writeln!(text, "S{index:03} = {value:?}")?;
+    }
+    let path = directory.join("VARIABLES.txt");
+    std::fs::write(&path, text)?;
+    Ok(path)
+}
+
+const fn snapshot_len(snapshot: &VariableSnapshot) -> usize {
+    snapshot.bytes.len()
+        + snapshot.integers.len()
+        + snapshot.doubles.len()
+        + snapshot.reals.len()
+        + snapshot.strings.len()
+}
+
+// Minimal job file with the given instructions
+fn job(name: &str, instructions: &str) -> Vec<u8> {
+    let header = format!("/JOB\r\n//NAME {name}\r\n//POS\r\n///NPOS 0,0,0,0,0,0\r\n//INST\r\n");
+    format!("{header}NOP\r\n{instructions}\r\nEND\r\n").into_bytes()
+}
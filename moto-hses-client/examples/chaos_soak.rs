@@ -0,0 +1,158 @@
+//! Resilience soak test against a mock server with random faults
+//!
+//! Starts a mock server with `MockConfig::chaos(seed)`, which drops, duplicates and
+//! delays responses and answers some requests with an error status, and drives mixed
+//! register, I/O, variable and status traffic through several clients with the default
+//! timeout and retries. It fails if more than the allowed share of the operations fail
+//! after all retries, or if a client does not answer once the faults stop; the run
+//! is reproduced with the same seed.
+//!
+//! Usage: `chaos_soak [duration_secs] [seed] [max_error_percent]`, by default 60 seconds
+//! with seed 1 and at most 2% of the operations failing.
+
+use log::{LevelFilter, info, warn};
+use moto_hses_client::{ClientConfig, ClientError, HsesClient};
+use moto_hses_mock::MockConfig;
+use moto_hses_mock::server::MockServerBuilder;
+use std::time::Duration;
+use tokio::time::Instant;
+
+const DEFAULT_DURATION_SECS: u64 = 60;
+const DEFAULT_SEED: u64 = 1;
+const DEFAULT_MAX_ERROR_PERCENT: f64 = 2.0;
+const WORKERS: u16 = 4;
+// Pause between the operations of a worker
+const PERIOD: Duration = Duration::from_millis(5);
+
+// Outcome of one worker
+#[derive(Debug, Default)]
+struct WorkerReport {
+    operations: u64,
+    failures: u64,
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    env_logger::init();
+    let args: Vec<String> = std::env::args().collect();
+    let arg = |index: usize| args.get(index).map(String::as_str);
+    let duration = Duration::from_secs(
+        arg(1).map_or(Ok(DEFAULT_DURATION_SECS), str::parse).map_err(|e| format!("{e}"))?,
+    );
+    let seed = arg(2).map_or(Ok(DEFAULT_SEED), str::parse).map_err(|e| format!("{e}"))?;
+    let max_error_percent =
+        arg(3).map_or(Ok(DEFAULT_MAX_ERROR_PERCENT), str::parse).map_err(|e| format!("{e}"))?;
+
+    let mut config = MockConfig::chaos(seed);
+    config.robot_port = 0;
+    config.file_port = 0;
+    let server =
+        MockServerBuilder::from_config(config).with_log_level(LevelFilter::Warn).build().await?;
+    let addr = server.local_addr()?;
+    let mock = server.handle();
+    let server_task = tokio::spawn(async move { server.run().await });
+
+    info!("Soaking {addr} for {duration:?} with chaos seed {seed} from {WORKERS} workers");
+    let started_at = Instant::now();
+    let mut tasks = Vec::new();
+    for worker in 0..WORKERS {
+        let config = ClientConfig {
+            host: addr.ip().to_string(),
+            port: addr.port(),
+            ..ClientConfig::default()
+        };
+        let client = HsesClient::new_with_config(config).await?;
+        tasks.push(tokio::spawn(run_worker(client, worker, duration)));
+    }
+
+    let mut report = WorkerReport::default();
+    let mut clients = Vec::new();
+    for task in tasks {
+        let (client, worker) = task.await?;
+        clients.push(client);
+        report.operations += worker.operations;
+        report.failures += worker.failures;
+    }
+    let metrics = mock.metrics().await;
+    info!("Operations: {} in {:.2?}", report.operations, started_at.elapsed());
+    info!(
+        "Faults: {} responses dropped and {} error statuses in {} requests, including retries",
+        metrics.dropped_responses,
+        metrics.chaos_errors,
+        mock.robot_request_count().await
+    );
+    #[allow(clippy::cast_precision_loss)] // Far fewer operations than 2^52
+    let error_percent = report.failures as f64 * 100.0 / report.operations.max(1) as f64;
+    info!("Failed after retries: {} ({error_percent:.2}%)", report.failures);
+
+    // Every client answers again once the faults stop
+    mock.set_chaos(None).await;
+    for client in &clients {
+        client.read_status().await?;
+    }
+    server_task.abort();
+
+    if error_percent > max_error_percent {
+        return Err(format!(
+            "{error_percent:.2}% of the operations failed, more than {max_error_percent}%"
+        )
+        .into());
+    }
+    info!("Error rate within {max_error_percent}%");
+    Ok(())
+}
+
+// Send one operation per `PERIOD` until `duration` has passed
+async fn run_worker(
+    client: HsesClient,
+    worker: u16,
+    duration: Duration,
+) -> (HsesClient, WorkerReport) {
+    let mut report = WorkerReport::default();
+    let started_at = Instant::now();
+    let mut sequence: u32 = 0;
+    while started_at.elapsed() < duration {
+        if let Err(e) = send_operation(&client, worker, sequence).await {
+            warn!("Worker {worker}: operation {sequence} failed: {e}");
+            report.failures += 1;
+        }
+        report.operations += 1;
+        sequence = sequence.wrapping_add(1);
+        tokio::time::sleep(PERIOD).await;
+    }
+    (client, report)
+}
+
+// One of the commonly used operations, in turn; writes are read back
+async fn send_operation(
+    client: &HsesClient,
+    worker: u16,
+    sequence: u32,
+) -> Result<(), ClientError> {
+    // Each worker uses its own register, I/O and variable so that workers do not interfere
+    let value = i16::try_from(sequence % 1000).unwrap_or_default();
+    match sequence % 4 {
+        0 => client.read_status().await.map(|_| ()),
+        1 => {
+            client.write_register(worker, value).await?;
+            let read = client.read_register(worker).await?;
+            check(read == value, || format!("register {worker}: wrote {value}, read {read}"))
+        }
+        2 => {
+            let io_number = 2701 + worker;
+            let state = u8::from(sequence.is_multiple_of(8));
+            client.write_io(io_number, state).await?;
+            let read = client.read_io(io_number).await?;
+            check(read == state, || format!("I/O {io_number}: wrote {state}, read {read}"))
+        }
+        _ => {
+            client.write_variable::<i32>(worker, i32::from(value)).await?;
+            let read = client.read_variable::<i32>(worker).await?;
+            check(read == i32::from(value), || format!("D{worker:03}: wrote {value}, read {read}"))
+        }
+    }
+}
+
+fn check(ok: bool, message: impl FnOnce() -> String) -> Result<(), ClientError> {
+    if ok { Ok(()) } else { Err(ClientError::VerificationFailed(message())) }
+}
@@ -0,0 +1,102 @@
+//! Setup shared by the scenario examples
+//!
+//! Every scenario takes `[--mock] [host] [robot_port] [file_port]`, by default
+//! 127.0.0.1 with the standard HSES ports. With `--mock`, the scenario starts a
+//! [`MockServer`] in-process on ephemeral ports, seeded with the data the scenario needs,
+//! and runs against it; no controller is required.
+
+// Each scenario uses a part of the setup
+#![allow(dead_code)]
+
+use log::{LevelFilter, info};
+use moto_hses_client::{ClientConfig, HsesClient};
+use moto_hses_mock::MockServer;
+use moto_hses_mock::server::MockServerBuilder;
+use moto_hses_proto::{FILE_CONTROL_PORT, ROBOT_CONTROL_PORT};
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+pub type Error = Box<dyn std::error::Error + Send + Sync>;
+
+/// Controller a scenario runs against
+pub struct Scenario {
+    pub robot_addr: SocketAddr,
+    pub file_addr: SocketAddr,
+    /// In-process mock, with `--mock`
+    pub mock: Option<Arc<MockServer>>,
+    /// Arguments left after the options and addresses
+    pub args: Vec<String>,
+}
+
+impl Scenario {
+    /// Parse the command line and start the mock seeded by `seed` if `--mock` is given
+    ///
+    /// Arguments starting with `--` other than `--mock` are left in [`Self::args`] for the
+    /// scenario.
+    pub async fn start(
+        seed: impl FnOnce(MockServerBuilder) -> MockServerBuilder,
+    ) -> Result<Self, Error> {
+        let mut mock = false;
+        let mut positional = Vec::new();
+        let mut args = Vec::new();
+        for arg in std::env::args().skip(1) {
+            if arg == "--mock" {
+                mock = true;
+            } else if arg.starts_with("--") {
+                args.push(arg);
+            } else {
+                positional.push(arg);
+            }
+        }
+
+        if mock {
+            let builder = MockServerBuilder::new()
+                .host("127.0.0.1")
+                .robot_port(0)
+                .file_port(0)
+                .with_log_level(LevelFilter::Warn);
+            let server = Arc::new(seed(builder).build().await?);
+            let robot_addr = server.local_addr()?;
+            let file_addr = server.file_local_addr()?;
+            let runner = Arc::clone(&server);
+            tokio::spawn(async move {
+                if let Err(e) = runner.run().await {
+                    log::error!("Mock server stopped: {e}");
+                }
+            });
+            info!("Running against the in-process mock at {robot_addr}");
+            return Ok(Self { robot_addr, file_addr, mock: Some(server), args });
+        }
+
+        let host = positional.first().map_or("127.0.0.1", String::as_str);
+        let port = |index: usize, default: u16| -> Result<u16, Error> {
+            positional.get(index).map_or(Ok(default), |port| {
+                port.parse().map_err(|e| format!("Invalid port: {port} - {e}").into())
+            })
+        };
+        let robot_addr = format!("{host}:{}", port(1, ROBOT_CONTROL_PORT)?).parse()?;
+        let file_addr = format!("{host}:{}", port(2, FILE_CONTROL_PORT)?).parse()?;
+        info!("Running against the controller at {robot_addr}");
+        Ok(Self { robot_addr, file_addr, mock: None, args })
+    }
+
+    /// Whether `--option` was given
+    pub fn has_option(&self, option: &str) -> bool {
+        self.args.iter().any(|arg| arg == option)
+    }
+
+    /// Client of the robot control port
+    pub async fn client(&self) -> Result<HsesClient, Error> {
+        Ok(HsesClient::connect(&self.robot_addr.to_string(), ClientConfig::default()).await?)
+    }
+
+    /// Client of the file control port
+    pub async fn file_client(&self) -> Result<HsesClient, Error> {
+        let config = ClientConfig {
+            host: self.file_addr.ip().to_string(),
+            port: self.file_addr.port(),
+            ..ClientConfig::default()
+        };
+        Ok(HsesClient::new_with_config(config).await?)
+    }
+}
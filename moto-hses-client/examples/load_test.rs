@@ -0,0 +1,160 @@
+//! Load test against the mock server
+//!
+//! Starts a mock server on ephemeral ports and drives mixed register, I/O, variable and
+//! status traffic at a fixed total rate from several workers, each with its own client.
+//! At the end it reports the achieved throughput, the latency percentiles, the requests
+//! that failed after all retries (drops) and the requests that could not be sent on
+//! schedule, as a baseline for capacity planning.
+//!
+//! Usage: `load_test [rate_hz] [duration_secs] [workers]`, by default 1000 requests per
+//! second for 10 seconds from 4 workers.
+
+use log::{LevelFilter, info};
+use moto_hses_client::{ClientConfig, ClientError, HsesClient};
+use moto_hses_mock::server::MockServerBuilder;
+use std::time::Duration;
+use tokio::time::{Instant, MissedTickBehavior};
+
+const DEFAULT_RATE_HZ: u32 = 1000;
+const DEFAULT_DURATION_SECS: u32 = 10;
+const DEFAULT_WORKERS: u32 = 4;
+
+// Outcome of one worker
+#[derive(Debug, Default)]
+struct WorkerReport {
+    latencies: Vec<Duration>,
+    failures: u64,
+    behind_schedule: u64,
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    env_logger::init();
+    let args: Vec<String> = std::env::args().collect();
+    let arg = |index: usize, default: u32| -> Result<u32, String> {
+        args.get(index).map_or(Ok(default), |value| {
+            value.parse().map_err(|e| format!("Invalid argument: {value} - {e}"))
+        })
+    };
+    let rate = arg(1, DEFAULT_RATE_HZ)?.max(1);
+    let duration = Duration::from_secs(u64::from(arg(2, DEFAULT_DURATION_SECS)?));
+    let workers = arg(3, DEFAULT_WORKERS)?.clamp(1, rate);
+
+    let server = MockServerBuilder::new()
+        .host("127.0.0.1")
+        .robot_port(0)
+        .file_port(0)
+        .with_log_level(LevelFilter::Warn)
+        .build()
+        .await?;
+    let addr = server.local_addr()?;
+    let mock = server.handle();
+    let server_task = tokio::spawn(async move { server.run().await });
+
+    info!("Sending {rate} requests/s for {duration:?} from {workers} workers to {addr}");
+    let period = Duration::from_secs_f64(f64::from(workers) / f64::from(rate));
+    let started_at = Instant::now();
+    let mut tasks = Vec::new();
+    for worker in 0..workers {
+        let config = ClientConfig {
+            host: addr.ip().to_string(),
+            port: addr.port(),
+            timeout: Duration::from_millis(100),
+            retry_count: 1,
+            ..ClientConfig::default()
+        };
+        let client = HsesClient::new_with_config(config).await?;
+        tasks.push(tokio::spawn(run_worker(client, worker, period, duration)));
+    }
+
+    let mut report = WorkerReport::default();
+    for task in tasks {
+        let worker = task.await?;
+        report.latencies.extend(worker.latencies);
+        report.failures += worker.failures;
+        report.behind_schedule += worker.behind_schedule;
+    }
+    let elapsed = started_at.elapsed();
+    server_task.abort();
+
+    report.latencies.sort_unstable();
+    let completed = report.latencies.len();
+    info!("Completed: {completed} requests in {elapsed:.2?}");
+    #[allow(clippy::cast_precision_loss)] // Far fewer requests than 2^52
+    let throughput = completed as f64 / elapsed.as_secs_f64();
+    info!("Throughput: {throughput:.0} requests/s");
+    info!(
+        "Latency: p50={:?} p99={:?} max={:?}",
+        percentile(&report.latencies, 50),
+        percentile(&report.latencies, 99),
+        report.latencies.last().copied().unwrap_or_default()
+    );
+    info!("Dropped (failed after retries): {}", report.failures);
+    info!("Behind schedule (not sent): {}", report.behind_schedule);
+    info!("Requests seen by the mock, including retries: {}", mock.robot_request_count().await);
+    Ok(())
+}
+
+// Send one request per `period` until `duration` has passed
+async fn run_worker(
+    client: HsesClient,
+    worker: u32,
+    period: Duration,
+    duration: Duration,
+) -> WorkerReport {
+    let mut report = WorkerReport::default();
+    let mut interval = tokio::time::interval(period);
+    interval.set_missed_tick_behavior(MissedTickBehavior::Skip);
+    let started_at = Instant::now();
+    let mut sequence: u32 = 0;
+    loop {
+        let tick = interval.tick().await;
+        if tick.duration_since(started_at) >= duration {
+            break;
+        }
+        // Ticks skipped since the previous request
+        let expected = tick.duration_since(started_at).as_nanos() / period.as_nanos().max(1);
+        let behind = u64::try_from(expected).unwrap_or(u64::MAX).saturating_sub(sequence.into());
+        report.behind_schedule += behind;
+        sequence = sequence.saturating_add(u32::try_from(behind).unwrap_or(u32::MAX));
+
+        let sent_at = Instant::now();
+        match send_mixed_request(&client, worker, sequence).await {
+            Ok(()) => report.latencies.push(sent_at.elapsed()),
+            Err(e) => {
+                log::warn!("Worker {worker}: request {sequence} failed: {e}");
+                report.failures += 1;
+            }
+        }
+        sequence = sequence.saturating_add(1);
+    }
+    report
+}
+
+// One of the commonly polled commands, in turn
+async fn send_mixed_request(
+    client: &HsesClient,
+    worker: u32,
+    sequence: u32,
+) -> Result<(), ClientError> {
+    // Each worker uses its own register, I/O and variable so that workers do not interfere
+    let index = u16::try_from(worker % 100).unwrap_or_default();
+    let value = i16::try_from(sequence % 1000).unwrap_or_default();
+    match sequence % 6 {
+        0 => client.read_status().await.map(|_| ()),
+        1 => client.write_register(index, value).await,
+        2 => client.read_register(index).await.map(|_| ()),
+        3 => client.write_io(2701 + index, u8::from(sequence.is_multiple_of(2))).await,
+        4 => client.read_io(2701 + index).await.map(|_| ()),
+        _ => client.write_variable::<i32>(index, i32::from(value)).await,
+    }
+}
+
+// The `percent`th percentile of sorted `latencies`
+fn percentile(latencies: &[Duration], percent: usize) -> Duration {
+    if latencies.is_empty() {
+        return Duration::ZERO;
+    }
+    let rank = (latencies.len() * percent).div_ceil(100).max(1);
+    latencies[rank - 1]
+}
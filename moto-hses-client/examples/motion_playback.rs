@@ -0,0 +1,115 @@
+//! Motion playback scenario
+//!
+//! Plays back a taught path relative to the current tool position: a 100 mm square in
+//! the horizontal plane followed by the return to the start. The servo power is switched
+//! on first, the progress is reported while the robot moves, and the playback is paused
+//! for a second after the second corner and resumed, as an operator would from an HMI.
+//!
+//! Only run this against a real controller with the cell cleared and the robot in a
+//! position where the square is reachable.
+//!
+//! Usage: `motion_playback [--mock] [host] [robot_port]`
+
+mod common;
+
+use common::{Error, Scenario};
+use log::info;
+use moto_hses_client::{
+    CartesianPosition, MoveSpeed, Position, TrajectoryControl, TrajectoryOptions,
+};
+use moto_hses_mock::MotionSimulation;
+use moto_hses_proto::payload::position::{Configuration, ExtendedConfiguration};
+use std::time::Duration;
+
+const ROBOT: u8 = 1;
+// Control group of the Cartesian position of `ROBOT`
+const ROBOT_CARTESIAN: u8 = 100 + ROBOT;
+// Side of the square [mm]
+const SIDE: f32 = 100.0;
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+
+#[tokio::main]
+async fn main() -> Result<(), Error> {
+    env_logger::init();
+    let home = CartesianPosition::new(
+        400.0,
+        0.0,
+        300.0,
+        180.0,
+        0.0,
+        0.0,
+        0,
+        0,
+        Configuration::from_raw(0),
+        ExtendedConfiguration::from_raw(0),
+    );
+    let scenario = Scenario::start(|builder| {
+        builder
+            .with_position(Position::Cartesian(home))
+            .with_motion_simulation(MotionSimulation::default())
+    })
+    .await?;
+    let client = scenario.client().await?;
+
+    let Position::Cartesian(start) = client.read_position(ROBOT_CARTESIAN).await? else {
+        return Err("robot did not report a Cartesian position".into());
+    };
+    info!("Start: x {:.1} y {:.1} z {:.1}", start.x, start.y, start.z);
+    let offset = |dx: f32, dy: f32| {
+        let mut point = start.clone();
+        point.x += dx;
+        point.y += dy;
+        Position::Cartesian(point)
+    };
+    let points = [offset(SIDE, 0.0), offset(SIDE, SIDE), offset(0.0, SIDE), offset(0.0, 0.0)];
+
+    client.set_servo(true).await?;
+    let options = TrajectoryOptions {
+        speed: MoveSpeed::Linear(250.0),
+        blend: Some(5.0),
+        ..TrajectoryOptions::default()
+    };
+    let control = TrajectoryControl::new();
+    let operator = control.clone();
+    let monitor = tokio::spawn(async move {
+        let mut paused_once = false;
+        let mut last = None;
+        while !operator.is_cancelled() {
+            let progress = operator.progress();
+            if last != Some(progress) {
+                info!("Progress: {}/{} points", progress.completed, progress.total);
+                last = Some(progress);
+            }
+            if progress.total > 0 && progress.completed == progress.total {
+                break;
+            }
+            if progress.completed == 2 && !paused_once {
+                paused_once = true;
+                operator.pause();
+                info!("Paused by the operator");
+                tokio::time::sleep(Duration::from_secs(1)).await;
+                operator.resume();
+                info!("Resumed");
+            }
+            tokio::time::sleep(PROGRESS_INTERVAL).await;
+        }
+    });
+
+    let result = client.play_trajectory(ROBOT, &points, &options, &control).await;
+    control.cancel();
+    monitor.await?;
+    let report = result?;
+    info!(
+        "Playback {}: {}/{} points in {:?}",
+        if report.cancelled { "cancelled" } else { "completed" },
+        report.completed,
+        report.total,
+        report.duration
+    );
+
+    if let Position::Cartesian(end) = client.read_position(ROBOT_CARTESIAN).await? {
+        info!("End: x {:.1} y {:.1} z {:.1}", end.x, end.y, end.z);
+    }
+    client.set_servo(false).await?;
+    Ok(())
+}
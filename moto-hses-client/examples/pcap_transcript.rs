@@ -0,0 +1,117 @@
+//! Decode captured HSES traffic into a transcript
+//!
+//! Reads a capture of HSES traffic saved as pcap, e.g. by Wireshark or tcpdump, and
+//! writes the requests it holds and their outcomes as a transcript, so that traffic of
+//! another HSES client can be read or replayed with `replay_transcript`. Each request is
+//! preceded by comment lines with its time, client, command name and the fields of its
+//! payload as the crate decodes them; `Transcript::load` skips these lines.
+//!
+//! The transcript is written to `<capture>.txt` unless another output path is given.
+//! Character variables and job names are decoded as UTF-8 unless `--shift-jis` is given.
+//!
+//! Usage: `pcap_transcript <capture.pcap> [--shift-jis] [output] [robot_port] [file_port]`
+
+use std::fmt::{Debug, Write};
+
+use log::info;
+use moto_hses_client::{CapturedRequest, TranscriptEntry, read_pcap};
+use moto_hses_proto::commands::registry::command_info;
+use moto_hses_proto::{
+    Alarm, BasePosition, Division, ExecutingJobInfo, FILE_CONTROL_PORT, HsesPayload, Position,
+    ROBOT_CONTROL_PORT, Service, StationPosition, Status, SystemInfo, TextEncoding,
+};
+
+fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    env_logger::init();
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let positional: Vec<&String> = args.iter().filter(|arg| !arg.starts_with("--")).collect();
+    let Some(capture) = positional.first() else {
+        return Err("Usage: pcap_transcript <capture.pcap> [--shift-jis] [output] [robot_port] \
+                    [file_port]"
+            .into());
+    };
+    let encoding = if args.iter().any(|arg| arg == "--shift-jis") {
+        TextEncoding::ShiftJis
+    } else {
+        TextEncoding::Utf8
+    };
+    let output = positional.get(1).map_or_else(
+        || std::path::Path::new(capture.as_str()).with_extension("txt"),
+        |output| std::path::PathBuf::from(output.as_str()),
+    );
+    let port = |index: usize, default: u16| -> Result<u16, String> {
+        positional.get(index).map_or(Ok(default), |port| {
+            port.parse().map_err(|e| format!("Invalid port: {port} - {e}"))
+        })
+    };
+
+    let requests = read_pcap(
+        &std::fs::read(capture.as_str())?,
+        port(2, ROBOT_CONTROL_PORT)?,
+        port(3, FILE_CONTROL_PORT)?,
+    )?;
+    let mut text = String::new();
+    for request in &requests {
+        annotate(&mut text, request, encoding)?;
+        writeln!(text, "{}", request.entry)?;
+    }
+    std::fs::write(&output, text)?;
+
+    let failed = requests.iter().filter(|request| request.entry.response.is_err()).count();
+    info!(
+        "Decoded {} requests ({failed} failed or unanswered) from {capture} into {}",
+        requests.len(),
+        output.display()
+    );
+    Ok(())
+}
+
+// Comment lines naming a request and breaking down its payload
+fn annotate(
+    text: &mut String,
+    request: &CapturedRequest,
+    encoding: TextEncoding,
+) -> std::fmt::Result {
+    let entry = &request.entry;
+    let name = command_info(entry.division, entry.command, entry.service.code())
+        .map_or("Unknown command", |info| info.name);
+    writeln!(text, "# {:.6}s {} {name}", request.time.as_secs_f64(), request.client)?;
+    if let Some(fields) = fields(entry, encoding) {
+        writeln!(text, "#   {fields}")?;
+    }
+    Ok(())
+}
+
+// Fields of the data a request writes or its response reads, for the commands whose
+// whole data has a payload type
+fn fields(entry: &TranscriptEntry, encoding: TextEncoding) -> Option<String> {
+    let data = match entry.service {
+        Service::GetAll | Service::GetSingle => entry.response.as_deref().ok()?,
+        Service::SetAll | Service::SetSingle => &entry.payload,
+        _ => return None,
+    };
+    if entry.division != Division::Robot || data.is_empty() {
+        return None;
+    }
+    match entry.command {
+        0x70 | 0x71 => decode::<Alarm>(data, encoding),
+        0x72 => decode::<Status>(data, encoding),
+        0x73 => decode::<ExecutingJobInfo>(data, encoding),
+        0x75 | 0x7F => decode::<Position>(data, encoding),
+        0x78 | 0x7A => {
+            u8::deserialize(data, encoding).ok().map(|byte| format!("{byte} (0b{byte:08b})"))
+        }
+        0x79 | 0x7B => decode::<i16>(data, encoding),
+        0x7C => decode::<i32>(data, encoding),
+        0x7D => decode::<f32>(data, encoding),
+        0x7E => decode::<String>(data, encoding),
+        0x80 => decode::<BasePosition>(data, encoding),
+        0x81 => decode::<StationPosition>(data, encoding),
+        0x89 => decode::<SystemInfo>(data, encoding),
+        _ => None,
+    }
+}
+
+fn decode<T: HsesPayload + Debug>(data: &[u8], encoding: TextEncoding) -> Option<String> {
+    T::deserialize(data, encoding).ok().map(|value| format!("{value:?}"))
+}
@@ -0,0 +1,115 @@
+//! Production dashboard scenario
+//!
+//! Shows the state of a production cell for a while: a pipelined robot snapshot (status,
+//! executing job, position and alarm count) every second, and the changes of the
+//! production variables as they happen, i.e. the part counter in D000, the last cycle
+//! time in R000 and the product code in S000. Only reads are sent.
+//!
+//! With `--mock`, a simulated line increments the part counter every 400 ms.
+//!
+//! Usage: `production_dashboard [--mock] [host] [robot_port]`
+
+mod common;
+
+use common::{Error, Scenario};
+use futures::StreamExt;
+use log::info;
+use moto_hses_client::{ExecutingJobInfo, Position, VariableRef, VariableValue};
+use moto_hses_mock::{TypedVariables, VariableType};
+use std::time::Duration;
+use tokio::time::{Instant, MissedTickBehavior};
+
+const DASHBOARD_DURATION: Duration = Duration::from_secs(5);
+const SNAPSHOT_INTERVAL: Duration = Duration::from_secs(1);
+const WATCH_INTERVAL: Duration = Duration::from_millis(100);
+
+#[tokio::main]
+async fn main() -> Result<(), Error> {
+    env_logger::init();
+    let mut variables = TypedVariables::new();
+    variables.insert((VariableType::Double, 0), 0i32.to_le_bytes().to_vec());
+    variables.insert((VariableType::Real, 0), 0f32.to_le_bytes().to_vec());
+    let mut product = b"PART-A".to_vec();
+    product.resize(16, 0);
+    variables.insert((VariableType::String, 0), product);
+    let scenario = Scenario::start(|builder| {
+        builder.with_variables(variables).with_executing_job(ExecutingJobInfo::new(
+            "PALLETIZE".to_string(),
+            12,
+            4,
+            10000,
+        ))
+    })
+    .await?;
+    if let Some(mock) = scenario.mock.clone() {
+        tokio::spawn(async move {
+            let mut cycle_time: f32 = 11.5;
+            for parts in 1i32.. {
+                tokio::time::sleep(Duration::from_millis(400)).await;
+                cycle_time = if cycle_time >= 12.5 { 11.5 } else { cycle_time + 0.25 };
+                mock.set_variable(VariableType::Real, 0, cycle_time.to_le_bytes().to_vec()).await;
+                mock.set_variable(VariableType::Double, 0, parts.to_le_bytes().to_vec()).await;
+            }
+        });
+    }
+    let client = scenario.client().await?;
+
+    let changes = client.watch_variables(
+        vec![VariableRef::D(0), VariableRef::R(0), VariableRef::S(0)],
+        WATCH_INTERVAL,
+    );
+    let mut changes = std::pin::pin!(changes);
+    let mut snapshots = tokio::time::interval(SNAPSHOT_INTERVAL);
+    snapshots.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    let deadline = tokio::time::sleep_until(Instant::now() + DASHBOARD_DURATION);
+    tokio::pin!(deadline);
+
+    loop {
+        tokio::select! {
+            () = &mut deadline => break,
+            _ = snapshots.tick() => {
+                let snapshot = client.read_snapshot(&[1]).await?;
+                let job = &snapshot.executing_job;
+                info!(
+                    "[cell] {} | job {} line {} step {} | {} | alarms {} | sampled within {:?}",
+                    if snapshot.status.is_running() { "RUNNING" } else { "STOPPED" },
+                    job.job_name,
+                    job.line_number,
+                    job.step_number,
+                    describe_position(snapshot.positions.first()),
+                    snapshot.active_alarm_count,
+                    snapshot.skew
+                );
+            }
+            Some(change) = changes.next() => match change {
+                Ok(change) => info!(
+                    "[vars] {} = {}",
+                    change.variable,
+                    describe_value(&change.new)
+                ),
+                Err(e) => info!("[vars] read failed: {e}"),
+            },
+        }
+    }
+    Ok(())
+}
+
+fn describe_position(position: Option<&Position>) -> String {
+    match position {
+        Some(Position::Cartesian(position)) => {
+            format!("TCP x {:.1} y {:.1} z {:.1}", position.x, position.y, position.z)
+        }
+        Some(Position::Pulse(position)) => format!("pulses {:?}", position.joints),
+        None => "no position".to_string(),
+    }
+}
+
+fn describe_value(value: &VariableValue) -> String {
+    match value {
+        VariableValue::Byte(value) => value.to_string(),
+        VariableValue::Integer(value) => value.to_string(),
+        VariableValue::Double(value) => value.to_string(),
+        VariableValue::Real(value) => format!("{value:.2}"),
+        VariableValue::String(value) => format!("\"{value}\""),
+    }
+}
@@ -0,0 +1,119 @@
+//! Recipe changeover scenario
+//!
+//! Switches a cell from one product to the other: detects the recipe the controller
+//! variables currently match, takes the cooperative writer lock so that no other HMI
+//! writes at the same time, holds the robot, applies and verifies the other recipe,
+//! releases the hold and runs the production job once to confirm the changeover. Every
+//! write is recorded by the audit log under the target `moto_hses_client::audit`.
+//!
+//! Usage: `recipe_changeover [--mock] [host] [robot_port]`
+
+mod common;
+
+use common::{Error, Scenario};
+use log::{info, warn};
+use moto_hses_client::{
+    AuditOptions, HsesClientOps, LogAuditSink, Recipe, SharedHsesClient, VariableValue, WriterLock,
+    WriterLockOptions,
+};
+use moto_hses_mock::{JobSimulation, TypedVariables, VariableType};
+use std::time::Duration;
+
+const PRODUCTION_JOB: &str = "PRODUCE.JOB";
+
+#[tokio::main]
+async fn main() -> Result<(), Error> {
+    env_logger::init();
+    let recipes = [
+        Recipe::new("part-a")
+            .with_value(1, VariableValue::Byte(3))
+            .with_value(10, VariableValue::Double(25000))
+            .with_value(2, VariableValue::Real(1.5))
+            .with_text("S000", "PART-A")?,
+        Recipe::new("part-b")
+            .with_value(1, VariableValue::Byte(5))
+            .with_value(10, VariableValue::Double(32000))
+            .with_value(2, VariableValue::Real(2.25))
+            .with_text("S000", "PART-B")?,
+    ];
+
+    // The mock starts on part A
+    let mut variables = TypedVariables::new();
+    variables.insert((VariableType::Byte, 1), vec![3]);
+    variables.insert((VariableType::Double, 10), 25000i32.to_le_bytes().to_vec());
+    variables.insert((VariableType::Real, 2), 1.5f32.to_le_bytes().to_vec());
+    let mut product = b"PART-A".to_vec();
+    product.resize(16, 0);
+    variables.insert((VariableType::String, 0), product);
+    let scenario = Scenario::start(|builder| {
+        builder
+            .with_variables(variables)
+            .with_job_simulation(JobSimulation::new(Duration::from_millis(500)))
+    })
+    .await?;
+
+    let client = scenario.client().await?;
+    client.set_audit_sink(
+        LogAuditSink,
+        AuditOptions { actor: "recipe-changeover".to_string(), read_previous: true },
+    );
+    let client = SharedHsesClient::new(client);
+
+    let target = client.detect_recipe(&recipes).await?.map_or_else(
+        || {
+            warn!("Variables match no known recipe");
+            &recipes[0]
+        },
+        |current| {
+            info!("Cell is running {}", current.name);
+            recipes.iter().find(|recipe| recipe.name != current.name).unwrap_or(current)
+        },
+    );
+    info!("Changing over to {}", target.name);
+
+    let lock = WriterLock::new(client.clone(), WriterLockOptions::default());
+    apply_locked(&client, &lock, target).await?;
+
+    match client.detect_recipe(&recipes).await? {
+        Some(detected) if detected.name == target.name => info!("Recipe {} verified", target.name),
+        detected => {
+            return Err(format!(
+                "expected recipe {}, variables match {:?}",
+                target.name,
+                detected.map(|recipe| &recipe.name)
+            )
+            .into());
+        }
+    }
+
+    info!("Running {PRODUCTION_JOB} once to confirm the changeover");
+    let report = client
+        .run_job_and_wait(PRODUCTION_JOB, 0, Duration::from_secs(5), Duration::from_secs(60))
+        .await?;
+    if report.is_success() {
+        info!("{} completed in {:?}", report.job_name, report.duration);
+    } else {
+        for alarm in &report.alarms {
+            warn!("{} raised alarm {} {}", report.job_name, alarm.code, alarm.name);
+        }
+        return Err(format!("{} raised {} alarms", report.job_name, report.alarms.len()).into());
+    }
+    Ok(())
+}
+
+// Apply `recipe` with the robot held, while holding the writer lock
+// The lease is consumed by `release`, not dropped at the end
+#[allow(clippy::significant_drop_tightening)]
+async fn apply_locked(
+    client: &SharedHsesClient,
+    lock: &WriterLock<SharedHsesClient>,
+    recipe: &Recipe,
+) -> Result<(), Error> {
+    let lease = lock.acquire(Duration::from_secs(10)).await?;
+    info!("Writer lock 0x{:08X} acquired", lock.token());
+    client.set_hold(true).await?;
+    let applied = client.apply_recipe(recipe).await;
+    client.set_hold(false).await?;
+    lease.release().await?;
+    Ok(applied?)
+}
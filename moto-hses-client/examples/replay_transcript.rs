@@ -0,0 +1,75 @@
+//! Replay a recorded transcript against a controller
+//!
+//! Reads a transcript saved with `Transcript::save`, sends its requests again and prints
+//! the responses that differ from the recorded ones, e.g. to check that a controller
+//! answers the same after a firmware upgrade, or that the mock answers like the
+//! controller a transcript was recorded on. Robot control requests go to the robot port
+//! and file control requests to the file port. Writes are skipped unless
+//! `--send-writes` is given; with `--ignore-volatile`, only the success of status,
+//! position and clock reads is compared.
+//!
+//! Exits with status 1 if the responses differ.
+//!
+//! Usage: `replay_transcript <transcript> [--send-writes] [--ignore-volatile] [host]
+//! [robot_port] [file_port]`
+
+use log::info;
+use moto_hses_client::{
+    ClientConfig, HsesClient, ReplayOptions, ReplayWrites, Transcript, TranscriptEntry,
+};
+use moto_hses_proto::{Division, FILE_CONTROL_PORT, ROBOT_CONTROL_PORT};
+
+// Status, status data, current position, position error and clock reads
+const VOLATILE_COMMANDS: [u16; 6] = [0x72, 0x75, 0x76, 0x91, 0x89, 0x8A];
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    env_logger::init();
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let flag = |name: &str| args.iter().any(|arg| arg == name);
+    let positional: Vec<&String> = args.iter().filter(|arg| !arg.starts_with("--")).collect();
+    let Some(path) = positional.first() else {
+        return Err("Usage: replay_transcript <transcript> [--send-writes] [--ignore-volatile] \
+                    [host] [robot_port] [file_port]"
+            .into());
+    };
+    let host = positional.get(1).map_or("127.0.0.1", |host| host.as_str());
+    let port = |index: usize, default: u16| -> Result<u16, String> {
+        positional.get(index).map_or(Ok(default), |port| {
+            port.parse().map_err(|e| format!("Invalid port: {port} - {e}"))
+        })
+    };
+
+    let transcript = Transcript::load(path.as_str())?;
+    let options = ReplayOptions {
+        writes: if flag("--send-writes") { ReplayWrites::Send } else { ReplayWrites::Skip },
+        volatile_commands: if flag("--ignore-volatile") {
+            VOLATILE_COMMANDS.to_vec()
+        } else {
+            Vec::new()
+        },
+    };
+    info!("Replaying {} requests from {path}", transcript.entries.len());
+
+    let mut clean = true;
+    for (division, port) in [
+        (Division::Robot, port(2, ROBOT_CONTROL_PORT)?),
+        (Division::File, port(3, FILE_CONTROL_PORT)?),
+    ] {
+        let entries: Vec<TranscriptEntry> =
+            transcript.entries.iter().filter(|entry| entry.division == division).cloned().collect();
+        if entries.is_empty() {
+            continue;
+        }
+        let config = ClientConfig { host: host.to_string(), port, ..ClientConfig::default() };
+        let client = HsesClient::new_with_config(config).await?;
+        let report = client.replay_transcript(&Transcript { entries }, &options).await?;
+        info!("{division} control ({host}:{port}): {report}");
+        clean &= report.is_clean();
+    }
+
+    if !clean {
+        std::process::exit(1);
+    }
+    Ok(())
+}
@@ -4,7 +4,9 @@
 //! the HSES client from multiple concurrent tasks.
 
 use log::info;
-use moto_hses_client::{ClientConfig, HsesClient, HsesClientOps, SharedHsesClient};
+use moto_hses_client::{
+    ClientConfig, ControllerModel, DangerLevel, HsesClient, HsesClientOps, SharedHsesClient,
+};
 use moto_hses_proto::{ROBOT_CONTROL_PORT, TextEncoding};
 use std::time::Duration;
 
@@ -30,8 +32,17 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         timeout: Duration::from_millis(3000),
         retry_count: 0,
         retry_delay: Duration::from_millis(200),
+        operation_deadline: None,
         buffer_size: 8192,
         text_encoding: TextEncoding::ShiftJis,
+        controller_model: ControllerModel::YRC1000micro,
+        file_name_rules: None,
+        max_danger_level: DangerLevel::Write,
+        workspace_limits: None,
+        adaptive_timeout: None,
+        write_pacing: None,
+        expected_axes: None,
+        request_limits: None,
     };
 
     // Create HsesClient and wrap it with SharedHsesClient for thread-safe access
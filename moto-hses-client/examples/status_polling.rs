@@ -0,0 +1,84 @@
+//! Micro-benchmark of status polling against the mock server
+//!
+//! Polls the status of a local mock server back to back, once reading the whole status
+//! (`read_status`, 8 bytes of status data) and once only status data 1
+//! (`read_status_bit(StatusBit::Running)`, 4 bytes), and reports the polls per second
+//! and the latency percentiles of each.
+//!
+//! Usage: `status_polling [polls]`, by default 5000 polls of each kind.
+
+use log::{LevelFilter, info};
+use moto_hses_client::{ClientConfig, ClientError, HsesClient, StatusBit};
+use moto_hses_mock::server::MockServerBuilder;
+use std::time::Duration;
+use tokio::time::Instant;
+
+const DEFAULT_POLLS: u32 = 5000;
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    env_logger::init();
+    let polls = std::env::args()
+        .nth(1)
+        .map_or(Ok(DEFAULT_POLLS), |value| value.parse())
+        .map_err(|e| format!("Invalid argument: {e}"))?
+        .max(1);
+
+    let server = MockServerBuilder::new()
+        .host("127.0.0.1")
+        .robot_port(0)
+        .file_port(0)
+        .with_log_level(LevelFilter::Warn)
+        .build()
+        .await?;
+    let addr = server.local_addr()?;
+    let server_task = tokio::spawn(async move { server.run().await });
+
+    let config =
+        ClientConfig { host: addr.ip().to_string(), port: addr.port(), ..ClientConfig::default() };
+    let client = HsesClient::new_with_config(config).await?;
+
+    info!("Polling {addr} {polls} times per kind");
+    // Warm up the connection and the mock
+    poll(&client, 100, true).await?;
+    for (label, full) in
+        [("Full status (read_status)", true), ("Data 1 only (read_status_bit)", false)]
+    {
+        let mut latencies = poll(&client, polls, full).await?;
+        latencies.sort_unstable();
+        let total: Duration = latencies.iter().sum();
+        info!(
+            "{label}: {:.0} polls/s, p50={:?} p99={:?} max={:?}",
+            f64::from(polls) / total.as_secs_f64(),
+            percentile(&latencies, 50),
+            percentile(&latencies, 99),
+            latencies.last().copied().unwrap_or_default()
+        );
+    }
+    server_task.abort();
+    Ok(())
+}
+
+// Latencies of `polls` running-flag polls, with the whole status or only status data 1
+async fn poll(client: &HsesClient, polls: u32, full: bool) -> Result<Vec<Duration>, ClientError> {
+    let mut latencies = Vec::new();
+    for _ in 0..polls {
+        let sent_at = Instant::now();
+        if full {
+            client.read_status().await?;
+        } else {
+            client.read_status_bit(StatusBit::Running).await?;
+        }
+        latencies.push(sent_at.elapsed());
+    }
+    Ok(latencies)
+}
+
+// The `percent`th percentile of sorted `latencies`
+fn percentile(latencies: &[Duration], percent: usize) -> Duration {
+    if latencies.is_empty() {
+        return Duration::ZERO;
+    }
+    let rank = (latencies.len() * percent).div_ceil(100).max(1);
+    latencies[rank - 1]
+}
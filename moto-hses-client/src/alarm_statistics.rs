@@ -0,0 +1,114 @@
+//! Alarm occurrence counts per alarm code
+//!
+//! The HSES protocol has no command reporting alarm statistics on any controller
+//! generation, so [`HsesClient::alarm_statistics`] counts the entries of the alarm history
+//! instead. The history keeps the last [`ALARM_HISTORY_SIZE`] alarms of each category, so
+//! the counts cover a window that depends on how often alarms occur; compare
+//! [`AlarmStatistics::first_time`] with the period of a report.
+
+use std::collections::BTreeMap;
+
+use moto_hses_proto::{ALARM_HISTORY_SIZE, Alarm, AlarmCategory};
+
+use crate::types::{ClientError, HsesClient};
+
+// Categories whose history is counted
+const CATEGORIES: [AlarmCategory; 5] = [
+    AlarmCategory::MajorFailure,
+    AlarmCategory::MonitorAlarm,
+    AlarmCategory::UserAlarmSystem,
+    AlarmCategory::UserAlarmUser,
+    AlarmCategory::OfflineAlarm,
+];
+
+/// Occurrences of one alarm code in the alarm history
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AlarmStatistics {
+    pub code: u32,
+    /// Name of the latest occurrence
+    pub name: String,
+    pub count: usize,
+    /// Time of the earliest occurrence, "YYYY/MM/DD HH:MM"
+    pub first_time: String,
+    /// Time of the latest occurrence, "YYYY/MM/DD HH:MM"
+    pub last_time: String,
+}
+
+impl AlarmStatistics {
+    /// Count the occurrences of each alarm code in `alarms`, keyed by code
+    ///
+    /// Empty entries (code 0) are skipped.
+    #[must_use]
+    pub fn from_alarms<'a>(alarms: impl IntoIterator<Item = &'a Alarm>) -> BTreeMap<u32, Self> {
+        let mut statistics: BTreeMap<u32, Self> = BTreeMap::new();
+        for alarm in alarms.into_iter().filter(|alarm| alarm.code != 0) {
+            let entry = statistics.entry(alarm.code).or_insert_with(|| Self {
+                code: alarm.code,
+                name: alarm.name.clone(),
+                count: 0,
+                first_time: alarm.time.clone(),
+                last_time: alarm.time.clone(),
+            });
+            entry.count += 1;
+            // Times sort chronologically as text
+            if alarm.time < entry.first_time {
+                entry.first_time.clone_from(&alarm.time);
+            }
+            if alarm.time > entry.last_time {
+                entry.last_time.clone_from(&alarm.time);
+                entry.name.clone_from(&alarm.name);
+            }
+        }
+        statistics
+    }
+}
+
+impl HsesClient {
+    /// Occurrences of each alarm code in the alarm history of all categories, keyed by code
+    ///
+    /// Reads the complete history, [`ALARM_HISTORY_SIZE`] entries of each category, with
+    /// pipelined requests.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if communication fails
+    pub async fn alarm_statistics(&self) -> Result<BTreeMap<u32, AlarmStatistics>, ClientError> {
+        let mut alarms = Vec::new();
+        for category in CATEGORIES {
+            alarms.extend(self.read_alarm_history_range(category, 0, ALARM_HISTORY_SIZE).await?);
+        }
+        Ok(AlarmStatistics::from_alarms(&alarms))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_alarms() {
+        let alarm = |code, time: &str, name: &str| {
+            Alarm::new(code, 0, 0, time.to_string(), name.to_string())
+        };
+        let alarms = [
+            alarm(4107, "2025/03/09 22:40", "OUT OF RANGE"),
+            alarm(0, "", ""),
+            alarm(8001, "2025/03/09 03:17", "GRIPPER PRESSURE LOW"),
+            alarm(4107, "2025/03/10 09:12", "OUT OF RANGE (ABSO DATA)"),
+            alarm(4107, "2025/03/08 14:05", "OUT OF RANGE"),
+        ];
+        let statistics = AlarmStatistics::from_alarms(&alarms);
+        assert_eq!(statistics.keys().copied().collect::<Vec<_>>(), [4107, 8001]);
+        assert_eq!(
+            statistics[&4107],
+            AlarmStatistics {
+                code: 4107,
+                name: "OUT OF RANGE (ABSO DATA)".to_string(),
+                count: 3,
+                first_time: "2025/03/08 14:05".to_string(),
+                last_time: "2025/03/10 09:12".to_string(),
+            }
+        );
+        assert_eq!(statistics[&8001].count, 1);
+    }
+}
@@ -0,0 +1,294 @@
+//! Audit log of write operations
+//!
+//! Regulated environments need a record of every change made to a controller. With an
+//! [`AuditSink`] set through [`HsesClient::set_audit_sink`], the client passes an
+//! [`AuditRecord`] to the sink for every request of a writing service (set, plural write,
+//! file send and delete), whether it succeeded or not. Records say who made the change
+//! ([`AuditOptions::actor`]), when, to which controller and what was sent; with
+//! [`AuditOptions::read_previous`], writes of I/O, registers and variables also carry the
+//! value they replaced.
+//!
+//! [`FileAuditSink`] appends the records to a file, one line each. [`LogAuditSink`] passes
+//! them to the `log` facade under the target [`AUDIT_LOG_TARGET`], from where a logger can
+//! forward them to syslog. Closures taking an `&AuditRecord` are sinks too.
+//!
+//! Changes made at the pendant do not pass through the client. On controllers keeping an
+//! event log, [`HsesClient::read_event_log`] reads them with the mode changes, job starts
+//! and alarms, to be merged with the records.
+//!
+//! ```ignore
+//! client.set_audit_sink(
+//!     FileAuditSink::open("/var/log/robot-audit.log")?,
+//!     AuditOptions { actor: "hmi-1".to_string(), read_previous: true },
+//! );
+//! client.write_i32(5, 100).await?;
+//! // 1760000000.123 actor=hmi-1 controller=192.168.0.3:10040 robot command=0x007C ...
+//! ```
+
+use std::fmt;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use moto_hses_proto::event_log::EVENT_LOG_FILE;
+use moto_hses_proto::{Division, EventLog, Service};
+
+use crate::types::{ClientError, HsesClient};
+
+/// `log` target of the records passed to [`LogAuditSink`]
+pub const AUDIT_LOG_TARGET: &str = "moto_hses_client::audit";
+
+/// One write request sent to the controller
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditRecord {
+    /// Time the request was sent
+    pub time: SystemTime,
+    /// Application or user making the change, from [`AuditOptions::actor`]
+    pub actor: String,
+    /// Address of the controller
+    pub controller: String,
+    pub division: Division,
+    pub command: u16,
+    pub instance: u16,
+    pub attribute: u8,
+    pub service: Service,
+    /// Request payload, i.e. the value written
+    pub payload: Vec<u8>,
+    /// Response payload of reading the same data just before the write, if
+    /// [`AuditOptions::read_previous`] is set and the command has a read counterpart
+    pub previous: Option<Vec<u8>>,
+    /// `Ok` if the controller accepted the request, otherwise the error
+    pub outcome: Result<(), String>,
+}
+
+impl fmt::Display for AuditRecord {
+    /// One line, e.g. `1760000000.123 actor=hmi-1 controller=192.168.0.3:10040 robot
+    /// command=0x007C instance=5 attribute=1 service=0x10 payload=[64 00 00 00]
+    /// previous=[00 00 00 00] ok`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let since_epoch = self.time.duration_since(UNIX_EPOCH).unwrap_or_default();
+        write!(
+            f,
+            "{}.{:03} actor={} controller={} {} command=0x{:04X} instance={} attribute={} \
+             service=0x{:02X} payload=",
+            since_epoch.as_secs(),
+            since_epoch.subsec_millis(),
+            self.actor,
+            self.controller,
+            self.division,
+            self.command,
+            self.instance,
+            self.attribute,
+            self.service.code()
+        )?;
+        write_bytes(f, &self.payload)?;
+        if let Some(previous) = &self.previous {
+            f.write_str(" previous=")?;
+            write_bytes(f, previous)?;
+        }
+        match &self.outcome {
+            Ok(()) => f.write_str(" ok"),
+            Err(e) => write!(f, " failed: {e}"),
+        }
+    }
+}
+
+// Bytes as `[64 00 00 00]`
+pub(crate) fn write_bytes(f: &mut fmt::Formatter<'_>, bytes: &[u8]) -> fmt::Result {
+    f.write_str("[")?;
+    for (index, byte) in bytes.iter().enumerate() {
+        if index > 0 {
+            f.write_str(" ")?;
+        }
+        write!(f, "{byte:02X}")?;
+    }
+    f.write_str("]")
+}
+
+/// Receiver of audit records
+///
+/// Called on the task sending the request, after the response arrived; keep it short.
+pub trait AuditSink: Send + Sync {
+    /// Store or forward `record`
+    fn record(&self, record: &AuditRecord);
+}
+
+impl<F: Fn(&AuditRecord) + Send + Sync> AuditSink for F {
+    fn record(&self, record: &AuditRecord) {
+        self(record);
+    }
+}
+
+/// Sink appending one line per record to a file
+#[derive(Debug)]
+pub struct FileAuditSink {
+    file: Mutex<File>,
+}
+
+impl FileAuditSink {
+    /// Open `path` for appending, creating it if needed
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be opened
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self { file: Mutex::new(file) })
+    }
+}
+
+impl AuditSink for FileAuditSink {
+    fn record(&self, record: &AuditRecord) {
+        let Ok(mut file) = self.file.lock() else {
+            return;
+        };
+        if let Err(e) = writeln!(file, "{record}").and_then(|()| file.flush()) {
+            log::error!("Failed to write audit record: {e}");
+        }
+    }
+}
+
+/// Sink logging the records at info level under [`AUDIT_LOG_TARGET`]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LogAuditSink;
+
+impl AuditSink for LogAuditSink {
+    fn record(&self, record: &AuditRecord) {
+        log::info!(target: AUDIT_LOG_TARGET, "{record}");
+    }
+}
+
+/// Options for [`HsesClient::set_audit_sink`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AuditOptions {
+    /// Application or user recorded as making the changes (default: empty)
+    pub actor: String,
+    /// Read I/O, registers and variables before writing them, to record the value
+    /// replaced; costs one extra request per write (default: false)
+    pub read_previous: bool,
+}
+
+/// Sink and options set on a client
+#[derive(Clone)]
+pub(crate) struct Auditor {
+    pub sink: Arc<dyn AuditSink>,
+    pub options: AuditOptions,
+}
+
+/// Service reading the data that `service` of `command` writes, if the command has one
+///
+/// Covers I/O (0x78, 0x300), registers (0x79, 0x301) and variables (0x7A-0x7E,
+/// 0x302-0x306), whose writes and reads differ only in the service.
+pub(crate) const fn read_counterpart(command: u16, service: Service) -> Option<Service> {
+    if !matches!(command, 0x78..=0x7E | 0x300..=0x306) {
+        return None;
+    }
+    match service {
+        Service::SetAll => Some(Service::GetAll),
+        Service::SetSingle => Some(Service::GetSingle),
+        Service::WritePlural => Some(Service::ReadPlural),
+        _ => None,
+    }
+}
+
+impl HsesClient {
+    /// Pass a record of every write request to `sink`
+    ///
+    /// Replaces a previously set sink.
+    pub fn set_audit_sink(&self, sink: impl AuditSink + 'static, options: AuditOptions) {
+        if let Ok(mut auditor) = self.inner.auditor.lock() {
+            *auditor = Some(Auditor { sink: Arc::new(sink), options });
+        }
+    }
+
+    /// Stop recording write requests
+    pub fn clear_audit_sink(&self) {
+        if let Ok(mut auditor) = self.inner.auditor.lock() {
+            *auditor = None;
+        }
+    }
+
+    /// Read the event log of the controller
+    ///
+    /// HSES has no command reading the operation history, so it is taken from the event log
+    /// file (`LOGDATA.DAT`) received over the file control port. The entries have
+    /// controller times, which HSES gives no way to relate to the host times of the
+    /// [`AuditRecord`]s, as the controller clock cannot be read.
+    ///
+    /// The layout of the file is assumed rather than documented (see [`EventLog`]); a file
+    /// that does not follow it is returned with fewer typed entries and its content.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be received, e.g. from a controller keeping no
+    /// event log
+    pub async fn read_event_log(&self) -> Result<EventLog, ClientError> {
+        Ok(EventLog::parse(&self.receive_file(EVENT_LOG_FILE).await?))
+    }
+
+    // Sink to record a request of `service` with, if any
+    pub(crate) fn auditor_for(&self, service: Service) -> Option<Auditor> {
+        if !service.is_write() {
+            return None;
+        }
+        self.inner.auditor.lock().ok().and_then(|auditor| auditor.clone())
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn record() -> AuditRecord {
+        AuditRecord {
+            time: UNIX_EPOCH + Duration::from_millis(1_760_000_000_123),
+            actor: "hmi-1".to_string(),
+            controller: "192.168.0.3:10040".to_string(),
+            division: Division::Robot,
+            command: 0x7C,
+            instance: 5,
+            attribute: 1,
+            service: Service::SetSingle,
+            payload: 100i32.to_le_bytes().to_vec(),
+            previous: Some(vec![0; 4]),
+            outcome: Ok(()),
+        }
+    }
+
+    #[test]
+    fn test_record_line() {
+        assert_eq!(
+            record().to_string(),
+            "1760000000.123 actor=hmi-1 controller=192.168.0.3:10040 robot command=0x007C \
+             instance=5 attribute=1 service=0x10 payload=[64 00 00 00] previous=[00 00 00 00] ok"
+        );
+        let failed =
+            AuditRecord { previous: None, outcome: Err("Protocol error".to_string()), ..record() };
+        assert!(failed.to_string().ends_with("payload=[64 00 00 00] failed: Protocol error"));
+    }
+
+    #[test]
+    fn test_file_sink_appends_lines() {
+        let path = std::env::temp_dir().join(format!("hses-audit-{}.log", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let sink = FileAuditSink::open(&path).unwrap();
+        sink.record(&record());
+        sink.record(&record());
+        let content = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(content.lines().count(), 2);
+        assert_eq!(content.lines().next().unwrap(), record().to_string());
+    }
+
+    #[test]
+    fn test_read_counterpart() {
+        assert_eq!(read_counterpart(0x7C, Service::SetSingle), Some(Service::GetSingle));
+        assert_eq!(read_counterpart(0x302, Service::WritePlural), Some(Service::ReadPlural));
+        assert_eq!(read_counterpart(0x83, Service::SetSingle), None);
+        assert_eq!(read_counterpart(0x7C, Service::GetSingle), None);
+    }
+}
@@ -0,0 +1,108 @@
+//! Optional protocol features of the connected controller
+//!
+//! Controller models and firmware versions differ in a few optional commands: FS100
+//! controllers, for instance, answer no alarm sub codes. [`HsesClient::connect`] probes
+//! them with harmless reads and records the result as [`Capabilities`]; the high-level
+//! methods consult it, so one program can serve a mixed fleet without configuring each
+//! controller's model:
+//!
+//! - without plural variable commands, the plural variable methods send one request per
+//!   variable
+//! - without alarm sub codes, alarms are read with empty sub code fields, and reads of a
+//!   sub code attribute alone fail with [`ClientError::Unsupported`]
+//! - S variables are read and written with the detected size
+
+use std::fmt;
+
+use moto_hses_proto::commands::{ReadAlarmData, ReadMultipleVariables, ReadVariable};
+use moto_hses_proto::payload::variable::STRING_VARIABLE_SIZE;
+use moto_hses_proto::{AlarmAttribute, Command, Division, ProtocolError};
+
+use crate::protocol::RequestParams;
+use crate::types::{ClientError, HsesClient};
+
+/// Optional protocol features of a controller
+///
+/// Clients created without [`HsesClient::connect`] assume every feature until
+/// [`HsesClient::detect_capabilities`] is called.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Capabilities {
+    /// Plural variable reads and writes (0x302-0x306)
+    pub plural_variables: bool,
+    /// Sub code attributes (6-8) of alarm data and alarm history reads
+    pub alarm_sub_codes: bool,
+    /// Size of S variables [bytes]: 16, or 32 on controllers with extended strings
+    pub string_variable_size: usize,
+}
+
+impl Default for Capabilities {
+    fn default() -> Self {
+        Self {
+            plural_variables: true,
+            alarm_sub_codes: true,
+            string_variable_size: STRING_VARIABLE_SIZE,
+        }
+    }
+}
+
+impl fmt::Display for Capabilities {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let yes_no = |supported: bool| if supported { "yes" } else { "no" };
+        write!(
+            f,
+            "plural variables: {}, alarm sub codes: {}, S variables: {} bytes",
+            yes_no(self.plural_variables),
+            yes_no(self.alarm_sub_codes),
+            self.string_variable_size
+        )
+    }
+}
+
+impl HsesClient {
+    /// Optional features of the controller, as detected by [`Self::connect`] or
+    /// [`Self::detect_capabilities`]
+    #[must_use]
+    pub fn capabilities(&self) -> Capabilities {
+        self.inner.capabilities.lock().map(|capabilities| *capabilities).unwrap_or_default()
+    }
+
+    /// Probe the optional features of the controller and record them for the high-level
+    /// methods
+    ///
+    /// Sends a plural B variable read, an alarm sub code read and an S variable read; a
+    /// command the controller rejects marks its feature as missing.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a probe is not answered; the recorded capabilities are then
+    /// unchanged
+    pub async fn detect_capabilities(&self) -> Result<Capabilities, ClientError> {
+        let plural_variables = self.probe(&ReadMultipleVariables::<u8>::new(0, 2)?).await?;
+        let alarm_sub_codes =
+            self.probe(&ReadAlarmData::new(1, AlarmAttribute::SubCodeInfo)).await?;
+        let string_variable = self.probe(&ReadVariable::<String>::new(0)).await?;
+        let capabilities = Capabilities {
+            plural_variables: plural_variables.is_some(),
+            alarm_sub_codes: alarm_sub_codes.is_some(),
+            string_variable_size: string_variable
+                .map_or(STRING_VARIABLE_SIZE, |response| response.len().max(STRING_VARIABLE_SIZE)),
+        };
+        if let Ok(mut current) = self.inner.capabilities.lock() {
+            *current = capabilities;
+        }
+        Ok(capabilities)
+    }
+
+    // Send a probe request, with `None` if the controller rejects it
+    async fn probe<C: Command + Sync>(&self, command: &C) -> Result<Option<Vec<u8>>, ClientError> {
+        let request = RequestParams::of(command, Division::Robot);
+        match self.send_request_with_meta(request, command.serialize()?).await {
+            Ok((response, _meta)) => Ok(Some(response)),
+            Err(ClientError::ProtocolError(ProtocolError::ServerError(e))) => {
+                debug!("Command 0x{:03x} not supported: {e}", C::command_id());
+                Ok(None)
+            }
+            Err(e) => Err(e),
+        }
+    }
+}
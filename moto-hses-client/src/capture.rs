@@ -0,0 +1,359 @@
+//! Transcripts of captured HSES traffic
+//!
+//! [`read_pcap`] decodes a capture in the classic pcap format, e.g. saved by Wireshark or
+//! tcpdump, into the requests it holds and their outcomes, so that traffic of another
+//! HSES client can be read like a transcript recorded by this one or replayed with
+//! [`HsesClient::replay_transcript`](crate::HsesClient::replay_transcript):
+//!
+//! ```ignore
+//! let capture = std::fs::read("cell.pcap")?;
+//! let transcript = Transcript::from_pcap(&capture, ROBOT_CONTROL_PORT, FILE_CONTROL_PORT)?;
+//! transcript.save("cell.txt")?;
+//! ```
+//!
+//! Frames are taken from UDP datagrams over IPv4 or IPv6 to and from the robot and file
+//! ports, on Ethernet, Linux cooked, loopback and raw IP links. Requests are paired with
+//! their responses by the client address and request ID, and the blocks of file sends,
+//! file receives and file lists are joined into one entry. Requests without a captured
+//! response are kept with an error.
+
+use std::collections::HashMap;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::time::Duration;
+
+use moto_hses_proto::constants::header;
+use moto_hses_proto::{Division, HsesRequestMessage, HsesResponseMessage, ProtocolError, Service};
+
+use crate::transcript::{Transcript, TranscriptEntry};
+use crate::types::{ClientError, HsesClient};
+
+/// Error of requests whose response is not in the capture
+pub const NO_RESPONSE: &str = "no response captured";
+
+const LINKTYPE_NULL: u32 = 0;
+const LINKTYPE_ETHERNET: u32 = 1;
+const LINKTYPE_RAW: u32 = 101;
+const LINKTYPE_LINUX_SLL: u32 = 113;
+const LINKTYPE_IPV4: u32 = 228;
+const LINKTYPE_IPV6: u32 = 229;
+const LINKTYPE_LINUX_SLL2: u32 = 276;
+
+const UDP: u8 = 17;
+
+/// Request found in a capture and its outcome
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CapturedRequest {
+    /// Time of the request since the first packet of the capture
+    pub time: Duration,
+    /// Address the request was sent from
+    pub client: SocketAddr,
+    pub entry: TranscriptEntry,
+}
+
+impl Transcript {
+    /// Requests of a pcap capture and their outcomes, in sending order
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the capture is not in the classic pcap format
+    pub fn from_pcap(data: &[u8], robot_port: u16, file_port: u16) -> Result<Self, ClientError> {
+        let entries = read_pcap(data, robot_port, file_port)?
+            .into_iter()
+            .map(|request| request.entry)
+            .collect();
+        Ok(Self { entries })
+    }
+}
+
+/// Requests of a pcap capture sent to `robot_port` or `file_port`, in sending order
+///
+/// # Errors
+///
+/// Returns an error if the capture is not in the classic pcap format
+pub fn read_pcap(
+    data: &[u8],
+    robot_port: u16,
+    file_port: u16,
+) -> Result<Vec<CapturedRequest>, ClientError> {
+    let invalid = |reason: &str| -> ClientError {
+        ProtocolError::InvalidMessage(format!("invalid pcap capture: {reason}")).into()
+    };
+    let magic = data.get(..4).ok_or_else(|| invalid("missing file header"))?;
+    let (big_endian, nanos) = match magic {
+        [0xD4, 0xC3, 0xB2, 0xA1] => (false, false),
+        [0xA1, 0xB2, 0xC3, 0xD4] => (true, false),
+        [0x4D, 0x3C, 0xB2, 0xA1] => (false, true),
+        [0xA1, 0xB2, 0x3C, 0x4D] => (true, true),
+        [0x0A, 0x0D, 0x0D, 0x0A] => {
+            return Err(invalid("pcapng is not supported, save the capture as pcap"));
+        }
+        _ => return Err(invalid("unknown magic number")),
+    };
+    let word = |offset: usize| -> Option<u32> {
+        let bytes: [u8; 4] = data.get(offset..offset + 4)?.try_into().ok()?;
+        Some(if big_endian { u32::from_be_bytes(bytes) } else { u32::from_le_bytes(bytes) })
+    };
+    let link_type = word(20).ok_or_else(|| invalid("missing file header"))?;
+
+    let mut pairing = Pairing::default();
+    let mut start = None;
+    let mut offset = 24;
+    while let (Some(seconds), Some(fraction), Some(length)) =
+        (word(offset), word(offset + 4), word(offset + 8))
+    {
+        let body = offset + 16;
+        let end = body + length as usize;
+        let packet = data.get(body..end).ok_or_else(|| invalid("truncated packet"))?;
+        offset = end;
+
+        let fraction = if nanos { fraction } else { fraction.saturating_mul(1000) };
+        let time = Duration::new(u64::from(seconds), fraction);
+        let time = time.saturating_sub(*start.get_or_insert(time));
+        if let Some((source, destination, frame)) = udp_datagram(link_type, packet) {
+            if [robot_port, file_port].contains(&destination.port()) {
+                pairing.request(time, source, frame);
+            } else if [robot_port, file_port].contains(&source.port()) {
+                pairing.response(destination, frame);
+            }
+        }
+    }
+    Ok(pairing.finish())
+}
+
+// Source, destination and payload of a UDP datagram in a packet of `link_type`
+fn udp_datagram(link_type: u32, packet: &[u8]) -> Option<(SocketAddr, SocketAddr, &[u8])> {
+    let ip = match link_type {
+        LINKTYPE_NULL => packet.get(4..)?,
+        LINKTYPE_ETHERNET => {
+            let mut offset = 12;
+            // VLAN tags
+            while matches!(packet.get(offset..offset + 2)?, [0x81, 0x00] | [0x88, 0xA8]) {
+                offset += 4;
+            }
+            packet.get(offset + 2..)?
+        }
+        LINKTYPE_RAW | LINKTYPE_IPV4 | LINKTYPE_IPV6 => packet,
+        LINKTYPE_LINUX_SLL => packet.get(16..)?,
+        LINKTYPE_LINUX_SLL2 => packet.get(20..)?,
+        _ => return None,
+    };
+    let (source, destination, udp) = match ip.first()? >> 4 {
+        4 => {
+            let header_length = usize::from(ip[0] & 0x0F) * 4;
+            // Fragments other than the first one of unfragmented datagrams are skipped
+            let fragment = u16::from_be_bytes([*ip.get(6)?, *ip.get(7)?]) & 0x3FFF;
+            if *ip.get(9)? != UDP || fragment != 0 {
+                return None;
+            }
+            let address = |at: usize| -> Option<IpAddr> {
+                let octets: [u8; 4] = ip.get(at..at + 4)?.try_into().ok()?;
+                Some(Ipv4Addr::from(octets).into())
+            };
+            (address(12)?, address(16)?, ip.get(header_length..)?)
+        }
+        6 => {
+            if *ip.get(6)? != UDP {
+                return None;
+            }
+            let address = |at: usize| -> Option<IpAddr> {
+                let octets: [u8; 16] = ip.get(at..at + 16)?.try_into().ok()?;
+                Some(Ipv6Addr::from(octets).into())
+            };
+            (address(8)?, address(24)?, ip.get(40..)?)
+        }
+        _ => return None,
+    };
+    let port = |at: usize| Some(u16::from_be_bytes([*udp.get(at)?, *udp.get(at + 1)?]));
+    let length = usize::from(port(4)?);
+    let payload = udp.get(8..length.max(8))?;
+    Some((SocketAddr::new(source, port(0)?), SocketAddr::new(destination, port(2)?), payload))
+}
+
+// Request whose response is still being captured
+#[derive(Debug)]
+struct Pending {
+    request: CapturedRequest,
+    response: Vec<u8>,
+    done: bool,
+}
+
+#[derive(Debug, Default)]
+struct Pairing {
+    requests: Vec<Pending>,
+    // Index in `requests` by client, division and request ID; kept after the response
+    // as the blocks of a file send are answered one by one
+    open: HashMap<(SocketAddr, u8, u8), usize>,
+}
+
+impl Pairing {
+    fn request(&mut self, time: Duration, client: SocketAddr, frame: &[u8]) {
+        let Ok(message) = HsesRequestMessage::decode(frame) else {
+            return;
+        };
+        let frame_header = &message.header;
+        // ACKs of received file blocks are sent by the client with the ACK flag
+        if frame_header.ack != 0 {
+            return;
+        }
+        let key = (client, frame_header.division, frame_header.request_id);
+        if frame_header.block_number != 0 {
+            // Further block of a file send
+            if let Some(pending) = self.open.get(&key).map(|&index| &mut self.requests[index]) {
+                pending.request.entry.payload.extend_from_slice(&message.payload);
+            }
+            return;
+        }
+        let division = match frame_header.division {
+            1 => Division::Robot,
+            2 => Division::File,
+            _ => return,
+        };
+        let Ok(service) = Service::try_from(message.sub_header.service) else {
+            return;
+        };
+        let entry = TranscriptEntry {
+            division,
+            command: message.sub_header.command,
+            instance: message.sub_header.instance,
+            attribute: message.sub_header.attribute,
+            service,
+            payload: message.payload,
+            response: Err(NO_RESPONSE.to_string()),
+        };
+        self.open.insert(key, self.requests.len());
+        self.requests.push(Pending {
+            request: CapturedRequest { time, client, entry },
+            response: Vec::new(),
+            done: false,
+        });
+    }
+
+    fn response(&mut self, client: SocketAddr, frame: &[u8]) {
+        let Ok(message) = HsesResponseMessage::decode(frame) else {
+            return;
+        };
+        let frame_header = &message.header;
+        let key = (client, frame_header.division, frame_header.request_id);
+        let Some(pending) = self.open.get(&key).map(|&index| &mut self.requests[index]) else {
+            return;
+        };
+        let status = message.sub_header.status;
+        if status != 0 {
+            // Also a later block of a file send may fail
+            let error = HsesClient::build_error_message(status, frame);
+            pending.request.entry.response =
+                Err(ClientError::from(ProtocolError::ServerError(error)).to_string());
+            pending.done = true;
+        } else if !pending.done {
+            pending.response.extend_from_slice(&message.payload);
+            if frame_header.block_number & header::LAST_BLOCK_FLAG != 0 {
+                pending.request.entry.response = Ok(std::mem::take(&mut pending.response));
+                pending.done = true;
+            }
+        }
+    }
+
+    fn finish(self) -> Vec<CapturedRequest> {
+        self.requests.into_iter().map(|pending| pending.request).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CLIENT: [u8; 4] = [192, 168, 0, 10];
+    const CONTROLLER: [u8; 4] = [192, 168, 0, 1];
+
+    // Ethernet frame of a UDP datagram over IPv4
+    fn ethernet(source: ([u8; 4], u16), destination: ([u8; 4], u16), frame: &[u8]) -> Vec<u8> {
+        let udp_length = u16::try_from(8 + frame.len()).unwrap_or(u16::MAX);
+        let mut packet = vec![0; 12];
+        packet.extend_from_slice(&[0x08, 0x00, 0x45, 0, 0, 0, 0, 0, 0x40, 0, 64, UDP, 0, 0]);
+        packet[16..18].copy_from_slice(&(udp_length + 20).to_be_bytes());
+        packet.extend_from_slice(&source.0);
+        packet.extend_from_slice(&destination.0);
+        packet.extend_from_slice(&source.1.to_be_bytes());
+        packet.extend_from_slice(&destination.1.to_be_bytes());
+        packet.extend_from_slice(&udp_length.to_be_bytes());
+        packet.extend_from_slice(&[0, 0]);
+        packet.extend_from_slice(frame);
+        packet
+    }
+
+    fn pcap(packets: &[Vec<u8>]) -> Vec<u8> {
+        let mut data = vec![0xD4, 0xC3, 0xB2, 0xA1, 2, 0, 4, 0];
+        data.extend_from_slice(&[0; 8]);
+        data.extend_from_slice(&65535_u32.to_le_bytes());
+        data.extend_from_slice(&LINKTYPE_ETHERNET.to_le_bytes());
+        for (index, packet) in (0_u32..).zip(packets) {
+            let length = u32::try_from(packet.len()).unwrap_or(u32::MAX);
+            data.extend_from_slice(&100_u32.to_le_bytes());
+            data.extend_from_slice(&(index * 1000).to_le_bytes());
+            data.extend_from_slice(&length.to_le_bytes());
+            data.extend_from_slice(&length.to_le_bytes());
+            data.extend_from_slice(packet);
+        }
+        data
+    }
+
+    fn request(request_id: u8, block_number: u32, payload: &[u8]) -> Vec<u8> {
+        let mut message =
+            HsesRequestMessage::new(2, 0, request_id, 0, 0, 0, 0x15, payload.to_vec())
+                .map(|message| message.encode().to_vec())
+                .unwrap_or_default();
+        message[header::BLOCK_NUMBER_OFFSET..header::BLOCK_NUMBER_OFFSET + 4]
+            .copy_from_slice(&block_number.to_le_bytes());
+        message
+    }
+
+    fn response(division: u8, request_id: u8, status: u8, payload: &[u8]) -> Vec<u8> {
+        HsesResponseMessage::new(division, 1, request_id, 0x8E, status, 0x2010, payload.to_vec())
+            .map(|message| message.encode().to_vec())
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn test_read_pcap_pairs_requests_and_responses() {
+        let robot_request = HsesRequestMessage::new(1, 0, 7, 0x79, 3, 1, 0x0E, Vec::new())
+            .map(|message| message.encode().to_vec())
+            .unwrap_or_default();
+        let capture = pcap(&[
+            ethernet((CLIENT, 50000), (CONTROLLER, 10040), &robot_request),
+            // A response to another client is not paired
+            ethernet((CONTROLLER, 10040), (CLIENT, 50001), &response(1, 7, 0, &[9, 9])),
+            ethernet((CONTROLLER, 10040), (CLIENT, 50000), &response(1, 7, 0, &[0xD2, 0x04])),
+            ethernet((CLIENT, 50000), (CONTROLLER, 10041), &request(8, 0, b"JOB.JBI")),
+            ethernet((CONTROLLER, 10041), (CLIENT, 50000), &response(2, 8, 0, &[])),
+            ethernet((CLIENT, 50000), (CONTROLLER, 10041), &request(8, 1, b"abc")),
+            ethernet((CONTROLLER, 10041), (CLIENT, 50000), &response(2, 8, 0, &[])),
+            ethernet((CLIENT, 50000), (CONTROLLER, 10041), &request(8, 2, b"def")),
+            ethernet((CLIENT, 50000), (CONTROLLER, 10040), &robot_request[..20]),
+            ethernet((CLIENT, 50002), (CONTROLLER, 10040), &robot_request),
+            ethernet((CONTROLLER, 10040), (CLIENT, 50002), &response(1, 7, 0x1F, &[])),
+        ]);
+
+        let requests = read_pcap(&capture, 10040, 10041).unwrap_or_default();
+        assert_eq!(requests.len(), 3);
+        assert_eq!(requests[0].time, Duration::ZERO);
+        assert_eq!(requests[0].client, SocketAddr::from((CLIENT, 50000)));
+        assert_eq!(
+            requests[0].entry.to_string(),
+            "robot command=0x0079 instance=3 attribute=1 service=0x0E payload=[] \
+             response=[D2 04]"
+        );
+        assert_eq!(requests[1].time, Duration::from_micros(3000));
+        assert_eq!(requests[1].entry.payload, b"JOB.JBIabcdef");
+        assert_eq!(requests[1].entry.response, Ok(Vec::new()));
+        assert_eq!(
+            requests[2].entry.response,
+            Err("Protocol error: server error: Server returned error status: 0x1f \
+                 (added status: 0x2010)"
+                .to_string())
+        );
+
+        let transcript = Transcript::from_pcap(&capture, 10040, 10041).unwrap_or_default();
+        assert_eq!(transcript.to_string().parse::<Transcript>().ok(), Some(transcript));
+        assert!(read_pcap(&[0x0A, 0x0D, 0x0D, 0x0A], 10040, 10041).is_err());
+    }
+}
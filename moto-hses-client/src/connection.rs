@@ -1,51 +1,483 @@
 //! Connection management for HSES client
 
-use crate::types::{ClientConfig, ClientError, HsesClient, InnerClient};
+use crate::capabilities::Capabilities;
+use crate::transport::{RoutedTransport, Transport, UdpTransport};
+use crate::types::{
+    ClientConfig, ClientError, HsesClient, InnerClient, RequestLimits, ResponseMeta,
+};
+use std::collections::BTreeMap;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, PoisonError};
+use std::time::Duration;
+use tokio::sync::{Semaphore, SemaphorePermit, TryAcquireError};
 
 impl HsesClient {
     /// Create a new client with default configuration
     ///
+    /// `addr` is "host:port", with IPv6 hosts in brackets, e.g. `[::1]:10040`.
+    ///
     /// # Errors
     ///
     /// Returns an error if address parsing or connection fails
     pub async fn new(addr: &str) -> Result<Self, ClientError> {
-        let mut config = ClientConfig::default();
-        // Parse address and update config
-        let addr_parts: Vec<&str> = addr.split(':').collect();
-        if addr_parts.len() == 2 {
-            config.host = addr_parts[0].to_string();
-            config.port = addr_parts[1]
-                .parse()
-                .map_err(|e| ClientError::SystemError(format!("Invalid port: {e}")))?;
-        } else {
-            return Err(ClientError::SystemError(
-                "Invalid address format. Use 'host:port'".to_string(),
-            ));
-        }
-        Self::new_with_config(config).await
+        let (host, port) = parse_addr(addr)?;
+        Self::new_with_config(ClientConfig { host, port, ..ClientConfig::default() }).await
+    }
+
+    /// Create a client and check that a controller answers at `addr` ("host:port")
+    ///
+    /// The other constructors only bind a socket, so an unreachable controller shows up as
+    /// timeouts of the first command. This reads the status once, without retries, and
+    /// then the system information of robot R1, which [`Self::info`] returns, and detects
+    /// the optional features of the controller, which [`Self::capabilities`] returns. The
+    /// host and port of `config` are replaced by `addr`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ClientError::ConnectError`] if the status read is not answered within the
+    /// configured timeout, or an error if address parsing or communication fails
+    pub async fn connect(addr: &str, config: ClientConfig) -> Result<Self, ClientError> {
+        let (host, port) = parse_addr(addr)?;
+        let client = Self::new_with_config(ClientConfig { host, port, ..config }).await?;
+        client.identify().await?;
+        Ok(client)
     }
 
     /// Create a new client with custom configuration
     ///
+    /// The host is an IPv4 or IPv6 address, optionally with a scope ID such as
+    /// `fe80::1%eth0`, or a host name resolved through DNS; the first address found is used.
+    ///
     /// # Errors
     ///
-    /// Returns an error if connection fails
+    /// Returns an error if the host cannot be resolved or the socket cannot be bound
     pub async fn new_with_config(config: ClientConfig) -> Result<Self, ClientError> {
-        let addr = format!("{}:{}", config.host, config.port);
-        let client = Self {
+        let remote_addr = resolve_addr(&config.host, config.port).await?;
+        let transport = UdpTransport::bind(remote_addr).await?;
+        let priority_transport = UdpTransport::bind(remote_addr).await?;
+        Ok(Self::from_transports(config, Box::new(transport), Box::new(priority_transport), false))
+    }
+
+    /// Create a client exchanging frames through `transport` instead of UDP
+    ///
+    /// Emergency commands go through `priority_transport`, which must reach the same
+    /// controller. The host and port of `config` are only used in messages, and
+    /// [`Self::delete_files`] deletes one file at a time.
+    #[must_use]
+    pub fn with_transport(
+        config: ClientConfig,
+        transport: impl Transport + 'static,
+        priority_transport: impl Transport + 'static,
+    ) -> Self {
+        Self::from_transports(config, Box::new(transport), Box::new(priority_transport), true)
+    }
+
+    fn from_transports(
+        config: ClientConfig,
+        transport: Box<dyn Transport>,
+        priority_transport: Box<dyn Transport>,
+        custom_transport: bool,
+    ) -> Self {
+        Self {
             inner: std::sync::Arc::new(InnerClient {
-                socket: tokio::net::UdpSocket::bind("0.0.0.0:0").await?,
-                remote_addr: addr
-                    .parse()
-                    .map_err(|e| ClientError::SystemError(format!("Invalid address: {e}")))?,
+                request_slots: config.request_limits.map(RequestSlots::new),
+                remote_addr: format_addr(&config.host, config.port),
+                config: std::sync::RwLock::new(std::sync::Arc::new(config)),
+                transport: RoutedTransport::new(transport),
+                priority_transport: tokio::sync::Mutex::new(RoutedTransport::new(
+                    priority_transport,
+                )),
+                custom_transport,
                 request_id: std::sync::atomic::AtomicU8::new(1),
                 _pending_requests: std::sync::Arc::new(std::sync::Mutex::new(
                     std::collections::HashMap::new(),
                 )),
+                response_hook: std::sync::Mutex::new(None),
+                auditor: std::sync::Mutex::new(None),
+                transcript: std::sync::Mutex::new(None),
+                link_stats: std::sync::Mutex::new(LinkStatsRecorder::default()),
+                info: std::sync::Mutex::new(None),
+                capabilities: std::sync::Mutex::new(Capabilities::default()),
+                write_sequence: tokio::sync::Mutex::new(()),
+                last_paced_response: std::sync::Mutex::new(None),
+                strict_encoding: std::sync::atomic::AtomicBool::new(false),
             }),
-            config,
+        }
+    }
+
+    /// Copy of the current configuration of the client
+    ///
+    /// Replaces the former public `config` field, as the configuration can now be changed
+    /// with [`Self::update_config`]; a later change does not affect the returned copy.
+    #[must_use]
+    pub fn config(&self) -> ClientConfig {
+        ClientConfig::clone(&self.current_config())
+    }
+
+    // Current configuration, shared rather than copied for the requests
+    pub(crate) fn current_config(&self) -> Arc<ClientConfig> {
+        Arc::clone(&self.inner.config.read().unwrap_or_else(PoisonError::into_inner))
+    }
+
+    /// Change the configuration of the live client, e.g. its timeouts, retries or write
+    /// pacing
+    ///
+    /// `update` is applied to a copy of the configuration, which then replaces it as a
+    /// whole, so a request sees either the old or the new values. The change applies to
+    /// the requests started afterwards, also those of the watches, pollers and
+    /// [`PriorityLane`](crate::PriorityLane)s of the client, which keep running; requests
+    /// in flight finish with the values they were started with. The host and port of the
+    /// socket and the request limits are fixed when the client is created.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ClientError::SystemError`] if `update` changes the host, port or request
+    /// limits; the configuration is then left unchanged
+    pub fn update_config(&self, update: impl FnOnce(&mut ClientConfig)) -> Result<(), ClientError> {
+        let mut current = self.inner.config.write().unwrap_or_else(PoisonError::into_inner);
+        let mut config = ClientConfig::clone(&current);
+        update(&mut config);
+        if config.host != current.host
+            || config.port != current.port
+            || config.request_limits != current.request_limits
+        {
+            return Err(ClientError::SystemError(
+                "The host, port and request limits cannot be changed on a live client".to_string(),
+            ));
+        }
+        *current = Arc::new(config);
+        drop(current);
+        Ok(())
+    }
+
+    /// Round-trip times measured on the link to the controller
+    ///
+    /// Every answered request is measured from the sending of its successful attempt to
+    /// the arrival of its response; pipelined requests from the sending of their batch.
+    #[must_use]
+    pub fn link_stats(&self) -> LinkStats {
+        self.inner.link_stats.lock().map(|recorder| recorder.stats()).unwrap_or_default()
+    }
+
+    /// Discard the round-trip times measured so far, e.g. after the network changed
+    pub fn reset_link_stats(&self) {
+        if let Ok(mut recorder) = self.inner.link_stats.lock() {
+            *recorder = LinkStatsRecorder::default();
+        }
+    }
+
+    // Time to wait for the response to one attempt of `command`
+    //
+    // The configured timeout, or with an adaptive timeout a multiple of the smoothed RTT of
+    // the command, or of the whole link while the command has too few samples
+    pub(crate) fn response_timeout(&self, command: u16) -> Duration {
+        let Some(adaptive) = &self.current_config().adaptive_timeout else {
+            return self.current_config().timeout;
+        };
+        let Ok(recorder) = self.inner.link_stats.lock() else {
+            return self.current_config().timeout;
         };
+        [recorder.commands.get(&command), Some(&recorder.overall)]
+            .into_iter()
+            .flatten()
+            .find(|rtt| rtt.samples >= adaptive.min_samples)
+            .map_or(self.current_config().timeout, |rtt| {
+                (rtt.smoothed * adaptive.multiplier).clamp(adaptive.min, adaptive.max)
+            })
+    }
 
-        Ok(client)
+    // Take slots of the request limits for `count` requests to be sent, if limited
+    pub(crate) async fn acquire_request_slots(
+        &self,
+        count: usize,
+    ) -> Result<Option<SemaphorePermit<'_>>, ClientError> {
+        match &self.inner.request_slots {
+            Some(slots) => slots.acquire(count).await.map(Some),
+            None => Ok(None),
+        }
+    }
+
+    pub(crate) fn record_response(&self, meta: &ResponseMeta) {
+        if let Ok(mut recorder) = self.inner.link_stats.lock() {
+            recorder.overall.record(meta.rtt);
+            recorder.commands.entry(meta.command).or_default().record(meta.rtt);
+        }
+    }
+
+    pub(crate) fn record_timeout(&self, command: u16) {
+        if let Ok(mut recorder) = self.inner.link_stats.lock() {
+            recorder.overall.timeouts += 1;
+            recorder.commands.entry(command).or_default().timeouts += 1;
+        }
+    }
+}
+
+// Slots of the requests in flight and the count of the requests waiting for one
+pub(crate) struct RequestSlots {
+    limits: RequestLimits,
+    in_flight: Semaphore,
+    queued: AtomicUsize,
+}
+
+impl RequestSlots {
+    fn new(limits: RequestLimits) -> Self {
+        Self {
+            in_flight: Semaphore::new(limits.max_in_flight.max(1)),
+            queued: AtomicUsize::new(0),
+            limits,
+        }
+    }
+
+    // Take `count` slots, at most all of them, waiting for them if the queue has room
+    async fn acquire(&self, count: usize) -> Result<SemaphorePermit<'_>, ClientError> {
+        let count = count.clamp(1, self.limits.max_in_flight.max(1));
+        let count = u32::try_from(count).unwrap_or(u32::MAX);
+        match self.in_flight.try_acquire_many(count) {
+            Ok(permit) => return Ok(permit),
+            Err(TryAcquireError::NoPermits) => {}
+            Err(TryAcquireError::Closed) => {
+                return Err(ClientError::SystemError("Request slots closed".to_string()));
+            }
+        }
+        let queued = QueuedRequest::enter(&self.queued);
+        if queued.position >= self.limits.max_queued {
+            return Err(ClientError::Overloaded(format!(
+                "{} requests in flight and {} waiting",
+                self.limits.max_in_flight, self.limits.max_queued
+            )));
+        }
+        self.in_flight
+            .acquire_many(count)
+            .await
+            .map_err(|_| ClientError::SystemError("Request slots closed".to_string()))
+    }
+}
+
+// Place in the queue of requests waiting for a slot, left when dropped
+struct QueuedRequest<'a> {
+    queued: &'a AtomicUsize,
+    // Number of requests that were waiting before this one
+    position: usize,
+}
+
+impl<'a> QueuedRequest<'a> {
+    fn enter(queued: &'a AtomicUsize) -> Self {
+        let position = queued.fetch_add(1, Ordering::AcqRel);
+        Self { queued, position }
+    }
+}
+
+impl Drop for QueuedRequest<'_> {
+    fn drop(&mut self) {
+        self.queued.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+/// Round-trip times of a client, see [`HsesClient::link_stats`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LinkStats {
+    /// All requests
+    pub overall: RttStats,
+    /// Requests by command ID; file control requests all have command 0x00
+    pub commands: BTreeMap<u16, RttStats>,
+}
+
+/// Round-trip times of answered requests and the number of unanswered attempts
+///
+/// Percentiles are read from a histogram with buckets from 100 µs to 10 s in 1-2-5
+/// steps, so they are the upper bound of their bucket, at most the maximum.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RttStats {
+    /// Number of answered requests
+    pub samples: u64,
+    /// Number of attempts that timed out
+    pub timeouts: u64,
+    /// Exponentially weighted moving average of the round-trip times, weighting the
+    /// newest sample by 1/8
+    pub smoothed: Duration,
+    pub min: Duration,
+    pub max: Duration,
+    pub p50: Duration,
+    pub p90: Duration,
+    pub p99: Duration,
+}
+
+// Upper bounds of the histogram buckets [µs]; slower samples go to an overflow bucket
+const BUCKET_BOUNDS_US: [u64; 16] = [
+    100, 200, 500, 1_000, 2_000, 5_000, 10_000, 20_000, 50_000, 100_000, 200_000, 500_000,
+    1_000_000, 2_000_000, 5_000_000, 10_000_000,
+];
+
+// Round-trip times measured by a client
+#[derive(Debug, Default)]
+pub(crate) struct LinkStatsRecorder {
+    overall: RttRecorder,
+    commands: BTreeMap<u16, RttRecorder>,
+}
+
+impl LinkStatsRecorder {
+    fn stats(&self) -> LinkStats {
+        LinkStats {
+            overall: self.overall.stats(),
+            commands: self.commands.iter().map(|(command, rtt)| (*command, rtt.stats())).collect(),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct RttRecorder {
+    samples: u64,
+    timeouts: u64,
+    smoothed: Duration,
+    min: Duration,
+    max: Duration,
+    buckets: [u64; BUCKET_BOUNDS_US.len() + 1],
+}
+
+impl RttRecorder {
+    fn record(&mut self, rtt: Duration) {
+        if self.samples == 0 {
+            self.smoothed = rtt;
+            self.min = rtt;
+        } else {
+            self.smoothed = (self.smoothed * 7 + rtt) / 8;
+            self.min = self.min.min(rtt);
+        }
+        self.max = self.max.max(rtt);
+        self.samples += 1;
+        let micros = u64::try_from(rtt.as_micros()).unwrap_or(u64::MAX);
+        let bucket = BUCKET_BOUNDS_US.partition_point(|bound| *bound < micros);
+        self.buckets[bucket] += 1;
+    }
+
+    // Upper bound of the bucket holding the sample at `percent`, within the observed range
+    fn percentile(&self, percent: u64) -> Duration {
+        if self.samples == 0 {
+            return Duration::ZERO;
+        }
+        let rank = (self.samples * percent).div_ceil(100).max(1);
+        let mut count = 0;
+        for (bucket, samples) in self.buckets.iter().enumerate() {
+            count += samples;
+            if count >= rank {
+                return BUCKET_BOUNDS_US
+                    .get(bucket)
+                    .map_or(self.max, |bound| Duration::from_micros(*bound))
+                    .clamp(self.min, self.max);
+            }
+        }
+        self.max
+    }
+
+    fn stats(&self) -> RttStats {
+        RttStats {
+            samples: self.samples,
+            timeouts: self.timeouts,
+            smoothed: self.smoothed,
+            min: self.min,
+            max: self.max,
+            p50: self.percentile(50),
+            p90: self.percentile(90),
+            p99: self.percentile(99),
+        }
+    }
+}
+
+// Split a "host:port" or "[IPv6 host]:port" address
+fn parse_addr(addr: &str) -> Result<(String, u16), ClientError> {
+    let invalid = || {
+        ClientError::SystemError(format!(
+            "Invalid address format: {addr}. Use 'host:port' or '[IPv6 host]:port'"
+        ))
+    };
+    let (host, port) = addr.rsplit_once(':').ok_or_else(invalid)?;
+    let host = match host.strip_prefix('[') {
+        Some(bracketed) => bracketed.strip_suffix(']').ok_or_else(invalid)?,
+        // An IPv6 host without brackets cannot be told apart from its port
+        None if host.contains(':') => return Err(invalid()),
+        None => host,
+    };
+    if host.is_empty() {
+        return Err(invalid());
+    }
+    let port = port.parse().map_err(|e| ClientError::SystemError(format!("Invalid port: {e}")))?;
+    Ok((host.to_string(), port))
+}
+
+// "host:port", with IPv6 hosts in brackets
+fn format_addr(host: &str, port: u16) -> String {
+    if host.contains(':') { format!("[{host}]:{port}") } else { format!("{host}:{port}") }
+}
+
+// Resolve an address literal, including an IPv6 scope ID, or a host name
+async fn resolve_addr(host: &str, port: u16) -> Result<SocketAddr, ClientError> {
+    tokio::net::lookup_host((host, port))
+        .await
+        .map_err(|e| ClientError::SystemError(format!("Cannot resolve {host}: {e}")))?
+        .next()
+        .ok_or_else(|| ClientError::SystemError(format!("No address found for {host}")))
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_addr() {
+        assert_eq!(parse_addr("192.168.0.3:10040").unwrap(), ("192.168.0.3".into(), 10040));
+        assert_eq!(parse_addr("robot-1.plant:10040").unwrap(), ("robot-1.plant".into(), 10040));
+        assert_eq!(parse_addr("[::1]:10040").unwrap(), ("::1".into(), 10040));
+        assert_eq!(parse_addr("[fe80::1%eth0]:10040").unwrap(), ("fe80::1%eth0".into(), 10040));
+        for invalid in ["192.168.0.3", "::1:10040", "[::1:10040", ":10040", "host:port"] {
+            assert!(parse_addr(invalid).is_err(), "{invalid} should be rejected");
+        }
+        assert_eq!(format_addr("::1", 10040), "[::1]:10040");
+        assert_eq!(format_addr("localhost", 10040), "localhost:10040");
+    }
+
+    #[tokio::test]
+    async fn test_resolve_addr() {
+        let addr = resolve_addr("::1", 10040).await.unwrap();
+        assert_eq!(addr, "[::1]:10040".parse().unwrap());
+        let addr = resolve_addr("192.168.0.3", 10040).await.unwrap();
+        assert_eq!(addr, "192.168.0.3:10040".parse().unwrap());
+        assert!(resolve_addr("localhost", 10040).await.unwrap().ip().is_loopback());
+    }
+
+    #[tokio::test]
+    #[ignore = "queries the DNS resolver of the host"]
+    async fn test_resolve_unknown_host() {
+        assert!(resolve_addr("no-such-host.invalid", 10040).await.is_err());
+    }
+
+    #[test]
+    fn test_rtt_recorder() {
+        let mut recorder = RttRecorder::default();
+        assert_eq!(recorder.stats(), RttStats::default());
+
+        recorder.record(Duration::from_millis(8));
+        assert_eq!(recorder.smoothed, Duration::from_millis(8));
+        for _ in 0..98 {
+            recorder.record(Duration::from_micros(1_500));
+        }
+        recorder.record(Duration::from_millis(30));
+        let stats = recorder.stats();
+        assert_eq!(stats.samples, 100);
+        assert_eq!(stats.min, Duration::from_micros(1_500));
+        assert_eq!(stats.max, Duration::from_millis(30));
+        // 1.5 ms samples are in the bucket up to 2 ms
+        assert_eq!(stats.p50, Duration::from_millis(2));
+        assert_eq!(stats.p90, Duration::from_millis(2));
+        assert_eq!(stats.p99, Duration::from_millis(10));
+        // The slow last sample moves the settled average by an eighth of the difference
+        let expected = Duration::from_micros(1_500 + 28_500 / 8);
+        assert!(stats.smoothed.abs_diff(expected) < Duration::from_micros(10), "{stats:?}");
+
+        // Samples above the last bucket are reported as the maximum
+        let mut recorder = RttRecorder::default();
+        recorder.record(Duration::from_secs(20));
+        assert_eq!(recorder.percentile(50), Duration::from_secs(20));
     }
 }
@@ -77,13 +77,14 @@ impl HsesClient {
 
     /// Read a string variable (S variable) with encoding support
     ///
-    /// Uses `read_variable<String>` which handles encoding/decoding in the proto layer.
+    /// Uses `read_string_variable`, which decodes with the client's text encoding and
+    /// honors [`HsesClient::set_strict_encoding`].
     ///
     /// # Errors
     ///
-    /// Returns an error if communication fails
+    /// Returns an error if communication fails or the text cannot be decoded in strict mode
     pub async fn read_string(&self, index: u16) -> Result<String, ClientError> {
-        self.read_variable::<String>(index).await
+        self.read_string_variable(index).await
     }
 
     /// Write a string variable (S variable) with encoding support
@@ -92,7 +93,9 @@ impl HsesClient {
     ///
     /// # Errors
     ///
-    /// Returns an error if communication fails or if string exceeds 16 bytes when encoded
+    /// Returns an error if communication fails, if the string exceeds 16 bytes when encoded
+    /// ([`ProtocolError::StringTooLong`](moto_hses_proto::ProtocolError::StringTooLong)) or
+    /// if it has characters the text encoding cannot represent
     pub async fn write_string(&self, index: u16, value: String) -> Result<(), ClientError> {
         self.write_string_variable(index, value).await
     }
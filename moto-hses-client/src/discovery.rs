@@ -0,0 +1,126 @@
+//! Discovery of controllers on a network
+//!
+//! Controllers do not announce themselves over HSES, so [`discover`] probes candidate
+//! addresses: it sends one status read to each, many at a time, and lists the ones that
+//! answer together with the system information of robot R1. [`subnet_hosts`] lists the
+//! candidates of an IPv4 subnet:
+//!
+//! ```ignore
+//! let hosts = subnet_hosts(Ipv4Addr::new(192, 168, 0, 0), 24)?;
+//! for controller in discover(hosts.into_iter().map(IpAddr::V4), DiscoveryOptions::default()).await {
+//!     println!("{}: {:?}", controller.addr, controller.info.map(|info| info.model));
+//! }
+//! ```
+
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::time::Duration;
+
+use futures::StreamExt;
+use moto_hses_proto::{ROBOT_CONTROL_PORT, SystemInfo};
+
+use crate::types::{ClientConfig, ClientError, HsesClient};
+
+/// Smallest prefix length accepted by [`subnet_hosts`], limiting a scan to 65534 hosts
+pub const MIN_SUBNET_PREFIX_LEN: u8 = 16;
+
+/// Options for [`discover`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DiscoveryOptions {
+    /// Robot control port probed on every address (default: 10040)
+    pub port: u16,
+    /// Time to wait for the answer of each address (default: 200 ms)
+    pub timeout: Duration,
+    /// Addresses probed at the same time (default: 64)
+    pub concurrency: usize,
+}
+
+impl Default for DiscoveryOptions {
+    fn default() -> Self {
+        Self { port: ROBOT_CONTROL_PORT, timeout: Duration::from_millis(200), concurrency: 64 }
+    }
+}
+
+/// Controller that answered a discovery probe
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiscoveredController {
+    /// Robot control address of the controller
+    pub addr: SocketAddr,
+    /// System information of robot R1; `None` if the controller did not report it
+    pub info: Option<SystemInfo>,
+}
+
+/// Probe `addresses` and list the controllers that answer, sorted by address
+///
+/// Each address gets the handshake of [`HsesClient::connect`] without retries, so a
+/// controller that misses the first status read is not listed.
+pub async fn discover(
+    addresses: impl IntoIterator<Item = IpAddr>,
+    options: DiscoveryOptions,
+) -> Vec<DiscoveredController> {
+    let mut found: Vec<DiscoveredController> = futures::stream::iter(addresses)
+        .map(|ip| probe(SocketAddr::new(ip, options.port), options.timeout))
+        .buffer_unordered(options.concurrency.max(1))
+        .filter_map(|controller| async move { controller })
+        .collect()
+        .await;
+    found.sort_by_key(|controller| controller.addr);
+    found
+}
+
+/// Host addresses of the IPv4 subnet `network`/`prefix_len`
+///
+/// The network and broadcast addresses are left out of subnets with more than two
+/// addresses. Host bits set in `network` are ignored.
+///
+/// # Errors
+///
+/// Returns an error if `prefix_len` is above 32 or below [`MIN_SUBNET_PREFIX_LEN`]
+pub fn subnet_hosts(network: Ipv4Addr, prefix_len: u8) -> Result<Vec<Ipv4Addr>, ClientError> {
+    if !(MIN_SUBNET_PREFIX_LEN..=32).contains(&prefix_len) {
+        return Err(ClientError::SystemError(format!(
+            "Invalid subnet prefix length: {prefix_len} (expected {MIN_SUBNET_PREFIX_LEN}-32)"
+        )));
+    }
+    let mask = u32::MAX << (32 - u32::from(prefix_len));
+    let first = u32::from(network) & mask;
+    let last = first | !mask;
+    let hosts = if last - first < 2 { first..=last } else { first + 1..=last - 1 };
+    Ok(hosts.map(Ipv4Addr::from).collect())
+}
+
+// Handshake with one address; `None` if nothing answers
+async fn probe(addr: SocketAddr, timeout: Duration) -> Option<DiscoveredController> {
+    let config = ClientConfig { timeout, retry_count: 0, ..ClientConfig::default() };
+    match HsesClient::connect(&addr.to_string(), config).await {
+        Ok(client) => Some(DiscoveredController { addr, info: client.info() }),
+        Err(e) => {
+            log::trace!("No controller at {addr}: {e}");
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_subnet_hosts() {
+        let hosts = subnet_hosts(Ipv4Addr::new(192, 168, 0, 77), 24).unwrap();
+        assert_eq!(hosts.len(), 254);
+        assert_eq!(hosts[0], Ipv4Addr::new(192, 168, 0, 1));
+        assert_eq!(hosts[253], Ipv4Addr::new(192, 168, 0, 254));
+
+        let point_to_point = subnet_hosts(Ipv4Addr::new(10, 0, 0, 4), 31).unwrap();
+        assert_eq!(point_to_point, [Ipv4Addr::new(10, 0, 0, 4), Ipv4Addr::new(10, 0, 0, 5)]);
+        assert_eq!(
+            subnet_hosts(Ipv4Addr::new(10, 0, 0, 9), 32).unwrap(),
+            [Ipv4Addr::new(10, 0, 0, 9)]
+        );
+        assert_eq!(subnet_hosts(Ipv4Addr::new(10, 1, 0, 0), 16).unwrap().len(), 65534);
+
+        assert!(subnet_hosts(Ipv4Addr::new(10, 0, 0, 0), 8).is_err());
+        assert!(subnet_hosts(Ipv4Addr::new(10, 0, 0, 0), 33).is_err());
+    }
+}
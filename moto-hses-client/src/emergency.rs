@@ -0,0 +1,94 @@
+//! Emergency hold
+//!
+//! [`HsesClient::emergency_hold`](crate::HsesClient::emergency_hold) holds the robot, checks
+//! that it stopped and optionally turns the servo power off, all within one deadline. Its
+//! commands go through a priority lane with its own socket, so they are not delayed by
+//! requests in flight. A [`PriorityLane`] gives access to the lane without the client itself,
+//! e.g. while a [`SharedHsesClient`](crate::SharedHsesClient) is busy with a long operation.
+//!
+//! This is no substitute for the emergency stop circuit of the robot system.
+
+use std::time::Duration;
+
+use moto_hses_proto::Status;
+
+use crate::types::{ClientError, HsesClient};
+
+/// Options for [`HsesClient::emergency_hold`](crate::HsesClient::emergency_hold)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EmergencyHoldOptions {
+    /// Turn the servo power off once the robot has stopped (default: false)
+    pub servo_off: bool,
+    /// Time within which the routine returns, whatever the controller does (default: 500 ms)
+    pub deadline: Duration,
+}
+
+impl Default for EmergencyHoldOptions {
+    fn default() -> Self {
+        Self { servo_off: false, deadline: Duration::from_millis(500) }
+    }
+}
+
+/// Result of [`HsesClient::emergency_hold`](crate::HsesClient::emergency_hold)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EmergencyHoldReport {
+    /// Whether the status reported the robot as not running before the deadline
+    pub stopped: bool,
+    /// Whether the servo power was turned off
+    pub servo_off: bool,
+    /// Last status read, if any was read before the deadline
+    pub status: Option<Status>,
+    /// Time from the hold request until the routine returned
+    pub elapsed: Duration,
+}
+
+impl EmergencyHoldReport {
+    /// Whether the last status read reported an alarm
+    #[must_use]
+    pub fn has_alarm(&self) -> bool {
+        self.status.as_ref().is_some_and(Status::has_alarm)
+    }
+}
+
+/// Handle to the priority lane of a client
+///
+/// Clones of the handle share the client's connection; emergency commands sent through
+/// them do not wait for other operations on the client to finish.
+pub struct PriorityLane {
+    client: HsesClient,
+}
+
+impl PriorityLane {
+    pub(crate) const fn new(client: HsesClient) -> Self {
+        Self { client }
+    }
+
+    // Client sharing the connection of the lane
+    pub(crate) const fn client(&self) -> &HsesClient {
+        &self.client
+    }
+
+    /// Run [`HsesClient::emergency_hold`](crate::HsesClient::emergency_hold) on the client
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the hold request is not acknowledged before the deadline
+    pub async fn emergency_hold(
+        &self,
+        options: &EmergencyHoldOptions,
+    ) -> Result<EmergencyHoldReport, ClientError> {
+        self.client.emergency_hold(options).await
+    }
+}
+
+impl Clone for PriorityLane {
+    fn clone(&self) -> Self {
+        Self::new(self.client.share())
+    }
+}
+
+impl std::fmt::Debug for PriorityLane {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PriorityLane").field("remote_addr", &self.client.inner.remote_addr).finish()
+    }
+}
@@ -0,0 +1,297 @@
+//! Tool and user frame transforms
+//!
+//! The controller reports Cartesian positions as the pose of the tool control point in
+//! the base frame. [`Frames`] holds the tool and user frames, read with
+//! [`HsesClient::read_frames`](crate::HsesClient::read_frames), and re-expresses such
+//! positions for another tool and in a user frame, e.g. to hand the gripper pose to a
+//! vision system calibrated on a workpiece table, or converts positions given in a user
+//! frame back to the base frame.
+//!
+//! The robot frame is taken as the base frame, which holds unless the robot is mounted on
+//! a traveling axis.
+
+use std::collections::BTreeMap;
+
+use moto_hses_proto::frame_file::{TOOL_FILE, USER_FRAME_FILE};
+use moto_hses_proto::{CartesianPosition, FrameFile, FramePose, ProtocolError};
+
+use crate::types::{ClientError, HsesClient};
+
+/// Frame a Cartesian position is given in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReferenceFrame {
+    Base,
+    /// User frame 1-63
+    User(u8),
+}
+
+/// Tool and user frames of a controller
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Frames {
+    tools: BTreeMap<u8, FramePose>,
+    user_frames: BTreeMap<u8, FramePose>,
+}
+
+impl Frames {
+    /// Frames of a tool file and a user frame file
+    #[must_use]
+    pub fn new(tools: &FrameFile, user_frames: &FrameFile) -> Self {
+        Self { tools: tools.iter().collect(), user_frames: user_frames.iter().collect() }
+    }
+
+    /// Set tool `number` to `pose`, the control point in the flange frame
+    #[must_use]
+    pub fn with_tool(mut self, number: u8, pose: FramePose) -> Self {
+        self.tools.insert(number, pose);
+        self
+    }
+
+    /// Set user frame `number` to `pose`, the frame in the base frame
+    #[must_use]
+    pub fn with_user_frame(mut self, number: u8, pose: FramePose) -> Self {
+        self.user_frames.insert(number, pose);
+        self
+    }
+
+    /// Pose of tool `number`
+    #[must_use]
+    pub fn tool(&self, number: u8) -> Option<FramePose> {
+        self.tools.get(&number).copied()
+    }
+
+    /// Pose of user frame `number`
+    #[must_use]
+    pub fn user_frame(&self, number: u8) -> Option<FramePose> {
+        self.user_frames.get(&number).copied()
+    }
+
+    /// Express `position`, a base frame position of its tool, for tool `tool_no` in `frame`
+    ///
+    /// The result has the tool number `tool_no` and, in a user frame, its user coordinate
+    /// number; the configuration is kept.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ProtocolError::FileError`] if the tool of the position, tool `tool_no` or
+    /// the user frame is not defined
+    pub fn express(
+        &self,
+        position: &CartesianPosition,
+        tool_no: u8,
+        frame: ReferenceFrame,
+    ) -> Result<CartesianPosition, ClientError> {
+        let flange = Transform::from_position(position)
+            .compose(&self.tool_transform(position.tool_no)?.inverse());
+        let tool_point = flange.compose(&self.tool_transform(tool_no)?);
+        let (pose, user_coord_no) = match frame {
+            ReferenceFrame::Base => (tool_point, position.user_coord_no),
+            ReferenceFrame::User(number) => {
+                (self.user_transform(number)?.inverse().compose(&tool_point), number)
+            }
+        };
+        let mut result = pose.to_position(position);
+        result.tool_no = tool_no;
+        result.user_coord_no = user_coord_no;
+        Ok(result)
+    }
+
+    /// Base frame position of `position`, given in `frame`
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ProtocolError::FileError`] if the user frame is not defined
+    pub fn to_base(
+        &self,
+        position: &CartesianPosition,
+        frame: ReferenceFrame,
+    ) -> Result<CartesianPosition, ClientError> {
+        match frame {
+            ReferenceFrame::Base => Ok(position.clone()),
+            ReferenceFrame::User(number) => {
+                let pose =
+                    self.user_transform(number)?.compose(&Transform::from_position(position));
+                Ok(pose.to_position(position))
+            }
+        }
+    }
+
+    fn tool_transform(&self, number: u8) -> Result<Transform, ClientError> {
+        self.tool(number)
+            .map(|pose| Transform::from_pose(&pose))
+            .ok_or_else(|| ProtocolError::FileError(format!("tool {number} is not defined")).into())
+    }
+
+    fn user_transform(&self, number: u8) -> Result<Transform, ClientError> {
+        self.user_frame(number).map(|pose| Transform::from_pose(&pose)).ok_or_else(|| {
+            ProtocolError::FileError(format!("user frame {number} is not defined")).into()
+        })
+    }
+}
+
+impl HsesClient {
+    /// Read the tool and user frames
+    ///
+    /// HSES has no command reading frame data, so the frames are taken from the tool file
+    /// (`TOOL.CND`) and the user frame file (`UFRAME.CND`) received over the file control
+    /// port.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a file cannot be received or parsed
+    pub async fn read_frames(&self) -> Result<Frames, ClientError> {
+        let tools = FrameFile::parse(&self.receive_file(TOOL_FILE).await?)?;
+        let user_frames = FrameFile::parse(&self.receive_file(USER_FRAME_FILE).await?)?;
+        Ok(Frames::new(&tools, &user_frames))
+    }
+}
+
+// Rigid transform: rotation matrix and translation [mm]
+#[derive(Debug, Clone, Copy)]
+struct Transform {
+    rotation: [[f64; 3]; 3],
+    translation: [f64; 3],
+}
+
+impl Transform {
+    // Rotation Rz * Ry * Rx of angles in degrees
+    fn new(translation: [f64; 3], [rx, ry, rz]: [f64; 3]) -> Self {
+        let (sx, cx) = rx.to_radians().sin_cos();
+        let (sy, cy) = ry.to_radians().sin_cos();
+        let (sz, cz) = rz.to_radians().sin_cos();
+        let rotation = [
+            [cz * cy, (cz * sy).mul_add(sx, -sz * cx), (cz * sy).mul_add(cx, sz * sx)],
+            [sz * cy, (sz * sy).mul_add(sx, cz * cx), (sz * sy).mul_add(cx, -cz * sx)],
+            [-sy, cy * sx, cy * cx],
+        ];
+        Self { rotation, translation }
+    }
+
+    fn from_pose(pose: &FramePose) -> Self {
+        Self::new([pose.x, pose.y, pose.z], [pose.rx, pose.ry, pose.rz])
+    }
+
+    fn from_position(position: &CartesianPosition) -> Self {
+        let [x, y, z, rx, ry, rz] =
+            [position.x, position.y, position.z, position.rx, position.ry, position.rz]
+                .map(f64::from);
+        Self::new([x, y, z], [rx, ry, rz])
+    }
+
+    // `self` followed by `other` given in the frame of `self`
+    fn compose(&self, other: &Self) -> Self {
+        let rotation = std::array::from_fn(|row| {
+            std::array::from_fn(|column| {
+                (0..3).map(|k| self.rotation[row][k] * other.rotation[k][column]).sum()
+            })
+        });
+        let moved = self.rotate(other.translation);
+        let translation = std::array::from_fn(|row| self.translation[row] + moved[row]);
+        Self { rotation, translation }
+    }
+
+    fn inverse(&self) -> Self {
+        let rotation =
+            std::array::from_fn(|row| std::array::from_fn(|column| self.rotation[column][row]));
+        let inverse = Self { rotation, translation: [0.0; 3] };
+        let moved = inverse.rotate(self.translation);
+        Self { rotation, translation: moved.map(|value| -value) }
+    }
+
+    fn rotate(&self, vector: [f64; 3]) -> [f64; 3] {
+        self.rotation.map(|row| (0..3).map(|k| row[k] * vector[k]).sum())
+    }
+
+    // Position at this pose, with the tool, user frame and configuration of `template`
+    #[allow(clippy::cast_possible_truncation)] // Positions are sent as f32 anyway
+    fn to_position(self, template: &CartesianPosition) -> CartesianPosition {
+        let r = &self.rotation;
+        let ry = (-r[2][0]).atan2(r[0][0].hypot(r[1][0]));
+        // At Ry = +-90 deg only Rz - Rx (or Rz + Rx) is defined; Rx is taken as 0
+        let (rx, rz) = if r[0][0].hypot(r[1][0]) < 1e-9 {
+            (0.0, (-r[0][1]).atan2(r[1][1]))
+        } else {
+            (r[2][1].atan2(r[2][2]), r[1][0].atan2(r[0][0]))
+        };
+        let mut position = template.clone();
+        let [x, y, z] = self.translation;
+        position.x = x as f32;
+        position.y = y as f32;
+        position.z = z as f32;
+        position.rx = rx.to_degrees() as f32;
+        position.ry = ry.to_degrees() as f32;
+        position.rz = rz.to_degrees() as f32;
+        position
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use moto_hses_proto::payload::position::{Configuration, ExtendedConfiguration};
+
+    fn position(values: [f32; 6], tool_no: u8) -> CartesianPosition {
+        let [x, y, z, rx, ry, rz] = values;
+        CartesianPosition::new(
+            x,
+            y,
+            z,
+            rx,
+            ry,
+            rz,
+            tool_no,
+            0,
+            Configuration::from_raw(4),
+            ExtendedConfiguration::from_raw(0),
+        )
+    }
+
+    fn assert_close(actual: &CartesianPosition, expected: [f32; 6]) {
+        let values = [actual.x, actual.y, actual.z, actual.rx, actual.ry, actual.rz];
+        for (index, (a, e)) in values.into_iter().zip(expected).enumerate() {
+            // Angles of -180 and 180 deg are the same
+            let difference =
+                if index < 3 { a - e } else { (a - e + 180.0).rem_euclid(360.0) - 180.0 };
+            assert!(difference.abs() < 1e-3, "{values:?} != {expected:?}");
+        }
+    }
+
+    fn frames() -> Frames {
+        let pose = |x, y, z, rx, ry, rz| FramePose { x, y, z, rx, ry, rz };
+        Frames::default()
+            .with_tool(0, FramePose::default())
+            .with_tool(1, pose(0.0, 0.0, 200.0, 0.0, 0.0, 0.0))
+            .with_user_frame(1, pose(1000.0, 500.0, 0.0, 0.0, 0.0, 90.0))
+    }
+
+    #[test]
+    fn test_express_for_tool_and_user_frame() {
+        let frames = frames();
+        // Flange at (800, 0, 600) pointing down (Rx 180); tool 1 reaches 200 mm further down
+        let flange = position([800.0, 0.0, 600.0, 180.0, 0.0, 0.0], 0);
+        let tool = frames.express(&flange, 1, ReferenceFrame::Base).unwrap();
+        assert_close(&tool, [800.0, 0.0, 400.0, 180.0, 0.0, 0.0]);
+        assert_eq!((tool.tool_no, tool.configuration.to_raw()), (1, 4));
+
+        // The user frame is at (1000, 500) turned by 90 deg: base -X is user Y
+        let user = frames.express(&tool, 1, ReferenceFrame::User(1)).unwrap();
+        assert_close(&user, [-500.0, 200.0, 400.0, 180.0, 0.0, -90.0]);
+        assert_eq!(user.user_coord_no, 1);
+
+        let base = frames.to_base(&user, ReferenceFrame::User(1)).unwrap();
+        assert_close(&base, [800.0, 0.0, 400.0, 180.0, 0.0, 0.0]);
+
+        assert!(frames.express(&flange, 2, ReferenceFrame::Base).is_err());
+        assert!(frames.express(&flange, 0, ReferenceFrame::User(2)).is_err());
+    }
+
+    #[test]
+    fn test_transform_angles_round_trip() {
+        for angles in [[10.0, 20.0, 30.0], [-170.0, 45.0, 120.0], [0.0, -89.0, -45.0]] {
+            let [rx, ry, rz] = angles;
+            let original = position([1.0, 2.0, 3.0, rx, ry, rz], 0);
+            let pose = Transform::from_position(&original).to_position(&original);
+            assert_close(&pose, [1.0, 2.0, 3.0, rx, ry, rz]);
+        }
+    }
+}
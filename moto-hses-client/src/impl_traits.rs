@@ -1,11 +1,25 @@
 //! Implementation of `HsesClientOps` trait for `HsesClient`
 
+use crate::emergency::{EmergencyHoldOptions, EmergencyHoldReport};
+use crate::job_diff::JobDiff;
+use crate::jog::{JogDeadman, JogDirection, JogReport};
+use crate::recipe::{Recipe, VariableValue};
+use crate::robot_snapshot::RobotSnapshot;
+use crate::snapshot::{VariableProfile, VariableSnapshot};
 use crate::traits::HsesClientOps;
-use crate::types::{ClientError, HsesClient};
+use crate::trajectory::{TrajectoryControl, TrajectoryOptions, TrajectoryReport};
+use crate::types::{
+    CartesianDelta, ClientError, DeleteFilesOptions, FileDeleteResult, HsesClient, IncrementalMove,
+    IncrementalMoveOptions, JobRunReport, ResponseMeta,
+};
+use crate::watch::VariableRef;
 use moto_hses_proto::commands::JobSelectType;
 use moto_hses_proto::{
-    Alarm, AlarmAttribute, CycleMode, ExecutingJobInfo, Position, Status, StatusData1, StatusData2,
+    Alarm, AlarmAttribute, AlarmCategory, BasePosition, CycleMode, ExecutingJobInfo, MoveFrame,
+    MoveSpeed, Position, SelectedJob, StationPosition, Status, StatusData1, StatusData2,
+    SystemInfo,
 };
+use std::time::Duration;
 
 #[async_trait::async_trait]
 impl HsesClientOps for HsesClient {
@@ -15,6 +29,14 @@ impl HsesClientOps for HsesClient {
         Self::read_status(self).await
     }
 
+    async fn read_status_with_meta(&self) -> Result<(Status, ResponseMeta), ClientError> {
+        Self::read_status_with_meta(self).await
+    }
+
+    async fn read_system_info(&self, instance: u16) -> Result<SystemInfo, ClientError> {
+        Self::read_system_info(self, instance).await
+    }
+
     async fn read_status_data1(&self) -> Result<StatusData1, ClientError> {
         Self::read_status_data1(self).await
     }
@@ -29,6 +51,49 @@ impl HsesClientOps for HsesClient {
         Self::read_position(self, control_group).await
     }
 
+    async fn read_snapshot(&self, control_groups: &[u8]) -> Result<RobotSnapshot, ClientError> {
+        Self::read_snapshot(self, control_groups).await
+    }
+
+    async fn move_incremental(
+        &self,
+        robot: u8,
+        delta: CartesianDelta,
+        options: &IncrementalMoveOptions,
+    ) -> Result<IncrementalMove, ClientError> {
+        Self::move_incremental(self, robot, delta, options).await
+    }
+
+    async fn move_pulse(
+        &self,
+        robot: u8,
+        joints: Vec<i32>,
+        speed: MoveSpeed,
+    ) -> Result<(), ClientError> {
+        Self::move_pulse(self, robot, joints, speed).await
+    }
+
+    async fn jog(
+        &self,
+        robot: u8,
+        frame: MoveFrame,
+        direction: JogDirection,
+        speed: f32,
+        deadman: &JogDeadman,
+    ) -> Result<JogReport, ClientError> {
+        Self::jog(self, robot, frame, direction, speed, deadman).await
+    }
+
+    async fn play_trajectory(
+        &self,
+        robot: u8,
+        points: &[Position],
+        options: &TrajectoryOptions,
+        control: &TrajectoryControl,
+    ) -> Result<TrajectoryReport, ClientError> {
+        Self::play_trajectory(self, robot, points, options, control).await
+    }
+
     // ========== Alarm Operations ==========
 
     async fn read_alarm_data(
@@ -47,6 +112,15 @@ impl HsesClientOps for HsesClient {
         Self::read_alarm_history(self, instance, attribute).await
     }
 
+    async fn read_alarm_history_range(
+        &self,
+        category: AlarmCategory,
+        start: u16,
+        count: u16,
+    ) -> Result<Vec<Alarm>, ClientError> {
+        Self::read_alarm_history_range(self, category, start, count).await
+    }
+
     async fn reset_alarm(&self) -> Result<(), ClientError> {
         Self::reset_alarm(self).await
     }
@@ -69,6 +143,13 @@ impl HsesClientOps for HsesClient {
         Self::set_hlock(self, enabled).await
     }
 
+    async fn emergency_hold(
+        &self,
+        options: &EmergencyHoldOptions,
+    ) -> Result<EmergencyHoldReport, ClientError> {
+        Self::emergency_hold(self, options).await
+    }
+
     async fn set_cycle_mode(&self, mode: CycleMode) -> Result<(), ClientError> {
         Self::set_cycle_mode(self, mode).await
     }
@@ -88,6 +169,20 @@ impl HsesClientOps for HsesClient {
         Self::select_job(self, select_type, job_name, line_number).await
     }
 
+    async fn read_selected_job(&self) -> Result<SelectedJob, ClientError> {
+        Self::read_selected_job(self).await
+    }
+
+    async fn run_job_and_wait(
+        &self,
+        name: &str,
+        line: u32,
+        start_timeout: Duration,
+        run_timeout: Duration,
+    ) -> Result<JobRunReport, ClientError> {
+        Self::run_job_and_wait(self, name, line, start_timeout, run_timeout).await
+    }
+
     async fn read_executing_job_info(
         &self,
         task_type: u16,
@@ -145,6 +240,42 @@ impl HsesClientOps for HsesClient {
         Self::write_string(self, index, value).await
     }
 
+    async fn read_position_variable(&self, index: u16) -> Result<Position, ClientError> {
+        Self::read_position_variable(self, index).await
+    }
+
+    async fn write_position_variable(
+        &self,
+        index: u16,
+        value: Position,
+    ) -> Result<(), ClientError> {
+        Self::write_position_variable(self, index, value).await
+    }
+
+    async fn read_base_position_variable(&self, index: u16) -> Result<BasePosition, ClientError> {
+        Self::read_base_position_variable(self, index).await
+    }
+
+    async fn write_base_position_variable(
+        &self,
+        index: u16,
+        value: BasePosition,
+    ) -> Result<(), ClientError> {
+        Self::write_base_position_variable(self, index, value).await
+    }
+
+    async fn read_station_variable(&self, index: u16) -> Result<StationPosition, ClientError> {
+        Self::read_station_variable(self, index).await
+    }
+
+    async fn write_station_variable(
+        &self,
+        index: u16,
+        value: StationPosition,
+    ) -> Result<(), ClientError> {
+        Self::write_station_variable(self, index, value).await
+    }
+
     // ========== Multiple Variable Operations ==========
 
     async fn read_multiple_u8(
@@ -227,6 +358,35 @@ impl HsesClientOps for HsesClient {
         Self::write_multiple_strings(self, start_variable_number, values).await
     }
 
+    async fn read_variables(
+        &self,
+        variables: &[VariableRef],
+    ) -> Result<Vec<VariableValue>, ClientError> {
+        Self::read_variables(self, variables).await
+    }
+
+    async fn snapshot_variables(
+        &self,
+        profile: &VariableProfile,
+    ) -> Result<VariableSnapshot, ClientError> {
+        Self::snapshot_variables(self, profile).await
+    }
+
+    async fn restore_snapshot(&self, snapshot: &VariableSnapshot) -> Result<(), ClientError> {
+        Self::restore_snapshot(self, snapshot).await
+    }
+
+    async fn apply_recipe(&self, recipe: &Recipe) -> Result<(), ClientError> {
+        Self::apply_recipe(self, recipe).await
+    }
+
+    async fn detect_recipe<'a>(
+        &self,
+        recipes: &'a [Recipe],
+    ) -> Result<Option<&'a Recipe>, ClientError> {
+        Self::detect_recipe(self, recipes).await
+    }
+
     // ========== I/O Operations ==========
 
     async fn read_io(&self, io_number: u16) -> Result<u8, ClientError> {
@@ -237,6 +397,23 @@ impl HsesClientOps for HsesClient {
         Self::write_io(self, io_number, value).await
     }
 
+    async fn read_io_bit(&self, address: u32) -> Result<bool, ClientError> {
+        Self::read_io_bit(self, address).await
+    }
+
+    async fn write_io_bit(&self, address: u32, on: bool) -> Result<(), ClientError> {
+        Self::write_io_bit(self, address, on).await
+    }
+
+    async fn pulse_io(
+        &self,
+        io_number: u16,
+        value: u8,
+        duration: Duration,
+    ) -> Result<(), ClientError> {
+        Self::pulse_io(self, io_number, value, duration).await
+    }
+
     async fn read_multiple_io(
         &self,
         start_io_number: u16,
@@ -296,4 +473,20 @@ impl HsesClientOps for HsesClient {
     async fn delete_file(&self, filename: &str) -> Result<(), ClientError> {
         Self::delete_file(self, filename).await
     }
+
+    async fn diff_job(&self, name: &str, local_source: &str) -> Result<JobDiff, ClientError> {
+        Self::diff_job(self, name, local_source).await
+    }
+
+    async fn delete_files(
+        &self,
+        pattern: &str,
+        options: DeleteFilesOptions,
+    ) -> Result<Vec<FileDeleteResult>, ClientError> {
+        Self::delete_files(self, pattern, options).await
+    }
+
+    async fn send_file_verified(&self, filename: &str, content: &[u8]) -> Result<(), ClientError> {
+        Self::send_file_verified(self, filename, content).await
+    }
 }
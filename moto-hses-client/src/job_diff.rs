@@ -0,0 +1,221 @@
+//! Line-based comparison of job files
+//!
+//! Used by [`HsesClient::diff_job`](crate::HsesClient::diff_job) to compare the job stored
+//! on the controller with a local source.
+
+/// A single difference between the local source and the controller job
+///
+/// Line numbers are 1-based.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum JobDiffLine {
+    /// Line present only in the controller job
+    Added { controller_line: usize, text: String },
+    /// Line present only in the local source
+    Removed { local_line: usize, text: String },
+    /// Line that differs between the local source and the controller job
+    Changed { local_line: usize, controller_line: usize, local: String, controller: String },
+}
+
+/// Result of comparing a controller job with a local source
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct JobDiff {
+    /// Differences in order of appearance
+    pub lines: Vec<JobDiffLine>,
+}
+
+impl JobDiff {
+    /// Compare two job sources line by line
+    ///
+    /// Line endings (CR LF, LF or CR) and a trailing line break are normalized before
+    /// comparing, so a job saved by the controller matches a local source with Unix line
+    /// endings.
+    #[must_use]
+    pub fn compute(local_source: &str, controller_source: &str) -> Self {
+        let local = normalized_lines(local_source);
+        let controller = normalized_lines(controller_source);
+
+        // Skip the common prefix and suffix to keep the LCS table small
+        let prefix = local.iter().zip(&controller).take_while(|(l, c)| l == c).count();
+        let suffix = local[prefix..]
+            .iter()
+            .rev()
+            .zip(controller[prefix..].iter().rev())
+            .take_while(|(l, c)| l == c)
+            .count();
+        let local_mid = &local[prefix..local.len() - suffix];
+        let controller_mid = &controller[prefix..controller.len() - suffix];
+
+        let mut lines = Vec::new();
+        let mut removed: Vec<(usize, &str)> = Vec::new();
+        let mut added: Vec<(usize, &str)> = Vec::new();
+        for op in lcs_edit_script(local_mid, controller_mid) {
+            match op {
+                EditOp::Keep => flush_changes(&mut lines, &mut removed, &mut added),
+                EditOp::Remove(i) => removed.push((prefix + i + 1, &local_mid[i])),
+                EditOp::Add(j) => added.push((prefix + j + 1, &controller_mid[j])),
+            }
+        }
+        flush_changes(&mut lines, &mut removed, &mut added);
+
+        Self { lines }
+    }
+
+    /// Whether the local source and the controller job are identical
+    #[must_use]
+    pub const fn is_identical(&self) -> bool {
+        self.lines.is_empty()
+    }
+
+    /// Number of lines present only in the controller job
+    #[must_use]
+    pub fn added_count(&self) -> usize {
+        self.lines.iter().filter(|line| matches!(line, JobDiffLine::Added { .. })).count()
+    }
+
+    /// Number of lines present only in the local source
+    #[must_use]
+    pub fn removed_count(&self) -> usize {
+        self.lines.iter().filter(|line| matches!(line, JobDiffLine::Removed { .. })).count()
+    }
+
+    /// Number of lines that differ between the local source and the controller job
+    #[must_use]
+    pub fn changed_count(&self) -> usize {
+        self.lines.iter().filter(|line| matches!(line, JobDiffLine::Changed { .. })).count()
+    }
+}
+
+enum EditOp {
+    Keep,
+    Remove(usize),
+    Add(usize),
+}
+
+fn normalized_lines(source: &str) -> Vec<String> {
+    let source = source.replace("\r\n", "\n").replace('\r', "\n");
+    let source = source.strip_suffix('\n').unwrap_or(&source);
+    if source.is_empty() {
+        return Vec::new();
+    }
+    source.split('\n').map(str::to_string).collect()
+}
+
+// Edit script turning `local` into `controller` based on the longest common subsequence
+fn lcs_edit_script(local: &[String], controller: &[String]) -> Vec<EditOp> {
+    let (local_len, controller_len) = (local.len(), controller.len());
+    // table[i][j] = LCS length of local[i..] and controller[j..]
+    let mut table = vec![vec![0u32; controller_len + 1]; local_len + 1];
+    for i in (0..local_len).rev() {
+        for j in (0..controller_len).rev() {
+            table[i][j] = if local[i] == controller[j] {
+                table[i + 1][j + 1] + 1
+            } else {
+                table[i + 1][j].max(table[i][j + 1])
+            };
+        }
+    }
+
+    let mut ops = Vec::with_capacity(local_len + controller_len);
+    let (mut i, mut j) = (0, 0);
+    while i < local_len || j < controller_len {
+        match (local.get(i), controller.get(j)) {
+            (Some(local_text), Some(controller_text)) if local_text == controller_text => {
+                ops.push(EditOp::Keep);
+                i += 1;
+                j += 1;
+            }
+            (_, Some(_)) if i == local_len || table[i][j + 1] >= table[i + 1][j] => {
+                ops.push(EditOp::Add(j));
+                j += 1;
+            }
+            _ => {
+                ops.push(EditOp::Remove(i));
+                i += 1;
+            }
+        }
+    }
+    ops
+}
+
+// Pair removed and added lines of one hunk as changes; the remainder stays removed/added
+fn flush_changes(
+    lines: &mut Vec<JobDiffLine>,
+    removed: &mut Vec<(usize, &str)>,
+    added: &mut Vec<(usize, &str)>,
+) {
+    let paired = removed.len().min(added.len());
+    for (&(local_line, local), &(controller_line, controller)) in
+        removed.iter().zip(added.iter()).take(paired)
+    {
+        lines.push(JobDiffLine::Changed {
+            local_line,
+            controller_line,
+            local: local.to_string(),
+            controller: controller.to_string(),
+        });
+    }
+    for &(local_line, text) in &removed[paired..] {
+        lines.push(JobDiffLine::Removed { local_line, text: text.to_string() });
+    }
+    for &(controller_line, text) in &added[paired..] {
+        lines.push(JobDiffLine::Added { controller_line, text: text.to_string() });
+    }
+    removed.clear();
+    added.clear();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const JOB: &str = "/JOB\r\n//NAME TEST\r\n//INST\r\nNOP\r\nMOVJ VJ=10.00\r\nEND\r\n";
+
+    #[test]
+    fn test_identical_with_different_line_endings() {
+        let local = JOB.replace("\r\n", "\n");
+        let diff = JobDiff::compute(&local, JOB);
+        assert!(diff.is_identical());
+    }
+
+    #[test]
+    fn test_changed_line() {
+        let local = JOB.replace("VJ=10.00", "VJ=50.00");
+        let diff = JobDiff::compute(&local, JOB);
+        assert_eq!(
+            diff.lines,
+            vec![JobDiffLine::Changed {
+                local_line: 5,
+                controller_line: 5,
+                local: "MOVJ VJ=50.00".to_string(),
+                controller: "MOVJ VJ=10.00".to_string(),
+            }]
+        );
+        assert_eq!(diff.changed_count(), 1);
+    }
+
+    #[test]
+    fn test_added_and_removed_lines() {
+        let local = "/JOB\nNOP\nTIMER T=1.00\nEND\n";
+        let controller = "/JOB\nNOP\nEND\nNOP\n";
+        let diff = JobDiff::compute(local, controller);
+        assert_eq!(
+            diff.lines,
+            vec![
+                JobDiffLine::Removed { local_line: 3, text: "TIMER T=1.00".to_string() },
+                JobDiffLine::Added { controller_line: 4, text: "NOP".to_string() },
+            ]
+        );
+        assert_eq!(diff.added_count(), 1);
+        assert_eq!(diff.removed_count(), 1);
+    }
+
+    #[test]
+    fn test_empty_sources() {
+        assert!(JobDiff::compute("", "").is_identical());
+        let diff = JobDiff::compute("", "NOP\r\n");
+        assert_eq!(
+            diff.lines,
+            vec![JobDiffLine::Added { controller_line: 1, text: "NOP".to_string() }]
+        );
+    }
+}
@@ -0,0 +1,189 @@
+//! Jogging with a deadman token
+//!
+//! [`HsesClient::jog`](crate::HsesClient::jog) moves the robot in small increments for as
+//! long as a [`JogDeadman`] is held and kept alive, like the enable switch of a teach
+//! pendant.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+use tokio::sync::watch;
+
+use crate::types::CartesianDelta;
+
+/// Cartesian axis to jog along or around
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JogAxis {
+    X,
+    Y,
+    Z,
+    Rx,
+    Ry,
+    Rz,
+}
+
+impl JogAxis {
+    /// Whether jogging this axis changes the posture rather than the position
+    #[must_use]
+    pub const fn is_rotation(self) -> bool {
+        matches!(self, Self::Rx | Self::Ry | Self::Rz)
+    }
+}
+
+/// Axis and sense of a jog
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct JogDirection {
+    pub axis: JogAxis,
+    /// Jog towards negative coordinates
+    pub negative: bool,
+}
+
+impl JogDirection {
+    #[must_use]
+    pub const fn positive(axis: JogAxis) -> Self {
+        Self { axis, negative: false }
+    }
+
+    #[must_use]
+    pub const fn negative(axis: JogAxis) -> Self {
+        Self { axis, negative: true }
+    }
+
+    /// Delta of `amount` (mm or deg) in this direction
+    #[must_use]
+    pub fn delta(self, amount: f32) -> CartesianDelta {
+        let amount = if self.negative { -amount } else { amount };
+        let mut delta = CartesianDelta::default();
+        match self.axis {
+            JogAxis::X => delta.x = amount,
+            JogAxis::Y => delta.y = amount,
+            JogAxis::Z => delta.z = amount,
+            JogAxis::Rx => delta.rx = amount,
+            JogAxis::Ry => delta.ry = amount,
+            JogAxis::Rz => delta.rz = amount,
+        }
+        delta
+    }
+}
+
+/// Enable token for [`HsesClient::jog`](crate::HsesClient::jog)
+///
+/// The jog continues while the token is held: it stops as soon as [`release`](Self::release)
+/// is called, or when [`keepalive`](Self::keepalive) has not been called within the timeout,
+/// e.g. because the user interface driving the jog froze. Clones share the same state, so
+/// one clone can be passed to the jog while another is kept by the user interface.
+#[derive(Debug, Clone)]
+pub struct JogDeadman {
+    inner: Arc<DeadmanState>,
+}
+
+#[derive(Debug)]
+struct DeadmanState {
+    released: watch::Sender<bool>,
+    created_at: Instant,
+    // Time of the last keepalive, in milliseconds since `created_at`
+    last_keepalive_ms: AtomicU64,
+    timeout: Duration,
+}
+
+impl JogDeadman {
+    /// Create a held token that expires `timeout` after the last keepalive
+    #[must_use]
+    pub fn new(timeout: Duration) -> Self {
+        Self {
+            inner: Arc::new(DeadmanState {
+                released: watch::Sender::new(false),
+                created_at: Instant::now(),
+                last_keepalive_ms: AtomicU64::new(0),
+                timeout,
+            }),
+        }
+    }
+
+    /// Signal that the operator still holds the token
+    pub fn keepalive(&self) {
+        self.inner.last_keepalive_ms.store(self.elapsed_ms(), Ordering::Relaxed);
+    }
+
+    /// Release the token, stopping any jog using it
+    pub fn release(&self) {
+        self.inner.released.send_replace(true);
+    }
+
+    #[must_use]
+    pub fn is_released(&self) -> bool {
+        *self.inner.released.borrow()
+    }
+
+    /// Whether the keepalive timeout has passed since the last keepalive
+    #[must_use]
+    pub fn is_expired(&self) -> bool {
+        let last_keepalive = self.inner.last_keepalive_ms.load(Ordering::Relaxed);
+        Duration::from_millis(self.elapsed_ms().saturating_sub(last_keepalive)) > self.inner.timeout
+    }
+
+    // Complete once the token is released
+    pub(crate) async fn released(&self) {
+        let mut receiver = self.inner.released.subscribe();
+        // The sender lives as long as `self`, so waiting cannot fail
+        let _ = receiver.wait_for(|released| *released).await;
+    }
+
+    fn elapsed_ms(&self) -> u64 {
+        u64::try_from(self.inner.created_at.elapsed().as_millis()).unwrap_or(u64::MAX)
+    }
+}
+
+/// Why a jog stopped
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JogStopReason {
+    /// The deadman token was released
+    Released,
+    /// The deadman token was not kept alive
+    KeepaliveTimeout,
+    /// The next increment would have left the configured workspace limits
+    WorkspaceLimit,
+}
+
+/// Result of [`HsesClient::jog`](crate::HsesClient::jog)
+#[derive(Debug, Clone, PartialEq)]
+pub struct JogReport {
+    /// Number of increments sent
+    pub steps: u32,
+    /// Total distance commanded [mm or deg]
+    pub distance: f32,
+    /// Time from the first increment until the jog stopped
+    pub duration: Duration,
+    pub stop_reason: JogStopReason,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_deadman_release_and_expiry() {
+        let deadman = JogDeadman::new(Duration::from_millis(50));
+        assert!(!deadman.is_released());
+        assert!(!deadman.is_expired());
+
+        tokio::time::sleep(Duration::from_millis(80)).await;
+        assert!(deadman.is_expired());
+        deadman.keepalive();
+        assert!(!deadman.is_expired());
+
+        let clone = deadman.clone();
+        let waiter = tokio::spawn(async move { clone.released().await });
+        deadman.release();
+        assert!(deadman.is_released());
+        assert!(tokio::time::timeout(Duration::from_secs(1), waiter).await.is_ok());
+    }
+
+    #[test]
+    fn test_jog_direction_delta() {
+        let delta = JogDirection::negative(JogAxis::Ry).delta(0.5);
+        assert_eq!(delta, CartesianDelta::rotation(0.0, -0.5, 0.0));
+        assert!(JogAxis::Ry.is_rotation());
+        assert!(!JogAxis::Z.is_rotation());
+    }
+}
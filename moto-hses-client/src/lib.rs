@@ -12,10 +12,19 @@
 //!
 //! Both implement the [`HsesClientOps`] trait, allowing generic code to work with either.
 //!
+//! The [`prelude`] brings the client, its trait and the commonly needed protocol types
+//! into scope.
+//!
+//! # Configuration
+//!
+//! [`HsesClient::config`] returns a copy of the [`ClientConfig`] of a client, and
+//! [`HsesClient::update_config`] changes it while the client runs. Earlier versions had a
+//! public `config` field instead; `client.config.timeout` is now `client.config().timeout`.
+//!
 //! # Example
 //!
 //! ```ignore
-//! use moto_hses_client::{HsesClient, SharedHsesClient, HsesClientOps};
+//! use moto_hses_client::prelude::*;
 //!
 //! #[tokio::main]
 //! async fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -45,18 +54,113 @@
 #[macro_use]
 extern crate log;
 
+// Paths used by the expansion of `hses_trace!` in other crates
+#[doc(hidden)]
+pub mod __private {
+    pub use ::log::debug;
+    pub use moto_hses_proto::trace::FrameDump;
+}
+
+/// Log a raw HSES frame at debug level with its decoded header fields
+///
+/// The frame is formatted by [`FrameDump`](moto_hses_proto::trace::FrameDump), only when
+/// debug logging is enabled for the calling module:
+///
+/// ```text
+/// Received response: HSES response, 36 bytes
+///   header:     division=robot ack=1 request_id=3 block=0x80000000 payload_size=4
+///   sub-header: service=0x8E (GetSingle) status=0x00 added_status=0x0000
+///   payload:    0000  64 00 00 00                                      d...
+/// ```
+///
+/// ```
+/// let frame = [0x59, 0x45, 0x52, 0x43];
+/// moto_hses_client::hses_trace!("Received", &frame);
+/// ```
+#[macro_export]
+macro_rules! hses_trace {
+    ($label:expr, $frame:expr) => {
+        $crate::__private::debug!("{}: {}", $label, $crate::__private::FrameDump::new($frame))
+    };
+}
+
+pub mod alarm_statistics;
+pub mod audit;
+pub mod capabilities;
+pub mod capture;
 pub mod connection;
 pub mod convenience;
+pub mod discovery;
+pub mod emergency;
+pub mod frames;
 mod impl_traits;
+pub mod job_diff;
+pub mod jog;
+pub mod loopback;
+pub mod prelude;
 pub mod protocol;
+pub mod recipe;
+pub mod robot_snapshot;
 pub mod shared;
+pub mod snapshot;
+pub mod status_watch;
+pub mod supervisor;
+mod task;
+pub mod teach_points;
+#[cfg(feature = "telemetry")]
+pub mod telemetry;
 pub mod traits;
+pub mod trajectory;
+pub mod transcript;
+pub mod transport;
 pub mod types;
+pub mod watch;
+pub mod workspace;
+pub mod writer_lock;
 
 // Re-export main types for convenience
+pub use alarm_statistics::AlarmStatistics;
+pub use audit::{AuditOptions, AuditRecord, AuditSink, FileAuditSink, LogAuditSink};
+pub use capabilities::Capabilities;
+pub use capture::{CapturedRequest, read_pcap};
+pub use connection::{LinkStats, RttStats};
+pub use discovery::{DiscoveredController, DiscoveryOptions, discover, subnet_hosts};
+pub use emergency::{EmergencyHoldOptions, EmergencyHoldReport, PriorityLane};
+pub use frames::{Frames, ReferenceFrame};
+pub use job_diff::{JobDiff, JobDiffLine};
+pub use jog::{JogAxis, JogDeadman, JogDirection, JogReport, JogStopReason};
+pub use loopback::LoopbackTransport;
+pub use recipe::{Recipe, RecipeAssignment, VariableValue};
+pub use robot_snapshot::RobotSnapshot;
 pub use shared::SharedHsesClient;
+pub use snapshot::{VariableProfile, VariableSnapshot};
+pub use status_watch::{StatusBit, StatusChange, read_status_bits, watch_status};
+pub use supervisor::{ConnectionEvent, ConnectionSupervisor, SupervisorOptions};
+pub use teach_points::{TeachPoint, TeachPointTable};
+#[cfg(feature = "telemetry")]
+pub use telemetry::{TelemetryLogger, TelemetryOptions, TelemetryStats};
 pub use traits::HsesClientOps;
-pub use types::{ClientConfig, ClientError, HsesClient};
+pub use trajectory::{TrajectoryControl, TrajectoryOptions, TrajectoryProgress, TrajectoryReport};
+pub use transcript::{
+    Divergence, ReplayGate, ReplayOptions, ReplayReport, ReplayWrites, Transcript, TranscriptEntry,
+};
+pub use transport::{ChannelTransport, Transport, UdpTransport};
+pub use types::{
+    AdaptiveTimeout, AttributeStrategy, CartesianDelta, ClientConfig, ClientError, DangerLevel,
+    DeleteFilesOptions, FileDeleteOutcome, FileDeleteResult, HsesClient, IncrementalMove,
+    IncrementalMoveLimits, IncrementalMoveOptions, JobRunReport, RequestLimits, ResponseHook,
+    ResponseMeta, WritePacing,
+};
+pub use watch::{VariableChange, VariableRef, watch_variables};
+pub use workspace::{AxisLimits, AxisRange, CartesianBounds, WorkspaceLimits};
+pub use writer_lock::{WriterLease, WriterLock, WriterLockOptions};
 
 // Re-export protocol types that are commonly used
-pub use moto_hses_proto::{Alarm, ExecutingJobInfo, HsesPayload, Position, Status, TextEncoding};
+pub use moto_hses_proto::{
+    Alarm, AlarmAttribute, AlarmCategory, AttributeDeserialize, BasePosition, BasePositionType,
+    CartesianPosition, Command, ControllerDateTime, ControllerEvent, ControllerModel, CycleMode,
+    EventLog, EventLogEntry, ExecutingJobInfo, FILE_CONTROL_PORT, FileNameRules, HsesPayload,
+    IoBit, JobFile, JobSelectType, MoveFrame, MoveSpeed, MultipleVariableCommandId, OperationMode,
+    Position, ProtocolError, PulsePosition, ROBOT_CONTROL_PORT, SelectedJob, StationPosition,
+    Status, StatusData1, StatusData2, SystemInfo, TextEncoding, VariableCommandId,
+};
@@ -0,0 +1,140 @@
+//! In-process connection to a mock controller
+//!
+//! [`HsesClient::loopback`] connects a client to a [`MockController`] without sockets.
+//! Requests are still encoded and responses decoded, so the whole protocol path is
+//! exercised, but nothing binds a port, which makes the mock usable from doctests and from
+//! tests running thousands of requests:
+//!
+//! ```
+//! use moto_hses_client::{ClientConfig, HsesClient};
+//! use moto_hses_mock::server::MockServerBuilder;
+//!
+//! tokio_test::block_on(async {
+//!     let controller = MockServerBuilder::new().build_controller();
+//!     let client = HsesClient::loopback(ClientConfig::default(), &controller);
+//!
+//!     client.write_register(3, 1234).await.unwrap();
+//!     assert_eq!(client.read_register(3).await.unwrap(), 1234);
+//! });
+//! ```
+
+use crate::task;
+use crate::transport::Transport;
+use crate::types::{ClientConfig, HsesClient};
+use moto_hses_mock::MockController;
+use std::io;
+use tokio::sync::{Mutex, mpsc};
+
+/// Transport answering every frame with the responses of a [`MockController`]
+///
+/// Responses are queued for [`Transport::recv_datagram`] once the controller handled the
+/// request; delays from the response latency or injected faults are waited for in a
+/// separate task, so a delayed response can arrive after the client gave up on it.
+pub struct LoopbackTransport {
+    controller: MockController,
+    sender: mpsc::UnboundedSender<Vec<u8>>,
+    receiver: Mutex<mpsc::UnboundedReceiver<Vec<u8>>>,
+}
+
+impl LoopbackTransport {
+    /// Transport to `controller`; clones of a controller share its state
+    #[must_use]
+    pub fn new(controller: MockController) -> Self {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        Self { controller, sender, receiver: Mutex::new(receiver) }
+    }
+}
+
+#[async_trait::async_trait]
+impl Transport for LoopbackTransport {
+    async fn send_datagram(&self, data: &[u8]) -> io::Result<()> {
+        let reply = self.controller.exchange(data).await;
+        if reply.delay.is_zero() {
+            for datagram in reply.datagrams {
+                // The receiver lives as long as the transport
+                let _ = self.sender.send(datagram);
+            }
+        } else {
+            let sender = self.sender.clone();
+            task::spawn("hses-loopback-delivery", async move {
+                tokio::time::sleep(reply.delay).await;
+                for datagram in reply.datagrams {
+                    let _ = sender.send(datagram);
+                }
+            });
+        }
+        Ok(())
+    }
+
+    async fn recv_datagram(&self, buffer: &mut [u8]) -> io::Result<usize> {
+        let frame = self
+            .receiver
+            .lock()
+            .await
+            .recv()
+            .await
+            .ok_or_else(|| io::Error::new(io::ErrorKind::BrokenPipe, "transport closed"))?;
+        let len = frame.len().min(buffer.len());
+        buffer[..len].copy_from_slice(&frame[..len]);
+        Ok(len)
+    }
+}
+
+impl HsesClient {
+    /// Create a client connected in-process to `controller`
+    ///
+    /// The client shares the state of `controller`, e.g. for inspection through
+    /// [`MockController::handle`].
+    #[must_use]
+    pub fn loopback(config: ClientConfig, controller: &MockController) -> Self {
+        Self::with_transport(
+            config,
+            LoopbackTransport::new(controller.clone()),
+            LoopbackTransport::new(controller.clone()),
+        )
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use moto_hses_mock::ResponseFault;
+    use moto_hses_mock::server::MockServerBuilder;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_register_round_trips() {
+        let controller =
+            MockServerBuilder::new().with_log_level(log::LevelFilter::Off).build_controller();
+        let client = HsesClient::loopback(ClientConfig::default(), &controller);
+
+        // Linear congruential generator, so that failures are reproducible
+        let mut seed: u32 = 0x1234_5678;
+        for _ in 0..1000 {
+            seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+            let [low, high, number, _] = seed.to_le_bytes();
+            let value = i16::from_le_bytes([low, high]);
+            let number = u16::from(number % 100);
+            client.write_register(number, value).await.unwrap();
+            assert_eq!(client.read_register(number).await.unwrap(), value);
+        }
+        assert_eq!(controller.handle().robot_request_count().await, 2000);
+    }
+
+    #[tokio::test]
+    async fn test_dropped_response_is_retried() {
+        let controller =
+            MockServerBuilder::new().with_log_level(log::LevelFilter::Off).build_controller();
+        let config = ClientConfig {
+            timeout: Duration::from_millis(50),
+            retry_delay: Duration::from_millis(1),
+            ..ClientConfig::default()
+        };
+        let client = HsesClient::loopback(config, &controller);
+
+        controller.handle().inject_fault(ResponseFault::Drop).await;
+        client.write_register(1, 7).await.unwrap();
+        assert_eq!(client.read_register(1).await.unwrap(), 7);
+    }
+}
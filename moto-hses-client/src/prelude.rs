@@ -0,0 +1,18 @@
+//! Commonly needed client types, with the protocol prelude
+//!
+//! ```no_run
+//! use moto_hses_client::prelude::*;
+//!
+//! # async fn run() -> Result<(), ClientError> {
+//! let client = HsesClient::new("192.168.0.3:10040").await?;
+//! let alarm = client.read_alarm_data(1, AlarmAttribute::All).await?;
+//! let position = client.read_position(1).await?;
+//! # Ok(())
+//! # }
+//! ```
+
+pub use crate::shared::SharedHsesClient;
+pub use crate::traits::HsesClientOps;
+pub use crate::transport::Transport;
+pub use crate::types::{ClientConfig, ClientError, DangerLevel, HsesClient};
+pub use moto_hses_proto::prelude::*;
@@ -1,23 +1,79 @@
 //! Protocol communication for HSES client
 
+use crate::transport::{Mailbox, RoutedTransport};
+use moto_hses_proto::constants::header;
+use moto_hses_proto::payload::AlarmLayout;
+use moto_hses_proto::payload::variable::{STRING_VARIABLE_SIZE, encode_string_variable};
 use moto_hses_proto::{
-    Alarm, AlarmAttribute, AlarmReset, Command, DeleteFile, Division, ExecutingJobInfo,
-    HoldServoControl, HsesPayload, Position, ReadAlarmData, ReadAlarmHistory, ReadCurrentPosition,
-    ReadExecutingJobInfo, ReadFileList, ReadIo, ReadStatus, ReadStatusData1, ReadStatusData2,
-    ReadVariable, ReceiveFile, SendFile, Status, StatusData1, StatusData2, VariableCommandId,
-    WriteIo, WriteVariable,
+    ALARM_HISTORY_SIZE, Alarm, AlarmAttribute, AlarmCategory, AlarmReset, AttributeDeserialize,
+    AttributeRead, BasePosition, Command, DeleteFile, Division, ExecutingJobInfo, HoldServoControl,
+    HsesPayload, IoBit, JobFile, JobInfoAttribute, MoveCartesian, MoveFrame, MovePulse, MoveSpeed,
+    MoveType, Position, ProtocolError, ReadAlarmData, ReadAlarmHistory, ReadBasePositionVar,
+    ReadCurrentPosition, ReadExecutingJobInfo, ReadFileList, ReadIo, ReadPositionVar,
+    ReadStationVar, ReadStatus, ReadStatusData1, ReadStatusData2, ReadSystemInfo, ReadVariable,
+    ReceiveFile, SelectedJob, SendFile, Service, StationPosition, Status, StatusData1, StatusData2,
+    SystemInfo, VariableCommandId, WriteBasePositionVar, WriteIo, WritePositionVar,
+    WriteStationVar, WriteVariable,
     commands::{
         JobSelectCommand, JobSelectType, JobStartCommand, MultipleVariableCommandId,
         MultipleVariableResponse, ReadMultipleIo, ReadMultipleVariables, WriteMultipleIo,
-        WriteMultipleStringVariables, WriteMultipleVariables, WriteStringVar, parse_file_content,
-        parse_file_list,
+        WriteMultipleStringVariables, WriteMultipleVariables, WriteStringVar, io::IoCategory,
+        parse_file_content, parse_file_list, validate_filename,
     },
 };
+use std::borrow::Cow;
+use std::collections::HashMap;
 use std::fmt::Write;
 use std::sync::atomic::Ordering;
+use std::time::{Duration, Instant, SystemTime};
 use tokio::time::{sleep, timeout};
 
-use crate::types::{ClientError, HsesClient};
+use crate::audit::{AuditRecord, Auditor, read_counterpart};
+use crate::emergency::{EmergencyHoldOptions, EmergencyHoldReport, PriorityLane};
+use crate::job_diff::JobDiff;
+use crate::jog::{JogDeadman, JogDirection, JogReport, JogStopReason};
+use crate::recipe::{Recipe, VariableValue, combined_profile};
+use crate::robot_snapshot::RobotSnapshot;
+use crate::snapshot::{VariableProfile, VariableSnapshot};
+use crate::status_watch::StatusBit;
+use crate::task;
+use crate::teach_points::{TeachPoint, TeachPointTable};
+use crate::trajectory::{TrajectoryControl, TrajectoryOptions, TrajectoryReport};
+use crate::types::{
+    AttributeStrategy, CartesianDelta, ClientError, DangerLevel, DeleteFilesOptions,
+    FileDeleteOutcome, FileDeleteResult, HsesClient, IncrementalMove, IncrementalMoveOptions,
+    JobRunReport, ResponseMeta, WritePacing,
+};
+use crate::watch::VariableRef;
+
+/// Interval between status reads while waiting for a job to finish
+const JOB_STATUS_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Interval between the increments sent by a jog
+const JOG_STEP_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Interval between status and position reads while waiting for a move to finish
+const MOTION_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Interval between the status reads and retries of an emergency hold
+const EMERGENCY_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Distance from a Cartesian trajectory point at which the robot counts as arrived [mm]
+const TRAJECTORY_DISTANCE_TOLERANCE: f32 = 0.1;
+
+/// Deviation per axis from a pulse trajectory point at which the robot counts as arrived
+const TRAJECTORY_PULSE_TOLERANCE: u32 = 10;
+
+/// Number of current alarms reported by the controller (instances 1-4)
+const CURRENT_ALARM_COUNT: u16 = 4;
+
+/// Maximum number of requests in flight in a pipelined exchange
+///
+/// Request IDs are one byte, so more requests would reuse the IDs of unanswered ones.
+pub const MAX_PIPELINED_REQUESTS: usize = 64;
+
+/// Offset from a robot number to the control group reporting its Cartesian position
+const ROBOT_CARTESIAN_CONTROL_GROUP_OFFSET: u8 = 100;
 
 /// Sequence control parameters
 #[derive(Debug, Clone)]
@@ -29,12 +85,147 @@ struct SequenceParams {
 
 /// Request-specific parameters
 #[derive(Debug, Clone)]
-struct RequestParams {
-    division: Division,
-    command: u16,
-    instance: u16,
-    attribute: u8,
-    service: u8,
+pub(crate) struct RequestParams {
+    pub division: Division,
+    pub command: u16,
+    pub instance: u16,
+    pub attribute: u8,
+    pub service: Service,
+    /// Whether the request may be resent without knowing if the controller received it,
+    /// see [`Command::is_idempotent`]
+    pub idempotent: bool,
+    /// Block number of a data block of a file sent in blocks; 0 for other requests
+    pub block_number: u32,
+}
+
+impl RequestParams {
+    pub(crate) fn of<C: Command>(command: &C, division: Division) -> Self {
+        Self {
+            division,
+            command: C::command_id(),
+            instance: command.instance(),
+            attribute: command.attribute(),
+            service: command.service(),
+            idempotent: command.is_idempotent(),
+            block_number: 0,
+        }
+    }
+}
+
+// I/O data bytes holding consecutive signals, for plural I/O requests
+//
+// The byte count is padded to the even count the plural I/O command requires: after the
+// last byte, or before the first one where the byte after the last is outside the I/O
+// range of the signals.
+struct IoSpan {
+    io_number: u16,
+    byte_count: usize,
+    // Signals of the first bytes before the first one wanted
+    skip: usize,
+    count: usize,
+}
+
+impl IoSpan {
+    fn new(start: u32, count: usize) -> Result<Self, ProtocolError> {
+        let signal = IoBit::from_address(start)?;
+        if count == 0 {
+            return Err(ProtocolError::InvalidMessage("no I/O signals requested".to_string()));
+        }
+        let skip = usize::from(signal.bit);
+        let byte_count = (skip + count).div_ceil(8);
+        if byte_count.is_multiple_of(2) {
+            return Ok(Self { io_number: signal.io_number, byte_count, skip, count });
+        }
+        let category = IoCategory::from_io_number(signal.io_number);
+        let after_last = usize::from(signal.io_number) + byte_count;
+        let pad_before = category.is_some_and(|category| {
+            let (first, last) = category.range();
+            after_last > usize::from(last) && signal.io_number > first
+        });
+        Ok(if pad_before {
+            Self {
+                io_number: signal.io_number - 1,
+                byte_count: byte_count + 1,
+                skip: skip + 8,
+                count,
+            }
+        } else {
+            Self { io_number: signal.io_number, byte_count: byte_count + 1, skip, count }
+        })
+    }
+
+    // Byte count of the plural I/O command, which rejects counts above its limit
+    fn io_count(&self) -> u32 {
+        u32::try_from(self.byte_count).unwrap_or(u32::MAX)
+    }
+
+    // Whether the signals fill all bytes of the span, so that nothing has to be read
+    // before writing them
+    const fn is_whole(&self) -> bool {
+        self.skip == 0 && self.count == self.byte_count * 8
+    }
+
+    fn unpack(&self, data: &[u8]) -> Vec<bool> {
+        (self.skip..self.skip + self.count)
+            .map(|index| data[index / 8] & 1 << (index % 8) != 0)
+            .collect()
+    }
+
+    fn pack(&self, values: &[bool], data: &mut [u8]) {
+        for (index, &on) in (self.skip..).zip(values) {
+            let mask = 1 << (index % 8);
+            if on {
+                data[index / 8] |= mask;
+            } else {
+                data[index / 8] &= !mask;
+            }
+        }
+    }
+}
+
+// Clears the network input of an interrupted `pulse_io` when dropped while armed
+struct PulseGuard {
+    client: HsesClient,
+    io_number: u16,
+    armed: bool,
+}
+
+impl Drop for PulseGuard {
+    fn drop(&mut self) {
+        if !self.armed {
+            return;
+        }
+        let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+            warn!("I/O #{} left set: no runtime to clear it", self.io_number);
+            return;
+        };
+        let client = self.client.share();
+        let io_number = self.io_number;
+        task::spawn_on(&runtime, "hses-pulse-clear", async move {
+            let deadline = Instant::now()
+                + client.current_config().timeout * (client.current_config().retry_count + 1);
+            let command = WriteIo { io_number, value: 0 };
+            match client.send_priority_command(&command, deadline).await {
+                Ok(_) => debug!("I/O #{io_number} cleared after an interrupted pulse"),
+                Err(e) => warn!("Failed to clear I/O #{io_number} after a pulse: {e}"),
+            }
+        });
+    }
+}
+
+/// Robot request sent as part of a pipelined exchange
+struct PipelinedRequest {
+    request: RequestParams,
+    payload: Vec<u8>,
+}
+
+impl PipelinedRequest {
+    fn new<C: Command>(command: &C) -> Result<Self, ClientError> {
+        Ok(Self {
+            request: RequestParams::of(command, Division::Robot),
+            payload: command.serialize()?,
+        })
+    }
 }
 
 impl HsesClient {
@@ -48,7 +239,7 @@ impl HsesClient {
     {
         let command = ReadVariable::<T> { index, _phantom: std::marker::PhantomData };
         let response = self.send_command_with_retry(command, Division::Robot).await?;
-        T::deserialize(&response, self.config.text_encoding).map_err(ClientError::from)
+        T::deserialize(&response, self.current_config().text_encoding).map_err(ClientError::from)
     }
 
     /// # Errors
@@ -63,18 +254,47 @@ impl HsesClient {
         Ok(())
     }
 
+    /// Read string variable, decoded with the client's text encoding
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if communication fails, if the response does not have the S variable
+    /// size of the [`Capabilities`](crate::Capabilities), or with [`Self::set_strict_encoding`]
+    /// if the text is not valid in the client's text encoding
+    pub async fn read_string_variable(&self, index: u16) -> Result<String, ClientError> {
+        let command = ReadVariable::<String>::new(index);
+        let response = self.send_command_with_retry(command, Division::Robot).await?;
+        let size = self.capabilities().string_variable_size;
+        if response.len() != size {
+            return Err(ClientError::ProtocolError(ProtocolError::UnexpectedLength {
+                expected: size,
+                got: response.len(),
+            }));
+        }
+        let text = response.split(|&b| b == 0).next().unwrap_or_default();
+        self.decode_text(text, &format!("S{index:03}"))
+    }
+
     /// Write string variable with encoding support
     ///
     /// # Errors
     ///
-    /// Returns an error if communication fails
+    /// Returns an error if communication fails, [`ProtocolError::StringTooLong`] if the
+    /// string exceeds the S variable size of the [`Capabilities`](crate::Capabilities) when
+    /// encoded, or an error if it has characters the client's text encoding cannot represent
     pub async fn write_string_variable(
         &self,
         index: u16,
         value: String,
     ) -> Result<(), ClientError> {
-        let command = WriteStringVar { index, value, text_encoding: self.config.text_encoding };
-        let _response = self.send_command_with_retry(command, Division::Robot).await?;
+        let payload = encode_string_variable(
+            &value,
+            self.current_config().text_encoding,
+            self.capabilities().string_variable_size,
+        )?;
+        let command =
+            WriteStringVar { index, value, text_encoding: self.current_config().text_encoding };
+        self.send_request_with_meta(RequestParams::of(&command, Division::Robot), payload).await?;
         Ok(())
     }
 
@@ -82,6 +302,8 @@ impl HsesClient {
     ///
     /// This method is encoding-agnostic and works with raw byte arrays for S variables.
     /// For string handling with encoding support, use convenience methods in convenience.rs.
+    /// On controllers without plural variable commands, the variables are read with
+    /// pipelined single variable reads.
     ///
     /// # Errors
     /// Returns an error if communication fails or parameters are invalid
@@ -94,15 +316,21 @@ impl HsesClient {
         T: MultipleVariableCommandId + MultipleVariableResponse + Send + Sync + PartialEq,
     {
         let command = ReadMultipleVariables::<T>::new(start_variable_number, count)?;
-        let response = self.send_command_with_retry(command, Division::Robot).await?;
-        T::deserialize_multiple(&response, count, self.config.text_encoding)
+        let response = if self.capabilities().plural_variables {
+            self.send_command_with_retry(command, Division::Robot).await?
+        } else {
+            self.read_single_variables(T::multiple_command_id(), start_variable_number, count)
+                .await?
+        };
+        T::deserialize_multiple(&response, count, self.current_config().text_encoding)
             .map_err(ClientError::from)
     }
 
     /// Write multiple variables of type T
     ///
     /// This is a generic method that works with any type T that implements the required traits.
-    /// For type-specific convenience methods, use the methods in convenience.rs.
+    /// For type-specific convenience methods, use the methods in convenience.rs. On
+    /// controllers without plural variable commands, the variables are written one by one.
     ///
     /// # Type Parameters
     /// - `T`: The variable type (u8, i16, i32, f32, [u8; 16])
@@ -119,71 +347,1020 @@ impl HsesClient {
         WriteMultipleVariables<T>: Command<Response = ()>,
     {
         let command = WriteMultipleVariables::<T>::new(start_variable_number, values)?;
+        if !self.capabilities().plural_variables {
+            let data = command.serialize()?;
+            return self
+                .write_single_variables(
+                    T::multiple_command_id(),
+                    start_variable_number,
+                    data.get(4..).unwrap_or_default().chunks(T::element_size()),
+                )
+                .await;
+        }
+        self.send_command_with_retry(command, Division::Robot).await?;
+        Ok(())
+    }
+
+    /// Write multiple string variables with encoding support
+    ///
+    /// On controllers without plural variable commands, the variables are written one by
+    /// one.
+    ///
+    /// # Errors
+    /// Returns an error if communication fails or parameters are invalid
+    pub async fn write_multiple_string_variables(
+        &self,
+        start_variable_number: u16,
+        values: Vec<String>,
+    ) -> Result<(), ClientError> {
+        let command = WriteMultipleStringVariables {
+            start_variable_number,
+            values,
+            text_encoding: self.current_config().text_encoding,
+        };
+        if !self.capabilities().plural_variables {
+            let data = command.serialize()?;
+            return self
+                .write_single_variables(
+                    WriteMultipleStringVariables::command_id(),
+                    start_variable_number,
+                    data.get(4..).unwrap_or_default().chunks(STRING_VARIABLE_SIZE),
+                )
+                .await;
+        }
+        self.send_command_with_retry(command, Division::Robot).await?;
+        Ok(())
+    }
+
+    // Data of a plural variable read (the count, then the values) read with pipelined
+    // single variable reads, for controllers without the plural commands
+    async fn read_single_variables(
+        &self,
+        plural_command: u16,
+        start_variable_number: u16,
+        count: u32,
+    ) -> Result<Vec<u8>, ClientError> {
+        let first = usize::from(start_variable_number);
+        let requests = (0..count)
+            .map(|offset| {
+                let request = RequestParams {
+                    division: Division::Robot,
+                    command: single_variable_command(plural_command),
+                    instance: variable_number(first + offset as usize)?,
+                    attribute: 0,
+                    service: Service::GetSingle,
+                    idempotent: true,
+                    block_number: 0,
+                };
+                Ok(PipelinedRequest { request, payload: Vec::new() })
+            })
+            .collect::<Result<Vec<_>, ClientError>>()?;
+        let mut data = count.to_le_bytes().to_vec();
+        for chunk in requests.chunks(MAX_PIPELINED_REQUESTS) {
+            for response in self.send_pipelined(chunk).await? {
+                data.extend_from_slice(&response);
+            }
+        }
+        Ok(data)
+    }
+
+    // Write the values of a plural variable write one by one, for controllers without the
+    // plural commands
+    async fn write_single_variables(
+        &self,
+        plural_command: u16,
+        start_variable_number: u16,
+        values: std::slice::Chunks<'_, u8>,
+    ) -> Result<(), ClientError> {
+        for (index, value) in (usize::from(start_variable_number)..).zip(values) {
+            let request = RequestParams {
+                division: Division::Robot,
+                command: single_variable_command(plural_command),
+                instance: variable_number(index)?,
+                attribute: 0,
+                service: Service::SetSingle,
+                idempotent: true,
+                block_number: 0,
+            };
+            self.send_request_with_meta(request, value.to_vec()).await?;
+        }
+        Ok(())
+    }
+
+    /// Read the values of `variables`, of any types and numbers, with pipelined requests
+    ///
+    /// Requests are sent back to back in groups of [`MAX_PIPELINED_REQUESTS`], so each
+    /// group takes about one round trip.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if communication fails, the controller rejects a request or, with
+    /// [`Self::set_strict_encoding`], an S variable cannot be decoded
+    pub async fn read_variables(
+        &self,
+        variables: &[VariableRef],
+    ) -> Result<Vec<VariableValue>, ClientError> {
+        let mut values = Vec::with_capacity(variables.len());
+        for chunk in variables.chunks(MAX_PIPELINED_REQUESTS) {
+            let requests = chunk
+                .iter()
+                .map(|&variable| match variable {
+                    VariableRef::B(index) => PipelinedRequest::new(&ReadVariable::<u8>::new(index)),
+                    VariableRef::I(index) => {
+                        PipelinedRequest::new(&ReadVariable::<i16>::new(index))
+                    }
+                    VariableRef::D(index) => {
+                        PipelinedRequest::new(&ReadVariable::<i32>::new(index))
+                    }
+                    VariableRef::R(index) => {
+                        PipelinedRequest::new(&ReadVariable::<f32>::new(index))
+                    }
+                    VariableRef::S(index) => {
+                        PipelinedRequest::new(&ReadVariable::<String>::new(index))
+                    }
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            let responses = self.send_pipelined(&requests).await?;
+            for (&variable, response) in chunk.iter().zip(responses) {
+                values.push(self.decode_variable(variable, &response)?);
+            }
+        }
+        Ok(values)
+    }
+
+    /// Read the variables selected by `profile` with plural commands
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if communication fails or a variable does not exist
+    pub async fn snapshot_variables(
+        &self,
+        profile: &VariableProfile,
+    ) -> Result<VariableSnapshot, ClientError> {
+        // Plural byte commands read an even number of variables
+        let mut bytes = self.read_variables_chunked::<u8>(profile.byte & !1).await?;
+        if profile.byte % 2 == 1 {
+            bytes.push(self.read_variable::<u8>(profile.byte - 1).await?);
+        }
+        Ok(VariableSnapshot {
+            bytes,
+            integers: self.read_variables_chunked(profile.integer).await?,
+            doubles: self.read_variables_chunked(profile.double).await?,
+            reals: self.read_variables_chunked(profile.real).await?,
+            strings: self.read_variables_chunked(profile.string).await?,
+        })
+    }
+
+    /// Write the variables of a snapshot back with plural commands
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the snapshot holds more than `u16::MAX` variables of a type,
+    /// a string exceeds 16 bytes when encoded, or communication fails
+    pub async fn restore_snapshot(&self, snapshot: &VariableSnapshot) -> Result<(), ClientError> {
+        // Plural byte commands write an even number of variables
+        let even = snapshot.bytes.len() & !1;
+        self.write_variables_chunked(&snapshot.bytes[..even]).await?;
+        if let Some(&value) = snapshot.bytes.get(even) {
+            self.write_variable(variable_number(even)?, value).await?;
+        }
+        self.write_variables_chunked(&snapshot.integers).await?;
+        self.write_variables_chunked(&snapshot.doubles).await?;
+        self.write_variables_chunked(&snapshot.reals).await?;
+        for (index, strings) in snapshot.strings.chunks(max_chunk::<String>()).enumerate() {
+            let start = variable_number(index * max_chunk::<String>())?;
+            self.write_multiple_string_variables(start, strings.to_vec()).await?;
+        }
+        Ok(())
+    }
+
+    /// Write the variables of `recipe` and verify them by reading them back
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ClientError::VerificationFailed`] naming the variables that do not hold
+    /// their recipe value afterwards, or an error if communication fails
+    pub async fn apply_recipe(&self, recipe: &Recipe) -> Result<(), ClientError> {
+        for assignment in &recipe.assignments {
+            let index = assignment.index;
+            match &assignment.value {
+                VariableValue::Byte(value) => self.write_variable(index, *value).await?,
+                VariableValue::Integer(value) => self.write_variable(index, *value).await?,
+                VariableValue::Double(value) => self.write_variable(index, *value).await?,
+                VariableValue::Real(value) => self.write_variable(index, *value).await?,
+                VariableValue::String(value) => {
+                    self.write_string_variable(index, value.clone()).await?;
+                }
+            }
+        }
+
+        let snapshot = self.snapshot_variables(&recipe.profile()).await?;
+        let mismatches = recipe.mismatches(&snapshot);
+        if mismatches.is_empty() {
+            return Ok(());
+        }
+        let variables: Vec<String> =
+            mismatches.iter().map(|assignment| assignment.variable_name()).collect();
+        Err(ClientError::VerificationFailed(format!(
+            "recipe '{}' not applied to {}",
+            recipe.name,
+            variables.join(", ")
+        )))
+    }
+
+    /// Find the first of `recipes` whose variables all hold their recipe values
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if communication fails
+    pub async fn detect_recipe<'a>(
+        &self,
+        recipes: &'a [Recipe],
+    ) -> Result<Option<&'a Recipe>, ClientError> {
+        let snapshot = self.snapshot_variables(&combined_profile(recipes)).await?;
+        Ok(recipes.iter().find(|recipe| recipe.matches(&snapshot)))
+    }
+
+    /// Read a robot position variable (P variable)
+    ///
+    /// Pulse positions hold 8 axes, resized to `expected_axes` if configured.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if communication fails
+    pub async fn read_position_variable(&self, index: u16) -> Result<Position, ClientError> {
+        let command = ReadPositionVar::new(index);
+        let response = self.send_command_with_retry(command, Division::Robot).await?;
+        let position = Position::deserialize(&response, self.current_config().text_encoding)?;
+        Ok(self.fit_axes(position))
+    }
+
+    /// Write a robot position variable (P variable)
+    ///
+    /// Axes missing from a pulse position are written as 0.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ProtocolError::PositionError`] if a pulse position has more than 8 axes,
+    /// or an error if communication fails
+    pub async fn write_position_variable(
+        &self,
+        index: u16,
+        value: Position,
+    ) -> Result<(), ClientError> {
+        let command = WritePositionVar::new(index, value);
+        let _response = self.send_command_with_retry(command, Division::Robot).await?;
+        Ok(())
+    }
+
+    /// Read a base position variable (BP variable)
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if communication fails
+    pub async fn read_base_position_variable(
+        &self,
+        index: u16,
+    ) -> Result<BasePosition, ClientError> {
+        let command = ReadBasePositionVar::new(index);
+        let response = self.send_command_with_retry(command, Division::Robot).await?;
+        BasePosition::deserialize(&response, self.current_config().text_encoding)
+            .map_err(ClientError::from)
+    }
+
+    /// Write a base position variable (BP variable)
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if communication fails
+    pub async fn write_base_position_variable(
+        &self,
+        index: u16,
+        value: BasePosition,
+    ) -> Result<(), ClientError> {
+        let command = WriteBasePositionVar { index, value };
+        let _response = self.send_command_with_retry(command, Division::Robot).await?;
+        Ok(())
+    }
+
+    /// Read a station variable (EX variable)
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if communication fails
+    pub async fn read_station_variable(&self, index: u16) -> Result<StationPosition, ClientError> {
+        let command = ReadStationVar::new(index);
+        let response = self.send_command_with_retry(command, Division::Robot).await?;
+        StationPosition::deserialize(&response, self.current_config().text_encoding)
+            .map_err(ClientError::from)
+    }
+
+    /// Write a station variable (EX variable)
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if communication fails
+    pub async fn write_station_variable(
+        &self,
+        index: u16,
+        value: StationPosition,
+    ) -> Result<(), ClientError> {
+        let command = WriteStationVar { index, value };
+        let _response = self.send_command_with_retry(command, Division::Robot).await?;
+        Ok(())
+    }
+
+    /// Write the positions of `table` to their P variables and verify them by reading them
+    /// back
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ClientError::VerificationFailed`] naming the points whose variables do not
+    /// hold their position afterwards, [`ClientError::InvalidVariable`] if a position does
+    /// not fit a P variable, or an error if communication fails
+    pub async fn write_teach_points(&self, table: &TeachPointTable) -> Result<(), ClientError> {
+        let mut expected = Vec::with_capacity(table.points.len());
+        for point in &table.points {
+            expected.push((point.variable_position()?, point.variable_data()?));
+        }
+        for (point, (position, _)) in table.points.iter().zip(&expected) {
+            self.write_position_variable(point.index, position.clone()).await?;
+        }
+
+        let mut mismatches = Vec::new();
+        for (point, (_, data)) in table.points.iter().zip(expected) {
+            let command = ReadPositionVar::new(point.index);
+            let response = self.send_command_with_retry(command, Division::Robot).await?;
+            if response != data {
+                mismatches.push(format!("{} ({})", point.name, point.variable_name()));
+            }
+        }
+        if mismatches.is_empty() {
+            return Ok(());
+        }
+        Err(ClientError::VerificationFailed(format!(
+            "teach points not written: {}",
+            mismatches.join(", ")
+        )))
+    }
+
+    /// Read P variables `indices` as a teach point table, with the points named after
+    /// their variables, e.g. "P001"
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if communication fails or a variable does not hold a pulse or a
+    /// Cartesian position
+    pub async fn read_teach_points(&self, indices: &[u16]) -> Result<TeachPointTable, ClientError> {
+        let mut points = Vec::with_capacity(indices.len());
+        for &index in indices {
+            let position = self.read_position_variable(index).await?;
+            points.push(TeachPoint::new(format!("P{index:03}"), index, position));
+        }
+        Ok(TeachPointTable::new(points))
+    }
+
+    // Read variables 0 to `count` - 1 in requests of at most the plural command limit
+    async fn read_variables_chunked<T>(&self, count: u16) -> Result<Vec<T>, ClientError>
+    where
+        T: MultipleVariableCommandId + MultipleVariableResponse + Send + Sync + PartialEq,
+    {
+        let mut values = Vec::with_capacity(usize::from(count));
+        let chunk = u16::try_from(max_chunk::<T>()).unwrap_or(u16::MAX);
+        let mut start = 0;
+        while start < count {
+            let n = chunk.min(count - start);
+            values.extend(self.read_multiple_variables::<T>(start, u32::from(n)).await?);
+            start += n;
+        }
+        Ok(values)
+    }
+
+    // Write `values` to variables 0 onwards in requests of at most the plural command limit
+    async fn write_variables_chunked<T>(&self, values: &[T]) -> Result<(), ClientError>
+    where
+        T: MultipleVariableCommandId + Send + Sync + Clone + HsesPayload + PartialEq,
+        WriteMultipleVariables<T>: Command<Response = ()>,
+    {
+        for (index, chunk) in values.chunks(max_chunk::<T>()).enumerate() {
+            let start = variable_number(index * max_chunk::<T>())?;
+            self.write_multiple_variables(start, chunk.to_vec()).await?;
+        }
+        Ok(())
+    }
+
+    /// Read complete status information (both Data 1 and Data 2) efficiently
+    /// Uses service=0x01 (`Get_Attribute_All`) with attribute=0 to get both data in one request
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if communication fails
+    pub async fn read_status(&self) -> Result<Status, ClientError> {
+        let response = self.send_command_with_retry(ReadStatus, Division::Robot).await?;
+        Status::deserialize(&response, self.current_config().text_encoding)
+            .map_err(ClientError::from)
+    }
+
+    /// Read complete status information together with request metadata
+    ///
+    /// Same as [`Self::read_status`], additionally returning the request ID, number of
+    /// attempts and round-trip time of the request.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if communication fails
+    pub async fn read_status_with_meta(&self) -> Result<(Status, ResponseMeta), ClientError> {
+        let (response, meta) = self.send_command_with_meta(ReadStatus, Division::Robot).await?;
+        let status = Status::deserialize(&response, self.current_config().text_encoding)?;
+        Ok((status, meta))
+    }
+
+    /// Read the system information of a robot, station or the application
+    ///
+    /// # Arguments
+    /// * `instance` - System (11-18: Robots R1-R8, 21-44: Stations S1-S24, 101: Application)
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if communication fails
+    pub async fn read_system_info(&self, instance: u16) -> Result<SystemInfo, ClientError> {
+        let command = ReadSystemInfo::new(instance);
+        let response = self.send_command_with_retry(command, Division::Robot).await?;
+        SystemInfo::deserialize(&response, self.current_config().text_encoding)
+            .map_err(ClientError::from)
+    }
+
+    /// System information of robot R1 recorded by [`Self::connect`]
+    ///
+    /// `None` for clients created without the handshake, or if the controller did not
+    /// report its system information.
+    #[must_use]
+    pub fn info(&self) -> Option<SystemInfo> {
+        self.inner.info.lock().ok().and_then(|info| info.clone())
+    }
+
+    // Handshake of `connect`: one status read without retries to check that a controller
+    // answers, then the system information of robot R1 to record its identity and the
+    // probes of the optional features
+    pub(crate) async fn identify(&self) -> Result<(), ClientError> {
+        match self.send_command_once(&ReadStatus, Division::Robot).await {
+            Ok(_) => {}
+            Err(ClientError::TimeoutError(_) | ClientError::ConnectionError(_)) => {
+                return Err(ClientError::ConnectError(format!(
+                    "no response from {} within {:?}",
+                    self.inner.remote_addr,
+                    self.current_config().timeout
+                )));
+            }
+            Err(e) => return Err(e),
+        }
+
+        match self.read_system_info(ReadSystemInfo::robot(1).instance).await {
+            Ok(info) => {
+                info!(
+                    "Connected to {}: model {}, software version {}",
+                    self.inner.remote_addr, info.model, info.software_version
+                );
+                if let Ok(mut current) = self.inner.info.lock() {
+                    *current = Some(info);
+                }
+            }
+            Err(ClientError::ProtocolError(ProtocolError::ServerError(e))) => {
+                warn!(
+                    "Controller at {} did not report system information: {e}",
+                    self.inner.remote_addr
+                );
+            }
+            Err(e) => return Err(e),
+        }
+
+        match self.detect_capabilities().await {
+            Ok(capabilities) => {
+                info!("Capabilities of {}: {capabilities}", self.inner.remote_addr);
+            }
+            Err(e) => {
+                warn!(
+                    "Could not detect the capabilities of {}, assuming all: {e}",
+                    self.inner.remote_addr
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Set a callback receiving the [`ResponseMeta`] of every successful request
+    ///
+    /// Useful to correlate application logs with packet captures or to record link quality
+    /// without changing the call sites. Replaces a previously set hook.
+    pub fn set_response_hook(&self, hook: impl Fn(&ResponseMeta) + Send + Sync + 'static) {
+        if let Ok(mut current) = self.inner.response_hook.lock() {
+            *current = Some(std::sync::Arc::new(hook));
+        }
+    }
+
+    /// Remove the callback set with [`Self::set_response_hook`]
+    pub fn clear_response_hook(&self) {
+        if let Ok(mut current) = self.inner.response_hook.lock() {
+            *current = None;
+        }
+    }
+
+    /// Fail text reads that cannot be decoded with the configured text encoding instead of
+    /// logging a warning and returning the lossy result (default: off)
+    pub fn set_strict_encoding(&self, strict: bool) {
+        self.inner.strict_encoding.store(strict, Ordering::Relaxed);
+    }
+
+    /// Handle to the priority lane, usable while the client is busy elsewhere
+    ///
+    /// [`SharedHsesClient`](crate::SharedHsesClient) keeps one to run
+    /// [`emergency_hold`](Self::emergency_hold) without waiting for its lock.
+    #[must_use]
+    pub fn priority_lane(&self) -> PriorityLane {
+        PriorityLane::new(self.share())
+    }
+
+    // Another client on the same connection and configuration
+    pub(crate) fn share(&self) -> Self {
+        Self { inner: self.inner.clone() }
+    }
+
+    /// Read status data 1 (basic status information)
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if communication fails
+    pub async fn read_status_data1(&self) -> Result<StatusData1, ClientError> {
+        let response = self.send_command_with_retry(ReadStatusData1, Division::Robot).await?;
+        StatusData1::deserialize(&response, self.current_config().text_encoding)
+            .map_err(ClientError::from)
+    }
+
+    /// Read status data 2 (additional status information)
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if communication fails
+    pub async fn read_status_data2(&self) -> Result<StatusData2, ClientError> {
+        let response = self.send_command_with_retry(ReadStatusData2, Division::Robot).await?;
+        StatusData2::deserialize(&response, self.current_config().text_encoding)
+            .map_err(ClientError::from)
+    }
+
+    /// # Errors
+    ///
+    /// Returns an error if communication fails
+    pub async fn read_position(&self, control_group: u8) -> Result<Position, ClientError> {
+        let command = ReadCurrentPosition { control_group };
+        let response = self.send_command_with_retry(command, Division::Robot).await?;
+        let position = Position::deserialize(&response, self.current_config().text_encoding)?;
+        Ok(self.fit_axes(position))
+    }
+
+    /// Read the status, the positions of `control_groups`, the executing job of the master
+    /// task and the number of active alarms with pipelined requests
+    ///
+    /// All requests are sent before the first response is awaited, so the fields are
+    /// sampled within about one round trip of each other. Requests whose response is lost
+    /// are sent again, up to the configured number of retries.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if more than [`MAX_PIPELINED_REQUESTS`] requests would be needed,
+    /// communication fails or the controller rejects a request
+    pub async fn read_snapshot(&self, control_groups: &[u8]) -> Result<RobotSnapshot, ClientError> {
+        let mut requests = vec![
+            PipelinedRequest::new(&ReadStatus)?,
+            PipelinedRequest::new(&ReadExecutingJobInfo::new(1, JobInfoAttribute::All))?,
+        ];
+        for &control_group in control_groups {
+            requests.push(PipelinedRequest::new(&ReadCurrentPosition { control_group })?);
+        }
+        for instance in 1..=CURRENT_ALARM_COUNT {
+            requests
+                .push(PipelinedRequest::new(&ReadAlarmData::new(instance, AlarmAttribute::Code))?);
+        }
+
+        let captured_at = SystemTime::now();
+        let started = Instant::now();
+        let responses = self.send_pipelined(&requests).await?;
+        let skew = started.elapsed();
+
+        let encoding = self.current_config().text_encoding;
+        let (positions, alarm_codes) = responses[2..].split_at(control_groups.len());
+        Ok(RobotSnapshot {
+            captured_at,
+            skew,
+            status: Status::deserialize(&responses[0], encoding)?,
+            positions: positions
+                .iter()
+                .map(|response| Ok(self.fit_axes(Position::deserialize(response, encoding)?)))
+                .collect::<Result<_, ProtocolError>>()?,
+            executing_job: ExecutingJobInfo::deserialize(&responses[1], encoding)?,
+            active_alarm_count: alarm_codes
+                .iter()
+                .filter(|response| response.get(..4).is_some_and(|code| code != [0; 4]))
+                .count(),
+        })
+    }
+
+    // Resize the joints of a pulse position read to the configured number of axes,
+    // warning if the controller reported other axes
+    fn fit_axes(&self, mut position: Position) -> Position {
+        if let (Some(expected), Position::Pulse(pulse)) =
+            (self.current_config().expected_axes, &mut position)
+        {
+            let joints = &mut pulse.joints;
+            if joints.len() < expected {
+                warn!(
+                    "Pulse position has {} axes, {expected} expected; missing axes read as 0",
+                    joints.len()
+                );
+            } else if let Some(axis) = joints[expected..].iter().position(|&value| value != 0) {
+                warn!(
+                    "Pulse position has data on axis {} beyond the {expected} expected axes",
+                    expected + axis + 1
+                );
+            }
+            joints.resize(expected, 0);
+        }
+        position
+    }
+
+    // Reject pulse targets whose joint count differs from the configured number of axes;
+    // missing axes would be sent as 0
+    fn check_axes(&self, joints: &[i32]) -> Result<(), ClientError> {
+        match self.current_config().expected_axes {
+            Some(expected) if joints.len() != expected => Err(ProtocolError::PositionError(
+                format!("{} joints given for robots with {expected} axes", joints.len()),
+            )
+            .into()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Move a robot by a Cartesian increment (IMOV)
+    ///
+    /// # Arguments
+    ///
+    /// * `robot` - Robot number (1-8)
+    /// * `delta` - Increment in `options.frame`
+    /// * `options` - Frame, speed and the per-axis limits applied to `delta`
+    ///
+    /// Reads the current Cartesian position to move with its tool, user coordinate and
+    /// configuration, clamps the delta to the limits and sends a straight incremental
+    /// move. The command returns once the controller has accepted the move, not when the
+    /// robot has reached the target. Like [`Self::start_job`], the move is not resent after
+    /// a lost response while the robot is running.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if writes are not permitted, the delta is not finite, the target
+    /// is outside the configured workspace limits, communication fails or the controller
+    /// rejects the move
+    pub async fn move_incremental(
+        &self,
+        robot: u8,
+        delta: CartesianDelta,
+        options: &IncrementalMoveOptions,
+    ) -> Result<IncrementalMove, ClientError> {
+        self.check_danger_level(DangerLevel::Write, "move robot")?;
+        let applied = delta.clamped(&options.limits);
+        if ![applied.x, applied.y, applied.z, applied.rx, applied.ry, applied.rz]
+            .iter()
+            .all(|value| value.is_finite())
+        {
+            return Err(
+                ProtocolError::InvalidMessage(format!("invalid move delta {delta:?}")).into()
+            );
+        }
+
+        // Control groups 101-108 report the robot position in Cartesian coordinates
+        let Position::Cartesian(start) =
+            self.read_position(robot.saturating_add(ROBOT_CARTESIAN_CONTROL_GROUP_OFFSET)).await?
+        else {
+            return Err(ProtocolError::PositionError(format!(
+                "robot {robot} did not report a Cartesian position"
+            ))
+            .into());
+        };
+
+        if let Some(limits) = &self.current_config().workspace_limits {
+            limits.check_increment(&start, &applied, options.frame)?;
+        }
+
+        let command = MoveCartesian::new(
+            MoveType::LinearIncremental,
+            robot,
+            options.speed,
+            options.frame,
+            applied.x,
+            applied.y,
+            applied.z,
+            applied.rx,
+            applied.ry,
+            applied.rz,
+        )
+        .with_settings_of(&start);
         self.send_command_with_retry(command, Division::Robot).await?;
-        Ok(())
+
+        Ok(IncrementalMove { start, delta: applied, clamped: applied != delta })
     }
 
-    /// Write multiple string variables with encoding support
+    /// Move a robot to an absolute pulse position (PMOV)
+    ///
+    /// # Arguments
+    ///
+    /// * `robot` - Robot number (1-8)
+    /// * `joints` - Target position of each robot axis [pulse] (up to 8 axes)
+    /// * `speed` - Speed of the move
+    ///
+    /// The command returns once the controller has accepted the move, not when the robot
+    /// has reached the target.
     ///
     /// # Errors
-    /// Returns an error if communication fails or parameters are invalid
-    pub async fn write_multiple_string_variables(
+    ///
+    /// Returns an error if writes are not permitted, `joints` does not match the configured
+    /// number of axes, the target is outside the configured workspace limits,
+    /// communication fails or the controller rejects the move
+    pub async fn move_pulse(
         &self,
-        start_variable_number: u16,
-        values: Vec<String>,
+        robot: u8,
+        joints: Vec<i32>,
+        speed: MoveSpeed,
     ) -> Result<(), ClientError> {
-        let command = WriteMultipleStringVariables {
-            start_variable_number,
-            values,
-            text_encoding: self.config.text_encoding,
-        };
+        self.check_danger_level(DangerLevel::Write, "move robot")?;
+        self.check_axes(&joints)?;
+        if let Some(limits) = &self.current_config().workspace_limits {
+            limits.check_pulse(&joints)?;
+        }
+        let command = MovePulse::new(MoveType::JointAbsolute, robot, speed, joints);
         self.send_command_with_retry(command, Division::Robot).await?;
         Ok(())
     }
 
-    /// Read complete status information (both Data 1 and Data 2) efficiently
-    /// Uses service=0x01 (`Get_Attribute_All`) with attribute=0 to get both data in one request
+    /// Jog a robot while `deadman` is held
     ///
-    /// # Errors
+    /// # Arguments
     ///
-    /// Returns an error if communication fails
-    pub async fn read_status(&self) -> Result<Status, ClientError> {
-        let response = self.send_command_with_retry(ReadStatus, Division::Robot).await?;
-        Status::deserialize(&response, self.config.text_encoding).map_err(ClientError::from)
-    }
-
-    /// Read status data 1 (basic status information)
+    /// * `robot` - Robot number (1-8)
+    /// * `frame` - Frame the direction is given in
+    /// * `direction` - Axis and sense to jog
+    /// * `speed` - Jog speed [mm/s, or deg/s for rotations]
+    /// * `deadman` - Token that keeps the jog going
+    ///
+    /// Sends an incremental move every 100 ms, each covering the distance travelled at
+    /// `speed` in that time, until the deadman is released or its keepalive times out.
+    /// No further increment is sent once the token is released; the robot stops at the
+    /// end of the increment in progress. With workspace limits configured, the jog also
+    /// stops before an increment would leave them. Increments are not retried, so a lost
+    /// request ends the jog with an error.
     ///
     /// # Errors
     ///
-    /// Returns an error if communication fails
-    pub async fn read_status_data1(&self) -> Result<StatusData1, ClientError> {
-        let response = self.send_command_with_retry(ReadStatusData1, Division::Robot).await?;
-        StatusData1::deserialize(&response, self.config.text_encoding).map_err(ClientError::from)
+    /// Returns an error if writes are not permitted, the speed is not positive,
+    /// communication fails or the controller rejects a move
+    #[allow(clippy::cast_precision_loss)]
+    pub async fn jog(
+        &self,
+        robot: u8,
+        frame: MoveFrame,
+        direction: JogDirection,
+        speed: f32,
+        deadman: &JogDeadman,
+    ) -> Result<JogReport, ClientError> {
+        self.check_danger_level(DangerLevel::Write, "jog robot")?;
+        if !speed.is_finite() || speed <= 0.0 {
+            return Err(ProtocolError::InvalidMessage(format!("invalid jog speed {speed}")).into());
+        }
+
+        let Position::Cartesian(start) =
+            self.read_position(robot.saturating_add(ROBOT_CARTESIAN_CONTROL_GROUP_OFFSET)).await?
+        else {
+            return Err(ProtocolError::PositionError(format!(
+                "robot {robot} did not report a Cartesian position"
+            ))
+            .into());
+        };
+
+        let step = speed * JOG_STEP_INTERVAL.as_secs_f32();
+        let delta = direction.delta(step);
+        let move_speed = if direction.axis.is_rotation() {
+            MoveSpeed::Rotational(speed)
+        } else {
+            MoveSpeed::Linear(speed)
+        };
+        let command = MoveCartesian::new(
+            MoveType::LinearIncremental,
+            robot,
+            move_speed,
+            frame,
+            delta.x,
+            delta.y,
+            delta.z,
+            delta.rx,
+            delta.ry,
+            delta.rz,
+        )
+        .with_settings_of(&start);
+        let request = RequestParams::of(&command, Division::Robot);
+        let payload = command.serialize()?;
+
+        let started_at = Instant::now();
+        let mut ticker = tokio::time::interval(JOG_STEP_INTERVAL);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        let mut steps = 0u32;
+        let stop_reason = loop {
+            tokio::select! {
+                biased;
+                () = deadman.released() => break JogStopReason::Released,
+                _ = ticker.tick() => {}
+            }
+            if deadman.is_expired() {
+                break JogStopReason::KeepaliveTimeout;
+            }
+            if let Some(limits) = &self.current_config().workspace_limits {
+                let travelled = direction.delta(step * (steps + 1) as f32);
+                if limits.check_increment(&start, &travelled, frame).is_err() {
+                    break JogStopReason::WorkspaceLimit;
+                }
+            }
+            let audit = self.begin_audit(&request, &payload).await;
+            let transport = &self.inner.transport;
+            let result = self.send_request_on(transport, &request, payload.clone()).await;
+            finish_audit(audit, result.as_ref().map(|_| ()));
+            self.record_exchange(&request, &payload, result.as_ref().map(|(response, _)| response));
+            result?;
+            steps += 1;
+        };
+
+        Ok(JogReport {
+            steps,
+            distance: step * steps as f32,
+            duration: started_at.elapsed(),
+            stop_reason,
+        })
     }
 
-    /// Read status data 2 (additional status information)
+    /// Move a robot through a sequence of positions
+    ///
+    /// # Arguments
+    ///
+    /// * `robot` - Robot number (1-8)
+    /// * `points` - Targets in order; Cartesian points are in the base frame
+    /// * `options` - Speed, blending and the timeout of each segment
+    /// * `control` - Pauses, resumes or cancels the playback and reports its progress
+    ///
+    /// Sends one move instruction per point, a joint move for joint speeds and a straight
+    /// move otherwise, and monitors the status and position until the point is reached
+    /// before sending the next. All points are checked against the workspace limits before
+    /// the first move is sent.
     ///
     /// # Errors
     ///
-    /// Returns an error if communication fails
-    pub async fn read_status_data2(&self) -> Result<StatusData2, ClientError> {
-        let response = self.send_command_with_retry(ReadStatusData2, Division::Robot).await?;
-        StatusData2::deserialize(&response, self.config.text_encoding).map_err(ClientError::from)
+    /// Returns an error if writes are not permitted, a pulse point does not match the
+    /// configured number of axes, a point is outside the workspace limits, communication
+    /// fails, the controller rejects a move or a point is not
+    /// reached within the segment timeout
+    pub async fn play_trajectory(
+        &self,
+        robot: u8,
+        points: &[Position],
+        options: &TrajectoryOptions,
+        control: &TrajectoryControl,
+    ) -> Result<TrajectoryReport, ClientError> {
+        self.check_danger_level(DangerLevel::Write, "move robot")?;
+        for point in points {
+            if let Position::Pulse(pulse) = point {
+                self.check_axes(&pulse.joints)?;
+            }
+        }
+        if let Some(limits) = &self.current_config().workspace_limits {
+            for point in points {
+                match point {
+                    Position::Pulse(pulse) => limits.check_pulse(&pulse.joints)?,
+                    Position::Cartesian(cart) => limits.check_point([cart.x, cart.y, cart.z])?,
+                }
+            }
+        }
+
+        control.start(points.len());
+        let started_at = Instant::now();
+        let mut completed = 0;
+        for (index, point) in points.iter().enumerate() {
+            control.wait_resumed().await;
+            if control.is_cancelled() {
+                break;
+            }
+            self.send_trajectory_point(robot, point, options.speed).await?;
+            timeout(options.segment_timeout, self.wait_for_trajectory_point(robot, point, options))
+                .await
+                .map_err(|_| {
+                    ClientError::TimeoutError(format!(
+                        "Trajectory point {} not reached after {:?}",
+                        index + 1,
+                        options.segment_timeout
+                    ))
+                })??;
+            completed += 1;
+            control.set_completed(completed);
+        }
+
+        Ok(TrajectoryReport {
+            completed,
+            total: points.len(),
+            cancelled: completed < points.len(),
+            duration: started_at.elapsed(),
+        })
     }
 
-    /// # Errors
-    ///
-    /// Returns an error if communication fails
-    pub async fn read_position(&self, control_group: u8) -> Result<Position, ClientError> {
-        let command = ReadCurrentPosition { control_group };
-        let response = self.send_command_with_retry(command, Division::Robot).await?;
-        Position::deserialize(&response, self.config.text_encoding).map_err(ClientError::from)
+    // Send the move instruction to one trajectory point
+    async fn send_trajectory_point(
+        &self,
+        robot: u8,
+        point: &Position,
+        speed: MoveSpeed,
+    ) -> Result<(), ClientError> {
+        let move_type = if matches!(speed, MoveSpeed::Joint(_)) {
+            MoveType::JointAbsolute
+        } else {
+            MoveType::LinearAbsolute
+        };
+        match point {
+            Position::Pulse(pulse) => {
+                let command = MovePulse::new(move_type, robot, speed, pulse.joints.clone());
+                self.send_command_with_retry(command, Division::Robot).await?;
+            }
+            Position::Cartesian(cart) => {
+                let command = MoveCartesian::new(
+                    move_type,
+                    robot,
+                    speed,
+                    MoveFrame::Base,
+                    cart.x,
+                    cart.y,
+                    cart.z,
+                    cart.rx,
+                    cart.ry,
+                    cart.rz,
+                )
+                .with_settings_of(cart);
+                self.send_command_with_retry(command, Division::Robot).await?;
+            }
+        }
+        Ok(())
+    }
+
+    // Poll until the robot has stopped at `point`, or is within the blend distance of it
+    async fn wait_for_trajectory_point(
+        &self,
+        robot: u8,
+        point: &Position,
+        options: &TrajectoryOptions,
+    ) -> Result<(), ClientError> {
+        loop {
+            let running = self.read_status_bit(StatusBit::Running).await?;
+            let arrived = match point {
+                Position::Pulse(target) => {
+                    let Position::Pulse(current) = self.read_position(robot).await? else {
+                        return Err(ProtocolError::PositionError(format!(
+                            "robot {robot} did not report a pulse position"
+                        ))
+                        .into());
+                    };
+                    !running
+                        && target.joints.iter().zip(&current.joints).all(|(target, current)| {
+                            target.abs_diff(*current) <= TRAJECTORY_PULSE_TOLERANCE
+                        })
+                }
+                Position::Cartesian(target) => {
+                    let Position::Cartesian(current) = self
+                        .read_position(robot.saturating_add(ROBOT_CARTESIAN_CONTROL_GROUP_OFFSET))
+                        .await?
+                    else {
+                        return Err(ProtocolError::PositionError(format!(
+                            "robot {robot} did not report a Cartesian position"
+                        ))
+                        .into());
+                    };
+                    let distance = (target.z - current.z)
+                        .hypot(target.y - current.y)
+                        .hypot(target.x - current.x);
+                    options.blend.is_some_and(|blend| distance <= blend)
+                        || (!running && distance <= TRAJECTORY_DISTANCE_TOLERANCE)
+                }
+            };
+            if arrived {
+                return Ok(());
+            }
+            sleep(MOTION_POLL_INTERVAL).await;
+        }
     }
 
     /// # Errors
     ///
-    /// Returns an error if communication fails
+    /// Returns [`ClientError::Unsupported`] for a sub code attribute if the controller
+    /// has no alarm sub codes, or an error if communication fails
     pub async fn read_alarm_data(
         &self,
         instance: u16,
@@ -195,7 +1372,8 @@ impl HsesClient {
 
     /// # Errors
     ///
-    /// Returns an error if communication fails
+    /// Returns [`ClientError::Unsupported`] for a sub code attribute if the controller
+    /// has no alarm sub codes, or an error if communication fails
     pub async fn read_alarm_history(
         &self,
         instance: u16,
@@ -205,6 +1383,56 @@ impl HsesClient {
         self.read_alarm_attribute(command, attribute).await
     }
 
+    /// Read `count` alarm history entries of `category`, starting at entry `start` (0-based)
+    ///
+    /// The entries are requested with pipelined 0x71 requests, one per entry. Empty entries
+    /// are skipped and the alarms are returned newest first.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the category is invalid, the range exceeds the
+    /// [`ALARM_HISTORY_SIZE`] entries of the category, or communication fails
+    pub async fn read_alarm_history_range(
+        &self,
+        category: AlarmCategory,
+        start: u16,
+        count: u16,
+    ) -> Result<Vec<Alarm>, ClientError> {
+        let Some(first_instance) = category.first_instance() else {
+            return Err(ProtocolError::InvalidMessage(format!(
+                "invalid alarm category '{category}'"
+            ))
+            .into());
+        };
+        let max = ALARM_HISTORY_SIZE.saturating_sub(start);
+        if count > max {
+            return Err(ProtocolError::InvalidCount {
+                command: 0x71,
+                got: usize::from(count),
+                max: usize::from(max),
+            }
+            .into());
+        }
+
+        let requests = (first_instance + start..first_instance + start + count)
+            .map(|instance| {
+                PipelinedRequest::new(&ReadAlarmHistory::new(instance, AlarmAttribute::All))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut alarms = Vec::new();
+        for chunk in requests.chunks(MAX_PIPELINED_REQUESTS) {
+            for response in self.send_pipelined(chunk).await? {
+                let alarm = self.decode_alarm(&response)?;
+                if alarm.code != 0 {
+                    alarms.push(alarm);
+                }
+            }
+        }
+        // Times are "YYYY/MM/DD HH:MM", so they sort chronologically as text
+        alarms.sort_by(|a, b| b.time.cmp(&a.time));
+        Ok(alarms)
+    }
+
     /// Reset alarm (0x82 command with instance 1)
     ///
     /// This command resets the current alarm state.
@@ -274,6 +1502,63 @@ impl HsesClient {
         Ok(())
     }
 
+    /// Hold the robot, check that it stopped and optionally turn the servo power off
+    ///
+    /// The commands go through the priority lane (see [`Self::priority_lane`]) and the
+    /// routine returns within `options.deadline`. Once the hold is acknowledged, the status
+    /// is read until the robot is no longer running; the servo power is only turned off
+    /// after that. Whatever could not be done before the deadline is reported rather than
+    /// returned as an error, so the caller always learns the state the robot was left in.
+    ///
+    /// Not limited by `max_danger_level`, as it only ever stops the robot.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the hold request is not acknowledged before the deadline
+    pub async fn emergency_hold(
+        &self,
+        options: &EmergencyHoldOptions,
+    ) -> Result<EmergencyHoldReport, ClientError> {
+        let started_at = Instant::now();
+        let deadline = started_at + options.deadline;
+        self.send_priority_command(&HoldServoControl::hold_on(), deadline).await?;
+
+        let mut status = None;
+        let mut stopped = false;
+        while Instant::now() < deadline {
+            let response = self.send_priority_command(&ReadStatus, deadline).await;
+            match response.and_then(|response| {
+                Status::deserialize(&response, self.current_config().text_encoding)
+                    .map_err(ClientError::from)
+            }) {
+                Ok(current) => {
+                    stopped = !current.is_running();
+                    status = Some(current);
+                    if stopped {
+                        break;
+                    }
+                }
+                Err(e) => warn!("Failed to read status after hold: {e}"),
+            }
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            sleep(EMERGENCY_POLL_INTERVAL.min(remaining)).await;
+        }
+
+        let mut servo_off = false;
+        if options.servo_off {
+            if stopped {
+                match self.send_priority_command(&HoldServoControl::servo_off(), deadline).await {
+                    Ok(_) => servo_off = true,
+                    Err(e) => warn!("Failed to turn servo power off after hold: {e}"),
+                }
+            } else {
+                warn!("Servo power left on: the robot did not stop before the deadline");
+            }
+        }
+
+        Ok(EmergencyHoldReport { stopped, servo_off, status, elapsed: started_at.elapsed() })
+    }
+
     /// Set cycle mode (0x84 command)
     ///
     /// # Arguments
@@ -293,9 +1578,13 @@ impl HsesClient {
 
     /// Start job execution (0x86 command)
     ///
+    /// If the response is lost, the command is only sent again if the status shows that
+    /// the robot is not running, so that a lost response does not start the job twice.
+    ///
     /// # Errors
     ///
-    /// Returns an error if communication fails
+    /// Returns an error if communication fails, or if the response is lost and the status
+    /// cannot be read
     pub async fn start_job(&self) -> Result<(), ClientError> {
         let command = JobStartCommand::new();
         let _response = self.send_command_with_retry(command, Division::Robot).await?;
@@ -322,8 +1611,10 @@ impl HsesClient {
         let job_name = job_name.into();
 
         // Validate job name byte length (max 32 bytes when encoded)
-        let encoded_bytes =
-            moto_hses_proto::encoding_utils::encode_string(&job_name, self.config.text_encoding);
+        let encoded_bytes = moto_hses_proto::encoding_utils::encode_string(
+            &job_name,
+            self.current_config().text_encoding,
+        );
         if encoded_bytes.len() > 32 {
             return Err(ClientError::SystemError(
                 "Job name exceeds 32 bytes when encoded".to_string(),
@@ -335,12 +1626,157 @@ impl HsesClient {
             return Err(ClientError::SystemError("Line number must be 0-9999".to_string()));
         }
 
-        let command =
-            JobSelectCommand::new(select_type, job_name, line_number, self.config.text_encoding);
+        let command = JobSelectCommand::new(
+            select_type,
+            job_name,
+            line_number,
+            self.current_config().text_encoding,
+        );
         let _response = self.send_command_with_retry(command, Division::Robot).await?;
         Ok(())
     }
 
+    /// Run a job and wait until it stops
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Name of the job to run
+    /// * `line` - Line number to start from
+    /// * `start_timeout` - Maximum time to wait for the controller to report the job running
+    /// * `run_timeout` - Maximum time to wait for the job to stop once it is running
+    ///
+    /// Selects the job, checks that the servo is on, starts the job and polls the status
+    /// until the controller reports it running, then until it is no longer running. The
+    /// controller may report the job running some time after accepting the start request,
+    /// so a status that is not running is only taken as the end of the job once it was
+    /// seen running. The report lists alarms that became active while the job was running.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the servo is off, communication fails, the job is not running
+    /// within `start_timeout` or is still running after `run_timeout`
+    pub async fn run_job_and_wait(
+        &self,
+        name: &str,
+        line: u32,
+        start_timeout: Duration,
+        run_timeout: Duration,
+    ) -> Result<JobRunReport, ClientError> {
+        self.select_job(JobSelectType::InExecution, name, line).await?;
+
+        let status = self.read_status().await?;
+        if !status.data2.servo_on {
+            return Err(ClientError::SystemError(format!("Cannot start job {name}: servo is off")));
+        }
+        let alarms_before = self.read_current_alarms().await?;
+
+        let started_at = Instant::now();
+        self.start_job().await?;
+        timeout(start_timeout, self.poll_running(true)).await.map_err(|_| {
+            ClientError::TimeoutError(format!("Job {name} did not start within {start_timeout:?}"))
+        })??;
+        let final_status =
+            timeout(run_timeout, self.poll_running(false)).await.map_err(|_| {
+                ClientError::TimeoutError(format!("Job {name} still running after {run_timeout:?}"))
+            })??;
+        let duration = started_at.elapsed();
+
+        let alarms = self
+            .read_current_alarms()
+            .await?
+            .into_iter()
+            .filter(|alarm| !alarms_before.contains(alarm))
+            .collect();
+        Ok(JobRunReport { job_name: name.to_string(), duration, final_status, alarms })
+    }
+
+    // Poll the status until its running flag is `running`, returning that status
+    async fn poll_running(&self, running: bool) -> Result<Status, ClientError> {
+        loop {
+            let status = self.read_status().await?;
+            if status.data1.running == running {
+                return Ok(status);
+            }
+            sleep(JOB_STATUS_POLL_INTERVAL).await;
+        }
+    }
+
+    // Read the active alarms, latest first
+    async fn read_current_alarms(&self) -> Result<Vec<Alarm>, ClientError> {
+        let mut alarms = Vec::new();
+        for instance in 1..=CURRENT_ALARM_COUNT {
+            let code = self.read_alarm_data(instance, AlarmAttribute::Code).await?.code;
+            if code == 0 {
+                break;
+            }
+            alarms.push(self.read_alarm_data(instance, AlarmAttribute::All).await?);
+        }
+        Ok(alarms)
+    }
+
+    /// Read the job and line currently selected for the master task (0x73 command)
+    ///
+    /// HSES cannot read back a job selection (0x87 only sets it), so this reads the job
+    /// name and line number from the executing job information of the master task, which
+    /// follow the selection. They name the program that the next [`Self::start_job`]
+    /// would run, so it can be confirmed before starting.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if communication fails, or with strict encoding if the job name
+    /// is not valid text in the configured encoding
+    pub async fn read_selected_job(&self) -> Result<SelectedJob, ClientError> {
+        let command = ReadExecutingJobInfo::new(1, JobInfoAttribute::All); // Master task
+        let response = self.send_command_with_retry(command, Division::Robot).await?;
+        let (job, had_replacements) = SelectedJob::deserialize_with_replacements(
+            &response,
+            self.current_config().text_encoding,
+        )?;
+        self.report_replacements(had_replacements, "selected job name", || {
+            format!("{:?}", job.job_name)
+        })?;
+        Ok(job)
+    }
+
+    /// Move the selected job to `line` without selecting another job (0x87 command)
+    ///
+    /// Reads the selected job and selects it again at `line`, so that the next
+    /// [`Self::start_job`] resumes there. With `job`, the downloaded job file, e.g. parsed
+    /// from [`Self::receive_file`] on a file control client, the line is checked against
+    /// its `END` before anything is sent. Returns the job and line now selected.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no job is selected, `job` is another job, `line` is past its
+    /// `END` or beyond 9999, or communication fails
+    pub async fn jump_to_line(
+        &self,
+        line: u32,
+        job: Option<&JobFile>,
+    ) -> Result<SelectedJob, ClientError> {
+        let selected = self.read_selected_job().await?;
+        if selected.job_name.is_empty() {
+            return Err(ClientError::SystemError("Cannot jump to a line: no job selected".into()));
+        }
+        if let Some(job) = job {
+            if !job.is_job(&selected.job_name) {
+                return Err(ClientError::SystemError(format!(
+                    "Job file {} is not the selected job {}",
+                    job.name, selected.job_name
+                )));
+            }
+            if line > job.last_line() {
+                return Err(ClientError::SystemError(format!(
+                    "Line {line} is past the end of {} (line {})",
+                    selected.job_name,
+                    job.last_line()
+                )));
+            }
+        }
+        self.select_job(JobSelectType::InExecution, selected.job_name.clone(), line).await?;
+        Ok(SelectedJob::new(selected.job_name, line))
+    }
+
     /// Read executing job information
     ///
     /// # Arguments
@@ -349,24 +1785,22 @@ impl HsesClient {
     ///
     /// # Errors
     ///
-    /// Returns an error if communication fails
+    /// Returns an error if communication fails, or
+    /// [`ProtocolError::InvalidAttribute`] without sending a request if `attribute` is not
+    /// 0-4
     pub async fn read_executing_job_info(
         &self,
         task_type: u16,
         attribute: u8,
     ) -> Result<ExecutingJobInfo, ClientError> {
-        let command = ReadExecutingJobInfo::new(task_type, attribute);
+        let command = ReadExecutingJobInfo::new(task_type, JobInfoAttribute::try_from(attribute)?);
         let response = self.send_command_with_retry(command, Division::Robot).await?;
-
-        // Use attribute-specific deserialization for single attributes
-        if attribute > 0 {
-            ExecutingJobInfo::deserialize_attribute(&response, attribute, self.config.text_encoding)
-                .map_err(ClientError::from)
-        } else {
-            // Use standard deserialization for complete data
-            ExecutingJobInfo::deserialize(&response, self.config.text_encoding)
-                .map_err(ClientError::from)
-        }
+        ExecutingJobInfo::deserialize_attribute(
+            &response,
+            attribute,
+            self.current_config().text_encoding,
+        )
+        .map_err(ClientError::from)
     }
 
     /// Read complete executing job information (all attributes)
@@ -384,6 +1818,65 @@ impl HsesClient {
         self.read_executing_job_info(task_type, 0).await
     }
 
+    /// Read some attributes of a payload, such as the line and step of the executing job
+    ///
+    /// An empty `attributes` list, or one containing 0, reads all attributes. With
+    /// [`AttributeStrategy::Auto`], several attributes are read with one
+    /// `Get_Attribute_All` request if `command` supports it, and a single attribute with a
+    /// `Get_Attribute_Single` request. Single reads of several attributes are pipelined and
+    /// merged; the fields of attributes not read are left empty or 0.
+    ///
+    /// # Arguments
+    /// * `command` - Read of the payload; its own attribute is ignored
+    /// * `attributes` - Attributes to read, numbered from 1
+    /// * `strategy` - Service to read them with
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ProtocolError::InvalidAttribute`] without sending a request for an unknown
+    /// attribute, [`ProtocolError::InvalidService`] for [`AttributeStrategy::All`] if the
+    /// command has no `Get_Attribute_All`, or an error if communication fails
+    pub async fn read_attributes<C>(
+        &self,
+        command: &C,
+        attributes: &[u8],
+        strategy: AttributeStrategy,
+    ) -> Result<C::Response, ClientError>
+    where
+        C: AttributeRead + Send + Sync,
+        C::Response: AttributeDeserialize,
+    {
+        let encoding = self.current_config().text_encoding;
+        let attributes: Vec<u8> = if attributes.is_empty() || attributes.contains(&0) {
+            let count = u8::try_from(C::Response::ATTRIBUTES.len()).unwrap_or(u8::MAX);
+            (1..=count).collect()
+        } else {
+            attributes.to_vec()
+        };
+        let all = match strategy {
+            AttributeStrategy::Auto => command.supports_all() && attributes.len() > 1,
+            AttributeStrategy::All if !command.supports_all() => {
+                return Err(ProtocolError::InvalidService.into());
+            }
+            AttributeStrategy::All => true,
+            AttributeStrategy::Single => false,
+        };
+        if all {
+            let response =
+                self.send_command_with_retry(command.with_attribute(0)?, Division::Robot).await?;
+            return C::Response::deserialize_all(&response, encoding).map_err(ClientError::from);
+        }
+
+        let requests = attributes
+            .iter()
+            .map(|&attribute| PipelinedRequest::new(&command.with_attribute(attribute)?))
+            .collect::<Result<Vec<_>, ClientError>>()?;
+        let responses = self.send_pipelined(&requests).await?;
+        let reads: Vec<(u8, &[u8])> =
+            attributes.iter().copied().zip(responses.iter().map(Vec::as_slice)).collect();
+        C::Response::deserialize_attributes(&reads, encoding).map_err(ClientError::from)
+    }
+
     // Common helper method for alarm attribute reading
     async fn read_alarm_attribute<C: Command + Send + Sync>(
         &self,
@@ -393,95 +1886,53 @@ impl HsesClient {
     where
         C::Response: HsesPayload + Into<Alarm>,
     {
+        let sub_code = matches!(
+            attribute,
+            AlarmAttribute::SubCodeInfo
+                | AlarmAttribute::SubCodeData
+                | AlarmAttribute::SubCodeReverse
+        );
+        if sub_code && !self.capabilities().alarm_sub_codes {
+            return Err(ClientError::Unsupported(format!(
+                "alarm attribute {attribute:?} (no alarm sub codes)"
+            )));
+        }
         let response = self.send_command_with_retry(command, Division::Robot).await?;
-
         if attribute == AlarmAttribute::All {
-            // Service = 0x01 (Get_Attribute_All) - Return all data
-            let deserialized: C::Response =
-                C::Response::deserialize(&response, self.config.text_encoding)
-                    .map_err(ClientError::from)?;
-            Ok(deserialized.into())
-        } else {
-            // Service = 0x0E (Get_Attribute_Single) - Return only specified attribute
-
-            // Attribute-specific deserialization
-            match attribute {
-                AlarmAttribute::Code => {
-                    // Alarm code (4 bytes)
-                    if response.len() >= 4 {
-                        let code = u32::from_le_bytes([
-                            response[0],
-                            response[1],
-                            response[2],
-                            response[3],
-                        ]);
-                        Ok(Alarm::new(code, 0, 0, String::new(), String::new()))
-                    } else {
-                        Ok(Alarm::new(0, 0, 0, String::new(), String::new()))
-                    }
-                }
-                AlarmAttribute::Data => {
-                    // Alarm data (4 bytes)
-                    if response.len() >= 4 {
-                        let data = u32::from_le_bytes([
-                            response[0],
-                            response[1],
-                            response[2],
-                            response[3],
-                        ]);
-                        Ok(Alarm::new(0, data, 0, String::new(), String::new()))
-                    } else {
-                        Ok(Alarm::new(0, 0, 0, String::new(), String::new()))
-                    }
-                }
-                AlarmAttribute::Type => {
-                    // Alarm type (4 bytes)
-                    if response.len() >= 4 {
-                        let alarm_type = u32::from_le_bytes([
-                            response[0],
-                            response[1],
-                            response[2],
-                            response[3],
-                        ]);
-                        Ok(Alarm::new(0, 0, alarm_type, String::new(), String::new()))
-                    } else {
-                        Ok(Alarm::new(0, 0, 0, String::new(), String::new()))
-                    }
-                }
-                AlarmAttribute::Time => {
-                    // Alarm time (16 bytes)
-                    if response.len() >= 16 {
-                        let time_end = response.iter().position(|&b| b == 0).unwrap_or(16);
-                        let time_bytes = &response[..time_end];
-                        let time = moto_hses_proto::encoding_utils::decode_string_with_fallback(
-                            time_bytes,
-                            self.config.text_encoding,
-                        );
-                        Ok(Alarm::new(0, 0, 0, time, String::new()))
-                    } else {
-                        Ok(Alarm::new(0, 0, 0, String::new(), String::new()))
-                    }
-                }
-                AlarmAttribute::Name => {
-                    // Alarm name (32 bytes)
-                    if response.len() >= 32 {
-                        let name_end = response.iter().position(|&b| b == 0).unwrap_or(32);
-                        let name_bytes = &response[..name_end];
-                        let name = moto_hses_proto::encoding_utils::decode_string_with_fallback(
-                            name_bytes,
-                            self.config.text_encoding,
-                        );
-                        Ok(Alarm::new(0, 0, 0, String::new(), name))
-                    } else {
-                        Ok(Alarm::new(0, 0, 0, String::new(), String::new()))
-                    }
-                }
-                _ => {
-                    // Invalid attribute
-                    Ok(Alarm::new(0, 0, 0, String::new(), String::new()))
-                }
-            }
+            return self.decode_alarm(&response);
+        }
+
+        let mut alarm = Alarm::deserialize_attribute(
+            &response,
+            attribute as u8,
+            self.current_config().text_encoding,
+        )?;
+        // Re-decode text attributes so that encoding mismatches are reported
+        let text = |what: &str| {
+            let end = response.iter().position(|&b| b == 0).unwrap_or(response.len());
+            self.decode_text(&response[..end], what)
+        };
+        match attribute {
+            AlarmAttribute::Time => alarm.time = text("alarm time")?,
+            AlarmAttribute::Name => alarm.name = text("alarm name")?,
+            _ => {}
+        }
+        Ok(alarm)
+    }
+
+    // Decode the data of all alarm attributes
+    fn decode_alarm(&self, response: &[u8]) -> Result<Alarm, ClientError> {
+        // Controllers without sub codes answer attributes 1-5 only
+        let mut response = Cow::Borrowed(response);
+        if response.len() < AlarmLayout::SIZE && !self.capabilities().alarm_sub_codes {
+            response.to_mut().resize(AlarmLayout::SIZE, 0);
         }
+        let (alarm, had_replacements) =
+            Alarm::deserialize_with_replacements(&response, self.current_config().text_encoding)?;
+        self.report_replacements(had_replacements, &format!("alarm {} text", alarm.code), || {
+            format!("{:?} {:?}", alarm.time, alarm.name)
+        })?;
+        Ok(alarm)
     }
 
     /// # Errors
@@ -505,11 +1956,135 @@ impl HsesClient {
 
     /// # Errors
     ///
-    /// Returns an error if communication fails
-    pub async fn write_io(&self, io_number: u16, value: u8) -> Result<(), ClientError> {
-        let command = WriteIo { io_number, value };
-        let _response = self.send_command_with_retry(command, Division::Robot).await?;
-        Ok(())
+    /// Returns an error if communication fails
+    pub async fn write_io(&self, io_number: u16, value: u8) -> Result<(), ClientError> {
+        let command = WriteIo { io_number, value };
+        let _response = self.send_command_with_retry(command, Division::Robot).await?;
+        Ok(())
+    }
+
+    /// Write `value` to a network input for `duration`, then clear it to 0
+    ///
+    /// The input is cleared even if the pulse is not completed: if this future is dropped,
+    /// e.g. by a timeout around it, or the clearing write fails, a spawned task clears the
+    /// input through the priority lane, which does not interfere with requests on the
+    /// regular socket. The whole byte of 8 signals is written.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `io_number` is not a network input (2701-2956), or if
+    /// communication fails
+    pub async fn pulse_io(
+        &self,
+        io_number: u16,
+        value: u8,
+        duration: Duration,
+    ) -> Result<(), ClientError> {
+        if !IoCategory::from_io_number(io_number).is_some_and(IoCategory::is_writable) {
+            return Err(ProtocolError::InvalidMessage(format!(
+                "I/O #{io_number} is not a network input (2701-2956)"
+            ))
+            .into());
+        }
+
+        let mut guard = PulseGuard { client: self.share(), io_number, armed: true };
+        let set = self.write_io(io_number, value).await;
+        if set.is_ok() {
+            sleep(duration).await;
+        }
+        // Cleared even after a failed write, which may have reached the controller
+        let clear = self.write_io(io_number, 0).await;
+        guard.armed = clear.is_err();
+        set.and(clear)
+    }
+
+    /// Read a single I/O signal by its address, e.g. 10010 for bit 0 of I/O #1001
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the address is not a valid signal address (see [`IoBit`]) or
+    /// communication fails
+    pub async fn read_io_bit(&self, address: u32) -> Result<bool, ClientError> {
+        let signal = IoBit::from_address(address)?;
+        Ok(self.read_io(signal.io_number).await? & signal.mask() != 0)
+    }
+
+    /// Turn a single network input signal on or off by its address, e.g. 27013 for bit 3
+    /// of I/O #2701
+    ///
+    /// The I/O data byte holding the signal is read and written back with the bit changed,
+    /// so a concurrent change of another signal of the byte can be overwritten.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the address is not a valid signal address (see [`IoBit`]), the
+    /// signal is not a network input, or communication fails
+    pub async fn write_io_bit(&self, address: u32, on: bool) -> Result<(), ClientError> {
+        let signal = IoBit::from_address(address)?;
+        if !IoCategory::from_io_number(signal.io_number).is_some_and(IoCategory::is_writable) {
+            return Err(ProtocolError::InvalidMessage(format!(
+                "I/O signal {signal} is not a network input (27010-29567)"
+            ))
+            .into());
+        }
+        let current = self.read_io(signal.io_number).await?;
+        let value = if on { current | signal.mask() } else { current & !signal.mask() };
+        if value != current {
+            self.write_io(signal.io_number, value).await?;
+        }
+        Ok(())
+    }
+
+    /// Read `count` consecutive I/O signals from the signal address `start`, e.g. 10010 for
+    /// bit 0 of I/O #1001
+    ///
+    /// Signals continue from bit 7 of an I/O number to bit 0 of the next, e.g. 10017, 10020.
+    /// The I/O data bytes holding them are read with one plural I/O request, padded to the
+    /// even byte count it requires.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `start` is not a valid signal address (see [`IoBit`]), `count` is
+    /// 0 or the signals span more than 474 I/O data bytes, or communication fails
+    pub async fn read_io_bits(&self, start: u32, count: usize) -> Result<Vec<bool>, ClientError> {
+        let span = IoSpan::new(start, count)?;
+        let data = self.read_multiple_io(span.io_number, span.io_count()).await?;
+        Ok(span.unpack(&data))
+    }
+
+    /// Set consecutive network input signals from the signal address `start` to `values`
+    ///
+    /// The signals are written with one plural I/O request. Unless they fill whole I/O data
+    /// bytes, an even number of them, the bytes are read first and written back with the
+    /// other signals unchanged, so a concurrent change of those can be overwritten.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `start` is not a valid signal address (see [`IoBit`]), `values`
+    /// is empty, the signals are not network inputs or span more than 474 I/O data bytes, or
+    /// communication fails
+    pub async fn write_io_bits(&self, start: u32, values: &[bool]) -> Result<(), ClientError> {
+        let span = IoSpan::new(start, values.len())?;
+        let last = usize::from(span.io_number) + span.byte_count - 1;
+        let writable = |io_number: usize| {
+            u16::try_from(io_number)
+                .ok()
+                .and_then(IoCategory::from_io_number)
+                .is_some_and(IoCategory::is_writable)
+        };
+        if !writable(usize::from(span.io_number)) || !writable(last) {
+            return Err(ProtocolError::InvalidMessage(format!(
+                "I/O signals from #{start} are not all network inputs (27010-29567)"
+            ))
+            .into());
+        }
+        let mut data = if span.is_whole() {
+            vec![0; span.byte_count]
+        } else {
+            self.read_multiple_io(span.io_number, span.io_count()).await?
+        };
+        span.pack(values, &mut data);
+        self.write_multiple_io(span.io_number, data).await
     }
 
     /// Read multiple I/O data (0x300 command)
@@ -750,9 +2325,9 @@ impl HsesClient {
     ///
     /// Returns an error if the file list request fails
     pub async fn read_file_list(&self, pattern: &str) -> Result<Vec<String>, ClientError> {
-        let command = ReadFileList::new(pattern.to_string(), self.config.text_encoding);
+        let command = ReadFileList::new(pattern.to_string(), self.current_config().text_encoding);
         let response = self.send_command_with_retry(command, Division::File).await?;
-        parse_file_list(&response, self.config.text_encoding).map_err(ClientError::from)
+        parse_file_list(&response, self.current_config().text_encoding).map_err(ClientError::from)
     }
 
     /// Send file to controller
@@ -761,16 +2336,66 @@ impl HsesClient {
     /// * `filename` - Name of the file to send
     /// * `content` - File content as bytes
     ///
+    /// Files larger than one datagram are sent as numbered blocks of at most
+    /// [`MAX_PAYLOAD_SIZE`](moto_hses_proto::constants::MAX_PAYLOAD_SIZE) bytes, each
+    /// acknowledged by the controller before the next is sent.
+    ///
     /// # Errors
     ///
-    /// Returns an error if the file send request fails
+    /// Returns an error if the file name is not valid for the configured controller model
+    /// or the file send request fails
     pub async fn send_file(&self, filename: &str, content: &[u8]) -> Result<(), ClientError> {
-        let command =
-            SendFile::new(filename.to_string(), content.to_vec(), self.config.text_encoding);
-        let _response = self.send_command_with_retry(command, Division::File).await?;
+        self.validate_filename(filename)?;
+        let command = SendFile::new(
+            filename.to_string(),
+            content.to_vec(),
+            self.current_config().text_encoding,
+        );
+        let blocks = command.blocks()?;
+        let last = blocks.len() - 1;
+        for (index, payload) in blocks.into_iter().enumerate() {
+            let mut request = RequestParams::of(&command, Division::File);
+            if index > 0 {
+                let flag = if index == last { header::LAST_BLOCK_FLAG } else { 0 };
+                request.block_number = u32::try_from(index).unwrap_or(u32::MAX) | flag;
+            }
+            self.send_request_with_meta(request, payload).await?;
+        }
         Ok(())
     }
 
+    /// Send file to controller and verify the stored content
+    ///
+    /// # Arguments
+    /// * `filename` - Name of the file to send
+    /// * `content` - File content as bytes
+    ///
+    /// HSES has no file checksum service, so after the upload the file is received again and
+    /// compared with `content`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ClientError::VerificationFailed`] if the stored content differs from
+    /// `content`, or an error if sending or reading back the file fails
+    pub async fn send_file_verified(
+        &self,
+        filename: &str,
+        content: &[u8],
+    ) -> Result<(), ClientError> {
+        self.send_file(filename, content).await?;
+
+        let stored = self.receive_file_bytes(filename).await?;
+        if stored == content {
+            return Ok(());
+        }
+        let offset = stored.iter().zip(content).take_while(|(stored, sent)| stored == sent).count();
+        Err(ClientError::VerificationFailed(format!(
+            "{filename}: controller holds {} bytes, sent {} bytes, first difference at byte {offset}",
+            stored.len(),
+            content.len()
+        )))
+    }
+
     /// Receive file from controller
     ///
     /// # Arguments
@@ -782,17 +2407,10 @@ impl HsesClient {
     ///
     /// Returns an error if the file receive request fails
     pub async fn receive_file(&self, filename: &str) -> Result<String, ClientError> {
-        let command = ReceiveFile::new(filename.to_string(), self.config.text_encoding);
-        let response = self.send_command_with_retry(command, Division::File).await?;
-        let content_bytes = parse_file_content(&response).map_err(ClientError::from)?;
+        let content_bytes = self.receive_file_bytes(filename).await?;
 
         // Decode bytes to string using client's text encoding
-        let content_string = moto_hses_proto::encoding_utils::decode_string_with_fallback(
-            &content_bytes,
-            self.config.text_encoding,
-        );
-
-        Ok(content_string)
+        self.decode_text(&content_bytes, "file content")
     }
 
     /// Delete file from controller
@@ -802,32 +2420,399 @@ impl HsesClient {
     ///
     /// # Errors
     ///
-    /// Returns an error if the file delete request fails
+    /// Returns an error if the file name is not valid for the configured controller model
+    /// or the file delete request fails
     pub async fn delete_file(&self, filename: &str) -> Result<(), ClientError> {
-        let command = DeleteFile::new(filename.to_string(), self.config.text_encoding);
+        self.validate_filename(filename)?;
+        let command = DeleteFile::new(filename.to_string(), self.current_config().text_encoding);
         let _response = self.send_command_with_retry(command, Division::File).await?;
         Ok(())
     }
 
+    /// Compare a job on the controller with a local source
+    ///
+    /// # Arguments
+    /// * `name` - Job file name on the controller (e.g., "TEST.JBI")
+    /// * `local_source` - Expected job content
+    ///
+    /// Downloads the job, decodes it with the client's text encoding and compares it line
+    /// by line with `local_source`, ignoring line ending differences. Lines only in the
+    /// controller job are reported as added, lines only in the local source as removed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file receive request fails
+    pub async fn diff_job(&self, name: &str, local_source: &str) -> Result<JobDiff, ClientError> {
+        let controller_source = self.receive_file(name).await?;
+        Ok(JobDiff::compute(local_source, &controller_source))
+    }
+
+    /// Delete all files matching a pattern
+    ///
+    /// # Arguments
+    /// * `pattern` - File name pattern to match (e.g., "*.JBI", "TMP*.JBI")
+    /// * `options` - Dry-run and concurrency options
+    ///
+    /// Lists the matching files and deletes them with at most `options.max_concurrency`
+    /// requests in flight. With `options.dry_run` the files are only listed. Returns one
+    /// result per matching file in listing order; a failed delete does not stop the others.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file list request fails or additional connections for
+    /// concurrent deletion cannot be created
+    pub async fn delete_files(
+        &self,
+        pattern: &str,
+        options: DeleteFilesOptions,
+    ) -> Result<Vec<FileDeleteResult>, ClientError> {
+        let files = self.read_file_list(pattern).await?;
+        debug!("{} file(s) match pattern '{pattern}'", files.len());
+
+        if options.dry_run {
+            return Ok(files
+                .into_iter()
+                .map(|filename| FileDeleteResult {
+                    filename,
+                    outcome: FileDeleteOutcome::WouldDelete,
+                })
+                .collect());
+        }
+
+        // Requests on one socket are serialized, so each extra worker gets its own client;
+        // a client on a custom transport cannot open another one
+        let worker_count = if self.inner.custom_transport {
+            1
+        } else {
+            options.max_concurrency.clamp(1, files.len().max(1))
+        };
+        let mut extra_clients = Vec::with_capacity(worker_count - 1);
+        let hook = self.inner.response_hook.lock().ok().and_then(|hook| hook.clone());
+        for _ in 1..worker_count {
+            let client = Self::new_with_config((*self.current_config()).clone()).await?;
+            if let Some(hook) = &hook {
+                let hook = std::sync::Arc::clone(hook);
+                client.set_response_hook(move |meta| hook(meta));
+            }
+            extra_clients.push(client);
+        }
+
+        let queue = std::sync::Mutex::new(files.into_iter().enumerate());
+        let workers = std::iter::once(self)
+            .chain(extra_clients.iter())
+            .map(|client| client.delete_file_worker(&queue));
+        let mut results: Vec<_> =
+            futures::future::join_all(workers).await.into_iter().flatten().collect();
+        results.sort_by_key(|(index, _)| *index);
+        Ok(results.into_iter().map(|(_, result)| result).collect())
+    }
+
+    // Delete files taken from the shared queue until it is empty, tagging each result
+    // with its position in the listing
+    async fn delete_file_worker(
+        &self,
+        queue: &std::sync::Mutex<std::iter::Enumerate<std::vec::IntoIter<String>>>,
+    ) -> Vec<(usize, FileDeleteResult)> {
+        let mut results = Vec::new();
+        while let Some((index, filename)) = queue.lock().ok().and_then(|mut files| files.next()) {
+            let outcome = match self.delete_file(&filename).await {
+                Ok(()) => FileDeleteOutcome::Deleted,
+                Err(e) => {
+                    warn!("Failed to delete {filename}: {e}");
+                    FileDeleteOutcome::Failed(e)
+                }
+            };
+            results.push((index, FileDeleteResult { filename, outcome }));
+        }
+        results
+    }
+
+    // Receive file content without decoding it
+    async fn receive_file_bytes(&self, filename: &str) -> Result<Vec<u8>, ClientError> {
+        let command = ReceiveFile::new(filename.to_string(), self.current_config().text_encoding);
+        let response = self.send_command_with_retry(command, Division::File).await?;
+        parse_file_content(&response).map_err(ClientError::from)
+    }
+
+    // Reject operations more dangerous than the configured maximum
+    pub(crate) fn check_danger_level(
+        &self,
+        level: DangerLevel,
+        operation: &str,
+    ) -> Result<(), ClientError> {
+        if level > self.current_config().max_danger_level {
+            return Err(ClientError::OperationNotPermitted(format!(
+                "{operation} is a {level:?} operation (max_danger_level: {:?})",
+                self.current_config().max_danger_level
+            )));
+        }
+        Ok(())
+    }
+
+    // Reject write requests unless `max_danger_level` permits writes
+    fn check_write_permitted(&self, request: &RequestParams) -> Result<(), ClientError> {
+        if !request.service.is_write() {
+            return Ok(());
+        }
+        let operation = format!("{:?} of command 0x{:02x}", request.service, request.command);
+        self.check_danger_level(DangerLevel::Write, &operation)
+    }
+
+    // Start the audit record of `command` if it writes and a sink is set, reading the data
+    // it replaces if requested
+    async fn begin_audit(
+        &self,
+        request: &RequestParams,
+        payload: &[u8],
+    ) -> Option<(Auditor, AuditRecord)> {
+        let auditor = self.auditor_for(request.service)?;
+        let previous = match read_counterpart(request.command, request.service) {
+            Some(service)
+                if auditor.options.read_previous && request.division == Division::Robot =>
+            {
+                // Plural reads take the count that starts the plural write payload
+                let count = if service == Service::ReadPlural {
+                    payload.get(..4).unwrap_or_default().to_vec()
+                } else {
+                    Vec::new()
+                };
+                let read = PipelinedRequest {
+                    request: RequestParams { service, ..request.clone() },
+                    payload: count,
+                };
+                match self.send_pipelined(&[read]).await {
+                    Ok(mut responses) => responses.pop(),
+                    Err(e) => {
+                        warn!(
+                            "Failed to read the value replaced by 0x{:02x}: {e}",
+                            request.command
+                        );
+                        None
+                    }
+                }
+            }
+            _ => None,
+        };
+        let record = AuditRecord {
+            time: SystemTime::now(),
+            actor: auditor.options.actor.clone(),
+            controller: self.inner.remote_addr.clone(),
+            division: request.division,
+            command: request.command,
+            instance: request.instance,
+            attribute: request.attribute,
+            service: request.service,
+            payload: payload.to_vec(),
+            previous,
+            outcome: Ok(()),
+        };
+        Some((auditor, record))
+    }
+
+    // Reject file names the controller would not accept before sending the request
+    fn validate_filename(&self, filename: &str) -> Result<(), ClientError> {
+        let config = self.current_config();
+        let rules =
+            config.file_name_rules.unwrap_or_else(|| config.controller_model.file_name_rules());
+        validate_filename(filename, rules, config.text_encoding).map_err(ClientError::from)
+    }
+
+    // Value of `variable` in the response to its single read
+    fn decode_variable(
+        &self,
+        variable: VariableRef,
+        data: &[u8],
+    ) -> Result<VariableValue, ClientError> {
+        let encoding = self.current_config().text_encoding;
+        Ok(match variable {
+            VariableRef::B(_) => VariableValue::Byte(u8::deserialize(data, encoding)?),
+            VariableRef::I(_) => VariableValue::Integer(i16::deserialize(data, encoding)?),
+            VariableRef::D(_) => VariableValue::Double(i32::deserialize(data, encoding)?),
+            VariableRef::R(_) => VariableValue::Real(f32::deserialize(data, encoding)?),
+            VariableRef::S(_) => {
+                let text = data.split(|&b| b == 0).next().unwrap_or_default();
+                VariableValue::String(self.decode_text(text, &variable.to_string())?)
+            }
+        })
+    }
+
+    // Decode text received from the controller, reporting characters that could not be
+    // decoded with the configured encoding
+    fn decode_text(&self, bytes: &[u8], context: &str) -> Result<String, ClientError> {
+        let (text, had_replacements) =
+            moto_hses_proto::encoding_utils::decode_string_with_replacements(
+                bytes,
+                self.current_config().text_encoding,
+            );
+        self.report_replacements(had_replacements, context, || format!("{text:?}"))?;
+        Ok(text)
+    }
+
+    // Warn about text decoded with replacement characters, or fail with strict encoding;
+    // `decoded` describes the text as decoded
+    pub(crate) fn report_replacements(
+        &self,
+        had_replacements: bool,
+        context: &str,
+        decoded: impl FnOnce() -> String,
+    ) -> Result<(), ClientError> {
+        if !had_replacements {
+            return Ok(());
+        }
+        let encoding = self.current_config().text_encoding;
+        if self.inner.strict_encoding.load(Ordering::Relaxed) {
+            return Err(ClientError::EncodingError(format!(
+                "{context} is not valid {encoding:?} text"
+            )));
+        }
+        warn!("{context} is not valid {encoding:?} text; decoded as {}", decoded());
+        Ok(())
+    }
+
     // Command sending with retry logic (returns raw bytes)
     async fn send_command_with_retry<C: Command + Send + Sync>(
         &self,
         command: C,
         division: Division,
     ) -> Result<Vec<u8>, ClientError> {
+        let (response, _meta) = self.send_command_with_meta(command, division).await?;
+        Ok(response)
+    }
+
+    // Command sending with retry logic, also returning the request metadata; write
+    // requests are recorded once, however many attempts they take
+    async fn send_command_with_meta<C: Command + Send + Sync>(
+        &self,
+        command: C,
+        division: Division,
+    ) -> Result<(Vec<u8>, ResponseMeta), ClientError> {
+        let payload = command.serialize()?;
+        self.send_request_with_meta(RequestParams::of(&command, division), payload).await
+    }
+
+    // Send a request with retry logic, recording it in the audit log and the transcript
+    pub(crate) async fn send_request_with_meta(
+        &self,
+        request: RequestParams,
+        payload: Vec<u8>,
+    ) -> Result<(Vec<u8>, ResponseMeta), ClientError> {
+        self.check_write_permitted(&request)?;
+        let robot = request.division == Division::Robot;
+        let write = robot && request.service.is_write();
+        let _sequence = match self.current_config().write_pacing {
+            Some(pacing) if write => {
+                let sequence = self.inner.write_sequence.lock().await;
+                self.wait_write_gap(pacing).await;
+                Some(sequence)
+            }
+            _ => None,
+        };
+        let _slots = self.acquire_request_slots(1).await?;
+        let audit = self.begin_audit(&request, &payload).await;
+        let result = self.send_request_attempts(&request, &payload).await;
+        finish_audit(audit, result.as_ref().map(|_| ()));
+        self.record_exchange(&request, &payload, result.as_ref().map(|(response, _)| response));
+        if robot {
+            self.mark_paced_response(write);
+        }
+        result
+    }
+
+    // Wait until the gap of the write pacing has passed since the last counted response
+    async fn wait_write_gap(&self, pacing: WritePacing) {
+        let last = self.inner.last_paced_response.lock().ok().and_then(|last| *last);
+        if let Some(last) = last {
+            tokio::time::sleep_until((last + pacing.min_gap).into()).await;
+        }
+    }
+
+    // Record the end of a robot control request that the write pacing counts
+    fn mark_paced_response(&self, write: bool) {
+        if !self.current_config().write_pacing.is_some_and(|pacing| write || pacing.after_reads) {
+            return;
+        }
+        if let Ok(mut last) = self.inner.last_paced_response.lock() {
+            *last = Some(Instant::now());
+        }
+    }
+
+    // Send a command until it is answered or the retries are exhausted
+    //
+    // With an operation deadline, an attempt still waiting at the deadline is abandoned and
+    // no retry is started that could not wait for its response within it. A request that
+    // is not idempotent is not resent after a timeout before the controller status shows
+    // that it was not executed.
+    async fn send_request_attempts(
+        &self,
+        request: &RequestParams,
+        payload: &[u8],
+    ) -> Result<(Vec<u8>, ResponseMeta), ClientError> {
         let mut last_error = None;
         let mut attempts = 0;
-        let max_attempts = self.config.retry_count + 1; // Initial attempt + retries
+        // Taken once, so that all attempts follow the same configuration
+        let config = self.current_config();
+        let max_attempts = config.retry_count + 1; // Initial attempt + retries
+        let deadline = config.operation_deadline.map(|limit| (Instant::now() + limit, limit));
+        let deadline_exceeded = |attempts: u32, limit: Duration| {
+            ClientError::TimeoutError(format!(
+                "Command 0x{:02x} not answered within the operation deadline of {limit:?} \
+                 ({attempts} attempts)",
+                request.command
+            ))
+        };
 
         while attempts < max_attempts {
-            match self.send_command_once(&command, division).await {
-                Ok(response) => return Ok(response),
+            let sent_at = Instant::now();
+            let request_id = self.inner.request_id.fetch_add(1, Ordering::Relaxed);
+            let attempt =
+                self.send_request_as(&self.inner.transport, request_id, request, payload.to_vec());
+            let result = match deadline {
+                Some((deadline, limit)) => {
+                    match tokio::time::timeout_at(deadline.into(), attempt).await {
+                        Ok(result) => result,
+                        Err(_) => return Err(deadline_exceeded(attempts + 1, limit)),
+                    }
+                }
+                None => attempt.await,
+            };
+            match result {
+                Ok(response) => {
+                    let meta = ResponseMeta {
+                        request_id,
+                        attempts: attempts + 1,
+                        rtt: sent_at.elapsed(),
+                        command: request.command,
+                        instance: request.instance,
+                    };
+                    self.on_response(&meta);
+                    return Ok((response, meta));
+                }
                 Err(e) => {
-                    last_error = Some(e);
+                    let timed_out = matches!(e, ClientError::TimeoutError(_));
+                    if timed_out {
+                        self.record_timeout(request.command);
+                    }
                     attempts += 1;
+                    if timed_out && !request.idempotent && self.executed_unanswered(request).await?
+                    {
+                        let meta = ResponseMeta {
+                            request_id,
+                            attempts,
+                            rtt: sent_at.elapsed(),
+                            command: request.command,
+                            instance: request.instance,
+                        };
+                        return Ok((Vec::new(), meta));
+                    }
+                    last_error = Some(e);
 
                     if attempts < max_attempts {
-                        sleep(self.config.retry_delay).await;
+                        if let Some((deadline, limit)) = deadline
+                            && Instant::now() + config.retry_delay >= deadline
+                        {
+                            return Err(deadline_exceeded(attempts, limit));
+                        }
+                        sleep(config.retry_delay).await;
                     }
                 }
             }
@@ -836,38 +2821,277 @@ impl HsesClient {
         Err(last_error.unwrap_or_else(|| ClientError::SystemError("Unknown error".to_string())))
     }
 
-    // Single command sending (no retry, returns raw bytes)
+    // Whether the controller executed a request that is not idempotent although its
+    // response did not arrive, judged by whether the robot is running
+    //
+    // A job that was started and finished again within the timeout looks like one that was
+    // not started. Fails if the status cannot be read within the configured retries, since
+    // the request must then not be resent.
+    async fn executed_unanswered(&self, request: &RequestParams) -> Result<bool, ClientError> {
+        let mut attempts = 0;
+        let response = loop {
+            attempts += 1;
+            match self.send_command_once(&ReadStatus, Division::Robot).await {
+                Ok((response, _request_id)) => break response,
+                Err(e) if attempts > self.current_config().retry_count => {
+                    return Err(ClientError::TimeoutError(format!(
+                        "Command 0x{:02x} not answered and not resent, as the status read to \
+                         tell whether it was executed failed: {e}",
+                        request.command
+                    )));
+                }
+                Err(_) => sleep(self.current_config().retry_delay).await,
+            }
+        };
+        let status = Status::deserialize(&response, self.current_config().text_encoding)?;
+        if status.is_running() {
+            info!(
+                "Command 0x{:02x} not answered, but the robot is running; not resent",
+                request.command
+            );
+        }
+        Ok(status.is_running())
+    }
+
+    // Record the RTT of an answered request and pass its metadata to the response hook
+    fn on_response(&self, meta: &ResponseMeta) {
+        self.record_response(meta);
+        let hook = self.inner.response_hook.lock().ok().and_then(|hook| hook.clone());
+        if let Some(hook) = hook {
+            hook(meta);
+        }
+    }
+
+    // Single command sending (no retry, returns raw bytes and the request ID used)
     async fn send_command_once<C: Command + Send + Sync>(
         &self,
         command: &C,
         division: Division,
+    ) -> Result<(Vec<u8>, u8), ClientError> {
+        self.send_command_on(&self.inner.transport, command, division).await
+    }
+
+    // Send a robot command through the priority lane, retrying until `deadline`
+    //
+    // The lane has its own socket, so the command is neither queued behind nor mixed up
+    // with the responses to requests in flight on the regular socket.
+    async fn send_priority_command<C: Command + Send + Sync>(
+        &self,
+        command: &C,
+        deadline: Instant,
     ) -> Result<Vec<u8>, ClientError> {
-        let request_id = self.inner.request_id.fetch_add(1, Ordering::Relaxed);
+        let request = RequestParams::of(command, Division::Robot);
         let payload = command.serialize()?;
+        let audit = self.begin_audit(&request, &payload).await;
+        let exchange = async {
+            // Held across retries so concurrent emergency commands do not interleave
+            #[allow(clippy::significant_drop_tightening)]
+            let transport = self.inner.priority_transport.lock().await;
+            loop {
+                match self.send_request_on(&transport, &request, payload.clone()).await {
+                    Ok((response, _request_id)) => return Ok(response),
+                    Err(ClientError::ProtocolError(e)) => return Err(e.into()),
+                    Err(e) => {
+                        debug!("Retrying priority command 0x{:02x}: {e}", C::command_id());
+                        sleep(EMERGENCY_POLL_INTERVAL).await;
+                    }
+                }
+            }
+        };
+        let result = tokio::time::timeout_at(deadline.into(), exchange).await.map_err(|_| {
+            ClientError::TimeoutError(format!(
+                "Priority command 0x{:02x} not acknowledged before the deadline",
+                C::command_id()
+            ))
+        });
+        let result = result.and_then(|result| result);
+        finish_audit(audit, result.as_ref().map(|_| ()));
+        self.record_exchange(&request, &payload, result.as_ref());
+        result
+    }
+
+    // Send a command on `transport` and wait for its response on the same transport
+    async fn send_command_on<C: Command + Send + Sync>(
+        &self,
+        transport: &RoutedTransport,
+        command: &C,
+        division: Division,
+    ) -> Result<(Vec<u8>, u8), ClientError> {
+        let payload = command.serialize()?;
+        self.send_request_on(transport, &RequestParams::of(command, division), payload).await
+    }
+
+    // Send a request on `transport` and wait for its response on the same transport
+    async fn send_request_on(
+        &self,
+        transport: &RoutedTransport,
+        request: &RequestParams,
+        payload: Vec<u8>,
+    ) -> Result<(Vec<u8>, u8), ClientError> {
+        let request_id = self.inner.request_id.fetch_add(1, Ordering::Relaxed);
+        let response = self.send_request_as(transport, request_id, request, payload).await?;
+        Ok((response, request_id))
+    }
 
+    // Send a request with `request_id` on `transport` and wait for its response
+    async fn send_request_as(
+        &self,
+        transport: &RoutedTransport,
+        request_id: u8,
+        request: &RequestParams,
+        payload: Vec<u8>,
+    ) -> Result<Vec<u8>, ClientError> {
         // Create and send message
-        let request = RequestParams {
-            division,
-            command: C::command_id(),
-            instance: command.instance(),
-            attribute: command.attribute(),
-            service: command.service(),
-        };
         let sequence = SequenceParams {
             request_id,
-            block_number: 0u32, // Block number (0 for requests)
-            ack: 0x00,          // ACK (Request)
+            block_number: request.block_number, // Block number (0 for requests)
+            ack: 0x00,                          // ACK (Request)
         };
-        let message = Self::create_message(&request, &sequence, payload)?;
+        let message = Self::create_message(request, &sequence, payload)?;
         debug!("Sending message to {}: {} bytes", self.inner.remote_addr, message.len());
-        debug!("Message bytes: {message:02X?}");
-        self.inner.socket.send_to(&message, self.inner.remote_addr).await?;
+        crate::hses_trace!("Request", &message);
+        // Registered before sending, so that a response read by another request is kept
+        let mut mailbox = transport.register(&[request_id]);
+        transport.send_datagram(&message).await?;
 
         // Wait for response
-        let response = self.wait_for_response(request_id, division, command.service()).await?;
+        self.wait_for_response(transport, &mut mailbox, request_id, request).await
+    }
 
-        // Return raw response payload
-        Ok(response)
+    // Send robot requests back to back on the regular socket and collect their responses
+    // in request order
+    //
+    // Requests still unanswered after the timeout are sent again with new request IDs, up
+    // to the configured number of retries.
+    async fn send_pipelined(
+        &self,
+        requests: &[PipelinedRequest],
+    ) -> Result<Vec<Vec<u8>>, ClientError> {
+        if requests.len() > MAX_PIPELINED_REQUESTS {
+            return Err(ProtocolError::InvalidCount {
+                command: requests.first().map_or(0, |request| request.request.command),
+                got: requests.len(),
+                max: MAX_PIPELINED_REQUESTS,
+            }
+            .into());
+        }
+        for request in requests {
+            self.check_write_permitted(&request.request)?;
+        }
+        let _slots = self.acquire_request_slots(requests.len()).await?;
+
+        let config = self.current_config();
+        let mut responses: Vec<Option<Vec<u8>>> = vec![None; requests.len()];
+        let response_timeout = requests
+            .iter()
+            .map(|request| self.response_timeout(request.request.command))
+            .max()
+            .unwrap_or(config.timeout);
+        let mut attempts = 0;
+        loop {
+            attempts += 1;
+            let sent_at = Instant::now();
+            let mut pending = HashMap::new();
+            let mut messages = Vec::new();
+            for (index, request) in requests.iter().enumerate() {
+                if responses[index].is_some() {
+                    continue;
+                }
+                let request_id = self.inner.request_id.fetch_add(1, Ordering::Relaxed);
+                let sequence = SequenceParams { request_id, block_number: 0, ack: 0x00 };
+                messages.push(Self::create_message(
+                    &request.request,
+                    &sequence,
+                    request.payload.clone(),
+                )?);
+                pending.insert(request_id, index);
+            }
+            let request_ids: Vec<u8> = pending.keys().copied().collect();
+            let mut mailbox = self.inner.transport.register(&request_ids);
+            for message in &messages {
+                self.inner.transport.send_datagram(message).await?;
+            }
+
+            while !pending.is_empty() {
+                let Ok(received) =
+                    timeout(response_timeout, self.receive_response(&mut mailbox, &pending)).await
+                else {
+                    for index in pending.values() {
+                        self.record_timeout(requests[*index].request.command);
+                    }
+                    break;
+                };
+                let (request_id, response) = received?;
+                let Some(index) = pending.remove(&request_id) else {
+                    continue;
+                };
+                let request = &requests[index].request;
+                let meta = ResponseMeta {
+                    request_id,
+                    attempts,
+                    rtt: sent_at.elapsed(),
+                    command: request.command,
+                    instance: request.instance,
+                };
+                self.on_response(&meta);
+                responses[index] = Some(response?);
+            }
+            self.mark_paced_response(false);
+
+            if pending.is_empty() {
+                let responses: Vec<Vec<u8>> = responses.into_iter().flatten().collect();
+                for (request, response) in requests.iter().zip(&responses) {
+                    self.record_exchange(&request.request, &request.payload, Ok(response));
+                }
+                return Ok(responses);
+            }
+            if attempts > config.retry_count {
+                return Err(ClientError::TimeoutError(format!(
+                    "{} of {} pipelined requests unanswered",
+                    pending.len(),
+                    requests.len()
+                )));
+            }
+            sleep(config.retry_delay).await;
+        }
+    }
+
+    // Receive the next single-block response to one of the `pending` request IDs
+    async fn receive_response(
+        &self,
+        mailbox: &mut Mailbox<'_>,
+        pending: &HashMap<u8, usize>,
+    ) -> Result<(u8, Result<Vec<u8>, ClientError>), ClientError> {
+        let buffer_size = self.current_config().buffer_size;
+        loop {
+            let response_data = mailbox.recv(buffer_size).await?;
+            let response_data = response_data.as_slice();
+            let len = response_data.len();
+            if len < header::SIZE
+                || &response_data[..header::MAGIC.len()] != header::MAGIC
+                || response_data[header::ACK_OFFSET] != 0x01
+            {
+                continue;
+            }
+            let request_id = response_data[header::REQUEST_ID_OFFSET];
+            if !pending.contains_key(&request_id) {
+                continue;
+            }
+
+            let status = response_data[header::STATUS_OFFSET];
+            if status != 0x00 {
+                let error_message = Self::build_error_message(status, response_data);
+                return Ok((request_id, Err(ProtocolError::ServerError(error_message).into())));
+            }
+            let payload_size = Self::payload_size(response_data);
+            if len < header::SIZE + payload_size {
+                continue;
+            }
+            return Ok((
+                request_id,
+                Ok(response_data[header::SIZE..header::SIZE + payload_size].to_vec()),
+            ));
+        }
     }
 
     fn create_message(
@@ -893,9 +3117,10 @@ impl HsesClient {
 
         // Payload size
         let payload_len = u16::try_from(payload.len()).map_err(|_| {
-            ClientError::ProtocolError(moto_hses_proto::ProtocolError::InvalidMessage(
-                "Payload too large for protocol".to_string(),
-            ))
+            ClientError::ProtocolError(moto_hses_proto::ProtocolError::PayloadTooLarge {
+                size: payload.len(),
+                max: usize::from(u16::MAX),
+            })
         })?;
         message.extend_from_slice(&payload_len.to_le_bytes());
 
@@ -927,7 +3152,7 @@ impl HsesClient {
         message.push(request.attribute);
 
         // Service
-        message.push(request.service);
+        message.push(request.service.code());
 
         // Padding
         message.extend_from_slice(&0u16.to_le_bytes());
@@ -940,99 +3165,89 @@ impl HsesClient {
 
     async fn wait_for_response(
         &self,
+        transport: &RoutedTransport,
+        mailbox: &mut Mailbox<'_>,
         request_id: u8,
-        division: Division,
-        service: u8,
+        request: &RequestParams,
     ) -> Result<Vec<u8>, ClientError> {
-        let mut buffer = vec![0u8; self.config.buffer_size];
+        let RequestParams { division, service, .. } = *request;
+        let response_timeout = self.response_timeout(request.command);
+        let buffer_size = self.current_config().buffer_size;
         let mut all_payload = Vec::new();
         let mut expected_block_number = 1u32;
 
         loop {
-            let (len, _addr) =
-                timeout(self.config.timeout, self.inner.socket.recv_from(&mut buffer))
-                    .await
-                    .map_err(|_| ClientError::TimeoutError("Response timeout".to_string()))??;
-
-            let response_data = &buffer[..len];
+            let response_data = timeout(response_timeout, mailbox.recv(buffer_size))
+                .await
+                .map_err(|_| ClientError::TimeoutError("Response timeout".to_string()))??;
+            let response_data = response_data.as_slice();
 
             // Debug: Log received data
-            debug!("Received response: {len} bytes");
-            debug!("Response data: {response_data:02X?}");
-            if len >= 4 {
-                debug!("Magic bytes: {:?}", &response_data[0..4]);
-            }
-            if len >= 11 {
-                debug!("Request ID: 0x{:02x}", response_data[11]);
-            }
-            if len >= 10 {
-                debug!("ACK: 0x{:02x}", response_data[10]);
-            }
-
+            crate::hses_trace!("Received response", response_data);
             // Parse response header
-            if response_data.len() < 32 {
+            if response_data.len() < header::SIZE {
                 continue;
             }
+            debug!("Magic bytes: {:?}", &response_data[..header::MAGIC.len()]);
+            debug!("Request ID: 0x{:02x}", response_data[header::REQUEST_ID_OFFSET]);
+            debug!("ACK: 0x{:02x}", response_data[header::ACK_OFFSET]);
 
             // Verify magic bytes "YERC"
-            if &response_data[0..4] != b"YERC" {
+            if &response_data[..header::MAGIC.len()] != header::MAGIC {
                 continue;
             }
 
-            // Check request ID (byte 11)
-            let response_request_id = response_data[11];
+            // Check request ID
+            let response_request_id = response_data[header::REQUEST_ID_OFFSET];
             if response_request_id != request_id {
                 continue;
             }
 
-            // Check ACK (byte 10, should be 0x01 for response)
-            let ack = response_data[10];
+            // Check ACK (should be 0x01 for response)
+            let ack = response_data[header::ACK_OFFSET];
             if ack != 0x01 {
                 continue;
             }
 
-            // Check status (byte 25 in response sub-header)
-            if response_data.len() >= 26 {
-                let status = response_data[25];
-                if status != 0x00 {
-                    let error_message = Self::build_error_message(status, response_data);
-                    return Err(ClientError::ProtocolError(
-                        moto_hses_proto::ProtocolError::ServerError(error_message),
-                    ));
-                }
+            // Check status in the response sub-header
+            let status = response_data[header::STATUS_OFFSET];
+            if status != 0x00 {
+                let error_message = Self::build_error_message(status, response_data);
+                return Err(ClientError::ProtocolError(
+                    moto_hses_proto::ProtocolError::ServerError(error_message),
+                ));
             }
 
-            // Extract payload size (bytes 6-7)
-            let payload_size = u16::from_le_bytes([response_data[6], response_data[7]]) as usize;
+            let payload_size = Self::payload_size(response_data);
 
             // Ensure we have enough data
-            if response_data.len() < 32 + payload_size {
+            if response_data.len() < header::SIZE + payload_size {
                 continue;
             }
 
-            // Extract payload (starting from byte 32)
-            let payload = response_data[32..32 + payload_size].to_vec();
+            // Extract payload (following the header)
+            let payload = response_data[header::SIZE..header::SIZE + payload_size].to_vec();
 
-            // Extract block number (bytes 12-15)
+            // Extract block number
             let block_number = u32::from_le_bytes([
-                response_data[12],
-                response_data[13],
-                response_data[14],
-                response_data[15],
+                response_data[header::BLOCK_NUMBER_OFFSET],
+                response_data[header::BLOCK_NUMBER_OFFSET + 1],
+                response_data[header::BLOCK_NUMBER_OFFSET + 2],
+                response_data[header::BLOCK_NUMBER_OFFSET + 3],
             ]);
 
-            // Check if this is a single-block response (block_number == 0x8000_0000)
-            if block_number == 0x8000_0000 {
+            // Check if this is a single-block response (only the last block flag set)
+            if block_number == header::LAST_BLOCK_FLAG {
                 debug!("Received single-block response");
                 return Ok(payload);
             }
 
             // Multi-block response handling for file control commands
-            // Only read_file_list (0x32) and receive_file (0x16) use multi-block responses
-            if service == 0x32 || service == 0x16 {
-                // Check if this is the final block (0x8000_0000 flag)
-                let is_final_block = (block_number & 0x8000_0000) != 0;
-                let actual_block_number = block_number & 0x7FFF_FFFF;
+            // Only read_file_list and receive_file use multi-block responses
+            if matches!(service, Service::FileList | Service::FileReceive) {
+                // Check if this is the final block
+                let is_final_block = (block_number & header::LAST_BLOCK_FLAG) != 0;
+                let actual_block_number = block_number & !header::LAST_BLOCK_FLAG;
 
                 debug!("Received block {actual_block_number} (final: {is_final_block})");
 
@@ -1048,8 +3263,9 @@ impl HsesClient {
                 all_payload.extend_from_slice(&payload);
 
                 // Send ACK packet for this block
-                if let Err(e) =
-                    self.send_ack_packet(request_id, block_number, division, service).await
+                if let Err(e) = self
+                    .send_ack_packet(transport, request_id, block_number, division, service)
+                    .await
                 {
                     debug!("Failed to send ACK packet: {e}");
                     // Continue anyway, as the main response was received
@@ -1065,14 +3281,22 @@ impl HsesClient {
                 expected_block_number += 1;
             } else {
                 // For other commands, treat as single-block response
-                debug!("Received single-block response for service 0x{service:02x}");
+                debug!("Received single-block response for service {service:?}");
                 return Ok(payload);
             }
         }
     }
 
+    // Payload size field of a response of at least `header::SIZE` bytes
+    fn payload_size(response_data: &[u8]) -> usize {
+        usize::from(u16::from_le_bytes([
+            response_data[header::PAYLOAD_SIZE_OFFSET],
+            response_data[header::PAYLOAD_SIZE_OFFSET + 1],
+        ]))
+    }
+
     /// Build error message with added status information
-    fn build_error_message(status: u8, response_data: &[u8]) -> String {
+    pub(crate) fn build_error_message(status: u8, response_data: &[u8]) -> String {
         let mut error_message = format!("Server returned error status: 0x{status:02x}");
 
         if let Some(added_status) = Self::read_added_status(response_data) {
@@ -1085,10 +3309,11 @@ impl HsesClient {
     /// Send ACK packet for file operations
     async fn send_ack_packet(
         &self,
+        transport: &RoutedTransport,
         request_id: u8,
         block_number: u32,
         division: Division,
-        service: u8,
+        service: Service,
     ) -> Result<(), ClientError> {
         let sequence = SequenceParams {
             request_id,
@@ -1101,6 +3326,8 @@ impl HsesClient {
             instance: 0x0000, // Instance (0x0000 for ACK)
             attribute: 0x00,  // Attribute (0x00 for ACK)
             service,          // Service (same as original request service)
+            idempotent: true,
+            block_number,
         };
         let ack_message = Self::create_message_common(
             &sequence,
@@ -1109,47 +3336,46 @@ impl HsesClient {
         )?;
 
         debug!("Sending ACK packet: {} bytes", ack_message.len());
-        debug!("ACK message bytes: {ack_message:02X?}");
+        crate::hses_trace!("ACK", &ack_message);
 
-        self.inner.socket.send_to(&ack_message, self.inner.remote_addr).await?;
+        transport.send_datagram(&ack_message).await?;
         Ok(())
     }
 
     /// Read added status from response data
     fn read_added_status(response_data: &[u8]) -> Option<u32> {
-        // Check if we have enough data for added status size (byte 26)
-        if response_data.len() < 27 {
-            return None;
+        let added_status_size = *response_data.get(header::ADDED_STATUS_SIZE_OFFSET)?;
+        let added_status = response_data.get(header::ADDED_STATUS_OFFSET..)?;
+        match (added_status_size, added_status) {
+            // 1 WORD data (2 bytes)
+            (1, [low, high, ..]) => Some(u32::from(u16::from_le_bytes([*low, *high]))),
+            // 2 WORD data (4 bytes)
+            (2, [b0, b1, b2, b3, ..]) => Some(u32::from_le_bytes([*b0, *b1, *b2, *b3])),
+            _ => None,
         }
+    }
+}
 
-        let added_status_size = response_data[26];
-        if added_status_size == 0 {
-            return None;
-        }
+// Most variables of type `T` a plural command transfers
+fn max_chunk<T: MultipleVariableCommandId>() -> usize {
+    usize::try_from(T::max_count()).unwrap_or(usize::MAX)
+}
 
-        match added_status_size {
-            1 => {
-                // 1 WORD data (2 bytes) - added status is at bytes 28-29
-                if response_data.len() >= 30 {
-                    Some(u32::from(u16::from_le_bytes([response_data[28], response_data[29]])))
-                } else {
-                    None
-                }
-            }
-            2 => {
-                // 2 WORD data (4 bytes) - added status is at bytes 28-31
-                if response_data.len() >= 32 {
-                    Some(u32::from_le_bytes([
-                        response_data[28],
-                        response_data[29],
-                        response_data[30],
-                        response_data[31],
-                    ]))
-                } else {
-                    None
-                }
-            }
-            _ => None,
-        }
+fn variable_number(index: usize) -> Result<u16, ClientError> {
+    u16::try_from(index).map_err(|_| {
+        ProtocolError::InvalidMessage(format!("variable number {index} out of range")).into()
+    })
+}
+
+// Single variable command (0x7A-0x7E) of the same type as a plural command (0x302-0x306)
+const fn single_variable_command(plural_command: u16) -> u16 {
+    plural_command - 0x302 + 0x7a
+}
+
+// Pass the audit record started by `begin_audit` to the sink with the outcome
+fn finish_audit(audit: Option<(Auditor, AuditRecord)>, outcome: Result<(), &ClientError>) {
+    if let Some((auditor, mut record)) = audit {
+        record.outcome = outcome.map_err(ToString::to_string);
+        auditor.sink.record(&record);
     }
 }
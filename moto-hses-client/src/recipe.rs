@@ -0,0 +1,332 @@
+//! Recipes
+//!
+//! A [`Recipe`] is a named set of variable values, e.g. the parameters of one product.
+//! [`HsesClient::apply_recipe`](crate::HsesClient::apply_recipe) writes and verifies them,
+//! and [`HsesClient::detect_recipe`](crate::HsesClient::detect_recipe) finds the recipe the
+//! controller variables currently match. Both read the variables through a
+//! [`VariableSnapshot`].
+//!
+//! With the `serde` feature, recipes are stored as a name and a table of variables keyed
+//! by variable name, e.g. in TOML:
+//!
+//! ```toml
+//! name = "part-a"
+//!
+//! [variables]
+//! B001 = 3
+//! D010 = 25000
+//! R002 = 1.5
+//! S000 = "PART-A"
+//! ```
+
+use crate::snapshot::{VariableProfile, VariableSnapshot};
+use crate::types::ClientError;
+
+/// Value of a recipe variable; the variant selects the variable type
+#[derive(Debug, Clone, PartialEq)]
+pub enum VariableValue {
+    /// Byte variable (B)
+    Byte(u8),
+    /// Integer variable (I)
+    Integer(i16),
+    /// Double precision integer variable (D)
+    Double(i32),
+    /// Real variable (R)
+    Real(f32),
+    /// Character variable (S)
+    String(String),
+}
+
+impl VariableValue {
+    /// Letter of the variable type, e.g. 'D'
+    #[must_use]
+    pub const fn type_letter(&self) -> char {
+        match self {
+            Self::Byte(_) => 'B',
+            Self::Integer(_) => 'I',
+            Self::Double(_) => 'D',
+            Self::Real(_) => 'R',
+            Self::String(_) => 'S',
+        }
+    }
+}
+
+/// Value assigned to one variable by a recipe
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecipeAssignment {
+    pub index: u16,
+    pub value: VariableValue,
+}
+
+impl RecipeAssignment {
+    /// Name of the variable, e.g. "D010"
+    #[must_use]
+    pub fn variable_name(&self) -> String {
+        format!("{}{:03}", self.value.type_letter(), self.index)
+    }
+
+    // Value of the assigned variable in `snapshot`, if the snapshot holds it
+    fn snapshot_value(&self, snapshot: &VariableSnapshot) -> Option<VariableValue> {
+        let index = usize::from(self.index);
+        match self.value {
+            VariableValue::Byte(_) => snapshot.bytes.get(index).copied().map(VariableValue::Byte),
+            VariableValue::Integer(_) => {
+                snapshot.integers.get(index).copied().map(VariableValue::Integer)
+            }
+            VariableValue::Double(_) => {
+                snapshot.doubles.get(index).copied().map(VariableValue::Double)
+            }
+            VariableValue::Real(_) => snapshot.reals.get(index).copied().map(VariableValue::Real),
+            VariableValue::String(_) => {
+                snapshot.strings.get(index).cloned().map(VariableValue::String)
+            }
+        }
+    }
+}
+
+/// Named set of variable values
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "serde_format::RecipeFile", into = "serde_format::RecipeFile")
+)]
+pub struct Recipe {
+    pub name: String,
+    pub assignments: Vec<RecipeAssignment>,
+}
+
+impl Recipe {
+    #[must_use]
+    pub fn new(name: impl Into<String>) -> Self {
+        Self { name: name.into(), assignments: Vec::new() }
+    }
+
+    /// Assign `value` to variable `index` of the type of the value
+    #[must_use]
+    pub fn with_value(mut self, index: u16, value: VariableValue) -> Self {
+        self.assignments.push(RecipeAssignment { index, value });
+        self
+    }
+
+    /// Assign a value given as text to the variable named `variable`, e.g. "R002" and "1.5"
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ClientError::InvalidVariable`] if the name or the value is invalid for
+    /// the variable type
+    pub fn with_text(self, variable: &str, value: &str) -> Result<Self, ClientError> {
+        let (letter, index) = parse_variable_name(variable)?;
+        let invalid =
+            || ClientError::InvalidVariable(format!("invalid value '{value}' for {variable}"));
+        let value = match letter {
+            'B' => VariableValue::Byte(value.trim().parse().map_err(|_| invalid())?),
+            'I' => VariableValue::Integer(value.trim().parse().map_err(|_| invalid())?),
+            'D' => VariableValue::Double(value.trim().parse().map_err(|_| invalid())?),
+            'R' => VariableValue::Real(value.trim().parse().map_err(|_| invalid())?),
+            _ => VariableValue::String(value.to_string()),
+        };
+        Ok(self.with_value(index, value))
+    }
+
+    /// Profile of a snapshot holding every variable of the recipe
+    #[must_use]
+    pub fn profile(&self) -> VariableProfile {
+        let mut profile = VariableProfile::uniform(0);
+        for assignment in &self.assignments {
+            let count = match assignment.value {
+                VariableValue::Byte(_) => &mut profile.byte,
+                VariableValue::Integer(_) => &mut profile.integer,
+                VariableValue::Double(_) => &mut profile.double,
+                VariableValue::Real(_) => &mut profile.real,
+                VariableValue::String(_) => &mut profile.string,
+            };
+            *count = (*count).max(assignment.index.saturating_add(1));
+        }
+        profile
+    }
+
+    /// Assignments whose variable holds a different value in `snapshot`, or is not in it
+    #[must_use]
+    pub fn mismatches(&self, snapshot: &VariableSnapshot) -> Vec<&RecipeAssignment> {
+        self.assignments
+            .iter()
+            .filter(|assignment| {
+                assignment.snapshot_value(snapshot).as_ref() != Some(&assignment.value)
+            })
+            .collect()
+    }
+
+    /// Whether every variable of the recipe holds its value in `snapshot`
+    #[must_use]
+    pub fn matches(&self, snapshot: &VariableSnapshot) -> bool {
+        self.mismatches(snapshot).is_empty()
+    }
+}
+
+// Profile of a snapshot holding every variable of all `recipes`
+pub(crate) fn combined_profile(recipes: &[Recipe]) -> VariableProfile {
+    recipes.iter().map(Recipe::profile).fold(VariableProfile::uniform(0), |a, b| VariableProfile {
+        byte: a.byte.max(b.byte),
+        integer: a.integer.max(b.integer),
+        double: a.double.max(b.double),
+        real: a.real.max(b.real),
+        string: a.string.max(b.string),
+    })
+}
+
+// Split a variable name such as "D010" into its type letter and number
+fn parse_variable_name(name: &str) -> Result<(char, u16), ClientError> {
+    let invalid = || ClientError::InvalidVariable(format!("invalid variable name '{name}'"));
+    let name = name.trim();
+    let letter = name.chars().next().ok_or_else(invalid)?.to_ascii_uppercase();
+    if !matches!(letter, 'B' | 'I' | 'D' | 'R' | 'S') {
+        return Err(invalid());
+    }
+    let index = name[1..].parse().map_err(|_| invalid())?;
+    Ok((letter, index))
+}
+
+#[cfg(feature = "serde")]
+mod serde_format {
+    use std::collections::BTreeMap;
+
+    use super::{Recipe, RecipeAssignment, VariableValue, parse_variable_name};
+    use crate::types::ClientError;
+
+    // Stored form of a recipe: variables keyed by name
+    #[derive(serde::Serialize, serde::Deserialize)]
+    pub struct RecipeFile {
+        name: String,
+        variables: BTreeMap<String, FileValue>,
+    }
+
+    #[derive(serde::Serialize, serde::Deserialize)]
+    #[serde(untagged)]
+    enum FileValue {
+        Integer(i64),
+        Real(f64),
+        Text(String),
+    }
+
+    impl TryFrom<RecipeFile> for Recipe {
+        type Error = ClientError;
+
+        fn try_from(file: RecipeFile) -> Result<Self, Self::Error> {
+            let mut recipe = Self::new(file.name);
+            for (variable, value) in file.variables {
+                let (letter, index) = parse_variable_name(&variable)?;
+                let invalid =
+                    || ClientError::InvalidVariable(format!("invalid value for {variable}"));
+                let value = match (letter, value) {
+                    ('B', FileValue::Integer(value)) => {
+                        VariableValue::Byte(value.try_into().map_err(|_| invalid())?)
+                    }
+                    ('I', FileValue::Integer(value)) => {
+                        VariableValue::Integer(value.try_into().map_err(|_| invalid())?)
+                    }
+                    ('D', FileValue::Integer(value)) => {
+                        VariableValue::Double(value.try_into().map_err(|_| invalid())?)
+                    }
+                    #[allow(clippy::cast_possible_truncation, clippy::cast_precision_loss)]
+                    ('R', FileValue::Integer(value)) => VariableValue::Real(value as f32),
+                    #[allow(clippy::cast_possible_truncation)]
+                    ('R', FileValue::Real(value)) => VariableValue::Real(value as f32),
+                    ('S', FileValue::Text(value)) => VariableValue::String(value),
+                    _ => return Err(invalid()),
+                };
+                recipe.assignments.push(RecipeAssignment { index, value });
+            }
+            Ok(recipe)
+        }
+    }
+
+    impl From<Recipe> for RecipeFile {
+        fn from(recipe: Recipe) -> Self {
+            let variables = recipe
+                .assignments
+                .into_iter()
+                .map(|assignment| {
+                    let name = assignment.variable_name();
+                    let value = match assignment.value {
+                        VariableValue::Byte(value) => FileValue::Integer(value.into()),
+                        VariableValue::Integer(value) => FileValue::Integer(value.into()),
+                        VariableValue::Double(value) => FileValue::Integer(value.into()),
+                        VariableValue::Real(value) => FileValue::Real(value.into()),
+                        VariableValue::String(value) => FileValue::Text(value),
+                    };
+                    (name, value)
+                })
+                .collect();
+            Self { name: recipe.name, variables }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn recipe() -> Recipe {
+        Recipe::new("part-a")
+            .with_value(1, VariableValue::Byte(3))
+            .with_value(10, VariableValue::Double(25_000))
+            .with_value(2, VariableValue::Real(1.5))
+            .with_value(0, VariableValue::String("PART-A".to_string()))
+    }
+
+    #[test]
+    fn test_recipe_profile_and_matching() {
+        let recipe = recipe();
+        assert_eq!(
+            recipe.profile(),
+            VariableProfile { byte: 2, integer: 0, double: 11, real: 3, string: 1 }
+        );
+
+        let mut snapshot = VariableSnapshot {
+            bytes: vec![0, 3],
+            doubles: vec![0; 11],
+            reals: vec![0.0, 0.0, 1.5],
+            strings: vec!["PART-A".to_string()],
+            ..VariableSnapshot::default()
+        };
+        let mismatches: Vec<String> =
+            recipe.mismatches(&snapshot).iter().map(|a| a.variable_name()).collect();
+        assert_eq!(mismatches, vec!["D010"]);
+
+        snapshot.doubles[10] = 25_000;
+        assert!(recipe.matches(&snapshot));
+        snapshot.strings.clear();
+        assert!(!recipe.matches(&snapshot));
+    }
+
+    #[test]
+    fn test_recipe_with_text() {
+        let parsed = Recipe::new("part-a")
+            .with_text("b001", "3")
+            .and_then(|recipe| recipe.with_text("D010", "25000"))
+            .and_then(|recipe| recipe.with_text("R002", "1.5"))
+            .and_then(|recipe| recipe.with_text("S000", "PART-A"));
+        assert_eq!(parsed.ok(), Some(recipe()));
+
+        assert!(Recipe::new("x").with_text("B001", "256").is_err());
+        assert!(Recipe::new("x").with_text("P001", "1").is_err());
+        assert!(Recipe::new("x").with_text("D", "1").is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn test_recipe_serde_format() {
+        let json =
+            r#"{"name":"part-a","variables":{"B001":3,"D010":25000,"R002":1.5,"S000":"PART-A"}}"#;
+        let parsed: Recipe = serde_json::from_str(json).unwrap();
+        let mut expected = recipe();
+        expected.assignments.sort_by_key(RecipeAssignment::variable_name);
+        assert_eq!(parsed, expected);
+        assert_eq!(serde_json::to_string(&parsed).unwrap(), json);
+
+        let json = r#"{"name":"x","variables":{"B001":300}}"#;
+        assert!(serde_json::from_str::<Recipe>(json).is_err());
+    }
+}
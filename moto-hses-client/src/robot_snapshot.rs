@@ -0,0 +1,27 @@
+//! Robot state snapshots
+//!
+//! [`HsesClient::read_snapshot`](crate::HsesClient::read_snapshot) reads the status, the
+//! positions of a set of control groups, the executing job of the master task and the
+//! number of active alarms into one [`RobotSnapshot`]. The requests are pipelined: all
+//! are sent before the first response is awaited, so the fields are sampled within about
+//! one round trip of each other, e.g. for telemetry frames captured at a fixed rate.
+
+use std::time::{Duration, SystemTime};
+
+use moto_hses_proto::{ExecutingJobInfo, Position, Status};
+
+/// State of the robot read with one pipelined exchange
+#[derive(Debug, Clone, PartialEq)]
+pub struct RobotSnapshot {
+    /// Time the requests were sent
+    pub captured_at: SystemTime,
+    /// Time from sending the first request until the last response arrived
+    pub skew: Duration,
+    pub status: Status,
+    /// Current position of each control group, in the order the groups were requested
+    pub positions: Vec<Position>,
+    /// Job executed by the master task
+    pub executing_job: ExecutingJobInfo,
+    /// Number of active alarms (0-4)
+    pub active_alarm_count: usize,
+}
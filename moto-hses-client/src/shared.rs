@@ -3,13 +3,27 @@
 //! This module provides `SharedHsesClient`, a thread-safe wrapper around `HsesClient`
 //! that can be safely shared across multiple tasks and threads.
 
+use crate::emergency::{EmergencyHoldOptions, EmergencyHoldReport, PriorityLane};
+use crate::job_diff::JobDiff;
+use crate::jog::{JogDeadman, JogDirection, JogReport};
+use crate::recipe::{Recipe, VariableValue};
+use crate::robot_snapshot::RobotSnapshot;
+use crate::snapshot::{VariableProfile, VariableSnapshot};
 use crate::traits::HsesClientOps;
-use crate::types::{ClientError, HsesClient};
+use crate::trajectory::{TrajectoryControl, TrajectoryOptions, TrajectoryReport};
+use crate::types::{
+    CartesianDelta, ClientConfig, ClientError, DeleteFilesOptions, FileDeleteResult, HsesClient,
+    IncrementalMove, IncrementalMoveOptions, JobRunReport, ResponseMeta,
+};
+use crate::watch::VariableRef;
 use moto_hses_proto::commands::JobSelectType;
 use moto_hses_proto::{
-    Alarm, AlarmAttribute, CycleMode, ExecutingJobInfo, Position, Status, StatusData1, StatusData2,
+    Alarm, AlarmAttribute, AlarmCategory, BasePosition, CycleMode, ExecutingJobInfo, MoveFrame,
+    MoveSpeed, Position, SelectedJob, StationPosition, Status, StatusData1, StatusData2,
+    SystemInfo,
 };
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::Mutex;
 
 /// A thread-safe wrapper around `HsesClient`
@@ -47,22 +61,45 @@ use tokio::sync::Mutex;
 #[derive(Clone)]
 pub struct SharedHsesClient {
     client: Arc<Mutex<HsesClient>>,
+    // Lane for emergency commands, unknown when created from an existing mutex
+    priority_lane: Option<PriorityLane>,
 }
 
 impl SharedHsesClient {
     /// Create a new thread-safe wrapper around an `HsesClient`
     #[must_use]
     pub fn new(client: HsesClient) -> Self {
-        Self { client: Arc::new(Mutex::new(client)) }
+        let priority_lane = Some(client.priority_lane());
+        Self { client: Arc::new(Mutex::new(client)), priority_lane }
     }
 
     /// Create a new `SharedHsesClient` from an existing `Arc<Mutex<HsesClient>>`
     ///
     /// This is useful when you already have an `Arc<Mutex<HsesClient>>` and want
-    /// to use the `HsesClientOps` trait methods.
+    /// to use the `HsesClientOps` trait methods. The priority lane of the client is not
+    /// accessible without the lock, so `emergency_hold` waits for other operations.
     #[must_use]
     pub const fn from_arc(client: Arc<Mutex<HsesClient>>) -> Self {
-        Self { client }
+        Self { client, priority_lane: None }
+    }
+
+    /// Change the configuration of the client, see [`HsesClient::update_config`]
+    ///
+    /// Does not wait for other operations on this client to finish when the client was
+    /// created with [`Self::new`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ClientError::SystemError`] if `update` changes the host, port or request
+    /// limits
+    pub async fn update_config(
+        &self,
+        update: impl FnOnce(&mut ClientConfig),
+    ) -> Result<(), ClientError> {
+        if let Some(lane) = &self.priority_lane {
+            return lane.client().update_config(update);
+        }
+        self.client.lock().await.update_config(update)
     }
 
     /// Get a reference to the inner `Arc<Mutex<HsesClient>>`
@@ -81,6 +118,16 @@ impl HsesClientOps for SharedHsesClient {
         client.read_status().await
     }
 
+    async fn read_status_with_meta(&self) -> Result<(Status, ResponseMeta), ClientError> {
+        let client = self.client.lock().await;
+        client.read_status_with_meta().await
+    }
+
+    async fn read_system_info(&self, instance: u16) -> Result<SystemInfo, ClientError> {
+        let client = self.client.lock().await;
+        client.read_system_info(instance).await
+    }
+
     async fn read_status_data1(&self) -> Result<StatusData1, ClientError> {
         let client = self.client.lock().await;
         client.read_status_data1().await
@@ -98,6 +145,60 @@ impl HsesClientOps for SharedHsesClient {
         client.read_position(control_group).await
     }
 
+    async fn read_snapshot(&self, control_groups: &[u8]) -> Result<RobotSnapshot, ClientError> {
+        let client = self.client.lock().await;
+        client.read_snapshot(control_groups).await
+    }
+
+    async fn move_incremental(
+        &self,
+        robot: u8,
+        delta: CartesianDelta,
+        options: &IncrementalMoveOptions,
+    ) -> Result<IncrementalMove, ClientError> {
+        let client = self.client.lock().await;
+        client.move_incremental(robot, delta, options).await
+    }
+
+    async fn move_pulse(
+        &self,
+        robot: u8,
+        joints: Vec<i32>,
+        speed: MoveSpeed,
+    ) -> Result<(), ClientError> {
+        let client = self.client.lock().await;
+        client.move_pulse(robot, joints, speed).await
+    }
+
+    /// Jog a robot while a deadman token is held
+    ///
+    /// Other operations on this client wait until the jog has stopped.
+    async fn jog(
+        &self,
+        robot: u8,
+        frame: MoveFrame,
+        direction: JogDirection,
+        speed: f32,
+        deadman: &JogDeadman,
+    ) -> Result<JogReport, ClientError> {
+        let client = self.client.lock().await;
+        client.jog(robot, frame, direction, speed, deadman).await
+    }
+
+    /// Move a robot through a sequence of positions
+    ///
+    /// Other operations on this client wait until the playback has ended.
+    async fn play_trajectory(
+        &self,
+        robot: u8,
+        points: &[Position],
+        options: &TrajectoryOptions,
+        control: &TrajectoryControl,
+    ) -> Result<TrajectoryReport, ClientError> {
+        let client = self.client.lock().await;
+        client.play_trajectory(robot, points, options, control).await
+    }
+
     // ========== Alarm Operations ==========
 
     async fn read_alarm_data(
@@ -118,6 +219,16 @@ impl HsesClientOps for SharedHsesClient {
         client.read_alarm_history(instance, attribute).await
     }
 
+    async fn read_alarm_history_range(
+        &self,
+        category: AlarmCategory,
+        start: u16,
+        count: u16,
+    ) -> Result<Vec<Alarm>, ClientError> {
+        let client = self.client.lock().await;
+        client.read_alarm_history_range(category, start, count).await
+    }
+
     async fn reset_alarm(&self) -> Result<(), ClientError> {
         let client = self.client.lock().await;
         client.reset_alarm().await
@@ -145,6 +256,21 @@ impl HsesClientOps for SharedHsesClient {
         client.set_hlock(enabled).await
     }
 
+    /// Hold the robot, check that it stopped and optionally turn the servo power off
+    ///
+    /// Does not wait for other operations on this client to finish when the client was
+    /// created with [`SharedHsesClient::new`].
+    async fn emergency_hold(
+        &self,
+        options: &EmergencyHoldOptions,
+    ) -> Result<EmergencyHoldReport, ClientError> {
+        if let Some(lane) = &self.priority_lane {
+            return lane.emergency_hold(options).await;
+        }
+        let client = self.client.lock().await;
+        client.emergency_hold(options).await
+    }
+
     async fn set_cycle_mode(&self, mode: CycleMode) -> Result<(), ClientError> {
         let client = self.client.lock().await;
         client.set_cycle_mode(mode).await
@@ -168,6 +294,22 @@ impl HsesClientOps for SharedHsesClient {
         client.select_job(select_type, job_name, line_number).await
     }
 
+    async fn read_selected_job(&self) -> Result<SelectedJob, ClientError> {
+        let client = self.client.lock().await;
+        client.read_selected_job().await
+    }
+
+    async fn run_job_and_wait(
+        &self,
+        name: &str,
+        line: u32,
+        start_timeout: Duration,
+        run_timeout: Duration,
+    ) -> Result<JobRunReport, ClientError> {
+        let client = self.client.lock().await;
+        client.run_job_and_wait(name, line, start_timeout, run_timeout).await
+    }
+
     async fn read_executing_job_info(
         &self,
         task_type: u16,
@@ -237,6 +379,48 @@ impl HsesClientOps for SharedHsesClient {
         client.write_string(index, value).await
     }
 
+    async fn read_position_variable(&self, index: u16) -> Result<Position, ClientError> {
+        let client = self.client.lock().await;
+        client.read_position_variable(index).await
+    }
+
+    async fn write_position_variable(
+        &self,
+        index: u16,
+        value: Position,
+    ) -> Result<(), ClientError> {
+        let client = self.client.lock().await;
+        client.write_position_variable(index, value).await
+    }
+
+    async fn read_base_position_variable(&self, index: u16) -> Result<BasePosition, ClientError> {
+        let client = self.client.lock().await;
+        client.read_base_position_variable(index).await
+    }
+
+    async fn write_base_position_variable(
+        &self,
+        index: u16,
+        value: BasePosition,
+    ) -> Result<(), ClientError> {
+        let client = self.client.lock().await;
+        client.write_base_position_variable(index, value).await
+    }
+
+    async fn read_station_variable(&self, index: u16) -> Result<StationPosition, ClientError> {
+        let client = self.client.lock().await;
+        client.read_station_variable(index).await
+    }
+
+    async fn write_station_variable(
+        &self,
+        index: u16,
+        value: StationPosition,
+    ) -> Result<(), ClientError> {
+        let client = self.client.lock().await;
+        client.write_station_variable(index, value).await
+    }
+
     // ========== Multiple Variable Operations ==========
 
     async fn read_multiple_u8(
@@ -329,6 +513,40 @@ impl HsesClientOps for SharedHsesClient {
         client.write_multiple_strings(start_variable_number, values).await
     }
 
+    async fn read_variables(
+        &self,
+        variables: &[VariableRef],
+    ) -> Result<Vec<VariableValue>, ClientError> {
+        let client = self.client.lock().await;
+        client.read_variables(variables).await
+    }
+
+    async fn snapshot_variables(
+        &self,
+        profile: &VariableProfile,
+    ) -> Result<VariableSnapshot, ClientError> {
+        let client = self.client.lock().await;
+        client.snapshot_variables(profile).await
+    }
+
+    async fn restore_snapshot(&self, snapshot: &VariableSnapshot) -> Result<(), ClientError> {
+        let client = self.client.lock().await;
+        client.restore_snapshot(snapshot).await
+    }
+
+    async fn apply_recipe(&self, recipe: &Recipe) -> Result<(), ClientError> {
+        let client = self.client.lock().await;
+        client.apply_recipe(recipe).await
+    }
+
+    async fn detect_recipe<'a>(
+        &self,
+        recipes: &'a [Recipe],
+    ) -> Result<Option<&'a Recipe>, ClientError> {
+        let client = self.client.lock().await;
+        client.detect_recipe(recipes).await
+    }
+
     // ========== I/O Operations ==========
 
     async fn read_io(&self, io_number: u16) -> Result<u8, ClientError> {
@@ -341,6 +559,26 @@ impl HsesClientOps for SharedHsesClient {
         client.write_io(io_number, value).await
     }
 
+    async fn read_io_bit(&self, address: u32) -> Result<bool, ClientError> {
+        let client = self.client.lock().await;
+        client.read_io_bit(address).await
+    }
+
+    async fn write_io_bit(&self, address: u32, on: bool) -> Result<(), ClientError> {
+        let client = self.client.lock().await;
+        client.write_io_bit(address, on).await
+    }
+
+    async fn pulse_io(
+        &self,
+        io_number: u16,
+        value: u8,
+        duration: Duration,
+    ) -> Result<(), ClientError> {
+        let client = self.client.lock().await;
+        client.pulse_io(io_number, value, duration).await
+    }
+
     async fn read_multiple_io(
         &self,
         start_io_number: u16,
@@ -410,6 +648,25 @@ impl HsesClientOps for SharedHsesClient {
         let client = self.client.lock().await;
         client.delete_file(filename).await
     }
+
+    async fn diff_job(&self, name: &str, local_source: &str) -> Result<JobDiff, ClientError> {
+        let client = self.client.lock().await;
+        client.diff_job(name, local_source).await
+    }
+
+    async fn delete_files(
+        &self,
+        pattern: &str,
+        options: DeleteFilesOptions,
+    ) -> Result<Vec<FileDeleteResult>, ClientError> {
+        let client = self.client.lock().await;
+        client.delete_files(pattern, options).await
+    }
+
+    async fn send_file_verified(&self, filename: &str, content: &[u8]) -> Result<(), ClientError> {
+        let client = self.client.lock().await;
+        client.send_file_verified(filename, content).await
+    }
 }
 
 #[cfg(test)]
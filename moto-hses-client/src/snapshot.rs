@@ -0,0 +1,68 @@
+//! Variable snapshots
+//!
+//! [`HsesClient::snapshot_variables`](crate::HsesClient::snapshot_variables) reads the B, I,
+//! D, R and S variables selected by a [`VariableProfile`] with plural commands, and
+//! [`HsesClient::restore_snapshot`](crate::HsesClient::restore_snapshot) writes them back,
+//! e.g. for backups or recipe changeovers. With the `serde` feature, a [`VariableSnapshot`]
+//! can be stored in any serde format.
+//!
+//! Position variables are not included.
+
+/// Number of variables of each type in a snapshot, counted from variable 0
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct VariableProfile {
+    /// Byte variables (B)
+    pub byte: u16,
+    /// Integer variables (I)
+    pub integer: u16,
+    /// Double precision integer variables (D)
+    pub double: u16,
+    /// Real variables (R)
+    pub real: u16,
+    /// Character variables (S)
+    pub string: u16,
+}
+
+impl VariableProfile {
+    /// The same number of variables of every type
+    #[must_use]
+    pub const fn uniform(count: u16) -> Self {
+        Self { byte: count, integer: count, double: count, real: count, string: count }
+    }
+}
+
+impl Default for VariableProfile {
+    /// 100 variables of each type, the default variable counts of the controller
+    fn default() -> Self {
+        Self::uniform(100)
+    }
+}
+
+/// Values of the variables of a [`VariableProfile`], indexed by variable number
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct VariableSnapshot {
+    pub bytes: Vec<u8>,
+    pub integers: Vec<i16>,
+    pub doubles: Vec<i32>,
+    pub reals: Vec<f32>,
+    pub strings: Vec<String>,
+}
+
+impl VariableSnapshot {
+    /// Profile reading back the variables held by the snapshot
+    ///
+    /// Counts beyond `u16::MAX` are capped.
+    #[must_use]
+    pub fn profile(&self) -> VariableProfile {
+        let count = |len: usize| u16::try_from(len).unwrap_or(u16::MAX);
+        VariableProfile {
+            byte: count(self.bytes.len()),
+            integer: count(self.integers.len()),
+            double: count(self.doubles.len()),
+            real: count(self.reals.len()),
+            string: count(self.strings.len()),
+        }
+    }
+}
@@ -0,0 +1,286 @@
+//! Status bit reads, waits and watch
+//!
+//! Most status polling needs a few bits, e.g. whether a job is running. A
+//! [`StatusBit`] names one bit of the status, and the reads here request only the status
+//! data word holding the bits asked for (`read_status_data1` or `read_status_data2`),
+//! falling back to the whole status when the bits span both words, which halves the
+//! response data of each poll.
+//!
+//! [`HsesClient::wait_for_status`] polls one bit until it has the expected value or the
+//! deadline passes, and [`HsesClient::watch_status`] reads bits at a fixed interval and
+//! yields a [`StatusChange`] for every bit whose value differs from the previous read,
+//! like [`watch_variables`](crate::watch) does for variables.
+//!
+//! ```ignore
+//! use moto_hses_client::StatusBit;
+//!
+//! client.start_job().await?;
+//! client.wait_for_status(StatusBit::Running, false, Duration::from_millis(100), run_timeout).await?;
+//! ```
+
+use std::collections::VecDeque;
+use std::time::{Duration, SystemTime};
+
+use futures::Stream;
+use moto_hses_proto::{Status, StatusData1, StatusData2};
+use tokio::time::{Instant, Interval, MissedTickBehavior, sleep};
+
+use crate::traits::HsesClientOps;
+use crate::types::{ClientError, HsesClient};
+
+/// One bit of the controller status
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StatusBit {
+    // Status data 1
+    Step,
+    OneCycle,
+    Continuous,
+    Running,
+    SpeedLimited,
+    Teach,
+    Play,
+    Remote,
+    // Status data 2
+    TeachPendantHold,
+    ExternalHold,
+    CommandHold,
+    Alarm,
+    Error,
+    ServoOn,
+}
+
+impl StatusBit {
+    /// Whether the bit is in status data 1; the others are in status data 2
+    #[must_use]
+    pub const fn in_data1(self) -> bool {
+        matches!(
+            self,
+            Self::Step
+                | Self::OneCycle
+                | Self::Continuous
+                | Self::Running
+                | Self::SpeedLimited
+                | Self::Teach
+                | Self::Play
+                | Self::Remote
+        )
+    }
+
+    /// Value of the bit in `status`
+    #[must_use]
+    pub const fn of(self, status: &Status) -> bool {
+        match self.of_data1(&status.data1) {
+            Some(value) => value,
+            None => self.of_data2(&status.data2),
+        }
+    }
+
+    const fn of_data1(self, data1: &StatusData1) -> Option<bool> {
+        Some(match self {
+            Self::Step => data1.step,
+            Self::OneCycle => data1.one_cycle,
+            Self::Continuous => data1.continuous,
+            Self::Running => data1.running,
+            Self::SpeedLimited => data1.speed_limited,
+            Self::Teach => data1.teach,
+            Self::Play => data1.play,
+            Self::Remote => data1.remote,
+            _ => return None,
+        })
+    }
+
+    const fn of_data2(self, data2: &StatusData2) -> bool {
+        match self {
+            Self::TeachPendantHold => data2.teach_pendant_hold,
+            Self::ExternalHold => data2.external_hold,
+            Self::CommandHold => data2.command_hold,
+            Self::Alarm => data2.alarm,
+            Self::Error => data2.error,
+            Self::ServoOn => data2.servo_on,
+            _ => false,
+        }
+    }
+}
+
+/// Value of a watched status bit that changed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StatusChange {
+    pub bit: StatusBit,
+    /// Value of the previous read; `None` on the first read
+    pub old: Option<bool>,
+    pub new: bool,
+    /// Time the read with the new value was sent
+    pub time: SystemTime,
+}
+
+/// Read `bits` through any client with the smallest status read holding them all
+///
+/// # Errors
+///
+/// Returns an error if communication fails
+pub async fn read_status_bits<C: HsesClientOps>(
+    client: &C,
+    bits: &[StatusBit],
+) -> Result<Vec<bool>, ClientError> {
+    let data1 = bits.iter().any(|bit| bit.in_data1());
+    let data2 = bits.iter().any(|bit| !bit.in_data1());
+    let values = if data1 && data2 {
+        let status = client.read_status().await?;
+        bits.iter().map(|bit| bit.of(&status)).collect()
+    } else if data2 {
+        let data2 = client.read_status_data2().await?;
+        bits.iter().map(|bit| bit.of_data2(&data2)).collect()
+    } else {
+        let data1 = client.read_status_data1().await?;
+        bits.iter().map(|bit| bit.of_data1(&data1).unwrap_or_default()).collect()
+    };
+    Ok(values)
+}
+
+impl HsesClient {
+    /// Read one status bit with a read of the status data word holding it
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if communication fails
+    pub async fn read_status_bit(&self, bit: StatusBit) -> Result<bool, ClientError> {
+        let values = read_status_bits(self, &[bit]).await?;
+        Ok(values.first().copied().unwrap_or_default())
+    }
+
+    /// Poll `bit` every `interval` until it is `value`
+    ///
+    /// Each poll reads only the status data word holding the bit. The wait between polls
+    /// is shortened so that the last poll is sent at `deadline`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ClientError::TimeoutError`] if the bit is not `value` within `deadline`,
+    /// or an error if communication fails
+    pub async fn wait_for_status(
+        &self,
+        bit: StatusBit,
+        value: bool,
+        interval: Duration,
+        deadline: Duration,
+    ) -> Result<(), ClientError> {
+        let started_at = Instant::now();
+        loop {
+            if self.read_status_bit(bit).await? == value {
+                return Ok(());
+            }
+            let remaining = deadline.saturating_sub(started_at.elapsed());
+            if remaining.is_zero() {
+                return Err(ClientError::TimeoutError(format!(
+                    "{bit:?} not {value} after {deadline:?}"
+                )));
+            }
+            sleep(interval.min(remaining)).await;
+        }
+    }
+
+    /// Read `bits` every `interval` and yield the changes of their values
+    ///
+    /// See the [module documentation](crate::status_watch).
+    pub fn watch_status(
+        &self,
+        bits: Vec<StatusBit>,
+        interval: Duration,
+    ) -> impl Stream<Item = Result<StatusChange, ClientError>> + '_ {
+        watch_status(self, bits, interval)
+    }
+}
+
+/// Read `bits` through any client every `interval` and yield the changes of their values
+///
+/// Same as [`HsesClient::watch_status`], for e.g. a
+/// [`SharedHsesClient`](crate::SharedHsesClient).
+pub fn watch_status<C: HsesClientOps>(
+    client: &C,
+    bits: Vec<StatusBit>,
+    interval: Duration,
+) -> impl Stream<Item = Result<StatusChange, ClientError>> + '_ {
+    let watch = StatusWatch {
+        client,
+        values: vec![None; bits.len()],
+        bits,
+        interval,
+        ticker: None,
+        pending: VecDeque::new(),
+    };
+    futures::stream::unfold(watch, |mut watch| async move {
+        let next = watch.next().await;
+        Some((next, watch))
+    })
+}
+
+// State of a running status watch
+struct StatusWatch<'a, C> {
+    client: &'a C,
+    bits: Vec<StatusBit>,
+    values: Vec<Option<bool>>,
+    interval: Duration,
+    // Created on the first read, within the runtime
+    ticker: Option<Interval>,
+    pending: VecDeque<StatusChange>,
+}
+
+impl<C: HsesClientOps> StatusWatch<'_, C> {
+    // Next change, reading the bits as often as needed to find one
+    async fn next(&mut self) -> Result<StatusChange, ClientError> {
+        loop {
+            if let Some(change) = self.pending.pop_front() {
+                return Ok(change);
+            }
+            let interval = self.interval.max(Duration::from_millis(1));
+            let ticker = self.ticker.get_or_insert_with(|| {
+                let mut ticker = tokio::time::interval(interval);
+                ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+                ticker
+            });
+            ticker.tick().await;
+
+            let time = SystemTime::now();
+            let values = read_status_bits(self.client, &self.bits).await?;
+            for ((&bit, old), new) in self.bits.iter().zip(&mut self.values).zip(values) {
+                if *old != Some(new) {
+                    self.pending.push_back(StatusChange { bit, old: old.replace(new), new, time });
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status_bit_words() {
+        let data1 = StatusData1 {
+            step: false,
+            one_cycle: false,
+            continuous: true,
+            running: true,
+            speed_limited: false,
+            teach: false,
+            play: true,
+            remote: true,
+        };
+        let data2 = StatusData2 {
+            teach_pendant_hold: false,
+            external_hold: false,
+            command_hold: false,
+            alarm: false,
+            error: false,
+            servo_on: true,
+        };
+        let status = Status::new(data1, data2);
+        assert!(StatusBit::Running.in_data1());
+        assert!(!StatusBit::ServoOn.in_data1());
+        assert!(StatusBit::Running.of(&status));
+        assert!(StatusBit::ServoOn.of(&status));
+        assert!(!StatusBit::Alarm.of(&status));
+        assert!(!StatusBit::Teach.of(&status));
+    }
+}
@@ -0,0 +1,159 @@
+//! Connection supervision
+//!
+//! HSES runs over UDP, so a lost link to the controller only shows as requests that are
+//! not answered. A [`ConnectionSupervisor`] reads the status in the background and reports
+//! the state of the link as [`ConnectionEvent`]s, e.g. to grey out the controls of an HMI
+//! while the robot cannot be reached and to log how long the link was down.
+//!
+//! While the link is lost, the status is read again after a delay that starts at the
+//! probe interval and doubles after every failed read, up to the maximum backoff.
+
+use std::time::{Duration, Instant};
+use tokio::sync::{broadcast, watch};
+
+use crate::task;
+use crate::traits::HsesClientOps;
+
+/// Change of the state of the link to the controller
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConnectionEvent {
+    /// The controller answered for the first time
+    Connected,
+    /// The controller stopped answering
+    Lost {
+        /// Error of the status read that was not answered
+        reason: String,
+    },
+    /// The controller answered again after the link was lost
+    Recovered {
+        /// Time from the start of the first unanswered status read until the first
+        /// answered one completed
+        downtime: Duration,
+    },
+}
+
+/// Options for [`ConnectionSupervisor::start`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SupervisorOptions {
+    /// Interval between status reads while the link is up (default: 1 s)
+    pub interval: Duration,
+    /// Longest delay between status reads while the link is lost (default: 30 s)
+    pub max_backoff: Duration,
+    /// Events kept for subscribers that fall behind; older events are skipped
+    /// (default: 64)
+    pub event_capacity: usize,
+}
+
+impl Default for SupervisorOptions {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(30),
+            event_capacity: 64,
+        }
+    }
+}
+
+/// Background task watching the link to the controller
+///
+/// Dropping the supervisor stops the task.
+#[derive(Debug)]
+pub struct ConnectionSupervisor {
+    stop: watch::Sender<bool>,
+    task: tokio::task::JoinHandle<()>,
+    events: broadcast::Sender<ConnectionEvent>,
+    connected: watch::Receiver<bool>,
+}
+
+impl ConnectionSupervisor {
+    /// Start reading the status of `client` as configured by `options`
+    ///
+    /// The first read is sent at once. Subscribe before awaiting anything else to receive
+    /// its [`ConnectionEvent::Connected`]. Must be called within a Tokio runtime.
+    #[must_use]
+    pub fn start<C: HsesClientOps + 'static>(client: C, options: SupervisorOptions) -> Self {
+        let (events, _) = broadcast::channel(options.event_capacity.max(1));
+        let (connected_sender, connected) = watch::channel(false);
+        let (stop, stopped) = watch::channel(false);
+        let task = task::spawn(
+            "hses-supervisor",
+            supervise(client, options, events.clone(), connected_sender, stopped),
+        );
+        Self { stop, task, events, connected }
+    }
+
+    /// Receiver of the events from now on
+    #[must_use]
+    pub fn subscribe(&self) -> broadcast::Receiver<ConnectionEvent> {
+        self.events.subscribe()
+    }
+
+    /// Whether the last status read was answered
+    #[must_use]
+    pub fn is_connected(&self) -> bool {
+        *self.connected.borrow()
+    }
+
+    /// Stop once the status read in progress completes
+    pub async fn stop(self) {
+        self.stop.send_replace(true);
+        let _ = self.task.await;
+    }
+}
+
+// Read the status until stopped and publish the changes of the link state
+async fn supervise<C: HsesClientOps>(
+    client: C,
+    options: SupervisorOptions,
+    events: broadcast::Sender<ConnectionEvent>,
+    connected: watch::Sender<bool>,
+    mut stopped: watch::Receiver<bool>,
+) {
+    let mut delay = Duration::ZERO;
+    let mut ever_connected = false;
+    // Start of the first unanswered read while the link is lost
+    let mut lost_since: Option<Instant> = None;
+
+    loop {
+        tokio::select! {
+            () = tokio::time::sleep(delay) => {}
+            // Also completes when the supervisor was dropped
+            _ = stopped.wait_for(|stopped| *stopped) => return,
+        }
+
+        let sent_at = Instant::now();
+        match client.read_status().await {
+            Ok(_) => {
+                if !*connected.borrow() {
+                    let event = match lost_since.take() {
+                        Some(since) if ever_connected => {
+                            ConnectionEvent::Recovered { downtime: since.elapsed() }
+                        }
+                        _ => ConnectionEvent::Connected,
+                    };
+                    info!("Controller link: {event:?}");
+                    ever_connected = true;
+                    connected.send_replace(true);
+                    // No subscribers is not an error
+                    let _ = events.send(event);
+                }
+                delay = options.interval;
+            }
+            Err(e) => {
+                if lost_since.is_none() {
+                    lost_since = Some(sent_at);
+                    delay = options.interval;
+                    if ever_connected {
+                        warn!("Controller link lost: {e}");
+                        connected.send_replace(false);
+                        let _ = events.send(ConnectionEvent::Lost { reason: e.to_string() });
+                    }
+                } else {
+                    delay = delay.saturating_mul(2);
+                }
+                delay = delay.min(options.max_backoff);
+                debug!("Next status read in {delay:?}");
+            }
+        }
+    }
+}
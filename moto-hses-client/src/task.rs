@@ -0,0 +1,46 @@
+//! Named background tasks
+//!
+//! Every task the client spawns has a name, e.g. `hses-supervisor` for the loop of a
+//! [`ConnectionSupervisor`](crate::ConnectionSupervisor), logged at trace level when the
+//! task starts. With the `console` feature and `RUSTFLAGS="--cfg tokio_unstable"`, the
+//! name is also given to tokio, so that `tokio-console` lists the tasks by name with the
+//! time they spent busy and idle; the application installs the console subscriber, e.g.
+//! with `console_subscriber::init()`.
+
+use std::future::Future;
+
+use tokio::runtime::Handle;
+use tokio::task::JoinHandle;
+
+/// Spawn `future` on the current runtime as the task `name`
+///
+/// # Panics
+///
+/// Panics if called outside a tokio runtime, like [`tokio::spawn`]
+#[track_caller]
+pub fn spawn<F>(name: &'static str, future: F) -> JoinHandle<F::Output>
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    spawn_on(&Handle::current(), name, future)
+}
+
+/// Spawn `future` on `runtime` as the task `name`
+#[track_caller]
+pub fn spawn_on<F>(runtime: &Handle, name: &'static str, future: F) -> JoinHandle<F::Output>
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    trace!("Spawning task {name}");
+    #[cfg(all(feature = "console", tokio_unstable))]
+    {
+        // The builder never fails on a handle; the `Result` is reserved by tokio
+        let builder = tokio::task::Builder::new().name(name);
+        #[allow(clippy::expect_used)]
+        builder.spawn_on(future, runtime).expect("runtime shutting down")
+    }
+    #[cfg(not(all(feature = "console", tokio_unstable)))]
+    runtime.spawn(future)
+}
@@ -0,0 +1,399 @@
+//! Teach points
+//!
+//! A [`TeachPointTable`] names robot position variables (P), e.g. the approach and pick
+//! positions of a cell. [`HsesClient::write_teach_points`](crate::HsesClient::write_teach_points)
+//! writes them and verifies them by reading them back, and
+//! [`HsesClient::read_teach_points`](crate::HsesClient::read_teach_points) exports the
+//! positions the controller holds.
+//!
+//! Tables are stored as CSV with one point per line. Pulse positions give the 8 axes in
+//! pulses; Cartesian positions give the tool and user frame numbers, the raw
+//! configuration bytes, X, Y, Z in mm, Rx, Ry, Rz in degrees and the raw 7th and 8th axis
+//! data:
+//!
+//! ```text
+//! name,variable,type,tool,user_frame,configuration,extended_configuration,a1,a2,a3,a4,a5,a6,a7,a8
+//! HOME,P000,pulse,,,,,0,0,0,0,-90000,0,0,0
+//! PICK,P001,cartesian,1,2,4,0,350.5,-120.25,410,180,0,90,0,0
+//! ```
+//!
+//! With the `serde` feature, a table is a list of points, e.g. in JSON:
+//!
+//! ```json
+//! [
+//!   {"name": "HOME", "variable": "P000", "pulse": [0, 0, 0, 0, -90000, 0, 0, 0]},
+//!   {"name": "PICK", "variable": "P001", "cartesian": {
+//!     "x": 350.5, "y": -120.25, "z": 410.0, "rx": 180.0, "ry": 0.0, "rz": 90.0,
+//!     "tool": 1, "user_frame": 2, "configuration": 4}}
+//! ]
+//! ```
+
+use std::fmt::Write;
+
+use moto_hses_proto::commands::POSITION_VARIABLE_AXES as AXES;
+use moto_hses_proto::payload::position::{Configuration, ExtendedConfiguration};
+use moto_hses_proto::{CartesianPosition, Command, Position, PulsePosition, WritePositionVar};
+
+use crate::types::ClientError;
+
+/// Header line of the CSV form
+pub const CSV_HEADER: &str = "name,variable,type,tool,user_frame,configuration,\
+                              extended_configuration,a1,a2,a3,a4,a5,a6,a7,a8";
+
+/// Named robot position variable
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "serde_format::TeachPointFile", into = "serde_format::TeachPointFile")
+)]
+pub struct TeachPoint {
+    pub name: String,
+    /// Number of the P variable
+    pub index: u16,
+    pub position: Position,
+}
+
+impl TeachPoint {
+    #[must_use]
+    pub fn new(name: impl Into<String>, index: u16, position: Position) -> Self {
+        Self { name: name.into(), index, position }
+    }
+
+    /// Name of the variable, e.g. "P001"
+    #[must_use]
+    pub fn variable_name(&self) -> String {
+        format!("P{:03}", self.index)
+    }
+
+    /// Position written to the P variable; pulse positions are padded to 8 axes
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ClientError::InvalidVariable`] if a pulse position has more than 8 axes
+    pub fn variable_position(&self) -> Result<Position, ClientError> {
+        match &self.position {
+            Position::Pulse(pulse) if pulse.joints.len() > AXES => {
+                Err(ClientError::InvalidVariable(format!(
+                    "{} has {} axes, at most {AXES} fit a P variable",
+                    self.name,
+                    pulse.joints.len()
+                )))
+            }
+            Position::Pulse(pulse) => {
+                let mut joints = pulse.joints.clone();
+                joints.resize(AXES, 0);
+                Ok(Position::Pulse(PulsePosition::new(joints)))
+            }
+            Position::Cartesian(_) => Ok(self.position.clone()),
+        }
+    }
+
+    /// Data of the P variable holding the position
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ClientError::InvalidVariable`] if a pulse position has more than 8 axes
+    pub fn variable_data(&self) -> Result<Vec<u8>, ClientError> {
+        let position = self.variable_position()?;
+        Ok(WritePositionVar::new(self.index, position).serialize()?)
+    }
+
+    // One line of the CSV form
+    fn csv_line(&self) -> String {
+        let mut line = format!("{},{},", self.name, self.variable_name());
+        // Writing to a String does not fail
+        let _ = match &self.position {
+            Position::Pulse(pulse) => {
+                let mut joints = pulse.joints.clone();
+                joints.resize(AXES, 0);
+                let joints: Vec<String> = joints.iter().map(ToString::to_string).collect();
+                write!(line, "pulse,,,,,{}", joints.join(","))
+            }
+            Position::Cartesian(cart) => write!(
+                line,
+                "cartesian,{},{},{},{},{},{},{},{},{},{},{},{}",
+                cart.tool_no,
+                cart.user_coord_no,
+                cart.configuration.to_raw(),
+                cart.extended_configuration.to_raw(),
+                cart.x,
+                cart.y,
+                cart.z,
+                cart.rx,
+                cart.ry,
+                cart.rz,
+                cart.extra_axes[0],
+                cart.extra_axes[1]
+            ),
+        };
+        line
+    }
+
+    // Point of one line of the CSV form
+    fn from_csv_line(line: &str) -> Result<Self, ClientError> {
+        let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+        let invalid = |reason: &str| ClientError::InvalidVariable(format!("{reason}: {line}"));
+        let [name, variable, kind, tool, user_frame, configuration, extended, axes @ ..] =
+            fields.as_slice()
+        else {
+            return Err(invalid("too few columns"));
+        };
+        if axes.len() != AXES {
+            return Err(invalid("expected 8 axis columns"));
+        }
+        let index = parse_variable_name(variable)?;
+        let position = match kind.to_ascii_lowercase().as_str() {
+            "pulse" => {
+                let joints = axes
+                    .iter()
+                    .map(|axis| axis.parse().map_err(|_| invalid("invalid pulse value")))
+                    .collect::<Result<Vec<i32>, _>>()?;
+                Position::Pulse(PulsePosition::new(joints))
+            }
+            "cartesian" => {
+                let byte = |field: &str| field.parse::<u8>().map_err(|_| invalid("invalid number"));
+                let value =
+                    |field: &str| field.parse::<f32>().map_err(|_| invalid("invalid value"));
+                let extra =
+                    |field: &str| field.parse::<i32>().map_err(|_| invalid("invalid value"));
+                let mut cart = CartesianPosition::new(
+                    value(axes[0])?,
+                    value(axes[1])?,
+                    value(axes[2])?,
+                    value(axes[3])?,
+                    value(axes[4])?,
+                    value(axes[5])?,
+                    byte(tool)?,
+                    byte(user_frame)?,
+                    Configuration::from_raw(byte(configuration)?),
+                    ExtendedConfiguration::from_raw(byte(extended)?),
+                );
+                cart.extra_axes = [extra(axes[6])?, extra(axes[7])?];
+                Position::Cartesian(cart)
+            }
+            _ => return Err(invalid("type is neither pulse nor cartesian")),
+        };
+        Ok(Self::new(*name, index, position))
+    }
+}
+
+/// Named robot position variables
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(transparent))]
+pub struct TeachPointTable {
+    pub points: Vec<TeachPoint>,
+}
+
+impl TeachPointTable {
+    #[must_use]
+    pub const fn new(points: Vec<TeachPoint>) -> Self {
+        Self { points }
+    }
+
+    /// Point named `name`
+    #[must_use]
+    pub fn get(&self, name: &str) -> Option<&TeachPoint> {
+        self.points.iter().find(|point| point.name == name)
+    }
+
+    /// Parse the CSV form; the header line and empty lines are skipped
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ClientError::InvalidVariable`] naming the first invalid line
+    pub fn from_csv(text: &str) -> Result<Self, ClientError> {
+        let points = text
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with("name,"))
+            .map(TeachPoint::from_csv_line)
+            .collect::<Result<_, _>>()?;
+        Ok(Self { points })
+    }
+
+    /// CSV form, with a header line; names must not contain commas
+    #[must_use]
+    pub fn to_csv(&self) -> String {
+        let mut text = format!("{CSV_HEADER}\n");
+        for point in &self.points {
+            text.push_str(&point.csv_line());
+            text.push('\n');
+        }
+        text
+    }
+}
+
+// Number of a P variable named e.g. "P001"
+fn parse_variable_name(name: &str) -> Result<u16, ClientError> {
+    name.strip_prefix(['P', 'p'])
+        .and_then(|index| index.parse().ok())
+        .ok_or_else(|| ClientError::InvalidVariable(format!("invalid P variable name: {name}")))
+}
+
+#[cfg(feature = "serde")]
+mod serde_format {
+    use moto_hses_proto::payload::position::{Configuration, ExtendedConfiguration};
+    use moto_hses_proto::{CartesianPosition, Position, PulsePosition};
+
+    use super::{TeachPoint, parse_variable_name};
+    use crate::types::ClientError;
+
+    // Stored form of a teach point: the variable by name and the position by type
+    #[derive(serde::Serialize, serde::Deserialize)]
+    pub struct TeachPointFile {
+        name: String,
+        variable: String,
+        #[serde(flatten)]
+        position: FilePosition,
+    }
+
+    #[derive(serde::Serialize, serde::Deserialize)]
+    #[serde(rename_all = "lowercase")]
+    enum FilePosition {
+        Pulse(Vec<i32>),
+        Cartesian(FileCartesian),
+    }
+
+    #[derive(serde::Serialize, serde::Deserialize)]
+    struct FileCartesian {
+        x: f32,
+        y: f32,
+        z: f32,
+        rx: f32,
+        ry: f32,
+        rz: f32,
+        #[serde(default)]
+        tool: u8,
+        #[serde(default)]
+        user_frame: u8,
+        #[serde(default)]
+        configuration: u8,
+        #[serde(default)]
+        extended_configuration: u8,
+        #[serde(default)]
+        extra_axes: [i32; 2],
+    }
+
+    impl TryFrom<TeachPointFile> for TeachPoint {
+        type Error = ClientError;
+
+        fn try_from(file: TeachPointFile) -> Result<Self, Self::Error> {
+            let index = parse_variable_name(&file.variable)?;
+            let position = match file.position {
+                FilePosition::Pulse(joints) => Position::Pulse(PulsePosition::new(joints)),
+                FilePosition::Cartesian(file) => {
+                    let mut cart = CartesianPosition::new(
+                        file.x,
+                        file.y,
+                        file.z,
+                        file.rx,
+                        file.ry,
+                        file.rz,
+                        file.tool,
+                        file.user_frame,
+                        Configuration::from_raw(file.configuration),
+                        ExtendedConfiguration::from_raw(file.extended_configuration),
+                    );
+                    cart.extra_axes = file.extra_axes;
+                    Position::Cartesian(cart)
+                }
+            };
+            Ok(Self::new(file.name, index, position))
+        }
+    }
+
+    impl From<TeachPoint> for TeachPointFile {
+        fn from(point: TeachPoint) -> Self {
+            let variable = point.variable_name();
+            let position = match point.position {
+                Position::Pulse(pulse) => FilePosition::Pulse(pulse.joints),
+                Position::Cartesian(cart) => FilePosition::Cartesian(FileCartesian {
+                    x: cart.x,
+                    y: cart.y,
+                    z: cart.z,
+                    rx: cart.rx,
+                    ry: cart.ry,
+                    rz: cart.rz,
+                    tool: cart.tool_no,
+                    user_frame: cart.user_coord_no,
+                    configuration: cart.configuration.to_raw(),
+                    extended_configuration: cart.extended_configuration.to_raw(),
+                    extra_axes: cart.extra_axes,
+                }),
+            };
+            Self { name: point.name, variable, position }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table() -> TeachPointTable {
+        TeachPointTable::new(vec![
+            TeachPoint::new(
+                "HOME",
+                0,
+                Position::Pulse(PulsePosition::new(vec![0, 0, 0, 0, -90_000, 0, 0, 0])),
+            ),
+            TeachPoint::new(
+                "PICK",
+                1,
+                Position::Cartesian(CartesianPosition::new(
+                    350.5,
+                    -120.25,
+                    410.0,
+                    180.0,
+                    0.0,
+                    90.0,
+                    1,
+                    2,
+                    Configuration::from_raw(4),
+                    ExtendedConfiguration::from_raw(0),
+                )),
+            ),
+        ])
+    }
+
+    #[test]
+    fn test_teach_point_csv() {
+        let csv = table().to_csv();
+        assert_eq!(
+            csv.lines().skip(1).collect::<Vec<_>>(),
+            [
+                "HOME,P000,pulse,,,,,0,0,0,0,-90000,0,0,0",
+                "PICK,P001,cartesian,1,2,4,0,350.5,-120.25,410,180,0,90,0,0"
+            ]
+        );
+        assert_eq!(TeachPointTable::from_csv(&csv).ok(), Some(table()));
+        assert_eq!(table().get("PICK").map(TeachPoint::variable_name), Some("P001".to_string()));
+
+        assert!(TeachPointTable::from_csv("A,P001,pulse,,,,,0,0,0,0,0,0").is_err());
+        assert!(TeachPointTable::from_csv("A,D001,pulse,,,,,0,0,0,0,0,0,0,0").is_err());
+        assert!(TeachPointTable::from_csv("A,P001,tool,,,,,0,0,0,0,0,0,0,0").is_err());
+        assert!(TeachPointTable::from_csv("A,P001,cartesian,,,,,0,0,0,0,0,0,0,0").is_err());
+    }
+
+    #[test]
+    fn test_teach_point_variable_data() {
+        let pulse = TeachPoint::new("A", 0, Position::Pulse(PulsePosition::new(vec![1; 6])));
+        assert_eq!(pulse.variable_data().map(|data| data.len()).ok(), Some(52));
+        let long = TeachPoint::new("B", 0, Position::Pulse(PulsePosition::new(vec![1; 9])));
+        assert!(long.variable_data().is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn test_teach_point_serde_format() {
+        let json = r#"[{"name":"HOME","variable":"P000","pulse":[0,0,0,0,-90000,0,0,0]},{"name":"PICK","variable":"P001","cartesian":{"x":350.5,"y":-120.25,"z":410.0,"rx":180.0,"ry":0.0,"rz":90.0,"tool":1,"user_frame":2,"configuration":4,"extended_configuration":0,"extra_axes":[0,0]}}]"#;
+        let parsed: TeachPointTable = serde_json::from_str(json).unwrap();
+        assert_eq!(parsed, table());
+        assert_eq!(serde_json::to_string(&parsed).unwrap(), json);
+
+        let json = r#"[{"name":"A","variable":"B001","pulse":[0]}]"#;
+        assert!(serde_json::from_str::<TeachPointTable>(json).is_err());
+    }
+}
@@ -0,0 +1,445 @@
+//! Telemetry logging
+//!
+//! A [`TelemetryLogger`] reads a [`RobotSnapshot`] at a fixed rate and appends it as one
+//! CSV row to rotating files, e.g. for long-term cycle analysis. Files are named
+//! `telemetry-v{SCHEMA_VERSION}-{unix time of the first row in ms}.csv`, so readers can
+//! tell the column layout of a file from its name; the layout of a version never changes.
+//!
+//! Snapshots are handed to a writer thread through a bounded queue. When the disk falls
+//! behind and the queue is full, new snapshots are dropped and counted instead of
+//! delaying the polling.
+//!
+//! Requires the `telemetry` feature.
+
+use std::collections::VecDeque;
+use std::fmt::Write as _;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write as _};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, SyncSender, TrySendError};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::watch;
+use tokio::time::MissedTickBehavior;
+
+use moto_hses_proto::Position;
+
+use crate::robot_snapshot::RobotSnapshot;
+use crate::task;
+use crate::traits::HsesClientOps;
+use crate::types::ClientError;
+
+/// Version of the column layout, part of every file name
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Value columns per control group: pulse axes 1-8, or X, Y, Z, RX, RY, RZ, tool and
+/// user coordinate number
+const POSITION_COLUMNS: usize = 8;
+
+/// Options for [`TelemetryLogger::start`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TelemetryOptions {
+    /// Directory the files are written to; created if missing
+    pub directory: PathBuf,
+    /// Interval between snapshots (default: 100 ms)
+    pub interval: Duration,
+    /// Control groups whose positions are recorded (default: robot 1 in pulses)
+    pub control_groups: Vec<u8>,
+    /// Rows per file before the next file is started (default: 36 000, one hour at the
+    /// default interval)
+    pub rows_per_file: usize,
+    /// Number of files kept; older files of the logger are deleted, 0 keeps all
+    /// (default: 24)
+    pub max_files: usize,
+    /// Snapshots queued for the writer before new ones are dropped (default: 1024)
+    pub queue_capacity: usize,
+}
+
+impl TelemetryOptions {
+    /// Default options writing to `directory`
+    #[must_use]
+    pub fn new(directory: impl Into<PathBuf>) -> Self {
+        Self {
+            directory: directory.into(),
+            interval: Duration::from_millis(100),
+            control_groups: vec![1],
+            rows_per_file: 36_000,
+            max_files: 24,
+            queue_capacity: 1024,
+        }
+    }
+}
+
+/// Counters of a [`TelemetryLogger`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TelemetryStats {
+    /// Snapshots written to a file
+    pub recorded: u64,
+    /// Snapshots dropped because the writer queue was full
+    pub dropped: u64,
+    /// Snapshots that could not be read from the controller
+    pub read_errors: u64,
+}
+
+#[derive(Debug, Default)]
+struct Counters {
+    recorded: AtomicU64,
+    dropped: AtomicU64,
+    read_errors: AtomicU64,
+}
+
+impl Counters {
+    fn stats(&self) -> TelemetryStats {
+        TelemetryStats {
+            recorded: self.recorded.load(Ordering::Relaxed),
+            dropped: self.dropped.load(Ordering::Relaxed),
+            read_errors: self.read_errors.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Records robot snapshots to rotating CSV files until stopped
+///
+/// Dropping the logger stops the polling; [`stop`](Self::stop) additionally waits until
+/// the queued snapshots are written.
+#[derive(Debug)]
+pub struct TelemetryLogger {
+    stop: watch::Sender<bool>,
+    poller: tokio::task::JoinHandle<()>,
+    writer: thread::JoinHandle<io::Result<()>>,
+    counters: Arc<Counters>,
+}
+
+impl TelemetryLogger {
+    /// Start polling `client` and writing snapshots as configured by `options`
+    ///
+    /// Must be called within a Tokio runtime.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the directory cannot be created or the writer thread cannot
+    /// be started
+    pub fn start<C: HsesClientOps + 'static>(
+        client: C,
+        options: TelemetryOptions,
+    ) -> Result<Self, ClientError> {
+        fs::create_dir_all(&options.directory)?;
+        let counters = Arc::new(Counters::default());
+        let (sender, receiver) = mpsc::sync_channel(options.queue_capacity.max(1));
+
+        let writer = RotatingWriter {
+            directory: options.directory,
+            control_groups: options.control_groups.clone(),
+            rows_per_file: options.rows_per_file.max(1),
+            max_files: options.max_files,
+            file: None,
+            rows: 0,
+            files: VecDeque::new(),
+            counters: Arc::clone(&counters),
+        };
+        let writer =
+            thread::Builder::new().name("hses-telemetry".to_string()).spawn(move || {
+                let result = writer.run(&receiver);
+                if let Err(e) = &result {
+                    warn!("Telemetry writer stopped: {e}");
+                }
+                result
+            })?;
+
+        let (stop, stopped) = watch::channel(false);
+        let poller = task::spawn(
+            "hses-telemetry-poller",
+            poll(
+                client,
+                options.interval,
+                options.control_groups,
+                sender,
+                Arc::clone(&counters),
+                stopped,
+            ),
+        );
+        Ok(Self { stop, poller, writer, counters })
+    }
+
+    #[must_use]
+    pub fn stats(&self) -> TelemetryStats {
+        self.counters.stats()
+    }
+
+    /// Stop polling once the snapshot in progress is read, and wait until the queued
+    /// snapshots are written
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if writing a file failed while the logger ran
+    pub async fn stop(self) -> Result<TelemetryStats, ClientError> {
+        self.stop.send_replace(true);
+        let _ = self.poller.await;
+        let writer = self.writer;
+        let result = tokio::task::spawn_blocking(move || writer.join())
+            .await
+            .map_err(|e| ClientError::SystemError(format!("telemetry writer failed: {e}")))?
+            .map_err(|_| ClientError::SystemError("telemetry writer panicked".to_string()))?;
+        result?;
+        Ok(self.counters.stats())
+    }
+}
+
+// Read snapshots at `interval` and queue them for the writer until stopped
+async fn poll<C: HsesClientOps>(
+    client: C,
+    interval: Duration,
+    control_groups: Vec<u8>,
+    sender: SyncSender<RobotSnapshot>,
+    counters: Arc<Counters>,
+    mut stopped: watch::Receiver<bool>,
+) {
+    let mut ticker = tokio::time::interval(interval);
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Skip);
+    loop {
+        tokio::select! {
+            _ = ticker.tick() => {}
+            // Also completes when the logger was dropped
+            _ = stopped.wait_for(|stopped| *stopped) => return,
+        }
+        match client.read_snapshot(&control_groups).await {
+            Ok(snapshot) => match sender.try_send(snapshot) {
+                Ok(()) => {}
+                Err(TrySendError::Full(_)) => {
+                    counters.dropped.fetch_add(1, Ordering::Relaxed);
+                }
+                // The writer failed
+                Err(TrySendError::Disconnected(_)) => return,
+            },
+            Err(e) => {
+                counters.read_errors.fetch_add(1, Ordering::Relaxed);
+                debug!("Telemetry snapshot failed: {e}");
+            }
+        }
+    }
+}
+
+// Writes snapshots to CSV files, starting a new file every `rows_per_file` rows
+struct RotatingWriter {
+    directory: PathBuf,
+    control_groups: Vec<u8>,
+    rows_per_file: usize,
+    max_files: usize,
+    file: Option<BufWriter<File>>,
+    rows: usize,
+    files: VecDeque<PathBuf>,
+    counters: Arc<Counters>,
+}
+
+impl RotatingWriter {
+    fn run(mut self, receiver: &Receiver<RobotSnapshot>) -> io::Result<()> {
+        while let Ok(snapshot) = receiver.recv() {
+            self.write(&snapshot)?;
+            // Flush once the queue is drained rather than after every row
+            while let Ok(snapshot) = receiver.try_recv() {
+                self.write(&snapshot)?;
+            }
+            self.flush()?;
+        }
+        self.flush()
+    }
+
+    fn write(&mut self, snapshot: &RobotSnapshot) -> io::Result<()> {
+        if self.rows >= self.rows_per_file || self.file.is_none() {
+            self.rotate(snapshot.captured_at)?;
+        }
+        if let Some(file) = &mut self.file {
+            writeln!(file, "{}", row(snapshot, self.control_groups.len()))?;
+        }
+        self.rows += 1;
+        self.counters.recorded.fetch_add(1, Ordering::Relaxed);
+        Ok(())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.as_mut().map_or(Ok(()), io::Write::flush)
+    }
+
+    // Start a new file and delete the oldest files beyond `max_files`
+    fn rotate(&mut self, first_row_at: SystemTime) -> io::Result<()> {
+        self.flush()?;
+        let millis = first_row_at.duration_since(UNIX_EPOCH).unwrap_or_default().as_millis();
+        let path = self.directory.join(format!("telemetry-v{SCHEMA_VERSION}-{millis}.csv"));
+        let mut file = BufWriter::new(File::create(&path)?);
+        writeln!(file, "{}", header(&self.control_groups))?;
+        self.file = Some(file);
+        self.rows = 0;
+        self.files.push_back(path);
+
+        while self.max_files > 0 && self.files.len() > self.max_files {
+            if let Some(oldest) = self.files.pop_front()
+                && let Err(e) = fs::remove_file(&oldest)
+                && e.kind() != io::ErrorKind::NotFound
+            {
+                return Err(e);
+            }
+        }
+        Ok(())
+    }
+}
+
+fn header(control_groups: &[u8]) -> String {
+    let mut header = String::from(
+        "captured_at_ms,skew_us,step,one_cycle,continuous,running,speed_limited,teach,play,\
+         remote,teach_pendant_hold,external_hold,command_hold,alarm,error,servo_on,job_name,\
+         job_line,job_step,speed_override,active_alarms",
+    );
+    for group in control_groups {
+        let _ = write!(header, ",g{group}_type");
+        for column in 1..=POSITION_COLUMNS {
+            let _ = write!(header, ",g{group}_{column}");
+        }
+    }
+    header
+}
+
+fn row(snapshot: &RobotSnapshot, control_groups: usize) -> String {
+    let data1 = &snapshot.status.data1;
+    let data2 = &snapshot.status.data2;
+    let job = &snapshot.executing_job;
+    let mut row = format!(
+        "{},{}",
+        snapshot.captured_at.duration_since(UNIX_EPOCH).unwrap_or_default().as_millis(),
+        snapshot.skew.as_micros()
+    );
+    for flag in [
+        data1.step,
+        data1.one_cycle,
+        data1.continuous,
+        data1.running,
+        data1.speed_limited,
+        data1.teach,
+        data1.play,
+        data1.remote,
+        data2.teach_pendant_hold,
+        data2.external_hold,
+        data2.command_hold,
+        data2.alarm,
+        data2.error,
+        data2.servo_on,
+    ] {
+        let _ = write!(row, ",{}", u8::from(flag));
+    }
+    let _ = write!(
+        row,
+        ",{},{},{},{},{}",
+        csv_text(&job.job_name),
+        job.line_number,
+        job.step_number,
+        job.speed_override_value,
+        snapshot.active_alarm_count
+    );
+
+    for index in 0..control_groups {
+        let values: Vec<String> = match snapshot.positions.get(index) {
+            Some(Position::Pulse(pulse)) => {
+                row.push_str(",pulse");
+                pulse.joints.iter().map(ToString::to_string).collect()
+            }
+            Some(Position::Cartesian(cartesian)) => {
+                row.push_str(",cartesian");
+                [cartesian.x, cartesian.y, cartesian.z, cartesian.rx, cartesian.ry, cartesian.rz]
+                    .iter()
+                    .map(ToString::to_string)
+                    .chain([cartesian.tool_no.to_string(), cartesian.user_coord_no.to_string()])
+                    .collect()
+            }
+            None => {
+                row.push(',');
+                Vec::new()
+            }
+        };
+        for column in 0..POSITION_COLUMNS {
+            let _ = write!(row, ",{}", values.get(column).map_or("", String::as_str));
+        }
+    }
+    row
+}
+
+// Quote a text field if it contains a separator, quote or line break
+fn csv_text(text: &str) -> String {
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use moto_hses_proto::payload::position::PulsePosition;
+    use moto_hses_proto::{ExecutingJobInfo, Status, StatusData1, StatusData2};
+
+    fn snapshot(millis: u64) -> RobotSnapshot {
+        RobotSnapshot {
+            captured_at: UNIX_EPOCH + Duration::from_millis(millis),
+            skew: Duration::from_micros(850),
+            status: Status::new(
+                StatusData1::from_bytes(&[0x08, 0, 0, 0]).unwrap(),
+                StatusData2::from_bytes(&[0x40, 0, 0, 0]).unwrap(),
+            ),
+            positions: vec![Position::Pulse(PulsePosition::new(vec![1, -2, 3, 0, 0, 0]))],
+            executing_job: ExecutingJobInfo::new("WELD,A".to_string(), 12, 3, 100),
+            active_alarm_count: 0,
+        }
+    }
+
+    #[test]
+    fn test_row_matches_header() {
+        let header = header(&[1, 2]);
+        let row = row(&snapshot(1_000), 2);
+        assert_eq!(
+            row,
+            "1000,850,0,0,0,1,0,0,0,0,0,0,0,0,0,1,\"WELD,A\",12,3,100,0,pulse,1,-2,3,0,0,0,,\
+             ,,,,,,,,,"
+        );
+        assert!(header.starts_with("captured_at_ms,"));
+        assert!(header.ends_with(",g2_type,g2_1,g2_2,g2_3,g2_4,g2_5,g2_6,g2_7,g2_8"));
+        // The quoted job name holds one separator
+        assert_eq!(header.matches(',').count() + 1, row.matches(',').count());
+    }
+
+    #[test]
+    fn test_rotation_keeps_max_files() {
+        let directory =
+            std::env::temp_dir().join(format!("moto-hses-telemetry-test-{}", std::process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        let (sender, receiver) = mpsc::sync_channel(16);
+        let counters = Arc::new(Counters::default());
+        let writer = RotatingWriter {
+            directory: directory.clone(),
+            control_groups: vec![1],
+            rows_per_file: 2,
+            max_files: 2,
+            file: None,
+            rows: 0,
+            files: VecDeque::new(),
+            counters: Arc::clone(&counters),
+        };
+        for millis in 1..=5 {
+            sender.send(snapshot(millis)).unwrap();
+        }
+        drop(sender);
+        writer.run(&receiver).unwrap();
+
+        let mut files: Vec<String> = fs::read_dir(&directory)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        files.sort();
+        assert_eq!(files, vec!["telemetry-v1-3.csv", "telemetry-v1-5.csv"]);
+        let content = fs::read_to_string(directory.join("telemetry-v1-3.csv")).unwrap();
+        assert_eq!(content.lines().count(), 3);
+        assert_eq!(counters.recorded.load(Ordering::Relaxed), 5);
+        fs::remove_dir_all(&directory).unwrap();
+    }
+}
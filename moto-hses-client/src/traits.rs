@@ -3,11 +3,25 @@
 //! This module provides the `HsesClientOps` trait which abstracts all client operations,
 //! enabling thread-safe wrappers and mock implementations.
 
-use crate::types::ClientError;
+use crate::emergency::{EmergencyHoldOptions, EmergencyHoldReport};
+use crate::job_diff::JobDiff;
+use crate::jog::{JogDeadman, JogDirection, JogReport};
+use crate::recipe::{Recipe, VariableValue};
+use crate::robot_snapshot::RobotSnapshot;
+use crate::snapshot::{VariableProfile, VariableSnapshot};
+use crate::trajectory::{TrajectoryControl, TrajectoryOptions, TrajectoryReport};
+use crate::types::{
+    CartesianDelta, ClientError, DeleteFilesOptions, FileDeleteResult, IncrementalMove,
+    IncrementalMoveOptions, JobRunReport, ResponseMeta,
+};
+use crate::watch::VariableRef;
 use moto_hses_proto::commands::JobSelectType;
 use moto_hses_proto::{
-    Alarm, AlarmAttribute, CycleMode, ExecutingJobInfo, Position, Status, StatusData1, StatusData2,
+    Alarm, AlarmAttribute, AlarmCategory, BasePosition, CycleMode, ExecutingJobInfo, MoveFrame,
+    MoveSpeed, Position, SelectedJob, StationPosition, Status, StatusData1, StatusData2,
+    SystemInfo,
 };
+use std::time::Duration;
 
 /// Trait for HSES client operations
 ///
@@ -34,6 +48,12 @@ pub trait HsesClientOps: Send + Sync {
     /// Read complete status information (both Data 1 and Data 2)
     async fn read_status(&self) -> Result<Status, ClientError>;
 
+    /// Read complete status information together with request metadata
+    async fn read_status_with_meta(&self) -> Result<(Status, ResponseMeta), ClientError>;
+
+    /// Read the system information of a robot, station or the application
+    async fn read_system_info(&self, instance: u16) -> Result<SystemInfo, ClientError>;
+
     /// Read status data 1 (basic status information)
     async fn read_status_data1(&self) -> Result<StatusData1, ClientError>;
 
@@ -45,6 +65,44 @@ pub trait HsesClientOps: Send + Sync {
     /// Read current position for a control group
     async fn read_position(&self, control_group: u8) -> Result<Position, ClientError>;
 
+    /// Read status, positions, executing job and active alarm count with pipelined requests
+    async fn read_snapshot(&self, control_groups: &[u8]) -> Result<RobotSnapshot, ClientError>;
+
+    /// Move a robot by a Cartesian increment
+    async fn move_incremental(
+        &self,
+        robot: u8,
+        delta: CartesianDelta,
+        options: &IncrementalMoveOptions,
+    ) -> Result<IncrementalMove, ClientError>;
+
+    /// Move a robot to an absolute pulse position
+    async fn move_pulse(
+        &self,
+        robot: u8,
+        joints: Vec<i32>,
+        speed: MoveSpeed,
+    ) -> Result<(), ClientError>;
+
+    /// Jog a robot while a deadman token is held
+    async fn jog(
+        &self,
+        robot: u8,
+        frame: MoveFrame,
+        direction: JogDirection,
+        speed: f32,
+        deadman: &JogDeadman,
+    ) -> Result<JogReport, ClientError>;
+
+    /// Move a robot through a sequence of positions
+    async fn play_trajectory(
+        &self,
+        robot: u8,
+        points: &[Position],
+        options: &TrajectoryOptions,
+        control: &TrajectoryControl,
+    ) -> Result<TrajectoryReport, ClientError>;
+
     // ========== Alarm Operations ==========
 
     /// Read alarm data
@@ -61,6 +119,14 @@ pub trait HsesClientOps: Send + Sync {
         attribute: AlarmAttribute,
     ) -> Result<Alarm, ClientError>;
 
+    /// Read a range of alarm history entries of a category, newest first
+    async fn read_alarm_history_range(
+        &self,
+        category: AlarmCategory,
+        start: u16,
+        count: u16,
+    ) -> Result<Vec<Alarm>, ClientError>;
+
     /// Reset alarm
     async fn reset_alarm(&self) -> Result<(), ClientError>;
 
@@ -78,6 +144,12 @@ pub trait HsesClientOps: Send + Sync {
     /// Set HLOCK state
     async fn set_hlock(&self, enabled: bool) -> Result<(), ClientError>;
 
+    /// Hold the robot, check that it stopped and optionally turn the servo power off
+    async fn emergency_hold(
+        &self,
+        options: &EmergencyHoldOptions,
+    ) -> Result<EmergencyHoldReport, ClientError>;
+
     /// Set cycle mode
     async fn set_cycle_mode(&self, mode: CycleMode) -> Result<(), ClientError>;
 
@@ -94,6 +166,18 @@ pub trait HsesClientOps: Send + Sync {
         line_number: u32,
     ) -> Result<(), ClientError>;
 
+    /// Read the job and line currently selected for the master task
+    async fn read_selected_job(&self) -> Result<SelectedJob, ClientError>;
+
+    /// Run a job and wait until it stops
+    async fn run_job_and_wait(
+        &self,
+        name: &str,
+        line: u32,
+        start_timeout: Duration,
+        run_timeout: Duration,
+    ) -> Result<JobRunReport, ClientError>;
+
     /// Read executing job information
     async fn read_executing_job_info(
         &self,
@@ -139,6 +223,33 @@ pub trait HsesClientOps: Send + Sync {
     /// Write a string variable (S variable)
     async fn write_string(&self, index: u16, value: String) -> Result<(), ClientError>;
 
+    /// Read a robot position variable (P variable)
+    async fn read_position_variable(&self, index: u16) -> Result<Position, ClientError>;
+
+    /// Write a robot position variable (P variable)
+    async fn write_position_variable(&self, index: u16, value: Position)
+    -> Result<(), ClientError>;
+
+    /// Read a base position variable (BP variable)
+    async fn read_base_position_variable(&self, index: u16) -> Result<BasePosition, ClientError>;
+
+    /// Write a base position variable (BP variable)
+    async fn write_base_position_variable(
+        &self,
+        index: u16,
+        value: BasePosition,
+    ) -> Result<(), ClientError>;
+
+    /// Read a station variable (EX variable)
+    async fn read_station_variable(&self, index: u16) -> Result<StationPosition, ClientError>;
+
+    /// Write a station variable (EX variable)
+    async fn write_station_variable(
+        &self,
+        index: u16,
+        value: StationPosition,
+    ) -> Result<(), ClientError>;
+
     // ========== Multiple Variable Operations ==========
 
     /// Read multiple u8 variables (B)
@@ -211,6 +322,30 @@ pub trait HsesClientOps: Send + Sync {
         values: Vec<String>,
     ) -> Result<(), ClientError>;
 
+    /// Read variables of any types and numbers with pipelined requests
+    async fn read_variables(
+        &self,
+        variables: &[VariableRef],
+    ) -> Result<Vec<VariableValue>, ClientError>;
+
+    /// Read the variables selected by a profile
+    async fn snapshot_variables(
+        &self,
+        profile: &VariableProfile,
+    ) -> Result<VariableSnapshot, ClientError>;
+
+    /// Write the variables of a snapshot back
+    async fn restore_snapshot(&self, snapshot: &VariableSnapshot) -> Result<(), ClientError>;
+
+    /// Write and verify the variables of a recipe
+    async fn apply_recipe(&self, recipe: &Recipe) -> Result<(), ClientError>;
+
+    /// Find the first recipe whose variables all hold their values
+    async fn detect_recipe<'a>(
+        &self,
+        recipes: &'a [Recipe],
+    ) -> Result<Option<&'a Recipe>, ClientError>;
+
     // ========== I/O Operations ==========
 
     /// Read single I/O
@@ -219,6 +354,20 @@ pub trait HsesClientOps: Send + Sync {
     /// Write single I/O
     async fn write_io(&self, io_number: u16, value: u8) -> Result<(), ClientError>;
 
+    /// Read a single I/O signal by its address, e.g. 10010 for bit 0 of I/O #1001
+    async fn read_io_bit(&self, address: u32) -> Result<bool, ClientError>;
+
+    /// Turn a single network input signal on or off by its address
+    async fn write_io_bit(&self, address: u32, on: bool) -> Result<(), ClientError>;
+
+    /// Write a network input for a duration, then clear it even if interrupted
+    async fn pulse_io(
+        &self,
+        io_number: u16,
+        value: u8,
+        duration: Duration,
+    ) -> Result<(), ClientError>;
+
     /// Read multiple I/O data
     async fn read_multiple_io(
         &self,
@@ -268,4 +417,17 @@ pub trait HsesClientOps: Send + Sync {
 
     /// Delete file from controller
     async fn delete_file(&self, filename: &str) -> Result<(), ClientError>;
+
+    /// Compare a job on the controller with a local source
+    async fn diff_job(&self, name: &str, local_source: &str) -> Result<JobDiff, ClientError>;
+
+    /// Delete all files matching a pattern
+    async fn delete_files(
+        &self,
+        pattern: &str,
+        options: DeleteFilesOptions,
+    ) -> Result<Vec<FileDeleteResult>, ClientError>;
+
+    /// Send file to controller and verify the stored content
+    async fn send_file_verified(&self, filename: &str, content: &[u8]) -> Result<(), ClientError>;
 }
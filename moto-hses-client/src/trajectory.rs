@@ -0,0 +1,144 @@
+//! Trajectory playback
+//!
+//! [`HsesClient::play_trajectory`](crate::HsesClient::play_trajectory) moves the robot
+//! through a sequence of positions, one move instruction per point. A
+//! [`TrajectoryControl`] pauses, resumes or cancels the playback and reports its progress
+//! while it runs.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::time::Duration;
+use tokio::sync::watch;
+
+use moto_hses_proto::MoveSpeed;
+
+/// Options for [`HsesClient::play_trajectory`](crate::HsesClient::play_trajectory)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TrajectoryOptions {
+    /// Speed of every segment (default: 10%)
+    pub speed: MoveSpeed,
+    /// Distance to a Cartesian point [mm] at which the next segment is sent; `None`
+    /// waits until the robot has stopped at each point (default: `None`)
+    ///
+    /// Pulse points always wait until the robot has stopped.
+    pub blend: Option<f32>,
+    /// Longest time to wait for a single segment (default: 60 s)
+    pub segment_timeout: Duration,
+}
+
+impl Default for TrajectoryOptions {
+    fn default() -> Self {
+        Self {
+            speed: MoveSpeed::Joint(10.0),
+            blend: None,
+            segment_timeout: Duration::from_secs(60),
+        }
+    }
+}
+
+/// Progress of a trajectory playback
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TrajectoryProgress {
+    /// Number of points reached
+    pub completed: usize,
+    pub total: usize,
+}
+
+/// Pause, resume and cancel a trajectory playback and follow its progress
+///
+/// Clones share the same state. Pausing and cancelling take effect between segments: the
+/// segment in progress is completed first.
+#[derive(Debug, Clone)]
+pub struct TrajectoryControl {
+    inner: Arc<ControlState>,
+}
+
+#[derive(Debug)]
+struct ControlState {
+    paused: watch::Sender<bool>,
+    cancelled: AtomicBool,
+    completed: AtomicUsize,
+    total: AtomicUsize,
+}
+
+impl Default for TrajectoryControl {
+    fn default() -> Self {
+        Self {
+            inner: Arc::new(ControlState {
+                paused: watch::Sender::new(false),
+                cancelled: AtomicBool::new(false),
+                completed: AtomicUsize::new(0),
+                total: AtomicUsize::new(0),
+            }),
+        }
+    }
+}
+
+impl TrajectoryControl {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Hold back the next segment until [`resume`](Self::resume) is called
+    pub fn pause(&self) {
+        self.inner.paused.send_replace(true);
+    }
+
+    pub fn resume(&self) {
+        self.inner.paused.send_replace(false);
+    }
+
+    /// Stop the playback after the segment in progress
+    pub fn cancel(&self) {
+        self.inner.cancelled.store(true, Ordering::Relaxed);
+        // Wake a paused playback so it can stop
+        self.inner.paused.send_replace(false);
+    }
+
+    #[must_use]
+    pub fn is_paused(&self) -> bool {
+        *self.inner.paused.borrow()
+    }
+
+    #[must_use]
+    pub fn is_cancelled(&self) -> bool {
+        self.inner.cancelled.load(Ordering::Relaxed)
+    }
+
+    #[must_use]
+    pub fn progress(&self) -> TrajectoryProgress {
+        TrajectoryProgress {
+            completed: self.inner.completed.load(Ordering::Relaxed),
+            total: self.inner.total.load(Ordering::Relaxed),
+        }
+    }
+
+    pub(crate) fn start(&self, total: usize) {
+        self.inner.total.store(total, Ordering::Relaxed);
+        self.inner.completed.store(0, Ordering::Relaxed);
+    }
+
+    pub(crate) fn set_completed(&self, completed: usize) {
+        self.inner.completed.store(completed, Ordering::Relaxed);
+    }
+
+    // Complete once the playback is not paused
+    pub(crate) async fn wait_resumed(&self) {
+        let mut receiver = self.inner.paused.subscribe();
+        // The sender lives as long as `self`, so waiting cannot fail
+        let _ = receiver.wait_for(|paused| !*paused).await;
+    }
+}
+
+/// Result of [`HsesClient::play_trajectory`](crate::HsesClient::play_trajectory)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TrajectoryReport {
+    /// Number of points reached
+    pub completed: usize,
+    pub total: usize,
+    /// Whether the playback was cancelled before the last point
+    pub cancelled: bool,
+    /// Time from the first move until the playback ended
+    pub duration: Duration,
+}
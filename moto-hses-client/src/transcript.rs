@@ -0,0 +1,494 @@
+//! Request transcripts and their replay
+//!
+//! Between [`HsesClient::start_transcript`] and [`HsesClient::take_transcript`], the client
+//! records every request it sends together with the response payload or the error, as a
+//! [`Transcript`]. Transcripts are saved as text, one request per line in the format of
+//! the mock's request transcripts followed by the outcome:
+//!
+//! ```text
+//! robot command=0x0079 instance=3 attribute=1 service=0x0E payload=[] response=[D2 04]
+//! robot command=0x0079 instance=900 attribute=1 service=0x0E payload=[] error=Protocol error: ...
+//! ```
+//!
+//! [`HsesClient::replay_transcript`] sends the requests of a transcript again, e.g. to a
+//! controller after a firmware upgrade or to the mock, and reports the responses that
+//! differ from the recorded ones:
+//!
+//! ```ignore
+//! client.start_transcript();
+//! run_cell_checks(&client).await?;
+//! client.take_transcript().save("before-upgrade.txt")?;
+//!
+//! // After the upgrade
+//! let transcript = Transcript::load("before-upgrade.txt")?;
+//! let report = client.replay_transcript(&transcript, &ReplayOptions::default()).await?;
+//! println!("{report}");
+//! ```
+//!
+//! Writes are skipped unless [`ReplayOptions::writes`] sends them, so by default a replay
+//! only reads from the controller.
+
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use moto_hses_proto::{Division, ProtocolError, Service};
+
+use crate::audit::write_bytes;
+use crate::protocol::RequestParams;
+use crate::types::{ClientError, DangerLevel, HsesClient};
+
+/// Request sent by the client and its outcome
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TranscriptEntry {
+    pub division: Division,
+    pub command: u16,
+    pub instance: u16,
+    pub attribute: u8,
+    pub service: Service,
+    pub payload: Vec<u8>,
+    /// Response payload, or the message of the error the request failed with
+    pub response: Result<Vec<u8>, String>,
+}
+
+impl fmt::Display for TranscriptEntry {
+    /// One line, e.g. `robot command=0x0079 instance=3 attribute=1 service=0x0E payload=[]
+    /// response=[D2 04]`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} command=0x{:04X} instance={} attribute={} service=0x{:02X} payload=",
+            self.division,
+            self.command,
+            self.instance,
+            self.attribute,
+            self.service.code()
+        )?;
+        write_bytes(f, &self.payload)?;
+        match &self.response {
+            Ok(response) => {
+                f.write_str(" response=")?;
+                write_bytes(f, response)
+            }
+            // Messages are kept on one line
+            Err(e) => write!(f, " error={}", e.replace('\n', " ")),
+        }
+    }
+}
+
+impl FromStr for TranscriptEntry {
+    type Err = ClientError;
+
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let invalid = |reason: &str| -> ClientError {
+            ProtocolError::InvalidMessage(format!("invalid transcript line '{line}': {reason}"))
+                .into()
+        };
+        let (division, mut rest) = line.trim().split_once(' ').ok_or_else(|| invalid("empty"))?;
+        let division = division.parse::<Division>().map_err(|e| invalid(&e.to_string()))?;
+
+        let mut fields = [None; 6];
+        let mut response = None;
+        while let Some((key, value, remainder)) = next_field(rest) {
+            rest = remainder;
+            let slot = match key {
+                "command" => 0,
+                "instance" => 1,
+                "attribute" => 2,
+                "service" => 3,
+                "payload" => 4,
+                "response" => 5,
+                "error" => {
+                    response = Some(Err(value.to_string()));
+                    break;
+                }
+                _ => return Err(invalid(&format!("unknown field '{key}'"))),
+            };
+            fields[slot] = Some(value);
+        }
+        let field = |slot: usize, name: &str| {
+            fields[slot].ok_or_else(|| invalid(&format!("missing field '{name}'")))
+        };
+
+        let command = parse_hex(field(0, "command")?).ok_or_else(|| invalid("bad command"))?;
+        let instance = field(1, "instance")?.parse().map_err(|_| invalid("bad instance"))?;
+        let attribute = field(2, "attribute")?.parse().map_err(|_| invalid("bad attribute"))?;
+        let service = parse_hex(field(3, "service")?)
+            .and_then(|code| u8::try_from(code).ok())
+            .ok_or_else(|| invalid("bad service"))?;
+        let service = Service::try_from(service).map_err(|e| invalid(&e.to_string()))?;
+        let payload = parse_bytes(field(4, "payload")?).ok_or_else(|| invalid("bad payload"))?;
+        let response = match (response, fields[5]) {
+            (Some(error), _) => error,
+            (None, Some(bytes)) => Ok(parse_bytes(bytes).ok_or_else(|| invalid("bad response"))?),
+            (None, None) => return Err(invalid("missing field 'response' or 'error'")),
+        };
+        Ok(Self { division, command, instance, attribute, service, payload, response })
+    }
+}
+
+// Next `key=value` of a line and the rest after it; a value in brackets may contain
+// spaces and an error message runs to the end of the line
+fn next_field(text: &str) -> Option<(&str, &str, &str)> {
+    let text = text.trim_start();
+    let (key, rest) = text.split_once('=')?;
+    if key == "error" {
+        return Some((key, rest.trim_end(), ""));
+    }
+    let end = if rest.starts_with('[') {
+        rest.find(']')? + 1
+    } else {
+        rest.find(' ').unwrap_or(rest.len())
+    };
+    Some((key, &rest[..end], &rest[end..]))
+}
+
+fn parse_hex(text: &str) -> Option<u16> {
+    u16::from_str_radix(text.strip_prefix("0x")?, 16).ok()
+}
+
+// Bytes written as `[D2 04]`
+fn parse_bytes(text: &str) -> Option<Vec<u8>> {
+    let inner = text.strip_prefix('[')?.strip_suffix(']')?;
+    inner.split_whitespace().map(|byte| u8::from_str_radix(byte, 16).ok()).collect()
+}
+
+/// Requests recorded by a client, in sending order
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Transcript {
+    pub entries: Vec<TranscriptEntry>,
+}
+
+impl fmt::Display for Transcript {
+    /// One line per entry
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for entry in &self.entries {
+            writeln!(f, "{entry}")?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Transcript {
+    type Err = ClientError;
+
+    /// Entries of the lines of `text`; empty lines and lines starting with `#` are skipped
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let entries = text
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(str::parse)
+            .collect::<Result<_, _>>()?;
+        Ok(Self { entries })
+    }
+}
+
+impl Transcript {
+    /// Read a transcript saved with [`Self::save`]
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or a line is invalid
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ClientError> {
+        std::fs::read_to_string(path)?.parse()
+    }
+
+    /// Write the transcript to `path`, one line per entry
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be written
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), ClientError> {
+        Ok(std::fs::write(path, self.to_string())?)
+    }
+}
+
+/// Decision whether to send a recorded write, see [`ReplayWrites::Gate`]
+pub type ReplayGate = Arc<dyn Fn(&TranscriptEntry) -> bool + Send + Sync>;
+
+/// What [`HsesClient::replay_transcript`] does with recorded writes
+#[derive(Clone, Default)]
+pub enum ReplayWrites {
+    /// Leave them out (default)
+    #[default]
+    Skip,
+    /// Send them like the reads; requires a client allowing writes
+    Send,
+    /// Send the writes for which the gate returns true, e.g. after asking the operator
+    Gate(ReplayGate),
+}
+
+impl fmt::Debug for ReplayWrites {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Skip => f.write_str("Skip"),
+            Self::Send => f.write_str("Send"),
+            Self::Gate(_) => f.write_str("Gate(..)"),
+        }
+    }
+}
+
+/// Options for [`HsesClient::replay_transcript`]
+#[derive(Debug, Clone, Default)]
+pub struct ReplayOptions {
+    pub writes: ReplayWrites,
+    /// Commands whose responses are expected to change between reads, e.g. the status
+    /// (0x72), positions (0x75) or the clock (0x91); only whether they succeed is compared
+    /// (default: none)
+    pub volatile_commands: Vec<u16>,
+}
+
+/// Replayed request whose outcome differs from the recorded one
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Divergence {
+    /// Position of the request in the transcript
+    pub index: usize,
+    pub recorded: TranscriptEntry,
+    /// Response payload or error message of the replay
+    pub replayed: Result<Vec<u8>, String>,
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "#{} {}", self.index + 1, self.recorded)?;
+        match (&self.recorded.response, &self.replayed) {
+            (Ok(recorded), Ok(replayed)) => {
+                let offset = recorded
+                    .iter()
+                    .zip(replayed)
+                    .position(|(recorded, replayed)| recorded != replayed)
+                    .unwrap_or_else(|| recorded.len().min(replayed.len()));
+                f.write_str("    replayed response=")?;
+                write_bytes(f, replayed)?;
+                write!(f, " (first difference at byte {offset})")
+            }
+            (_, Ok(replayed)) => {
+                f.write_str("    replayed response=")?;
+                write_bytes(f, replayed)
+            }
+            (_, Err(e)) => write!(f, "    replayed error={e}"),
+        }
+    }
+}
+
+/// Result of [`HsesClient::replay_transcript`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReplayReport {
+    /// Requests sent
+    pub replayed: usize,
+    /// Writes left out
+    pub skipped: usize,
+    pub divergences: Vec<Divergence>,
+}
+
+impl ReplayReport {
+    /// Whether every replayed request had the recorded outcome
+    #[must_use]
+    pub const fn is_clean(&self) -> bool {
+        self.divergences.is_empty()
+    }
+}
+
+impl fmt::Display for ReplayReport {
+    /// Summary line followed by the divergences
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} requests replayed, {} writes skipped, {} divergences",
+            self.replayed,
+            self.skipped,
+            self.divergences.len()
+        )?;
+        for divergence in &self.divergences {
+            write!(f, "\n{divergence}")?;
+        }
+        Ok(())
+    }
+}
+
+impl HsesClient {
+    /// Start recording the requests sent by this client and its clones
+    ///
+    /// Discards the requests recorded since the previous start.
+    pub fn start_transcript(&self) {
+        if let Ok(mut transcript) = self.inner.transcript.lock() {
+            *transcript = Some(Vec::new());
+        }
+    }
+
+    /// Stop recording and return the requests recorded since [`Self::start_transcript`]
+    #[must_use]
+    pub fn take_transcript(&self) -> Transcript {
+        let entries =
+            self.inner.transcript.lock().ok().and_then(|mut transcript| transcript.take());
+        Transcript { entries: entries.unwrap_or_default() }
+    }
+
+    /// Send the requests of `transcript` again and report the outcomes that differ from
+    /// the recorded ones
+    ///
+    /// Requests are sent one at a time in the recorded order. A request that fails is
+    /// reported as a divergence unless it failed with the same message when recorded; the
+    /// replay goes on with the next request.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ClientError::OperationNotPermitted`] if writes are to be sent and the
+    /// client does not allow them
+    pub async fn replay_transcript(
+        &self,
+        transcript: &Transcript,
+        options: &ReplayOptions,
+    ) -> Result<ReplayReport, ClientError> {
+        if !matches!(options.writes, ReplayWrites::Skip)
+            && transcript.entries.iter().any(|entry| entry.service.is_write())
+        {
+            self.check_danger_level(DangerLevel::Write, "replay writes")?;
+        }
+
+        let mut report = ReplayReport::default();
+        for (index, entry) in transcript.entries.iter().enumerate() {
+            let send = !entry.service.is_write()
+                || match &options.writes {
+                    ReplayWrites::Skip => false,
+                    ReplayWrites::Send => true,
+                    ReplayWrites::Gate(gate) => gate(entry),
+                };
+            if !send {
+                report.skipped += 1;
+                continue;
+            }
+
+            let request = RequestParams {
+                division: entry.division,
+                command: entry.command,
+                instance: entry.instance,
+                attribute: entry.attribute,
+                service: entry.service,
+                // Resent like the reads, the writes being sent only on request
+                idempotent: true,
+                block_number: 0,
+            };
+            let replayed = self
+                .send_request_with_meta(request, entry.payload.clone())
+                .await
+                .map(|(response, _meta)| response)
+                .map_err(|e| e.to_string().replace('\n', " "));
+            report.replayed += 1;
+
+            let same = match (&entry.response, &replayed) {
+                (Ok(_), Ok(_)) if options.volatile_commands.contains(&entry.command) => true,
+                (recorded, replayed) => recorded == replayed,
+            };
+            if !same {
+                report.divergences.push(Divergence { index, recorded: entry.clone(), replayed });
+            }
+        }
+        Ok(report)
+    }
+
+    // Add a sent request to the transcript, if one is being recorded
+    pub(crate) fn record_exchange(
+        &self,
+        request: &RequestParams,
+        payload: &[u8],
+        result: Result<&Vec<u8>, &ClientError>,
+    ) {
+        let Ok(mut transcript) = self.inner.transcript.lock() else {
+            return;
+        };
+        if let Some(entries) = transcript.as_mut() {
+            entries.push(TranscriptEntry {
+                division: request.division,
+                command: request.command,
+                instance: request.instance,
+                attribute: request.attribute,
+                service: request.service,
+                payload: payload.to_vec(),
+                response: result.cloned().map_err(|e| e.to_string().replace('\n', " ")),
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    fn entry(response: Result<Vec<u8>, String>) -> TranscriptEntry {
+        TranscriptEntry {
+            division: Division::Robot,
+            command: 0x79,
+            instance: 3,
+            attribute: 1,
+            service: Service::GetSingle,
+            payload: Vec::new(),
+            response,
+        }
+    }
+
+    #[test]
+    fn test_entry_line_round_trip() {
+        let read = entry(Ok(vec![0xD2, 0x04]));
+        let line = read.to_string();
+        assert_eq!(
+            line,
+            "robot command=0x0079 instance=3 attribute=1 service=0x0E payload=[] response=[D2 04]"
+        );
+        assert_eq!(line.parse::<TranscriptEntry>().unwrap(), read);
+
+        let failed = entry(Err("Protocol error: status 0x1F, added status 0x2010".to_string()));
+        assert_eq!(failed.to_string().parse::<TranscriptEntry>().unwrap(), failed);
+
+        let file = TranscriptEntry {
+            division: Division::File,
+            command: 0,
+            instance: 0,
+            attribute: 0,
+            service: Service::FileList,
+            payload: b"*.JBI".to_vec(),
+            response: Ok(b"A.JBI\r\n".to_vec()),
+        };
+        assert_eq!(file.to_string().parse::<TranscriptEntry>().unwrap(), file);
+    }
+
+    #[test]
+    fn test_transcript_parse() {
+        let text = "# recorded before the upgrade\n\n\
+                    robot command=0x0079 instance=3 attribute=1 service=0x0E payload=[] response=[D2 04]\n";
+        let transcript: Transcript = text.parse().unwrap();
+        assert_eq!(transcript.entries, [entry(Ok(vec![0xD2, 0x04]))]);
+
+        for invalid in [
+            "robot command=0x0079 instance=3 attribute=1 service=0x0E payload=[]",
+            "robot command=79 instance=3 attribute=1 service=0x0E payload=[] response=[]",
+            "robot command=0x0079 instance=3 attribute=1 service=0x0E payload=[ZZ] response=[]",
+            "arm command=0x0079 instance=3 attribute=1 service=0x0E payload=[] response=[]",
+        ] {
+            assert!(invalid.parse::<TranscriptEntry>().is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn test_divergence_report() {
+        let report = ReplayReport {
+            replayed: 2,
+            skipped: 1,
+            divergences: vec![Divergence {
+                index: 1,
+                recorded: entry(Ok(vec![0xD2, 0x04])),
+                replayed: Ok(vec![0xD2, 0x05]),
+            }],
+        };
+        assert!(!report.is_clean());
+        assert_eq!(
+            report.to_string(),
+            "2 requests replayed, 1 writes skipped, 1 divergences\n\
+             #2 robot command=0x0079 instance=3 attribute=1 service=0x0E payload=[] response=[D2 04]\n    \
+             replayed response=[D2 05] (first difference at byte 1)"
+        );
+    }
+}
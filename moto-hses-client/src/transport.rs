@@ -0,0 +1,330 @@
+//! Datagram transports
+//!
+//! The client sends and receives whole HSES frames through a [`Transport`]. It uses
+//! [`UdpTransport`] unless given another one with [`HsesClient::with_transport`], e.g. a
+//! [`ChannelTransport`] whose other end is played by a test, so that retries and block
+//! transfers can be tested without sockets:
+//!
+//! ```ignore
+//! let (client_end, controller_end) = ChannelTransport::pair();
+//! let (priority_end, _) = ChannelTransport::pair();
+//! let client = HsesClient::with_transport(ClientConfig::default(), client_end, priority_end);
+//!
+//! // Answer the requests from `controller_end.recv_datagram(..)`
+//! ```
+//!
+//! [`HsesClient::with_transport`]: crate::HsesClient::with_transport
+
+use moto_hses_proto::constants::header;
+use std::collections::HashMap;
+use std::io;
+use std::net::SocketAddr;
+use tokio::net::UdpSocket;
+use tokio::sync::{Mutex, mpsc};
+
+/// Carrier of HSES frames between the client and a controller
+///
+/// Frames are sent and received whole, one datagram each, and may be lost, duplicated or
+/// reordered like UDP datagrams; the client retries and matches responses by request ID.
+#[async_trait::async_trait]
+pub trait Transport: Send + Sync {
+    /// Send one frame to the controller
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the frame cannot be sent
+    async fn send_datagram(&self, data: &[u8]) -> io::Result<()>;
+
+    /// Wait for the next frame from the controller and copy it into `buffer`
+    ///
+    /// Returns the number of bytes copied; the rest of a longer frame is discarded.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if receiving fails or the transport was closed
+    async fn recv_datagram(&self, buffer: &mut [u8]) -> io::Result<usize>;
+}
+
+/// UDP socket bound to an ephemeral port, sending to the controller address
+#[derive(Debug)]
+pub struct UdpTransport {
+    socket: UdpSocket,
+    remote_addr: SocketAddr,
+}
+
+impl UdpTransport {
+    /// Bind a socket of the address family of `remote_addr` for exchanging frames with it
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the socket cannot be bound
+    pub async fn bind(remote_addr: SocketAddr) -> io::Result<Self> {
+        let local_addr = if remote_addr.is_ipv6() { "[::]:0" } else { "0.0.0.0:0" };
+        Ok(Self { socket: UdpSocket::bind(local_addr).await?, remote_addr })
+    }
+}
+
+#[async_trait::async_trait]
+impl Transport for UdpTransport {
+    async fn send_datagram(&self, data: &[u8]) -> io::Result<()> {
+        self.socket.send_to(data, self.remote_addr).await.map(|_| ())
+    }
+
+    async fn recv_datagram(&self, buffer: &mut [u8]) -> io::Result<usize> {
+        self.socket.recv_from(buffer).await.map(|(len, _addr)| len)
+    }
+}
+
+/// In-memory transport delivering frames to the other end of a pair
+#[derive(Debug)]
+pub struct ChannelTransport {
+    sender: mpsc::UnboundedSender<Vec<u8>>,
+    receiver: Mutex<mpsc::UnboundedReceiver<Vec<u8>>>,
+}
+
+impl ChannelTransport {
+    /// Two connected ends; frames sent on one are received on the other
+    #[must_use]
+    pub fn pair() -> (Self, Self) {
+        let (to_second, from_first) = mpsc::unbounded_channel();
+        let (to_first, from_second) = mpsc::unbounded_channel();
+        (
+            Self { sender: to_second, receiver: Mutex::new(from_second) },
+            Self { sender: to_first, receiver: Mutex::new(from_first) },
+        )
+    }
+}
+
+#[async_trait::async_trait]
+impl Transport for ChannelTransport {
+    async fn send_datagram(&self, data: &[u8]) -> io::Result<()> {
+        self.sender
+            .send(data.to_vec())
+            .map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, "other end dropped"))
+    }
+
+    async fn recv_datagram(&self, buffer: &mut [u8]) -> io::Result<usize> {
+        let frame = self
+            .receiver
+            .lock()
+            .await
+            .recv()
+            .await
+            .ok_or_else(|| io::Error::new(io::ErrorKind::BrokenPipe, "other end dropped"))?;
+        let len = frame.len().min(buffer.len());
+        buffer[..len].copy_from_slice(&frame[..len]);
+        Ok(len)
+    }
+}
+
+// Transport whose responses are routed by request ID to the requests waiting for them
+//
+// Whichever waiting request holds the reader receives from the transport and passes the
+// frames of other request IDs to their mailboxes, so concurrent requests on one socket do
+// not read and drop each other's responses. Frames of request IDs no one waits for, e.g.
+// late responses to abandoned attempts, are dropped.
+pub(crate) struct RoutedTransport {
+    transport: Box<dyn Transport>,
+    // Receive buffer, held by the request currently reading
+    reader: Mutex<Vec<u8>>,
+    mailboxes: std::sync::Mutex<HashMap<u8, mpsc::UnboundedSender<Vec<u8>>>>,
+}
+
+impl RoutedTransport {
+    pub(crate) fn new(transport: Box<dyn Transport>) -> Self {
+        Self {
+            transport,
+            reader: Mutex::new(Vec::new()),
+            mailboxes: std::sync::Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub(crate) async fn send_datagram(&self, data: &[u8]) -> io::Result<()> {
+        self.transport.send_datagram(data).await
+    }
+
+    // Mailbox for the responses to `request_ids`, to be taken before the requests are sent
+    //
+    // A request ID still registered by another mailbox, after the IDs wrapped around, is
+    // taken over.
+    pub(crate) fn register(&self, request_ids: &[u8]) -> Mailbox<'_> {
+        let (sender, frames) = mpsc::unbounded_channel();
+        if let Ok(mut mailboxes) = self.mailboxes.lock() {
+            for request_id in request_ids {
+                mailboxes.insert(*request_id, sender.clone());
+            }
+        }
+        Mailbox { transport: self, request_ids: request_ids.to_vec(), sender, frames }
+    }
+
+    // Pass a received frame to the mailbox of its request ID; false if it is for `own`
+    fn route(&self, frame: &[u8], own: &[u8]) -> bool {
+        if frame.len() < header::SIZE {
+            return false;
+        }
+        let request_id = frame[header::REQUEST_ID_OFFSET];
+        if own.contains(&request_id) {
+            return true;
+        }
+        if let Ok(mailboxes) = self.mailboxes.lock()
+            && let Some(mailbox) = mailboxes.get(&request_id)
+        {
+            let _ = mailbox.send(frame.to_vec());
+        }
+        false
+    }
+}
+
+// Responses to the requests registered with `RoutedTransport::register`, unregistered when
+// dropped
+pub(crate) struct Mailbox<'a> {
+    transport: &'a RoutedTransport,
+    request_ids: Vec<u8>,
+    sender: mpsc::UnboundedSender<Vec<u8>>,
+    frames: mpsc::UnboundedReceiver<Vec<u8>>,
+}
+
+impl Mailbox<'_> {
+    // Next frame of one of the registered request IDs, at most `buffer_size` bytes long
+    //
+    // Reads from the transport while no other request does, and otherwise waits for the
+    // reading request to pass a frame on.
+    pub(crate) async fn recv(&mut self, buffer_size: usize) -> io::Result<Vec<u8>> {
+        let transport = self.transport;
+        loop {
+            let mut buffer = tokio::select! {
+                frame = self.frames.recv() => {
+                    // The mailbox holds a sender itself, so the channel stays open
+                    if let Some(frame) = frame {
+                        return Ok(frame);
+                    }
+                    continue;
+                }
+                buffer = transport.reader.lock() => buffer,
+            };
+            // A frame may have been passed on before the reader was free
+            if let Ok(frame) = self.frames.try_recv() {
+                return Ok(frame);
+            }
+            buffer.resize(buffer_size, 0);
+            loop {
+                let len = transport.transport.recv_datagram(&mut buffer).await?;
+                if transport.route(&buffer[..len], &self.request_ids) {
+                    return Ok(buffer[..len].to_vec());
+                }
+            }
+        }
+    }
+}
+
+impl Drop for Mailbox<'_> {
+    fn drop(&mut self) {
+        if let Ok(mut mailboxes) = self.transport.mailboxes.lock() {
+            for request_id in &self.request_ids {
+                if mailboxes
+                    .get(request_id)
+                    .is_some_and(|mailbox| mailbox.same_channel(&self.sender))
+                {
+                    mailboxes.remove(request_id);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use crate::{ClientConfig, HsesClient};
+    use moto_hses_proto::constants::header;
+    use moto_hses_proto::{HsesRequestMessage, HsesResponseMessage};
+    use std::time::Duration;
+
+    fn test_client() -> (HsesClient, ChannelTransport) {
+        let (client_end, controller_end) = ChannelTransport::pair();
+        let (priority_end, _) = ChannelTransport::pair();
+        let config = ClientConfig {
+            timeout: Duration::from_millis(50),
+            retry_count: 2,
+            retry_delay: Duration::from_millis(1),
+            ..ClientConfig::default()
+        };
+        (HsesClient::with_transport(config, client_end, priority_end), controller_end)
+    }
+
+    async fn next_request(controller: &ChannelTransport) -> HsesRequestMessage {
+        let mut buffer = [0u8; 512];
+        let len = controller.recv_datagram(&mut buffer).await.unwrap();
+        HsesRequestMessage::decode(&buffer[..len]).unwrap()
+    }
+
+    fn response(request: &HsesRequestMessage, block_number: u32, payload: &[u8]) -> Vec<u8> {
+        let mut message = HsesResponseMessage::new(
+            request.header.division,
+            1,
+            request.header.request_id,
+            request.sub_header.service,
+            0,
+            0,
+            payload.to_vec(),
+        )
+        .unwrap();
+        message.header.block_number = block_number;
+        message.encode().to_vec()
+    }
+
+    #[tokio::test]
+    async fn test_retry_after_lost_response() {
+        let (client, controller) = test_client();
+        let controller = tokio::spawn(async move {
+            // The response to the first attempt is lost
+            let first = next_request(&controller).await;
+            let retry = next_request(&controller).await;
+            assert_eq!(retry.sub_header.instance, first.sub_header.instance);
+            controller.send_datagram(&response(&retry, header::LAST_BLOCK_FLAG, &[42, 0])).await
+        });
+
+        assert_eq!(client.read_register(7).await.unwrap(), 42);
+        controller.await.unwrap().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_job_start_resent_when_not_running() {
+        let (client, controller) = test_client();
+        let controller = tokio::spawn(async move {
+            // The first start is lost, and the status shows that the job did not start
+            let first = next_request(&controller).await;
+            assert_eq!(first.sub_header.command, 0x86);
+            let status = next_request(&controller).await;
+            assert_eq!(status.sub_header.command, 0x72);
+            controller.send_datagram(&response(&status, header::LAST_BLOCK_FLAG, &[0; 8])).await?;
+            let retry = next_request(&controller).await;
+            assert_eq!(retry.sub_header.command, 0x86);
+            controller.send_datagram(&response(&retry, header::LAST_BLOCK_FLAG, &[])).await
+        });
+
+        client.start_job().await.unwrap();
+        controller.await.unwrap().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_multi_block_file_receive() {
+        let (client, controller) = test_client();
+        let controller = tokio::spawn(async move {
+            let request = next_request(&controller).await;
+            controller.send_datagram(&response(&request, 1, b"NOP\r\n")).await.unwrap();
+            // The next block is sent once the client acknowledged the first one
+            let mut buffer = [0u8; 512];
+            let len = controller.recv_datagram(&mut buffer).await.unwrap();
+            assert_eq!(buffer[header::ACK_OFFSET], 1);
+            assert_eq!(&buffer[header::BLOCK_NUMBER_OFFSET..][..4], &1u32.to_le_bytes());
+            assert_eq!(len, header::SIZE);
+            let last = 2 | header::LAST_BLOCK_FLAG;
+            controller.send_datagram(&response(&request, last, b"END\r\n")).await
+        });
+
+        assert_eq!(client.receive_file("TEST.JBI").await.unwrap(), "NOP\r\nEND\r\n");
+        controller.await.unwrap().unwrap();
+    }
+}
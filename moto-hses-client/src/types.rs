@@ -1,27 +1,162 @@
 //! Type definitions for HSES client
 
 use std::collections::HashMap;
-use std::net::SocketAddr;
 use std::sync::Arc;
 use std::sync::Mutex;
 use std::sync::atomic::AtomicU8;
 use std::time::Duration;
 use thiserror::Error;
-use tokio::net::UdpSocket;
 
-use moto_hses_proto::{ProtocolError, TextEncoding};
+use moto_hses_proto::{
+    Alarm, CartesianPosition, ControllerModel, FileNameRules, MoveFrame, MoveSpeed, ProtocolError,
+    Status, SystemInfo, TextEncoding,
+};
+
+use crate::transport::RoutedTransport;
+use crate::workspace::WorkspaceLimits;
 
 /// Client configuration options
 #[derive(Debug, Clone)]
 pub struct ClientConfig {
     pub host: String,
     pub port: u16,
+    /// Time to wait for the response to one attempt of a request, or until enough
+    /// round-trip times are measured with `adaptive_timeout` (default: 300 ms)
     pub timeout: Duration,
     pub retry_count: u32,
     pub retry_delay: Duration,
+    /// Time within which a request must be answered, including all retries and the
+    /// delays between them; `None` leaves it to `retry_count` and `timeout`
+    /// (default: none)
+    pub operation_deadline: Option<Duration>,
     pub buffer_size: usize,
     /// Text encoding used by the server (default: UTF-8)
     pub text_encoding: TextEncoding,
+    /// Controller model, used to select model-specific constraints such as
+    /// file name rules (default: `YRC1000micro`)
+    pub controller_model: ControllerModel,
+    /// File name rules checked before files are sent or deleted, instead of those of
+    /// `controller_model`, which accept names of up to 32 bytes on every model; e.g.
+    /// [`FileNameRules::Short`] for a controller known to use 8.3 names (default: none)
+    pub file_name_rules: Option<FileNameRules>,
+    /// Most dangerous class of operation this client may perform; operations above it
+    /// fail with [`ClientError::OperationNotPermitted`] without being sent. With `Read`,
+    /// every write request fails: variables, I/O, job control, moves and file sends and
+    /// deletes. Emergency commands of the priority lane are not limited (default: `Write`)
+    pub max_danger_level: DangerLevel,
+    /// Limits checked before move instructions are sent; moves with targets outside them
+    /// fail with [`ClientError::SafetyViolation`] (default: none)
+    pub workspace_limits: Option<WorkspaceLimits>,
+    /// Derive the response timeout from the measured round-trip times instead of using
+    /// `timeout` throughout (default: none)
+    pub adaptive_timeout: Option<AdaptiveTimeout>,
+    /// Gap kept before robot control writes, for controllers that reject writes sent
+    /// back to back (default: none)
+    pub write_pacing: Option<WritePacing>,
+    /// Number of axes of the robots; pulse positions read are resized to it with a warning
+    /// if they carry other axes, and pulse moves with another joint count are rejected
+    /// (default: none, all 8 axes of the position data)
+    pub expected_axes: Option<usize>,
+    /// Limits on the requests in flight and waiting for a slot; further requests fail with
+    /// [`ClientError::Overloaded`] (default: none, every request is sent at once)
+    pub request_limits: Option<RequestLimits>,
+}
+
+/// Response timeout following the round-trip times of [`HsesClient::link_stats`]
+///
+/// A fixed timeout is either too short for a Wi-Fi link or waits much longer than
+/// needed on a wired one. Once a command has been answered `min_samples` times, an attempt
+/// of it waits for `multiplier` times its smoothed round-trip time, within `min` and
+/// `max`; commands with fewer samples use the smoothed round-trip time of all requests,
+/// and the configured timeout until the link has `min_samples`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AdaptiveTimeout {
+    /// Factor applied to the smoothed round-trip time (default: 4)
+    pub multiplier: u32,
+    /// Shortest timeout (default: 20 ms)
+    pub min: Duration,
+    /// Longest timeout (default: 2 s)
+    pub max: Duration,
+    /// Number of answered requests needed before the measured round-trip time is used
+    /// (default: 8)
+    pub min_samples: u64,
+}
+
+impl Default for AdaptiveTimeout {
+    fn default() -> Self {
+        Self {
+            multiplier: 4,
+            min: Duration::from_millis(20),
+            max: Duration::from_secs(2),
+            min_samples: 8,
+        }
+    }
+}
+
+/// Pacing of robot control writes
+///
+/// Some FS100 controllers reject writes that follow the previous request too closely
+/// under load. With pacing, the robot control writes of a client are sent one at a time,
+/// each at least `min_gap` after the previous write was answered. Requests of the priority
+/// lane are not paced.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WritePacing {
+    /// Least time from the previous response until a write is sent (default: 20 ms)
+    pub min_gap: Duration,
+    /// Also keep the gap after robot control reads (default: false)
+    pub after_reads: bool,
+}
+
+impl Default for WritePacing {
+    fn default() -> Self {
+        Self { min_gap: Duration::from_millis(20), after_reads: false }
+    }
+}
+
+/// Limits on the requests of a client waiting for a response
+///
+/// Without limits, every task sending a request sends it at once, so under load the
+/// requests pile up at the controller and their latency grows without bound. With limits,
+/// at most `max_in_flight` requests wait for a response, pipelined requests counting one
+/// each, and up to `max_queued` requests wait for one of them to finish. Requests beyond
+/// that fail with [`ClientError::Overloaded`] without being sent, so that producers can
+/// back off. Requests of the priority lane are not limited. The limits are taken when the
+/// client is created.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RequestLimits {
+    /// Most requests waiting for a response at a time (default: 8)
+    pub max_in_flight: usize,
+    /// Most requests waiting to be sent (default: 32)
+    pub max_queued: usize,
+}
+
+impl Default for RequestLimits {
+    fn default() -> Self {
+        Self { max_in_flight: 8, max_queued: 32 }
+    }
+}
+
+/// Service used by [`HsesClient::read_attributes`] to read payload attributes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum AttributeStrategy {
+    /// One `Get_Attribute_All` request for several attributes if the command supports it,
+    /// otherwise single reads
+    #[default]
+    Auto,
+    /// Always one `Get_Attribute_All` request
+    All,
+    /// One `Get_Attribute_Single` request per attribute, sent pipelined
+    Single,
+}
+
+/// Risk class of a client operation, ordered from least to most dangerous
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub enum DangerLevel {
+    /// Reads that do not change controller state
+    Read,
+    /// Writes such as variables, I/O, job control, moves and file transfers
+    #[default]
+    Write,
 }
 
 impl Default for ClientConfig {
@@ -32,18 +167,224 @@ impl Default for ClientConfig {
             timeout: Duration::from_millis(300),
             retry_count: 3,
             retry_delay: Duration::from_millis(100),
+            operation_deadline: None,
             buffer_size: 8192,
             text_encoding: TextEncoding::Utf8,
+            controller_model: ControllerModel::YRC1000micro,
+            file_name_rules: None,
+            max_danger_level: DangerLevel::Write,
+            workspace_limits: None,
+            adaptive_timeout: None,
+            write_pacing: None,
+            expected_axes: None,
+            request_limits: None,
         }
     }
 }
 
+/// Options for [`HsesClient::delete_files`]
+#[derive(Debug, Clone)]
+pub struct DeleteFilesOptions {
+    /// List the matching files without deleting them (default: false)
+    pub dry_run: bool,
+    /// Maximum number of delete requests in flight at once (default: 4)
+    ///
+    /// Each additional request uses its own connection to the controller.
+    pub max_concurrency: usize,
+}
+
+impl Default for DeleteFilesOptions {
+    fn default() -> Self {
+        Self { dry_run: false, max_concurrency: 4 }
+    }
+}
+
+/// Outcome of deleting a single file with [`HsesClient::delete_files`]
+#[derive(Debug)]
+pub enum FileDeleteOutcome {
+    /// The file was deleted
+    Deleted,
+    /// The file matched the pattern and would be deleted (dry run)
+    WouldDelete,
+    /// The delete request failed
+    Failed(ClientError),
+}
+
+/// Per-file result of [`HsesClient::delete_files`]
+#[derive(Debug)]
+pub struct FileDeleteResult {
+    pub filename: String,
+    pub outcome: FileDeleteOutcome,
+}
+
+impl FileDeleteResult {
+    /// Whether the file was deleted or would be deleted in a dry run
+    #[must_use]
+    pub const fn is_ok(&self) -> bool {
+        !matches!(self.outcome, FileDeleteOutcome::Failed(_))
+    }
+}
+
+/// Result of [`HsesClient::run_job_and_wait`]
+#[derive(Debug, Clone)]
+pub struct JobRunReport {
+    pub job_name: String,
+    /// Time from the start request until the controller reported the job stopped
+    pub duration: Duration,
+    /// Status read after the job stopped
+    pub final_status: Status,
+    /// Alarms that were not active before the job was started
+    pub alarms: Vec<Alarm>,
+}
+
+impl JobRunReport {
+    /// Whether the job stopped without raising alarms
+    #[must_use]
+    pub const fn is_success(&self) -> bool {
+        self.alarms.is_empty()
+    }
+}
+
+/// Cartesian increment for [`HsesClient::move_incremental`]
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct CartesianDelta {
+    // X, Y, Z [mm]
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+    // RX, RY, RZ [deg]
+    pub rx: f32,
+    pub ry: f32,
+    pub rz: f32,
+}
+
+impl CartesianDelta {
+    /// Translation without a change of posture
+    #[must_use]
+    pub const fn translation(x: f32, y: f32, z: f32) -> Self {
+        Self { x, y, z, rx: 0.0, ry: 0.0, rz: 0.0 }
+    }
+
+    /// Posture change without translation
+    #[must_use]
+    pub const fn rotation(rx: f32, ry: f32, rz: f32) -> Self {
+        Self { x: 0.0, y: 0.0, z: 0.0, rx, ry, rz }
+    }
+
+    /// Delta with every component limited to the maximum of `limits`
+    #[must_use]
+    pub fn clamped(&self, limits: &IncrementalMoveLimits) -> Self {
+        let translation = |value: f32| value.clamp(-limits.max_translation, limits.max_translation);
+        let rotation = |value: f32| value.clamp(-limits.max_rotation, limits.max_rotation);
+        Self {
+            x: translation(self.x),
+            y: translation(self.y),
+            z: translation(self.z),
+            rx: rotation(self.rx),
+            ry: rotation(self.ry),
+            rz: rotation(self.rz),
+        }
+    }
+}
+
+/// Largest increment [`HsesClient::move_incremental`] sends in a single move
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct IncrementalMoveLimits {
+    /// Maximum translation per axis [mm] (default: 10.0)
+    pub max_translation: f32,
+    /// Maximum rotation per axis [deg] (default: 5.0)
+    pub max_rotation: f32,
+}
+
+impl Default for IncrementalMoveLimits {
+    fn default() -> Self {
+        Self { max_translation: 10.0, max_rotation: 5.0 }
+    }
+}
+
+/// Options for [`HsesClient::move_incremental`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct IncrementalMoveOptions {
+    /// Frame the delta is given in (default: base frame)
+    pub frame: MoveFrame,
+    /// Speed of the move (default: 10 mm/s)
+    pub speed: MoveSpeed,
+    /// Components beyond these limits are clamped before the move is sent
+    pub limits: IncrementalMoveLimits,
+}
+
+impl Default for IncrementalMoveOptions {
+    fn default() -> Self {
+        Self {
+            frame: MoveFrame::Base,
+            speed: MoveSpeed::Linear(10.0),
+            limits: IncrementalMoveLimits::default(),
+        }
+    }
+}
+
+/// Result of [`HsesClient::move_incremental`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct IncrementalMove {
+    /// Position of the robot before the move (base frame)
+    pub start: CartesianPosition,
+    /// Delta sent to the controller after clamping
+    pub delta: CartesianDelta,
+    /// Whether the requested delta exceeded the limits
+    pub clamped: bool,
+}
+
+/// Request ID and timing of a completed request
+///
+/// The request ID matches the one in the HSES header of the successful attempt, so it can
+/// be used to find the exchange in packet captures.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResponseMeta {
+    pub request_id: u8,
+    /// Number of attempts including the successful one
+    pub attempts: u32,
+    /// Round-trip time of the successful attempt
+    pub rtt: Duration,
+    pub command: u16,
+    pub instance: u16,
+}
+
+/// Callback invoked with the [`ResponseMeta`] of every successful request
+pub type ResponseHook = Arc<dyn Fn(&ResponseMeta) + Send + Sync>;
+
 /// Internal client state
 pub(crate) struct InnerClient {
-    pub socket: UdpSocket,
-    pub remote_addr: SocketAddr,
+    /// Configuration, replaced as a whole by [`HsesClient::update_config`]
+    pub config: std::sync::RwLock<Arc<ClientConfig>>,
+    pub transport: RoutedTransport,
+    /// Transport of the priority lane used by emergency commands
+    pub priority_transport: tokio::sync::Mutex<RoutedTransport>,
+    /// Controller address, for messages
+    pub remote_addr: String,
+    /// Whether the transports were given to [`HsesClient::with_transport`] rather than
+    /// bound to the configured address
+    pub custom_transport: bool,
     pub request_id: AtomicU8,
     pub _pending_requests: Arc<Mutex<HashMap<u8, PendingRequest>>>,
+    pub response_hook: Mutex<Option<ResponseHook>>,
+    /// Sink receiving a record of every write request
+    pub auditor: Mutex<Option<crate::audit::Auditor>>,
+    /// Requests recorded since [`HsesClient::start_transcript`], if recording
+    pub transcript: Mutex<Option<Vec<crate::transcript::TranscriptEntry>>>,
+    /// Round-trip times of the answered requests
+    pub link_stats: Mutex<crate::connection::LinkStatsRecorder>,
+    /// Controller identity recorded by [`HsesClient::connect`]
+    pub info: Mutex<Option<SystemInfo>>,
+    /// Optional controller features detected by [`HsesClient::connect`]
+    pub capabilities: Mutex<crate::capabilities::Capabilities>,
+    /// Held while a paced write waits for its gap and is answered
+    pub write_sequence: tokio::sync::Mutex<()>,
+    /// When the last request counted by the write pacing was answered
+    pub last_paced_response: Mutex<Option<std::time::Instant>>,
+    /// Slots of the configured request limits
+    pub request_slots: Option<crate::connection::RequestSlots>,
+    /// Set by [`HsesClient::set_strict_encoding`]
+    pub strict_encoding: std::sync::atomic::AtomicBool,
 }
 
 /// Pending request tracking
@@ -55,7 +396,6 @@ pub(crate) struct PendingRequest {
 /// Main HSES client
 pub struct HsesClient {
     pub(crate) inner: Arc<InnerClient>,
-    pub config: ClientConfig,
 }
 
 /// Client-specific errors
@@ -73,6 +413,20 @@ pub enum ClientError {
     SystemError(String),
     #[error("Connection failed after {0} retries")]
     ConnectionFailed(u32),
+    #[error("Connect error: {0}")]
+    ConnectError(String),
+    #[error("Encoding error: {0}")]
+    EncodingError(String),
+    #[error("Verification failed: {0}")]
+    VerificationFailed(String),
+    #[error("Operation not permitted: {0}")]
+    OperationNotPermitted(String),
+    #[error("Safety violation: {0}")]
+    SafetyViolation(String),
+    #[error("Overloaded: {0}")]
+    Overloaded(String),
+    #[error("Unsupported by the controller: {0}")]
+    Unsupported(String),
 }
 
 #[cfg(test)]
@@ -85,7 +439,14 @@ mod tests {
         assert_eq!(config.timeout.as_millis(), 300);
         assert_eq!(config.retry_count, 3);
         assert_eq!(config.retry_delay.as_millis(), 100);
+        assert!(config.operation_deadline.is_none());
         assert_eq!(config.buffer_size, 8192);
+        assert_eq!(config.max_danger_level, DangerLevel::Write);
+        assert!(config.workspace_limits.is_none());
+        assert!(config.adaptive_timeout.is_none());
+        assert!(config.write_pacing.is_none());
+        assert!(config.expected_axes.is_none());
+        assert!(config.request_limits.is_none());
     }
 
     #[test]
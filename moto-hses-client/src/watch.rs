@@ -0,0 +1,177 @@
+//! Variable watch
+//!
+//! [`HsesClient::watch_variables`] reads a list of variables at a fixed interval, like the
+//! variable monitor of the programming pendant, and yields a [`VariableChange`] for every
+//! variable whose value differs from the previous read. The first read reports every
+//! variable, without an old value. Each read is one batch of pipelined requests
+//! ([`HsesClient::read_variables`]).
+//!
+//! ```ignore
+//! use moto_hses_client::VariableRef::{B, D, R};
+//!
+//! let changes = client.watch_variables(vec![B(0), D(5), R(2)], Duration::from_millis(200));
+//! let mut changes = std::pin::pin!(changes);
+//! while let Some(change) = changes.next().await {
+//!     let change = change?;
+//!     println!("{}: {:?} -> {:?}", change.variable, change.old, change.new);
+//! }
+//! ```
+//!
+//! A failed read is yielded as an error; the watch goes on with the next interval.
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::time::{Duration, SystemTime};
+
+use futures::Stream;
+use tokio::time::{Interval, MissedTickBehavior};
+
+use crate::recipe::VariableValue;
+use crate::traits::HsesClientOps;
+use crate::types::{ClientError, HsesClient};
+
+/// Variable of a given type and number
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum VariableRef {
+    /// Byte variable (B)
+    B(u16),
+    /// Integer variable (I)
+    I(u16),
+    /// Double precision integer variable (D)
+    D(u16),
+    /// Real variable (R)
+    R(u16),
+    /// Character variable (S)
+    S(u16),
+}
+
+impl VariableRef {
+    #[must_use]
+    pub const fn index(self) -> u16 {
+        match self {
+            Self::B(index) | Self::I(index) | Self::D(index) | Self::R(index) | Self::S(index) => {
+                index
+            }
+        }
+    }
+
+    /// Letter of the variable type, e.g. 'D'
+    #[must_use]
+    pub const fn type_letter(self) -> char {
+        match self {
+            Self::B(_) => 'B',
+            Self::I(_) => 'I',
+            Self::D(_) => 'D',
+            Self::R(_) => 'R',
+            Self::S(_) => 'S',
+        }
+    }
+}
+
+impl fmt::Display for VariableRef {
+    /// Name of the variable, e.g. "D005"
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{:03}", self.type_letter(), self.index())
+    }
+}
+
+/// Value of a watched variable that changed
+#[derive(Debug, Clone, PartialEq)]
+pub struct VariableChange {
+    pub variable: VariableRef,
+    /// Value of the previous read; `None` on the first read
+    pub old: Option<VariableValue>,
+    pub new: VariableValue,
+    /// Time the read with the new value was sent
+    pub time: SystemTime,
+}
+
+impl HsesClient {
+    /// Read `variables` every `interval` and yield the changes of their values
+    ///
+    /// See the [module documentation](crate::watch).
+    pub fn watch_variables(
+        &self,
+        variables: Vec<VariableRef>,
+        interval: Duration,
+    ) -> impl Stream<Item = Result<VariableChange, ClientError>> + '_ {
+        watch_variables(self, variables, interval)
+    }
+}
+
+/// Read `variables` through any client every `interval` and yield the changes of their
+/// values
+///
+/// Same as [`HsesClient::watch_variables`], for e.g. a
+/// [`SharedHsesClient`](crate::SharedHsesClient).
+pub fn watch_variables<C: HsesClientOps>(
+    client: &C,
+    variables: Vec<VariableRef>,
+    interval: Duration,
+) -> impl Stream<Item = Result<VariableChange, ClientError>> + '_ {
+    let watch = Watch {
+        client,
+        values: vec![None; variables.len()],
+        variables,
+        interval,
+        ticker: None,
+        pending: VecDeque::new(),
+    };
+    futures::stream::unfold(watch, |mut watch| async move {
+        let next = watch.next().await;
+        Some((next, watch))
+    })
+}
+
+// State of a running watch
+struct Watch<'a, C> {
+    client: &'a C,
+    variables: Vec<VariableRef>,
+    values: Vec<Option<VariableValue>>,
+    interval: Duration,
+    // Created on the first read, within the runtime
+    ticker: Option<Interval>,
+    pending: VecDeque<VariableChange>,
+}
+
+impl<C: HsesClientOps> Watch<'_, C> {
+    // Next change, reading the variables as often as needed to find one
+    async fn next(&mut self) -> Result<VariableChange, ClientError> {
+        loop {
+            if let Some(change) = self.pending.pop_front() {
+                return Ok(change);
+            }
+            let interval = self.interval.max(Duration::from_millis(1));
+            let ticker = self.ticker.get_or_insert_with(|| {
+                let mut ticker = tokio::time::interval(interval);
+                ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+                ticker
+            });
+            ticker.tick().await;
+
+            let time = SystemTime::now();
+            let values = self.client.read_variables(&self.variables).await?;
+            for ((&variable, old), new) in self.variables.iter().zip(&mut self.values).zip(values) {
+                if old.as_ref() != Some(&new) {
+                    self.pending.push_back(VariableChange {
+                        variable,
+                        old: old.replace(new.clone()),
+                        new,
+                        time,
+                    });
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_variable_name() {
+        assert_eq!(VariableRef::D(5).to_string(), "D005");
+        assert_eq!(VariableRef::S(120).to_string(), "S120");
+    }
+}
@@ -0,0 +1,306 @@
+//! Client-side workspace limits
+//!
+//! Configured through [`ClientConfig::workspace_limits`](crate::ClientConfig::workspace_limits),
+//! the limits are checked before a move instruction is sent, so an out-of-bounds target is
+//! rejected with [`ClientError::SafetyViolation`] without reaching the controller. They
+//! complement, and do not replace, the soft limits and interference areas configured on
+//! the controller. [`HsesClient::read_axis_limits`] reads those soft limits, so the
+//! client-side limits can follow them.
+
+use moto_hses_proto::parameter::{SERVO_PARAMETER_FILE, SOFT_LIMIT_AXES};
+use moto_hses_proto::{CartesianPosition, MoveFrame, ParameterFile, ProtocolError};
+
+use crate::types::{CartesianDelta, ClientError, HsesClient};
+
+/// Allowed range of a robot axis [pulse]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AxisRange {
+    pub min: i32,
+    pub max: i32,
+}
+
+impl AxisRange {
+    #[must_use]
+    pub const fn new(min: i32, max: i32) -> Self {
+        Self { min, max }
+    }
+
+    #[must_use]
+    pub const fn contains(&self, pulse: i32) -> bool {
+        self.min <= pulse && pulse <= self.max
+    }
+}
+
+/// Pulse soft limits of the axes of a control group, as configured on the controller
+///
+/// The ranges bound the position sliders of a UI; [`Self::to_workspace_limits`] checks
+/// moves against them before they are sent.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AxisLimits {
+    /// Control group of the axes (1: R1, 2: R2, ...)
+    pub control_group: u8,
+    /// Range of each axis in axis order
+    pub axes: Vec<AxisRange>,
+}
+
+impl AxisLimits {
+    /// Soft limits of `control_group` in a servo parameter file
+    ///
+    /// Axes are taken in order until one lacks a limit; trailing axes with both limits 0
+    /// are unused and left out.
+    #[must_use]
+    pub fn from_parameters(file: &ParameterFile, control_group: u8) -> Self {
+        let mut axes: Vec<AxisRange> = (0..SOFT_LIMIT_AXES)
+            .map_while(|axis| file.soft_limit(control_group, axis))
+            .map(|(min, max)| AxisRange::new(min, max))
+            .collect();
+        while axes.last().is_some_and(|range| *range == AxisRange::new(0, 0)) {
+            axes.pop();
+        }
+        Self { control_group, axes }
+    }
+
+    /// Workspace limits checking pulse moves against these soft limits
+    #[must_use]
+    pub fn to_workspace_limits(&self) -> WorkspaceLimits {
+        WorkspaceLimits {
+            axis_ranges: self.axes.iter().copied().map(Some).collect(),
+            ..WorkspaceLimits::new()
+        }
+    }
+}
+
+/// Axis-aligned box the control point must stay in, in the base frame [mm]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CartesianBounds {
+    /// Lower X, Y and Z bounds
+    pub min: [f32; 3],
+    /// Upper X, Y and Z bounds
+    pub max: [f32; 3],
+}
+
+impl CartesianBounds {
+    #[must_use]
+    pub const fn new(min: [f32; 3], max: [f32; 3]) -> Self {
+        Self { min, max }
+    }
+
+    #[must_use]
+    pub fn contains(&self, point: [f32; 3]) -> bool {
+        (0..3).all(|axis| self.min[axis] <= point[axis] && point[axis] <= self.max[axis])
+    }
+}
+
+/// Limits a move target has to satisfy before it is sent
+///
+/// Pulse ranges apply to pulse moves and the Cartesian bounds to Cartesian moves; the
+/// client has no robot kinematics to convert between the two.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct WorkspaceLimits {
+    /// Range of each robot axis in axis order; axes without a range are not checked
+    pub axis_ranges: Vec<Option<AxisRange>>,
+    /// Region of the control point; `None` leaves Cartesian moves unchecked
+    pub cartesian_bounds: Option<CartesianBounds>,
+}
+
+impl WorkspaceLimits {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Limit robot axis `axis` (0-based) to `range`
+    #[must_use]
+    pub fn with_axis_range(mut self, axis: usize, range: AxisRange) -> Self {
+        if self.axis_ranges.len() <= axis {
+            self.axis_ranges.resize(axis + 1, None);
+        }
+        self.axis_ranges[axis] = Some(range);
+        self
+    }
+
+    #[must_use]
+    pub const fn with_cartesian_bounds(mut self, bounds: CartesianBounds) -> Self {
+        self.cartesian_bounds = Some(bounds);
+        self
+    }
+
+    /// Check a pulse target against the axis ranges
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ClientError::SafetyViolation`] naming the first axis out of its range
+    pub fn check_pulse(&self, joints: &[i32]) -> Result<(), ClientError> {
+        for (axis, (&pulse, range)) in joints.iter().zip(&self.axis_ranges).enumerate() {
+            if let Some(range) = range
+                && !range.contains(pulse)
+            {
+                return Err(ClientError::SafetyViolation(format!(
+                    "axis {} target {pulse} outside {}..={}",
+                    axis + 1,
+                    range.min,
+                    range.max
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Check a control point position in the base frame against the Cartesian bounds
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ClientError::SafetyViolation`] if the point is outside the bounds
+    pub fn check_point(&self, point: [f32; 3]) -> Result<(), ClientError> {
+        match &self.cartesian_bounds {
+            Some(bounds) if !bounds.contains(point) => Err(ClientError::SafetyViolation(format!(
+                "target ({}, {}, {}) outside {:?}..={:?}",
+                point[0], point[1], point[2], bounds.min, bounds.max
+            ))),
+            _ => Ok(()),
+        }
+    }
+
+    /// Check the target of an incremental move from `start`
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ClientError::SafetyViolation`] if the target is outside the bounds, or
+    /// cannot be located because the delta is given in a user frame
+    pub fn check_increment(
+        &self,
+        start: &CartesianPosition,
+        delta: &CartesianDelta,
+        frame: MoveFrame,
+    ) -> Result<(), ClientError> {
+        if self.cartesian_bounds.is_none() {
+            return Ok(());
+        }
+        let target = incremental_target(start, delta, frame).ok_or_else(|| {
+            ClientError::SafetyViolation(
+                "targets of moves in a user frame cannot be checked".to_string(),
+            )
+        })?;
+        self.check_point(target)
+    }
+}
+
+impl HsesClient {
+    /// Read the pulse soft limits of control group `control_group`
+    ///
+    /// HSES has no parameter read command, so the limits are taken from the servo
+    /// parameter file (`SC.PRM`) received over the file control port.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be received or parsed, or
+    /// [`ProtocolError::FileError`] if it has no soft limits for the control group
+    pub async fn read_axis_limits(&self, control_group: u8) -> Result<AxisLimits, ClientError> {
+        let content = self.receive_file(SERVO_PARAMETER_FILE).await?;
+        let limits = AxisLimits::from_parameters(&ParameterFile::parse(&content)?, control_group);
+        if limits.axes.is_empty() {
+            return Err(ProtocolError::FileError(format!(
+                "{SERVO_PARAMETER_FILE} has no soft limits for control group {control_group}"
+            ))
+            .into());
+        }
+        Ok(limits)
+    }
+}
+
+// Control point position in the base frame after an incremental move
+//
+// The robot frame is taken as the base frame, which holds unless the robot is mounted on
+// a traveling axis. Increments in a user frame need the user frame definition, which the
+// client does not know.
+fn incremental_target(
+    start: &CartesianPosition,
+    delta: &CartesianDelta,
+    frame: MoveFrame,
+) -> Option<[f32; 3]> {
+    let translation = match frame {
+        MoveFrame::Base | MoveFrame::Robot => [delta.x, delta.y, delta.z],
+        MoveFrame::Tool => rotate([delta.x, delta.y, delta.z], start),
+        MoveFrame::User => return None,
+    };
+    Some([start.x + translation[0], start.y + translation[1], start.z + translation[2]])
+}
+
+// Rotate a tool frame vector into the base frame (posture Rz * Ry * Rx)
+#[allow(clippy::suboptimal_flops)]
+fn rotate(vector: [f32; 3], posture: &CartesianPosition) -> [f32; 3] {
+    let (sx, cx) = posture.rx.to_radians().sin_cos();
+    let (sy, cy) = posture.ry.to_radians().sin_cos();
+    let (sz, cz) = posture.rz.to_radians().sin_cos();
+    let rows = [
+        [cz * cy, cz * sy * sx - sz * cx, cz * sy * cx + sz * sx],
+        [sz * cy, sz * sy * sx + cz * cx, sz * sy * cx - cz * sx],
+        [-sy, cy * sx, cy * cx],
+    ];
+    rows.map(|row| row[0] * vector[0] + row[1] * vector[1] + row[2] * vector[2])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use moto_hses_proto::payload::position::{Configuration, ExtendedConfiguration};
+
+    fn position(rx: f32, ry: f32, rz: f32) -> CartesianPosition {
+        CartesianPosition::new(
+            100.0,
+            0.0,
+            50.0,
+            rx,
+            ry,
+            rz,
+            0,
+            0,
+            Configuration::from_raw(0),
+            ExtendedConfiguration::from_raw(0),
+        )
+    }
+
+    #[test]
+    fn test_check_pulse() {
+        let limits = WorkspaceLimits::new().with_axis_range(1, AxisRange::new(-1000, 1000));
+        assert!(limits.check_pulse(&[50_000, 1000, 50_000]).is_ok());
+        assert!(matches!(
+            limits.check_pulse(&[0, -1001]),
+            Err(ClientError::SafetyViolation(message)) if message.starts_with("axis 2")
+        ));
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn test_axis_limits_from_parameters() {
+        let file = ParameterFile::parse(
+            "S1C1G400=1000,2000,0,0,0,0,0,0\nS1C1G410=-1000,-500,0,0,0,0,0,0\nS1C2G400=10\n",
+        )
+        .unwrap();
+        let limits = AxisLimits::from_parameters(&file, 1);
+        assert_eq!(limits.axes, vec![AxisRange::new(-1000, 1000), AxisRange::new(-500, 2000)]);
+        assert!(limits.to_workspace_limits().check_pulse(&[0, 2001]).is_err());
+        assert!(AxisLimits::from_parameters(&file, 2).axes.is_empty());
+    }
+
+    #[test]
+    fn test_check_increment_in_tool_frame() {
+        let limits = WorkspaceLimits::new()
+            .with_cartesian_bounds(CartesianBounds::new([0.0, -10.0, 0.0], [200.0, 10.0, 60.0]));
+        let delta = CartesianDelta::translation(0.0, 0.0, 20.0);
+
+        // Tool Z pointing down (rotated 180 deg about X) moves the control point down
+        assert!(
+            limits.check_increment(&position(180.0, 0.0, 0.0), &delta, MoveFrame::Tool).is_ok()
+        );
+        assert!(limits.check_increment(&position(0.0, 0.0, 0.0), &delta, MoveFrame::Tool).is_err());
+        assert!(limits.check_increment(&position(0.0, 0.0, 0.0), &delta, MoveFrame::Base).is_err());
+
+        // Tool Z rotated onto base -Y by 90 deg about X
+        let sideways = CartesianDelta::translation(0.0, 0.0, 15.0);
+        assert!(
+            limits.check_increment(&position(90.0, 0.0, 0.0), &sideways, MoveFrame::Tool).is_err()
+        );
+        assert!(limits.check_increment(&position(0.0, 0.0, 0.0), &delta, MoveFrame::User).is_err());
+    }
+}
@@ -0,0 +1,308 @@
+//! Cooperative exclusive writer lock
+//!
+//! Several applications, e.g. two HMIs, can talk to the same controller, and nothing stops
+//! them from overwriting each other's variables and I/O. A [`WriterLock`] lets applications
+//! that agree on it take turns: the owner of the lock is kept in a double variable (D) of
+//! the controller, and the next variable is a heartbeat counter the owner increments while
+//! it holds the lock. A lock whose heartbeat has not changed for the TTL is stale and can be
+//! taken over, so the lock is released automatically when its owner disconnects or stops.
+//!
+//! Clones of a [`WriterLock`] share it within the application: only one task holds the
+//! lease at a time.
+//!
+//! ```ignore
+//! let lock = WriterLock::new(client, WriterLockOptions::default());
+//! let lease = lock.acquire(Duration::from_secs(10)).await?;
+//! // Write variables and I/O while `lease.is_held()`
+//! lease.release().await?;
+//! ```
+//!
+//! The lock is cooperative: writes of applications that do not use it are not blocked.
+//! HSES has no atomic compare-and-swap, so two applications acquiring a free lock in the
+//! same instant can both succeed; the loser notices at its next heartbeat, when
+//! [`WriterLease::is_held`] turns false.
+
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use tokio::sync::{Mutex, OwnedMutexGuard, watch};
+
+use crate::task;
+use crate::traits::HsesClientOps;
+use crate::types::ClientError;
+
+/// Options for [`WriterLock::new`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WriterLockOptions {
+    /// Double variable holding the owner token; the next one holds the heartbeat counter
+    /// (default: 98)
+    pub variable: u16,
+    /// Time without heartbeat after which a lock is stale (default: 5 s)
+    pub ttl: Duration,
+    /// Interval between heartbeats of the owner and between attempts of
+    /// [`WriterLock::acquire`] (default: 1 s)
+    pub heartbeat_interval: Duration,
+}
+
+impl Default for WriterLockOptions {
+    fn default() -> Self {
+        Self {
+            variable: 98,
+            ttl: Duration::from_secs(5),
+            heartbeat_interval: Duration::from_secs(1),
+        }
+    }
+}
+
+// Owner and heartbeat of a lock held by another application, and when they were first seen
+#[derive(Debug, Clone, Copy)]
+struct Observation {
+    owner: i32,
+    heartbeat: i32,
+    since: Instant,
+}
+
+/// Exclusive writer lock shared with other applications through controller variables
+pub struct WriterLock<C> {
+    client: Arc<C>,
+    options: WriterLockOptions,
+    token: i32,
+    local: Arc<Mutex<()>>,
+    observed: Arc<std::sync::Mutex<Option<Observation>>>,
+}
+
+impl<C> Clone for WriterLock<C> {
+    fn clone(&self) -> Self {
+        Self {
+            client: Arc::clone(&self.client),
+            options: self.options,
+            token: self.token,
+            local: Arc::clone(&self.local),
+            observed: Arc::clone(&self.observed),
+        }
+    }
+}
+
+impl<C: HsesClientOps + 'static> WriterLock<C> {
+    /// Lock of `client` with a token identifying this application
+    #[must_use]
+    pub fn new(client: C, options: WriterLockOptions) -> Self {
+        Self {
+            client: Arc::new(client),
+            options,
+            token: new_token(),
+            local: Arc::new(Mutex::new(())),
+            observed: Arc::new(std::sync::Mutex::new(None)),
+        }
+    }
+
+    /// Token written to the owner variable while this application holds the lock
+    #[must_use]
+    pub const fn token(&self) -> i32 {
+        self.token
+    }
+
+    /// Token of the current owner, `None` if the lock is free
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if communication fails
+    pub async fn owner(&self) -> Result<Option<i32>, ClientError> {
+        let owner = self.client.read_i32(self.options.variable).await?;
+        Ok((owner != 0).then_some(owner))
+    }
+
+    /// Take the lock if it is free, stale or held by this application
+    ///
+    /// A lock held by another application is stale once its heartbeat has been seen
+    /// unchanged for the TTL, so the first attempt on a stale lock fails too.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ClientError::OperationNotPermitted`] if another task or application
+    /// holds the lock, or an error if communication fails
+    pub async fn try_acquire(&self) -> Result<WriterLease<C>, ClientError> {
+        let guard = Arc::clone(&self.local).try_lock_owned().map_err(|_| {
+            ClientError::OperationNotPermitted(
+                "writer lock held by another task of this application".to_string(),
+            )
+        })?;
+        let variable = self.options.variable;
+        let values = self.client.read_multiple_i32(variable, 2).await?;
+        let [owner, heartbeat] = values[..] else {
+            return Err(ClientError::SystemError("short read of the writer lock".to_string()));
+        };
+        if owner != 0 && owner != self.token && !self.is_stale(owner, heartbeat) {
+            return Err(ClientError::OperationNotPermitted(format!(
+                "writer lock held by 0x{owner:08X}"
+            )));
+        }
+
+        self.client.write_multiple_i32(variable, vec![self.token, 0]).await?;
+        // Another application may have written its token at the same time
+        let owner = self.client.read_i32(variable).await?;
+        if owner != self.token {
+            return Err(ClientError::OperationNotPermitted(format!(
+                "writer lock taken by 0x{owner:08X}"
+            )));
+        }
+        if let Ok(mut observed) = self.observed.lock() {
+            *observed = None;
+        }
+        log::debug!("Writer lock 0x{:08X} acquired in D{variable}", self.token);
+        Ok(WriterLease::start(self.clone(), guard))
+    }
+
+    /// Take the lock, trying again every heartbeat interval until `timeout`
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ClientError::TimeoutError`] if the lock is not free before `timeout`, or
+    /// an error if communication fails
+    pub async fn acquire(&self, timeout: Duration) -> Result<WriterLease<C>, ClientError> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            match self.try_acquire().await {
+                Err(ClientError::OperationNotPermitted(reason)) => {
+                    if Instant::now() + self.options.heartbeat_interval > deadline {
+                        return Err(ClientError::TimeoutError(format!(
+                            "{reason} for longer than {timeout:?}"
+                        )));
+                    }
+                    tokio::time::sleep(self.options.heartbeat_interval).await;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    // Whether the lock of `owner` has shown `heartbeat` for the TTL
+    fn is_stale(&self, owner: i32, heartbeat: i32) -> bool {
+        let Ok(mut observed) = self.observed.lock() else {
+            return false;
+        };
+        match *observed {
+            Some(seen) if seen.owner == owner && seen.heartbeat == heartbeat => {
+                seen.since.elapsed() >= self.options.ttl
+            }
+            _ => {
+                *observed = Some(Observation { owner, heartbeat, since: Instant::now() });
+                false
+            }
+        }
+    }
+}
+
+/// Ownership of a [`WriterLock`], kept alive by a heartbeat task
+///
+/// Dropping the lease within a Tokio runtime releases the lock in the background;
+/// [`Self::release`] waits for it.
+pub struct WriterLease<C: HsesClientOps + 'static> {
+    lock: WriterLock<C>,
+    heartbeat: Option<tokio::task::JoinHandle<()>>,
+    held: watch::Receiver<bool>,
+    guard: Option<OwnedMutexGuard<()>>,
+}
+
+impl<C: HsesClientOps + 'static> WriterLease<C> {
+    fn start(lock: WriterLock<C>, guard: OwnedMutexGuard<()>) -> Self {
+        let (held_sender, held) = watch::channel(true);
+        let heartbeat = task::spawn("hses-writer-lock-heartbeat", beat(lock.clone(), held_sender));
+        Self { lock, heartbeat: Some(heartbeat), held, guard: Some(guard) }
+    }
+
+    /// Whether the lock is still held: false once another application took it over or
+    /// the heartbeat failed for the TTL
+    #[must_use]
+    pub fn is_held(&self) -> bool {
+        *self.held.borrow()
+    }
+
+    /// Wait until the lock is lost, e.g. to stop writing
+    pub async fn lost(&mut self) {
+        let _ = self.held.wait_for(|held| !held).await;
+    }
+
+    /// Release the lock, unless another application took it over meanwhile
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if communication fails; the lock then becomes stale after the TTL
+    pub async fn release(mut self) -> Result<(), ClientError> {
+        if let Some(heartbeat) = self.heartbeat.take() {
+            heartbeat.abort();
+        }
+        let result = release(&self.lock).await;
+        self.guard.take();
+        result
+    }
+}
+
+impl<C: HsesClientOps + 'static> Drop for WriterLease<C> {
+    fn drop(&mut self) {
+        if let Some(heartbeat) = self.heartbeat.take() {
+            heartbeat.abort();
+            let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+                return;
+            };
+            let lock = self.lock.clone();
+            let guard = self.guard.take();
+            task::spawn_on(&runtime, "hses-writer-lock-release", async move {
+                if let Err(e) = release(&lock).await {
+                    log::warn!("Failed to release writer lock 0x{:08X}: {e}", lock.token);
+                }
+                drop(guard);
+            });
+        }
+    }
+}
+
+// Increment the heartbeat while the lock is ours
+async fn beat<C: HsesClientOps>(lock: WriterLock<C>, held: watch::Sender<bool>) {
+    let variable = lock.options.variable;
+    let mut counter: i32 = 0;
+    let mut last_beat = Instant::now();
+    loop {
+        tokio::time::sleep(lock.options.heartbeat_interval).await;
+        match lock.client.read_i32(variable).await {
+            Ok(owner) if owner == lock.token => {
+                counter = counter.wrapping_add(1);
+                match lock.client.write_i32(variable.saturating_add(1), counter).await {
+                    Ok(()) => last_beat = Instant::now(),
+                    Err(e) => log::debug!("Writer lock heartbeat failed: {e}"),
+                }
+            }
+            Ok(owner) => {
+                log::warn!("Writer lock 0x{:08X} taken over by 0x{owner:08X}", lock.token);
+                held.send_replace(false);
+                return;
+            }
+            Err(e) => log::debug!("Writer lock heartbeat failed: {e}"),
+        }
+        if last_beat.elapsed() >= lock.options.ttl {
+            log::warn!("Writer lock 0x{:08X} lost: no heartbeat for the TTL", lock.token);
+            held.send_replace(false);
+            return;
+        }
+    }
+}
+
+// Clear the owner variable if it still holds our token
+async fn release<C: HsesClientOps>(lock: &WriterLock<C>) -> Result<(), ClientError> {
+    let variable = lock.options.variable;
+    if lock.client.read_i32(variable).await? == lock.token {
+        lock.client.write_multiple_i32(variable, vec![0, 0]).await?;
+        log::debug!("Writer lock 0x{:08X} released", lock.token);
+    }
+    Ok(())
+}
+
+// Non-zero token unlikely to be chosen by another application
+fn new_token() -> i32 {
+    let nanos = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |t| t.subsec_nanos());
+    let mixed = (nanos ^ std::process::id().rotate_left(16)).wrapping_mul(0x9E37_79B9);
+    match i32::from_le_bytes(mixed.to_le_bytes()) {
+        0 => 1,
+        token => token,
+    }
+}
@@ -0,0 +1,44 @@
+#![allow(clippy::expect_used)]
+// Comparison of mock request transcripts with checked-in golden files
+//
+// A golden file lists the requests of a test, one per line, as recorded by
+// `MockHandle::take_transcript`. Run the tests with `UPDATE_GOLDEN=1` to rewrite the
+// files after an intended protocol change, and review the diff before committing it.
+
+use moto_hses_mock::{MockHandle, RecordedRequest};
+use std::fmt::Write;
+use std::path::PathBuf;
+
+fn golden_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/golden").join(format!("{name}.txt"))
+}
+
+fn render(transcript: &[RecordedRequest]) -> String {
+    let mut rendered = String::new();
+    for request in transcript {
+        let _ = writeln!(rendered, "{request}");
+    }
+    rendered
+}
+
+/// Compare the requests received by `mock` since the transcript was started with the
+/// golden file `tests/golden/<name>.txt`
+///
+/// # Panics
+///
+/// Panics if the requests differ from the golden file, or if it cannot be written
+pub async fn assert_transcript(mock: &MockHandle, name: &str) {
+    let actual = render(&mock.take_transcript().await);
+    let path = golden_path(name);
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        std::fs::write(&path, &actual).expect("Failed to write golden file");
+        return;
+    }
+    // A missing golden file compares as empty
+    let expected = std::fs::read_to_string(&path).unwrap_or_default();
+    assert!(
+        expected == actual,
+        "Requests differ from {}; run with UPDATE_GOLDEN=1 to accept them\n--- expected\n{expected}--- actual\n{actual}",
+        path.display()
+    );
+}
@@ -0,0 +1,108 @@
+#![allow(clippy::expect_used)]
+// Profiles for running integration scenarios against every text encoding and controller
+// preset
+//
+// Most integration tests run against the default mock configuration in UTF-8. A scenario
+// written as `async fn(&Profile, &MockServerManager)` and registered with `matrix_test!`
+// runs once per profile instead, each time against a fresh mock server started from the
+// preset, so text in Shift-JIS and the presets' file name rules, latencies and sample
+// data get the same coverage.
+
+use moto_hses_client::{ClientConfig, HsesClient};
+use moto_hses_mock::MockConfig;
+use moto_hses_proto::{Alarm, ControllerModel, TextEncoding};
+use std::fmt;
+use std::time::Duration;
+
+/// Name of the active alarm every profile starts with, not representable in ASCII
+pub const ALARM_NAME: &str = "非常停止中";
+
+/// Mock preset and text encoding of one run of a matrix scenario
+#[derive(Debug, Clone, Copy)]
+pub struct Profile {
+    pub preset: &'static str,
+    preset_config: fn() -> MockConfig,
+    pub encoding: TextEncoding,
+}
+
+/// Every combination of mock preset and text encoding
+pub const PROFILES: [Profile; 8] = [
+    Profile::new("default", MockConfig::default, TextEncoding::Utf8),
+    Profile::new("fs100", MockConfig::fs100, TextEncoding::Utf8),
+    Profile::new("dx200", MockConfig::dx200, TextEncoding::Utf8),
+    Profile::new("yrc1000", MockConfig::yrc1000, TextEncoding::Utf8),
+    Profile::new("default", MockConfig::default, TextEncoding::ShiftJis),
+    Profile::new("fs100", MockConfig::fs100, TextEncoding::ShiftJis),
+    Profile::new("dx200", MockConfig::dx200, TextEncoding::ShiftJis),
+    Profile::new("yrc1000", MockConfig::yrc1000, TextEncoding::ShiftJis),
+];
+
+impl Profile {
+    const fn new(
+        preset: &'static str,
+        preset_config: fn() -> MockConfig,
+        encoding: TextEncoding,
+    ) -> Self {
+        Self { preset, preset_config, encoding }
+    }
+
+    /// Mock configuration of the profile, with a single active alarm named [`ALARM_NAME`]
+    #[must_use]
+    pub fn config(&self) -> MockConfig {
+        let mut config = (self.preset_config)();
+        config.text_encoding = self.encoding;
+        config.alarms =
+            vec![Alarm::new(1010, 0, 0, "2024/04/22 10:31".to_string(), ALARM_NAME.to_string())];
+        config
+    }
+
+    /// Controller model of the preset
+    #[must_use]
+    pub fn model(&self) -> ControllerModel {
+        self.config().controller_model
+    }
+
+    /// Client for `port` of the mock server, with the encoding and model of the profile
+    ///
+    /// # Panics
+    ///
+    /// Panics if the client fails to connect
+    pub async fn client(&self, port: u16) -> HsesClient {
+        let config = ClientConfig {
+            port,
+            timeout: Duration::from_millis(500),
+            text_encoding: self.encoding,
+            controller_model: self.model(),
+            ..ClientConfig::default()
+        };
+        HsesClient::new_with_config(config).await.expect("Failed to create client")
+    }
+}
+
+impl fmt::Display for Profile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} preset, {:?}", self.preset, self.encoding)
+    }
+}
+
+/// Define a test running `scenario` once per profile of [`PROFILES`]
+///
+/// The scenario gets the profile and the started mock server; the profile of each run is
+/// logged, and failure messages should name it.
+#[macro_export]
+macro_rules! matrix_test {
+    ($test_name:ident, $scenario:path) => {
+        $crate::test_with_logging!($test_name, {
+            for profile in $crate::common::matrix::PROFILES {
+                log::info!("Running {} with the {profile}", stringify!($scenario));
+                let mut server = $crate::common::mock_server_setup::MockServerManager::new();
+                server
+                    .start_with_config(profile.config(), |builder| builder)
+                    .await
+                    .unwrap_or_else(|e| panic!("{profile}: failed to start mock server: {e}"));
+                $scenario(&profile, &server).await;
+                server.stop().await;
+            }
+        });
+    };
+}
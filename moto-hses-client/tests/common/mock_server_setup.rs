@@ -4,9 +4,9 @@
 use crate::common::test_logging;
 use log::error;
 use moto_hses_mock::{
-    MockConfig, MockServer, TypedVariables, VariableType, server::MockServerBuilder,
+    MockConfig, MockHandle, MockServer, TypedVariables, VariableType, server::MockServerBuilder,
 };
-use moto_hses_proto::{FILE_CONTROL_PORT, ROBOT_CONTROL_PORT};
+use moto_hses_proto::{AlarmCategory, FILE_CONTROL_PORT, ROBOT_CONTROL_PORT};
 use std::time::Duration;
 use tokio::time::sleep;
 
@@ -24,6 +24,7 @@ pub fn create_test_server_with_host(host: &str) -> MockServerManager {
 
 pub struct MockServerManager {
     handle: Option<tokio::task::JoinHandle<()>>,
+    mock: Option<MockHandle>,
     host: String,
     robot_port: u16,
     file_port: u16,
@@ -55,7 +56,7 @@ impl MockServerManager {
     /// Create a new `MockServerManager` with custom host and ports
     #[must_use]
     pub const fn new_with_host_and_ports(host: String, robot_port: u16, file_port: u16) -> Self {
-        Self { handle: None, host, robot_port, file_port }
+        Self { handle: None, mock: None, host, robot_port, file_port }
     }
 
     /// # Errors
@@ -76,6 +77,9 @@ impl MockServerManager {
             }
         };
 
+        self.mock = Some(server.handle());
+        test_logging::log_mock_seed(server.handle().seed().await);
+
         // Start server in background task
         let handle = tokio::spawn(async move {
             if let Err(e) = server.run().await {
@@ -113,13 +117,32 @@ impl MockServerManager {
         F: FnOnce(MockServerBuilder) -> MockServerBuilder,
     {
         // Create mock server with custom configuration
-        let builder = MockServerBuilder::new()
+        self.start_with_config(MockConfig::default(), builder_fn).await
+    }
+
+    /// Start a server from `config`, e.g. a controller preset, on the ports of the manager
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the server fails to start
+    pub async fn start_with_config<F>(
+        &mut self,
+        config: MockConfig,
+        builder_fn: F,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>>
+    where
+        F: FnOnce(MockServerBuilder) -> MockServerBuilder,
+    {
+        let builder = MockServerBuilder::from_config(config)
             .host(&self.host)
             .robot_port(self.robot_port)
             .file_port(self.file_port);
 
         let server = builder_fn(builder).build().await?;
 
+        self.mock = Some(server.handle());
+        test_logging::log_mock_seed(server.handle().seed().await);
+
         // Start server in background task
         let handle = tokio::spawn(async move {
             if let Err(e) = server.run().await {
@@ -134,6 +157,25 @@ impl MockServerManager {
         Ok(())
     }
 
+    /// Handle to the state of the running server
+    ///
+    /// # Panics
+    ///
+    /// Panics if the server has not been started
+    #[must_use]
+    pub const fn mock(&self) -> &MockHandle {
+        self.mock.as_ref().expect("Mock server not started")
+    }
+
+    /// Stop the server and wait until its sockets are closed, so that the ports can be
+    /// bound again in the same test
+    pub async fn stop(&mut self) {
+        if let Some(handle) = self.handle.take() {
+            handle.abort();
+            let _ = handle.await;
+        }
+    }
+
     #[must_use]
     pub const fn is_running(&self) -> bool {
         self.handle.is_some()
@@ -204,6 +246,8 @@ pub async fn create_io_test_server()
                 .with_io_state(2, 0) // Input 2 = OFF
                 .with_io_state(1001, 0) // Output 1 = OFF
                 .with_io_state(1002, 1) // Output 2 = ON
+                .with_io_state(2701, 0) // Network input 1 = OFF
+                .with_io_state(2702, 1) // Network input 2 = ON
         })
         .await?;
 
@@ -227,11 +271,19 @@ pub async fn create_alarm_test_server()
                 .with_alarm(moto_hses_proto::payload::alarm::test_alarms::emergency_stop()) // Instance 2
                 .with_alarm(moto_hses_proto::payload::alarm::test_alarms::safety_error()) // Instance 3
                 .with_alarm(moto_hses_proto::payload::alarm::test_alarms::communication_error()) // Instance 4
-                // Add alarm history for testing
-                .with_alarm_history(moto_hses_proto::payload::alarm::test_alarms::servo_error()) // Major failure #1
-                .with_alarm_history(moto_hses_proto::payload::alarm::test_alarms::emergency_stop()) // Major failure #2
-                .with_alarm_history(moto_hses_proto::payload::alarm::test_alarms::safety_error())
-            // Major failure #3
+                // Add alarm history entries after the default ones, one per user and offline category
+                .with_alarm_history(
+                    AlarmCategory::UserAlarmSystem,
+                    moto_hses_proto::payload::alarm::test_alarms::emergency_stop(),
+                ) // Instance 2001
+                .with_alarm_history(
+                    AlarmCategory::UserAlarmUser,
+                    moto_hses_proto::payload::alarm::test_alarms::safety_error(),
+                ) // Instance 3001
+                .with_alarm_history(
+                    AlarmCategory::OfflineAlarm,
+                    moto_hses_proto::payload::alarm::test_alarms::servo_error(),
+                ) // Instance 4001
         })
         .await?;
 
@@ -250,11 +302,13 @@ pub async fn create_position_test_server()
     manager
         .start_with_builder(|builder| {
             // Set up position data with known values for testing
-            let test_position =
-                moto_hses_proto::Position::Pulse(moto_hses_proto::PulsePosition::new(
-                    vec![100, 200, 300, 400, 500, 600, 700, 800], // Known test values
-                ));
-            builder.with_position(test_position)
+            let test_pulses = moto_hses_proto::PulsePosition::new(
+                vec![100, 200, 300, 400, 500, 600, 700, 800], // Known test values
+            );
+            builder
+                .with_position(moto_hses_proto::Position::Pulse(test_pulses.clone()))
+                .with_pulse_position(11, test_pulses.clone()) // B1
+                .with_pulse_position(12, test_pulses) // B2
         })
         .await?;
 
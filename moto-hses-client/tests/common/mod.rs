@@ -1,5 +1,7 @@
 // Common utilities for integration tests
 
+pub mod golden;
+pub mod matrix;
 pub mod mock_server_setup;
 pub mod test_logging;
 pub mod test_utils;
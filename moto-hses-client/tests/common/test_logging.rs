@@ -70,6 +70,13 @@ pub fn log_mock_server_startup(host: &str, port: u16) {
     debug!("Starting mock server on {host}:{port}");
 }
 
+/// Log the seed of a mock server, also to stderr so that the output of a failed test shows
+/// how to replay it
+pub fn log_mock_seed(seed: u64) {
+    info!("Mock seed {seed}");
+    eprintln!("mock seed {seed}; replay with {}={seed}", moto_hses_mock::SEED_ENV);
+}
+
 /// Log mock server startup failure
 pub fn log_mock_server_startup_failure(host: &str, port: u16, error: &str) {
     error!("Failed to start mock server on {host}:{port} - {error}");
@@ -1,7 +1,7 @@
 #![allow(clippy::expect_used)]
 // Test utilities for integration tests
 
-use moto_hses_client::{ClientConfig, HsesClient, SharedHsesClient};
+use moto_hses_client::{ClientConfig, ControllerModel, DangerLevel, HsesClient, SharedHsesClient};
 use moto_hses_proto::ROBOT_CONTROL_PORT;
 use std::time::Duration;
 
@@ -40,8 +40,17 @@ pub async fn create_test_client_with_host_and_port(
         timeout: Duration::from_millis(500),
         retry_count: 3,
         retry_delay: Duration::from_millis(100),
+        operation_deadline: None,
         buffer_size: 8192,
         text_encoding: moto_hses_proto::TextEncoding::Utf8,
+        controller_model: ControllerModel::YRC1000micro,
+        file_name_rules: None,
+        max_danger_level: DangerLevel::Write,
+        workspace_limits: None,
+        adaptive_timeout: None,
+        write_pacing: None,
+        expected_axes: None,
+        request_limits: None,
     };
 
     let client = HsesClient::new_with_config(config).await?;
@@ -6,7 +6,8 @@ use crate::common::{
     test_utils::create_test_client,
 };
 use crate::test_with_logging;
-use moto_hses_proto::AlarmAttribute;
+use moto_hses_client::ClientError;
+use moto_hses_proto::{AlarmAttribute, AlarmCategory, ProtocolError};
 
 test_with_logging!(test_complete_alarm_data, {
     log::info!("Creating alarm test server...");
@@ -235,6 +236,62 @@ test_with_logging!(test_alarm_history_monitor, {
     log::info!("Monitor alarm history test completed");
 });
 
+test_with_logging!(test_alarm_history_categories, {
+    let _server = create_alarm_test_server().await.expect("Failed to start mock server");
+
+    let client = create_test_client().await.expect("Failed to create client");
+
+    // Configured history entries are read through the instance range of their category
+    for (instance, expected_code, expected_name) in
+        [(2001, 2001, "Emergency Stop"), (3001, 3001, "Safety Error"), (4001, 1001, "Servo Error")]
+    {
+        let alarm = client
+            .read_alarm_history(instance, AlarmAttribute::All)
+            .await
+            .expect("Failed to read alarm history");
+        assert_eq!(alarm.code, expected_code, "code of alarm history instance {instance}");
+        assert_eq!(alarm.name, expected_name, "name of alarm history instance {instance}");
+    }
+
+    // The default major failure history is unchanged
+    let alarm = client
+        .read_alarm_history(4, AlarmAttribute::Code)
+        .await
+        .expect("Failed to read alarm history");
+    assert_eq!(alarm.code, 0, "major failure history should hold only the default alarms");
+});
+
+test_with_logging!(test_read_alarm_history_range, {
+    let mut server = MockServerManager::new();
+    server.start().await.expect("Failed to start mock server");
+
+    let client = create_test_client().await.expect("Failed to create client");
+
+    // The default history has three major failures; empty entries are skipped
+    let alarms = client
+        .read_alarm_history_range(AlarmCategory::MajorFailure, 0, 10)
+        .await
+        .expect("Failed to read alarm history range");
+    let codes: Vec<u32> = alarms.iter().map(|alarm| alarm.code).collect();
+    assert_eq!(codes, vec![3001, 2001, 1001], "alarms should be sorted newest first");
+
+    let alarms = client
+        .read_alarm_history_range(AlarmCategory::MajorFailure, 1, 1)
+        .await
+        .expect("Failed to read alarm history range");
+    assert_eq!(alarms.len(), 1);
+    assert_eq!(alarms[0].name, "Emergency Stop");
+
+    let result = client.read_alarm_history_range(AlarmCategory::MonitorAlarm, 90, 11).await;
+    assert!(
+        matches!(
+            result,
+            Err(ClientError::ProtocolError(ProtocolError::InvalidCount { got: 11, max: 10, .. }))
+        ),
+        "{result:?}"
+    );
+});
+
 test_with_logging!(test_alarm_history_attributes, {
     let mut server = MockServerManager::new();
     server.start().await.expect("Failed to start mock server");
@@ -400,3 +457,64 @@ test_with_logging!(test_alarm_operations_comprehensive, {
 
     log::info!("Comprehensive alarm operations test completed successfully");
 });
+
+test_with_logging!(test_alarm_name_encoding_mismatch, {
+    let mut server = MockServerManager::new();
+    server
+        .start_with_builder(|builder| {
+            builder.text_encoding(moto_hses_proto::TextEncoding::ShiftJis).with_alarm(
+                moto_hses_proto::Alarm::new(
+                    1001,
+                    1,
+                    1,
+                    "2024/01/01 12:00".to_string(),
+                    "テストアラーム".to_string(),
+                ),
+            )
+        })
+        .await
+        .expect("Failed to start mock server");
+
+    // Lenient client (default): the read succeeds and the mismatch is only logged
+    let client = create_test_client().await.expect("Failed to create client");
+    let alarm =
+        client.read_alarm_data(1, AlarmAttribute::All).await.expect("Failed to read alarm data");
+    assert_ne!(alarm.name, "テストアラーム", "Name decoded as UTF-8 should not match");
+
+    // Strict client: the mismatch is reported as an encoding error
+    let strict_client = create_test_client().await.expect("Failed to create client");
+    strict_client.set_strict_encoding(true);
+
+    let result = strict_client.read_alarm_data(1, AlarmAttribute::All).await;
+    assert!(
+        matches!(result, Err(ClientError::EncodingError(_))),
+        "Strict client should reject undecodable alarm name, got {result:?}"
+    );
+
+    let result = strict_client.read_alarm_data(1, AlarmAttribute::Name).await;
+    assert!(
+        matches!(result, Err(ClientError::EncodingError(_))),
+        "Strict client should reject undecodable alarm name attribute, got {result:?}"
+    );
+
+    // Time is plain ASCII and decodes cleanly even in strict mode
+    let alarm_time = strict_client
+        .read_alarm_data(1, AlarmAttribute::Time)
+        .await
+        .expect("Failed to read alarm time");
+    assert_eq!(alarm_time.time, "2024/01/01 12:00");
+});
+
+test_with_logging!(test_alarm_statistics, {
+    let server = create_alarm_test_server().await.expect("Failed to start mock server");
+    let client = create_test_client().await.expect("Failed to create client");
+
+    // Default history plus one entry in each user and offline category
+    let statistics = client.alarm_statistics().await.expect("Failed to read alarm statistics");
+    let counts: Vec<(u32, usize)> =
+        statistics.values().map(|statistic| (statistic.code, statistic.count)).collect();
+    assert_eq!(counts, [(1001, 3), (2001, 2), (3001, 2), (4001, 1)]);
+    assert_eq!(statistics[&1001].name, "Servo Error");
+    assert_eq!(statistics[&1001].last_time, "2024/01/01 12:00");
+    assert_eq!(server.mock().robot_request_count().await, 500, "Whole history is read");
+});
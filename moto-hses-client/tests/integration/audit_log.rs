@@ -0,0 +1,75 @@
+#![allow(clippy::expect_used)]
+// Integration tests for the audit records of write requests
+
+use crate::common::{mock_server_setup::MockServerManager, test_utils::create_test_client};
+use crate::test_with_logging;
+use moto_hses_client::{AuditOptions, AuditRecord, HsesClient};
+use moto_hses_mock::ResponseFault;
+use moto_hses_proto::Service;
+use std::sync::{Arc, Mutex};
+
+// Collect the records of every write request
+fn record_writes(client: &HsesClient, read_previous: bool) -> Arc<Mutex<Vec<AuditRecord>>> {
+    let records = Arc::new(Mutex::new(Vec::new()));
+    let recorded = Arc::clone(&records);
+    client.set_audit_sink(
+        move |record: &AuditRecord| {
+            recorded.lock().expect("Audit log poisoned").push(record.clone());
+        },
+        AuditOptions { actor: "test-hmi".to_string(), read_previous },
+    );
+    records
+}
+
+test_with_logging!(test_writes_are_audited, {
+    let mut server = MockServerManager::new();
+    server.start().await.expect("Failed to start mock server");
+    let client = create_test_client().await.expect("Failed to create client");
+    let records = record_writes(&client, true);
+
+    client.write_i32(5, 100).await.expect("Failed to write variable");
+    client.write_i32(5, 200).await.expect("Failed to write variable");
+    client.read_i32(5).await.expect("Failed to read variable");
+    client.write_multiple_i16(1, vec![7, 8]).await.expect("Failed to write integer variables");
+    client.set_hold(false).await.expect("Failed to release hold");
+
+    let records = records.lock().expect("Audit log poisoned").clone();
+    assert_eq!(records.len(), 4, "Reads are not audited: {records:#?}");
+    assert!(records.iter().all(|record| record.actor == "test-hmi" && record.outcome.is_ok()));
+
+    let [first, second, integers, hold] = &records[..] else {
+        unreachable!("Four records expected");
+    };
+    assert_eq!((first.command, first.instance, first.service), (0x7C, 5, Service::SetSingle));
+    assert_eq!(first.payload, 100i32.to_le_bytes());
+    assert_eq!(first.previous.as_deref(), Some(&0i32.to_le_bytes()[..]));
+    assert_eq!(second.previous.as_deref(), Some(&100i32.to_le_bytes()[..]));
+
+    // Plural writes record the replaced values read with the same count
+    assert_eq!((integers.command, integers.service), (0x303, Service::WritePlural));
+    let previous = integers.previous.as_deref().expect("Variables have a read counterpart");
+    assert_eq!(&previous[..4], &2u32.to_le_bytes());
+
+    // Commands without a read counterpart have no previous value
+    assert_eq!(hold.command, 0x83);
+    assert_eq!(hold.previous, None);
+});
+
+test_with_logging!(test_failed_write_is_audited_once, {
+    let mut server = MockServerManager::new();
+    server.start().await.expect("Failed to start mock server");
+    let client = create_test_client().await.expect("Failed to create client");
+    let records = record_writes(&client, false);
+
+    // Retries of a write are one record
+    server.mock().inject_fault(ResponseFault::Drop).await;
+    client.write_register(3, 42).await.expect("Write should succeed after a retry");
+    // The mock rejects registers outside 0-999
+    assert!(client.write_register(1000, 1).await.is_err());
+
+    let records = records.lock().expect("Audit log poisoned").clone();
+    assert_eq!(records.len(), 2, "{records:#?}");
+    assert!(records[0].outcome.is_ok());
+    assert_eq!(records[0].previous, None);
+    assert!(records[1].outcome.is_err(), "{}", records[1]);
+});
@@ -6,6 +6,14 @@ use crate::common::{
     test_utils::{create_test_client, create_test_client_with_host_and_port},
 };
 use crate::test_with_logging;
+use moto_hses_client::{
+    ClientConfig, ClientError, ConnectionEvent, ConnectionSupervisor, DiscoveryOptions, HsesClient,
+    SupervisorOptions, discover,
+};
+use moto_hses_mock::server::MockServerBuilder;
+use moto_hses_proto::{ROBOT_CONTROL_PORT, SystemInfo};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::time::Duration;
 
 test_with_logging!(test_basic_connection, {
     log::debug!("Creating test server...");
@@ -57,6 +65,128 @@ test_with_logging!(test_connection_timeout_handling, {
     assert!(status_result.is_err(), "Communication with non-existent server should fail");
 });
 
+test_with_logging!(test_connect_records_controller_info, {
+    let mut server = MockServerManager::new();
+    server
+        .start_with_builder(|builder| {
+            builder.with_system_info(SystemInfo::new(
+                "YAS4.10.00A-00".to_string(),
+                "GP8".to_string(),
+                "1.10".to_string(),
+            ))
+        })
+        .await
+        .expect("Failed to start mock server");
+
+    let addr = format!("127.0.0.1:{ROBOT_CONTROL_PORT}");
+    let client = HsesClient::connect(&addr, ClientConfig::default())
+        .await
+        .expect("Failed to connect to mock server");
+
+    let info = client.info().expect("Controller info should be recorded");
+    assert_eq!(info.software_version, "YAS4.10.00A-00");
+    assert_eq!(info.model, "GP8");
+    assert_eq!(info.parameter_version, "1.10");
+    assert_eq!(client.read_system_info(11).await.expect("Failed to read system info"), info);
+
+    let client = create_test_client().await.expect("Failed to create client");
+    assert!(client.info().is_none(), "Clients created without handshake have no info");
+});
+
+test_with_logging!(test_connect_fails_fast_without_controller, {
+    let config = ClientConfig {
+        timeout: Duration::from_millis(200),
+        retry_count: 5,
+        ..ClientConfig::default()
+    };
+
+    let start = std::time::Instant::now();
+    match HsesClient::connect("127.0.0.1:65535", config).await {
+        Err(ClientError::ConnectError(message)) => {
+            log::debug!("✓ Connect correctly failed: {message}");
+        }
+        result => unreachable!("Connect without controller should fail: {:?}", result.err()),
+    }
+    // A single attempt, not one per retry
+    assert!(start.elapsed() < Duration::from_millis(600), "Took {:?}", start.elapsed());
+});
+
+test_with_logging!(test_connection_supervisor_events, {
+    let (robot_port, file_port) = (30093, 30094);
+    let mut server =
+        MockServerManager::new_with_host_and_ports("127.0.0.1".to_string(), robot_port, file_port);
+    server.start().await.expect("Failed to start mock server");
+
+    let config = ClientConfig {
+        port: robot_port,
+        timeout: Duration::from_millis(100),
+        retry_count: 0,
+        ..ClientConfig::default()
+    };
+    let client = HsesClient::new_with_config(config).await.expect("Failed to create client");
+    let options = SupervisorOptions {
+        interval: Duration::from_millis(50),
+        max_backoff: Duration::from_millis(200),
+        ..SupervisorOptions::default()
+    };
+    let supervisor = ConnectionSupervisor::start(client, options);
+    let mut events = supervisor.subscribe();
+    let mut next_event = async || {
+        tokio::time::timeout(Duration::from_secs(3), events.recv())
+            .await
+            .expect("No connection event")
+            .expect("Event channel closed")
+    };
+
+    assert_eq!(next_event().await, ConnectionEvent::Connected);
+    assert!(supervisor.is_connected());
+
+    // Link loss: the server stops answering
+    drop(server);
+    let lost_at = std::time::Instant::now();
+    assert!(matches!(next_event().await, ConnectionEvent::Lost { .. }));
+    assert!(!supervisor.is_connected());
+
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    let mut server =
+        MockServerManager::new_with_host_and_ports("127.0.0.1".to_string(), robot_port, file_port);
+    server.start().await.expect("Failed to restart mock server");
+    match next_event().await {
+        ConnectionEvent::Recovered { downtime } => {
+            log::info!("Recovered after {downtime:?}");
+            assert!(downtime >= Duration::from_millis(300), "Downtime {downtime:?}");
+            assert!(downtime <= lost_at.elapsed() + Duration::from_millis(200));
+        }
+        event => unreachable!("Expected recovery, got {event:?}"),
+    }
+    assert!(supervisor.is_connected());
+    supervisor.stop().await;
+});
+
+test_with_logging!(test_operation_deadline_limits_retries, {
+    // Without a deadline this would wait 10 × 200 ms plus the retry delays
+    let config = ClientConfig {
+        port: 65535,
+        timeout: Duration::from_millis(200),
+        retry_count: 9,
+        retry_delay: Duration::from_millis(50),
+        operation_deadline: Some(Duration::from_millis(500)),
+        ..ClientConfig::default()
+    };
+    let client = HsesClient::new_with_config(config).await.expect("Failed to create client");
+
+    let start = std::time::Instant::now();
+    match client.read_status().await {
+        Err(ClientError::TimeoutError(message)) => {
+            log::debug!("✓ Read correctly timed out: {message}");
+            assert!(message.contains("operation deadline"), "{message}");
+        }
+        result => unreachable!("Read without controller should time out: {:?}", result.err()),
+    }
+    let elapsed = start.elapsed();
+    assert!(elapsed < Duration::from_millis(700), "Took {elapsed:?}");
+});
+
 test_with_logging!(test_multiple_connections, {
     let mut server =
         MockServerManager::new_with_host_and_ports("127.0.0.1".to_string(), 10096, 10097);
@@ -118,8 +248,17 @@ test_with_logging!(test_retry_mechanism_actual, {
         timeout: std::time::Duration::from_millis(50), // Short timeout
         retry_count: 3,
         retry_delay: std::time::Duration::from_millis(25),
+        operation_deadline: None,
         buffer_size: 8192,
         text_encoding: moto_hses_proto::TextEncoding::Utf8,
+        controller_model: moto_hses_client::ControllerModel::YRC1000micro,
+        file_name_rules: None,
+        max_danger_level: moto_hses_client::DangerLevel::Write,
+        workspace_limits: None,
+        adaptive_timeout: None,
+        write_pacing: None,
+        expected_axes: None,
+        request_limits: None,
     };
 
     let client = moto_hses_client::HsesClient::new_with_config(config)
@@ -147,3 +286,51 @@ test_with_logging!(test_retry_mechanism_actual, {
         }
     }
 });
+
+test_with_logging!(test_ipv6_and_host_name_addresses, {
+    for (server_host, client_host) in [("::1", "[::1]"), ("localhost", "localhost")] {
+        let server = MockServerBuilder::new()
+            .host(server_host)
+            .robot_port(0)
+            .file_port(0)
+            .build()
+            .await
+            .expect("Failed to build mock server");
+        let port = server.local_addr().expect("Failed to get server address").port();
+        let server_task = tokio::spawn(async move { server.run().await });
+
+        let client = HsesClient::new(&format!("{client_host}:{port}"))
+            .await
+            .expect("Failed to create client");
+        client.read_status().await.expect("Failed to read status");
+        server_task.abort();
+    }
+
+    let result = HsesClient::new("no-such-controller.invalid:10040").await;
+    assert!(
+        matches!(result, Err(ClientError::SystemError(_))),
+        "Unexpected result: {:?}",
+        result.err()
+    );
+});
+
+test_with_logging!(test_discover_controllers, {
+    let server = MockServerBuilder::new()
+        .host("127.0.0.1")
+        .robot_port(0)
+        .file_port(0)
+        .build()
+        .await
+        .expect("Failed to build mock server");
+    let port = server.local_addr().expect("Failed to get server address").port();
+    let server_task = tokio::spawn(async move { server.run().await });
+
+    let options = DiscoveryOptions { port, ..DiscoveryOptions::default() };
+    let candidates = [Ipv4Addr::new(127, 0, 0, 2), Ipv4Addr::LOCALHOST].map(IpAddr::V4);
+    let found = discover(candidates, options).await;
+    server_task.abort();
+
+    assert_eq!(found.len(), 1, "Found {found:?}");
+    assert_eq!(found[0].addr, SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), port));
+    assert!(found[0].info.is_some(), "The mock reports its system information");
+});
@@ -0,0 +1,145 @@
+#![allow(clippy::expect_used)]
+// Integration tests for the detection of optional controller features
+
+use crate::common::{mock_server_setup::MockServerManager, test_utils::create_test_client};
+use crate::test_with_logging;
+use moto_hses_client::{Capabilities, ClientConfig, ClientError, HsesClient};
+use moto_hses_mock::server::MockServerBuilder;
+use moto_hses_mock::{CommandHandler, MockState, VariableType};
+use moto_hses_proto::{
+    Alarm, AlarmAttribute, HsesRequestMessage, ProtocolError, ROBOT_CONTROL_PORT, Service,
+    TextEncoding,
+};
+
+// Alarm data of a controller without sub codes: attributes 1-5 only
+struct AlarmWithoutSubCodes;
+
+impl CommandHandler for AlarmWithoutSubCodes {
+    fn handle(
+        &self,
+        message: &HsesRequestMessage,
+        _state: &mut MockState,
+    ) -> Result<Vec<u8>, ProtocolError> {
+        let alarm =
+            Alarm::new(4100, 1, 0, "2024/04/22 10:31".to_string(), "OVERRUN(ROBOT)".to_string());
+        match message.sub_header.attribute {
+            0 => Ok(alarm.serialize_complete(TextEncoding::Utf8)?[..60].to_vec()),
+            attribute @ 1..=5 => alarm.serialize(attribute, TextEncoding::Utf8),
+            _ => Err(ProtocolError::InvalidAttribute),
+        }
+    }
+}
+
+// S variables of a controller with 32-byte strings
+struct ExtendedStrings;
+
+impl CommandHandler for ExtendedStrings {
+    fn handle(
+        &self,
+        message: &HsesRequestMessage,
+        state: &mut MockState,
+    ) -> Result<Vec<u8>, ProtocolError> {
+        let index = message.sub_header.instance;
+        match Service::try_from(message.sub_header.service)? {
+            Service::GetSingle => {
+                let mut data =
+                    state.get_variable(VariableType::String, index).cloned().unwrap_or_default();
+                data.resize(32, 0);
+                Ok(data)
+            }
+            Service::SetSingle => {
+                state.set_variable(VariableType::String, index, message.payload.clone());
+                Ok(Vec::new())
+            }
+            _ => Err(ProtocolError::InvalidService),
+        }
+    }
+}
+
+async fn connect() -> HsesClient {
+    HsesClient::connect(&format!("127.0.0.1:{ROBOT_CONTROL_PORT}"), ClientConfig::default())
+        .await
+        .expect("Failed to connect to mock server")
+}
+
+test_with_logging!(test_detect_capabilities, {
+    let mut server = MockServerManager::new();
+    server.start().await.expect("Failed to start mock server");
+
+    let client = connect().await;
+    assert_eq!(client.capabilities(), Capabilities::default());
+    assert_eq!(client.capabilities().string_variable_size, 16);
+
+    let client = create_test_client().await.expect("Failed to create client");
+    assert_eq!(client.capabilities(), Capabilities::default(), "All features are assumed");
+    let detected = client.detect_capabilities().await.expect("Failed to detect capabilities");
+    assert_eq!(detected, Capabilities::default());
+});
+
+test_with_logging!(test_controller_without_optional_commands, {
+    let mut server = MockServerManager::new();
+    server
+        .start_with_builder(|builder| {
+            (0x302..=0x306)
+                .fold(builder, MockServerBuilder::with_unsupported_command)
+                .with_handler(0x70, AlarmWithoutSubCodes)
+        })
+        .await
+        .expect("Failed to start mock server");
+
+    let client = connect().await;
+    let capabilities = client.capabilities();
+    assert!(!capabilities.plural_variables, "{capabilities}");
+    assert!(!capabilities.alarm_sub_codes, "{capabilities}");
+
+    // Plural variable methods fall back to single variable requests
+    client
+        .write_multiple_variables::<i32>(10, vec![1, -2, 3])
+        .await
+        .expect("Failed to write variables");
+    assert_eq!(client.read_i32(11).await.expect("Failed to read variable"), -2);
+    let values =
+        client.read_multiple_variables::<i32>(10, 3).await.expect("Failed to read variables");
+    assert_eq!(values, vec![1, -2, 3]);
+    client
+        .write_multiple_string_variables(5, vec!["FIRST".to_string(), "SECOND".to_string()])
+        .await
+        .expect("Failed to write string variables");
+    assert_eq!(client.read_string_variable(6).await.expect("Failed to read variable"), "SECOND");
+
+    // Alarms are read with empty sub codes
+    let alarm = client.read_alarm_data(1, AlarmAttribute::All).await.expect("Failed to read alarm");
+    assert_eq!((alarm.code, alarm.name.as_str()), (4100, "OVERRUN(ROBOT)"));
+    assert!(alarm.sub_code_info.is_empty());
+    for attribute in
+        [AlarmAttribute::SubCodeInfo, AlarmAttribute::SubCodeData, AlarmAttribute::SubCodeReverse]
+    {
+        let result = client.read_alarm_data(1, attribute).await;
+        assert!(matches!(result, Err(ClientError::Unsupported(_))), "{attribute:?}: {result:?}");
+        let result = client.read_alarm_history(1, attribute).await;
+        assert!(matches!(result, Err(ClientError::Unsupported(_))), "{attribute:?}: {result:?}");
+    }
+    let alarm =
+        client.read_alarm_data(1, AlarmAttribute::Code).await.expect("Failed to read alarm code");
+    assert_eq!(alarm.code, 4100);
+});
+
+test_with_logging!(test_extended_string_variables, {
+    let mut server = MockServerManager::new();
+    server
+        .start_with_builder(|builder| builder.with_handler(0x7e, ExtendedStrings))
+        .await
+        .expect("Failed to start mock server");
+
+    let client = connect().await;
+    assert_eq!(client.capabilities().string_variable_size, 32);
+
+    let text = "Text longer than sixteen bytes";
+    client.write_string_variable(3, text.to_string()).await.expect("Failed to write variable");
+    assert_eq!(client.read_string_variable(3).await.expect("Failed to read variable"), text);
+    let result = client.write_string_variable(3, "x".repeat(33)).await;
+    assert!(
+        matches!(result, Err(ClientError::ProtocolError(ProtocolError::StringTooLong { .. }))),
+        "{result:?}"
+    );
+});
@@ -4,8 +4,14 @@
 
 use crate::common::mock_server_setup::MockServerManager;
 use crate::test_with_logging;
-use moto_hses_client::{ClientConfig, HsesClient};
-use moto_hses_proto::{FILE_CONTROL_PORT, TextEncoding};
+use moto_hses_client::{
+    AxisRange, ClientConfig, ClientError, ControllerEvent, ControllerModel, DangerLevel,
+    DeleteFilesOptions, FileDeleteOutcome, FileNameRules, HsesClient, JobDiffLine, OperationMode,
+    ReferenceFrame,
+};
+use moto_hses_mock::SecurityMode;
+use moto_hses_proto::payload::position::{Configuration, ExtendedConfiguration};
+use moto_hses_proto::{CartesianPosition, FILE_CONTROL_PORT, ProtocolError, TextEncoding};
 use std::time::Duration;
 
 // Helper function to create client with ShiftJIS encoding
@@ -16,8 +22,17 @@ async fn create_file_client() -> HsesClient {
         timeout: Duration::from_millis(500),
         retry_count: 5,
         retry_delay: Duration::from_millis(200),
+        operation_deadline: None,
         buffer_size: 8192,
         text_encoding: TextEncoding::ShiftJis,
+        controller_model: ControllerModel::YRC1000micro,
+        file_name_rules: None,
+        max_danger_level: DangerLevel::Write,
+        workspace_limits: None,
+        adaptive_timeout: None,
+        write_pacing: None,
+        expected_axes: None,
+        request_limits: None,
     };
 
     HsesClient::new_with_config(config).await.expect("Failed to create client")
@@ -160,3 +175,310 @@ test_with_logging!(test_file_operations_comprehensive, {
 
     log::info!("✓ Comprehensive file operations test completed successfully");
 });
+
+test_with_logging!(test_file_list_multi_block, {
+    let mut server = MockServerManager::new();
+    server.start().await.expect("Failed to start mock server");
+
+    let client = create_file_client().await;
+
+    // 40 additional jobs produce a listing longer than one data block
+    for i in 0..40 {
+        let filename = format!("MULTI_BLOCK_{i:02}.JBI");
+        client.send_file(&filename, b"NOP\r\nEND\r\n").await.expect("Failed to send file");
+    }
+
+    let files = client.read_file_list("*.JBI").await.expect("Failed to get file list");
+    assert_eq!(files.len(), 41, "Listing should include TEST.JBI and all sent jobs");
+    assert_eq!(files.first().map(String::as_str), Some("MULTI_BLOCK_00.JBI"));
+    assert_eq!(files.last().map(String::as_str), Some("TEST.JBI"));
+});
+
+test_with_logging!(test_file_name_validation, {
+    let mut server = MockServerManager::new();
+    server.start().await.expect("Failed to start mock server");
+
+    let client = create_file_client().await;
+
+    // Names the controller would reject are refused before any request is sent
+    for filename in ["NO_EXTENSION", "MY JOB.JBI", "DIR/TEST.JBI", "TEST.JOBS"] {
+        let result = client.send_file(filename, b"NOP").await;
+        assert!(
+            matches!(result, Err(ClientError::ProtocolError(ProtocolError::InvalidFileName(_)))),
+            "send_file should reject '{filename}', got {result:?}"
+        );
+        let result = client.delete_file(filename).await;
+        assert!(
+            matches!(result, Err(ClientError::ProtocolError(ProtocolError::InvalidFileName(_)))),
+            "delete_file should reject '{filename}', got {result:?}"
+        );
+    }
+
+    let files = client.read_file_list("*").await.expect("Failed to get file list");
+    assert_eq!(files, vec!["TEST.JBI".to_string()], "No file should have been created");
+
+    // Long names are accepted on every model, also by the FS100, unless 8.3 names are chosen
+    let long_name = "WELDING_PROGRAM.JBI";
+    let fs100_config = ClientConfig {
+        port: FILE_CONTROL_PORT,
+        controller_model: ControllerModel::FS100,
+        ..ClientConfig::default()
+    };
+    let fs100_client =
+        HsesClient::new_with_config(fs100_config.clone()).await.expect("Failed to create client");
+    fs100_client.send_file(long_name, b"NOP").await.expect("Failed to send file");
+
+    let config = ClientConfig { file_name_rules: Some(FileNameRules::Short), ..fs100_config };
+    let short_client = HsesClient::new_with_config(config).await.expect("Failed to create client");
+    let result = short_client.delete_file(long_name).await;
+    assert!(
+        matches!(result, Err(ClientError::ProtocolError(ProtocolError::InvalidFileName(_)))),
+        "8.3 rules should reject '{long_name}', got {result:?}"
+    );
+    fs100_client.delete_file(long_name).await.expect("Failed to delete file");
+    short_client.delete_file("TEST.JBI").await.expect("Failed to delete file");
+});
+
+test_with_logging!(test_delete_files_with_pattern, {
+    let mut server = MockServerManager::new();
+    server.start().await.expect("Failed to start mock server");
+
+    let client = create_file_client().await;
+
+    for i in 0..10 {
+        let filename = format!("TMP{i:02}.JBI");
+        client.send_file(&filename, b"NOP\r\nEND\r\n").await.expect("Failed to send file");
+    }
+
+    // Dry run only reports the matching files
+    let options = DeleteFilesOptions { dry_run: true, ..DeleteFilesOptions::default() };
+    let results = client.delete_files("TMP*", options).await.expect("Failed to run dry run");
+    assert_eq!(results.len(), 10);
+    assert!(results.iter().all(|r| matches!(r.outcome, FileDeleteOutcome::WouldDelete)));
+    assert_eq!(results[0].filename, "TMP00.JBI");
+    let files = client.read_file_list("*").await.expect("Failed to get file list");
+    assert_eq!(files.len(), 11, "Dry run should not delete anything");
+
+    // Delete with several requests in flight
+    let options = DeleteFilesOptions { max_concurrency: 3, ..DeleteFilesOptions::default() };
+    let results = client.delete_files("TMP*", options).await.expect("Failed to delete files");
+    assert_eq!(results.len(), 10);
+    assert!(results.iter().all(|r| matches!(r.outcome, FileDeleteOutcome::Deleted)));
+    let filenames: Vec<&str> = results.iter().map(|r| r.filename.as_str()).collect();
+    assert_eq!(filenames.first(), Some(&"TMP00.JBI"), "Results should keep listing order");
+    assert_eq!(filenames.last(), Some(&"TMP09.JBI"), "Results should keep listing order");
+
+    let files = client.read_file_list("*").await.expect("Failed to get file list");
+    assert_eq!(files, vec!["TEST.JBI".to_string()]);
+
+    // No match is not an error
+    let results = client
+        .delete_files("TMP*", DeleteFilesOptions::default())
+        .await
+        .expect("Failed to delete files");
+    assert!(results.is_empty());
+});
+
+test_with_logging!(test_diff_job, {
+    let mut server = MockServerManager::new();
+    server.start().await.expect("Failed to start mock server");
+
+    let client = create_file_client().await;
+
+    let controller_job = "/JOB\r\n//NAME DIFF\r\n//INST\r\nNOP\r\nMOVJ VJ=10.00\r\nEND\r\n";
+    client.send_file("DIFF.JBI", controller_job.as_bytes()).await.expect("Failed to send file");
+
+    // Same content with Unix line endings is identical
+    let local = controller_job.replace("\r\n", "\n");
+    let diff = client.diff_job("DIFF.JBI", &local).await.expect("Failed to diff job");
+    assert!(diff.is_identical(), "Line endings should be normalized: {diff:?}");
+
+    // A modified speed and an extra instruction are reported
+    let local = "/JOB\n//NAME DIFF\n//INST\nNOP\nMOVJ VJ=50.00\nTIMER T=1.00\nEND\n";
+    let diff = client.diff_job("DIFF.JBI", local).await.expect("Failed to diff job");
+    assert_eq!(
+        diff.lines,
+        vec![
+            JobDiffLine::Changed {
+                local_line: 5,
+                controller_line: 5,
+                local: "MOVJ VJ=50.00".to_string(),
+                controller: "MOVJ VJ=10.00".to_string(),
+            },
+            JobDiffLine::Removed { local_line: 6, text: "TIMER T=1.00".to_string() },
+        ]
+    );
+});
+
+test_with_logging!(test_send_file_verified, {
+    let mut server = MockServerManager::new();
+    server.start().await.expect("Failed to start mock server");
+
+    let client = create_file_client().await;
+
+    // The file is read back and compared after the upload
+    let content = "/JOB\r\n//NAME VERIFY\r\n//INST\r\nNOP\r\nEND\r\n";
+    client
+        .send_file_verified("VERIFY.JBI", content.as_bytes())
+        .await
+        .expect("Failed to verify file");
+    let stored = client.receive_file("VERIFY.JBI").await.expect("Failed to receive file");
+    assert_eq!(stored, content);
+});
+
+test_with_logging!(test_read_axis_limits, {
+    let parameters = "//S1CxG\r\n\
+        S1C1G400=170000,90000,150000,180000,120000,250000,0,0\r\n\
+        S1C1G410=-170000,-60000,-70000,-180000,-120000,-250000,0,0\r\n";
+    let mut server = MockServerManager::new();
+    server
+        .start_with_builder(|builder| builder.with_file("SC.PRM", parameters.as_bytes().to_vec()))
+        .await
+        .expect("Failed to start mock server");
+
+    let client = create_file_client().await;
+
+    let limits = client.read_axis_limits(1).await.expect("Failed to read axis limits");
+    assert_eq!(limits.axes.len(), 6);
+    assert_eq!(limits.axes[1], AxisRange::new(-60_000, 90_000));
+    let workspace = limits.to_workspace_limits();
+    assert!(workspace.check_pulse(&[0, 90_000, 0, 0, 0, 0]).is_ok());
+    assert!(workspace.check_pulse(&[0, 90_001, 0, 0, 0, 0]).is_err());
+
+    let result = client.read_axis_limits(2).await;
+    assert!(
+        matches!(result, Err(ClientError::ProtocolError(ProtocolError::FileError(_)))),
+        "{result:?}"
+    );
+});
+
+test_with_logging!(test_read_frames_and_express_position, {
+    let tools = "//TOOL 0\r\n///NAME \r\n0.000,0.000,0.000,0.0000,0.0000,0.0000\r\n0.000\r\n\
+        //TOOL 1\r\n///NAME GRIPPER\r\n0.000,0.000,200.000,0.0000,0.0000,0.0000\r\n2.500\r\n";
+    let user_frames = "//UFRAME 1\r\n///NAME TABLE\r\n///GROUP1 RB1\r\n\
+        ///USER 1000.000,500.000,0.000,0.0000,0.0000,90.0000\r\n";
+    let mut server = MockServerManager::new();
+    server
+        .start_with_builder(|builder| {
+            builder
+                .with_file("TOOL.CND", tools.as_bytes().to_vec())
+                .with_file("UFRAME.CND", user_frames.as_bytes().to_vec())
+        })
+        .await
+        .expect("Failed to start mock server");
+
+    let client = create_file_client().await;
+
+    let frames = client.read_frames().await.expect("Failed to read frames");
+    assert_eq!(frames.tool(1).map(|tool| tool.z), Some(200.0));
+    assert_eq!(frames.user_frame(1).map(|frame| frame.rz), Some(90.0));
+
+    // Flange pointing down at (800, 0, 600); the gripper point is 200 mm below it
+    let flange = CartesianPosition::new(
+        800.0,
+        0.0,
+        600.0,
+        180.0,
+        0.0,
+        0.0,
+        0,
+        0,
+        Configuration::from_raw(0),
+        ExtendedConfiguration::from_raw(0),
+    );
+    let gripper =
+        frames.express(&flange, 1, ReferenceFrame::User(1)).expect("Failed to express position");
+    let values = [gripper.x, gripper.y, gripper.z];
+    for (value, expected) in values.into_iter().zip([-500.0, 200.0, 400.0]) {
+        assert!((value - expected).abs() < 1e-3, "{values:?}");
+    }
+    assert_eq!((gripper.tool_no, gripper.user_coord_no), (1, 1));
+
+    let result = frames.express(&flange, 2, ReferenceFrame::Base);
+    assert!(
+        matches!(result, Err(ClientError::ProtocolError(ProtocolError::FileError(_)))),
+        "{result:?}"
+    );
+});
+
+test_with_logging!(test_read_event_log, {
+    let content = "//LOGDATA\r\n///INDEX 1\r\nDATE:2025/03/01 08:00:05\r\n\
+        EVENT:MODE CHANGE\r\nMODE:REMOTE\r\n///INDEX 2\r\nDATE:2025/03/01 08:01:10\r\n\
+        EVENT:JOB START\r\nJOB:WELD-A\r\n";
+    let mut server = MockServerManager::new();
+    server
+        .start_with_builder(|builder| builder.with_file("LOGDATA.DAT", content.as_bytes().to_vec()))
+        .await
+        .expect("Failed to start mock server");
+
+    let client = create_file_client().await;
+    let log = client.read_event_log().await.expect("Failed to read event log");
+    let events: Vec<_> = log.entries().iter().map(|entry| entry.event.clone()).collect();
+    assert_eq!(
+        events,
+        [
+            ControllerEvent::ModeChange(OperationMode::Remote),
+            ControllerEvent::JobStart { job: "WELD-A".to_string() },
+        ]
+    );
+
+    // A layout the parser does not know is returned rather than failing
+    let content = "2025/03/01 08:00:05 MODE CHANGE PLAY\r\n";
+    client.send_file("LOGDATA.DAT", content.as_bytes()).await.expect("Failed to send file");
+    let log = client.read_event_log().await.expect("Failed to read event log");
+    assert!(log.is_empty());
+    assert_eq!(log.content(), content);
+});
+
+test_with_logging!(test_send_file_in_blocks, {
+    let mut server = MockServerManager::new();
+    server.start().await.expect("Failed to start mock server");
+
+    let client = create_file_client().await;
+
+    // Larger than one datagram: sent and received in blocks of at most 479 bytes
+    let moves = "MOVJ C00000 VJ=10.00\r\n".repeat(100);
+    let content = format!("/JOB\r\n//NAME LARGE\r\n//INST\r\n{moves}END\r\n");
+    assert!(content.len() > 2000);
+    client.send_file("LARGE.JBI", content.as_bytes()).await.expect("Failed to send file");
+
+    let received = client.receive_file("LARGE.JBI").await.expect("Failed to receive file");
+    assert_eq!(received, content);
+});
+
+test_with_logging!(test_file_changes_denied_by_security_mode, {
+    let mut server = MockServerManager::new();
+    server
+        .start_with_builder(|builder| builder.with_security_mode(SecurityMode::Operation))
+        .await
+        .expect("Failed to start mock server");
+    let mock = server.mock().clone();
+
+    let client = create_file_client().await;
+    let job = b"/JOB\r\n//NAME SECURE\r\n//INST\r\nNOP\r\nEND\r\n";
+    let is_denied = |result: Result<(), ClientError>| {
+        matches!(
+            result,
+            Err(ClientError::ProtocolError(ProtocolError::ServerError(ref message)))
+                if message.contains("0x1f") && message.contains("0x2110")
+        )
+    };
+
+    // Operation mode: files are read but not changed
+    assert_eq!(client.read_file_list("*.JBI").await.expect("Failed to get file list").len(), 1);
+    assert!(client.receive_file("TEST.JBI").await.is_ok());
+    assert!(is_denied(client.send_file("SECURE.JBI", job).await));
+    assert!(is_denied(client.delete_file("TEST.JBI").await));
+
+    // Editing mode: job files only
+    mock.set_security_mode(SecurityMode::Editing).await;
+    client.send_file("SECURE.JBI", job).await.expect("Failed to send job in editing mode");
+    assert!(is_denied(client.send_file("TOOL.CND", b"//TOOL 0\r\n").await));
+
+    // Management mode: every file
+    mock.set_security_mode(SecurityMode::Management).await;
+    client.send_file("TOOL.CND", b"//TOOL 0\r\n").await.expect("Failed to send condition file");
+    client.delete_file("TEST.JBI").await.expect("Failed to delete file");
+    let files = client.read_file_list("*").await.expect("Failed to get file list");
+    assert!(files.contains(&"SECURE.JBI".to_string()) && !files.contains(&"TEST.JBI".to_string()));
+});
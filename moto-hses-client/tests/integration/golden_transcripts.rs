@@ -0,0 +1,61 @@
+#![allow(clippy::expect_used)]
+// Integration tests comparing the requests sent by the client with golden transcripts
+//
+// State-based tests pass as long as the end state is right; these catch changes on the
+// wire such as extra retries, changed attributes or reordered requests.
+
+use crate::common::golden::assert_transcript;
+use crate::common::mock_server_setup::MockServerManager;
+use crate::common::test_utils::create_test_client;
+use crate::test_with_logging;
+use moto_hses_client::{ClientConfig, HsesClient};
+use moto_hses_mock::ResponseFault;
+use moto_hses_proto::FILE_CONTROL_PORT;
+use std::time::Duration;
+
+test_with_logging!(test_register_transcript, {
+    let mut server = MockServerManager::new();
+    server.start().await.expect("Failed to start mock server");
+    let client = create_test_client().await.expect("Failed to create client");
+    server.mock().start_transcript().await;
+
+    client.write_register(3, 1234).await.expect("Failed to write register");
+    let value = client.read_register(3).await.expect("Failed to read register");
+    assert_eq!(value, 1234);
+    client.read_status().await.expect("Failed to read status");
+
+    assert_transcript(server.mock(), "register").await;
+});
+
+test_with_logging!(test_retry_transcript, {
+    let mut server = MockServerManager::new();
+    server.start().await.expect("Failed to start mock server");
+    let config = ClientConfig {
+        timeout: Duration::from_millis(150),
+        retry_count: 3,
+        retry_delay: Duration::from_millis(20),
+        ..ClientConfig::default()
+    };
+    let client = HsesClient::new_with_config(config).await.expect("Failed to create client");
+    server.mock().start_transcript().await;
+
+    server.mock().inject_fault(ResponseFault::Drop).await;
+    client.read_register(1).await.expect("Read should succeed after a retry");
+
+    assert_transcript(server.mock(), "retry").await;
+});
+
+test_with_logging!(test_file_transcript, {
+    let mut server = MockServerManager::new();
+    server.start().await.expect("Failed to start mock server");
+    let config = ClientConfig { port: FILE_CONTROL_PORT, ..ClientConfig::default() };
+    let client = HsesClient::new_with_config(config).await.expect("Failed to create client");
+    server.mock().start_transcript().await;
+
+    client.send_file("GOLDEN.JBI", b"NOP\r\nEND\r\n").await.expect("Failed to send file");
+    let files = client.read_file_list("*.JBI").await.expect("Failed to read file list");
+    assert!(files.contains(&"GOLDEN.JBI".to_string()), "{files:?}");
+    client.delete_file("GOLDEN.JBI").await.expect("Failed to delete file");
+
+    assert_transcript(server.mock(), "file").await;
+});
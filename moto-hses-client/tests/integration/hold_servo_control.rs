@@ -4,9 +4,31 @@
 
 use crate::common::{
     mock_server_setup::MockServerManager,
-    test_utils::{create_test_client, wait_for_operation},
+    test_utils::{create_shared_test_client, create_test_client, wait_for_operation},
 };
 use crate::test_with_logging;
+use moto_hses_client::{
+    CartesianPosition, ClientError, EmergencyHoldOptions, HsesClient, HsesClientOps, MoveSpeed,
+    Position, TrajectoryControl, TrajectoryOptions,
+};
+use moto_hses_mock::MotionSimulation;
+use moto_hses_proto::payload::position::{Configuration, ExtendedConfiguration};
+use std::time::Duration;
+
+const fn cartesian(x: f32) -> Position {
+    Position::Cartesian(CartesianPosition::new(
+        x,
+        0.0,
+        300.0,
+        180.0,
+        0.0,
+        0.0,
+        0,
+        0,
+        Configuration::from_raw(0),
+        ExtendedConfiguration::from_raw(0),
+    ))
+}
 
 test_with_logging!(test_hold_control_commands, {
     let mut server = MockServerManager::new();
@@ -144,3 +166,72 @@ test_with_logging!(test_hlock_control_commands, {
 
     log::info!("  ✓ HLOCK {} command sent", if initial_hlock_state { "ON" } else { "OFF" });
 });
+
+test_with_logging!(test_emergency_hold, {
+    let mut server = MockServerManager::new();
+    server.start().await.expect("Failed to start mock server");
+
+    let client = create_test_client().await.expect("Failed to create client");
+
+    let options = EmergencyHoldOptions { servo_off: true, ..EmergencyHoldOptions::default() };
+    let report = client.emergency_hold(&options).await.expect("Failed to hold");
+    assert!(report.stopped);
+    assert!(report.servo_off);
+    assert!(!report.has_alarm());
+    assert!(report.elapsed <= options.deadline, "Unexpected report: {report:?}");
+
+    let status = client.read_status().await.expect("Failed to read status");
+    assert!(status.data2.command_hold);
+    assert!(!status.is_servo_on());
+
+    // Without a controller the routine still returns by the deadline
+    let client = HsesClient::new("127.0.0.1:10098").await.expect("Failed to create client");
+    let options = EmergencyHoldOptions::default();
+    let started_at = std::time::Instant::now();
+    let result = client.emergency_hold(&options).await;
+    assert!(matches!(result, Err(ClientError::TimeoutError(_))), "Unexpected result: {result:?}");
+    assert!(started_at.elapsed() < options.deadline + Duration::from_millis(100));
+});
+
+test_with_logging!(test_emergency_hold_while_client_busy, {
+    let mut server = MockServerManager::new();
+    server
+        .start_with_builder(|builder| {
+            builder
+                .with_position(cartesian(400.0))
+                .with_motion_simulation(MotionSimulation::default())
+        })
+        .await
+        .expect("Failed to start mock server");
+
+    let client = create_shared_test_client().await.expect("Failed to create client");
+    let emergency = client.clone();
+
+    // A 1 s move holds the client lock until the segment times out
+    let points = [cartesian(600.0)];
+    let options = TrajectoryOptions {
+        speed: MoveSpeed::Linear(200.0),
+        segment_timeout: Duration::from_millis(1500),
+        ..TrajectoryOptions::default()
+    };
+    let control = TrajectoryControl::new();
+    let (result, report) =
+        tokio::join!(client.play_trajectory(1, &points, &options, &control), async move {
+            tokio::time::sleep(Duration::from_millis(300)).await;
+            emergency.emergency_hold(&EmergencyHoldOptions::default()).await
+        });
+
+    let report = report.expect("Failed to hold");
+    assert!(report.stopped);
+    assert!(!report.servo_off);
+    assert!(report.elapsed < Duration::from_millis(500), "Unexpected report: {report:?}");
+    assert!(matches!(result, Err(ClientError::TimeoutError(_))), "Unexpected result: {result:?}");
+
+    // The robot stopped part way
+    let Position::Cartesian(position) =
+        client.read_position(101).await.expect("Failed to read position")
+    else {
+        unreachable!("Expected Cartesian position");
+    };
+    assert!(position.x > 420.0 && position.x < 580.0, "x: {}", position.x);
+});
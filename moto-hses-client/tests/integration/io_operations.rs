@@ -6,6 +6,10 @@ use crate::common::{
     test_utils::create_test_client,
 };
 use crate::test_with_logging;
+use moto_hses_client::ClientError;
+use moto_hses_mock::IoForceConflict;
+use moto_hses_proto::ProtocolError;
+use std::time::Duration;
 
 test_with_logging!(test_read_io, {
     log::debug!("Creating I/O test server...");
@@ -42,33 +46,96 @@ test_with_logging!(test_write_io, {
 
     let client = create_test_client().await.expect("Failed to create client");
 
-    // Test writing to robot user output I/O (as per legacy example)
-    log::info!("Writing to robot user output I/O #1001...");
-    client.write_io(1001, 0b0000_0001).await.expect("Failed to write to I/O #1001");
-    log::info!("Successfully set I/O #1001 to ON");
+    // Only network input signals are writable
+    log::info!("Writing to network input I/O #2701...");
+    client.write_io(2701, 0b0000_0001).await.expect("Failed to write to I/O #2701");
+    log::info!("Successfully set I/O #2701 to ON");
 
     // Wait a moment and verify the change (as per legacy example)
     tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
-    log::info!("Verifying I/O #1001 state...");
+    log::info!("Verifying I/O #2701 state...");
     let io_state_after_write =
-        client.read_io(1001).await.expect("Failed to read I/O #1001 after write");
+        client.read_io(2701).await.expect("Failed to read I/O #2701 after write");
 
-    log::info!("I/O #1001 state after write: 0b{io_state_after_write:08b}");
-    assert_eq!(io_state_after_write, 0b0000_0001, "I/O #1001 should be ON after write");
+    log::info!("I/O #2701 state after write: 0b{io_state_after_write:08b}");
+    assert_eq!(io_state_after_write, 0b0000_0001, "I/O #2701 should be ON after write");
 
     // Additional I/O operations (as per legacy example)
-    log::info!("Writing to robot user output I/O #1002...");
-    client.write_io(1002, 0b0000_0000).await.expect("Failed to write OFF to I/O #1002");
-    log::info!("Successfully set I/O #1002 to OFF");
+    log::info!("Writing to network input I/O #2702...");
+    client.write_io(2702, 0b0000_0000).await.expect("Failed to write OFF to I/O #2702");
+    log::info!("Successfully set I/O #2702 to OFF");
 
     // Wait a moment and verify the change
     tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
-    log::info!("Verifying I/O #1002 state...");
-    let io1002_state_after_write =
-        client.read_io(1002).await.expect("Failed to read I/O #1002 after write");
+    log::info!("Verifying I/O #2702 state...");
+    let io2702_state_after_write =
+        client.read_io(2702).await.expect("Failed to read I/O #2702 after write");
 
-    log::info!("I/O #1002 state after write: 0b{io1002_state_after_write:08b}");
-    assert_eq!(io1002_state_after_write, 0b0000_0000, "I/O #1002 should be OFF after write");
+    log::info!("I/O #2702 state after write: 0b{io2702_state_after_write:08b}");
+    assert_eq!(io2702_state_after_write, 0b0000_0000, "I/O #2702 should be OFF after write");
+});
+
+test_with_logging!(test_write_io_updates_mock_state, {
+    let server = create_io_test_server().await.expect("Failed to start mock server");
+
+    let client = create_test_client().await.expect("Failed to create client");
+
+    client.write_io(2701, 0b0000_0001).await.expect("Failed to write to I/O #2701");
+    server.mock().assert_io_eq(2701, 0b0000_0001).await;
+    server.mock().assert_io(2701, true).await;
+
+    client.write_io(2702, 0b0000_0000).await.expect("Failed to write OFF to I/O #2702");
+    server.mock().assert_io(2702, false).await;
+});
+
+test_with_logging!(test_write_read_only_io_is_rejected, {
+    let server = create_io_test_server().await.expect("Failed to start mock server");
+
+    let client = create_test_client().await.expect("Failed to create client");
+
+    // Robot user input and output signals are driven by the controller
+    for (io_number, initial) in [(1, 0b0000_0001), (1001, 0b0000_0000)] {
+        match client.write_io(io_number, 0b1010_1010).await {
+            Err(ClientError::ProtocolError(ProtocolError::ServerError(message))) => {
+                log::debug!("✓ Write to I/O #{io_number} correctly rejected: {message}");
+                assert!(message.contains("0x28"), "Unexpected status: {message}");
+            }
+            result => unreachable!("Write to I/O #{io_number} should be rejected: {result:?}"),
+        }
+        server.mock().assert_io_eq(io_number, initial).await;
+    }
+
+    // The rejection does not affect later requests
+    client.write_io(2701, 0b0000_0001).await.expect("Failed to write to I/O #2701");
+    server.mock().assert_io(2701, true).await;
+});
+
+test_with_logging!(test_write_multiple_read_only_io_is_rejected, {
+    let server = create_io_test_server().await.expect("Failed to start mock server");
+
+    let client = create_test_client().await.expect("Failed to create client");
+
+    match client.write_multiple_io(1001, vec![0b1111_1111, 0b1111_1111]).await {
+        Err(ClientError::ProtocolError(ProtocolError::ServerError(message))) => {
+            assert!(message.contains("0x28"), "Unexpected status: {message}");
+        }
+        result => unreachable!("Write to I/O #1001 should be rejected: {result:?}"),
+    }
+    server.mock().assert_io_eq(1001, 0b0000_0000).await;
+    server.mock().assert_io_eq(1002, 0b0000_0001).await;
+});
+
+test_with_logging!(test_wait_for_io_written_by_client, {
+    let server = create_io_test_server().await.expect("Failed to start mock server");
+    let mock = server.mock();
+
+    let client = create_test_client().await.expect("Failed to create client");
+    let writer = tokio::spawn(async move { client.write_io(2701, 0b0000_0001).await });
+
+    tokio::time::timeout(std::time::Duration::from_secs(2), mock.wait_for_io(2701, true))
+        .await
+        .expect("I/O #2701 should turn ON");
+    writer.await.expect("Writer task panicked").expect("Failed to write to I/O #2701");
 });
 
 test_with_logging!(test_read_and_write_io_with_invalid_number, {
@@ -263,3 +330,125 @@ test_with_logging!(test_multiple_io_boundary_conditions, {
         .expect("Failed to read back maximum safe count");
     assert_eq!(read_data, large_io_data, "Read back data should match written data");
 });
+
+test_with_logging!(test_pulse_io, {
+    let server = create_io_test_server().await.expect("Failed to start mock server");
+    let mock = server.mock();
+    let client = create_test_client().await.expect("Failed to create client");
+
+    let pulse = tokio::spawn(async move {
+        client.pulse_io(2701, 0b0000_0101, Duration::from_millis(300)).await
+    });
+    tokio::time::timeout(Duration::from_secs(2), mock.wait_for_io(2701, true))
+        .await
+        .expect("I/O #2701 should turn ON");
+    mock.assert_io_eq(2701, 0b0000_0101).await;
+    pulse.await.expect("Pulse task panicked").expect("Failed to pulse I/O #2701");
+    mock.assert_io_eq(2701, 0).await;
+});
+
+test_with_logging!(test_pulse_io_cleared_when_dropped, {
+    let server = create_io_test_server().await.expect("Failed to start mock server");
+    let mock = server.mock();
+    let client = create_test_client().await.expect("Failed to create client");
+
+    // A timeout around the pulse drops it while the input is set
+    let pulse = client.pulse_io(2701, 0b0000_0001, Duration::from_secs(30));
+    assert!(tokio::time::timeout(Duration::from_millis(300), pulse).await.is_err());
+    tokio::time::timeout(Duration::from_secs(2), mock.wait_for_io(2701, false))
+        .await
+        .expect("I/O #2701 should be cleared");
+});
+
+test_with_logging!(test_pulse_io_rejects_read_only_io, {
+    let server = create_io_test_server().await.expect("Failed to start mock server");
+    let client = create_test_client().await.expect("Failed to create client");
+
+    let result = client.pulse_io(1001, 1, Duration::from_millis(10)).await;
+    assert!(matches!(result, Err(ClientError::ProtocolError(ProtocolError::InvalidMessage(_)))));
+    server.mock().assert_io_eq(1001, 0).await;
+});
+
+test_with_logging!(test_io_bit_read_write, {
+    let server = create_io_test_server().await.expect("Failed to start mock server");
+    let client = create_test_client().await.expect("Failed to create client");
+
+    // Output #1002 and network input #2702 hold 0b0000_0001
+    assert!(client.read_io_bit(10020).await.expect("Failed to read #10020"));
+    assert!(!client.read_io_bit(10021).await.expect("Failed to read #10021"));
+
+    client.write_io_bit(27023, true).await.expect("Failed to turn #27023 on");
+    server.mock().assert_io_eq(2702, 0b0000_1001).await;
+    assert!(client.read_io_bit(27023).await.expect("Failed to read #27023"));
+    client.write_io_bit(27020, false).await.expect("Failed to turn #27020 off");
+    server.mock().assert_io_eq(2702, 0b0000_1000).await;
+
+    for address in [10028, 5130] {
+        let result = client.read_io_bit(address).await;
+        assert!(matches!(result, Err(ClientError::ProtocolError(_))), "#{address}: {result:?}");
+    }
+    let result = client.write_io_bit(10020, false).await;
+    assert!(matches!(result, Err(ClientError::ProtocolError(ProtocolError::InvalidMessage(_)))));
+    server.mock().assert_io_eq(1002, 0b0000_0001).await;
+});
+
+test_with_logging!(test_io_bits, {
+    let server = create_io_test_server().await.expect("Failed to start mock server");
+    let client = create_test_client().await.expect("Failed to create client");
+
+    // Output #1001 holds 0 and #1002 0b0000_0001; signals continue from bit 7 to the next byte
+    let bits = client.read_io_bits(10017, 3).await.expect("Failed to read I/O bits");
+    assert_eq!(bits, [false, true, false]);
+    let bits = client.read_io_bits(10020, 4).await.expect("Failed to read I/O bits");
+    assert_eq!(bits, [true, false, false, false]);
+
+    // The last network input byte is padded before, not after the range
+    client.write_io(2956, 0b1000_0001).await.expect("Failed to write I/O");
+    let bits = client.read_io_bits(29560, 8).await.expect("Failed to read I/O bits");
+    assert_eq!(bits, [true, false, false, false, false, false, false, true]);
+
+    // Partial bytes keep their other signals
+    client.write_io_bits(27021, &[true, true]).await.expect("Failed to write I/O bits");
+    server.mock().assert_io_eq(2702, 0b0000_0111).await;
+    server.mock().assert_io_eq(2701, 0).await;
+    client.write_io_bits(29567, &[false]).await.expect("Failed to write I/O bits");
+    server.mock().assert_io_eq(2956, 0b0000_0001).await;
+    client.write_io_bits(27010, &[true; 16]).await.expect("Failed to write I/O bits");
+    server.mock().assert_io_eq(2701, 0xff).await;
+    server.mock().assert_io_eq(2702, 0xff).await;
+
+    let result = client.write_io_bits(10010, &[true]).await;
+    assert!(matches!(result, Err(ClientError::ProtocolError(ProtocolError::InvalidMessage(_)))));
+    let result = client.write_io_bits(29567, &[true, true]).await;
+    assert!(matches!(result, Err(ClientError::ProtocolError(ProtocolError::InvalidMessage(_)))));
+    let result = client.read_io_bits(10010, 0).await;
+    assert!(matches!(result, Err(ClientError::ProtocolError(ProtocolError::InvalidMessage(_)))));
+    server.mock().assert_io_eq(1001, 0).await;
+});
+
+test_with_logging!(test_forced_io_masks_writes, {
+    let server = create_io_test_server().await.expect("Failed to start mock server");
+    let mock = server.mock();
+    let client = create_test_client().await.expect("Failed to create client");
+
+    // The controller accepts writes to a forced signal, which keeps its forced value
+    mock.force_io(2701, 0b0000_0001).await;
+    client.write_io(2701, 0b0000_0000).await.expect("Failed to write to I/O #2701");
+    client.write_multiple_io(2701, vec![0b1000_0000, 0b0000_0000]).await.expect("Failed to write");
+    mock.assert_io_eq(2701, 0b0000_0001).await;
+    mock.assert_io_eq(2702, 0b0000_0000).await;
+    assert_eq!(client.read_io(2701).await.expect("Failed to read I/O #2701"), 0b0000_0001);
+    assert_eq!(
+        mock.take_io_force_conflicts().await,
+        [
+            IoForceConflict { io_number: 2701, forced: 0b0000_0001, written: 0b0000_0000 },
+            IoForceConflict { io_number: 2701, forced: 0b0000_0001, written: 0b1000_0000 },
+        ]
+    );
+
+    // Once released, writes take effect again
+    mock.release_io(2701).await;
+    client.write_io(2701, 0b0000_0000).await.expect("Failed to write to I/O #2701");
+    mock.assert_io(2701, false).await;
+    mock.assert_no_io_force_conflicts().await;
+});
@@ -1,11 +1,17 @@
 #![allow(clippy::expect_used)]
 // Integration tests for job control operations
 
-use crate::common::test_utils::{create_test_client, wait_for_operation};
+use crate::common::mock_server_setup::MockServerManager;
+use crate::common::test_utils::{
+    create_test_client, create_test_client_with_host_and_port, wait_for_operation,
+};
 use crate::test_with_logging;
+use moto_hses_client::{ClientError, JobFile};
+use moto_hses_mock::JobSimulation;
 use moto_hses_mock::server::MockServerBuilder;
-use moto_hses_proto::{FILE_CONTROL_PORT, ROBOT_CONTROL_PORT, commands::JobSelectType};
+use moto_hses_proto::{Alarm, FILE_CONTROL_PORT, ROBOT_CONTROL_PORT, commands::JobSelectType};
 use std::sync::Arc;
+use std::time::Duration;
 
 test_with_logging!(test_job_start_command, {
     // Create mock server
@@ -265,3 +271,178 @@ test_with_logging!(test_job_select_command_all_types, {
     // Clean up
     server_handle.abort();
 });
+
+test_with_logging!(test_read_selected_job, {
+    // Create mock server
+    let server = Arc::new(
+        MockServerBuilder::new()
+            .host("127.0.0.1")
+            .robot_port(ROBOT_CONTROL_PORT)
+            .file_port(FILE_CONTROL_PORT)
+            .build()
+            .await
+            .expect("Failed to build mock server"),
+    );
+
+    // Start server in background
+    let server_clone = Arc::clone(&server);
+    let server_handle = tokio::spawn(async move {
+        server_clone.run().await.expect("Failed to run mock server");
+    });
+
+    // Wait for server to be ready
+    wait_for_operation().await;
+
+    let client = create_test_client().await.expect("Failed to create client");
+
+    // Before any selection the loaded (executing) job is reported
+    let selected = client.read_selected_job().await.expect("Failed to read selected job");
+    assert_eq!(selected.job_name, "TEST.JOB");
+    assert_eq!(selected.line_number, 2);
+
+    client
+        .select_job(JobSelectType::InExecution, "PICK.JOB", 15)
+        .await
+        .expect("Failed to select job");
+
+    let selected = client.read_selected_job().await.expect("Failed to read selected job");
+    assert_eq!(selected.job_name, "PICK.JOB");
+    assert_eq!(selected.line_number, 15);
+    log::info!("✓ Selected job read back: {selected:?}");
+
+    // Clean up
+    server_handle.abort();
+});
+
+test_with_logging!(test_run_job_and_wait, {
+    let mut server = MockServerManager::new();
+    server
+        .start_with_builder(|builder| {
+            builder.with_job_simulation(JobSimulation::new(Duration::from_millis(300)))
+        })
+        .await
+        .expect("Failed to start mock server");
+
+    let client = create_test_client().await.expect("Failed to create client");
+
+    let report = client
+        .run_job_and_wait("PICK.JOB", 0, Duration::from_secs(1), Duration::from_secs(5))
+        .await
+        .expect("Failed to run job");
+    assert_eq!(report.job_name, "PICK.JOB");
+    assert!(report.duration >= Duration::from_millis(300), "Job stopped early: {report:?}");
+    assert!(!report.final_status.data1.running);
+    assert!(report.is_success(), "No alarms expected: {:?}", report.alarms);
+
+    // The started job is reported as executing
+    let job = client.read_executing_job_info_complete(1).await.expect("Failed to read job info");
+    assert_eq!(job.job_name, "PICK.JOB");
+    log::info!("✓ Job run completed in {:?}", report.duration);
+});
+
+test_with_logging!(test_run_job_and_wait_reports_alarms, {
+    let alarm = Alarm::new(4100, 1, 0, "2024/01/01 12:00".to_string(), "OVERRUN".to_string());
+    let simulation = JobSimulation::new(Duration::from_millis(100)).with_alarm(alarm.clone());
+    let mut server = MockServerManager::new();
+    server
+        .start_with_builder(move |builder| builder.with_job_simulation(simulation))
+        .await
+        .expect("Failed to start mock server");
+
+    let client = create_test_client().await.expect("Failed to create client");
+
+    let report = client
+        .run_job_and_wait("PICK.JOB", 0, Duration::from_secs(1), Duration::from_secs(5))
+        .await
+        .expect("Failed to run job");
+    assert!(!report.is_success());
+    assert_eq!(report.alarms, vec![alarm]);
+    assert!(report.final_status.data2.alarm);
+});
+
+test_with_logging!(test_run_job_and_wait_delayed_start, {
+    // The controller reports the job running only some time after the start request
+    let simulation =
+        JobSimulation::new(Duration::from_millis(200)).with_start_delay(Duration::from_millis(300));
+    let mut server = MockServerManager::new();
+    server
+        .start_with_builder(move |builder| builder.with_job_simulation(simulation))
+        .await
+        .expect("Failed to start mock server");
+
+    let client = create_test_client().await.expect("Failed to create client");
+
+    // The status read before the job is running is not taken as its end
+    let report = client
+        .run_job_and_wait("PICK.JOB", 0, Duration::from_secs(1), Duration::from_secs(5))
+        .await
+        .expect("Failed to run job");
+    assert!(report.duration >= Duration::from_millis(500), "Job stopped early: {report:?}");
+    assert!(!report.final_status.data1.running);
+
+    // A job that is not running within the start timeout is reported as not started
+    let result = client
+        .run_job_and_wait("PICK.JOB", 0, Duration::from_millis(100), Duration::from_secs(5))
+        .await;
+    assert!(
+        matches!(&result, Err(ClientError::TimeoutError(message)) if message.contains("did not start")),
+        "Unexpected result: {result:?}"
+    );
+});
+
+test_with_logging!(test_run_job_and_wait_errors, {
+    let mut server = MockServerManager::new();
+    server.start().await.expect("Failed to start mock server");
+
+    let client = create_test_client().await.expect("Failed to create client");
+
+    // Without a job simulation the started job keeps running
+    let result = client
+        .run_job_and_wait("PICK.JOB", 0, Duration::from_secs(1), Duration::from_millis(300))
+        .await;
+    assert!(matches!(result, Err(ClientError::TimeoutError(_))), "Unexpected result: {result:?}");
+
+    client.set_servo(false).await.expect("Failed to turn servo off");
+    let result = client
+        .run_job_and_wait("PICK.JOB", 0, Duration::from_secs(1), Duration::from_millis(300))
+        .await;
+    assert!(matches!(result, Err(ClientError::SystemError(_))), "Unexpected result: {result:?}");
+});
+
+test_with_logging!(test_jump_to_line, {
+    let weld = "/JOB\r\n//NAME WELD\r\n//POS\r\n///NPOS 0,0,0,0,0,0\r\n//INST\r\n\
+                ///DATE 2024/04/22 10:15\r\n///ATTR SC,RW\r\n///GROUP1 RB1\r\n\
+                NOP\r\nMOVJ VJ=10.00\r\nARCON\r\nARCOF\r\nEND\r\n";
+    let mut server = MockServerManager::new();
+    server
+        .start_with_builder(|builder| builder.with_file("WELD.JBI", weld.as_bytes().to_vec()))
+        .await
+        .expect("Failed to start mock server");
+
+    let client = create_test_client().await.expect("Failed to create client");
+    let file_client = create_test_client_with_host_and_port("127.0.0.1", FILE_CONTROL_PORT)
+        .await
+        .expect("Failed to create file client");
+    client.select_job(JobSelectType::InExecution, "WELD.JOB", 0).await.expect("Failed to select");
+
+    // Checked against the downloaded job
+    let source = file_client.receive_file("WELD.JBI").await.expect("Failed to receive job");
+    let job = JobFile::parse(&source).expect("Failed to parse job");
+    assert_eq!(job.last_line(), 4);
+    let selected = client.jump_to_line(3, Some(&job)).await.expect("Failed to jump");
+    assert_eq!((selected.job_name.as_str(), selected.line_number), ("WELD.JOB", 3));
+    let read = client.read_selected_job().await.expect("Failed to read");
+    assert_eq!(read, selected);
+
+    let result = client.jump_to_line(5, Some(&job)).await;
+    assert!(matches!(result, Err(ClientError::SystemError(_))), "Unexpected result: {result:?}");
+    let other = JobFile::parse("/JOB\r\n//NAME PICK\r\n//INST\r\nNOP\r\nEND\r\n").expect("Parse");
+    let result = client.jump_to_line(1, Some(&other)).await;
+    assert!(matches!(result, Err(ClientError::SystemError(_))), "Unexpected result: {result:?}");
+
+    // Without the job, only the line range of job selection is checked
+    assert!(client.jump_to_line(10_000, None).await.is_err());
+    client.jump_to_line(1, None).await.expect("Failed to jump");
+    let read = client.read_selected_job().await.expect("Failed to read");
+    assert_eq!((read.job_name.as_str(), read.line_number), ("WELD.JOB", 1));
+});
@@ -0,0 +1,53 @@
+#![allow(clippy::expect_used)]
+// Integration tests for responses whose payload is shorter or longer than expected
+
+use crate::common::{
+    mock_server_setup::{MockServerManager, create_alarm_test_server},
+    test_utils::create_test_client,
+};
+use crate::test_with_logging;
+use moto_hses_client::ClientError;
+use moto_hses_mock::PayloadFault;
+use moto_hses_proto::{AlarmAttribute, ProtocolError};
+
+test_with_logging!(test_truncated_register_payload, {
+    let mut server = MockServerManager::new();
+    server.start().await.expect("Failed to start mock server");
+    let client = create_test_client().await.expect("Failed to create client");
+
+    server.mock().set_payload_fault(0x79, PayloadFault::Truncate(1)).await;
+    match client.read_register(1).await {
+        Err(ClientError::ProtocolError(ProtocolError::Deserialization(message))) => {
+            log::debug!("✓ Short register payload rejected: {message}");
+        }
+        result => unreachable!("Short register payload should be rejected: {result:?}"),
+    }
+
+    server.mock().clear_payload_fault(0x79).await;
+    assert_eq!(client.read_register(1).await.expect("Failed to read register"), 100);
+});
+
+test_with_logging!(test_extended_register_payload, {
+    let mut server = MockServerManager::new();
+    server.start().await.expect("Failed to start mock server");
+    let client = create_test_client().await.expect("Failed to create client");
+
+    // Trailing bytes after the register data are ignored
+    server.mock().set_payload_fault(0x79, PayloadFault::Extend(8)).await;
+    assert_eq!(client.read_register(1).await.expect("Failed to read register"), 100);
+});
+
+test_with_logging!(test_truncated_alarm_payload, {
+    let server = create_alarm_test_server().await.expect("Failed to start mock server");
+    let client = create_test_client().await.expect("Failed to create client");
+
+    server.mock().set_payload_fault(0x70, PayloadFault::Truncate(59)).await;
+    let result = client.read_alarm_data(1, AlarmAttribute::All).await;
+    assert!(
+        matches!(result, Err(ClientError::ProtocolError(_))),
+        "Short alarm payload should be rejected: {result:?}"
+    );
+
+    // Other commands are answered unaltered
+    client.read_status().await.expect("Failed to read status");
+});
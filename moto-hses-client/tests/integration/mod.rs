@@ -2,15 +2,23 @@
 // These tests verify the actual communication between client and mock server
 
 pub mod alarm_operations;
+pub mod audit_log;
 pub mod connection_management;
+pub mod controller_capabilities;
 pub mod cycle_mode_control;
 pub mod file_operations;
+pub mod golden_transcripts;
 pub mod hold_servo_control;
 pub mod io_operations;
 pub mod job_control;
+pub mod malformed_responses;
 pub mod position_operations;
+pub mod profile_matrix;
 pub mod read_executing_job_info;
 pub mod read_status;
 pub mod register_operations;
+pub mod retry_behavior;
 pub mod shared_client;
+pub mod transcript_replay;
 pub mod variable_operations;
+pub mod writer_lock;
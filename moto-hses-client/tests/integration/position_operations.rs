@@ -6,6 +6,31 @@ use crate::common::{
     test_utils::create_test_client,
 };
 use crate::test_with_logging;
+use moto_hses_client::{
+    AxisRange, CartesianBounds, CartesianDelta, ClientConfig, ClientError, HsesClient,
+    IncrementalMoveLimits, IncrementalMoveOptions, JogAxis, JogDeadman, JogDirection,
+    JogStopReason, MoveFrame, MoveSpeed, Position, TrajectoryControl, TrajectoryOptions,
+    WorkspaceLimits,
+};
+use moto_hses_mock::MotionSimulation;
+use moto_hses_proto::PulsePosition;
+use moto_hses_proto::payload::position::{Configuration, ExtendedConfiguration};
+use std::time::Duration;
+
+const fn cartesian_start() -> Position {
+    Position::Cartesian(moto_hses_proto::CartesianPosition::new(
+        400.0,
+        0.0,
+        300.0,
+        180.0,
+        0.0,
+        0.0,
+        2,
+        0,
+        Configuration::from_raw(0),
+        ExtendedConfiguration::from_raw(0),
+    ))
+}
 
 test_with_logging!(test_read_robot_pulse_position, {
     log::debug!("Creating position test server...");
@@ -231,6 +256,31 @@ test_with_logging!(test_read_b2_position, {
     }
 });
 
+test_with_logging!(test_read_position_by_control_group, {
+    let mut server = MockServerManager::new();
+    server
+        .start_with_builder(|builder| {
+            builder
+                .with_position(cartesian_start())
+                .with_pulse_position(1, PulsePosition::new(vec![10, 20, 30, 40, 50, 60, 0, 0]))
+        })
+        .await
+        .expect("Failed to start mock server");
+
+    let client = create_test_client().await.expect("Failed to create client");
+
+    assert_eq!(
+        client.read_position(1).await.expect("Failed to read R1 pulse position"),
+        Position::Pulse(PulsePosition::new(vec![10, 20, 30, 40, 50, 60, 0, 0]))
+    );
+    assert_eq!(
+        client.read_position(101).await.expect("Failed to read R1 Cartesian position"),
+        cartesian_start()
+    );
+    let result = client.read_position(99).await;
+    assert!(result.is_err(), "Unexpected result: {result:?}");
+});
+
 test_with_logging!(test_continuous_position_monitoring, {
     let _server =
         create_position_test_server().await.expect("Failed to start position test server");
@@ -275,3 +325,486 @@ test_with_logging!(test_continuous_position_monitoring, {
     }
     log::debug!("Position monitoring completed successfully");
 });
+
+test_with_logging!(test_move_incremental, {
+    let mut server = MockServerManager::new();
+    server
+        .start_with_builder(|builder| builder.with_position(cartesian_start()))
+        .await
+        .expect("Failed to start mock server");
+
+    let client = create_test_client().await.expect("Failed to create client");
+
+    let result = client
+        .move_incremental(
+            1,
+            CartesianDelta::translation(5.0, -2.5, 1.0),
+            &IncrementalMoveOptions { frame: MoveFrame::Tool, ..IncrementalMoveOptions::default() },
+        )
+        .await
+        .expect("Failed to move robot");
+    assert!(!result.clamped);
+    assert_eq!(result.start.tool_no, 2);
+
+    let Position::Cartesian(position) =
+        client.read_position(101).await.expect("Failed to read position")
+    else {
+        unreachable!("Expected Cartesian position");
+    };
+    assert!((position.x - 405.0).abs() < 0.01, "x: {}", position.x);
+    assert!((position.y + 2.5).abs() < 0.01, "y: {}", position.y);
+    assert!((position.z - 301.0).abs() < 0.01, "z: {}", position.z);
+    assert_eq!(position.tool_no, 2);
+});
+
+test_with_logging!(test_move_incremental_clamps_delta, {
+    let mut server = MockServerManager::new();
+    server
+        .start_with_builder(|builder| builder.with_position(cartesian_start()))
+        .await
+        .expect("Failed to start mock server");
+
+    let client = create_test_client().await.expect("Failed to create client");
+
+    let options = IncrementalMoveOptions {
+        limits: IncrementalMoveLimits { max_translation: 2.0, max_rotation: 1.0 },
+        ..IncrementalMoveOptions::default()
+    };
+    let delta = CartesianDelta { x: 50.0, z: -0.5, rz: -10.0, ..CartesianDelta::default() };
+    let result = client.move_incremental(1, delta, &options).await.expect("Failed to move robot");
+    assert!(result.clamped);
+    assert_eq!(
+        result.delta,
+        CartesianDelta { x: 2.0, z: -0.5, rz: -1.0, ..CartesianDelta::default() }
+    );
+
+    let Position::Cartesian(position) =
+        client.read_position(101).await.expect("Failed to read position")
+    else {
+        unreachable!("Expected Cartesian position");
+    };
+    assert!((position.x - 402.0).abs() < 0.01, "x: {}", position.x);
+    assert!((position.rz + 1.0).abs() < 0.01, "rz: {}", position.rz);
+});
+
+test_with_logging!(test_move_incremental_errors, {
+    let mut server = MockServerManager::new();
+    server.start().await.expect("Failed to start mock server");
+
+    let client = create_test_client().await.expect("Failed to create client");
+
+    // The default mock position is a pulse position
+    let result = client
+        .move_incremental(
+            1,
+            CartesianDelta::translation(1.0, 0.0, 0.0),
+            &IncrementalMoveOptions::default(),
+        )
+        .await;
+    assert!(matches!(result, Err(ClientError::ProtocolError(_))), "Unexpected result: {result:?}");
+
+    let result = client
+        .move_incremental(
+            1,
+            CartesianDelta::translation(f32::NAN, 0.0, 0.0),
+            &IncrementalMoveOptions::default(),
+        )
+        .await;
+    assert!(matches!(result, Err(ClientError::ProtocolError(_))), "Unexpected result: {result:?}");
+});
+
+test_with_logging!(test_jog_until_released, {
+    let mut server = MockServerManager::new();
+    server
+        .start_with_builder(|builder| {
+            builder
+                .with_position(cartesian_start())
+                .with_motion_simulation(MotionSimulation::default())
+        })
+        .await
+        .expect("Failed to start mock server");
+
+    let client = create_test_client().await.expect("Failed to create client");
+
+    let deadman = JogDeadman::new(Duration::from_secs(1));
+    let operator = deadman.clone();
+    let (report, ()) = tokio::join!(
+        client.jog(1, MoveFrame::Base, JogDirection::negative(JogAxis::Z), 20.0, &deadman),
+        async move {
+            tokio::time::sleep(Duration::from_millis(350)).await;
+            operator.release();
+        }
+    );
+    let report = report.expect("Failed to jog");
+    assert_eq!(report.stop_reason, JogStopReason::Released);
+    assert!((2..=6).contains(&report.steps), "Unexpected steps: {report:?}");
+
+    // Let the last increment finish
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    let status = client.read_status().await.expect("Failed to read status");
+    assert!(!status.is_running(), "Robot should have stopped");
+    let Position::Cartesian(position) =
+        client.read_position(101).await.expect("Failed to read position")
+    else {
+        unreachable!("Expected Cartesian position");
+    };
+    assert!((300.0 - position.z - report.distance).abs() < 0.01, "z: {}", position.z);
+});
+
+test_with_logging!(test_jog_stops_without_keepalive, {
+    let mut server = MockServerManager::new();
+    server
+        .start_with_builder(|builder| {
+            builder
+                .with_position(cartesian_start())
+                .with_motion_simulation(MotionSimulation::default())
+        })
+        .await
+        .expect("Failed to start mock server");
+
+    let client = create_test_client().await.expect("Failed to create client");
+
+    let deadman = JogDeadman::new(Duration::from_millis(250));
+    let report = tokio::time::timeout(
+        Duration::from_secs(2),
+        client.jog(1, MoveFrame::Tool, JogDirection::positive(JogAxis::Rz), 5.0, &deadman),
+    )
+    .await
+    .expect("Jog should stop without keepalive")
+    .expect("Failed to jog");
+    assert_eq!(report.stop_reason, JogStopReason::KeepaliveTimeout);
+    assert!(report.steps >= 1, "Unexpected steps: {report:?}");
+
+    // A released token does not move the robot at all
+    deadman.release();
+    let report = client
+        .jog(1, MoveFrame::Base, JogDirection::positive(JogAxis::X), 5.0, &deadman)
+        .await
+        .expect("Failed to jog");
+    assert_eq!(report.stop_reason, JogStopReason::Released);
+    assert_eq!(report.steps, 0);
+});
+
+test_with_logging!(test_move_pulse, {
+    let mut server = MockServerManager::new();
+    server.start().await.expect("Failed to start mock server");
+
+    let client = create_test_client().await.expect("Failed to create client");
+
+    let target = vec![1000, -2000, 3000, 0, 500, -600, 0, 0];
+    client
+        .move_pulse(1, target.clone(), MoveSpeed::Joint(10.0))
+        .await
+        .expect("Failed to move robot");
+
+    let Position::Pulse(position) = client.read_position(1).await.expect("Failed to read position")
+    else {
+        unreachable!("Expected pulse position");
+    };
+    assert_eq!(position.joints, target);
+});
+
+test_with_logging!(test_workspace_limits, {
+    let mut server = MockServerManager::new();
+    server
+        .start_with_builder(|builder| builder.with_position(cartesian_start()))
+        .await
+        .expect("Failed to start mock server");
+
+    let limits = WorkspaceLimits::new()
+        .with_axis_range(0, AxisRange::new(-5000, 5000))
+        .with_cartesian_bounds(CartesianBounds::new([0.0, -100.0, 295.0], [500.0, 100.0, 305.0]));
+    let client = HsesClient::new_with_config(ClientConfig {
+        port: moto_hses_proto::ROBOT_CONTROL_PORT,
+        workspace_limits: Some(limits),
+        ..ClientConfig::default()
+    })
+    .await
+    .expect("Failed to create client");
+
+    // Targets outside the limits are rejected before anything is sent
+    let result = client.move_pulse(1, vec![6000, 0, 0, 0, 0, 0], MoveSpeed::Joint(10.0)).await;
+    assert!(
+        matches!(result, Err(ClientError::SafetyViolation(_))),
+        "Unexpected result: {result:?}"
+    );
+    let result = client
+        .move_incremental(
+            1,
+            CartesianDelta::translation(0.0, 0.0, 8.0),
+            &IncrementalMoveOptions::default(),
+        )
+        .await;
+    assert!(
+        matches!(result, Err(ClientError::SafetyViolation(_))),
+        "Unexpected result: {result:?}"
+    );
+    let result = client
+        .move_incremental(
+            1,
+            CartesianDelta::translation(0.0, 0.0, 1.0),
+            &IncrementalMoveOptions { frame: MoveFrame::User, ..IncrementalMoveOptions::default() },
+        )
+        .await;
+    assert!(
+        matches!(result, Err(ClientError::SafetyViolation(_))),
+        "Unexpected result: {result:?}"
+    );
+
+    // Moves within the limits go through
+    client
+        .move_incremental(
+            1,
+            CartesianDelta::translation(0.0, 0.0, 4.0),
+            &IncrementalMoveOptions::default(),
+        )
+        .await
+        .expect("Failed to move robot");
+
+    // A jog stops before leaving the limits (1 mm per increment, 1 mm left)
+    let deadman = JogDeadman::new(Duration::from_secs(5));
+    let report = client
+        .jog(1, MoveFrame::Base, JogDirection::positive(JogAxis::Z), 10.0, &deadman)
+        .await
+        .expect("Failed to jog");
+    assert_eq!(report.stop_reason, JogStopReason::WorkspaceLimit);
+    assert_eq!(report.steps, 1);
+
+    let Position::Cartesian(position) =
+        client.read_position(101).await.expect("Failed to read position")
+    else {
+        unreachable!("Expected Cartesian position");
+    };
+    assert!((position.z - 305.0).abs() < 0.01, "z: {}", position.z);
+});
+
+fn cartesian_point(x: f32, y: f32, z: f32) -> Position {
+    let Position::Cartesian(mut point) = cartesian_start() else {
+        unreachable!("Expected Cartesian position");
+    };
+    point.x = x;
+    point.y = y;
+    point.z = z;
+    Position::Cartesian(point)
+}
+
+test_with_logging!(test_play_trajectory, {
+    let mut server = MockServerManager::new();
+    server
+        .start_with_builder(|builder| {
+            builder
+                .with_position(cartesian_start())
+                .with_motion_simulation(MotionSimulation::default())
+        })
+        .await
+        .expect("Failed to start mock server");
+
+    let client = create_test_client().await.expect("Failed to create client");
+
+    // 20 mm per segment at 200 mm/s
+    let points = [
+        cartesian_point(420.0, 0.0, 300.0),
+        cartesian_point(420.0, 20.0, 300.0),
+        cartesian_point(420.0, 20.0, 280.0),
+    ];
+    let options =
+        TrajectoryOptions { speed: MoveSpeed::Linear(200.0), ..TrajectoryOptions::default() };
+    let control = TrajectoryControl::new();
+    let report =
+        client.play_trajectory(1, &points, &options, &control).await.expect("Failed to play");
+    assert_eq!(report.completed, 3);
+    assert!(!report.cancelled);
+    assert!(report.duration >= Duration::from_millis(250), "Unexpected report: {report:?}");
+    assert_eq!(control.progress().completed, 3);
+    assert_eq!(control.progress().total, 3);
+
+    let Position::Cartesian(position) =
+        client.read_position(101).await.expect("Failed to read position")
+    else {
+        unreachable!("Expected Cartesian position");
+    };
+    assert!((position.z - 280.0).abs() < 0.01, "z: {}", position.z);
+    assert!((position.y - 20.0).abs() < 0.01, "y: {}", position.y);
+
+    // Pulse points
+    let points = [
+        Position::Pulse(moto_hses_proto::PulsePosition::new(vec![1000, 0, 0, 0, 0, 0, 0, 0])),
+        Position::Pulse(moto_hses_proto::PulsePosition::new(vec![1000, 2000, 0, 0, 0, 0, 0, 0])),
+    ];
+    let report = client
+        .play_trajectory(1, &points, &TrajectoryOptions::default(), &control)
+        .await
+        .expect("Failed to play");
+    assert_eq!(report.completed, 2);
+    let Position::Pulse(position) = client.read_position(1).await.expect("Failed to read position")
+    else {
+        unreachable!("Expected pulse position");
+    };
+    assert_eq!(position.joints, vec![1000, 2000, 0, 0, 0, 0, 0, 0]);
+});
+
+test_with_logging!(test_play_trajectory_pause_and_cancel, {
+    let mut server = MockServerManager::new();
+    server
+        .start_with_builder(|builder| {
+            builder
+                .with_position(cartesian_start())
+                .with_motion_simulation(MotionSimulation::default())
+        })
+        .await
+        .expect("Failed to start mock server");
+
+    let client = create_test_client().await.expect("Failed to create client");
+
+    let points: Vec<Position> = (1..=10u8)
+        .map(|i| cartesian_point(f32::from(i).mul_add(10.0, 400.0), 0.0, 300.0))
+        .collect();
+    let options = TrajectoryOptions {
+        speed: MoveSpeed::Linear(200.0),
+        blend: Some(1.0),
+        ..TrajectoryOptions::default()
+    };
+    let control = TrajectoryControl::new();
+    control.pause();
+    let operator = control.clone();
+    let (report, ()) =
+        tokio::join!(client.play_trajectory(1, &points, &options, &control), async move {
+            // Nothing moves while paused
+            tokio::time::sleep(Duration::from_millis(200)).await;
+            assert_eq!(operator.progress().completed, 0);
+            operator.resume();
+
+            while operator.progress().completed < 2 {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+            operator.cancel();
+        });
+    let report = report.expect("Failed to play");
+    assert!(report.cancelled);
+    assert_eq!(report.total, 10);
+    assert!((2..10).contains(&report.completed), "Unexpected report: {report:?}");
+    assert_eq!(control.progress().completed, report.completed);
+});
+
+test_with_logging!(test_play_trajectory_errors, {
+    let mut server = MockServerManager::new();
+    server
+        .start_with_builder(|builder| builder.with_position(cartesian_start()))
+        .await
+        .expect("Failed to start mock server");
+
+    let limits = WorkspaceLimits::new()
+        .with_cartesian_bounds(CartesianBounds::new([0.0, -100.0, 0.0], [500.0, 100.0, 500.0]));
+    let client = HsesClient::new_with_config(ClientConfig {
+        port: moto_hses_proto::ROBOT_CONTROL_PORT,
+        workspace_limits: Some(limits),
+        ..ClientConfig::default()
+    })
+    .await
+    .expect("Failed to create client");
+
+    // A point outside the limits rejects the whole trajectory
+    let control = TrajectoryControl::new();
+    let points = [cartesian_point(450.0, 0.0, 300.0), cartesian_point(600.0, 0.0, 300.0)];
+    let result = client.play_trajectory(1, &points, &TrajectoryOptions::default(), &control).await;
+    assert!(
+        matches!(result, Err(ClientError::SafetyViolation(_))),
+        "Unexpected result: {result:?}"
+    );
+    let Position::Cartesian(position) =
+        client.read_position(101).await.expect("Failed to read position")
+    else {
+        unreachable!("Expected Cartesian position");
+    };
+    assert!((position.x - 400.0).abs() < 0.01, "x: {}", position.x);
+});
+
+test_with_logging!(test_read_snapshot, {
+    let mut server = MockServerManager::new();
+    server
+        .start_with_builder(|builder| {
+            builder
+                .with_position(cartesian_start())
+                .with_pulse_position(1, PulsePosition::new(vec![1, 2, 3, 4, 5, 6, 0, 0]))
+        })
+        .await
+        .expect("Failed to start mock server");
+
+    let client = create_test_client().await.expect("Failed to create client");
+
+    let snapshot = client.read_snapshot(&[1, 101]).await.expect("Failed to read snapshot");
+    assert_eq!(
+        snapshot.positions,
+        vec![Position::Pulse(PulsePosition::new(vec![1, 2, 3, 4, 5, 6, 0, 0])), cartesian_start()]
+    );
+    assert_eq!(snapshot.status, client.read_status().await.expect("Failed to read status"));
+    assert_eq!(snapshot.executing_job.job_name, "TEST.JOB");
+    // The mock starts with the four current alarms set
+    assert_eq!(snapshot.active_alarm_count, 4);
+    assert!(snapshot.skew < Duration::from_secs(1), "skew: {:?}", snapshot.skew);
+});
+
+#[cfg(feature = "telemetry")]
+test_with_logging!(test_telemetry_logger, {
+    use moto_hses_client::{SharedHsesClient, TelemetryLogger, TelemetryOptions};
+
+    let mut server = MockServerManager::new();
+    server.start().await.expect("Failed to start mock server");
+
+    let client =
+        SharedHsesClient::new(create_test_client().await.expect("Failed to create client"));
+    let directory =
+        std::env::temp_dir().join(format!("moto-hses-telemetry-{}", std::process::id()));
+    let options = TelemetryOptions {
+        interval: Duration::from_millis(20),
+        rows_per_file: 3,
+        ..TelemetryOptions::new(&directory)
+    };
+    let logger = TelemetryLogger::start(client, options).expect("Failed to start logger");
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    let stats = logger.stop().await.expect("Failed to stop logger");
+
+    assert!(stats.recorded >= 3, "{stats:?}");
+    assert_eq!(stats.dropped + stats.read_errors, 0, "{stats:?}");
+    let files = std::fs::read_dir(&directory).expect("Failed to list files").count();
+    assert!(files >= 2, "rows should be spread over several files");
+    std::fs::remove_dir_all(&directory).expect("Failed to remove files");
+});
+
+test_with_logging!(test_expected_axes, {
+    let _server =
+        create_position_test_server().await.expect("Failed to start position test server");
+
+    let client = HsesClient::new_with_config(ClientConfig {
+        port: moto_hses_proto::ROBOT_CONTROL_PORT,
+        expected_axes: Some(6),
+        ..ClientConfig::default()
+    })
+    .await
+    .expect("Failed to create client");
+
+    // Pulse positions are resized to the expected axes
+    let Position::Pulse(pulse) = client.read_position(1).await.expect("Failed to read position")
+    else {
+        unreachable!("Expected pulse position type");
+    };
+    assert_eq!(pulse.joints, vec![100, 200, 300, 400, 500, 600]);
+    let snapshot = client.read_snapshot(&[1]).await.expect("Failed to read snapshot");
+    assert_eq!(snapshot.positions[0], Position::Pulse(pulse));
+
+    // Pulse moves must give every axis
+    let result = client.move_pulse(1, vec![0; 7], MoveSpeed::Joint(10.0)).await;
+    assert!(
+        matches!(
+            result,
+            Err(ClientError::ProtocolError(moto_hses_proto::ProtocolError::PositionError(_)))
+        ),
+        "{result:?}"
+    );
+    let points = [Position::Pulse(PulsePosition::new(vec![0; 5]))];
+    let result = client
+        .play_trajectory(1, &points, &TrajectoryOptions::default(), &TrajectoryControl::new())
+        .await;
+    assert!(result.is_err(), "{result:?}");
+    client.move_pulse(1, vec![0; 6], MoveSpeed::Joint(10.0)).await.expect("Failed to move");
+});
@@ -0,0 +1,81 @@
+#![allow(clippy::expect_used)]
+// Integration tests run against every combination of text encoding and mock preset
+//
+// Each scenario runs once per profile of `common::matrix::PROFILES`; see there for
+// adding a scenario.
+
+use crate::common::matrix::{ALARM_NAME, Profile};
+use crate::common::mock_server_setup::MockServerManager;
+use crate::matrix_test;
+use moto_hses_proto::encoding_utils::{decode_string_with_fallback, encode_string};
+use moto_hses_proto::{AlarmAttribute, FILE_CONTROL_PORT, ROBOT_CONTROL_PORT};
+
+async fn variables(profile: &Profile, _server: &MockServerManager) {
+    let client = profile.client(ROBOT_CONTROL_PORT).await;
+
+    client.write_i32(10, -123_456).await.expect("Failed to write D variable");
+    assert_eq!(
+        client.read_i32(10).await.expect("Failed to read D variable"),
+        -123_456,
+        "{profile}"
+    );
+    client.write_f32(10, 2.5).await.expect("Failed to write R variable");
+    assert!(
+        (client.read_f32(10).await.expect("Failed to read R variable") - 2.5).abs() < f32::EPSILON
+    );
+
+    // 15 bytes in UTF-8, 10 in Shift-JIS: within the 16-byte S variable either way
+    let text = "溶接ライン";
+    client.write_string_variable(5, text.to_string()).await.expect("Failed to write S variable");
+    let value = client.read_string_variable(5).await.expect("Failed to read S variable");
+    assert_eq!(value, text, "{profile}");
+    client
+        .write_multiple_string_variables(6, vec!["搬送".to_string(), "PICK".to_string()])
+        .await
+        .expect("Failed to write S variables");
+    let values = client.read_multiple_strings(6, 2).await.expect("Failed to read S variables");
+    assert_eq!(values, ["搬送", "PICK"], "{profile}");
+}
+
+async fn alarms_and_status(profile: &Profile, _server: &MockServerManager) {
+    let client = profile.client(ROBOT_CONTROL_PORT).await;
+    let config = profile.config();
+
+    let alarm = client.read_alarm_data(1, AlarmAttribute::All).await.expect("Failed to read alarm");
+    assert_eq!((alarm.code, alarm.name.as_str()), (1010, ALARM_NAME), "{profile}");
+    let name = client.read_alarm_data(1, AlarmAttribute::Name).await.expect("Failed to read name");
+    assert_eq!(name.name, ALARM_NAME, "{profile}");
+
+    let job = client.read_executing_job_info(1, 0).await.expect("Failed to read job info");
+    assert_eq!(Some(job), config.executing_job, "{profile}");
+    let status = client.read_status().await.expect("Failed to read status");
+    assert_eq!(status, config.default_status, "{profile}");
+    let position = client.read_position(1).await.expect("Failed to read position");
+    assert_eq!(position, config.default_position, "{profile}");
+}
+
+async fn files(profile: &Profile, _server: &MockServerManager) {
+    let client = profile.client(FILE_CONTROL_PORT).await;
+
+    // Sample files of the preset
+    for (name, content) in profile.config().files {
+        let received = client.receive_file(&name).await.expect("Failed to receive sample file");
+        assert_eq!(received, decode_string_with_fallback(&content, profile.encoding), "{profile}");
+    }
+
+    // 8.3 name valid on every model
+    let content = "/JOB\r\n//NAME MATRIX\r\n//INST\r\n'溶接開始\r\nNOP\r\nEND\r\n";
+    client
+        .send_file("MATRIX.JBI", &encode_string(content, profile.encoding))
+        .await
+        .expect("Failed to send file");
+    let files = client.read_file_list("*.JBI").await.expect("Failed to read file list");
+    assert!(files.iter().any(|file| file == "MATRIX.JBI"), "{profile}: {files:?}");
+    let received = client.receive_file("MATRIX.JBI").await.expect("Failed to receive file");
+    assert_eq!(received, content, "{profile}");
+    client.delete_file("MATRIX.JBI").await.expect("Failed to delete file");
+}
+
+matrix_test!(test_variables_matrix, variables);
+matrix_test!(test_alarms_and_status_matrix, alarms_and_status);
+matrix_test!(test_files_matrix, files);
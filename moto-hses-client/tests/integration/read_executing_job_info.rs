@@ -5,6 +5,8 @@ use crate::common::{
     mock_server_setup::create_job_info_test_server, test_utils::create_test_client,
 };
 use crate::test_with_logging;
+use moto_hses_client::{AttributeStrategy, ClientError};
+use moto_hses_proto::{JobInfoAttribute, ProtocolError, ReadExecutingJobInfo};
 
 test_with_logging!(test_read_complete_job_info, {
     let _server =
@@ -89,3 +91,57 @@ test_with_logging!(test_read_task_types, {
         );
     }
 });
+
+test_with_logging!(test_invalid_attribute_fails_locally, {
+    let server = create_job_info_test_server().await.expect("Failed to start job info test server");
+
+    let client = create_test_client().await.expect("Failed to create client");
+
+    let result = client.read_executing_job_info(1, 5).await;
+    assert!(
+        matches!(result, Err(ClientError::ProtocolError(ProtocolError::InvalidAttribute))),
+        "Attribute 5 should be rejected: {result:?}"
+    );
+    assert_eq!(server.mock().robot_request_count().await, 0, "No request should be sent");
+});
+
+test_with_logging!(test_read_attributes_strategy, {
+    let server = create_job_info_test_server().await.expect("Failed to start job info test server");
+
+    let client = create_test_client().await.expect("Failed to create client");
+    let command = ReadExecutingJobInfo::new(1, JobInfoAttribute::All);
+
+    // Several attributes are read with one Get_Attribute_All request
+    let info = client
+        .read_attributes(&command, &[2, 3], AttributeStrategy::Auto)
+        .await
+        .expect("Failed to read attributes");
+    assert_eq!((info.line_number, info.step_number), (2, 1));
+    assert_eq!(server.mock().robot_request_count().await, 1);
+
+    // Single reads are merged, leaving the other attributes empty
+    let info = client
+        .read_attributes(&command, &[2, 3], AttributeStrategy::Single)
+        .await
+        .expect("Failed to read attributes");
+    assert_eq!((info.job_name.as_str(), info.line_number, info.step_number), ("", 2, 1));
+    assert_eq!(server.mock().robot_request_count().await, 3);
+
+    let info = client
+        .read_attributes(&command, &[4], AttributeStrategy::Auto)
+        .await
+        .expect("Failed to read attribute");
+    assert_eq!((info.job_name.as_str(), info.speed_override_value), ("", 100));
+    let info = client
+        .read_attributes(&command, &[], AttributeStrategy::Single)
+        .await
+        .expect("Failed to read all attributes");
+    assert_eq!(info.job_name, "TEST_JOB");
+    assert_eq!(server.mock().robot_request_count().await, 8);
+
+    let result = client.read_attributes(&command, &[5], AttributeStrategy::Single).await;
+    assert!(
+        matches!(result, Err(ClientError::ProtocolError(ProtocolError::InvalidAttribute))),
+        "{result:?}"
+    );
+});
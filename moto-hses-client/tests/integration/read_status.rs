@@ -8,6 +8,10 @@ use crate::common::{
     test_utils::create_test_client,
 };
 use crate::test_with_logging;
+use futures::StreamExt;
+use moto_hses_client::{ClientError, StatusBit, read_status_bits};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 test_with_logging!(test_read_complete_status, {
     let _server = create_status_test_server().await.expect("Failed to start status test server");
@@ -227,3 +231,68 @@ test_with_logging!(test_read_status_data2_all_false, {
     assert!(!data2.error);
     assert!(!data2.servo_on);
 });
+
+test_with_logging!(test_read_status_with_meta, {
+    let _server = create_status_test_server().await.expect("Failed to start status test server");
+
+    let client = create_test_client().await.expect("Failed to create client");
+
+    let recorded = Arc::new(Mutex::new(Vec::new()));
+    let hook_recorded = Arc::clone(&recorded);
+    client.set_response_hook(move |meta| {
+        hook_recorded.lock().expect("Hook lock poisoned").push(*meta);
+    });
+
+    let (status, meta) = client.read_status_with_meta().await.expect("Failed to read status");
+    assert!(status.is_running());
+    assert_eq!(meta.command, 0x72);
+    assert_eq!(meta.attempts, 1);
+    assert!(meta.rtt > Duration::ZERO);
+
+    // Every request is reported to the hook, each with its own request ID
+    client.read_status_data1().await.expect("Failed to read status data 1");
+    client.clear_response_hook();
+    client.read_status_data2().await.expect("Failed to read status data 2");
+
+    let recorded = recorded.lock().expect("Hook lock poisoned").clone();
+    assert_eq!(recorded.len(), 2);
+    assert_eq!(recorded[0], meta);
+    assert_eq!(recorded[1].request_id, meta.request_id.wrapping_add(1));
+});
+
+test_with_logging!(test_status_bit_reads, {
+    let server = create_status_test_server().await.expect("Failed to start status test server");
+
+    let client = create_test_client().await.expect("Failed to create client");
+
+    // Each read asks for the status data word holding the bits, or both if needed
+    server.mock().start_transcript().await;
+    assert!(client.read_status_bit(StatusBit::Running).await.expect("Failed to read bit"));
+    assert!(client.read_status_bit(StatusBit::ServoOn).await.expect("Failed to read bit"));
+    let bits = read_status_bits(&client, &[StatusBit::Teach, StatusBit::Alarm])
+        .await
+        .expect("Failed to read bits");
+    assert_eq!(bits, [false, true]);
+    let attributes: Vec<u8> =
+        server.mock().take_transcript().await.iter().map(|request| request.attribute).collect();
+    assert_eq!(attributes, [1, 2, 0]);
+
+    let interval = Duration::from_millis(10);
+    client
+        .wait_for_status(StatusBit::Running, true, interval, Duration::from_millis(100))
+        .await
+        .expect("Failed to wait for running");
+    let result =
+        client.wait_for_status(StatusBit::Teach, true, interval, Duration::from_millis(50)).await;
+    assert!(matches!(result, Err(ClientError::TimeoutError(_))), "{result:?}");
+
+    // The first read of a watch reports every bit
+    let changes = client.watch_status(vec![StatusBit::Running, StatusBit::Play], interval);
+    let changes: Vec<_> = changes.take(2).collect().await;
+    let changes: Vec<(StatusBit, Option<bool>, bool)> = changes
+        .into_iter()
+        .map(|change| change.expect("Failed to watch status"))
+        .map(|change| (change.bit, change.old, change.new))
+        .collect();
+    assert_eq!(changes, [(StatusBit::Running, None, true), (StatusBit::Play, None, true)]);
+});
@@ -6,6 +6,8 @@ use crate::common::{
     test_utils::{create_test_client, wait_for_operation},
 };
 use crate::test_with_logging;
+use moto_hses_client::ClientError;
+use moto_hses_proto::ProtocolError;
 
 test_with_logging!(test_register_read_operations, {
     let _server =
@@ -116,6 +118,25 @@ test_with_logging!(test_register_error_handling, {
     );
 });
 
+test_with_logging!(test_write_read_only_register_is_rejected, {
+    let server = create_register_test_server().await.expect("Failed to start register test server");
+
+    let client = create_test_client().await.expect("Failed to create client");
+
+    // Registers 560-999 can be read but not written
+    let initial = client.read_register(560).await.expect("Failed to read register 560");
+    match client.write_register(560, initial.wrapping_add(1)).await {
+        Err(ClientError::ProtocolError(ProtocolError::ServerError(message))) => {
+            assert!(message.contains("0x28"), "Unexpected status: {message}");
+        }
+        result => unreachable!("Write to register 560 should be rejected: {result:?}"),
+    }
+    server.mock().assert_register_eq(560, initial).await;
+
+    client.write_register(559, 42).await.expect("Failed to write register 559");
+    server.mock().assert_register_eq(559, 42).await;
+});
+
 test_with_logging!(test_read_multiple_registers, {
     let _server =
         create_register_test_server().await.expect("Failed to start register test server");
@@ -0,0 +1,233 @@
+#![allow(clippy::expect_used)]
+// Integration tests for retries, using faults injected into the mock server responses
+
+use crate::common::mock_server_setup::MockServerManager;
+use crate::test_with_logging;
+use moto_hses_client::{
+    AdaptiveTimeout, ClientConfig, ClientError, HsesClient, LinkStats, ResponseMeta,
+};
+use moto_hses_mock::ResponseFault;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+const ATTEMPT_TIMEOUT: Duration = Duration::from_millis(150);
+
+async fn create_retry_client(retry_count: u32) -> HsesClient {
+    let config = ClientConfig {
+        timeout: ATTEMPT_TIMEOUT,
+        retry_count,
+        retry_delay: Duration::from_millis(20),
+        ..ClientConfig::default()
+    };
+    HsesClient::new_with_config(config).await.expect("Failed to create client")
+}
+
+// Collect the metadata of every successful request
+fn record_responses(client: &HsesClient) -> Arc<Mutex<Vec<ResponseMeta>>> {
+    let responses = Arc::new(Mutex::new(Vec::new()));
+    let recorded = Arc::clone(&responses);
+    client.set_response_hook(move |meta| {
+        recorded.lock().expect("Response log poisoned").push(*meta);
+    });
+    responses
+}
+
+test_with_logging!(test_retry_after_dropped_response, {
+    let mut server = MockServerManager::new();
+    server.start().await.expect("Failed to start mock server");
+    let client = create_retry_client(3).await;
+    let responses = record_responses(&client);
+
+    server.mock().inject_fault(ResponseFault::Drop).await;
+    server.mock().inject_fault(ResponseFault::Drop).await;
+    let value = client.read_register(1).await.expect("Read should succeed after retries");
+    assert_eq!(value, 100);
+
+    let responses = responses.lock().expect("Response log poisoned").clone();
+    assert_eq!(responses.len(), 1);
+    assert_eq!(responses[0].attempts, 3);
+    assert_eq!(server.mock().robot_request_count().await, 3);
+
+    // Each attempt is a new request with its own request ID
+    let metrics = server.mock().metrics().await;
+    assert_eq!(metrics.robot_requests(0x79), 3);
+    assert_eq!(metrics.dropped_responses, 2);
+    assert_eq!(metrics.duplicate_request_ids, 0);
+});
+
+test_with_logging!(test_retries_exhausted, {
+    let mut server = MockServerManager::new();
+    server.start().await.expect("Failed to start mock server");
+    let client = create_retry_client(2).await;
+    let responses = record_responses(&client);
+
+    for _ in 0..3 {
+        server.mock().inject_fault(ResponseFault::Drop).await;
+    }
+    match client.read_status().await {
+        Err(ClientError::TimeoutError(message)) => {
+            log::debug!("✓ Read correctly timed out: {message}");
+        }
+        result => unreachable!("Read should time out: {:?}", result.err()),
+    }
+    assert!(responses.lock().expect("Response log poisoned").is_empty());
+    assert_eq!(server.mock().robot_request_count().await, 3, "Initial attempt and 2 retries");
+
+    // The next request is answered at the first attempt
+    let (_, meta) = client.read_status_with_meta().await.expect("Failed to read status");
+    assert_eq!(meta.attempts, 1);
+});
+
+test_with_logging!(test_duplicate_response_is_ignored, {
+    let mut server = MockServerManager::new();
+    server.start().await.expect("Failed to start mock server");
+    let client = create_retry_client(3).await;
+
+    server.mock().inject_fault(ResponseFault::Duplicate).await;
+    let (_, meta) = client.read_status_with_meta().await.expect("Failed to read status");
+    assert_eq!(meta.attempts, 1);
+
+    // The second copy of the status response must not be taken for the register value
+    client.write_register(3, 1234).await.expect("Failed to write register");
+    let value = client.read_register(3).await.expect("Failed to read register");
+    assert_eq!(value, 1234);
+    server.mock().assert_register_eq(3, 1234).await;
+    assert_eq!(server.mock().robot_request_count().await, 3);
+});
+
+test_with_logging!(test_late_response_after_retry, {
+    let mut server = MockServerManager::new();
+    server.start().await.expect("Failed to start mock server");
+    let client = create_retry_client(3).await;
+
+    // The first response arrives after the client gave up on the attempt, while the
+    // retry is waiting for its own response
+    server
+        .mock()
+        .inject_fault(ResponseFault::Delay(ATTEMPT_TIMEOUT + Duration::from_millis(50)))
+        .await;
+    let value = client.read_register(2).await.expect("Read should succeed after retry");
+    assert_eq!(value, 200);
+
+    // The late response waits in the socket buffer and must be skipped by the next request
+    tokio::time::sleep(ATTEMPT_TIMEOUT).await;
+    let value = client.read_register(1).await.expect("Failed to read register");
+    assert_eq!(value, 100);
+    let (_, meta) = client.read_status_with_meta().await.expect("Failed to read status");
+    assert_eq!(meta.attempts, 1);
+    assert_eq!(server.mock().robot_request_count().await, 4);
+});
+
+test_with_logging!(test_pipelined_responses_out_of_order, {
+    let mut server = MockServerManager::new();
+    server.start().await.expect("Failed to start mock server");
+    let client = create_retry_client(3).await;
+    let responses = record_responses(&client);
+
+    // The status response overtaken by the later responses of the snapshot
+    server.mock().inject_fault(ResponseFault::Delay(Duration::from_millis(50))).await;
+    let snapshot = client.read_snapshot(&[1]).await.expect("Failed to read snapshot");
+    let status = client.read_status().await.expect("Failed to read status");
+    assert_eq!(snapshot.status, status);
+    assert_eq!(snapshot.executing_job.job_name, "TEST.JOB");
+
+    let responses = responses.lock().expect("Response log poisoned").clone();
+    assert!(responses.iter().all(|meta| meta.attempts == 1), "{responses:?}");
+});
+
+test_with_logging!(test_operation_deadline_stops_retries, {
+    let mut server = MockServerManager::new();
+    server.start().await.expect("Failed to start mock server");
+    let config = ClientConfig {
+        timeout: ATTEMPT_TIMEOUT,
+        retry_count: 10,
+        retry_delay: Duration::from_millis(20),
+        operation_deadline: Some(Duration::from_millis(400)),
+        ..ClientConfig::default()
+    };
+    let client = HsesClient::new_with_config(config).await.expect("Failed to create client");
+
+    for _ in 0..10 {
+        server.mock().inject_fault(ResponseFault::Drop).await;
+    }
+    assert!(matches!(client.read_status().await, Err(ClientError::TimeoutError(_))));
+    // 150 ms attempts with 20 ms delays fit 3 attempts into 400 ms
+    assert_eq!(server.mock().robot_request_count().await, 3);
+});
+
+test_with_logging!(test_link_stats, {
+    let mut server = MockServerManager::new();
+    server.start().await.expect("Failed to start mock server");
+    let client = create_retry_client(1).await;
+
+    for _ in 0..5 {
+        client.read_status().await.expect("Failed to read status");
+    }
+    server.mock().inject_fault(ResponseFault::Drop).await;
+    client.read_register(1).await.expect("Read should succeed after a retry");
+
+    let stats = client.link_stats();
+    assert_eq!((stats.overall.samples, stats.overall.timeouts), (6, 1));
+    assert_eq!(stats.commands[&0x72].samples, 5);
+    assert_eq!((stats.commands[&0x79].samples, stats.commands[&0x79].timeouts), (1, 1));
+    assert!(stats.overall.min <= stats.overall.p50 && stats.overall.p99 <= stats.overall.max);
+    assert!(stats.overall.max < ATTEMPT_TIMEOUT, "{stats:?}");
+
+    client.reset_link_stats();
+    assert_eq!(client.link_stats(), LinkStats::default());
+});
+
+test_with_logging!(test_adaptive_timeout, {
+    let mut server = MockServerManager::new();
+    server.start().await.expect("Failed to start mock server");
+    let adaptive = AdaptiveTimeout { min_samples: 4, ..AdaptiveTimeout::default() };
+    let config = ClientConfig {
+        timeout: Duration::from_secs(2),
+        retry_count: 1,
+        retry_delay: Duration::from_millis(20),
+        adaptive_timeout: Some(adaptive),
+        ..ClientConfig::default()
+    };
+    let client = HsesClient::new_with_config(config).await.expect("Failed to create client");
+    for _ in 0..4 {
+        client.read_status().await.expect("Failed to read status");
+    }
+
+    // The local mock answers within a millisecond, so the attempt waiting for the delayed
+    // response is retried after the minimum timeout instead of the configured 2 s
+    server.mock().inject_fault(ResponseFault::Delay(Duration::from_millis(500))).await;
+    let started = std::time::Instant::now();
+    let (_, meta) = client.read_status_with_meta().await.expect("Failed to read status");
+    assert_eq!(meta.attempts, 2);
+    assert!(started.elapsed() < Duration::from_millis(400), "{:?}", started.elapsed());
+    assert_eq!(client.link_stats().commands[&0x72].timeouts, 1);
+});
+
+test_with_logging!(test_job_start_not_resent_when_running, {
+    let mut server = MockServerManager::new();
+    server.start().await.expect("Failed to start mock server");
+    let client = create_retry_client(3).await;
+
+    // The job was started, only the response was lost
+    server.mock().inject_fault(ResponseFault::Drop).await;
+    client.start_job().await.expect("Start should be confirmed by the status");
+    assert_eq!(server.mock().metrics().await.robot_requests(0x86), 1);
+    assert!(client.read_status().await.expect("Failed to read status").is_running());
+});
+
+test_with_logging!(test_job_start_not_resent_without_status, {
+    let mut server = MockServerManager::new();
+    server.start().await.expect("Failed to start mock server");
+    let client = create_retry_client(1).await;
+
+    // The responses to the start and both status reads are lost
+    for _ in 0..3 {
+        server.mock().inject_fault(ResponseFault::Drop).await;
+    }
+    match client.start_job().await {
+        Err(ClientError::TimeoutError(message)) => assert!(message.contains("not resent")),
+        result => unreachable!("Start should fail: {result:?}"),
+    }
+    let metrics = server.mock().metrics().await;
+    assert_eq!((metrics.robot_requests(0x86), metrics.robot_requests(0x72)), (1, 2));
+});
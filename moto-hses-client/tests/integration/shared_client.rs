@@ -5,7 +5,8 @@ use crate::common::{
     mock_server_setup::create_status_test_server, test_utils::create_shared_test_client,
 };
 use crate::test_with_logging;
-use moto_hses_client::HsesClientOps;
+use moto_hses_client::{ClientError, HsesClientOps};
+use std::time::Duration;
 
 test_with_logging!(test_shared_client_concurrent_access, {
     let _server = create_status_test_server().await.expect("Failed to start status test server");
@@ -35,3 +36,30 @@ test_with_logging!(test_shared_client_concurrent_access, {
     assert_eq!(status.data1, data1);
     assert_eq!(status.data2, data2);
 });
+
+test_with_logging!(test_update_config_on_live_client, {
+    let _server = create_status_test_server().await.expect("Failed to start status test server");
+    let shared_client = create_shared_test_client().await.expect("Failed to create shared client");
+    let timeout = Duration::from_millis(750);
+    shared_client
+        .update_config(|config| {
+            config.timeout = timeout;
+            config.retry_count = 1;
+        })
+        .await
+        .expect("Failed to update config");
+    let config = shared_client.inner().lock().await.config();
+    assert_eq!((config.timeout, config.retry_count), (timeout, 1));
+
+    // The socket cannot be moved; the rejected update leaves the configuration as it was
+    let result = shared_client
+        .update_config(|config| {
+            config.port = 1;
+            config.timeout = Duration::from_millis(10);
+        })
+        .await;
+    assert!(matches!(result, Err(ClientError::SystemError(_))), "{result:?}");
+    assert_eq!(shared_client.inner().lock().await.config().timeout, timeout);
+
+    shared_client.read_status().await.expect("Failed to read status");
+});
@@ -0,0 +1,92 @@
+#![allow(clippy::expect_used)]
+// Integration tests for recording request transcripts and replaying them
+
+use crate::common::{mock_server_setup::MockServerManager, test_utils::create_test_client};
+use crate::test_with_logging;
+use moto_hses_client::{
+    ClientConfig, ClientError, DangerLevel, HsesClient, ReplayOptions, ReplayWrites, Transcript,
+};
+use moto_hses_proto::{ROBOT_CONTROL_PORT, Service};
+use std::sync::Arc;
+
+test_with_logging!(test_transcript_replay, {
+    let mut server = MockServerManager::new();
+    server.start().await.expect("Failed to start mock server");
+    let client = create_test_client().await.expect("Failed to create client");
+
+    client.start_transcript();
+    client.write_i32(5, 100).await.expect("Failed to write variable");
+    client.read_i32(5).await.expect("Failed to read variable");
+    client.read_snapshot(&[1]).await.expect("Failed to read snapshot");
+    let transcript = client.take_transcript();
+
+    // The write, the read and the pipelined status, job, position and four alarm code
+    // reads of the snapshot
+    assert_eq!(transcript.entries.len(), 9, "{transcript}");
+    assert_eq!(transcript.entries[0].service, Service::SetSingle);
+    assert_eq!(transcript.entries[1].response, Ok(100i32.to_le_bytes().to_vec()));
+    assert!(client.take_transcript().entries.is_empty(), "Recording stopped");
+
+    let path = std::env::temp_dir().join(format!("hses-transcript-{}.txt", std::process::id()));
+    transcript.save(&path).expect("Failed to save transcript");
+    let loaded = Transcript::load(&path).expect("Failed to load transcript");
+    std::fs::remove_file(&path).expect("Failed to remove transcript");
+    assert_eq!(loaded, transcript);
+
+    // Replaying against an unchanged controller reads the same values
+    let report = client
+        .replay_transcript(&transcript, &ReplayOptions::default())
+        .await
+        .expect("Failed to replay");
+    assert!(report.is_clean(), "{report}");
+    assert_eq!((report.replayed, report.skipped), (8, 1));
+
+    // A changed variable is reported, the write being skipped
+    client.write_i32(5, 7).await.expect("Failed to write variable");
+    let report = client
+        .replay_transcript(&transcript, &ReplayOptions::default())
+        .await
+        .expect("Failed to replay");
+    assert_eq!(report.divergences.len(), 1, "{report}");
+    assert_eq!(report.divergences[0].index, 1);
+    assert_eq!(report.divergences[0].replayed, Ok(7i32.to_le_bytes().to_vec()));
+    log::info!("✓ Replay report:\n{report}");
+
+    // Sent writes restore the recorded value before it is read
+    let options = ReplayOptions { writes: ReplayWrites::Send, ..ReplayOptions::default() };
+    let report = client.replay_transcript(&transcript, &options).await.expect("Failed to replay");
+    assert!(report.is_clean(), "{report}");
+    assert_eq!((report.replayed, report.skipped), (9, 0));
+
+    // The gate decides per write
+    let gate = Arc::new(|entry: &moto_hses_client::TranscriptEntry| entry.instance != 5);
+    let options = ReplayOptions { writes: ReplayWrites::Gate(gate), ..ReplayOptions::default() };
+    let report = client.replay_transcript(&transcript, &options).await.expect("Failed to replay");
+    assert_eq!(report.skipped, 1);
+});
+
+test_with_logging!(test_replay_writes_need_write_permission, {
+    let mut server = MockServerManager::new();
+    server.start().await.expect("Failed to start mock server");
+    let client = create_test_client().await.expect("Failed to create client");
+    client.start_transcript();
+    client.write_i32(5, 100).await.expect("Failed to write variable");
+    let transcript = client.take_transcript();
+
+    let config = ClientConfig {
+        port: ROBOT_CONTROL_PORT,
+        max_danger_level: DangerLevel::Read,
+        ..ClientConfig::default()
+    };
+    let reader = HsesClient::new_with_config(config).await.expect("Failed to create client");
+    let options = ReplayOptions { writes: ReplayWrites::Send, ..ReplayOptions::default() };
+    let result = reader.replay_transcript(&transcript, &options).await;
+    assert!(matches!(result, Err(ClientError::OperationNotPermitted(_))), "{result:?}");
+
+    // Skipping the writes needs no permission
+    let report = reader
+        .replay_transcript(&transcript, &ReplayOptions::default())
+        .await
+        .expect("Failed to replay");
+    assert_eq!((report.replayed, report.skipped), (0, 1));
+});
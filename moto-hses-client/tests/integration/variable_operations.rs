@@ -7,6 +7,19 @@ use crate::common::{
     test_utils::{create_test_client, wait_for_operation},
 };
 use crate::test_with_logging;
+use futures::StreamExt;
+use moto_hses_client::{
+    ClientConfig, ClientError, DangerLevel, HsesClient, Recipe, RequestLimits, TeachPoint,
+    TeachPointTable, VariableProfile, VariableRef as Var, VariableValue, WritePacing,
+};
+use moto_hses_mock::VariableRef::{B, D, I, R};
+use moto_hses_mock::VariableType;
+use moto_hses_proto::payload::position::{Configuration, ExtendedConfiguration};
+use moto_hses_proto::{
+    BasePosition, CartesianPosition, HsesPayload, Position, ProtocolError, PulsePosition,
+    ROBOT_CONTROL_PORT, Service, StationPosition, TextEncoding, VariableCommandId,
+};
+use std::time::{Duration, Instant};
 
 test_with_logging!(test_variable_read_operations, {
     let _server =
@@ -63,6 +76,24 @@ test_with_logging!(test_variable_write_operations, {
     assert_eq!(client.read_u8(31).await.expect("Failed to read u8 after write"), 255);
 });
 
+test_with_logging!(test_variable_write_operations_update_mock_state, {
+    let server = create_variable_test_server().await.expect("Failed to start variable test server");
+
+    let client = create_test_client().await.expect("Failed to create client");
+
+    client.write_i16(1, 42).await.expect("Failed to write i16 variable");
+    client.write_i32(11, 12345).await.expect("Failed to write i32 variable");
+    client.write_f32(21, std::f32::consts::PI).await.expect("Failed to write f32 variable");
+    client.write_u8(31, 255).await.expect("Failed to write u8 variable");
+
+    // Verify written values in the mock state
+    let mock = server.mock();
+    mock.assert_variable_eq(I(1), 42i16).await;
+    mock.assert_variable_eq(D(11), 12345i32).await;
+    mock.assert_variable_eq(R(21), std::f32::consts::PI).await;
+    mock.assert_variable_eq(B(31), 255u8).await;
+});
+
 test_with_logging!(test_string_variable_operations, {
     let _server =
         create_variable_test_server().await.expect("Failed to start variable test server");
@@ -91,6 +122,54 @@ test_with_logging!(test_string_variable_operations, {
     assert_eq!(read_string, test_string);
 });
 
+test_with_logging!(test_string_variable_length_and_encoding, {
+    let server = create_variable_test_server().await.expect("Failed to start variable test server");
+
+    let config = ClientConfig {
+        host: "127.0.0.1".to_string(),
+        port: ROBOT_CONTROL_PORT,
+        text_encoding: TextEncoding::ShiftJis,
+        ..ClientConfig::default()
+    };
+    let client =
+        HsesClient::new_with_config(config.clone()).await.expect("Failed to create client");
+
+    // "テ" is 2 bytes in Shift-JIS: it fits in bytes 15-16, but not in bytes 16-17
+    let fits = format!("{}テ", "A".repeat(14));
+    client.write_string(42, fits.clone()).await.expect("Failed to write 16-byte string");
+    assert_eq!(client.read_string(42).await.expect("Failed to read S042"), fits);
+
+    let straddling = format!("{}テ", "A".repeat(15));
+    let too_long = |result: Result<(), ClientError>| {
+        matches!(
+            result,
+            Err(ClientError::ProtocolError(ProtocolError::StringTooLong {
+                limit: 16,
+                encoded_len: 17
+            }))
+        )
+    };
+    assert!(too_long(client.write_string(42, straddling.clone()).await));
+    // The plural write rejects it the same way
+    assert!(too_long(client.write_multiple_strings(42, vec![straddling]).await));
+    assert_eq!(client.read_string(42).await.expect("Failed to read S042"), fits);
+
+    // Characters Shift-JIS cannot represent are rejected rather than replaced
+    let result = client.write_string(42, "🤖".to_string()).await;
+    assert!(matches!(result, Err(ClientError::ProtocolError(ProtocolError::Serialization(_)))));
+
+    // A variable whose last byte is the first half of a character does not decode cleanly
+    let mut cut = b"AAAAAAAAAAAAAAA".to_vec();
+    cut.push(0x83);
+    server.mock().state().write().await.set_variable(VariableType::String, 43, cut);
+    let lenient = client.read_string(43).await.expect("Failed to read S043");
+    assert!(lenient.starts_with("AAAAAAAAAAAAAAA"));
+    let strict = HsesClient::new_with_config(config).await.expect("Failed to create client");
+    strict.set_strict_encoding(true);
+    let result = strict.read_string(43).await;
+    assert!(matches!(result, Err(ClientError::EncodingError(_))), "got {:?}", result.err());
+});
+
 test_with_logging!(test_invalid_variable_handling, {
     let _server =
         create_variable_test_server().await.expect("Failed to start variable test server");
@@ -627,51 +706,447 @@ test_with_logging!(test_multiple_character_variables_validation, {
 
     // Test invalid count: 0
     let result = client.read_multiple_strings(0, 0).await;
-    assert!(result.is_err());
-    assert!(
-        result
-            .expect_err("Should be error")
-            .to_string()
-            .contains("Invalid count: 0 (must be 1-29)")
-    );
+    assert!(matches!(
+        result,
+        Err(ClientError::ProtocolError(ProtocolError::InvalidCount {
+            command: 0x306,
+            got: 0,
+            max: 29
+        }))
+    ));
 
     // Test invalid count: > 29
     let result = client.read_multiple_strings(0, 30).await;
-    assert!(result.is_err());
-    assert!(
-        result
-            .expect_err("Should be error")
-            .to_string()
-            .contains("Invalid count: 30 (must be 1-29)")
-    );
+    assert!(matches!(
+        result,
+        Err(ClientError::ProtocolError(ProtocolError::InvalidCount { got: 30, max: 29, .. }))
+    ));
 
     // Test invalid count for write: 0
     let result = client.write_multiple_strings(0, vec![]).await;
-    assert!(result.is_err());
-    assert!(
-        result
-            .expect_err("Should be error")
-            .to_string()
-            .contains("Invalid count: 0 (must be 1-29)")
-    );
+    assert!(matches!(
+        result,
+        Err(ClientError::ProtocolError(ProtocolError::InvalidCount { got: 0, max: 29, .. }))
+    ));
 
     // Test invalid count for write: > 29
     let large_values: Vec<String> = (0..30).map(|i| format!("Test{i}")).collect();
     let result = client.write_multiple_strings(0, large_values).await;
-    assert!(result.is_err());
-    assert!(
-        result
-            .expect_err("Should be error")
-            .to_string()
-            .contains("Invalid count: 30 (must be 1-29)")
-    );
+    assert!(matches!(
+        result,
+        Err(ClientError::ProtocolError(ProtocolError::InvalidCount { got: 30, max: 29, .. }))
+    ));
 
     // Test string too long when encoded
     let long_string = "This is a very long string that exceeds 16 bytes when encoded";
     let long_values = vec![long_string.to_string()];
     let result = client.write_multiple_strings(0, long_values).await;
-    assert!(result.is_err());
+    assert!(matches!(
+        result,
+        Err(ClientError::ProtocolError(ProtocolError::StringTooLong {
+            limit: 16,
+            encoded_len: 61
+        }))
+    ));
+});
+
+test_with_logging!(test_variable_snapshot_and_restore, {
+    let server = create_variable_test_server().await.expect("Failed to start variable test server");
+
+    let client = create_test_client().await.expect("Failed to create client");
+
+    // S variables need several plural requests; B variables an odd count
+    let profile = VariableProfile { byte: 99, ..VariableProfile::default() };
+    let snapshot = client.snapshot_variables(&profile).await.expect("Failed to take snapshot");
+    assert_eq!(snapshot.profile(), profile);
+    assert_eq!(snapshot.integers[0], 100);
+    assert_eq!(snapshot.doubles[10], 1000);
+    assert_eq!(snapshot.reals[20], 1.5);
+    assert_eq!(snapshot.bytes[30], 10);
+
+    // Recipe changeover: overwrite some variables, then restore the snapshot
+    let mut recipe = snapshot.clone();
+    recipe.bytes[98] = 7;
+    recipe.integers[99] = -5;
+    recipe.doubles[0] = 123_456;
+    recipe.reals[50] = 0.25;
+    recipe.strings[60] = "recipe".to_string();
+    client.restore_snapshot(&recipe).await.expect("Failed to restore recipe");
+    server.mock().assert_variable_eq(B(98), 7u8).await;
+    server.mock().assert_variable_eq(I(99), -5i16).await;
+    server.mock().assert_variable_eq(D(0), 123_456i32).await;
+    server.mock().assert_variable_eq(R(50), 0.25f32).await;
+    assert_eq!(client.read_string(60).await.expect("Failed to read S060"), "recipe");
+
+    client.restore_snapshot(&snapshot).await.expect("Failed to restore snapshot");
+    let restored = client.snapshot_variables(&profile).await.expect("Failed to take snapshot");
+    assert_eq!(restored, snapshot);
+});
+
+test_with_logging!(test_apply_and_detect_recipe, {
+    let _server =
+        create_variable_test_server().await.expect("Failed to start variable test server");
+
+    let client = create_test_client().await.expect("Failed to create client");
+
+    let part_a = Recipe::new("part-a")
+        .with_value(5, VariableValue::Byte(1))
+        .with_value(40, VariableValue::Double(25_000))
+        .with_value(3, VariableValue::Real(1.5))
+        .with_value(7, VariableValue::String("PART-A".to_string()));
+    let part_b = Recipe::new("part-b")
+        .with_value(5, VariableValue::Byte(2))
+        .with_value(40, VariableValue::Double(-8))
+        .with_value(9, VariableValue::Integer(12));
+    let recipes = [part_a, part_b];
+
+    assert_eq!(client.detect_recipe(&recipes).await.expect("Failed to detect recipe"), None);
+
+    for recipe in &recipes {
+        client.apply_recipe(recipe).await.expect("Failed to apply recipe");
+        let detected = client.detect_recipe(&recipes).await.expect("Failed to detect recipe");
+        assert_eq!(detected.map(|recipe| recipe.name.as_str()), Some(recipe.name.as_str()));
+    }
+    assert_eq!(client.read_i32(40).await.expect("Failed to read D040"), -8);
+});
+
+test_with_logging!(test_write_and_read_teach_points, {
+    let _server =
+        create_variable_test_server().await.expect("Failed to start variable test server");
+
+    let client = create_test_client().await.expect("Failed to create client");
+
+    let csv = "name,variable,type,tool,user_frame,configuration,extended_configuration,\
+               a1,a2,a3,a4,a5,a6,a7,a8\n\
+               HOME,P010,pulse,,,,,0,0,0,0,-90000,0,0,0\n\
+               PICK,P011,cartesian,1,2,4,0,350.5,-120.25,410,180,0,90,0,0\n";
+    let table = TeachPointTable::from_csv(csv).expect("Failed to parse teach points");
+    client.write_teach_points(&table).await.expect("Failed to write teach points");
+
+    let exported = client.read_teach_points(&[10, 11]).await.expect("Failed to read teach points");
+    let names: Vec<&str> = exported.points.iter().map(|point| point.name.as_str()).collect();
+    assert_eq!(names, ["P010", "P011"]);
+    for (point, read) in table.points.iter().zip(&exported.points) {
+        assert_eq!(read.position, point.position);
+    }
+    assert_eq!(
+        exported.to_csv().lines().nth(2),
+        Some("P011,P011,cartesian,1,2,4,0,350.5,-120.25,410,180,0,90,0,0")
+    );
+
+    // Pulse positions are padded to the 8 axes of a P variable
+    let short = TeachPointTable::new(vec![TeachPoint::new(
+        "SHORT",
+        12,
+        Position::Pulse(PulsePosition::new(vec![1, 2, 3, 4, 5, 6])),
+    )]);
+    client.write_teach_points(&short).await.expect("Failed to write teach points");
+    let read = client.read_teach_points(&[12]).await.expect("Failed to read teach points");
+    assert_eq!(
+        read.points[0].position,
+        Position::Pulse(PulsePosition::new(vec![1, 2, 3, 4, 5, 6, 0, 0]))
+    );
+
+    // P variables end at P127
+    let out_of_range =
+        TeachPointTable::new(vec![TeachPoint { index: 128, ..short.points[0].clone() }]);
+    assert!(client.write_teach_points(&out_of_range).await.is_err());
+});
+
+test_with_logging!(test_position_variable_operations, {
+    let _server =
+        create_variable_test_server().await.expect("Failed to start variable test server");
+    let client = create_test_client().await.expect("Failed to create client");
+
+    let cartesian = Position::Cartesian(CartesianPosition::new(
+        350.5,
+        -120.25,
+        410.0,
+        180.0,
+        0.0,
+        90.0,
+        1,
+        0,
+        Configuration::from_raw(4),
+        ExtendedConfiguration::from_raw(0),
+    ));
+    client.write_position_variable(20, cartesian.clone()).await.expect("Failed to write P020");
+    assert_eq!(client.read_position_variable(20).await.expect("Failed to read P020"), cartesian);
+
+    // Pulse positions are written with 8 axes and read back with the configured axes
+    let pulse = Position::Pulse(PulsePosition::new(vec![100, -200, 300, 0, 0, 600]));
+    client.write_position_variable(21, pulse.clone()).await.expect("Failed to write P021");
+    assert_eq!(
+        client.read_position_variable(21).await.expect("Failed to read P021"),
+        Position::Pulse(PulsePosition::new(vec![100, -200, 300, 0, 0, 600, 0, 0]))
+    );
+    let config = ClientConfig { expected_axes: Some(6), ..client.config() };
+    let six_axes = HsesClient::new_with_config(config).await.expect("Failed to create client");
+    assert_eq!(six_axes.read_position_variable(21).await.expect("Failed to read P021"), pulse);
+
+    let nine_axes = Position::Pulse(PulsePosition::new(vec![0; 9]));
+    let result = client.write_position_variable(22, nine_axes).await;
     assert!(
-        result.expect_err("Should be error").to_string().contains("exceeds 16 bytes when encoded")
+        matches!(result, Err(ClientError::ProtocolError(ProtocolError::PositionError(_)))),
+        "{result:?}"
     );
 });
+
+test_with_logging!(test_base_position_variable_operations, {
+    let _server =
+        create_variable_test_server().await.expect("Failed to start variable test server");
+    let client = create_test_client().await.expect("Failed to create client");
+
+    // Unset variables are a pulse position at the origin
+    assert_eq!(
+        client.read_base_position_variable(4).await.expect("Failed to read BP004"),
+        BasePosition::pulse([0; 8])
+    );
+
+    let base = BasePosition::base([1_250_000, -40_000, 0, 0, 0, 0, 0, 0]);
+    client.write_base_position_variable(5, base).await.expect("Failed to write BP005");
+    assert_eq!(client.read_base_position_variable(5).await.expect("Failed to read BP005"), base);
+
+    let pulse = BasePosition::pulse([1000, 2000, 0, 0, 0, 0, 0, 0]);
+    client.write_base_position_variable(6, pulse).await.expect("Failed to write BP006");
+    assert_eq!(client.read_base_position_variable(6).await.expect("Failed to read BP006"), pulse);
+
+    // BP variables end at BP127
+    assert!(client.read_base_position_variable(128).await.is_err());
+});
+
+test_with_logging!(test_station_variable_operations, {
+    let _server =
+        create_variable_test_server().await.expect("Failed to start variable test server");
+    let client = create_test_client().await.expect("Failed to create client");
+
+    // Unset variables are at the origin
+    assert_eq!(
+        client.read_station_variable(3).await.expect("Failed to read EX003"),
+        StationPosition::default()
+    );
+
+    let station = StationPosition::new([90_000, -45_000, 0, 0, 0, 0, 0, 0]);
+    client.write_station_variable(4, station).await.expect("Failed to write EX004");
+    assert_eq!(client.read_station_variable(4).await.expect("Failed to read EX004"), station);
+
+    // EX variables end at EX127
+    assert!(client.write_station_variable(128, station).await.is_err());
+});
+
+// Application payload type packing I variables n to n+3, read and written in one request
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct GripperSettings([i16; 4]);
+
+impl VariableCommandId for GripperSettings {
+    fn command_id() -> u16 {
+        0x303
+    }
+    fn read_service() -> Service {
+        Service::ReadPlural
+    }
+    fn write_service() -> Service {
+        Service::WritePlural
+    }
+    fn read_request() -> Vec<u8> {
+        4u32.to_le_bytes().to_vec()
+    }
+}
+
+impl HsesPayload for GripperSettings {
+    fn serialize(&self, _: TextEncoding) -> Result<Vec<u8>, ProtocolError> {
+        let mut data = 4u32.to_le_bytes().to_vec();
+        data.extend(self.0.iter().flat_map(|value| value.to_le_bytes()));
+        Ok(data)
+    }
+    fn deserialize(data: &[u8], _: TextEncoding) -> Result<Self, ProtocolError> {
+        if data.len() != 12 {
+            return Err(ProtocolError::UnexpectedLength { expected: 12, got: data.len() });
+        }
+        let mut values = [0; 4];
+        for (value, bytes) in values.iter_mut().zip(data[4..].chunks_exact(2)) {
+            *value = i16::from_le_bytes([bytes[0], bytes[1]]);
+        }
+        Ok(Self(values))
+    }
+}
+
+test_with_logging!(test_user_defined_variable_payload, {
+    let server = create_variable_test_server().await.expect("Failed to start variable test server");
+
+    let client = create_test_client().await.expect("Failed to create client");
+
+    let settings = GripperSettings([120, -30, 4500, 2]);
+    client.write_variable(50, settings.clone()).await.expect("Failed to write gripper settings");
+    server.mock().assert_variable_eq(I(50), 120i16).await;
+    server.mock().assert_variable_eq(I(53), 2i16).await;
+
+    let read = client.read_variable::<GripperSettings>(50).await.expect("Failed to read settings");
+    assert_eq!(read, settings);
+});
+
+test_with_logging!(test_watch_variables, {
+    let server = create_variable_test_server().await.expect("Failed to start variable test server");
+
+    let client = create_test_client().await.expect("Failed to create client");
+
+    let watched = vec![Var::B(30), Var::D(10), Var::R(20), Var::S(40)];
+    let values = client.read_variables(&watched).await.expect("Failed to read variables");
+    assert_eq!(
+        values,
+        [
+            VariableValue::Byte(10),
+            VariableValue::Double(1000),
+            VariableValue::Real(1.5),
+            VariableValue::String("Hello".to_string())
+        ]
+    );
+
+    let changes = client.watch_variables(watched, std::time::Duration::from_millis(20));
+    let mut changes = std::pin::pin!(changes);
+    let mut next_change = async || {
+        tokio::time::timeout(std::time::Duration::from_secs(2), changes.next())
+            .await
+            .expect("Timed out waiting for a change")
+            .expect("Watch ended")
+            .expect("Failed to read variables")
+    };
+
+    // The first read reports every variable
+    for (expected, value) in
+        [Var::B(30), Var::D(10), Var::R(20), Var::S(40)].into_iter().zip(values)
+    {
+        let change = next_change().await;
+        assert_eq!((change.variable, change.old, change.new), (expected, None, value));
+    }
+
+    // Later reads report only what changed
+    server.mock().state().write().await.set_variable(
+        VariableType::Double,
+        10,
+        5i32.to_le_bytes().to_vec(),
+    );
+    let change = next_change().await;
+    assert_eq!(change.variable, Var::D(10));
+    assert_eq!(change.old, Some(VariableValue::Double(1000)));
+    assert_eq!(change.new, VariableValue::Double(5));
+});
+
+test_with_logging!(test_write_pacing, {
+    let _server = create_variable_test_server().await.expect("Failed to start mock server");
+    let pacing = WritePacing { min_gap: Duration::from_millis(100), after_reads: false };
+    let config = ClientConfig {
+        port: ROBOT_CONTROL_PORT,
+        write_pacing: Some(pacing),
+        ..ClientConfig::default()
+    };
+    let client = HsesClient::new_with_config(config).await.expect("Failed to create client");
+
+    // The second and third writes wait for the gap
+    let started = Instant::now();
+    for index in 1..=3 {
+        client.write_i32(index, i32::from(index)).await.expect("Failed to write variable");
+    }
+    assert!(started.elapsed() >= Duration::from_millis(200), "{:?}", started.elapsed());
+    assert_eq!(client.read_i32(3).await.expect("Failed to read variable"), 3);
+
+    // Reads are not paced unless configured
+    let started = Instant::now();
+    client.read_i32(1).await.expect("Failed to read variable");
+    client.write_i32(1, 4).await.expect("Failed to write variable");
+    let unpaced = started.elapsed();
+    let pacing = WritePacing { after_reads: true, ..pacing };
+    let config = ClientConfig { write_pacing: Some(pacing), ..client.config() };
+    let client = HsesClient::new_with_config(config).await.expect("Failed to create client");
+    client.read_i32(1).await.expect("Failed to read variable");
+    let started = Instant::now();
+    client.write_i32(1, 5).await.expect("Failed to write variable");
+    assert!(started.elapsed() >= Duration::from_millis(90), "{:?}", started.elapsed());
+    log::info!("✓ Write after read: {unpaced:?} unpaced, {:?} paced", started.elapsed());
+});
+
+test_with_logging!(test_request_limits, {
+    let _server = create_variable_test_server().await.expect("Failed to start mock server");
+    let limits = RequestLimits { max_in_flight: 1, max_queued: 1 };
+    let config = ClientConfig {
+        port: ROBOT_CONTROL_PORT,
+        request_limits: Some(limits),
+        ..ClientConfig::default()
+    };
+    let client = HsesClient::new_with_config(config).await.expect("Failed to create client");
+
+    // One read is sent and one waits for it; the others are rejected without being sent
+    let reads = (1..=10).map(|index| client.read_i32(index));
+    let results = futures::future::join_all(reads).await;
+    let overloaded =
+        results.iter().filter(|result| matches!(result, Err(ClientError::Overloaded(_)))).count();
+    assert!(overloaded >= 1, "{results:?}");
+    assert!(
+        results.iter().all(|result| matches!(result, Ok(_) | Err(ClientError::Overloaded(_)))),
+        "{results:?}"
+    );
+    assert!(results[..2].iter().all(Result::is_ok), "{results:?}");
+
+    // The slots are free again afterwards, also for pipelined reads
+    let values = client
+        .read_variables(&[Var::D(1), Var::D(2), Var::D(3)])
+        .await
+        .expect("Failed to read variables");
+    assert_eq!(values.len(), 3);
+    client.read_i32(1).await.expect("Failed to read variable");
+});
+
+test_with_logging!(test_read_only_client, {
+    let _server = create_variable_test_server().await.expect("Failed to start mock server");
+    let config = ClientConfig {
+        port: ROBOT_CONTROL_PORT,
+        max_danger_level: DangerLevel::Read,
+        ..ClientConfig::default()
+    };
+    let client = HsesClient::new_with_config(config).await.expect("Failed to create client");
+    let before = client.read_i32(1).await.expect("Failed to read variable");
+
+    // Every write fails without being sent
+    let results = [
+        client.write_i32(1, before + 1).await,
+        client.write_multiple_i32(1, vec![before + 1, 0]).await,
+        client.write_io(2701, 1).await,
+        client.start_job().await,
+    ];
+    for result in &results {
+        assert!(matches!(result, Err(ClientError::OperationNotPermitted(_))), "{result:?}");
+    }
+    assert_eq!(client.read_i32(1).await.expect("Failed to read variable"), before);
+});
+
+test_with_logging!(test_request_limits_concurrent, {
+    let _server = create_variable_test_server().await.expect("Failed to start mock server");
+    let config = ClientConfig {
+        port: ROBOT_CONTROL_PORT,
+        request_limits: Some(RequestLimits::default()),
+        ..ClientConfig::default()
+    };
+    let client = std::sync::Arc::new(
+        HsesClient::new_with_config(config).await.expect("Failed to create client"),
+    );
+
+    // Requests in flight together on one socket each get their own response
+    let tasks: Vec<_> = (0..8)
+        .map(|task| {
+            let client = std::sync::Arc::clone(&client);
+            tokio::spawn(async move {
+                for _ in 0..50 {
+                    client.read_i32(task % 3 + 1).await?;
+                }
+                Ok::<_, ClientError>(())
+            })
+        })
+        .collect();
+    for task in futures::future::join_all(tasks).await {
+        task.expect("Read task panicked").expect("Failed to read variable");
+    }
+
+    let stats = client.link_stats();
+    assert_eq!(stats.overall.samples, 400);
+    assert_eq!(stats.overall.timeouts, 0);
+});
@@ -0,0 +1,82 @@
+#![allow(clippy::expect_used, clippy::significant_drop_tightening)]
+// Integration tests for the cooperative writer lock shared by two applications
+
+use crate::common::{mock_server_setup::MockServerManager, test_utils::create_test_client};
+use crate::test_with_logging;
+use moto_hses_client::{ClientError, WriterLock, WriterLockOptions};
+use moto_hses_mock::VariableRef::D;
+use std::time::Duration;
+
+const OPTIONS: WriterLockOptions = WriterLockOptions {
+    variable: 50,
+    ttl: Duration::from_millis(300),
+    heartbeat_interval: Duration::from_millis(50),
+};
+
+test_with_logging!(test_writer_lock_is_exclusive, {
+    let mut server = MockServerManager::new();
+    server.start().await.expect("Failed to start mock server");
+    let first =
+        WriterLock::new(create_test_client().await.expect("Failed to create client"), OPTIONS);
+    let second =
+        WriterLock::new(create_test_client().await.expect("Failed to create client"), OPTIONS);
+
+    let lease = first.try_acquire().await.expect("Free lock should be acquired");
+    assert_eq!(first.owner().await.expect("Failed to read owner"), Some(first.token()));
+    server.mock().assert_variable_eq(D(50), first.token()).await;
+
+    // Neither another application nor another task of the same one gets the lock
+    match second.try_acquire().await {
+        Err(ClientError::OperationNotPermitted(message)) => log::debug!("✓ {message}"),
+        result => unreachable!("Held lock should not be acquired: {:?}", result.err()),
+    }
+    assert!(first.clone().try_acquire().await.is_err());
+
+    // The heartbeat keeps the lock from going stale
+    tokio::time::sleep(OPTIONS.ttl * 2).await;
+    assert!(second.try_acquire().await.is_err());
+    assert!(lease.is_held());
+
+    lease.release().await.expect("Failed to release lock");
+    assert_eq!(second.owner().await.expect("Failed to read owner"), None);
+    let lease =
+        second.acquire(Duration::from_secs(1)).await.expect("Released lock should be acquired");
+    assert!(lease.is_held());
+});
+
+test_with_logging!(test_stale_writer_lock_is_taken_over, {
+    let mut server = MockServerManager::new();
+    server.start().await.expect("Failed to start mock server");
+    let client = create_test_client().await.expect("Failed to create client");
+    // An application that stopped without releasing its lock
+    client.write_multiple_i32(50, vec![0x1234, 7]).await.expect("Failed to write lock");
+
+    let lock = WriterLock::new(client, OPTIONS);
+    assert!(lock.try_acquire().await.is_err(), "Stale only once seen unchanged for the TTL");
+    let lease = lock.acquire(OPTIONS.ttl * 3).await.expect("Stale lock should be taken over");
+    server.mock().assert_variable_eq(D(50), lock.token()).await;
+
+    // Dropping the lease releases the lock in the background
+    drop(lease);
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    server.mock().assert_variable_eq(D(50), 0i32).await;
+});
+
+test_with_logging!(test_writer_lock_taken_over_is_lost, {
+    let mut server = MockServerManager::new();
+    server.start().await.expect("Failed to start mock server");
+    let lock =
+        WriterLock::new(create_test_client().await.expect("Failed to create client"), OPTIONS);
+    let mut lease = lock.try_acquire().await.expect("Free lock should be acquired");
+
+    let other = create_test_client().await.expect("Failed to create client");
+    other.write_i32(50, 0x4321).await.expect("Failed to write lock");
+    tokio::time::timeout(Duration::from_secs(1), lease.lost())
+        .await
+        .expect("Lease should notice the takeover");
+    assert!(!lease.is_held());
+
+    // Releasing leaves the lock of the other application alone
+    lease.release().await.expect("Failed to release lock");
+    server.mock().assert_variable_eq(D(50), 0x4321i32).await;
+});
@@ -0,0 +1,354 @@
+//! Simulated controller without sockets
+//!
+//! [`MockController`] holds the state and command handlers of a mock and turns request
+//! frames into response frames. [`MockServer`](crate::MockServer) serves it over UDP;
+//! in-process transports call [`MockController::exchange`] directly, so that a client can
+//! run thousands of requests against the mock without binding a port.
+
+use crate::handle::MockHandle;
+use crate::handlers::CommandHandlerRegistry;
+use crate::log_target;
+use crate::state::{ChaosConfig, MockState, ResponseFault, SharedState};
+use log::LevelFilter;
+use moto_hses_proto as proto;
+use proto::constants::header;
+use proto::trace::FrameDump;
+use tokio::time::Duration;
+
+/// Maximum data size of a single block in file transfer responses
+pub const FILE_BLOCK_SIZE: usize = proto::constants::MAX_PAYLOAD_SIZE;
+
+/// Response frames to one request frame
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Reply {
+    /// Encoded response datagrams in sending order; empty if the request is not answered
+    pub datagrams: Vec<Vec<u8>>,
+    /// Time to wait before delivering the datagrams
+    pub delay: Duration,
+}
+
+/// State and command handlers of a simulated controller
+///
+/// Clones share the same state.
+#[derive(Clone)]
+pub struct MockController {
+    pub(crate) state: SharedState,
+    pub(crate) handlers: CommandHandlerRegistry,
+    pub(crate) log_level: LevelFilter,
+    pub(crate) response_latency: Duration,
+    pub(crate) latency_jitter: Duration,
+}
+
+impl MockController {
+    /// Controller with the state and built-in handlers described by `config`
+    ///
+    /// Custom handlers are registered with
+    /// [`MockServerBuilder::build_controller`](crate::server::MockServerBuilder::build_controller).
+    #[must_use]
+    pub fn new(config: &crate::MockConfig) -> Self {
+        let mut mock_state = MockState {
+            text_encoding: config.text_encoding,
+            status: config.default_status.clone(),
+            position: config.default_position.clone(),
+            io_states: config.io_states.clone(),
+            registers: config.registers.clone(),
+            variables: config.variables.clone(),
+            cycle_mode: config.cycle_mode,
+            security_mode: config.security_mode,
+            controller_model: config.controller_model,
+            file_name_rules: config.file_name_rules,
+            system_info: config.system_info.clone(),
+            job_simulation: config.job_simulation.clone(),
+            motion_simulation: config.motion_simulation,
+            chaos: config.chaos,
+            log_level: config.log_level,
+            seed: config.seed,
+            ..Default::default()
+        };
+
+        mock_state.positions.clone_from(&config.positions);
+        mock_state.update_position(config.default_position.clone());
+
+        // Apply configured files
+        mock_state.files.extend(config.files.clone());
+
+        // Apply configured job information
+        if let Some(job) = &config.executing_job {
+            mock_state.executing_job = Some(job.clone());
+        }
+
+        // Apply configured alarms if any
+        if !config.alarms.is_empty() {
+            mock_state.alarms.clone_from(&config.alarms);
+        }
+
+        // Add configured alarm history entries after the default ones of their category
+        for (category, alarm) in &config.alarm_history {
+            mock_state.alarm_history.add_alarm(*category, alarm.clone());
+        }
+
+        let mut handlers = CommandHandlerRegistry::default();
+        for &command in &config.unsupported_commands {
+            handlers.unregister(command);
+        }
+        handlers.set_unknown_command_policy(config.unknown_commands);
+
+        Self {
+            state: SharedState::new(mock_state),
+            handlers,
+            log_level: config.log_level,
+            response_latency: config.response_latency,
+            latency_jitter: config.latency_jitter,
+        }
+    }
+
+    /// Get a reference to the shared state
+    #[must_use]
+    pub const fn state(&self) -> &SharedState {
+        &self.state
+    }
+
+    /// Get a handle to inspect the state
+    #[must_use]
+    pub fn handle(&self) -> MockHandle {
+        MockHandle::new(self.state.clone())
+    }
+
+    /// Answer one request frame as the server would
+    ///
+    /// Frames that do not decode and ACKs of file data blocks get no response. Injected
+    /// response faults and the response latency are applied to robot requests: a dropped
+    /// response has no datagrams, a duplicated one has each datagram twice and a delayed
+    /// one has a longer `delay`.
+    pub async fn exchange(&self, frame: &[u8]) -> Reply {
+        let log_level = self.log_level;
+        mock_log!(log_level, Debug, log_target::TRAFFIC, "Received: {}", FrameDump::new(frame));
+        let message = match proto::HsesRequestMessage::decode(frame) {
+            Ok(message) if message.header.ack == 0x00 => message,
+            Ok(_) => return Reply::default(),
+            Err(e) => {
+                mock_log!(log_level, Warn, log_target::SERVER, "Failed to decode message: {e:?}");
+                return Reply::default();
+            }
+        };
+
+        let mut datagrams = match self.respond(&message).await {
+            Ok(datagrams) => datagrams,
+            Err(e) => {
+                mock_log!(log_level, Error, log_target::SERVER, "Error handling message: {e:?}");
+                return Reply::default();
+            }
+        };
+        let mut delay = self.response_latency;
+        if message.header.division == proto::Division::Robot as u8 {
+            let (fault, jitter) = self.next_robot_fault().await;
+            delay += jitter;
+            match fault {
+                Some(ResponseFault::Drop) => datagrams.clear(),
+                Some(ResponseFault::Delay(extra)) => delay += extra,
+                Some(ResponseFault::Duplicate) => datagrams.extend(datagrams.clone()),
+                None => {}
+            }
+        }
+        for datagram in &datagrams {
+            mock_log!(
+                log_level,
+                Debug,
+                log_target::TRAFFIC,
+                "Sending: {}",
+                FrameDump::new(datagram)
+            );
+        }
+        Reply { datagrams, delay }
+    }
+
+    /// Encoded response datagrams to `message`
+    ///
+    /// Requests whose payload exceeds [`MAX_PAYLOAD_SIZE`] are rejected with status 0x15
+    /// (too much data), as is a handler response that would exceed it. Payload faults set
+    /// for the command are applied to successful robot control responses afterwards, so an
+    /// `Extend` fault may still exceed it. File list and file receive responses whose
+    /// payload exceeds [`FILE_BLOCK_SIZE`] are split into multiple data blocks. Robot control
+    /// requests drawn for an error by the [`ChaosConfig`] are answered with its error status
+    /// without being handled.
+    ///
+    /// [`MAX_PAYLOAD_SIZE`]: proto::constants::MAX_PAYLOAD_SIZE
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the response cannot be encoded
+    pub async fn respond(
+        &self,
+        message: &proto::HsesRequestMessage,
+    ) -> Result<Vec<Vec<u8>>, Box<dyn std::error::Error + Send + Sync>> {
+        let is_robot = message.header.division == proto::Division::Robot as u8;
+        let chaos_error = {
+            let mut state = self.state.write().await;
+            state.record_request(message);
+            let is_error =
+                is_robot && state.chaos.is_some_and(|chaos| chaos.is_error(state.robot_requests));
+            if is_error {
+                state.metrics.chaos_errors += 1;
+            }
+            is_error
+        };
+        let crate::handlers::HandlerResponse { mut payload, status, added_status } = if chaos_error
+        {
+            mock_log!(
+                self.log_level,
+                Debug,
+                log_target::SERVER,
+                "Answering 0x{:04X} with an error status (chaos)",
+                message.sub_header.command
+            );
+            let (status, added_status) = ChaosConfig::ERROR_STATUS;
+            crate::handlers::HandlerResponse::error(status, added_status)
+        } else {
+            self.handle_sized(message).await
+        };
+        if is_robot && status == 0x00 {
+            let fault =
+                self.state.read().await.payload_faults.get(&message.sub_header.command).copied();
+            if let Some(fault) = fault {
+                mock_log!(
+                    self.log_level,
+                    Debug,
+                    log_target::SERVER,
+                    "Applying {fault:?} to the 0x{:04X} response (injected fault)",
+                    message.sub_header.command
+                );
+                fault.apply(&mut payload);
+            }
+        }
+
+        // File data is transferred in numbered blocks; the final block has bit 31 set
+        let is_block_transfer = message.header.division == proto::Division::File as u8
+            && matches!(
+                proto::Service::try_from(message.sub_header.service),
+                Ok(proto::Service::FileReceive | proto::Service::FileList)
+            )
+            && status == 0x00;
+        if is_block_transfer {
+            let chunks: Vec<&[u8]> = if payload.is_empty() {
+                vec![&[]]
+            } else {
+                payload.chunks(FILE_BLOCK_SIZE).collect()
+            };
+            let block_count = chunks.len();
+            let mut datagrams = Vec::with_capacity(block_count);
+            for (index, chunk) in chunks.into_iter().enumerate() {
+                let mut response_message = proto::HsesResponseMessage::new(
+                    message.header.division,
+                    0x01, // ACK
+                    message.header.request_id,
+                    message.sub_header.service,
+                    status,
+                    added_status,
+                    chunk.to_vec(),
+                )?;
+                let block_number = u32::try_from(index + 1)?;
+                response_message.header.block_number = if index + 1 == block_count {
+                    block_number | header::LAST_BLOCK_FLAG
+                } else {
+                    block_number
+                };
+                datagrams.push(response_message.encode().to_vec());
+            }
+            return Ok(datagrams);
+        }
+
+        // Create response message with proper structure using new message types
+        let mut response_message = proto::HsesResponseMessage::new(
+            message.header.division,
+            0x01, // ACK
+            message.header.request_id,
+            message.sub_header.service,
+            status,       // status: success (0x00) or error (non-zero)
+            added_status, // added_status: error code if status is non-zero
+            payload,
+        )
+        .map_err(|e| {
+            mock_log!(
+                self.log_level,
+                Error,
+                log_target::SERVER,
+                "Failed to create response message: {e}"
+            );
+            e
+        })?;
+
+        // Data blocks of a file sent in blocks are acknowledged with their block number
+        if message.header.division == proto::Division::File as u8
+            && message.header.block_number != 0
+        {
+            response_message.header.block_number = message.header.block_number;
+        }
+
+        // Encode the response
+        let response_data = response_message.encode();
+        Ok(vec![response_data.to_vec()])
+    }
+
+    // Handler response to `message`, keeping requests and responses within the payload
+    // size of one datagram; file transfers beyond it are split into blocks
+    async fn handle_sized(
+        &self,
+        message: &proto::HsesRequestMessage,
+    ) -> crate::handlers::HandlerResponse {
+        let max = proto::constants::MAX_PAYLOAD_SIZE;
+        let size = message.payload.len();
+        if size > max {
+            mock_log!(
+                self.log_level,
+                Warn,
+                log_target::SERVER,
+                "Rejecting 0x{:04X} request with {size} bytes of payload (max {max})",
+                message.sub_header.command
+            );
+            return proto::ProtocolError::PayloadTooLarge { size, max }.into();
+        }
+        let response = self.handlers.respond(message, &self.state).await;
+        let block_transfer = matches!(
+            proto::Service::try_from(message.sub_header.service),
+            Ok(proto::Service::FileReceive | proto::Service::FileList)
+        );
+        let size = response.payload.len();
+        if size > max && !block_transfer {
+            mock_log!(
+                self.log_level,
+                Error,
+                log_target::SERVER,
+                "Handler for 0x{:04X} answered {size} bytes of payload (max {max}); \
+                 sending an error instead",
+                message.sub_header.command
+            );
+            return proto::ProtocolError::PayloadTooLarge { size, max }.into();
+        }
+        response
+    }
+
+    // Count a handled robot request and take the next injected response fault, or else
+    // the fault drawn by the chaos configuration, and the latency jitter drawn for it
+    pub(crate) async fn next_robot_fault(&self) -> (Option<ResponseFault>, Duration) {
+        let mut state = self.state.write().await;
+        let request = state.robot_requests;
+        state.robot_requests += 1;
+        let fault = state
+            .response_faults
+            .pop_front()
+            .or_else(|| state.chaos.and_then(|chaos| chaos.fault(request)));
+        if fault == Some(ResponseFault::Drop) {
+            state.metrics.dropped_responses += 1;
+        }
+        let seed = state.seed;
+        drop(state);
+        // Drawn apart from the chaos draws, which add the request number to the seed
+        let jitter = if self.latency_jitter.is_zero() {
+            Duration::ZERO
+        } else {
+            let seed = seed.rotate_left(32).wrapping_add(request);
+            self.latency_jitter.mul_f64(crate::random::SplitMix64::new(seed).fraction())
+        };
+        (fault, jitter)
+    }
+}
@@ -0,0 +1,419 @@
+//! Direct access to the state of a running mock server
+//!
+//! A [`MockHandle`] checks what a client wrote without reading it back through the client,
+//! so a test of a write command does not also depend on the matching read command.
+//!
+//! ```ignore
+//! use moto_hses_mock::VariableRef::D;
+//!
+//! let mock = server.handle();
+//! client.write_i32(5, 100).await?;
+//! mock.assert_variable_eq(D(5), 100i32).await;
+//! ```
+
+use std::fmt::{Debug, Write};
+
+use moto_hses_proto as proto;
+use proto::HsesPayload;
+
+use crate::SEED_ENV;
+use crate::state::{
+    ChaosConfig, IoForceConflict, MockMetrics, PayloadFault, RecordedRequest, ResponseFault,
+    SecurityMode, SharedState, VariableType,
+};
+
+/// Variable of a given type and number
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum VariableRef {
+    /// Byte variable (B)
+    B(u16),
+    /// Integer variable (I)
+    I(u16),
+    /// Double precision integer variable (D)
+    D(u16),
+    /// Real variable (R)
+    R(u16),
+    /// Character variable (S)
+    S(u16),
+    /// Robot position variable (P)
+    P(u16),
+    /// Base position variable (BP)
+    BP(u16),
+    /// Station (external axis) variable (EX)
+    EX(u16),
+}
+
+impl VariableRef {
+    #[must_use]
+    pub const fn variable_type(self) -> VariableType {
+        match self {
+            Self::B(_) => VariableType::Byte,
+            Self::I(_) => VariableType::Integer,
+            Self::D(_) => VariableType::Double,
+            Self::R(_) => VariableType::Real,
+            Self::S(_) => VariableType::String,
+            Self::P(_) => VariableType::Position,
+            Self::BP(_) => VariableType::BasePosition,
+            Self::EX(_) => VariableType::ExternalAxis,
+        }
+    }
+
+    #[must_use]
+    pub const fn index(self) -> u16 {
+        match self {
+            Self::B(index)
+            | Self::I(index)
+            | Self::D(index)
+            | Self::R(index)
+            | Self::S(index)
+            | Self::P(index)
+            | Self::BP(index)
+            | Self::EX(index) => index,
+        }
+    }
+}
+
+/// Handle to the state of a mock server, usable while the server runs
+///
+/// The `assert_*` methods panic with a line diff of the expected and actual values.
+#[derive(Debug, Clone)]
+pub struct MockHandle {
+    state: SharedState,
+}
+
+impl MockHandle {
+    pub(crate) const fn new(state: SharedState) -> Self {
+        Self { state }
+    }
+
+    /// Get a reference to the shared state
+    #[must_use]
+    pub const fn state(&self) -> &SharedState {
+        &self.state
+    }
+
+    /// Seed of the random draws of the server, shown in the messages of failed assertions
+    ///
+    /// See [`test_seed`](crate::test_seed).
+    pub async fn seed(&self) -> u64 {
+        self.state.read().await.seed
+    }
+
+    /// Current value of a variable; unset variables are 0
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the stored bytes cannot be decoded as `T`
+    pub async fn variable<T: HsesPayload>(
+        &self,
+        variable: VariableRef,
+    ) -> Result<T, proto::ProtocolError> {
+        let state = self.state.read().await;
+        let var_type = variable.variable_type();
+        let mut data = state.get_variable(var_type, variable.index()).cloned().unwrap_or_default();
+        data.resize(var_type.size(), 0);
+        T::deserialize(&data, state.text_encoding)
+    }
+
+    /// Assert that a variable holds `expected`
+    ///
+    /// # Panics
+    ///
+    /// Panics if the value differs or cannot be decoded as `T`
+    #[allow(clippy::panic)]
+    pub async fn assert_variable_eq<T: HsesPayload + Debug + PartialEq>(
+        &self,
+        variable: VariableRef,
+        expected: T,
+    ) {
+        match self.variable::<T>(variable).await {
+            Ok(actual) => {
+                assert_state_eq(
+                    &format!("variable {variable:?}"),
+                    &expected,
+                    &actual,
+                    self.seed().await,
+                );
+            }
+            Err(e) => {
+                panic!(
+                    "variable {variable:?} cannot be decoded: {e}{}",
+                    replay_hint(self.seed().await)
+                )
+            }
+        }
+    }
+
+    /// Apply `fault` to the response of the next robot control request
+    ///
+    /// Faults queue up; each one applies to one request, in the order injected.
+    pub async fn inject_fault(&self, fault: ResponseFault) {
+        self.state.write().await.response_faults.push_back(fault);
+    }
+
+    /// Apply `fault` to the payload of every successful response to the robot control
+    /// `command` until [`clear_payload_fault`](Self::clear_payload_fault)
+    ///
+    /// Short or over-long payloads exercise the length checks of the client:
+    ///
+    /// ```ignore
+    /// mock.set_payload_fault(0x79, PayloadFault::Truncate(1)).await;
+    /// assert!(client.read_register(1).await.is_err());
+    /// ```
+    pub async fn set_payload_fault(&self, command: u16, fault: PayloadFault) {
+        self.state.write().await.payload_faults.insert(command, fault);
+    }
+
+    /// Answer `command` with unaltered payloads again
+    pub async fn clear_payload_fault(&self, command: u16) {
+        self.state.write().await.payload_faults.remove(&command);
+    }
+
+    /// Apply the random faults of `chaos` to the following robot control requests, or stop
+    /// applying them with `None`
+    ///
+    /// E.g. to set up a scenario without faults before a soak test of
+    /// [`MockConfig::chaos`](crate::MockConfig::chaos), or to check the recovery afterwards.
+    pub async fn set_chaos(&self, chaos: Option<ChaosConfig>) {
+        self.state.write().await.chaos = chaos;
+    }
+
+    /// Switch the security mode, as an operator logging in on the pendant would
+    ///
+    /// File writes and deletes the mode does not allow fail with
+    /// [`SecurityMode::DENIED_STATUS`], for testing how an application reports them.
+    pub async fn set_security_mode(&self, mode: SecurityMode) {
+        self.state.write().await.security_mode = mode;
+    }
+
+    /// Number of robot control requests received, including retries
+    pub async fn robot_request_count(&self) -> u64 {
+        self.state.read().await.robot_requests
+    }
+
+    /// Snapshot of the request and response counters
+    ///
+    /// Unlike timings, the counts are exact, e.g. a client that retried a dropped response
+    /// once sent the command twice:
+    ///
+    /// ```ignore
+    /// mock.inject_fault(ResponseFault::Drop).await;
+    /// client.read_register(1).await?;
+    /// let metrics = mock.metrics().await;
+    /// assert_eq!(metrics.robot_requests(0x79), 2);
+    /// assert_eq!(metrics.dropped_responses, 1);
+    /// ```
+    pub async fn metrics(&self) -> MockMetrics {
+        self.state.read().await.metrics.clone()
+    }
+
+    /// Reset the request and response counters to zero
+    pub async fn reset_metrics(&self) {
+        self.state.write().await.metrics = MockMetrics::default();
+    }
+
+    /// Record the requests received from now on, dropping any earlier transcript
+    ///
+    /// Requests are recorded as decoded, before they are handled, so a retried request
+    /// appears once per attempt and failed requests appear too.
+    pub async fn start_transcript(&self) {
+        self.state.write().await.transcript = Some(Vec::new());
+    }
+
+    /// Requests received since [`start_transcript`](Self::start_transcript), in the order
+    /// received; recording continues with an empty transcript
+    pub async fn take_transcript(&self) -> Vec<RecordedRequest> {
+        self.state.write().await.transcript.as_mut().map(std::mem::take).unwrap_or_default()
+    }
+
+    /// Force an I/O point to `value`, as an operator would from the pendant
+    ///
+    /// Writes to the point, by the client or through the server, leave it at `value` and are
+    /// recorded as [`IoForceConflict`]s, so a test can reproduce a forced signal masking
+    /// the writes of the application:
+    ///
+    /// ```ignore
+    /// mock.force_io(2701, 0b0000_0001).await;
+    /// client.write_io(2701, 0).await?;
+    /// mock.assert_io_eq(2701, 0b0000_0001).await;
+    /// assert_eq!(mock.take_io_force_conflicts().await.len(), 1);
+    /// ```
+    pub async fn force_io(&self, io_number: u16, value: u8) {
+        self.state.write().await.force_io(io_number, value);
+    }
+
+    /// Release a forced I/O point; it keeps the forced value until written again
+    pub async fn release_io(&self, io_number: u16) {
+        self.state.write().await.release_io(io_number);
+    }
+
+    /// Writes masked by forced I/O points since the last call, in the order received
+    pub async fn take_io_force_conflicts(&self) -> Vec<IoForceConflict> {
+        std::mem::take(&mut self.state.write().await.io_force_conflicts)
+    }
+
+    /// Assert that no write was masked by a forced I/O point since the last
+    /// [`take_io_force_conflicts`](Self::take_io_force_conflicts)
+    ///
+    /// # Panics
+    ///
+    /// Panics if a write was masked
+    pub async fn assert_no_io_force_conflicts(&self) {
+        let conflicts = self.state.read().await.io_force_conflicts.clone();
+        assert_state_eq("I/O force conflicts", &Vec::new(), &conflicts, self.seed().await);
+    }
+
+    /// Wait until any signal stored for `io_number` is ON, or all are OFF
+    ///
+    /// Wrap it in a timeout; it waits for as long as the state does not match.
+    pub async fn wait_for_io(&self, io_number: u16, on: bool) {
+        self.state.wait_for(|state| (state.get_io_state(io_number) != 0) == on).await;
+    }
+
+    /// Wait until the running flag of the status is `running`
+    pub async fn wait_for_running(&self, running: bool) {
+        self.state.wait_for(|state| state.get_running() == running).await;
+    }
+
+    /// Assert the byte of I/O signals stored for `io_number`
+    ///
+    /// # Panics
+    ///
+    /// Panics if the value differs
+    pub async fn assert_io_eq(&self, io_number: u16, expected: u8) {
+        let (forced, actual) = {
+            let state = self.state.read().await;
+            (state.is_io_forced(io_number), state.get_io_state(io_number))
+        };
+        let forced = if forced { " (forced)" } else { "" };
+        assert_state_eq(
+            &format!("I/O #{io_number}{forced}"),
+            &Bits(expected),
+            &Bits(actual),
+            self.seed().await,
+        );
+    }
+
+    /// Assert whether any signal stored for `io_number` is ON
+    ///
+    /// # Panics
+    ///
+    /// Panics if the state differs
+    pub async fn assert_io(&self, io_number: u16, expected: bool) {
+        let (forced, actual, seed) = {
+            let state = self.state.read().await;
+            (state.is_io_forced(io_number), state.get_io_state(io_number), state.seed)
+        };
+        let forced = if forced { " forced" } else { "" };
+        assert!(
+            (actual != 0) == expected,
+            "I/O #{io_number} is{forced} {} (0b{actual:08b}), expected {}{}",
+            if actual != 0 { "ON" } else { "OFF" },
+            if expected { "ON" } else { "OFF" },
+            replay_hint(seed)
+        );
+    }
+
+    /// Assert the value of a register
+    ///
+    /// # Panics
+    ///
+    /// Panics if the value differs
+    pub async fn assert_register_eq(&self, register_number: u16, expected: i16) {
+        let actual = self.state.read().await.get_register(register_number);
+        assert_state_eq(
+            &format!("register {register_number}"),
+            &expected,
+            &actual,
+            self.seed().await,
+        );
+    }
+
+    /// Assert the running flag of the status
+    ///
+    /// # Panics
+    ///
+    /// Panics if the flag differs
+    pub async fn assert_running(&self, expected: bool) {
+        let actual = self.state.read().await.get_running();
+        assert_state_eq("running", &expected, &actual, self.seed().await);
+    }
+
+    /// Assert the complete status
+    ///
+    /// # Panics
+    ///
+    /// Panics if the status differs
+    pub async fn assert_status(&self, expected: &proto::Status) {
+        let actual = self.state.read().await.status.clone();
+        assert_state_eq("status", expected, &actual, self.seed().await);
+    }
+}
+
+// I/O byte shown in binary in diffs
+#[derive(PartialEq, Eq)]
+struct Bits(u8);
+
+impl Debug for Bits {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "0b{:08b}", self.0)
+    }
+}
+
+fn assert_state_eq<T: Debug + PartialEq>(what: &str, expected: &T, actual: &T, seed: u64) {
+    assert!(
+        expected == actual,
+        "{what} does not match (-expected +actual):\n{}{}",
+        diff(expected, actual),
+        replay_hint(seed)
+    );
+}
+
+// Line of failure messages telling how to replay the run
+fn replay_hint(seed: u64) -> String {
+    format!("\nmock seed {seed}; replay with {SEED_ENV}={seed}")
+}
+
+// Line diff of the pretty-printed values; lines are compared by position, which suits
+// two values of the same type
+fn diff<T: Debug>(expected: &T, actual: &T) -> String {
+    let expected = format!("{expected:#?}");
+    let actual = format!("{actual:#?}");
+    let expected: Vec<&str> = expected.lines().collect();
+    let actual: Vec<&str> = actual.lines().collect();
+
+    let mut output = String::new();
+    for line in 0..expected.len().max(actual.len()) {
+        match (expected.get(line), actual.get(line)) {
+            (Some(e), Some(a)) if e == a => {
+                let _ = writeln!(output, "  {e}");
+            }
+            (e, a) => {
+                if let Some(e) = e {
+                    let _ = writeln!(output, "- {e}");
+                }
+                if let Some(a) = a {
+                    let _ = writeln!(output, "+ {a}");
+                }
+            }
+        }
+    }
+    output
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff_marks_changed_lines() {
+        let actual = proto::StatusData1::from_bytes(&[0; 4]).unwrap();
+        let expected = proto::StatusData1 { running: true, ..actual };
+        let diff = diff(&expected, &actual);
+
+        assert!(diff.contains("-     running: true,"), "{diff}");
+        assert!(diff.contains("+     running: false,"), "{diff}");
+        assert!(diff.contains("      step: false,"), "{diff}");
+    }
+}
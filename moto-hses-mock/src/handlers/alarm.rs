@@ -1,69 +1,26 @@
 //! Alarm-related command handlers
 
 use crate::{CommandHandler, state::MockState};
+use moto_hses_proto::Service;
 use moto_hses_proto::{
     Alarm, AlarmAttribute, HsesRequestMessage, ProtocolError, ReadAlarmData, ReadAlarmHistory,
-    encoding_utils,
+    payload::serialize_alarm_response,
 };
 
-/// Common helper function to handle alarm attribute reading based on service type
-fn handle_alarm_service_request(
+// Instances of the alarm data (0x70) and alarm history (0x71) commands
+const ALARM_INSTANCES: &str = "1-100, 1001-1100, 2001-2100, 3001-3100, 4001-4100";
+
+// Data part of a 0x70 or 0x71 response
+fn alarm_response(
     alarm: &Alarm,
-    service: u8,
+    service: Service,
     attribute: u8,
     state: &MockState,
 ) -> Result<Vec<u8>, ProtocolError> {
     match service {
-        0x01 => {
-            // Service = 0x01 (Get_Attribute_All) - Return complete alarm data (60 bytes)
-            alarm.serialize_complete(state.text_encoding)
-        }
-        0x0E => {
-            // Service = 0x0E (Get_Attribute_Single) - Return specific attribute data
-            Ok(get_alarm_attribute_data(alarm, attribute, state))
-        }
-        _ => {
-            // Invalid service - return empty data
-            Ok(vec![0u8; 4])
-        }
-    }
-}
-
-/// Common helper function to get specific alarm attribute data
-fn get_alarm_attribute_data(alarm: &Alarm, attribute: u8, state: &MockState) -> Vec<u8> {
-    match attribute {
-        1 => {
-            // Alarm code (4 bytes)
-            alarm.code.to_le_bytes().to_vec()
-        }
-        2 => {
-            // Alarm data (4 bytes)
-            alarm.data.to_le_bytes().to_vec()
-        }
-        3 => {
-            // Alarm type (4 bytes)
-            alarm.alarm_type.to_le_bytes().to_vec()
-        }
-        4 => {
-            // Alarm time (16 bytes)
-            let time_bytes = encoding_utils::encode_string(&alarm.time, state.text_encoding);
-            let mut padded_time = vec![0u8; 16];
-            padded_time[..time_bytes.len().min(16)]
-                .copy_from_slice(&time_bytes[..time_bytes.len().min(16)]);
-            padded_time
-        }
-        5 => {
-            // Alarm name (32 bytes)
-            let name_bytes = encoding_utils::encode_string(&alarm.name, state.text_encoding);
-            let mut padded_name = vec![0u8; 32];
-            padded_name[..name_bytes.len().min(32)]
-                .copy_from_slice(&name_bytes[..name_bytes.len().min(32)]);
-            padded_name
-        }
-        _ => {
-            // Invalid attribute - return empty data
-            vec![0u8; 4]
-        }
+        Service::GetAll => serialize_alarm_response(alarm, 0, state.text_encoding),
+        Service::GetSingle => serialize_alarm_response(alarm, attribute, state.text_encoding),
+        _ => Err(ProtocolError::InvalidService),
     }
 }
 
@@ -78,16 +35,14 @@ impl CommandHandler for AlarmDataHandler {
     ) -> Result<Vec<u8>, ProtocolError> {
         let instance = message.sub_header.instance;
         let attribute = message.sub_header.attribute;
-        let service = message.sub_header.service;
+        let service = Service::try_from(message.sub_header.service)?;
 
         // Create ReadAlarmData command to validate instance and attribute
         let alarm_data_cmd = ReadAlarmData::new(instance, AlarmAttribute::from(attribute));
 
         // Validate instance range
         if !alarm_data_cmd.is_valid_instance() {
-            return Err(ProtocolError::InvalidInstance(format!(
-                "Invalid alarm instance: {instance} (valid range: 1-1000)"
-            )));
+            return Err(super::invalid_instance(message, ALARM_INSTANCES));
         }
 
         let instance_usize = instance as usize;
@@ -97,7 +52,7 @@ impl CommandHandler for AlarmDataHandler {
         }
 
         let alarm = &state.alarms[instance_usize - 1];
-        handle_alarm_service_request(alarm, service, attribute, state)
+        alarm_response(alarm, service, attribute, state)
     }
 }
 
@@ -112,29 +67,23 @@ impl CommandHandler for AlarmInfoHandler {
     ) -> Result<Vec<u8>, ProtocolError> {
         let instance = message.sub_header.instance;
         let attribute = message.sub_header.attribute;
-        let service = message.sub_header.service;
+        let service = Service::try_from(message.sub_header.service)?;
 
         // Create ReadAlarmHistory command to validate instance
         let alarm_history_cmd = ReadAlarmHistory::new(instance, AlarmAttribute::from(attribute));
 
         // Validate instance range
         if !alarm_history_cmd.is_valid_instance() {
-            return Err(ProtocolError::InvalidInstance(format!(
-                "Invalid alarm history instance: {instance} (valid range: 1-1000)"
-            )));
+            return Err(super::invalid_instance(message, ALARM_INSTANCES));
         }
 
         let category = alarm_history_cmd.get_alarm_category();
         let index = alarm_history_cmd.get_alarm_index();
 
-        // Get alarm from history
-        state.alarm_history.get_alarm(category, index).map_or_else(
-            || {
-                // No alarm found at this index - return empty data
-                Ok(vec![0u8; 4])
-            },
-            |alarm| handle_alarm_service_request(alarm, service, attribute, state),
-        )
+        // Empty entries are answered with zeroed data of the requested size
+        let empty = Alarm::new(0, 0, 0, String::new(), String::new());
+        let alarm = state.alarm_history.get_alarm(category, index).unwrap_or(&empty);
+        alarm_response(alarm, service, attribute, state)
     }
 }
 
@@ -3,6 +3,7 @@
 use super::CommandHandler;
 use crate::state::MockState;
 use moto_hses_proto as proto;
+use moto_hses_proto::Service;
 
 /// Handler for cycle mode switching command (0x84)
 pub struct CycleModeSwitchingHandler;
@@ -15,10 +16,7 @@ impl CommandHandler for CycleModeSwitchingHandler {
     ) -> Result<Vec<u8>, proto::ProtocolError> {
         // Validate instance (must be 2)
         if message.sub_header.instance != 2 {
-            return Err(proto::ProtocolError::InvalidInstance(format!(
-                "Invalid instance: {} (expected: 2)",
-                message.sub_header.instance
-            )));
+            return Err(super::invalid_instance(message, "2"));
         }
 
         // Validate attribute (must be 1)
@@ -26,8 +24,8 @@ impl CommandHandler for CycleModeSwitchingHandler {
             return Err(proto::ProtocolError::InvalidAttribute);
         }
 
-        // Validate service (must be 0x10)
-        if message.sub_header.service != 0x10 {
+        // Validate service (must be Set_Attribute_Single)
+        if message.sub_header.service != Service::SetSingle.code() {
             return Err(proto::ProtocolError::InvalidService);
         }
 
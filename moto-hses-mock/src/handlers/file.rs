@@ -1,64 +1,112 @@
 //! File control command handlers
 
-use super::CommandHandler;
-use crate::state::MockState;
+use super::{CommandHandler, HandlerResponse};
+use crate::log_target;
+use crate::state::{MockState, SecurityMode};
 use moto_hses_proto as proto;
+use moto_hses_proto::Service;
+use moto_hses_proto::constants::header;
+
+/// Build a file list payload in controller format
+///
+/// Each file name is terminated by CR LF, e.g. `"MASTER.JBI\r\nTEST.JBI\r\n"`. Long
+/// listings are split into blocks by the server before being sent.
+fn file_list_payload(state: &MockState, pattern: &str) -> Vec<u8> {
+    let mut file_list = String::new();
+    for file in state.get_file_list(pattern) {
+        file_list.push_str(&file);
+        file_list.push_str("\r\n");
+    }
+    moto_hses_proto::encoding_utils::encode_string(&file_list, state.text_encoding)
+}
+
+/// Append data block `block` (1, 2, ...) of a file sent in blocks to the file
+///
+/// A block received again because its response was lost is acknowledged without being
+/// appended a second time.
+fn receive_file_block(
+    message: &proto::HsesRequestMessage,
+    state: &mut MockState,
+    block: u32,
+) -> Result<(), proto::ProtocolError> {
+    let Some((filename, last)) = state.file_upload.take() else {
+        return Err(proto::ProtocolError::FileError(format!(
+            "data block {block} without a file name block"
+        )));
+    };
+    if block == last {
+        state.file_upload = Some((filename, last));
+        return Ok(());
+    }
+    if block != last + 1 {
+        return Err(proto::ProtocolError::FileError(format!(
+            "data block {block} of {filename} received after block {last}"
+        )));
+    }
+    let content = state.files.entry(filename.clone()).or_default();
+    content.extend_from_slice(&message.payload);
+    mock_log!(
+        state.log_level,
+        Debug,
+        log_target::FILE,
+        "File block {block} of {filename} received ({} bytes so far)",
+        content.len()
+    );
+    state.file_upload = Some((filename, block));
+    Ok(())
+}
+
+// Name of the file a send (block 0) or delete request changes
+fn changed_file(message: &proto::HsesRequestMessage, state: &MockState) -> Option<String> {
+    let name = match Service::try_from(message.sub_header.service) {
+        Ok(Service::FileDelete) => message.payload.as_slice(),
+        Ok(Service::FileSend) if message.header.block_number & !header::LAST_BLOCK_FLAG == 0 => {
+            let end = message.payload.iter().position(|&b| b == 0)?;
+            &message.payload[..end]
+        }
+        _ => return None,
+    };
+    Some(moto_hses_proto::encoding_utils::decode_string_with_fallback(name, state.text_encoding))
+}
 
 /// Handler for file operations (0x00)
+///
+/// Sends and deletes the security mode does not allow are answered with
+/// [`SecurityMode::DENIED_STATUS`](crate::SecurityMode::DENIED_STATUS).
 pub struct FileControlHandler;
 
 impl CommandHandler for FileControlHandler {
+    fn respond(
+        &self,
+        message: &proto::HsesRequestMessage,
+        state: &mut MockState,
+    ) -> HandlerResponse {
+        if let Some(filename) = changed_file(message, state)
+            && !state.security_mode.may_change_file(&filename)
+        {
+            mock_log!(
+                state.log_level,
+                Debug,
+                log_target::FILE,
+                "Change of {filename} denied in {:?} mode",
+                state.security_mode
+            );
+            let (status, added_status) = SecurityMode::DENIED_STATUS;
+            return HandlerResponse::error(status, added_status);
+        }
+        self.handle(message, state).map_or_else(HandlerResponse::from, HandlerResponse::ok)
+    }
+
     #[allow(clippy::too_many_lines)]
     fn handle(
         &self,
         message: &proto::HsesRequestMessage,
         state: &mut MockState,
     ) -> Result<Vec<u8>, proto::ProtocolError> {
-        let service = message.sub_header.service;
+        let service = Service::try_from(message.sub_header.service)?;
 
         match service {
-            0x01 => {
-                // Get file list
-                // Return a simple file list
-                let file_list = "TEST.JOB\0";
-                let file_list_bytes =
-                    moto_hses_proto::encoding_utils::encode_string(file_list, state.text_encoding);
-                Ok(file_list_bytes)
-            }
-            0x02 => {
-                // Send file
-                // Parse filename from payload
-                if let Some(filename_pos) = message.payload.iter().position(|&b| b == 0) {
-                    let filename = moto_hses_proto::encoding_utils::decode_string_with_fallback(
-                        &message.payload[..filename_pos],
-                        state.text_encoding,
-                    );
-                    let content = message.payload[filename_pos + 1..].to_vec();
-                    state.set_file(filename, content);
-                }
-                Ok(vec![])
-            }
-            0x03 => {
-                // Receive file
-                // Parse filename from payload
-                if let Some(filename_pos) = message.payload.iter().position(|&b| b == 0) {
-                    let filename = moto_hses_proto::encoding_utils::decode_string_with_fallback(
-                        &message.payload[..filename_pos],
-                        state.text_encoding,
-                    );
-                    if let Some(content) = state.get_file(&filename) {
-                        let mut response = moto_hses_proto::encoding_utils::encode_string(
-                            &filename,
-                            state.text_encoding,
-                        );
-                        response.push(0);
-                        response.extend(content);
-                        return Ok(response);
-                    }
-                }
-                Ok(vec![])
-            }
-            0x09 => {
+            Service::FileDelete => {
                 // Delete file
                 // Parse filename from payload
                 let filename = moto_hses_proto::encoding_utils::decode_string_with_fallback(
@@ -66,26 +114,45 @@ impl CommandHandler for FileControlHandler {
                     state.text_encoding,
                 );
                 let deleted = state.delete_file(&filename);
-                debug!("File deletion requested: {filename} (deleted: {deleted})");
+                mock_log!(
+                    state.log_level,
+                    Debug,
+                    log_target::FILE,
+                    "File deletion requested: {filename} (deleted: {deleted})"
+                );
                 Ok(vec![])
             }
-            0x15 => {
-                // Send file (Python client uses this)
-                // Parse filename from payload
+            Service::FileSend => {
+                let block = message.header.block_number & !header::LAST_BLOCK_FLAG;
+                if block != 0 {
+                    receive_file_block(message, state, block)?;
+                    return Ok(vec![]);
+                }
+                // Block 0 carries the file name, followed by the content if it fits
                 if let Some(filename_pos) = message.payload.iter().position(|&b| b == 0) {
                     let filename = moto_hses_proto::encoding_utils::decode_string_with_fallback(
                         &message.payload[..filename_pos],
                         state.text_encoding,
                     );
+                    let rules = state
+                        .file_name_rules
+                        .unwrap_or_else(|| state.controller_model.file_name_rules());
+                    proto::commands::validate_filename(&filename, rules, state.text_encoding)?;
                     let content = message.payload[filename_pos + 1..].to_vec();
                     let filename_clone = filename.clone();
                     let content_len = content.len();
+                    state.file_upload = Some((filename.clone(), 0));
                     state.set_file(filename, content);
-                    debug!("File saved: {filename_clone} ({content_len} bytes)");
+                    mock_log!(
+                        state.log_level,
+                        Debug,
+                        log_target::FILE,
+                        "File saved: {filename_clone} ({content_len} bytes)"
+                    );
                 }
                 Ok(vec![])
             }
-            0x32 => {
+            Service::FileList => {
                 // Get file list (Python client uses this)
                 // Parse pattern from payload
                 let pattern = if message.payload.is_empty() {
@@ -97,18 +164,17 @@ impl CommandHandler for FileControlHandler {
                     )
                 };
 
-                let files = state.get_file_list(&pattern);
-                let mut file_list = String::new();
-                for file in files {
-                    file_list.push_str(&file);
-                    file_list.push_str("\r\n");
-                }
-                debug!("File list requested with pattern '{pattern}', returning: {file_list:?}");
-                let file_list_bytes =
-                    moto_hses_proto::encoding_utils::encode_string(&file_list, state.text_encoding);
+                let file_list_bytes = file_list_payload(state, &pattern);
+                mock_log!(
+                    state.log_level,
+                    Debug,
+                    log_target::FILE,
+                    "File list requested with pattern '{pattern}', returning {} bytes",
+                    file_list_bytes.len()
+                );
                 Ok(file_list_bytes)
             }
-            0x16 => {
+            Service::FileReceive => {
                 // Receive file (Python client uses this)
                 // Parse filename from payload
                 let filename = moto_hses_proto::encoding_utils::decode_string_with_fallback(
@@ -116,10 +182,17 @@ impl CommandHandler for FileControlHandler {
                     state.text_encoding,
                 );
                 if let Some(content) = state.get_file(&filename) {
-                    debug!("File requested: {} ({} bytes)", filename, content.len());
+                    mock_log!(
+                        state.log_level,
+                        Debug,
+                        log_target::FILE,
+                        "File requested: {} ({} bytes)",
+                        filename,
+                        content.len()
+                    );
                     return Ok(content.clone());
                 }
-                debug!("File not found: {filename}");
+                mock_log!(state.log_level, Debug, log_target::FILE, "File not found: {filename}");
                 Ok(vec![])
             }
             _ => Err(proto::ProtocolError::InvalidService),
@@ -3,6 +3,7 @@
 use super::CommandHandler;
 use crate::state::MockState;
 use moto_hses_proto as proto;
+use moto_hses_proto::Service;
 use moto_hses_proto::commands::io::IoCategory;
 
 /// Handler for I/O operations (0x78)
@@ -15,7 +16,7 @@ impl CommandHandler for IoHandler {
         state: &mut MockState,
     ) -> Result<Vec<u8>, proto::ProtocolError> {
         let io_number = message.sub_header.instance;
-        let service = message.sub_header.service;
+        let service = Service::try_from(message.sub_header.service)?;
 
         // Validate I/O number range
         if !IoCategory::is_valid_io_number(io_number) {
@@ -25,13 +26,16 @@ impl CommandHandler for IoHandler {
         }
 
         match service {
-            0x0e => {
+            Service::GetSingle => {
                 // Read - return 1 byte per I/O channel
                 let value = state.get_io_state(io_number);
                 Ok(vec![value])
             }
-            0x10 => {
-                // Write - accept 1 byte per I/O channel
+            Service::SetSingle => {
+                // Write - accept 1 byte per I/O channel of a writable category
+                if !IoCategory::from_io_number(io_number).is_some_and(IoCategory::is_writable) {
+                    return Err(super::invalid_instance(message, "2701-2956 (network input)"));
+                }
                 if !message.payload.is_empty() {
                     let value = message.payload[0];
                     state.set_io_state(io_number, value);
@@ -53,7 +57,7 @@ impl CommandHandler for PluralIoHandler {
         state: &mut MockState,
     ) -> Result<Vec<u8>, proto::ProtocolError> {
         let start_io_number = message.sub_header.instance;
-        let service = message.sub_header.service;
+        let service = Service::try_from(message.sub_header.service)?;
 
         // Validate attribute (should be 0)
         if message.sub_header.attribute != 0 {
@@ -91,7 +95,7 @@ impl CommandHandler for PluralIoHandler {
         }
 
         match service {
-            0x33 => {
+            Service::ReadPlural => {
                 // Read - validate full range before reading
                 let count_u16 = u16::try_from(count).map_err(|_| {
                     proto::ProtocolError::InvalidMessage(format!(
@@ -123,7 +127,7 @@ impl CommandHandler for PluralIoHandler {
                 response.extend_from_slice(&io_data);
                 Ok(response)
             }
-            0x34 => {
+            Service::WritePlural => {
                 // Write - validate payload length and update state
                 let expected_len = 4 + count as usize;
                 if message.payload.len() != expected_len {
@@ -134,10 +138,9 @@ impl CommandHandler for PluralIoHandler {
                 }
 
                 // Only network input signals are writable
-                if !(2701..=2956).contains(&start_io_number) {
-                    return Err(proto::ProtocolError::InvalidMessage(format!(
-                        "I/O number {start_io_number} is not writable (only network input range 2701..=2956 is writable)"
-                    )));
+                if !IoCategory::from_io_number(start_io_number).is_some_and(IoCategory::is_writable)
+                {
+                    return Err(super::invalid_instance(message, "2701-2956 (network input)"));
                 }
 
                 // Validate the full range of I/O numbers being written
@@ -1,8 +1,10 @@
 //! Job and movement command handlers
 
 use super::CommandHandler;
-use crate::state::MockState;
+use crate::state::{MockState, MotionSimulation};
 use moto_hses_proto as proto;
+use moto_hses_proto::Service;
+use std::time::Duration;
 
 /// Handler for reading executing job information (0x73)
 pub struct ExecutingJobInfoHandler;
@@ -15,13 +17,11 @@ impl CommandHandler for ExecutingJobInfoHandler {
     ) -> Result<Vec<u8>, proto::ProtocolError> {
         let task_type = message.sub_header.instance;
         let attribute = message.sub_header.attribute;
-        let service = message.sub_header.service;
+        let service = Service::try_from(message.sub_header.service)?;
 
         // Validate task type (1-6)
         if !matches!(task_type, 1..=6) {
-            return Err(proto::ProtocolError::InvalidInstance(format!(
-                "Invalid task type: {task_type} (valid range: 1-6)"
-            )));
+            return Err(super::invalid_instance(message, "1-6"));
         }
 
         // Validate attribute (0-4)
@@ -38,8 +38,8 @@ impl CommandHandler for ExecutingJobInfoHandler {
         };
 
         match service {
-            0x0e => job_info.serialize(attribute, state.text_encoding),
-            0x01 => job_info.serialize_complete(state.text_encoding),
+            Service::GetSingle => job_info.serialize(attribute, state.text_encoding),
+            Service::GetAll => job_info.serialize_complete(state.text_encoding),
             _ => Err(proto::ProtocolError::InvalidService),
         }
     }
@@ -56,15 +56,12 @@ impl CommandHandler for JobStartHandler {
     ) -> Result<Vec<u8>, proto::ProtocolError> {
         // Validate instance, attribute, service
         if message.sub_header.instance != 1 {
-            return Err(proto::ProtocolError::InvalidInstance(format!(
-                "Invalid instance: {} (expected: 1)",
-                message.sub_header.instance
-            )));
+            return Err(super::invalid_instance(message, "1"));
         }
         if message.sub_header.attribute != 1 {
             return Err(proto::ProtocolError::InvalidAttribute);
         }
-        if message.sub_header.service != 0x10 {
+        if message.sub_header.service != Service::SetSingle.code() {
             return Err(proto::ProtocolError::InvalidService);
         }
 
@@ -80,7 +77,7 @@ impl CommandHandler for JobStartHandler {
             ));
         }
 
-        state.set_running(true);
+        state.start_job();
         Ok(vec![])
     }
 }
@@ -105,8 +102,8 @@ impl CommandHandler for JobSelectHandler {
             return Err(proto::ProtocolError::InvalidAttribute);
         }
 
-        // Validate service (should be 0x02 for Set_Attribute_All)
-        if message.sub_header.service != 0x02 {
+        // Validate service (should be Set_Attribute_All)
+        if message.sub_header.service != Service::SetAll.code() {
             return Err(proto::ProtocolError::InvalidService);
         }
 
@@ -146,29 +143,136 @@ impl CommandHandler for JobSelectHandler {
 }
 
 /// Handler for MOV command (0x8a)
+///
+/// Targets are taken as robot position data regardless of the requested frame, and
+/// increments (instance 3) are added to the current Cartesian position, or to the target
+/// of a simulated move still in progress. The move completes immediately unless a
+/// [`MotionSimulation`](crate::state::MotionSimulation) is configured.
 pub struct MovHandler;
 
+// Little-endian word `index` of a move instruction payload
+fn payload_word(payload: &[u8], index: usize) -> i32 {
+    let offset = index * 4;
+    i32::from_le_bytes([
+        payload[offset],
+        payload[offset + 1],
+        payload[offset + 2],
+        payload[offset + 3],
+    ])
+}
+
+// Target position of a move instruction
+#[allow(clippy::cast_precision_loss, clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn move_target(
+    payload: &[u8],
+    incremental: bool,
+    state: &MockState,
+) -> Result<proto::CartesianPosition, proto::ProtocolError> {
+    // Data 6-11: X, Y, Z [μm] and RX, RY, RZ [0.0001deg]
+    let coordinate = |index: usize, scale: f32| payload_word(payload, index) as f32 / scale;
+    let (x, y, z) = (coordinate(5, 1000.0), coordinate(6, 1000.0), coordinate(7, 1000.0));
+    let (rx, ry, rz) = (coordinate(8, 10000.0), coordinate(9, 10000.0), coordinate(10, 10000.0));
+
+    if !incremental {
+        // Data 14-17: type, expanded type, tool number, user coordinate number
+        return Ok(proto::CartesianPosition::new(
+            x,
+            y,
+            z,
+            rx,
+            ry,
+            rz,
+            payload_word(payload, 15) as u8,
+            payload_word(payload, 16) as u8,
+            proto::payload::position::Configuration::from_raw(payload_word(payload, 13) as u8),
+            proto::payload::position::ExtendedConfiguration::from_raw(
+                payload_word(payload, 14) as u8
+            ),
+        ));
+    }
+
+    let current = match (&state.motion, &state.position) {
+        (Some(motion), _) => &motion.to,
+        (None, proto::Position::Cartesian(current)) => current,
+        (None, proto::Position::Pulse(_)) => {
+            return Err(proto::ProtocolError::PositionError(
+                "incremental move requires a Cartesian current position".to_string(),
+            ));
+        }
+    };
+    let mut target = current.clone();
+    target.x += x;
+    target.y += y;
+    target.z += z;
+    target.rx += rx;
+    target.ry += ry;
+    target.rz += rz;
+    Ok(target)
+}
+
+// Travel time of a simulated move at the commanded speed
+#[allow(clippy::cast_precision_loss)]
+fn move_duration(
+    payload: &[u8],
+    target: &proto::CartesianPosition,
+    state: &MockState,
+    simulation: MotionSimulation,
+) -> Result<Duration, proto::ProtocolError> {
+    let proto::Position::Cartesian(from) = &state.position else {
+        return Ok(Duration::ZERO);
+    };
+    let distance = (target.z - from.z).hypot(target.y - from.y).hypot(target.x - from.x);
+    let rotation = (target.rx - from.rx)
+        .abs()
+        .max((target.ry - from.ry).abs())
+        .max((target.rz - from.rz).abs());
+
+    // Data 3-4: speed classification and speed (0.01%, 0.1mm/s or 0.1deg/s)
+    let speed = payload_word(payload, 3) as f32;
+    let seconds = match payload_word(payload, 2) {
+        0 => distance / (simulation.max_linear_speed * speed / 10000.0),
+        1 => distance / (speed / 10.0),
+        2 => rotation / (speed / 10.0),
+        classification => {
+            return Err(proto::ProtocolError::InvalidMessage(format!(
+                "invalid speed classification {classification}"
+            )));
+        }
+    };
+    Duration::try_from_secs_f32(seconds)
+        .map_err(|_| proto::ProtocolError::InvalidMessage(format!("invalid speed {speed}")))
+}
+
 impl CommandHandler for MovHandler {
     fn handle(
         &self,
         message: &proto::HsesRequestMessage,
         state: &mut MockState,
     ) -> Result<Vec<u8>, proto::ProtocolError> {
-        let service = message.sub_header.service;
+        let service = Service::try_from(message.sub_header.service)?;
 
         match service {
-            0x02 => {
+            Service::SetAll => {
                 // SetAll
-                if message.payload.len() >= 104 {
-                    // Parse position data and update state
-                    if let Ok(position) =
-                        proto::Position::deserialize(&message.payload[0..52], state.text_encoding)
-                    {
-                        state.update_position(position);
-                    }
+                let payload = &message.payload;
+                if payload.len() < 104 {
+                    return Err(proto::ProtocolError::Underflow);
+                }
+                let incremental = match message.sub_header.instance {
+                    1 | 2 => false,
+                    3 => true,
+                    _ => return Err(super::invalid_instance(message, "1-3")),
+                };
+
+                let target = move_target(payload, incremental, state)?;
+                if let Some(simulation) = state.motion_simulation {
+                    let duration = move_duration(payload, &target, state, simulation)?;
+                    state.start_motion(target, duration);
+                } else {
+                    state.update_position(proto::Position::Cartesian(target));
+                    // Set running to false after MOV command
+                    state.set_running(false);
                 }
-                // Set running to false after MOV command
-                state.set_running(false);
                 Ok(vec![])
             }
             _ => Err(proto::ProtocolError::InvalidService),
@@ -177,6 +281,8 @@ impl CommandHandler for MovHandler {
 }
 
 /// Handler for PMOV command (0x8b)
+///
+/// The robot axes jump to the target pulse position immediately.
 pub struct PmovHandler;
 
 impl CommandHandler for PmovHandler {
@@ -185,19 +291,24 @@ impl CommandHandler for PmovHandler {
         message: &proto::HsesRequestMessage,
         state: &mut MockState,
     ) -> Result<Vec<u8>, proto::ProtocolError> {
-        let service = message.sub_header.service;
+        let service = Service::try_from(message.sub_header.service)?;
 
         match service {
-            0x02 => {
+            Service::SetAll => {
                 // SetAll
-                if message.payload.len() >= 88 {
-                    // Parse position data and update state
-                    if let Ok(position) =
-                        proto::Position::deserialize(&message.payload[0..52], state.text_encoding)
-                    {
-                        state.update_position(position);
-                    }
+                let payload = &message.payload;
+                if payload.len() < 88 {
+                    return Err(proto::ProtocolError::Underflow);
+                }
+                if !matches!(message.sub_header.instance, 1 | 2) {
+                    return Err(super::invalid_instance(message, "1-2"));
                 }
+
+                // Data 5-12: robot axis pulses
+                let joints = (4..12).map(|index| payload_word(payload, index)).collect();
+                state.motion = None;
+                state.update_position(proto::Position::Pulse(proto::PulsePosition::new(joints)));
+
                 // Set running to false after PMOV command
                 state.set_running(false);
                 Ok(vec![])
@@ -2,9 +2,61 @@
 //!
 //! This module contains all command handlers organized by functionality.
 
-use crate::state::MockState;
+use crate::log_target;
+use crate::state::{MockState, SharedState};
 use moto_hses_proto as proto;
 
+/// Response of a handler as sent on the wire
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HandlerResponse {
+    pub payload: Vec<u8>,
+    /// Status of the response sub-header; 0x00 for success
+    pub status: u8,
+    /// Added status of the response sub-header, detailing the status
+    pub added_status: u16,
+}
+
+impl HandlerResponse {
+    /// Successful response carrying `payload`
+    #[must_use]
+    pub const fn ok(payload: Vec<u8>) -> Self {
+        Self { payload, status: 0x00, added_status: 0x0000 }
+    }
+
+    /// Error response without payload
+    #[must_use]
+    pub const fn error(status: u8, added_status: u16) -> Self {
+        Self { payload: Vec::new(), status, added_status }
+    }
+
+    /// Successful response carrying `payload` and a warning in the added status
+    #[must_use]
+    pub const fn warning(payload: Vec<u8>, added_status: u16) -> Self {
+        Self { payload, status: 0x00, added_status }
+    }
+
+    #[must_use]
+    pub const fn is_success(&self) -> bool {
+        self.status == 0x00
+    }
+}
+
+impl From<proto::ProtocolError> for HandlerResponse {
+    fn from(error: proto::ProtocolError) -> Self {
+        match error {
+            proto::ProtocolError::InvalidCommand => Self::error(0x01, 0x0001),
+            proto::ProtocolError::InvalidService => Self::error(0x02, 0x0002),
+            proto::ProtocolError::InvalidAttribute => Self::error(0x03, 0x0003),
+            // Instance error, as the controller answers e.g. a write to a read-only signal
+            proto::ProtocolError::InvalidInstance { .. } => Self::error(0x28, 0xA001),
+            // Too much data, for requests longer than one datagram may carry
+            proto::ProtocolError::PayloadTooLarge { .. } => Self::error(0x15, 0x0000),
+            // Other errors are answered with the generic error status
+            _ => Self::error(0xFF, 0x00FF),
+        }
+    }
+}
+
 /// Command handler trait
 pub trait CommandHandler {
     /// Handle a command message
@@ -17,6 +69,74 @@ pub trait CommandHandler {
         message: &proto::HsesRequestMessage,
         state: &mut MockState,
     ) -> Result<Vec<u8>, proto::ProtocolError>;
+
+    /// Build the response to a command message
+    ///
+    /// The default maps the result of [`handle`](Self::handle) to a response; handlers
+    /// override it to answer with a specific status or added status.
+    fn respond(
+        &self,
+        message: &proto::HsesRequestMessage,
+        state: &mut MockState,
+    ) -> HandlerResponse {
+        self.handle(message, state).map_or_else(
+            |e| {
+                mock_log!(
+                    state.log_level,
+                    Debug,
+                    log_target::HANDLERS,
+                    "Command 0x{:04x} failed: {e}",
+                    message.sub_header.command
+                );
+                HandlerResponse::from(e)
+            },
+            HandlerResponse::ok,
+        )
+    }
+}
+
+/// Command handler that may await, e.g. timers or file IO
+///
+/// It gets the shared state rather than the locked state, and should lock it only while
+/// reading or updating it, so that waiting does not hold up other requests.
+#[async_trait::async_trait]
+pub trait AsyncCommandHandler {
+    /// Build the response to a command message
+    async fn respond(
+        &self,
+        message: &proto::HsesRequestMessage,
+        state: &SharedState,
+    ) -> HandlerResponse;
+}
+
+/// Adapter running a [`CommandHandler`] as an [`AsyncCommandHandler`]
+///
+/// The state is locked for writing while the handler runs.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SyncHandler<H>(pub H);
+
+#[async_trait::async_trait]
+impl<H: CommandHandler + Send + Sync> AsyncCommandHandler for SyncHandler<H> {
+    async fn respond(
+        &self,
+        message: &proto::HsesRequestMessage,
+        state: &SharedState,
+    ) -> HandlerResponse {
+        let mut state = state.write().await;
+        self.0.respond(message, &mut state)
+    }
+}
+
+/// Error for a request whose instance is outside the `valid` range of its command
+pub(crate) fn invalid_instance(
+    message: &proto::HsesRequestMessage,
+    valid: &str,
+) -> proto::ProtocolError {
+    proto::ProtocolError::InvalidInstance {
+        command: message.sub_header.command,
+        instance: message.sub_header.instance,
+        valid: valid.to_string(),
+    }
 }
 
 // Re-export all handler modules
@@ -32,4 +152,34 @@ pub mod system;
 pub mod variable;
 
 // Re-export the registry
-pub use registry::CommandHandlerRegistry;
+pub use registry::{CommandHandlerRegistry, UnknownCommandPolicy};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_protocol_error_status() {
+        assert_eq!(
+            HandlerResponse::from(proto::ProtocolError::InvalidService),
+            HandlerResponse::error(0x02, 0x0002)
+        );
+        assert_eq!(
+            HandlerResponse::from(proto::ProtocolError::InvalidInstance {
+                command: 0x78,
+                instance: 1,
+                valid: "2701-2956".to_string(),
+            }),
+            HandlerResponse::error(0x28, 0xA001)
+        );
+        assert_eq!(
+            HandlerResponse::from(proto::ProtocolError::Underflow),
+            HandlerResponse::error(0xFF, 0x00FF)
+        );
+        assert_eq!(
+            HandlerResponse::from(proto::ProtocolError::PayloadTooLarge { size: 480, max: 479 }),
+            HandlerResponse::error(0x15, 0x0000)
+        );
+        assert!(HandlerResponse::warning(vec![1], 0x0010).is_success());
+    }
+}
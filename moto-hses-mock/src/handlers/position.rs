@@ -1,23 +1,52 @@
 //! Position-related command handlers
 
 use super::CommandHandler;
-use crate::state::MockState;
+use crate::state::{MockState, VariableType};
 use moto_hses_proto as proto;
+use moto_hses_proto::Service;
+use proto::HsesPayload;
+
+/// Size of current position data: data type, form, tool number, user coordinate number,
+/// extended form and 8 axes (13 x 4 bytes)
+const CURRENT_POSITION_SIZE: usize = 52;
+
+/// Highest number of the P, BP and EX variables
+const MAX_POSITION_VARIABLE: u16 = 127;
 
 /// Handler for current position reading (0x75)
+///
+/// The instance selects the control group and coordinate type: robot (1-8), base (11-18)
+/// and station (21-44) pulse positions, and robot Cartesian positions (101-108), each
+/// answered from its own stored position. `Get_Attribute_All` with attribute 0 reads the
+/// whole position and `Get_Attribute_Single` one of the 13 data words.
 pub struct PositionHandler;
 
 impl CommandHandler for PositionHandler {
     fn handle(
         &self,
-        _message: &proto::HsesRequestMessage,
+        message: &proto::HsesRequestMessage,
         state: &mut MockState,
     ) -> Result<Vec<u8>, proto::ProtocolError> {
-        state.position.serialize()
+        let position = state
+            .current_position(message.sub_header.instance)
+            .ok_or_else(|| super::invalid_instance(message, "1-8, 11-18, 21-44, 101-108"))?;
+        // Unused axes are sent as 0
+        let mut data = position.serialize(state.text_encoding)?;
+        data.resize(CURRENT_POSITION_SIZE, 0);
+
+        let attribute = usize::from(message.sub_header.attribute);
+        match Service::try_from(message.sub_header.service)? {
+            Service::GetAll if attribute == 0 => Ok(data),
+            Service::GetSingle if (1..=CURRENT_POSITION_SIZE / 4).contains(&attribute) => {
+                Ok(data[(attribute - 1) * 4..attribute * 4].to_vec())
+            }
+            Service::GetAll | Service::GetSingle => Err(proto::ProtocolError::InvalidAttribute),
+            _ => Err(proto::ProtocolError::InvalidService),
+        }
     }
 }
 
-/// Handler for position variable operations (0x7f)
+/// Handler for robot position variable operations (0x7f)
 pub struct PositionVarHandler;
 
 impl CommandHandler for PositionVarHandler {
@@ -26,39 +55,7 @@ impl CommandHandler for PositionVarHandler {
         message: &proto::HsesRequestMessage,
         state: &mut MockState,
     ) -> Result<Vec<u8>, proto::ProtocolError> {
-        let service = message.sub_header.service;
-
-        match service {
-            0x01 => {
-                // GetAll
-                state.position.serialize()
-            }
-            0x02 => {
-                // SetAll
-                if message.payload.len() >= 52
-                    && let Ok(position) =
-                        proto::Position::deserialize(&message.payload, state.text_encoding)
-                {
-                    state.update_position(position);
-                }
-                Ok(vec![])
-            }
-            0x0e => {
-                // Read
-                state.position.serialize()
-            }
-            0x10 => {
-                // Write
-                if message.payload.len() >= 52
-                    && let Ok(position) =
-                        proto::Position::deserialize(&message.payload, state.text_encoding)
-                {
-                    state.update_position(position);
-                }
-                Ok(vec![])
-            }
-            _ => Err(proto::ProtocolError::InvalidService),
-        }
+        handle_position_variable(message, state, VariableType::Position)
     }
 }
 
@@ -71,45 +68,11 @@ impl CommandHandler for BasePositionVarHandler {
         message: &proto::HsesRequestMessage,
         state: &mut MockState,
     ) -> Result<Vec<u8>, proto::ProtocolError> {
-        let service = message.sub_header.service;
-
-        match service {
-            0x01 => {
-                // GetAll
-                state.position.serialize()
-            }
-            0x02 => {
-                // SetAll
-                if message.payload.len() >= 36 {
-                    // Parse base position data
-                    let mut data = vec![0u8; 52];
-                    data[0..36].copy_from_slice(&message.payload[0..36]);
-                    if let Ok(position) = proto::Position::deserialize(&data, state.text_encoding) {
-                        state.update_position(position);
-                    }
-                }
-                Ok(vec![])
-            }
-            0x0e => {
-                // Read
-                state.position.serialize()
-            }
-            0x10 => {
-                // Write
-                if message.payload.len() >= 52
-                    && let Ok(position) =
-                        proto::Position::deserialize(&message.payload, state.text_encoding)
-                {
-                    state.update_position(position);
-                }
-                Ok(vec![])
-            }
-            _ => Err(proto::ProtocolError::InvalidService),
-        }
+        handle_position_variable(message, state, VariableType::BasePosition)
     }
 }
 
-/// Handler for external axis variable operations (0x81)
+/// Handler for station (external axis) variable operations (0x81)
 pub struct ExternalAxisVarHandler;
 
 impl CommandHandler for ExternalAxisVarHandler {
@@ -118,41 +81,57 @@ impl CommandHandler for ExternalAxisVarHandler {
         message: &proto::HsesRequestMessage,
         state: &mut MockState,
     ) -> Result<Vec<u8>, proto::ProtocolError> {
-        let service = message.sub_header.service;
+        handle_position_variable(message, state, VariableType::ExternalAxis)
+    }
+}
 
-        match service {
-            0x01 => {
-                // GetAll
-                state.position.serialize()
-            }
-            0x02 => {
-                // SetAll
-                if message.payload.len() >= 36 {
-                    // Parse external axis data
-                    let mut data = vec![0u8; 52];
-                    data[0..36].copy_from_slice(&message.payload[0..36]);
-                    if let Ok(position) = proto::Position::deserialize(&data, state.text_encoding) {
-                        state.update_position(position);
-                    }
-                }
-                Ok(vec![])
-            }
-            0x0e => {
-                // Read
-                state.position.serialize()
-            }
-            0x10 => {
-                // Write
-                if message.payload.len() >= 52
-                    && let Ok(position) =
-                        proto::Position::deserialize(&message.payload, state.text_encoding)
-                {
-                    state.update_position(position);
-                }
-                Ok(vec![])
-            }
-            _ => Err(proto::ProtocolError::InvalidService),
+// Read or write a P, BP or EX variable
+//
+// Each variable is stored on its own as the raw data words (52 bytes for P, 36 bytes for BP
+// and EX); unset variables are 0, i.e. a pulse position at the origin. The whole variable is
+// read and written with attribute 0 and the `_All` services, one data word with attributes
+// from 1 and the `_Single` services.
+fn handle_position_variable(
+    message: &proto::HsesRequestMessage,
+    state: &mut MockState,
+    var_type: VariableType,
+) -> Result<Vec<u8>, proto::ProtocolError> {
+    let var_index = message.sub_header.instance;
+    if var_index > MAX_POSITION_VARIABLE {
+        return Err(super::invalid_instance(message, "0-127"));
+    }
+
+    let size = var_type.size();
+    let mut data = state.get_variable(var_type, var_index).cloned().unwrap_or_default();
+    data.resize(size, 0);
+    let attribute = usize::from(message.sub_header.attribute);
+    let word = if (1..=size / 4).contains(&attribute) { Some((attribute - 1) * 4) } else { None };
+    let expect_len = |expected: usize| {
+        if message.payload.len() == expected {
+            Ok(())
+        } else {
+            Err(proto::ProtocolError::UnexpectedLength { expected, got: message.payload.len() })
+        }
+    };
+
+    match (Service::try_from(message.sub_header.service)?, word) {
+        (Service::GetAll, None) if attribute == 0 => Ok(data),
+        (Service::GetSingle, Some(offset)) => Ok(data[offset..offset + 4].to_vec()),
+        (Service::SetAll, None) if attribute == 0 => {
+            expect_len(size)?;
+            state.set_variable(var_type, var_index, message.payload.clone());
+            Ok(vec![])
+        }
+        (Service::SetSingle, Some(offset)) => {
+            expect_len(4)?;
+            data[offset..offset + 4].copy_from_slice(&message.payload);
+            state.set_variable(var_type, var_index, data);
+            Ok(vec![])
+        }
+        (Service::GetAll | Service::GetSingle | Service::SetAll | Service::SetSingle, _) => {
+            Err(proto::ProtocolError::InvalidAttribute)
         }
+        _ => Err(proto::ProtocolError::InvalidService),
     }
 }
 
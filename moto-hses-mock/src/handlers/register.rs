@@ -3,6 +3,7 @@
 use super::CommandHandler;
 use crate::state::MockState;
 use moto_hses_proto as proto;
+use moto_hses_proto::Service;
 
 /// Handler for single register operations (0x79)
 pub struct RegisterHandler;
@@ -14,34 +15,30 @@ impl CommandHandler for RegisterHandler {
         state: &mut MockState,
     ) -> Result<Vec<u8>, proto::ProtocolError> {
         let reg_number = message.sub_header.instance;
-        let service = message.sub_header.service;
+        let service = Service::try_from(message.sub_header.service)?;
 
         // Validate register number range (0-999 for read, 0-559 for write)
         if reg_number > 999 {
-            return Err(proto::ProtocolError::InvalidInstance(format!(
-                "Invalid register number: {reg_number} (must be 0-999)"
-            )));
+            return Err(super::invalid_instance(message, "0-999"));
         }
 
         match service {
-            0x0e => {
+            Service::GetSingle => {
                 // Read - return 2 bytes (i16)
                 let value = state.get_register(reg_number);
                 Ok(value.to_le_bytes().to_vec())
             }
-            0x10 => {
+            Service::SetSingle => {
                 // Write - validate writable range (0-559)
                 if reg_number > 559 {
-                    return Err(proto::ProtocolError::InvalidInstance(format!(
-                        "Register {reg_number} is not writable (writable range: 0-559)"
-                    )));
+                    return Err(super::invalid_instance(message, "0-559 (writable)"));
                 }
 
                 if message.payload.len() != 2 {
-                    return Err(proto::ProtocolError::InvalidMessage(format!(
-                        "Invalid payload length for register write: expected 2 bytes, got {}",
-                        message.payload.len()
-                    )));
+                    return Err(proto::ProtocolError::UnexpectedLength {
+                        expected: 2,
+                        got: message.payload.len(),
+                    });
                 }
 
                 let value = i16::from_le_bytes([message.payload[0], message.payload[1]]);
@@ -63,7 +60,7 @@ impl CommandHandler for PluralRegisterHandler {
         state: &mut MockState,
     ) -> Result<Vec<u8>, proto::ProtocolError> {
         let start_register = message.sub_header.instance;
-        let service = message.sub_header.service;
+        let service = Service::try_from(message.sub_header.service)?;
 
         // Validate attribute (should be 0)
         if message.sub_header.attribute != 0 {
@@ -72,9 +69,7 @@ impl CommandHandler for PluralRegisterHandler {
 
         // Validate register number range (0-999)
         if start_register > 999 {
-            return Err(proto::ProtocolError::InvalidInstance(format!(
-                "Invalid register number: {start_register} (valid range: 0-999)"
-            )));
+            return Err(super::invalid_instance(message, "0-999"));
         }
 
         // Parse count from payload (first 4 bytes)
@@ -92,29 +87,25 @@ impl CommandHandler for PluralRegisterHandler {
             message.payload[3],
         ]);
 
-        // Validate count (max 237, must be > 0)
-        if count == 0 || count > 237 {
-            return Err(proto::ProtocolError::InvalidMessage(format!(
-                "Invalid count: {count} (must be 1-237)"
-            )));
+        // Validate count (max 237, must be > 0) and that the range ends at register 999
+        let max = usize::from(999 - start_register + 1).min(237);
+        if count == 0 || count as usize > max {
+            return Err(proto::ProtocolError::InvalidCount {
+                command: message.sub_header.command,
+                got: count as usize,
+                max,
+            });
         }
-
-        // Validate range doesn't exceed maximum register number
         let end_register = u32::from(start_register) + count - 1;
-        if end_register > 999 {
-            return Err(proto::ProtocolError::InvalidMessage(format!(
-                "Register range exceeds maximum: {start_register}-{end_register} (max 999)"
-            )));
-        }
 
         match service {
-            0x33 => {
+            Service::ReadPlural => {
                 // Read request must contain only count (4 bytes)
                 if message.payload.len() != 4 {
-                    return Err(proto::ProtocolError::InvalidMessage(format!(
-                        "Invalid payload length for plural register read: expected 4 bytes (count only), got {}",
-                        message.payload.len()
-                    )));
+                    return Err(proto::ProtocolError::UnexpectedLength {
+                        expected: 4,
+                        got: message.payload.len(),
+                    });
                 }
                 // Read - return count + register data
                 let values = state.get_multiple_registers(start_register, count as usize);
@@ -125,20 +116,26 @@ impl CommandHandler for PluralRegisterHandler {
                 }
                 Ok(response)
             }
-            0x34 => {
+            Service::WritePlural => {
                 // Write - validate payload length and update state
                 let expected_len = 4 + (count as usize * 2);
                 if message.payload.len() != expected_len {
-                    return Err(proto::ProtocolError::InvalidMessage(
-                        "Invalid payload length".to_string(),
-                    ));
+                    return Err(proto::ProtocolError::UnexpectedLength {
+                        expected: expected_len,
+                        got: message.payload.len(),
+                    });
                 }
 
                 // Only registers 0-559 are writable
-                if start_register > 559 || end_register > 559 {
-                    return Err(proto::ProtocolError::InvalidInstance(format!(
-                        "Register range {start_register}-{end_register} is not writable (writable range: 0-559)"
-                    )));
+                if start_register > 559 {
+                    return Err(super::invalid_instance(message, "0-559 (writable)"));
+                }
+                if end_register > 559 {
+                    return Err(proto::ProtocolError::InvalidCount {
+                        command: message.sub_header.command,
+                        got: count as usize,
+                        max: usize::from(559 - start_register + 1),
+                    });
                 }
 
                 // Parse register values
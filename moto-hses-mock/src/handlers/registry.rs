@@ -1,9 +1,11 @@
 //! Command handler registry
 
-use super::CommandHandler;
-use crate::state::MockState;
+use super::{AsyncCommandHandler, CommandHandler, HandlerResponse, SyncHandler};
+use crate::log_target;
+use crate::state::SharedState;
 use moto_hses_proto as proto;
-use std::sync::Arc;
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
 
 // Import all handlers
 use super::alarm::{AlarmDataHandler, AlarmInfoHandler, AlarmResetHandler};
@@ -28,10 +30,27 @@ use super::variable::{
     PluralRealVarHandler, RealVarHandler, StringVarHandler,
 };
 
+/// Answer to commands without a handler
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum UnknownCommandPolicy {
+    /// Reply with the undefined command status, as the controller does (default)
+    #[default]
+    Reject,
+    /// Reply with success and no data, warning once per command, e.g. to run a newer
+    /// client against the mock without failing on commands it does not simulate
+    Ignore,
+}
+
 /// Command handler registry
 #[derive(Clone)]
 pub struct CommandHandlerRegistry {
-    handlers: std::collections::HashMap<u16, Arc<dyn CommandHandler + Send + Sync>>,
+    handlers: std::collections::HashMap<u16, Arc<dyn AsyncCommandHandler + Send + Sync>>,
+    // Commands answered as undefined whatever the policy
+    unsupported: HashSet<u16>,
+    policy: UnknownCommandPolicy,
+    fallback: Option<Arc<dyn AsyncCommandHandler + Send + Sync>>,
+    // Unknown commands already warned about, shared by the clones of the server tasks
+    warned: Arc<Mutex<HashSet<u16>>>,
 }
 
 impl CommandHandlerRegistry {
@@ -40,111 +59,156 @@ impl CommandHandlerRegistry {
         let mut handlers = std::collections::HashMap::new();
 
         // File operations
-        handlers
-            .insert(0x00, Arc::new(FileControlHandler) as Arc<dyn CommandHandler + Send + Sync>);
+        handlers.insert(0x00, sync(FileControlHandler));
 
         // Alarm handlers
-        handlers.insert(0x70, Arc::new(AlarmDataHandler) as Arc<dyn CommandHandler + Send + Sync>);
-        handlers.insert(0x71, Arc::new(AlarmInfoHandler) as Arc<dyn CommandHandler + Send + Sync>);
-        handlers.insert(0x82, Arc::new(AlarmResetHandler) as Arc<dyn CommandHandler + Send + Sync>);
+        handlers.insert(0x70, sync(AlarmDataHandler));
+        handlers.insert(0x71, sync(AlarmInfoHandler));
+        handlers.insert(0x82, sync(AlarmResetHandler));
 
         // System information handlers
-        handlers.insert(0x72, Arc::new(StatusHandler) as Arc<dyn CommandHandler + Send + Sync>);
-        handlers.insert(
-            0x73,
-            Arc::new(ExecutingJobInfoHandler) as Arc<dyn CommandHandler + Send + Sync>,
-        );
-        handlers.insert(0x74, Arc::new(AxisNameHandler) as Arc<dyn CommandHandler + Send + Sync>);
-        handlers.insert(0x77, Arc::new(TorqueHandler) as Arc<dyn CommandHandler + Send + Sync>);
-        handlers
-            .insert(0x85, Arc::new(TextDisplayHandler) as Arc<dyn CommandHandler + Send + Sync>);
-        handlers
-            .insert(0x88, Arc::new(ManagementTimeHandler) as Arc<dyn CommandHandler + Send + Sync>);
-        handlers.insert(0x89, Arc::new(SystemInfoHandler) as Arc<dyn CommandHandler + Send + Sync>);
+        handlers.insert(0x72, sync(StatusHandler));
+        handlers.insert(0x73, sync(ExecutingJobInfoHandler));
+        handlers.insert(0x74, sync(AxisNameHandler));
+        handlers.insert(0x77, sync(TorqueHandler));
+        handlers.insert(0x85, sync(TextDisplayHandler));
+        handlers.insert(0x88, sync(ManagementTimeHandler));
+        handlers.insert(0x89, sync(SystemInfoHandler));
 
         // Position handlers
-        handlers.insert(0x75, Arc::new(PositionHandler) as Arc<dyn CommandHandler + Send + Sync>);
-        handlers
-            .insert(0x76, Arc::new(PositionErrorHandler) as Arc<dyn CommandHandler + Send + Sync>);
-        handlers
-            .insert(0x7f, Arc::new(PositionVarHandler) as Arc<dyn CommandHandler + Send + Sync>);
-        handlers.insert(
-            0x80,
-            Arc::new(BasePositionVarHandler) as Arc<dyn CommandHandler + Send + Sync>,
-        );
-        handlers.insert(
-            0x81,
-            Arc::new(ExternalAxisVarHandler) as Arc<dyn CommandHandler + Send + Sync>,
-        );
+        handlers.insert(0x75, sync(PositionHandler));
+        handlers.insert(0x76, sync(PositionErrorHandler));
+        handlers.insert(0x7f, sync(PositionVarHandler));
+        handlers.insert(0x80, sync(BasePositionVarHandler));
+        handlers.insert(0x81, sync(ExternalAxisVarHandler));
 
         // I/O handlers
-        handlers.insert(0x78, Arc::new(IoHandler) as Arc<dyn CommandHandler + Send + Sync>);
-        handlers.insert(0x300, Arc::new(PluralIoHandler) as Arc<dyn CommandHandler + Send + Sync>);
+        handlers.insert(0x78, sync(IoHandler));
+        handlers.insert(0x300, sync(PluralIoHandler));
 
         // Register handlers
-        handlers.insert(0x79, Arc::new(RegisterHandler) as Arc<dyn CommandHandler + Send + Sync>);
-        handlers.insert(
-            0x301,
-            Arc::new(PluralRegisterHandler) as Arc<dyn CommandHandler + Send + Sync>,
-        );
+        handlers.insert(0x79, sync(RegisterHandler));
+        handlers.insert(0x301, sync(PluralRegisterHandler));
 
         // Variable handlers
-        handlers.insert(0x7a, Arc::new(ByteVarHandler) as Arc<dyn CommandHandler + Send + Sync>);
-        handlers.insert(0x7b, Arc::new(IntegerVarHandler) as Arc<dyn CommandHandler + Send + Sync>);
-        handlers.insert(0x7c, Arc::new(DoubleVarHandler) as Arc<dyn CommandHandler + Send + Sync>);
-        handlers.insert(0x7d, Arc::new(RealVarHandler) as Arc<dyn CommandHandler + Send + Sync>);
-        handlers.insert(0x7e, Arc::new(StringVarHandler) as Arc<dyn CommandHandler + Send + Sync>);
-        handlers
-            .insert(0x302, Arc::new(PluralByteVarHandler) as Arc<dyn CommandHandler + Send + Sync>);
-        handlers.insert(
-            0x303,
-            Arc::new(PluralIntegerVarHandler) as Arc<dyn CommandHandler + Send + Sync>,
-        );
-        handlers.insert(
-            0x304,
-            Arc::new(PluralDoubleVarHandler) as Arc<dyn CommandHandler + Send + Sync>,
-        );
-        handlers
-            .insert(0x305, Arc::new(PluralRealVarHandler) as Arc<dyn CommandHandler + Send + Sync>);
-        handlers.insert(
-            0x306,
-            Arc::new(PluralCharacterVarHandler) as Arc<dyn CommandHandler + Send + Sync>,
-        );
+        handlers.insert(0x7a, sync(ByteVarHandler));
+        handlers.insert(0x7b, sync(IntegerVarHandler));
+        handlers.insert(0x7c, sync(DoubleVarHandler));
+        handlers.insert(0x7d, sync(RealVarHandler));
+        handlers.insert(0x7e, sync(StringVarHandler));
+        handlers.insert(0x302, sync(PluralByteVarHandler));
+        handlers.insert(0x303, sync(PluralIntegerVarHandler));
+        handlers.insert(0x304, sync(PluralDoubleVarHandler));
+        handlers.insert(0x305, sync(PluralRealVarHandler));
+        handlers.insert(0x306, sync(PluralCharacterVarHandler));
 
         // Job and movement handlers
-        handlers.insert(0x83, Arc::new(HoldServoHandler) as Arc<dyn CommandHandler + Send + Sync>);
-        handlers.insert(
-            0x84,
-            Arc::new(CycleModeSwitchingHandler) as Arc<dyn CommandHandler + Send + Sync>,
-        );
-        handlers.insert(0x86, Arc::new(JobStartHandler) as Arc<dyn CommandHandler + Send + Sync>);
-        handlers.insert(0x87, Arc::new(JobSelectHandler) as Arc<dyn CommandHandler + Send + Sync>);
-        handlers.insert(0x8a, Arc::new(MovHandler) as Arc<dyn CommandHandler + Send + Sync>);
-        handlers.insert(0x8b, Arc::new(PmovHandler) as Arc<dyn CommandHandler + Send + Sync>);
-
-        Self { handlers }
+        handlers.insert(0x83, sync(HoldServoHandler));
+        handlers.insert(0x84, sync(CycleModeSwitchingHandler));
+        handlers.insert(0x86, sync(JobStartHandler));
+        handlers.insert(0x87, sync(JobSelectHandler));
+        handlers.insert(0x8a, sync(MovHandler));
+        handlers.insert(0x8b, sync(PmovHandler));
+
+        Self {
+            handlers,
+            unsupported: HashSet::new(),
+            policy: UnknownCommandPolicy::default(),
+            fallback: None,
+            warned: Arc::default(),
+        }
+    }
+
+    /// Handle `command` with `handler`, replacing the built-in handler if any
+    pub fn register(&mut self, command: u16, handler: Arc<dyn AsyncCommandHandler + Send + Sync>) {
+        self.unsupported.remove(&command);
+        self.handlers.insert(command, handler);
     }
 
-    /// # Errors
+    /// Answer `command` as undefined, removing its handler
     ///
-    /// Returns an error if command handling fails
-    pub fn handle(
+    /// Unlike other commands without a handler, it is neither ignored nor passed to the
+    /// fallback handler.
+    pub fn unregister(&mut self, command: u16) {
+        self.handlers.remove(&command);
+        self.unsupported.insert(command);
+    }
+
+    /// Answer commands without a handler as `policy` says
+    pub const fn set_unknown_command_policy(&mut self, policy: UnknownCommandPolicy) {
+        self.policy = policy;
+    }
+
+    /// Pass commands without a handler to `handler`, overriding the unknown command policy
+    pub fn set_fallback(&mut self, handler: Arc<dyn AsyncCommandHandler + Send + Sync>) {
+        self.fallback = Some(handler);
+    }
+
+    /// Build the response to a message; commands without a handler are answered by the
+    /// fallback handler or as the unknown command policy says
+    pub async fn respond(
         &self,
         message: &proto::HsesRequestMessage,
-        state: &mut MockState,
-    ) -> Result<Vec<u8>, proto::ProtocolError> {
+        state: &SharedState,
+    ) -> HandlerResponse {
         let command = message.sub_header.command;
 
-        self.handlers.get(&command).map_or_else(
-            || {
-                debug!("Unknown command: 0x{command:04x}");
-                Err(proto::ProtocolError::InvalidCommand)
-            },
-            |handler| handler.handle(message, state),
-        )
+        // Simulated jobs finish between requests, before the controller state is observed
+        let log_level = {
+            let mut state = state.write().await;
+            state.advance_job_simulation();
+            state.advance_motion_simulation();
+            state.log_level
+        };
+
+        if let Some(handler) = self.handlers.get(&command) {
+            return handler.respond(message, state).await;
+        }
+        if self.unsupported.contains(&command) {
+            mock_log!(
+                log_level,
+                Debug,
+                log_target::HANDLERS,
+                "Unsupported command: 0x{command:04x}"
+            );
+            return HandlerResponse::from(proto::ProtocolError::InvalidCommand);
+        }
+        if let Some(fallback) = &self.fallback {
+            return fallback.respond(message, state).await;
+        }
+        match self.policy {
+            UnknownCommandPolicy::Reject => {
+                mock_log!(
+                    log_level,
+                    Debug,
+                    log_target::HANDLERS,
+                    "Unknown command: 0x{command:04x}"
+                );
+                HandlerResponse::from(proto::ProtocolError::InvalidCommand)
+            }
+            UnknownCommandPolicy::Ignore => {
+                let first = self.warned.lock().map_or(true, |mut warned| warned.insert(command));
+                if first {
+                    mock_log!(
+                        log_level,
+                        Warn,
+                        log_target::HANDLERS,
+                        "Ignoring unknown command 0x{command:04x}"
+                    );
+                }
+                HandlerResponse::ok(Vec::new())
+            }
+        }
     }
 }
 
+// Built-in handler run through the sync adapter
+fn sync(
+    handler: impl CommandHandler + Send + Sync + 'static,
+) -> Arc<dyn AsyncCommandHandler + Send + Sync> {
+    Arc::new(SyncHandler(handler))
+}
+
 impl Default for CommandHandlerRegistry {
     fn default() -> Self {
         Self::new()
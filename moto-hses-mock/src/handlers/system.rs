@@ -3,6 +3,8 @@
 use super::CommandHandler;
 use crate::state::MockState;
 use moto_hses_proto as proto;
+use moto_hses_proto::Service;
+use proto::HsesPayload;
 
 /// Handler for status reading (0x72)
 pub struct StatusHandler;
@@ -15,19 +17,14 @@ impl CommandHandler for StatusHandler {
     ) -> Result<Vec<u8>, proto::ProtocolError> {
         use moto_hses_proto::HsesPayload;
 
-        let attribute = message.sub_header.attribute;
-        let mut data = match attribute {
-            1 => state.status.data1.serialize(state.text_encoding)?,
-            2 => state.status.data2.serialize(state.text_encoding)?,
-            _ => state.status.serialize(state.text_encoding)?, // Default to complete status
-        };
-
-        // Extend to 8 bytes if needed
-        if data.len() < 8 {
-            data.extend(vec![0u8; 8 - data.len()]);
+        // Attribute 0 (Get_Attribute_All) returns both 4-byte words, attributes 1 and 2
+        // (Get_Attribute_Single) one word each
+        match message.sub_header.attribute {
+            0 => state.status.serialize(state.text_encoding),
+            1 => state.status.data1.serialize(state.text_encoding),
+            2 => state.status.data2.serialize(state.text_encoding),
+            _ => Err(proto::ProtocolError::InvalidAttribute),
         }
-
-        Ok(data)
     }
 }
 
@@ -82,6 +79,11 @@ impl CommandHandler for TorqueHandler {
 }
 
 /// Handler for management time acquisition (0x88)
+///
+/// Standard instances report the power-on time of the mock as operation start time
+/// (`YYYY/MM/DD HH:MM`, 16 bytes) and the time since then as elapse time
+/// (`HHHHHH:MM'SS`, 12 bytes), both following the controller clock. Like a controller, the
+/// mock has no instance reporting the clock itself.
 pub struct ManagementTimeHandler;
 
 impl CommandHandler for ManagementTimeHandler {
@@ -90,22 +92,30 @@ impl CommandHandler for ManagementTimeHandler {
         _message: &proto::HsesRequestMessage,
         state: &mut MockState,
     ) -> Result<Vec<u8>, proto::ProtocolError> {
-        let mut data = vec![0u8; 32];
-
-        // Set some default time values as strings
-        let start_time = "1234567890";
-        let elapse_time = "987654321";
-
-        // Copy start time (16 bytes)
+        let elapsed = state.power_on_at.elapsed().as_secs();
+        let now = state.controller_time().to_unix_seconds();
+        let start =
+            proto::ControllerDateTime::from_unix_seconds(now - i64::try_from(elapsed).unwrap_or(0))
+                .unwrap_or_default();
+        let start_time = format!(
+            "{:04}/{:02}/{:02} {:02}:{:02}",
+            start.year, start.month, start.day, start.hour, start.minute
+        );
+        let elapse_time =
+            format!("{:06}:{:02}'{:02}", elapsed / 3600, elapsed / 60 % 60, elapsed % 60);
+
+        let mut data = vec![0u8; 28];
+
+        // Operation start time (16 bytes)
         let start_bytes =
-            moto_hses_proto::encoding_utils::encode_string(start_time, state.text_encoding);
-        let start_len = start_bytes.len().min(15);
+            moto_hses_proto::encoding_utils::encode_string(&start_time, state.text_encoding);
+        let start_len = start_bytes.len().min(16);
         data[0..start_len].copy_from_slice(&start_bytes[0..start_len]);
 
-        // Copy elapse time (16 bytes)
+        // Elapse time (12 bytes)
         let elapse_bytes =
-            moto_hses_proto::encoding_utils::encode_string(elapse_time, state.text_encoding);
-        let elapse_len = elapse_bytes.len().min(15);
+            moto_hses_proto::encoding_utils::encode_string(&elapse_time, state.text_encoding);
+        let elapse_len = elapse_bytes.len().min(12);
         data[16..16 + elapse_len].copy_from_slice(&elapse_bytes[0..elapse_len]);
 
         Ok(data)
@@ -118,33 +128,13 @@ pub struct SystemInfoHandler;
 impl CommandHandler for SystemInfoHandler {
     fn handle(
         &self,
-        _message: &proto::HsesRequestMessage,
+        message: &proto::HsesRequestMessage,
         state: &mut MockState,
     ) -> Result<Vec<u8>, proto::ProtocolError> {
-        let mut data = vec![0u8; 48];
-
-        // Software version (16 bytes)
-        let version = "V1.0.0";
-        let version_bytes =
-            moto_hses_proto::encoding_utils::encode_string(version, state.text_encoding);
-        let len = version_bytes.len().min(15);
-        data[0..len].copy_from_slice(&version_bytes[0..len]);
-
-        // Model (16 bytes)
-        let model = "FS100";
-        let model_bytes =
-            moto_hses_proto::encoding_utils::encode_string(model, state.text_encoding);
-        let len = model_bytes.len().min(15);
-        data[16..16 + len].copy_from_slice(&model_bytes[0..len]);
-
-        // Parameter version (16 bytes)
-        let param_version = "P1.0.0";
-        let param_version_bytes =
-            moto_hses_proto::encoding_utils::encode_string(param_version, state.text_encoding);
-        let len = param_version_bytes.len().min(15);
-        data[32..32 + len].copy_from_slice(&param_version_bytes[0..len]);
-
-        Ok(data)
+        if message.sub_header.service != Service::GetAll.code() {
+            return Err(proto::ProtocolError::InvalidService);
+        }
+        state.system_info.serialize(state.text_encoding)
     }
 }
 
@@ -157,10 +147,10 @@ impl CommandHandler for TextDisplayHandler {
         message: &proto::HsesRequestMessage,
         _state: &mut MockState,
     ) -> Result<Vec<u8>, proto::ProtocolError> {
-        let service = message.sub_header.service;
+        let service = Service::try_from(message.sub_header.service)?;
 
         match service {
-            0x10 => {
+            Service::SetSingle => {
                 // Write
                 // Just acknowledge the text display command
                 Ok(vec![])
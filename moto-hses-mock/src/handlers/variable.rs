@@ -3,6 +3,7 @@
 use super::CommandHandler;
 use crate::state::{MockState, VariableType};
 use moto_hses_proto as proto;
+use moto_hses_proto::Service;
 
 /// Handler for byte variable operations (0x7a)
 pub struct ByteVarHandler;
@@ -13,43 +14,7 @@ impl CommandHandler for ByteVarHandler {
         message: &proto::HsesRequestMessage,
         state: &mut MockState,
     ) -> Result<Vec<u8>, proto::ProtocolError> {
-        let var_index = message.sub_header.instance; // Direct use since instance is u16
-        let service = message.sub_header.service;
-
-        // Validate variable index range (0-99 for B variables)
-        if var_index > 99 {
-            return Err(proto::ProtocolError::InvalidInstance(format!(
-                "Invalid variable index: {var_index} (valid range: 0-99)"
-            )));
-        }
-
-        match service {
-            0x0e => {
-                // Read
-                state.get_variable(VariableType::Byte, var_index).map_or_else(
-                    || {
-                        // B variable: 1 byte (actual data type size)
-                        Ok(vec![0])
-                    },
-                    |value| {
-                        if value.is_empty() {
-                            Ok(vec![0])
-                        } else {
-                            // Return actual data type size (1 byte for B variable)
-                            Ok(vec![value[0]])
-                        }
-                    },
-                )
-            }
-            0x10 => {
-                // Write
-                if !message.payload.is_empty() {
-                    state.set_variable(VariableType::Byte, var_index, message.payload.clone());
-                }
-                Ok(vec![])
-            }
-            _ => Err(proto::ProtocolError::InvalidService),
-        }
+        handle_scalar_variable(message, state, VariableType::Byte)
     }
 }
 
@@ -62,43 +27,7 @@ impl CommandHandler for IntegerVarHandler {
         message: &proto::HsesRequestMessage,
         state: &mut MockState,
     ) -> Result<Vec<u8>, proto::ProtocolError> {
-        let var_index = message.sub_header.instance; // Direct use since instance is u16
-        let service = message.sub_header.service;
-
-        // Validate variable index range (0-99 for I variables)
-        if var_index > 99 {
-            return Err(proto::ProtocolError::InvalidInstance(format!(
-                "Invalid variable index: {var_index} (valid range: 0-99)"
-            )));
-        }
-
-        match service {
-            0x0e => {
-                // Read
-                state.get_variable(VariableType::Integer, var_index).map_or_else(
-                    || {
-                        // I variable: 2 bytes (actual data type size)
-                        Ok(vec![0, 0])
-                    },
-                    |value| {
-                        if value.len() >= 2 {
-                            // Return actual data type size (2 bytes for I variable)
-                            Ok(value[0..2].to_vec())
-                        } else {
-                            Ok(vec![0, 0])
-                        }
-                    },
-                )
-            }
-            0x10 => {
-                // Write
-                if !message.payload.is_empty() {
-                    state.set_variable(VariableType::Integer, var_index, message.payload.clone());
-                }
-                Ok(vec![])
-            }
-            _ => Err(proto::ProtocolError::InvalidService),
-        }
+        handle_scalar_variable(message, state, VariableType::Integer)
     }
 }
 
@@ -111,43 +40,7 @@ impl CommandHandler for DoubleVarHandler {
         message: &proto::HsesRequestMessage,
         state: &mut MockState,
     ) -> Result<Vec<u8>, proto::ProtocolError> {
-        let var_index = message.sub_header.instance; // Direct use since instance is u16
-        let service = message.sub_header.service;
-
-        // Validate variable index range (0-99 for D variables)
-        if var_index > 99 {
-            return Err(proto::ProtocolError::InvalidInstance(format!(
-                "Invalid variable index: {var_index} (valid range: 0-99)"
-            )));
-        }
-
-        match service {
-            0x0e => {
-                // Read
-                state.get_variable(VariableType::Double, var_index).map_or_else(
-                    || {
-                        // Return 4 bytes for 32-bit integer variable
-                        Ok(vec![0, 0, 0, 0])
-                    },
-                    |value| {
-                        // Protocol specification: 4 bytes for 32-bit integer (D variable)
-                        if value.len() >= 4 {
-                            Ok(value[0..4].to_vec())
-                        } else {
-                            Ok(vec![0, 0, 0, 0])
-                        }
-                    },
-                )
-            }
-            0x10 => {
-                // Write
-                if !message.payload.is_empty() {
-                    state.set_variable(VariableType::Double, var_index, message.payload.clone());
-                }
-                Ok(vec![])
-            }
-            _ => Err(proto::ProtocolError::InvalidService),
-        }
+        handle_scalar_variable(message, state, VariableType::Double)
     }
 }
 
@@ -160,46 +53,46 @@ impl CommandHandler for RealVarHandler {
         message: &proto::HsesRequestMessage,
         state: &mut MockState,
     ) -> Result<Vec<u8>, proto::ProtocolError> {
-        let var_index = message.sub_header.instance; // Direct use since instance is u16
-        let service = message.sub_header.service;
+        handle_scalar_variable(message, state, VariableType::Real)
+    }
+}
 
-        // Validate variable index range (0-99 for R variables)
-        if var_index > 99 {
-            return Err(proto::ProtocolError::InvalidInstance(format!(
-                "Invalid variable index: {var_index} (valid range: 0-99)"
-            )));
-        }
+// Read or write a B, I, D or R variable
+//
+// The data part is exactly the size of the variable (1, 2, 4 and 4 bytes); writes of any
+// other size are rejected rather than truncated or padded.
+fn handle_scalar_variable(
+    message: &proto::HsesRequestMessage,
+    state: &mut MockState,
+    var_type: VariableType,
+) -> Result<Vec<u8>, proto::ProtocolError> {
+    let var_index = message.sub_header.instance; // Direct use since instance is u16
+    let size = var_type.size();
+
+    // Validate variable index range (0-99)
+    if var_index > 99 {
+        return Err(super::invalid_instance(message, "0-99"));
+    }
 
-        match service {
-            0x0e => {
-                // Read
-                state.get_variable(VariableType::Real, var_index).map_or_else(
-                    || {
-                        // Return 4 bytes for real variable as expected by Python client
-                        Ok(vec![0, 0, 0, 0])
-                    },
-                    |value| {
-                        // Python client expects 4 bytes
-                        if value.len() >= 4 {
-                            Ok(value[0..4].to_vec())
-                        } else {
-                            // Extend existing value to 4 bytes
-                            let mut extended_value = value.clone();
-                            extended_value.extend(vec![0u8; 4 - value.len()]);
-                            Ok(extended_value)
-                        }
-                    },
-                )
-            }
-            0x10 => {
-                // Write
-                if !message.payload.is_empty() {
-                    state.set_variable(VariableType::Real, var_index, message.payload.clone());
-                }
-                Ok(vec![])
+    match Service::try_from(message.sub_header.service)? {
+        Service::GetSingle => {
+            // Read; unset variables are 0
+            let mut value = state.get_variable(var_type, var_index).cloned().unwrap_or_default();
+            value.resize(size, 0);
+            Ok(value)
+        }
+        Service::SetSingle => {
+            // Write
+            if message.payload.len() != size {
+                return Err(proto::ProtocolError::UnexpectedLength {
+                    expected: size,
+                    got: message.payload.len(),
+                });
             }
-            _ => Err(proto::ProtocolError::InvalidService),
+            state.set_variable(var_type, var_index, message.payload.clone());
+            Ok(vec![])
         }
+        _ => Err(proto::ProtocolError::InvalidService),
     }
 }
 
@@ -213,17 +106,15 @@ impl CommandHandler for StringVarHandler {
         state: &mut MockState,
     ) -> Result<Vec<u8>, proto::ProtocolError> {
         let var_index = message.sub_header.instance; // Direct use since instance is u16
-        let service = message.sub_header.service;
+        let service = Service::try_from(message.sub_header.service)?;
 
         // Validate variable index range (0-99 for S variables)
         if var_index > 99 {
-            return Err(proto::ProtocolError::InvalidInstance(format!(
-                "Invalid variable index: {var_index} (valid range: 0-99)"
-            )));
+            return Err(super::invalid_instance(message, "0-99"));
         }
 
         match service {
-            0x0e => {
+            Service::GetSingle => {
                 // Read
                 state.get_variable(VariableType::String, var_index).map_or_else(
                     || {
@@ -240,7 +131,7 @@ impl CommandHandler for StringVarHandler {
                     },
                 )
             }
-            0x10 => {
+            Service::SetSingle => {
                 // Write
                 if message.payload.len() >= 16 {
                     // Store the full 16-byte S variable data, but trim trailing nulls for storage
@@ -279,7 +170,7 @@ impl CommandHandler for PluralByteVarHandler {
         state: &mut MockState,
     ) -> Result<Vec<u8>, proto::ProtocolError> {
         let start_variable = message.sub_header.instance;
-        let service = message.sub_header.service;
+        let service = Service::try_from(message.sub_header.service)?;
 
         // Validate attribute (should be 0)
         if message.sub_header.attribute != 0 {
@@ -310,20 +201,21 @@ impl CommandHandler for PluralByteVarHandler {
         }
 
         match service {
-            0x33 => {
+            Service::ReadPlural => {
                 // Read - return count + variable data
                 let values = state.get_multiple_byte_variables(start_variable, count as usize);
                 let mut response = count.to_le_bytes().to_vec();
                 response.extend_from_slice(&values);
                 Ok(response)
             }
-            0x34 => {
+            Service::WritePlural => {
                 // Write - validate payload length and update state
                 let expected_len = 4 + count as usize;
                 if message.payload.len() != expected_len {
-                    return Err(proto::ProtocolError::InvalidMessage(
-                        "Invalid payload length".to_string(),
-                    ));
+                    return Err(proto::ProtocolError::UnexpectedLength {
+                        expected: expected_len,
+                        got: message.payload.len(),
+                    });
                 }
 
                 // Parse variable values (1 byte each)
@@ -349,7 +241,7 @@ impl CommandHandler for PluralIntegerVarHandler {
         state: &mut MockState,
     ) -> Result<Vec<u8>, proto::ProtocolError> {
         let start_variable = message.sub_header.instance;
-        let service = message.sub_header.service;
+        let service = Service::try_from(message.sub_header.service)?;
 
         // Validate attribute (should be 0)
         if message.sub_header.attribute != 0 {
@@ -373,13 +265,15 @@ impl CommandHandler for PluralIntegerVarHandler {
 
         // Validate count (max 237, must be > 0)
         if count == 0 || count > 237 {
-            return Err(proto::ProtocolError::InvalidMessage(format!(
-                "Invalid count: {count} for start_variable {start_variable} (must be 1-237)"
-            )));
+            return Err(proto::ProtocolError::InvalidCount {
+                command: message.sub_header.command,
+                got: count as usize,
+                max: 237,
+            });
         }
 
         match service {
-            0x33 => {
+            Service::ReadPlural => {
                 // Read - return count + variable data
                 let values = state.get_multiple_integer_variables(start_variable, count as usize);
                 let mut response = count.to_le_bytes().to_vec();
@@ -388,14 +282,14 @@ impl CommandHandler for PluralIntegerVarHandler {
                 }
                 Ok(response)
             }
-            0x34 => {
+            Service::WritePlural => {
                 // Write - validate payload length and update state
                 let expected_len = 4 + (count as usize * 2);
                 if message.payload.len() != expected_len {
-                    return Err(proto::ProtocolError::InvalidMessage(format!(
-                        "Invalid payload length: got {} bytes, expected {expected_len}",
-                        message.payload.len()
-                    )));
+                    return Err(proto::ProtocolError::UnexpectedLength {
+                        expected: expected_len,
+                        got: message.payload.len(),
+                    });
                 }
 
                 // Parse variable values (2 bytes each)
@@ -427,7 +321,7 @@ impl CommandHandler for PluralDoubleVarHandler {
         state: &mut MockState,
     ) -> Result<Vec<u8>, proto::ProtocolError> {
         let start_variable = message.sub_header.instance;
-        let service = message.sub_header.service;
+        let service = Service::try_from(message.sub_header.service)?;
 
         // Validate attribute (should be 0)
         if message.sub_header.attribute != 0 {
@@ -451,13 +345,15 @@ impl CommandHandler for PluralDoubleVarHandler {
 
         // Validate count (max 118, must be > 0)
         if count == 0 || count > 118 {
-            return Err(proto::ProtocolError::InvalidMessage(format!(
-                "Invalid count: {count} for start_variable {start_variable} (must be 1-118)"
-            )));
+            return Err(proto::ProtocolError::InvalidCount {
+                command: message.sub_header.command,
+                got: count as usize,
+                max: 118,
+            });
         }
 
         match service {
-            0x33 => {
+            Service::ReadPlural => {
                 // Read - return count + variable data
                 let values = state.get_multiple_double_variables(start_variable, count as usize);
                 let mut response = count.to_le_bytes().to_vec();
@@ -466,14 +362,14 @@ impl CommandHandler for PluralDoubleVarHandler {
                 }
                 Ok(response)
             }
-            0x34 => {
+            Service::WritePlural => {
                 // Write - validate payload length and update state
                 let expected_len = 4 + (count as usize * 4);
                 if message.payload.len() != expected_len {
-                    return Err(proto::ProtocolError::InvalidMessage(format!(
-                        "Invalid payload length: got {} bytes, expected {expected_len}",
-                        message.payload.len()
-                    )));
+                    return Err(proto::ProtocolError::UnexpectedLength {
+                        expected: expected_len,
+                        got: message.payload.len(),
+                    });
                 }
 
                 // Parse variable values (4 bytes each)
@@ -509,7 +405,7 @@ impl CommandHandler for PluralRealVarHandler {
         state: &mut MockState,
     ) -> Result<Vec<u8>, proto::ProtocolError> {
         let start_variable = message.sub_header.instance;
-        let service = message.sub_header.service;
+        let service = Service::try_from(message.sub_header.service)?;
 
         // Validate attribute (should be 0)
         if message.sub_header.attribute != 0 {
@@ -533,13 +429,15 @@ impl CommandHandler for PluralRealVarHandler {
 
         // Validate count (max 118, must be > 0)
         if count == 0 || count > 118 {
-            return Err(proto::ProtocolError::InvalidMessage(format!(
-                "Invalid count: {count} for start_variable {start_variable} (must be 1-118)"
-            )));
+            return Err(proto::ProtocolError::InvalidCount {
+                command: message.sub_header.command,
+                got: count as usize,
+                max: 118,
+            });
         }
 
         match service {
-            0x33 => {
+            Service::ReadPlural => {
                 // Read - return count + variable data
                 let values = state.get_multiple_real_variables(start_variable, count as usize);
                 let mut response = count.to_le_bytes().to_vec();
@@ -548,14 +446,14 @@ impl CommandHandler for PluralRealVarHandler {
                 }
                 Ok(response)
             }
-            0x34 => {
+            Service::WritePlural => {
                 // Write - validate payload length and update state
                 let expected_len = 4 + (count as usize * 4);
                 if message.payload.len() != expected_len {
-                    return Err(proto::ProtocolError::InvalidMessage(format!(
-                        "Invalid payload length: got {} bytes, expected {expected_len}",
-                        message.payload.len()
-                    )));
+                    return Err(proto::ProtocolError::UnexpectedLength {
+                        expected: expected_len,
+                        got: message.payload.len(),
+                    });
                 }
 
                 // Parse variable values (4 bytes each)
@@ -591,7 +489,7 @@ impl CommandHandler for PluralCharacterVarHandler {
         state: &mut MockState,
     ) -> Result<Vec<u8>, proto::ProtocolError> {
         let start_variable = message.sub_header.instance;
-        let service = message.sub_header.service;
+        let service = Service::try_from(message.sub_header.service)?;
 
         // Validate attribute (should be 0)
         if message.sub_header.attribute != 0 {
@@ -615,13 +513,15 @@ impl CommandHandler for PluralCharacterVarHandler {
 
         // Validate count (max 29, must be > 0)
         if count == 0 || count > 29 {
-            return Err(proto::ProtocolError::InvalidMessage(format!(
-                "Invalid count: {count} for start_variable {start_variable} (must be 1-29)"
-            )));
+            return Err(proto::ProtocolError::InvalidCount {
+                command: message.sub_header.command,
+                got: count as usize,
+                max: 29,
+            });
         }
 
         match service {
-            0x33 => {
+            Service::ReadPlural => {
                 // Read - return count + variable data
                 let values = state.get_multiple_character_variables(start_variable, count as usize);
                 let mut response = count.to_le_bytes().to_vec();
@@ -630,14 +530,14 @@ impl CommandHandler for PluralCharacterVarHandler {
                 }
                 Ok(response)
             }
-            0x34 => {
+            Service::WritePlural => {
                 // Write - validate payload length and update state
                 let expected_len = 4 + (count as usize * 16);
                 if message.payload.len() != expected_len {
-                    return Err(proto::ProtocolError::InvalidMessage(format!(
-                        "Invalid payload length: got {} bytes, expected {expected_len}",
-                        message.payload.len()
-                    )));
+                    return Err(proto::ProtocolError::UnexpectedLength {
+                        expected: expected_len,
+                        got: message.payload.len(),
+                    });
                 }
 
                 // Parse variable values (16 bytes each)
@@ -4,16 +4,84 @@
 extern crate log;
 
 use moto_hses_proto as proto;
+use proto::commands::io::IoCategory;
+use random::SplitMix64;
 use std::collections::HashMap;
 use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
+/// Targets of the log records of the mock server
+///
+/// Filter on them to keep CI output readable, e.g. `RUST_LOG=moto_hses_mock::traffic=off`
+/// drops the frame dumps but keeps the handler and server records.
+pub mod log_target {
+    /// Sockets, tasks and the watchdog
+    pub const SERVER: &str = "moto_hses_mock::server";
+    /// Dumps of the frames received and sent, and injected faults
+    pub const TRAFFIC: &str = "moto_hses_mock::traffic";
+    /// Command handling, e.g. unknown commands and error responses
+    pub const HANDLERS: &str = "moto_hses_mock::handlers";
+    /// File storage and block transfers
+    pub const FILE: &str = "moto_hses_mock::file";
+}
+
+// Log at `level` to `target` when `level` is within the `max` level filter of the mock
+macro_rules! mock_log {
+    ($max:expr, $level:ident, $target:expr, $($arg:tt)+) => {
+        if ::log::Level::$level <= $max {
+            ::log::log!(target: $target, ::log::Level::$level, $($arg)+);
+        }
+    };
+}
+
+pub mod controller;
+pub mod handle;
 pub mod handlers;
+mod random;
 pub mod server;
 pub mod state;
+mod task;
+
+pub use controller::{MockController, Reply};
+pub use handle::{MockHandle, VariableRef};
+pub use handlers::{
+    AsyncCommandHandler, CommandHandler, HandlerResponse, SyncHandler, UnknownCommandPolicy,
+};
+pub use server::{MockServer, MockServerBuilder};
+pub use state::{
+    ChaosConfig, IoForceConflict, JobSimulation, MockMetrics, MockState, Motion, MotionSimulation,
+    PayloadFault, RecordedRequest, ResponseFault, SecurityMode, StateWriteGuard, TypedVariables,
+    VariableType,
+};
+
+/// Environment variable fixing the seed returned by [`test_seed`], e.g. to replay a
+/// failed CI run
+pub const SEED_ENV: &str = "MOTO_HSES_MOCK_SEED";
+
+// Distinguishes the seeds drawn in the same clock tick
+static SEED_COUNTER: AtomicU64 = AtomicU64::new(0);
 
-pub use handlers::CommandHandler;
-pub use server::MockServer;
-pub use state::{MockState, TypedVariables, VariableType};
+/// Seed of a test run: the value of [`SEED_ENV`] if set, otherwise drawn from the clock
+///
+/// Every random draw of a mock follows from its [`MockConfig::seed`], which defaults to
+/// this seed: the latency jitter, the chaos faults of [`MockConfig::chaos`] and the content
+/// of [`MockConfig::randomized`]. The server logs the seed at start and the assertions of
+/// its [`MockHandle`] show it when they fail, so a flaky test can be replayed with
+/// `MOTO_HSES_MOCK_SEED=<seed>`.
+#[must_use]
+pub fn test_seed() -> u64 {
+    if let Ok(value) = std::env::var(SEED_ENV) {
+        match value.trim().parse() {
+            Ok(seed) => return seed,
+            Err(e) => warn!(target: log_target::SERVER, "Ignoring {SEED_ENV}={value}: {e}"),
+        }
+    }
+    let nanos = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_nanos();
+    #[allow(clippy::cast_possible_truncation)] // The low bits vary the most
+    let seed = (nanos as u64) ^ SEED_COUNTER.fetch_add(1, Ordering::Relaxed).rotate_left(32);
+    SplitMix64::new(seed).next_u64()
+}
 
 /// Mock server configuration
 #[derive(Debug, Clone)]
@@ -24,13 +92,49 @@ pub struct MockConfig {
     pub text_encoding: proto::TextEncoding,
     pub default_status: proto::Status,
     pub default_position: proto::Position,
+    /// Current positions of other control groups and representations, by 0x75 instance;
+    /// `default_position` takes precedence for robot 1 in its representation
+    pub positions: HashMap<u16, proto::Position>,
     pub registers: HashMap<u16, i16>,
     pub variables: TypedVariables,
     pub io_states: HashMap<u16, u8>,
     pub alarms: Vec<proto::Alarm>,
-    pub alarm_history: Vec<proto::Alarm>,
+    /// Alarm history entries added to their category
+    pub alarm_history: Vec<(proto::AlarmCategory, proto::Alarm)>,
     pub executing_job: Option<proto::ExecutingJobInfo>,
     pub cycle_mode: proto::CycleMode,
+    /// Files added to the controller storage in addition to the default test job
+    pub files: HashMap<String, Vec<u8>>,
+    /// Security mode of the pendant; file writes and deletes may need a higher one
+    pub security_mode: SecurityMode,
+    /// Simulated job execution after a job start command
+    pub job_simulation: Option<JobSimulation>,
+    /// Simulated robot motion for move instructions
+    pub motion_simulation: Option<MotionSimulation>,
+    /// Longest time a request may take to be handled before the server stops with an
+    /// error; `None` disables the watchdog
+    pub watchdog_timeout: Option<std::time::Duration>,
+    /// Model whose file name rules apply to files sent to the controller
+    pub controller_model: proto::ControllerModel,
+    /// File name rules applied instead of those of `controller_model`, e.g. 8.3 names
+    pub file_name_rules: Option<proto::FileNameRules>,
+    /// System information reported by system information reads
+    pub system_info: proto::SystemInfo,
+    /// Commands answered as undefined, as by a controller without support for them
+    pub unsupported_commands: Vec<u16>,
+    /// Answer to the other commands without a handler
+    pub unknown_commands: UnknownCommandPolicy,
+    /// Delay before each response is sent
+    pub response_latency: Duration,
+    /// Longest random delay added to `response_latency` of robot control responses, drawn
+    /// from `seed` and the number of the request
+    pub latency_jitter: Duration,
+    /// Seed of every random draw of the server; see [`test_seed`]
+    pub seed: u64,
+    /// Random faults applied to robot control requests; `None` answers every request
+    pub chaos: Option<ChaosConfig>,
+    /// Most verbose level logged by the server, on top of the filter of the logger
+    pub log_level: log::LevelFilter,
 }
 
 impl MockConfig {
@@ -80,6 +184,7 @@ impl MockConfig {
             default_position: proto::Position::Pulse(proto::PulsePosition::new(vec![
                 0, 0, 0, 0, 0, 0, 0, 0,
             ])),
+            positions: HashMap::new(),
             registers,
             variables,
             io_states,
@@ -87,37 +192,237 @@ impl MockConfig {
             alarm_history: Vec::new(),
             executing_job: Some(proto::ExecutingJobInfo::new("TEST.JOB".to_string(), 2, 1, 100)),
             cycle_mode: proto::CycleMode::Continuous,
+            files: HashMap::new(),
+            security_mode: SecurityMode::default(),
+            job_simulation: None,
+            motion_simulation: None,
+            watchdog_timeout: Some(std::time::Duration::from_secs(10)),
+            controller_model: proto::ControllerModel::default(),
+            file_name_rules: None,
+            system_info: state::default_system_info(),
+            unsupported_commands: Vec::new(),
+            unknown_commands: UnknownCommandPolicy::default(),
+            response_latency: Duration::ZERO,
+            latency_jitter: Duration::ZERO,
+            seed: test_seed(),
+            chaos: None,
+            log_level: log::LevelFilter::Trace,
+        }
+    }
+
+    /// Preset of an FS100: 32-byte file names and 8 ms responses
+    ///
+    /// Like the other presets, it has a sample job and alarm history entry and uses the
+    /// default ports. Latencies are
+    /// rough figures for a controller on a quiet network. Character variables hold 16
+    /// bytes on every controller.
+    #[must_use]
+    pub fn fs100() -> Self {
+        Self::preset(proto::ControllerModel::FS100, Duration::from_millis(8), "PICK01")
+    }
+
+    /// Preset of a DX200: 32-byte file names and 4 ms responses
+    #[must_use]
+    pub fn dx200() -> Self {
+        Self::preset(proto::ControllerModel::DX200, Duration::from_millis(4), "PALLET_MAIN")
+    }
+
+    /// Preset of a YRC1000: 32-byte file names and 2 ms responses
+    #[must_use]
+    pub fn yrc1000() -> Self {
+        Self::preset(proto::ControllerModel::YRC1000, Duration::from_millis(2), "PALLET_MAIN")
+    }
+
+    /// Default configuration with random variables, registers, I/O, position and alarms
+    ///
+    /// For robustness tests of application code that must not rely on the default
+    /// values. The same seed gives the same content, and the seed, which also becomes the
+    /// [`seed`](Self::seed) of the configuration, is logged so that a failing run can be
+    /// reproduced. B, I, D, R and S variables 0-99, registers 0-999 and
+    /// the signals of every I/O range get values; there are 1-4 active alarms, since an
+    /// empty list keeps the default alarms, and up to 5 added history entries.
+    #[must_use]
+    pub fn randomized(seed: u64) -> Self {
+        info!(target: log_target::SERVER, "Mock state randomized with seed {seed}");
+        let mut rng = SplitMix64::new(seed);
+
+        let mut variables = TypedVariables::new();
+        for index in 0..100 {
+            variables.insert((VariableType::Byte, index), vec![rng.range(0..=255)]);
+            let integer: i16 = rng.range(-1000..=1000);
+            variables.insert((VariableType::Integer, index), integer.to_le_bytes().to_vec());
+            let double: i32 = rng.range(-100_000..=100_000);
+            variables.insert((VariableType::Double, index), double.to_le_bytes().to_vec());
+            let real = f32::from(rng.range::<i16>(-10_000..=10_000)) / 10.0;
+            variables.insert((VariableType::Real, index), real.to_le_bytes().to_vec());
+            let len: usize = rng.range(0..=16);
+            let mut text: Vec<u8> =
+                (0..len).map(|_| *rng.choose(b"ABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789_-")).collect();
+            text.resize(proto::payload::variable::STRING_VARIABLE_SIZE, 0);
+            variables.insert((VariableType::String, index), text);
+        }
+
+        let registers = (0..1000).map(|number| (number, rng.range(0..=9999))).collect();
+
+        // Mostly OFF signals, as on a cell at rest
+        let mut io_states = HashMap::new();
+        for (start, end) in IO_CATEGORIES.iter().map(IoCategory::range) {
+            for io_number in start..=end {
+                let value = if rng.chance(30) { rng.range::<u8>(0..=255) } else { 0 };
+                io_states.insert(io_number, value);
+            }
+        }
+
+        let axes = (0..8).map(|axis| if axis < 6 { rng.range(-200_000..=200_000) } else { 0 });
+        let default_position = proto::Position::Pulse(proto::PulsePosition::new(axes.collect()));
+
+        let random_alarm = |rng: &mut SplitMix64| {
+            let (code, name) = *rng.choose(&RANDOM_ALARMS);
+            let time = format!(
+                "2024/{:02}/{:02} {:02}:{:02}",
+                rng.range::<u8>(1..=12),
+                rng.range::<u8>(1..=28),
+                rng.range::<u8>(0..=23),
+                rng.range::<u8>(0..=59)
+            );
+            proto::Alarm::new(code, rng.range(0..=9), rng.range(0..=3), time, name.to_string())
+        };
+        let alarms = (0..rng.range::<u8>(1..=4)).map(|_| random_alarm(&mut rng)).collect();
+        let categories = [proto::AlarmCategory::MajorFailure, proto::AlarmCategory::MonitorAlarm];
+        let alarm_history = (0..rng.range::<u8>(0..=5))
+            .map(|_| (*rng.choose(&categories), random_alarm(&mut rng)))
+            .collect();
+
+        Self {
+            default_position,
+            registers,
+            variables,
+            io_states,
+            alarms,
+            alarm_history,
+            seed,
+            ..Self::default()
+        }
+    }
+
+    /// Default configuration with random faults on robot control requests, for resilience
+    /// soak tests of application code
+    ///
+    /// Responses take 2 ms plus a random delay of up to 20 ms, 1-5% of them are dropped
+    /// and 0.5% sent twice, and 0.5% of the requests are answered with an error status
+    /// instead of being handled; see [`ChaosConfig`]. The drop rate and the faults met by
+    /// each request follow from the seed, which also becomes the [`seed`](Self::seed) of the
+    /// configuration and is logged so that a failing run can be reproduced. File control requests are answered normally.
+    #[must_use]
+    pub fn chaos(seed: u64) -> Self {
+        let drop_percent: u8 = SplitMix64::new(seed).range(1..=5);
+        info!(
+            target: log_target::SERVER,
+            "Mock chaos with seed {seed}: {drop_percent}% of the responses dropped"
+        );
+        Self {
+            response_latency: Duration::from_millis(2),
+            seed,
+            chaos: Some(ChaosConfig {
+                seed,
+                drop_rate: f64::from(drop_percent) / 100.0,
+                duplicate_rate: 0.005,
+                error_rate: 0.005,
+                max_jitter: Duration::from_millis(20),
+            }),
+            ..Self::default()
+        }
+    }
+
+    fn preset(model: proto::ControllerModel, latency: Duration, job: &str) -> Self {
+        let job_file = format!(
+            "/JOB\r\n//NAME {job}\r\n//POS\r\n///NPOS 0,0,0,0,0,0\r\n//INST\r\n\
+             ///DATE 2024/04/22 10:15\r\n///ATTR SC,RW\r\n///GROUP1 RB1\r\nNOP\r\nEND\r\n"
+        );
+        let alarm = proto::Alarm::new(
+            4100,
+            1,
+            0,
+            "2024/04/22 10:31".to_string(),
+            "OVERRUN(ROBOT)".to_string(),
+        );
+        Self {
+            controller_model: model,
+            response_latency: latency,
+            executing_job: Some(proto::ExecutingJobInfo::new(format!("{job}.JOB"), 12, 5, 100)),
+            files: HashMap::from([(format!("{job}.JBI"), job_file.into_bytes())]),
+            alarm_history: vec![(proto::AlarmCategory::MonitorAlarm, alarm)],
+            ..Self::default()
         }
     }
 
     /// Get robot control socket address
     ///
+    /// The host must be an IP address literal; [`MockServer::new`] also resolves host names.
+    ///
     /// # Errors
     ///
     /// Returns an error if the address format is invalid
     pub fn robot_addr(&self) -> Result<SocketAddr, std::net::AddrParseError> {
-        format!("{host}:{port}", host = self.host, port = self.robot_port).parse()
+        self.robot_addr_string().parse()
     }
 
     /// Get file control socket address
     ///
+    /// The host must be an IP address literal; [`MockServer::new`] also resolves host names.
+    ///
     /// # Errors
     ///
     /// Returns an error if the address format is invalid
     pub fn file_addr(&self) -> Result<SocketAddr, std::net::AddrParseError> {
-        format!("{host}:{port}", host = self.host, port = self.file_port).parse()
+        self.file_addr_string().parse()
     }
 
-    /// Get robot control socket address as string
+    /// Get robot control socket address as string, with IPv6 hosts in brackets
     #[must_use]
     pub fn robot_addr_string(&self) -> String {
-        format!("{host}:{port}", host = self.host, port = self.robot_port)
+        format_addr(&self.host, self.robot_port)
     }
 
-    /// Get file control socket address as string
+    /// Get file control socket address as string, with IPv6 hosts in brackets
     #[must_use]
     pub fn file_addr_string(&self) -> String {
-        format!("{host}:{port}", host = self.host, port = self.file_port)
+        format_addr(&self.host, self.file_port)
+    }
+}
+
+// Categories whose signals get values in randomized configurations
+const IO_CATEGORIES: [IoCategory; 12] = [
+    IoCategory::RobotUserInput,
+    IoCategory::RobotUserOutput,
+    IoCategory::ExternalInput,
+    IoCategory::NetworkInput,
+    IoCategory::ExternalOutput,
+    IoCategory::NetworkOutput,
+    IoCategory::RobotSystemInput,
+    IoCategory::RobotSystemOutput,
+    IoCategory::InterfacePanelInput,
+    IoCategory::AuxiliaryRelay,
+    IoCategory::RobotControlStatusSignal,
+    IoCategory::PseudoInput,
+];
+
+// Alarms drawn by randomized configurations
+const RANDOM_ALARMS: [(u32, &str); 6] = [
+    (1030, "MEMORY ERROR(PARAMETER FILE)"),
+    (4100, "OVERRUN(ROBOT)"),
+    (4107, "OUT OF RANGE(ABSO DATA)"),
+    (4315, "COLLISION DETECT"),
+    (4321, "OVER LOAD"),
+    (4511, "OUT OF RANGE(PULSE LIMIT)"),
+];
+
+// "host:port", with IPv6 hosts in brackets
+fn format_addr(host: &str, port: u16) -> String {
+    if host.contains(':') && !host.starts_with('[') {
+        format!("[{host}]:{port}")
+    } else {
+        format!("{host}:{port}")
     }
 }
 
@@ -136,7 +441,15 @@ pub mod test_utils {
     use super::{MockConfig, MockServer, SocketAddr};
     use tokio::time::{Duration, sleep};
 
+    /// Lifetime of servers started by [`start_test_server`]
+    ///
+    /// Bounds the run time of a test that does not abort the returned handle, so that it
+    /// cannot keep a CI job alive.
+    pub const TEST_SERVER_TTL: Duration = Duration::from_secs(60);
+
     /// Start a mock server for testing
+    ///
+    /// The server stops after [`TEST_SERVER_TTL`], releasing its ports.
     /// # Errors
     ///
     /// Returns an error if server creation fails
@@ -159,9 +472,9 @@ pub mod test_utils {
         let server = server.ok_or("Could not find available port")?;
         let addr = server.local_addr()?;
 
-        let handle = tokio::spawn(async move {
-            if let Err(e) = server.run().await {
-                error!("Mock server error: {e}");
+        let handle = crate::task::spawn("hses-mock-server", async move {
+            if let Err(e) = server.run_for(TEST_SERVER_TTL).await {
+                error!(target: crate::log_target::SERVER, "Mock server error: {e}");
             }
         });
 
@@ -180,7 +493,7 @@ pub mod test_utils {
     ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         // Note: This would require the client to be available
         // For now, just return a placeholder
-        info!("Test client would connect to {addr}");
+        info!(target: crate::log_target::SERVER, "Test client would connect to {addr}");
         Ok(())
     }
 }
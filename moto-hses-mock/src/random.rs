@@ -0,0 +1,43 @@
+//! Reproducible pseudo-random numbers for [`MockConfig::randomized`](crate::MockConfig::randomized)
+//! and [`ChaosConfig`](crate::ChaosConfig)
+
+use std::ops::RangeInclusive;
+
+// SplitMix64: small and seedable, with a good enough distribution for test data
+pub struct SplitMix64(u64);
+
+impl SplitMix64 {
+    pub const fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    pub const fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    // Value in `range`; the modulo bias does not matter for test data
+    pub fn range<T: TryFrom<i64> + Default>(&mut self, range: RangeInclusive<i64>) -> T {
+        let (start, end) = range.into_inner();
+        let span = (end - start).unsigned_abs() + 1;
+        let offset = i64::try_from(self.next_u64() % span).unwrap_or(0);
+        T::try_from(start + offset).unwrap_or_default()
+    }
+
+    // Value in [0, 1)
+    pub fn fraction(&mut self) -> f64 {
+        f64::from(u32::try_from(self.next_u64() >> 32).unwrap_or(0)) / 4_294_967_296.0
+    }
+
+    // True with a probability of `percent` / 100
+    pub const fn chance(&mut self, percent: u64) -> bool {
+        self.next_u64() % 100 < percent
+    }
+
+    pub fn choose<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.range::<usize>(0..=i64::try_from(items.len()).unwrap_or(1) - 1)]
+    }
+}
@@ -1,19 +1,88 @@
 //! Mock HSES server implementation
 
-use crate::handlers::CommandHandlerRegistry;
-use crate::state::{MockState, SharedState, TypedVariables, VariableType};
+pub use crate::controller::FILE_BLOCK_SIZE;
+use crate::controller::MockController;
+use crate::handle::MockHandle;
+use crate::handlers::{AsyncCommandHandler, CommandHandler, SyncHandler};
+use crate::log_target;
+use crate::state::{
+    JobSimulation, MotionSimulation, ROBOT_CARTESIAN_INSTANCE_OFFSET, ResponseFault, SecurityMode,
+    SharedState, TypedVariables, VariableType,
+};
+use crate::task;
+use log::LevelFilter;
 use moto_hses_proto as proto;
 use proto::commands::alarm::AlarmCategory;
+use proto::constants::header;
+use proto::trace::FrameDump;
 use std::net::SocketAddr;
 use std::sync::Arc;
 use tokio::net::UdpSocket;
+use tokio::task::JoinSet;
+use tokio::time::{Duration, sleep};
+
+/// Time to wait for the client's ACK before sending the next data block
+const FILE_BLOCK_ACK_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Size of the receive buffers: the largest UDP datagram
+///
+/// Requests above the path MTU arrive as IP fragments and are reassembled by the kernel
+/// into one datagram. Reading it whole lets an oversize request be rejected with an error
+/// response instead of being truncated and dropped as undecodable.
+const RECEIVE_BUFFER_SIZE: usize = 65_536;
+
+/// Interval at which the watchdog checks the requests being handled, as a fraction of
+/// the watchdog timeout
+const WATCHDOG_CHECKS_PER_TIMEOUT: u32 = 4;
 
 /// Mock HSES server
 pub struct MockServer {
     robot_socket: Arc<UdpSocket>,
     file_socket: Arc<UdpSocket>,
-    state: SharedState,
-    handlers: CommandHandlerRegistry,
+    controller: MockController,
+    watchdog_timeout: Option<Duration>,
+}
+
+// Request being handled by one of the socket tasks, as seen by the watchdog
+#[derive(Debug, Default)]
+struct InFlight {
+    request: std::sync::Mutex<Option<(tokio::time::Instant, u16)>>,
+}
+
+impl InFlight {
+    fn start(&self, command: u16) {
+        if let Ok(mut request) = self.request.lock() {
+            *request = Some((tokio::time::Instant::now(), command));
+        }
+    }
+
+    fn finish(&self) {
+        if let Ok(mut request) = self.request.lock() {
+            *request = None;
+        }
+    }
+
+    // Command of the request if it has been handled for longer than `timeout`
+    fn stuck(&self, timeout: Duration) -> Option<u16> {
+        let request = self.request.lock().ok()?;
+        request.filter(|(started_at, _)| started_at.elapsed() > timeout).map(|(_, command)| command)
+    }
+}
+
+// Bind a UDP socket to the first usable address of `host`, an IP address literal, also
+// IPv6 in brackets or with a scope ID, or a host name resolved through DNS
+async fn bind_socket(host: &str, port: u16) -> std::io::Result<UdpSocket> {
+    let host = host.strip_prefix('[').and_then(|h| h.strip_suffix(']')).unwrap_or(host);
+    let mut last_error = None;
+    for addr in tokio::net::lookup_host((host, port)).await? {
+        match UdpSocket::bind(addr).await {
+            Ok(socket) => return Ok(socket),
+            Err(e) => last_error = Some(e),
+        }
+    }
+    Err(last_error.unwrap_or_else(|| {
+        std::io::Error::new(std::io::ErrorKind::NotFound, format!("No address found for {host}"))
+    }))
 }
 
 impl MockServer {
@@ -25,49 +94,37 @@ impl MockServer {
     pub async fn new(
         config: crate::MockConfig,
     ) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
-        let robot_addr = config.robot_addr()?;
-        let file_addr = config.file_addr()?;
-
-        let robot_socket = Arc::new(UdpSocket::bind(robot_addr).await?);
-        let file_socket = Arc::new(UdpSocket::bind(file_addr).await?);
-
-        let mut mock_state = MockState {
-            text_encoding: config.text_encoding,
-            status: config.default_status.clone(),
-            position: config.default_position.clone(),
-            registers: config.registers.clone(),
-            variables: config.variables.clone(),
-            cycle_mode: config.cycle_mode,
-            ..Default::default()
-        };
-
-        // Apply configured job information
-        if let Some(job) = &config.executing_job {
-            mock_state.executing_job = Some(job.clone());
-        }
-
-        // Apply configured alarms if any
-        if !config.alarms.is_empty() {
-            mock_state.alarms.clone_from(&config.alarms);
-        }
-
-        // Apply configured alarm history if any
-        if !config.alarm_history.is_empty() {
-            // Add alarms to appropriate history categories
-            for alarm in &config.alarm_history {
-                // Determine category based on alarm code or use a default category
-                // For simplicity, we'll add all to major_failure category
-                mock_state.alarm_history.add_alarm(AlarmCategory::MajorFailure, alarm.clone());
-            }
-        }
-
-        let state = SharedState::new(mock_state);
-        let handlers = CommandHandlerRegistry::default();
-
-        info!("Mock server listening on {robot_addr}");
-        info!("Mock server listening on {file_addr}");
+        Self::bind(&config, MockController::new(&config)).await
+    }
 
-        Ok(Self { robot_socket, file_socket, state, handlers })
+    // Serve `controller` on the addresses of `config`
+    async fn bind(
+        config: &crate::MockConfig,
+        controller: MockController,
+    ) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let robot_socket = Arc::new(bind_socket(&config.host, config.robot_port).await?);
+        let file_socket = Arc::new(bind_socket(&config.host, config.file_port).await?);
+        let robot_addr = robot_socket.local_addr()?;
+        let file_addr = file_socket.local_addr()?;
+
+        let log_level = config.log_level;
+        mock_log!(log_level, Info, log_target::SERVER, "Mock server listening on {robot_addr}");
+        mock_log!(log_level, Info, log_target::SERVER, "Mock server listening on {file_addr}");
+        mock_log!(
+            log_level,
+            Info,
+            log_target::SERVER,
+            "Mock seed {seed} (replay with {}={seed})",
+            crate::SEED_ENV,
+            seed = config.seed
+        );
+
+        Ok(Self {
+            robot_socket,
+            file_socket,
+            controller,
+            watchdog_timeout: config.watchdog_timeout,
+        })
     }
 
     /// Get the local address of the server
@@ -78,32 +135,84 @@ impl MockServer {
         Ok(self.robot_socket.local_addr()?)
     }
 
+    /// Get the local address of the file control socket
+    /// # Errors
+    ///
+    /// Returns an error if local address cannot be obtained
+    pub fn file_local_addr(&self) -> Result<SocketAddr, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(self.file_socket.local_addr()?)
+    }
+
+    /// Run the server for at most `duration`
+    ///
+    /// Returns `Ok` when the time is up; the ports are released once the server is dropped.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if server operation fails, as [`Self::run`]
+    pub async fn run_for(
+        &self,
+        duration: Duration,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        if let Ok(result) = tokio::time::timeout(duration, self.run()).await {
+            return result;
+        }
+        mock_log!(
+            self.controller.log_level,
+            Info,
+            log_target::SERVER,
+            "Mock server stopped after {duration:?}"
+        );
+        Ok(())
+    }
+
     /// Run the server
+    ///
+    /// The socket tasks are aborted when the returned future completes or is dropped.
+    ///
     /// # Errors
     ///
-    /// Returns an error if server operation fails
+    /// Returns an error if server operation fails, or if the watchdog finds a request that
+    /// has not been handled within the configured `watchdog_timeout`, e.g. because a
+    /// handler deadlocked
     #[allow(clippy::too_many_lines)]
     pub async fn run(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        // Create a task for each socket
+        // Create a task for each socket; dropping the set aborts them
         let robot_socket = Arc::clone(&self.robot_socket);
         let file_socket = Arc::clone(&self.file_socket);
-
-        let robot_task = {
-            let state = self.state.clone();
-            let handlers = self.handlers.clone();
-            tokio::spawn(async move {
-                let mut buf = vec![0u8; 2048];
+        let robot_in_flight = Arc::new(InFlight::default());
+        let file_in_flight = Arc::new(InFlight::default());
+        let mut tasks: JoinSet<Result<(), String>> = JoinSet::new();
+        let log_level = self.controller.log_level;
+
+        {
+            let controller = self.controller.clone();
+            let in_flight = Arc::clone(&robot_in_flight);
+            let latency = self.controller.response_latency;
+            task::spawn_in(&mut tasks, "hses-mock-robot", async move {
+                let mut buf = vec![0u8; RECEIVE_BUFFER_SIZE];
                 loop {
                     let (n, src) = match robot_socket.recv_from(&mut buf).await {
                         Ok(result) => result,
                         Err(e) => {
-                            error!("Error receiving from robot socket: {e:?}");
+                            mock_log!(
+                                log_level,
+                                Error,
+                                log_target::SERVER,
+                                "Error receiving from robot socket: {e:?}"
+                            );
                             continue;
                         }
                     };
 
-                    if n < 32 {
-                        debug!("Received message too short: {n} bytes");
+                    mock_log!(
+                        log_level,
+                        Debug,
+                        log_target::TRAFFIC,
+                        "Received from {src}: {}",
+                        FrameDump::new(&buf[..n])
+                    );
+                    if n < header::SIZE {
                         continue;
                     }
 
@@ -111,83 +220,118 @@ impl MockServer {
                     let message = match proto::HsesRequestMessage::decode(&buf[..n]) {
                         Ok(msg) => msg,
                         Err(e) => {
-                            error!("Failed to decode message: {e:?}");
+                            mock_log!(
+                                log_level,
+                                Warn,
+                                log_target::SERVER,
+                                "Failed to decode message from {src}: {e:?}"
+                            );
                             continue;
                         }
                     };
 
-                    debug!(
-                        "Received packet from {}: Header[division={}, ack={}, request_id={}, payload_size={}], SubHeader[command=0x{:04x}, instance={}, attribute={}, service={}], Payload[{} bytes: {:02x?}]",
-                        src,
-                        message.header.division,
-                        message.header.ack,
-                        message.header.request_id,
-                        message.header.payload_size,
-                        message.sub_header.command,
-                        message.sub_header.instance,
-                        message.sub_header.attribute,
-                        message.sub_header.service,
-                        message.payload.len(),
-                        message.payload
-                    );
-
                     // Handle the message
-                    let response = Self::handle_message_internal(&message, &state, &handlers).await;
+                    in_flight.start(message.sub_header.command);
+                    let response = controller.respond(&message).await;
+                    in_flight.finish();
+                    if !latency.is_zero() {
+                        sleep(latency).await;
+                    }
+                    let (fault, jitter) = controller.next_robot_fault().await;
+                    if !jitter.is_zero() {
+                        sleep(jitter).await;
+                    }
 
                     // Send response
-                    if let Ok(response_data) = response {
-                        if !response_data.is_empty() {
-                            // Decode response message for detailed logging
-                            if let Ok(response_message) =
-                                proto::HsesResponseMessage::decode(&response_data)
-                            {
-                                debug!(
-                                    "Sending response to {}: Header[division={}, ack={}, request_id={}, payload_size={}], SubHeader[service={}, status={}, added_status_size={}, added_status={}], Payload[{} bytes: {:02x?}]",
-                                    src,
-                                    response_message.header.division,
-                                    response_message.header.ack,
-                                    response_message.header.request_id,
-                                    response_message.header.payload_size,
-                                    response_message.sub_header.service,
-                                    response_message.sub_header.status,
-                                    response_message.sub_header.added_status_size,
-                                    response_message.sub_header.added_status,
-                                    response_message.payload.len(),
-                                    response_message.payload
+                    if let Ok(datagrams) = response {
+                        let copies = match fault {
+                            Some(ResponseFault::Drop) => {
+                                mock_log!(
+                                    log_level,
+                                    Debug,
+                                    log_target::TRAFFIC,
+                                    "Dropping response to {src} (injected fault)"
                                 );
-                            } else {
-                                debug!(
-                                    "Sending response: {} bytes (failed to decode for detailed logging)",
-                                    response_data.len()
+                                continue;
+                            }
+                            Some(ResponseFault::Delay(delay)) => {
+                                mock_log!(
+                                    log_level,
+                                    Debug,
+                                    log_target::TRAFFIC,
+                                    "Delaying response to {src} by {delay:?} (injected fault)"
                                 );
+                                let socket = Arc::clone(&robot_socket);
+                                task::spawn("hses-mock-delayed-response", async move {
+                                    sleep(delay).await;
+                                    for response_data in datagrams {
+                                        let _ = socket.send_to(&response_data, src).await;
+                                    }
+                                });
+                                continue;
                             }
-                            if let Err(e) = robot_socket.send_to(&response_data, src).await {
-                                debug!("Error sending response: {e:?}");
+                            Some(ResponseFault::Duplicate) => 2,
+                            None => 1,
+                        };
+                        for response_data in datagrams.iter().cycle().take(datagrams.len() * copies)
+                        {
+                            mock_log!(
+                                log_level,
+                                Debug,
+                                log_target::TRAFFIC,
+                                "Sending to {src}: {}",
+                                FrameDump::new(response_data)
+                            );
+                            if let Err(e) = robot_socket.send_to(response_data, src).await {
+                                mock_log!(
+                                    log_level,
+                                    Warn,
+                                    log_target::SERVER,
+                                    "Error sending response: {e:?}"
+                                );
                             }
                         }
                     } else {
-                        debug!("Error handling message: {:?}", response.err());
+                        mock_log!(
+                            log_level,
+                            Error,
+                            log_target::SERVER,
+                            "Error handling message: {:?}",
+                            response.err()
+                        );
                     }
                 }
-            })
-        };
-
-        let file_task = {
-            let state = self.state.clone();
-            let handlers = self.handlers.clone();
-            tokio::spawn(async move {
-                let mut buf = vec![0u8; 2048];
+            });
+        }
+
+        {
+            let controller = self.controller.clone();
+            let in_flight = Arc::clone(&file_in_flight);
+            let latency = self.controller.response_latency;
+            task::spawn_in(&mut tasks, "hses-mock-file", async move {
+                let mut buf = vec![0u8; RECEIVE_BUFFER_SIZE];
                 loop {
                     let (n, src) = match file_socket.recv_from(&mut buf).await {
                         Ok(result) => result,
                         Err(e) => {
-                            debug!("Error receiving from file socket: {e:?}");
+                            mock_log!(
+                                log_level,
+                                Error,
+                                log_target::SERVER,
+                                "Error receiving from file socket: {e:?}"
+                            );
                             continue;
                         }
                     };
 
-                    if n < 32 {
-                        debug!("Received file message too short: {n} bytes");
+                    mock_log!(
+                        log_level,
+                        Debug,
+                        log_target::TRAFFIC,
+                        "Received file packet from {src}: {}",
+                        FrameDump::new(&buf[..n])
+                    );
+                    if n < header::SIZE {
                         continue;
                     }
 
@@ -195,186 +339,229 @@ impl MockServer {
                     let message = match proto::HsesRequestMessage::decode(&buf[..n]) {
                         Ok(msg) => msg,
                         Err(e) => {
-                            debug!("Failed to decode file message: {e:?}");
+                            mock_log!(
+                                log_level,
+                                Warn,
+                                log_target::SERVER,
+                                "Failed to decode file message from {src}: {e:?}"
+                            );
                             continue;
                         }
                     };
 
-                    debug!(
-                        "Received file packet from {}: Header[division={}, ack={}, request_id={}, payload_size={}], SubHeader[command=0x{:04x}, instance={}, attribute={}, service={}], Payload[{} bytes: {:02x?}]",
-                        src,
-                        message.header.division,
-                        message.header.ack,
-                        message.header.request_id,
-                        message.header.payload_size,
-                        message.sub_header.command,
-                        message.sub_header.instance,
-                        message.sub_header.attribute,
-                        message.sub_header.service,
-                        message.payload.len(),
-                        message.payload
-                    );
+                    // ACKs for data blocks are consumed while sending multi-block responses;
+                    // late or duplicate ACKs are not requests and need no response
+                    if message.header.ack == 0x01 {
+                        mock_log!(
+                            log_level,
+                            Debug,
+                            log_target::FILE,
+                            "Ignoring ACK from {src} for block 0x{:08x}",
+                            message.header.block_number
+                        );
+                        continue;
+                    }
 
                     // Handle the message
-                    let response = Self::handle_message_internal(&message, &state, &handlers).await;
+                    in_flight.start(message.sub_header.command);
+                    let response = controller.respond(&message).await;
+                    in_flight.finish();
+                    if !latency.is_zero() {
+                        sleep(latency).await;
+                    }
 
                     // Send response
-                    if let Ok(response_data) = response {
-                        if !response_data.is_empty() {
-                            // Decode response message for detailed logging
-                            if let Ok(response_message) =
-                                proto::HsesResponseMessage::decode(&response_data)
-                            {
-                                debug!(
-                                    "Sending file response to {}: Header[division={}, ack={}, request_id={}, payload_size={}], SubHeader[service={}, status={}, added_status_size={}, added_status={}], Payload[{} bytes: {:02x?}]",
-                                    src,
-                                    response_message.header.division,
-                                    response_message.header.ack,
-                                    response_message.header.request_id,
-                                    response_message.header.payload_size,
-                                    response_message.sub_header.service,
-                                    response_message.sub_header.status,
-                                    response_message.sub_header.added_status_size,
-                                    response_message.sub_header.added_status,
-                                    response_message.payload.len(),
-                                    response_message.payload
-                                );
-                            } else {
-                                debug!(
-                                    "Sending file response: {} bytes (failed to decode for detailed logging)",
-                                    response_data.len()
+                    if let Ok(datagrams) = response {
+                        let block_count = datagrams.len();
+                        for (index, response_data) in datagrams.into_iter().enumerate() {
+                            mock_log!(
+                                log_level,
+                                Debug,
+                                log_target::TRAFFIC,
+                                "Sending file response to {src}: {}",
+                                FrameDump::new(&response_data)
+                            );
+                            let block_number = proto::HsesResponseMessage::decode(&response_data)
+                                .map_or(0, |response_message| response_message.header.block_number);
+                            if let Err(e) = file_socket.send_to(&response_data, src).await {
+                                mock_log!(
+                                    log_level,
+                                    Warn,
+                                    log_target::SERVER,
+                                    "Error sending file response: {e:?}"
                                 );
+                                break;
                             }
-                            if let Err(e) = file_socket.send_to(&response_data, src).await {
-                                debug!("Error sending file response: {e:?}");
+
+                            // The controller waits for the client's ACK before sending the
+                            // next block
+                            if index + 1 < block_count
+                                && !Self::wait_for_block_ack(
+                                    &file_socket,
+                                    src,
+                                    message.header.request_id,
+                                    block_number,
+                                    log_level,
+                                )
+                                .await
+                            {
+                                mock_log!(
+                                    log_level,
+                                    Warn,
+                                    log_target::FILE,
+                                    "No ACK for block 0x{block_number:08x}, aborting transfer"
+                                );
+                                break;
                             }
                         }
                     } else {
-                        debug!("Error handling file message: {:?}", response.err());
+                        mock_log!(
+                            log_level,
+                            Error,
+                            log_target::SERVER,
+                            "Error handling file message: {:?}",
+                            response.err()
+                        );
                     }
                 }
-            })
-        };
+            });
+        }
 
-        // Wait for either task to complete (they should run forever)
-        tokio::select! {
-            result = robot_task => {
-                let _ = result; // Tasks should run forever, ignore result
-            }
-            result = file_task => {
-                let _ = result; // Tasks should run forever, ignore result
-            }
+        if let Some(timeout) = self.watchdog_timeout {
+            task::spawn_in(&mut tasks, "hses-mock-watchdog", async move {
+                let mut interval = tokio::time::interval(timeout / WATCHDOG_CHECKS_PER_TIMEOUT);
+                loop {
+                    interval.tick().await;
+                    for (socket, in_flight) in
+                        [("robot", &robot_in_flight), ("file", &file_in_flight)]
+                    {
+                        if let Some(command) = in_flight.stuck(timeout) {
+                            let message = format!(
+                                "Watchdog: command 0x{command:04x} on the {socket} socket not handled within {timeout:?}"
+                            );
+                            mock_log!(log_level, Error, log_target::SERVER, "{message}");
+                            return Err(message);
+                        }
+                    }
+                }
+            });
         }
 
-        Ok(())
+        // The socket tasks run forever; the watchdog ends with an error
+        match tasks.join_next().await {
+            Some(Ok(Err(message))) => Err(message.into()),
+            Some(Err(e)) => Err(e.into()),
+            Some(Ok(Ok(()))) | None => Ok(()),
+        }
     }
 
-    /// Internal message handler (static method for use in tasks)
-    async fn handle_message_internal(
-        message: &proto::HsesRequestMessage,
-        state: &SharedState,
-        handlers: &CommandHandlerRegistry,
-    ) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>> {
-        let mut state = state.write().await;
-
-        // Handle the command using new message format
-        let (payload, status, added_status) = match handlers.handle(message, &mut state) {
-            Ok(payload) => (payload, 0x00, 0x0000), // Success
-            Err(proto::ProtocolError::InvalidCommand) => {
-                // For unknown commands, return error status
-                (vec![], 0x01, 0x0001) // Error status with command error code
-            }
-            Err(proto::ProtocolError::InvalidService) => {
-                // For invalid service, return error status
-                (vec![], 0x02, 0x0002) // Error status with service error code
-            }
-            Err(proto::ProtocolError::InvalidAttribute) => {
-                // For invalid attribute, return error status
-                (vec![], 0x03, 0x0003) // Error status with attribute error code
-            }
-            Err(proto::ProtocolError::InvalidMessage(msg)) => {
-                // Log detailed error message and return generic error status
-                error!("Protocol error: {msg}");
-                (vec![], 0xFF, 0x00FF) // Generic error status
+    /// Wait for the client's ACK of a data block
+    ///
+    /// Returns `false` if no matching ACK arrives within [`FILE_BLOCK_ACK_TIMEOUT`].
+    /// Other packets received while waiting are discarded.
+    async fn wait_for_block_ack(
+        socket: &UdpSocket,
+        src: SocketAddr,
+        request_id: u8,
+        block_number: u32,
+        log_level: LevelFilter,
+    ) -> bool {
+        let mut buf = vec![0u8; RECEIVE_BUFFER_SIZE];
+        let deadline = tokio::time::Instant::now() + FILE_BLOCK_ACK_TIMEOUT;
+        loop {
+            let Ok(Ok((n, from))) =
+                tokio::time::timeout_at(deadline, socket.recv_from(&mut buf)).await
+            else {
+                return false;
+            };
+            if from != src {
+                continue;
             }
-            Err(e) => {
-                // For other errors, log and return generic error status
-                error!("Protocol error: {e}");
-                (vec![], 0xFF, 0x00FF) // Generic error status
+            match proto::HsesRequestMessage::decode(&buf[..n]) {
+                Ok(ack)
+                    if ack.header.ack == 0x01
+                        && ack.header.request_id == request_id
+                        && ack.header.block_number == block_number =>
+                {
+                    return true;
+                }
+                Ok(other) => {
+                    mock_log!(
+                        log_level,
+                        Debug,
+                        log_target::FILE,
+                        "Discarding packet while waiting for ACK of block 0x{block_number:08x}: command=0x{:04x}, ack={}",
+                        other.sub_header.command,
+                        other.header.ack
+                    );
+                }
+                Err(e) => mock_log!(
+                    log_level,
+                    Debug,
+                    log_target::FILE,
+                    "Failed to decode packet while waiting for ACK: {e:?}"
+                ),
             }
-        };
-
-        // Create response message with proper structure using new message types
-        let response_message = proto::HsesResponseMessage::new(
-            message.header.division,
-            0x01, // ACK
-            message.header.request_id,
-            message.sub_header.service,
-            status,       // status: success (0x00) or error (non-zero)
-            added_status, // added_status: error code if status is non-zero
-            payload,
-        )
-        .map_err(|e| {
-            error!("Failed to create response message: {e}");
-            e
-        })?;
-
-        // Encode the response
-        let response_data = response_message.encode();
-        Ok(response_data.to_vec())
+        }
     }
 
     /// Get a reference to the shared state
     #[must_use]
     pub const fn state(&self) -> &SharedState {
-        &self.state
+        &self.controller.state
+    }
+
+    /// Get a handle to inspect the state while the server runs
+    #[must_use]
+    pub fn handle(&self) -> MockHandle {
+        MockHandle::new(self.controller.state.clone())
     }
 
     /// Add a test alarm to the server state
     pub async fn add_test_alarm(&self, alarm: proto::Alarm) {
-        let mut state = self.state.write().await;
+        let mut state = self.controller.state.write().await;
         state.add_alarm(alarm);
     }
 
     /// Set a variable in the server state
     pub async fn set_variable(&self, var_type: VariableType, index: u16, value: Vec<u8>) {
-        let mut state = self.state.write().await;
+        let mut state = self.controller.state.write().await;
         state.set_variable(var_type, index, value);
     }
 
     /// Set an I/O state in the server state
     pub async fn set_io_state(&self, io_number: u16, value: u8) {
-        let mut server_state = self.state.write().await;
+        let mut server_state = self.controller.state.write().await;
         server_state.set_io_state(io_number, value);
     }
 
     /// Set the robot status
     pub async fn set_status(&self, status: proto::Status) {
-        let mut state = self.state.write().await;
+        let mut state = self.controller.state.write().await;
         state.status = status;
     }
 
     /// Set the robot position
     pub async fn set_position(&self, position: proto::Position) {
-        let mut state = self.state.write().await;
+        let mut state = self.controller.state.write().await;
         state.update_position(position);
     }
 
     /// Get the current cycle mode
     pub async fn get_cycle_mode(&self) -> proto::CycleMode {
-        let state = self.state.read().await;
+        let state = self.controller.state.read().await;
         state.get_cycle_mode()
     }
 
     /// Get the current running state
     pub async fn get_running(&self) -> bool {
-        let state = self.state.read().await;
+        let state = self.controller.state.read().await;
         state.get_running()
     }
 
     /// Get the current selected job
     pub async fn get_selected_job(&self) -> Option<crate::state::SelectedJobInfo> {
-        let state = self.state.read().await;
+        let state = self.controller.state.read().await;
         state.get_selected_job().cloned()
     }
 }
@@ -382,12 +569,20 @@ impl MockServer {
 /// Server builder for easy configuration
 pub struct MockServerBuilder {
     config: crate::MockConfig,
+    handlers: Vec<(u16, Arc<dyn AsyncCommandHandler + Send + Sync>)>,
+    fallback: Option<Arc<dyn AsyncCommandHandler + Send + Sync>>,
 }
 
 impl MockServerBuilder {
     #[must_use]
     pub fn new() -> Self {
-        Self { config: crate::MockConfig::default() }
+        Self::from_config(crate::MockConfig::default())
+    }
+
+    /// Builder starting from `config`, e.g. a preset such as [`MockConfig::dx200`](crate::MockConfig::dx200)
+    #[must_use]
+    pub const fn from_config(config: crate::MockConfig) -> Self {
+        Self { config, handlers: Vec::new(), fallback: None }
     }
 
     #[must_use]
@@ -414,15 +609,31 @@ impl MockServerBuilder {
         self
     }
 
+    /// Apply the file name rules of `model` to files sent to the controller
+    #[must_use]
+    pub const fn controller_model(mut self, model: proto::ControllerModel) -> Self {
+        self.config.controller_model = model;
+        self
+    }
+
+    /// Apply `rules` instead of those of the controller model, e.g. to reject long names
+    #[must_use]
+    pub const fn file_name_rules(mut self, rules: proto::FileNameRules) -> Self {
+        self.config.file_name_rules = Some(rules);
+        self
+    }
+
     #[must_use]
     pub fn with_alarm(mut self, alarm: proto::Alarm) -> Self {
         self.config.alarms.push(alarm);
         self
     }
 
+    /// Add an alarm to the history of `category`, read through the instance range of the
+    /// category (e.g. 1001-1100 for monitor alarms)
     #[must_use]
-    pub fn with_alarm_history(mut self, alarm: proto::Alarm) -> Self {
-        self.config.alarm_history.push(alarm);
+    pub fn with_alarm_history(mut self, category: AlarmCategory, alarm: proto::Alarm) -> Self {
+        self.config.alarm_history.push((category, alarm));
         self
     }
 
@@ -438,6 +649,28 @@ impl MockServerBuilder {
         self
     }
 
+    /// Set the pulse position read through 0x75 `instance`: robots 1-8, base axes 11-18
+    /// or stations 21-44
+    #[must_use]
+    pub fn with_pulse_position(mut self, instance: u16, position: proto::PulsePosition) -> Self {
+        self.config.positions.insert(instance, proto::Position::Pulse(position));
+        self
+    }
+
+    /// Set the Cartesian position of `robot` (1-8), read through 0x75 instance 101-108
+    #[must_use]
+    pub fn with_cartesian_position(
+        mut self,
+        robot: u8,
+        position: proto::CartesianPosition,
+    ) -> Self {
+        self.config.positions.insert(
+            ROBOT_CARTESIAN_INSTANCE_OFFSET + u16::from(robot),
+            proto::Position::Cartesian(position),
+        );
+        self
+    }
+
     #[must_use]
     pub const fn with_status(mut self, status: proto::Status) -> Self {
         self.config.default_status = status;
@@ -462,17 +695,163 @@ impl MockServerBuilder {
         self
     }
 
+    #[must_use]
+    pub fn with_file(mut self, filename: impl Into<String>, content: Vec<u8>) -> Self {
+        self.config.files.insert(filename.into(), content);
+        self
+    }
+
+    /// Start in `mode`, which limits the files that may be sent or deleted
+    #[must_use]
+    pub const fn with_security_mode(mut self, mode: SecurityMode) -> Self {
+        self.config.security_mode = mode;
+        self
+    }
+
+    #[must_use]
+    pub fn with_job_simulation(mut self, simulation: JobSimulation) -> Self {
+        self.config.job_simulation = Some(simulation);
+        self
+    }
+
+    #[must_use]
+    pub const fn with_motion_simulation(mut self, simulation: MotionSimulation) -> Self {
+        self.config.motion_simulation = Some(simulation);
+        self
+    }
+
     #[must_use]
     pub const fn with_cycle_mode(mut self, mode: proto::CycleMode) -> Self {
         self.config.cycle_mode = mode;
         self
     }
 
+    #[must_use]
+    pub fn with_system_info(mut self, system_info: proto::SystemInfo) -> Self {
+        self.config.system_info = system_info;
+        self
+    }
+
+    /// Stop the server with an error when a request is not handled within `timeout`
+    #[must_use]
+    pub const fn with_watchdog_timeout(mut self, timeout: Duration) -> Self {
+        self.config.watchdog_timeout = Some(timeout);
+        self
+    }
+
+    #[must_use]
+    pub const fn without_watchdog(mut self) -> Self {
+        self.config.watchdog_timeout = None;
+        self
+    }
+
+    /// Delay every response by `latency`
+    #[must_use]
+    pub const fn with_response_latency(mut self, latency: Duration) -> Self {
+        self.config.response_latency = latency;
+        self
+    }
+
+    /// Delay robot control responses by up to `jitter` more, drawn from the seed
+    #[must_use]
+    pub const fn with_latency_jitter(mut self, jitter: Duration) -> Self {
+        self.config.latency_jitter = jitter;
+        self
+    }
+
+    /// Draw every random value of the server from `seed`, including the chaos faults of a
+    /// [`MockConfig::chaos`](crate::MockConfig::chaos) configuration
+    #[must_use]
+    pub const fn with_seed(mut self, seed: u64) -> Self {
+        self.config.seed = seed;
+        if let Some(chaos) = &mut self.config.chaos {
+            chaos.seed = seed;
+        }
+        self
+    }
+
+    /// Log at most at `level`, e.g. [`LevelFilter::Warn`] to keep the frame dumps out
+    /// of test output
+    #[must_use]
+    pub const fn with_log_level(mut self, level: LevelFilter) -> Self {
+        self.config.log_level = level;
+        self
+    }
+
+    /// Answer `command` as undefined
+    #[must_use]
+    pub fn with_unsupported_command(mut self, command: u16) -> Self {
+        self.config.unsupported_commands.push(command);
+        self
+    }
+
+    /// Answer commands without a handler as `policy` says
+    #[must_use]
+    pub const fn with_unknown_command_policy(
+        mut self,
+        policy: crate::UnknownCommandPolicy,
+    ) -> Self {
+        self.config.unknown_commands = policy;
+        self
+    }
+
+    /// Handle every command without a handler with `handler`, e.g. to record or answer
+    /// commands the mock does not simulate yet
+    ///
+    /// Commands configured with [`with_unsupported_command`](Self::with_unsupported_command)
+    /// are still answered as undefined.
+    #[must_use]
+    pub fn with_fallback_handler(
+        mut self,
+        handler: impl AsyncCommandHandler + Send + Sync + 'static,
+    ) -> Self {
+        self.fallback = Some(Arc::new(handler));
+        self
+    }
+
+    /// Handle `command` with `handler` instead of the built-in handler
+    #[must_use]
+    pub fn with_handler(
+        mut self,
+        command: u16,
+        handler: impl CommandHandler + Send + Sync + 'static,
+    ) -> Self {
+        self.handlers.push((command, Arc::new(SyncHandler(handler))));
+        self
+    }
+
+    /// Handle `command` with an asynchronous `handler` instead of the built-in handler
+    #[must_use]
+    pub fn with_async_handler(
+        mut self,
+        command: u16,
+        handler: impl AsyncCommandHandler + Send + Sync + 'static,
+    ) -> Self {
+        self.handlers.push((command, Arc::new(handler)));
+        self
+    }
+
     /// # Errors
     ///
     /// Returns an error if server creation fails
     pub async fn build(self) -> Result<MockServer, Box<dyn std::error::Error + Send + Sync>> {
-        MockServer::new(self.config).await
+        let config = self.config.clone();
+        MockServer::bind(&config, self.build_controller()).await
+    }
+
+    /// Controller answering requests in-process, without binding the ports
+    ///
+    /// The host, ports and watchdog settings are not used.
+    #[must_use]
+    pub fn build_controller(self) -> MockController {
+        let mut controller = MockController::new(&self.config);
+        for (command, handler) in self.handlers {
+            controller.handlers.register(command, handler);
+        }
+        if let Some(fallback) = self.fallback {
+            controller.handlers.set_fallback(fallback);
+        }
+        controller
     }
 }
 
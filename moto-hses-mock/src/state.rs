@@ -2,9 +2,9 @@
 
 use moto_hses_proto as proto;
 use proto::commands::alarm::AlarmCategory;
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use tokio::sync::{RwLock, watch};
 
 /// Variable type for HSES variable spaces.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -14,11 +14,92 @@ pub enum VariableType {
     Double,
     Real,
     String,
+    /// Robot position variable (P)
+    Position,
+    /// Base position variable (BP)
+    BasePosition,
+    /// Station (external axis) variable (EX)
+    ExternalAxis,
+}
+
+impl VariableType {
+    /// Size of one variable on the wire [bytes]
+    #[must_use]
+    pub const fn size(self) -> usize {
+        match self {
+            Self::Byte => 1,
+            Self::Integer => 2,
+            Self::Double | Self::Real => 4,
+            Self::String => 16,
+            Self::BasePosition | Self::ExternalAxis => 36,
+            Self::Position => 52,
+        }
+    }
 }
 
 /// Typed variable map keyed by `(VariableType, index)`.
 pub type TypedVariables = HashMap<(VariableType, u16), Vec<u8>>;
 
+/// Simulated job execution started by the job start command
+///
+/// The job starts running `start_delay` after the start request, runs for `run_time`,
+/// then stops. If `alarm` is set it is raised when the job stops, as if the job had been
+/// interrupted by that alarm.
+#[derive(Debug, Clone)]
+pub struct JobSimulation {
+    pub run_time: std::time::Duration,
+    pub alarm: Option<proto::Alarm>,
+    /// Time between the start request and the job being reported running
+    pub start_delay: std::time::Duration,
+}
+
+impl JobSimulation {
+    #[must_use]
+    pub const fn new(run_time: std::time::Duration) -> Self {
+        Self { run_time, alarm: None, start_delay: std::time::Duration::ZERO }
+    }
+
+    /// Report the job running only `delay` after the start request
+    #[must_use]
+    pub const fn with_start_delay(mut self, delay: std::time::Duration) -> Self {
+        self.start_delay = delay;
+        self
+    }
+
+    /// Raise `alarm` when the simulated job stops
+    #[must_use]
+    pub fn with_alarm(mut self, alarm: proto::Alarm) -> Self {
+        self.alarm = Some(alarm);
+        self
+    }
+}
+
+/// Simulated robot motion for move instructions
+///
+/// Without it a move instruction completes immediately. With it the robot travels to
+/// the target at the commanded speed and reports running until it arrives.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MotionSimulation {
+    /// Control point speed of a joint speed move at 100% [mm/s]
+    pub max_linear_speed: f32,
+}
+
+impl Default for MotionSimulation {
+    fn default() -> Self {
+        Self { max_linear_speed: 1000.0 }
+    }
+}
+
+/// Move in progress under a [`MotionSimulation`]
+#[derive(Debug, Clone)]
+pub struct Motion {
+    pub from: proto::CartesianPosition,
+    pub to: proto::CartesianPosition,
+    /// Start of the move, on the tokio clock like `MockState::job_started_at`
+    pub started_at: tokio::time::Instant,
+    pub duration: std::time::Duration,
+}
+
 /// Selected job information
 #[derive(Debug, Clone)]
 pub struct SelectedJobInfo {
@@ -27,14 +108,268 @@ pub struct SelectedJobInfo {
     pub select_type: u16, // Instance value
 }
 
+// Current host time in seconds since the Unix epoch
+fn host_unix_seconds() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |elapsed| i64::try_from(elapsed.as_secs()).unwrap_or(i64::MAX))
+}
+
+/// Fault injected into the response to a robot control request
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResponseFault {
+    /// Handle the request but send no response, as if the response was lost
+    Drop,
+    /// Send the response twice
+    Duplicate,
+    /// Send the response after a delay without holding up later requests, so that the
+    /// responses to later requests overtake it
+    Delay(std::time::Duration),
+}
+
+/// Change applied to the payload of successful responses to one command
+///
+/// Faults stay in effect until cleared, so that every retry sees the same malformed payload.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PayloadFault {
+    /// Send only the first bytes of the payload, at most this many
+    Truncate(usize),
+    /// Append this many zero bytes to the payload
+    Extend(usize),
+}
+
+impl PayloadFault {
+    /// Apply the fault to `payload`
+    pub fn apply(self, payload: &mut Vec<u8>) {
+        match self {
+            Self::Truncate(len) => payload.truncate(len),
+            Self::Extend(extra) => payload.resize(payload.len() + extra, 0),
+        }
+    }
+}
+
+/// Random faults applied to robot control requests, for resilience soak tests
+///
+/// Each request draws its faults independently, from the seed and the number of the
+/// request, so that the same sequence of requests meets the same faults again. Faults
+/// injected with [`MockHandle::inject_fault`](crate::MockHandle::inject_fault) take the
+/// place of the drawn drop, duplicate or delay.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ChaosConfig {
+    pub seed: u64,
+    /// Share of responses dropped, from 0.0 to 1.0
+    pub drop_rate: f64,
+    /// Share of responses sent twice
+    pub duplicate_rate: f64,
+    /// Share of requests answered with an error status (0x1F, added status 0x2010) instead
+    /// of being handled
+    pub error_rate: f64,
+    /// Longest delay added to a response; delayed responses may be overtaken by later ones
+    pub max_jitter: std::time::Duration,
+}
+
+impl ChaosConfig {
+    /// Status and added status of the error responses
+    pub const ERROR_STATUS: (u8, u16) = (0x1F, 0x2010);
+
+    /// Whether the `request`th robot control request is answered with an error status
+    #[must_use]
+    pub fn is_error(&self, request: u64) -> bool {
+        self.roll(request, 0) < self.error_rate
+    }
+
+    /// Fault applied to the response to the `request`th robot control request
+    #[must_use]
+    pub fn fault(&self, request: u64) -> Option<ResponseFault> {
+        if self.roll(request, 1) < self.drop_rate {
+            Some(ResponseFault::Drop)
+        } else if self.roll(request, 2) < self.duplicate_rate {
+            Some(ResponseFault::Duplicate)
+        } else if self.max_jitter.is_zero() {
+            None
+        } else {
+            Some(ResponseFault::Delay(self.max_jitter.mul_f64(self.roll(request, 3))))
+        }
+    }
+
+    // Random fraction of draw `draw` (0-3) of a request
+    fn roll(&self, request: u64, draw: u64) -> f64 {
+        let seed = self.seed.wrapping_add(request.wrapping_mul(4)).wrapping_add(draw);
+        crate::random::SplitMix64::new(seed).fraction()
+    }
+}
+
+/// Security mode of the programming pendant, which limits the files that may be changed
+///
+/// Files are read and listed in every mode. Changing them takes a higher mode: job files
+/// (`.JBI`) from editing mode, the other files, e.g. parameters and condition files, from
+/// management mode. Other requests are answered with [`Self::DENIED_STATUS`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub enum SecurityMode {
+    Operation,
+    Editing,
+    /// Every file may be changed; the default of the mock
+    #[default]
+    Management,
+}
+
+impl SecurityMode {
+    /// Status and added status of file writes and deletes denied by the security mode
+    /// (0x1F, added status 0x2110: the data cannot be accessed)
+    pub const DENIED_STATUS: (u8, u16) = (0x1F, 0x2110);
+
+    /// Whether the file `filename` may be sent or deleted in this mode
+    #[must_use]
+    pub fn may_change_file(self, filename: &str) -> bool {
+        let is_job = std::path::Path::new(filename)
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("JBI"));
+        let required = if is_job { Self::Editing } else { Self::Management };
+        self >= required
+    }
+}
+
+/// Number of recent requests whose IDs are checked for reuse
+pub const DUPLICATE_REQUEST_ID_WINDOW: usize = 64;
+
+/// Counters of the requests received and the responses withheld
+///
+/// Counters only grow until [`MockHandle::reset_metrics`](crate::MockHandle::reset_metrics).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MockMetrics {
+    /// Requests received per division and command, including retries
+    pub requests: HashMap<(u8, u16), u64>,
+    /// Requests whose division and request ID match one of the previous
+    /// [`DUPLICATE_REQUEST_ID_WINDOW`] requests
+    pub duplicate_request_ids: u64,
+    /// Responses not sent because of an injected [`ResponseFault::Drop`]
+    pub dropped_responses: u64,
+    /// Requests answered with the error status of a [`ChaosConfig`]
+    pub chaos_errors: u64,
+    recent_request_ids: VecDeque<(u8, u8)>,
+}
+
+impl MockMetrics {
+    /// Requests received for `command` of the robot control division
+    #[must_use]
+    pub fn robot_requests(&self, command: u16) -> u64 {
+        self.requests.get(&(proto::Division::Robot as u8, command)).copied().unwrap_or(0)
+    }
+
+    /// Requests received by the file control division
+    #[must_use]
+    pub fn file_requests(&self) -> u64 {
+        self.requests
+            .iter()
+            .filter(|((division, _), _)| *division == proto::Division::File as u8)
+            .map(|(_, count)| count)
+            .sum()
+    }
+
+    fn record(&mut self, message: &proto::HsesRequestMessage) {
+        let key = (message.header.division, message.sub_header.command);
+        *self.requests.entry(key).or_default() += 1;
+        let id = (message.header.division, message.header.request_id);
+        if self.recent_request_ids.contains(&id) {
+            self.duplicate_request_ids += 1;
+        }
+        if self.recent_request_ids.len() == DUPLICATE_REQUEST_ID_WINDOW {
+            self.recent_request_ids.pop_front();
+        }
+        self.recent_request_ids.push_back(id);
+    }
+}
+
+/// Write to a forced I/O point, masked by the forcing
+///
+/// On a controller, forcing a signal from the pendant holds it at the forced value whatever
+/// the application writes; these writes are kept so that a test can check that the
+/// application noticed, or at least that its writes were masked.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IoForceConflict {
+    pub io_number: u16,
+    /// Value the point is forced to
+    pub forced: u8,
+    /// Value written, which the point did not take
+    pub written: u8,
+}
+
+/// Request received by the mock, as listed in a transcript
+///
+/// It leaves out the request ID and block number, so that transcripts of the same
+/// exchange compare equal between runs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecordedRequest {
+    pub division: u8,
+    pub command: u16,
+    pub instance: u16,
+    pub attribute: u8,
+    pub service: u8,
+    pub payload: Vec<u8>,
+}
+
+impl RecordedRequest {
+    #[must_use]
+    pub fn new(message: &proto::HsesRequestMessage) -> Self {
+        Self {
+            division: message.header.division,
+            command: message.sub_header.command,
+            instance: message.sub_header.instance,
+            attribute: message.sub_header.attribute,
+            service: message.sub_header.service,
+            payload: message.payload.clone(),
+        }
+    }
+}
+
+impl std::fmt::Display for RecordedRequest {
+    /// One line, e.g. `robot command=0x0079 instance=5 attribute=1 service=0x0E payload=[]`
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.division {
+            1 => f.write_str("robot")?,
+            2 => f.write_str("file")?,
+            division => write!(f, "0x{division:02X}")?,
+        }
+        write!(
+            f,
+            " command=0x{:04X} instance={} attribute={} service=0x{:02X} payload=[",
+            self.command, self.instance, self.attribute, self.service
+        )?;
+        for (index, byte) in self.payload.iter().enumerate() {
+            if index > 0 {
+                f.write_str(" ")?;
+            }
+            write!(f, "{byte:02X}")?;
+        }
+        f.write_str("]")
+    }
+}
+
+/// Added to the robot number in the 0x75 instance of a robot's Cartesian position
+pub const ROBOT_CARTESIAN_INSTANCE_OFFSET: u16 = 100;
+
+// System information of a mock controller without a configured one
+pub(crate) fn default_system_info() -> proto::SystemInfo {
+    proto::SystemInfo::new("V1.0.0".to_string(), "FS100".to_string(), "P1.0.0".to_string())
+}
+
 /// Mock server state
 #[derive(Debug, Clone)]
+#[allow(clippy::struct_excessive_bools)]
 pub struct MockState {
     pub text_encoding: proto::TextEncoding,
     pub status: proto::Status,
+    /// Position of robot 1 as last set or moved to, in either representation
     pub position: proto::Position,
+    /// Current positions read with 0x75, by instance: pulse positions of robots (1-8), base
+    /// axes (11-18) and stations (21-44), and Cartesian positions of robots (101-108)
+    pub positions: HashMap<u16, proto::Position>,
     pub variables: TypedVariables,
     pub io_states: HashMap<u16, u8>,
+    /// Forced I/O points and the values they are forced to; writes to them are masked
+    pub forced_io: HashMap<u16, u8>,
+    /// Writes masked by forced I/O points, in the order received
+    pub io_force_conflicts: Vec<IoForceConflict>,
     pub registers: HashMap<u16, i16>,
     pub alarms: Vec<proto::Alarm>,
     pub alarm_history: AlarmHistory,
@@ -45,6 +380,43 @@ pub struct MockState {
     pub hlock_state: bool,
     pub cycle_mode: proto::CycleMode,
     pub files: HashMap<String, Vec<u8>>,
+    /// File being sent in blocks and the number of the last block received
+    pub file_upload: Option<(String, u32)>,
+    /// Security mode limiting the files that may be sent or deleted
+    pub security_mode: SecurityMode,
+    /// Model whose file name rules apply to files sent to the controller
+    pub controller_model: proto::ControllerModel,
+    /// File name rules applied instead of those of `controller_model`
+    pub file_name_rules: Option<proto::FileNameRules>,
+    /// System information reported for every system instance
+    pub system_info: proto::SystemInfo,
+    /// Job execution simulation; without it a started job runs until stopped by another command
+    pub job_simulation: Option<JobSimulation>,
+    /// Start request of the simulated job currently running, on the tokio clock so that tests
+    /// with paused time schedule the simulation deterministically
+    pub job_started_at: Option<tokio::time::Instant>,
+    /// Motion simulation; without it move instructions complete immediately
+    pub motion_simulation: Option<MotionSimulation>,
+    /// Simulated move currently in progress
+    pub motion: Option<Motion>,
+    /// Time the mock controller was powered on, the start of all management times
+    pub power_on_at: std::time::Instant,
+    /// Faults applied to the responses of the next robot control requests, one each
+    pub response_faults: VecDeque<ResponseFault>,
+    /// Faults applied to the payloads of successful responses, per robot control command
+    pub payload_faults: HashMap<u16, PayloadFault>,
+    /// Random faults applied to every robot control request
+    pub chaos: Option<ChaosConfig>,
+    /// Number of robot control requests received
+    pub robot_requests: u64,
+    /// Most verbose level logged by the handlers
+    pub log_level: log::LevelFilter,
+    /// Requests received since the transcript was started; `None` while not recording
+    pub transcript: Option<Vec<RecordedRequest>>,
+    /// Counters of the requests received and the responses withheld
+    pub metrics: MockMetrics,
+    /// Seed of the random draws of the server, from [`MockConfig::seed`](crate::MockConfig::seed)
+    pub seed: u64,
 }
 
 /// Alarm history organized by categories
@@ -198,8 +570,11 @@ impl MockState {
             position: proto::Position::Pulse(proto::PulsePosition::new(vec![
                 0, 0, 0, 0, 0, 0, 0, 0,
             ])),
+            positions: HashMap::new(),
             variables,
             io_states,
+            forced_io: HashMap::new(),
+            io_force_conflicts: Vec::new(),
             registers,
             alarms,
             alarm_history,
@@ -210,6 +585,24 @@ impl MockState {
             hlock_state: false,
             cycle_mode: proto::CycleMode::Continuous,
             files,
+            file_upload: None,
+            security_mode: SecurityMode::default(),
+            controller_model: proto::ControllerModel::default(),
+            file_name_rules: None,
+            system_info: default_system_info(),
+            job_simulation: None,
+            job_started_at: None,
+            motion_simulation: None,
+            motion: None,
+            power_on_at: std::time::Instant::now(),
+            response_faults: VecDeque::new(),
+            payload_faults: HashMap::new(),
+            chaos: None,
+            robot_requests: 0,
+            log_level: log::LevelFilter::Trace,
+            transcript: None,
+            metrics: MockMetrics::default(),
+            seed: 0,
         }
     }
     /// Get variable value
@@ -454,10 +847,40 @@ impl MockState {
     }
 
     /// Set I/O state
+    ///
+    /// A forced point keeps its forced value; a write of another value is recorded in
+    /// [`io_force_conflicts`](Self::io_force_conflicts) instead.
     pub fn set_io_state(&mut self, io_number: u16, value: u8) {
+        match self.forced_io.get(&io_number) {
+            Some(&forced) if forced != value => {
+                self.io_force_conflicts.push(IoForceConflict { io_number, forced, written: value });
+            }
+            Some(_) => {}
+            None => {
+                self.io_states.insert(io_number, value);
+            }
+        }
+    }
+
+    /// Force an I/O point to `value`, as from the pendant, until
+    /// [`release_io`](Self::release_io)
+    pub fn force_io(&mut self, io_number: u16, value: u8) {
+        self.forced_io.insert(io_number, value);
         self.io_states.insert(io_number, value);
     }
 
+    /// Release a forced I/O point, which keeps the forced value until written again;
+    /// returns whether the point was forced
+    pub fn release_io(&mut self, io_number: u16) -> bool {
+        self.forced_io.remove(&io_number).is_some()
+    }
+
+    /// Whether an I/O point is forced
+    #[must_use]
+    pub fn is_io_forced(&self, io_number: u16) -> bool {
+        self.forced_io.contains_key(&io_number)
+    }
+
     /// Get multiple I/O states
     ///
     /// # Errors
@@ -554,17 +977,21 @@ impl MockState {
     }
 
     /// Set servo state
-    pub const fn set_servo(&mut self, on: bool) {
+    pub fn set_servo(&mut self, on: bool) {
         self.servo_on = on;
         self.status.data2.servo_on = on;
+        if !on {
+            self.stop_motion();
+        }
     }
 
     /// Set hold state
-    pub const fn set_hold(&mut self, hold: bool) {
+    pub fn set_hold(&mut self, hold: bool) {
         self.hold_state = hold;
         self.status.data2.command_hold = hold;
         // If HOLD is ON, running should be false
         if hold {
+            self.stop_motion();
             self.status.data1.running = false;
         } else {
             // If HOLD is OFF, running should be true (assuming no other holds)
@@ -577,6 +1004,114 @@ impl MockState {
         self.status.data1.running = running;
     }
 
+    /// Start the selected job, simulating its execution if configured
+    pub fn start_job(&mut self) {
+        let start_delay = self
+            .job_simulation
+            .as_ref()
+            .map_or(std::time::Duration::ZERO, |simulation| simulation.start_delay);
+        if start_delay.is_zero() {
+            self.set_running(true);
+        }
+        if let Some(selected) = &self.selected_job {
+            let speed_override_value =
+                self.executing_job.as_ref().map_or(100, |job| job.speed_override_value);
+            self.executing_job = Some(proto::ExecutingJobInfo::new(
+                selected.job_name.clone(),
+                selected.line_number,
+                0,
+                speed_override_value,
+            ));
+        }
+        if self.job_simulation.is_some() {
+            self.job_started_at = Some(tokio::time::Instant::now());
+        }
+    }
+
+    /// Start the simulated job once its start delay has elapsed, and stop it once its run
+    /// time has elapsed too
+    pub fn advance_job_simulation(&mut self) {
+        let (Some(simulation), Some(started_at)) = (&self.job_simulation, self.job_started_at)
+        else {
+            return;
+        };
+        let elapsed = started_at.elapsed();
+        if elapsed < simulation.start_delay {
+            return;
+        }
+        if elapsed < simulation.start_delay + simulation.run_time {
+            self.set_running(true);
+            return;
+        }
+
+        self.job_started_at = None;
+        if let Some(alarm) = simulation.alarm.clone() {
+            // The latest alarm is reported as instance 1
+            self.alarms.insert(0, alarm);
+            self.status.data2.alarm = true;
+        }
+        self.set_running(false);
+    }
+
+    /// Start a simulated move from the current position to `target`
+    pub fn start_motion(
+        &mut self,
+        target: proto::CartesianPosition,
+        duration: std::time::Duration,
+    ) {
+        let proto::Position::Cartesian(from) = &self.position else {
+            // Without a Cartesian start there is nothing to interpolate
+            self.update_position(proto::Position::Cartesian(target));
+            return;
+        };
+        self.motion = Some(Motion {
+            from: from.clone(),
+            to: target,
+            started_at: tokio::time::Instant::now(),
+            duration,
+        });
+        self.set_running(true);
+    }
+
+    /// Move the robot along the simulated move, stopping it at the target
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn advance_motion_simulation(&mut self) {
+        let Some(motion) = &self.motion else {
+            return;
+        };
+        let elapsed = motion.started_at.elapsed();
+        if elapsed >= motion.duration {
+            self.update_position(proto::Position::Cartesian(motion.to.clone()));
+            self.motion = None;
+            self.set_running(false);
+            return;
+        }
+
+        let ratio = (elapsed.as_secs_f64() / motion.duration.as_secs_f64()) as f32;
+        let lerp = |from: f32, to: f32| (to - from).mul_add(ratio, from);
+        let (from, to) = (&motion.from, &motion.to);
+        let mut position = to.clone();
+        position.x = lerp(from.x, to.x);
+        position.y = lerp(from.y, to.y);
+        position.z = lerp(from.z, to.z);
+        position.rx = lerp(from.rx, to.rx);
+        position.ry = lerp(from.ry, to.ry);
+        position.rz = lerp(from.rz, to.rz);
+        self.update_position(proto::Position::Cartesian(position));
+    }
+
+    /// Stop a simulated move where the robot currently is
+    pub fn stop_motion(&mut self) {
+        self.advance_motion_simulation();
+        self.motion = None;
+    }
+
+    /// Current time of the controller clock, which follows the host clock
+    #[must_use]
+    pub fn controller_time(&self) -> proto::ControllerDateTime {
+        proto::ControllerDateTime::from_unix_seconds(host_unix_seconds()).unwrap_or_default()
+    }
+
     /// Get running state
     #[must_use]
     pub const fn get_running(&self) -> bool {
@@ -589,7 +1124,20 @@ impl MockState {
     }
 
     /// Set selected job
+    ///
+    /// Selecting the job in execution (select type 1) also moves the executing job
+    /// information of the master task (0x73) to the job and line, as on a controller.
     pub fn set_selected_job(&mut self, job_name: String, line_number: u32, select_type: u16) {
+        if select_type == 1 {
+            let speed_override_value =
+                self.executing_job.as_ref().map_or(100, |job| job.speed_override_value);
+            self.executing_job = Some(proto::ExecutingJobInfo::new(
+                job_name.clone(),
+                line_number,
+                0,
+                speed_override_value,
+            ));
+        }
         self.selected_job = Some(SelectedJobInfo { job_name, line_number, select_type });
     }
 
@@ -599,15 +1147,52 @@ impl MockState {
         self.selected_job.as_ref()
     }
 
-    /// Update position
+    /// Update the position of robot 1
+    ///
+    /// The position is also read back through the 0x75 instance of its representation;
+    /// the other representation keeps its value, as the mock has no kinematics to convert.
     pub fn update_position(&mut self, position: proto::Position) {
+        let instance = match position {
+            proto::Position::Pulse(_) => 1,
+            proto::Position::Cartesian(_) => ROBOT_CARTESIAN_INSTANCE_OFFSET + 1,
+        };
+        self.positions.insert(instance, position.clone());
         self.position = position;
     }
 
-    /// Get file list
+    /// Current position of a 0x75 instance
+    ///
+    /// Instances without a set position read as zero in their representation. Returns
+    /// `None` if `instance` is not a control group.
+    #[must_use]
+    pub fn current_position(&self, instance: u16) -> Option<proto::Position> {
+        if let Some(position) = self.positions.get(&instance) {
+            return Some(position.clone());
+        }
+        match instance {
+            1..=8 | 11..=18 | 21..=44 => {
+                Some(proto::Position::Pulse(proto::PulsePosition::new(vec![0; 8])))
+            }
+            101..=108 => Some(proto::Position::Cartesian(proto::CartesianPosition::new(
+                0.0,
+                0.0,
+                0.0,
+                0.0,
+                0.0,
+                0.0,
+                0,
+                0,
+                proto::payload::position::Configuration::from_raw(0),
+                proto::payload::position::ExtendedConfiguration::from_raw(0),
+            ))),
+            _ => None,
+        }
+    }
+
+    /// Get file list sorted by name
     #[must_use]
     pub fn get_file_list(&self, pattern: &str) -> Vec<String> {
-        if pattern == "*" || pattern.is_empty() {
+        let mut files: Vec<String> = if pattern == "*" || pattern.is_empty() {
             // Return all files
             self.files.keys().cloned().collect()
         } else if pattern.starts_with("*.") {
@@ -621,7 +1206,9 @@ impl MockState {
                 .filter(|name| name.contains(pattern.trim_matches('*')))
                 .cloned()
                 .collect()
-        }
+        };
+        files.sort();
+        files
     }
 
     /// Get file content
@@ -640,6 +1227,14 @@ impl MockState {
         self.files.remove(filename).is_some()
     }
 
+    /// Count `message` in the metrics and add it to the transcript, if recording
+    pub fn record_request(&mut self, message: &proto::HsesRequestMessage) {
+        self.metrics.record(message);
+        if let Some(transcript) = &mut self.transcript {
+            transcript.push(RecordedRequest::new(message));
+        }
+    }
+
     /// Set HLOCK state
     pub const fn set_hlock(&mut self, enabled: bool) {
         self.hlock_state = enabled;
@@ -665,7 +1260,9 @@ impl MockState {
 
 #[cfg(test)]
 mod tests {
-    use super::{MockState, VariableType};
+    use super::{DUPLICATE_REQUEST_ID_WINDOW, MockState, SharedState, VariableType};
+    use moto_hses_proto::HsesRequestMessage;
+    use std::sync::{Arc, Mutex};
 
     #[test]
     fn variables_are_isolated_by_type_at_same_index() {
@@ -690,32 +1287,223 @@ mod tests {
         );
         assert_eq!(state.get_variable(VariableType::String, index), Some(&b"abc".to_vec()));
     }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn metrics_count_reused_request_ids_within_window() {
+        let mut state = MockState::default();
+        let request = |request_id, command| {
+            HsesRequestMessage::new(1, 0, request_id, command, 1, 0, 0x0e, vec![]).unwrap()
+        };
+
+        state.record_request(&request(1, 0x79));
+        state.record_request(&request(2, 0x72));
+        state.record_request(&request(1, 0x79));
+        assert_eq!(state.metrics.robot_requests(0x79), 2);
+        assert_eq!(state.metrics.robot_requests(0x72), 1);
+        assert_eq!(state.metrics.duplicate_request_ids, 1);
+
+        // IDs that left the window are not duplicates
+        for request_id in 10..10 + DUPLICATE_REQUEST_ID_WINDOW {
+            state.record_request(&request(u8::try_from(request_id).unwrap(), 0x79));
+        }
+        state.record_request(&request(2, 0x72));
+        assert_eq!(state.metrics.duplicate_request_ids, 1);
+        assert_eq!(state.metrics.file_requests(), 0);
+    }
+
+    #[tokio::test]
+    #[allow(clippy::unwrap_used)]
+    async fn observers_see_changed_values_only() {
+        let state = SharedState::default();
+        state.write().await.set_io_state(1001, 0);
+        let changes = Arc::new(Mutex::new(Vec::new()));
+        let io_changes = Arc::clone(&changes);
+        state.on_io_changed(move |io_number, value| {
+            io_changes.lock().unwrap().push((io_number, value));
+        });
+
+        {
+            let mut guard = state.write().await;
+            guard.set_io_state(1001, 0);
+            guard.set_io_state(1002, 1);
+        }
+        assert_eq!(*changes.lock().unwrap(), vec![(1002, 1)]);
+
+        let waiter = state.clone();
+        let wait = tokio::spawn(async move {
+            waiter.wait_for(|state| state.get_io_state(1001) != 0).await;
+        });
+        state.write().await.set_io_state(1001, 0x01);
+        tokio::time::timeout(std::time::Duration::from_secs(1), wait).await.unwrap().unwrap();
+        assert_eq!(*changes.lock().unwrap(), vec![(1002, 1), (1001, 1)]);
+    }
+}
+
+type VariableObserver = Box<dyn Fn(VariableType, u16, &[u8]) + Send + Sync>;
+type IoObserver = Box<dyn Fn(u16, u8) + Send + Sync>;
+type StatusObserver = Box<dyn Fn(&proto::Status) + Send + Sync>;
+
+// Callbacks registered on a shared state, and a counter of the writes to it
+#[derive(Default)]
+struct Observers {
+    variable: std::sync::RwLock<Vec<VariableObserver>>,
+    io: std::sync::RwLock<Vec<IoObserver>>,
+    status: std::sync::RwLock<Vec<StatusObserver>>,
+    writes: watch::Sender<u64>,
+}
+
+impl Observers {
+    fn is_empty(&self) -> bool {
+        self.variable.read().is_ok_and(|observers| observers.is_empty())
+            && self.io.read().is_ok_and(|observers| observers.is_empty())
+            && self.status.read().is_ok_and(|observers| observers.is_empty())
+    }
+
+    // Call the observers of the values that differ between `before` and `after`
+    fn notify(&self, before: &Snapshot, after: &MockState) {
+        if let Ok(observers) = self.variable.read() {
+            for (&(var_type, index), value) in &after.variables {
+                if before.variables.get(&(var_type, index)) != Some(value) {
+                    observers.iter().for_each(|observer| observer(var_type, index, value));
+                }
+            }
+        }
+        if let Ok(observers) = self.io.read() {
+            for (&io_number, &value) in &after.io_states {
+                if before.io_states.get(&io_number) != Some(&value) {
+                    observers.iter().for_each(|observer| observer(io_number, value));
+                }
+            }
+        }
+        if let Ok(observers) = self.status.read()
+            && before.status != after.status
+        {
+            observers.iter().for_each(|observer| observer(&after.status));
+        }
+    }
+}
+
+impl std::fmt::Debug for Observers {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Observers").field("writes", &*self.writes.borrow()).finish_non_exhaustive()
+    }
+}
+
+// Observed values of the state when a write lock was taken
+struct Snapshot {
+    variables: TypedVariables,
+    io_states: HashMap<u16, u8>,
+    status: proto::Status,
+}
+
+/// Write access to the state; observers are notified of the changes when it is dropped
+pub struct StateWriteGuard<'a> {
+    guard: tokio::sync::RwLockWriteGuard<'a, MockState>,
+    observers: &'a Observers,
+    // Taken only when observers are registered
+    snapshot: Option<Snapshot>,
+}
+
+impl std::ops::Deref for StateWriteGuard<'_> {
+    type Target = MockState;
+
+    fn deref(&self) -> &MockState {
+        &self.guard
+    }
+}
+
+impl std::ops::DerefMut for StateWriteGuard<'_> {
+    fn deref_mut(&mut self) -> &mut MockState {
+        &mut self.guard
+    }
+}
+
+impl Drop for StateWriteGuard<'_> {
+    fn drop(&mut self) {
+        if let Some(snapshot) = &self.snapshot {
+            self.observers.notify(snapshot, &self.guard);
+        }
+        self.observers.writes.send_modify(|writes| *writes += 1);
+    }
 }
 
 /// Thread-safe state wrapper
+///
+/// Observers registered with the `on_*_changed` methods are called with the changed
+/// values when a write lock is released, while the state is still locked: they must not
+/// lock the state themselves. Changes made through [`clone_inner`](Self::clone_inner)
+/// are not observed.
 #[derive(Debug)]
 pub struct SharedState {
     inner: Arc<RwLock<MockState>>,
+    observers: Arc<Observers>,
 }
 
 impl SharedState {
     #[must_use]
     pub fn new(state: MockState) -> Self {
-        Self { inner: Arc::new(RwLock::new(state)) }
+        Self { inner: Arc::new(RwLock::new(state)), observers: Arc::default() }
     }
 
     pub async fn read(&self) -> tokio::sync::RwLockReadGuard<'_, MockState> {
         self.inner.read().await
     }
 
-    pub async fn write(&self) -> tokio::sync::RwLockWriteGuard<'_, MockState> {
-        self.inner.write().await
+    pub async fn write(&self) -> StateWriteGuard<'_> {
+        let guard = self.inner.write().await;
+        let snapshot = (!self.observers.is_empty()).then(|| Snapshot {
+            variables: guard.variables.clone(),
+            io_states: guard.io_states.clone(),
+            status: guard.status.clone(),
+        });
+        StateWriteGuard { guard, observers: &self.observers, snapshot }
     }
 
     #[must_use]
     pub fn clone_inner(&self) -> Arc<RwLock<MockState>> {
         Arc::clone(&self.inner)
     }
+
+    /// Call `observer` with the type, index and value of every changed variable
+    pub fn on_variable_changed(
+        &self,
+        observer: impl Fn(VariableType, u16, &[u8]) + Send + Sync + 'static,
+    ) {
+        if let Ok(mut observers) = self.observers.variable.write() {
+            observers.push(Box::new(observer));
+        }
+    }
+
+    /// Call `observer` with the number and value of every changed I/O
+    pub fn on_io_changed(&self, observer: impl Fn(u16, u8) + Send + Sync + 'static) {
+        if let Ok(mut observers) = self.observers.io.write() {
+            observers.push(Box::new(observer));
+        }
+    }
+
+    /// Call `observer` with the new status whenever it changes
+    pub fn on_status_changed(&self, observer: impl Fn(&proto::Status) + Send + Sync + 'static) {
+        if let Ok(mut observers) = self.observers.status.write() {
+            observers.push(Box::new(observer));
+        }
+    }
+
+    /// Wait until `condition` holds for the state
+    ///
+    /// The condition is checked now and again after every write.
+    pub async fn wait_for(&self, condition: impl Fn(&MockState) -> bool + Send) {
+        let mut writes = self.observers.writes.subscribe();
+        loop {
+            let state = self.read().await;
+            if condition(&state) {
+                return;
+            }
+            drop(state);
+            // The sender lives as long as `self`, so waiting cannot fail
+            let _ = writes.changed().await;
+        }
+    }
 }
 
 impl Default for SharedState {
@@ -726,6 +1514,6 @@ impl Default for SharedState {
 
 impl Clone for SharedState {
     fn clone(&self) -> Self {
-        Self { inner: Arc::clone(&self.inner) }
+        Self { inner: Arc::clone(&self.inner), observers: Arc::clone(&self.observers) }
     }
 }
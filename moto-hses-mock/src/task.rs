@@ -0,0 +1,49 @@
+//! Named server tasks
+//!
+//! The tasks of a running [`MockServer`](crate::MockServer) are named, e.g.
+//! `hses-mock-robot` for the receive loop of the robot control port. With the `console`
+//! feature and `RUSTFLAGS="--cfg tokio_unstable"`, the names are given to tokio, so that
+//! `tokio-console` lists the tasks of the mock by name next to those of the client.
+
+use std::future::Future;
+
+use tokio::task::{AbortHandle, JoinHandle, JoinSet};
+
+/// Spawn `future` on the current runtime as the task `name`
+#[track_caller]
+pub fn spawn<F>(name: &'static str, future: F) -> JoinHandle<F::Output>
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    #[cfg(all(feature = "console", tokio_unstable))]
+    {
+        // The builder never fails on the current runtime; the `Result` is reserved by tokio
+        #[allow(clippy::expect_used)]
+        tokio::task::Builder::new().name(name).spawn(future).expect("runtime shutting down")
+    }
+    #[cfg(not(all(feature = "console", tokio_unstable)))]
+    {
+        let _ = name;
+        tokio::spawn(future)
+    }
+}
+
+/// Spawn `future` into `tasks` as the task `name`
+#[track_caller]
+pub fn spawn_in<T, F>(tasks: &mut JoinSet<T>, name: &'static str, future: F) -> AbortHandle
+where
+    T: Send + 'static,
+    F: Future<Output = T> + Send + 'static,
+{
+    #[cfg(all(feature = "console", tokio_unstable))]
+    {
+        #[allow(clippy::expect_used)]
+        tasks.build_task().name(name).spawn(future).expect("runtime shutting down")
+    }
+    #[cfg(not(all(feature = "console", tokio_unstable)))]
+    {
+        let _ = name;
+        tasks.spawn(future)
+    }
+}
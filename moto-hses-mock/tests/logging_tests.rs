@@ -0,0 +1,112 @@
+//! Log records of the mock server
+//!
+//! The records are captured by a logger installed for this test binary only.
+
+#![allow(clippy::expect_used)]
+
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use moto_hses_mock::log_target;
+use moto_hses_mock::server::MockServerBuilder;
+use moto_hses_proto as proto;
+use std::sync::Mutex;
+use tokio::net::UdpSocket;
+use tokio::time::Duration;
+
+// Target, level and message of every record
+static RECORDS: Mutex<Vec<(String, Level, String)>> = Mutex::new(Vec::new());
+
+struct CapturingLogger;
+
+impl Log for CapturingLogger {
+    fn enabled(&self, _metadata: &Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &Record) {
+        RECORDS.lock().expect("Records poisoned").push((
+            record.target().to_string(),
+            record.level(),
+            record.args().to_string(),
+        ));
+    }
+
+    fn flush(&self) {}
+}
+
+static LOGGER: CapturingLogger = CapturingLogger;
+
+// Records logged to `target`
+fn records(target: &str) -> Vec<(Level, String)> {
+    RECORDS
+        .lock()
+        .expect("Records poisoned")
+        .iter()
+        .filter(|(t, _, _)| t == target)
+        .map(|(_, level, message)| (*level, message.clone()))
+        .collect()
+}
+
+fn clear_records() {
+    RECORDS.lock().expect("Records poisoned").clear();
+}
+
+async fn send_robot_request(addr: std::net::SocketAddr, command: u16) {
+    let socket = UdpSocket::bind("127.0.0.1:0").await.expect("Failed to bind socket");
+    let message = proto::HsesRequestMessage::new(1, 0, 1, command, 1, 1, 0x0e, vec![])
+        .expect("Failed to create request message");
+    socket.send_to(&message.encode(), addr).await.expect("Failed to send data");
+    let mut buf = vec![0u8; 1024];
+    tokio::time::timeout(Duration::from_secs(1), socket.recv_from(&mut buf))
+        .await
+        .expect("Timed out waiting for response")
+        .expect("Failed to receive data");
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_log_targets_and_level() {
+    log::set_logger(&LOGGER).expect("Failed to install logger");
+    log::set_max_level(LevelFilter::Trace);
+
+    let server = MockServerBuilder::new()
+        .robot_port(0)
+        .file_port(0)
+        .with_unknown_command_policy(moto_hses_mock::UnknownCommandPolicy::Ignore)
+        .with_log_level(LevelFilter::Info)
+        .build()
+        .await
+        .expect("Failed to build server");
+    let addr = server.local_addr().expect("Failed to get address");
+    let handle = tokio::spawn(async move { server.run_for(Duration::from_secs(5)).await });
+
+    send_robot_request(addr, 0x9999).await;
+    send_robot_request(addr, 0x9999).await;
+    assert_eq!(
+        records(log_target::HANDLERS),
+        vec![(Level::Warn, "Ignoring unknown command 0x9999".to_string())],
+        "Warned once per command"
+    );
+    // Frame dumps are debug records, above the level of the server
+    assert!(records(log_target::TRAFFIC).is_empty());
+    handle.abort();
+
+    let server = MockServerBuilder::new()
+        .robot_port(0)
+        .file_port(0)
+        .build()
+        .await
+        .expect("Failed to build server");
+    let addr = server.local_addr().expect("Failed to get address");
+    let handle = tokio::spawn(async move { server.run_for(Duration::from_secs(5)).await });
+    clear_records();
+
+    send_robot_request(addr, 0x9999).await;
+    assert_eq!(
+        records(log_target::HANDLERS),
+        vec![(Level::Debug, "Unknown command: 0x9999".to_string())]
+    );
+    let traffic = records(log_target::TRAFFIC);
+    assert_eq!(traffic.len(), 2, "Request and response dumps: {traffic:?}");
+    assert!(traffic.iter().all(|(level, _)| *level == Level::Debug));
+    assert!(traffic[0].1.contains("command=0x9999"), "{}", traffic[0].1);
+    handle.abort();
+}
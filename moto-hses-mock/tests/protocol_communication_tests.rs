@@ -5,8 +5,12 @@
 
 #![allow(clippy::expect_used)]
 
-use moto_hses_mock::test_utils;
+use moto_hses_mock::server::MockServerBuilder;
+use moto_hses_mock::{
+    ChaosConfig, JobSimulation, MockConfig, MockMetrics, MockServer, VariableType, test_utils,
+};
 use moto_hses_proto as proto;
+use std::sync::Arc;
 use tokio::net::UdpSocket;
 use tokio::time::{Duration, sleep};
 
@@ -378,7 +382,7 @@ async fn test_io_write_command() {
         0,    // ACK: Request
         1,    // Request ID
         0x78, // Command: I/O data writing
-        1,    // Instance: I/O number 1
+        2701, // Instance: network input 2701 (only network inputs are writable)
         1,    // Attribute: Fixed to 1
         0x10, // Service: Set_Attribute_Single
         payload,
@@ -407,3 +411,808 @@ async fn test_io_write_command() {
         // This is acceptable for this test
     }
 }
+
+/// Request the file list from a mock server populated with the files of a fixture listing
+/// and reassemble the data blocks, acknowledging each one like a real client.
+///
+/// The `file_list_synthetic_*` fixtures are hand-written listings, not data captured from
+/// controllers: one of short job names spanning two blocks and one of long job names
+/// spanning three. They check the block framing of the mock, not what a DX200 or YRC1000
+/// actually returns.
+async fn receive_file_list_blocks(fixture: &str) -> (Vec<u8>, Vec<u32>) {
+    let mut builder = MockServerBuilder::new().host("127.0.0.1").robot_port(0).file_port(0);
+    for name in fixture.split("\r\n").filter(|name| !name.is_empty()) {
+        builder = builder.with_file(name, b"NOP\r\nEND\r\n".to_vec());
+    }
+    let server = builder.build().await.expect("Failed to build mock server");
+    let file_addr = server.file_local_addr().expect("Failed to get file address");
+    let _handle = tokio::spawn(async move {
+        let _ = server.run().await;
+    });
+
+    let socket = UdpSocket::bind("127.0.0.1:0").await.expect("Failed to bind socket");
+    let request = proto::HsesRequestMessage::new(
+        2,                 // Division: File
+        0,                 // ACK: Request
+        7,                 // Request ID
+        0x00,              // Command: File control
+        0,                 // Instance
+        0,                 // Attribute
+        0x32,              // Service: Get file list
+        b"*.JBI".to_vec(), // Pattern
+    )
+    .expect("Failed to create request message");
+    socket.send_to(&request.encode(), file_addr).await.expect("Failed to send data");
+
+    let mut payload = Vec::new();
+    let mut block_numbers = Vec::new();
+    let mut buf = vec![0u8; 1024];
+    loop {
+        let (n, _) = tokio::time::timeout(Duration::from_secs(2), socket.recv_from(&mut buf))
+            .await
+            .expect("Timed out waiting for file list block")
+            .expect("Failed to receive data");
+        let response =
+            proto::HsesResponseMessage::decode(&buf[..n]).expect("Failed to decode response");
+        assert_eq!(response.header.request_id, 7);
+        assert_eq!(response.sub_header.service, 0xb2); // 0x32 + 0x80
+        assert_eq!(response.sub_header.status, 0x00);
+        assert!(response.payload.len() <= moto_hses_mock::server::FILE_BLOCK_SIZE);
+        payload.extend_from_slice(&response.payload);
+        block_numbers.push(response.header.block_number);
+
+        // Acknowledge the block
+        let mut ack = proto::HsesRequestMessage::new(2, 1, 7, 0x00, 0, 0, 0x32, vec![])
+            .expect("Failed to create ACK message");
+        ack.header.block_number = response.header.block_number;
+        socket.send_to(&ack.encode(), file_addr).await.expect("Failed to send ACK");
+
+        if response.header.block_number & 0x8000_0000 != 0 {
+            break;
+        }
+    }
+    (payload, block_numbers)
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_file_list_multi_block_short_names() {
+    let fixture = include_str!("fixtures/file_list_synthetic_short_names.txt");
+    let (payload, block_numbers) = receive_file_list_blocks(fixture).await;
+
+    assert_eq!(block_numbers, vec![1, 0x8000_0002]);
+    assert_eq!(payload, fixture.as_bytes());
+
+    let files = proto::commands::parse_file_list(&payload, proto::TextEncoding::Utf8)
+        .expect("Failed to parse file list");
+    assert_eq!(files.len(), 46);
+    assert_eq!(files.first().map(String::as_str), Some("HOME.JBI"));
+    assert!(files.contains(&"MASTER.JBI".to_string()));
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_file_list_multi_block_long_names() {
+    let fixture = include_str!("fixtures/file_list_synthetic_long_names.txt");
+    let (payload, block_numbers) = receive_file_list_blocks(fixture).await;
+
+    assert_eq!(block_numbers, vec![1, 2, 0x8000_0003]);
+    assert_eq!(payload, fixture.as_bytes());
+
+    let files = proto::commands::parse_file_list(&payload, proto::TextEncoding::Utf8)
+        .expect("Failed to parse file list");
+    assert_eq!(files.len(), 43);
+    assert!(files.contains(&"PALLETIZE_LAYER_30.JBI".to_string()));
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_file_list_single_block() {
+    let (payload, block_numbers) = receive_file_list_blocks("").await;
+
+    // Only the default TEST.JBI job; a short listing fits in one final block
+    assert_eq!(block_numbers, vec![0x8000_0001]);
+    assert_eq!(payload, b"TEST.JBI\r\n");
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_management_time_reports_power_on_time() {
+    let (addr, _handle) =
+        test_utils::start_test_server().await.expect("Failed to start test server");
+    let socket = UdpSocket::bind("127.0.0.1:0").await.expect("Failed to bind socket");
+    let mut buf = vec![0u8; 1024];
+
+    // Control power ON time (instance 1) starts at power-on
+    let message = proto::HsesRequestMessage::new(1, 0, 1, 0x88, 1, 0, 0x01, vec![])
+        .expect("Failed to create request message");
+    socket.send_to(&message.encode(), addr).await.expect("Failed to send data");
+    let (n, _) = tokio::time::timeout(Duration::from_secs(1), socket.recv_from(&mut buf))
+        .await
+        .expect("Timed out waiting for response")
+        .expect("Failed to receive data");
+    let response = proto::HsesResponseMessage::decode(&buf[..n]).expect("Failed to decode");
+    assert_eq!(response.sub_header.status, 0x00);
+    assert_eq!(response.payload.len(), 28);
+    // Operation start time "YYYY/MM/DD HH:MM" and elapse time "HHHHHH:MM'SS"
+    assert_eq!(response.payload[4], b'/', "{:?}", response.payload);
+    assert_eq!(response.payload[10], b' ', "{:?}", response.payload);
+    assert_eq!(response.payload[13], b':', "{:?}", response.payload);
+    assert!(response.payload[16..28].starts_with(b"000000:"), "{:?}", response.payload);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_status_command_attributes() {
+    let (addr, _handle) =
+        test_utils::start_test_server().await.expect("Failed to start test server");
+    let socket = UdpSocket::bind("127.0.0.1:0").await.expect("Failed to bind socket");
+    let mut buf = vec![0u8; 1024];
+
+    // (attribute, service, expected payload length or None for an error status)
+    let cases = [(0, 0x01, Some(8)), (1, 0x0e, Some(4)), (2, 0x0e, Some(4)), (3, 0x0e, None)];
+    for (request_id, (attribute, service, expected_len)) in (1..).zip(cases) {
+        let message =
+            proto::HsesRequestMessage::new(1, 0, request_id, 0x72, 1, attribute, service, vec![])
+                .expect("Failed to create request message");
+        socket.send_to(&message.encode(), addr).await.expect("Failed to send data");
+        let (n, _) = tokio::time::timeout(Duration::from_secs(1), socket.recv_from(&mut buf))
+            .await
+            .expect("Timed out waiting for response")
+            .expect("Failed to receive data");
+        let response = proto::HsesResponseMessage::decode(&buf[..n]).expect("Failed to decode");
+        match expected_len {
+            Some(len) => {
+                assert_eq!(response.sub_header.status, 0x00, "attribute {attribute}");
+                assert_eq!(response.payload.len(), len, "attribute {attribute}");
+            }
+            None => assert_ne!(response.sub_header.status, 0x00, "attribute {attribute}"),
+        }
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_scalar_variable_wire_sizes() {
+    let (addr, _handle) =
+        test_utils::start_test_server().await.expect("Failed to start test server");
+    let socket = UdpSocket::bind("127.0.0.1:0").await.expect("Failed to bind socket");
+    let mut buf = vec![0u8; 1024];
+    let mut request_id = 0u8;
+    let mut exchange = async |command: u16, service: u8, payload: Vec<u8>| {
+        request_id += 1;
+        let message =
+            proto::HsesRequestMessage::new(1, 0, request_id, command, 5, 1, service, payload)
+                .expect("Failed to create request message");
+        socket.send_to(&message.encode(), addr).await.expect("Failed to send data");
+        let (n, _) = tokio::time::timeout(Duration::from_secs(1), socket.recv_from(&mut buf))
+            .await
+            .expect("Timed out waiting for response")
+            .expect("Failed to receive data");
+        proto::HsesResponseMessage::decode(&buf[..n]).expect("Failed to decode response")
+    };
+
+    // B (0x7a): 1 byte, I (0x7b): 2 bytes, D (0x7c): 4 bytes, R (0x7d): 4 bytes
+    for (command, size) in [(0x7a, 1), (0x7b, 2), (0x7c, 4), (0x7d, 4)] {
+        let value: Vec<u8> = (1..=size).collect();
+        let response = exchange(command, 0x10, value.clone()).await;
+        assert_eq!(response.sub_header.status, 0x00, "command 0x{command:02x}");
+
+        let response = exchange(command, 0x0e, vec![]).await;
+        assert_eq!(response.sub_header.status, 0x00, "command 0x{command:02x}");
+        assert_eq!(response.payload, value, "command 0x{command:02x}");
+
+        // Neither shorter nor longer data is accepted
+        for wrong_size in [size - 1, size + 4] {
+            let response = exchange(command, 0x10, vec![0xff; usize::from(wrong_size)]).await;
+            assert_ne!(response.sub_header.status, 0x00, "command 0x{command:02x}");
+        }
+        let response = exchange(command, 0x0e, vec![]).await;
+        assert_eq!(response.payload, value, "command 0x{command:02x}");
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_run_for_releases_ports() {
+    let server = MockServerBuilder::new()
+        .robot_port(0)
+        .file_port(0)
+        .build()
+        .await
+        .expect("Failed to build server");
+    let addr = server.local_addr().expect("Failed to get address");
+
+    let started_at = tokio::time::Instant::now();
+    server.run_for(Duration::from_millis(200)).await.expect("Server failed");
+    assert!(started_at.elapsed() >= Duration::from_millis(200));
+
+    drop(server);
+    UdpSocket::bind(addr).await.expect("Port should be released");
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_watchdog_stops_stuck_server() {
+    let server = MockServerBuilder::new()
+        .robot_port(0)
+        .file_port(0)
+        .with_watchdog_timeout(Duration::from_millis(200))
+        .build()
+        .await
+        .expect("Failed to build server");
+    let addr = server.local_addr().expect("Failed to get address");
+    let state = server.state().clone();
+    let handle = tokio::spawn(async move { server.run().await.map_err(|e| e.to_string()) });
+
+    // A request cannot be handled while the state is locked
+    let _guard = state.write().await;
+    let socket = UdpSocket::bind("127.0.0.1:0").await.expect("Failed to bind socket");
+    let message = proto::HsesRequestMessage::new(1, 0, 1, 0x72, 1, 1, 0x0e, vec![])
+        .expect("Failed to create request message");
+    socket.send_to(&message.encode(), addr).await.expect("Failed to send data");
+
+    let result = tokio::time::timeout(Duration::from_secs(2), handle)
+        .await
+        .expect("Watchdog should stop the server")
+        .expect("Server task panicked");
+    let error = result.expect_err("Server should stop with an error");
+    assert!(error.contains("0x0072"), "{error}");
+}
+
+// Answers every request with a warning in the added status
+struct WarningHandler;
+
+impl moto_hses_mock::CommandHandler for WarningHandler {
+    fn handle(
+        &self,
+        _message: &proto::HsesRequestMessage,
+        _state: &mut moto_hses_mock::MockState,
+    ) -> Result<Vec<u8>, proto::ProtocolError> {
+        Ok(vec![0x01])
+    }
+
+    fn respond(
+        &self,
+        message: &proto::HsesRequestMessage,
+        state: &mut moto_hses_mock::MockState,
+    ) -> moto_hses_mock::HandlerResponse {
+        match self.handle(message, state) {
+            Ok(payload) => moto_hses_mock::HandlerResponse::warning(payload, 0x1234),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_custom_handler_status() {
+    let server = MockServerBuilder::new()
+        .robot_port(0)
+        .file_port(0)
+        .with_handler(0x72, WarningHandler)
+        .build()
+        .await
+        .expect("Failed to build server");
+    let addr = server.local_addr().expect("Failed to get address");
+    let _handle = tokio::spawn(async move { server.run_for(Duration::from_secs(5)).await });
+
+    let socket = UdpSocket::bind("127.0.0.1:0").await.expect("Failed to bind socket");
+    let message = proto::HsesRequestMessage::new(1, 0, 1, 0x72, 1, 1, 0x0e, vec![])
+        .expect("Failed to create request message");
+    socket.send_to(&message.encode(), addr).await.expect("Failed to send data");
+
+    let mut buf = vec![0u8; 1024];
+    let (n, _) = tokio::time::timeout(Duration::from_secs(1), socket.recv_from(&mut buf))
+        .await
+        .expect("Timed out waiting for response")
+        .expect("Failed to receive data");
+    let response =
+        proto::HsesResponseMessage::decode(&buf[..n]).expect("Failed to decode response");
+    assert_eq!(response.sub_header.status, 0x00);
+    assert_eq!(response.sub_header.added_status, 0x1234);
+    assert_eq!(response.payload, vec![0x01]);
+}
+
+// Answers with register 0 after a delay, without holding the state meanwhile
+struct DelayedRegisterHandler;
+
+#[async_trait::async_trait]
+impl moto_hses_mock::AsyncCommandHandler for DelayedRegisterHandler {
+    async fn respond(
+        &self,
+        _message: &proto::HsesRequestMessage,
+        state: &moto_hses_mock::state::SharedState,
+    ) -> moto_hses_mock::HandlerResponse {
+        sleep(Duration::from_millis(200)).await;
+        let value = state.read().await.get_register(0);
+        moto_hses_mock::HandlerResponse::ok(value.to_le_bytes().to_vec())
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_async_handler_does_not_hold_state() {
+    let server = MockServerBuilder::new()
+        .robot_port(0)
+        .file_port(0)
+        .with_async_handler(0x79, DelayedRegisterHandler)
+        .build()
+        .await
+        .expect("Failed to build server");
+    let addr = server.local_addr().expect("Failed to get address");
+    let state = server.state().clone();
+    let _handle = tokio::spawn(async move { server.run_for(Duration::from_secs(5)).await });
+
+    let socket = UdpSocket::bind("127.0.0.1:0").await.expect("Failed to bind socket");
+    let message = proto::HsesRequestMessage::new(1, 0, 1, 0x79, 0, 1, 0x0e, vec![])
+        .expect("Failed to create request message");
+    socket.send_to(&message.encode(), addr).await.expect("Failed to send data");
+
+    // The state can be updated while the handler waits
+    sleep(Duration::from_millis(50)).await;
+    tokio::time::timeout(Duration::from_millis(100), state.write())
+        .await
+        .expect("State should not be locked by the handler")
+        .set_register(0, 42);
+
+    let mut buf = vec![0u8; 1024];
+    let (n, _) = tokio::time::timeout(Duration::from_secs(1), socket.recv_from(&mut buf))
+        .await
+        .expect("Timed out waiting for response")
+        .expect("Failed to receive data");
+    let response =
+        proto::HsesResponseMessage::decode(&buf[..n]).expect("Failed to decode response");
+    assert_eq!(response.sub_header.status, 0x00);
+    assert_eq!(response.payload, 42i16.to_le_bytes().to_vec());
+}
+
+// Send a file to the file socket of `server` and return the response status
+async fn send_file_status(server: &MockServer, filename: &str) -> u8 {
+    let addr = server.file_local_addr().expect("Failed to get address");
+    let socket = UdpSocket::bind("127.0.0.1:0").await.expect("Failed to bind socket");
+    let mut payload = filename.as_bytes().to_vec();
+    payload.push(0);
+    payload.extend_from_slice(b"NOP\r\n");
+    let message = proto::HsesRequestMessage::new(2, 0, 1, 0x00, 0, 0, 0x15, payload)
+        .expect("Failed to create request message");
+    socket.send_to(&message.encode(), addr).await.expect("Failed to send data");
+
+    let mut buf = vec![0u8; 1024];
+    let (n, _) = tokio::time::timeout(Duration::from_secs(1), socket.recv_from(&mut buf))
+        .await
+        .expect("Timed out waiting for response")
+        .expect("Failed to receive data");
+    proto::HsesResponseMessage::decode(&buf[..n])
+        .expect("Failed to decode response")
+        .sub_header
+        .status
+}
+
+#[tokio::test]
+async fn test_controller_presets() {
+    let config = MockConfig { robot_port: 0, file_port: 0, ..MockConfig::fs100() };
+    let server = Arc::new(
+        MockServerBuilder::from_config(config)
+            .with_unsupported_command(0x72)
+            .build()
+            .await
+            .expect("Failed to build server"),
+    );
+    let addr = server.local_addr().expect("Failed to get address");
+    let running = Arc::clone(&server);
+    let _handle = tokio::spawn(async move { running.run_for(Duration::from_secs(5)).await });
+
+    // Responses are delayed by the preset latency; unsupported commands are undefined
+    let socket = UdpSocket::bind("127.0.0.1:0").await.expect("Failed to bind socket");
+    let message = proto::HsesRequestMessage::new(1, 0, 1, 0x72, 1, 1, 0x0e, vec![])
+        .expect("Failed to create request message");
+    let sent_at = std::time::Instant::now();
+    socket.send_to(&message.encode(), addr).await.expect("Failed to send data");
+    let mut buf = vec![0u8; 1024];
+    let (n, _) = tokio::time::timeout(Duration::from_secs(1), socket.recv_from(&mut buf))
+        .await
+        .expect("Timed out waiting for response")
+        .expect("Failed to receive data");
+    assert!(sent_at.elapsed() >= Duration::from_millis(8), "{:?}", sent_at.elapsed());
+    let response =
+        proto::HsesResponseMessage::decode(&buf[..n]).expect("Failed to decode response");
+    assert_eq!(response.sub_header.status, 0x01);
+
+    // The FS100 accepts long file names
+    assert_eq!(send_file_status(&server, "PICK02.JBI").await, 0x00);
+    assert_eq!(send_file_status(&server, "WELDING_PROGRAM.JBI").await, 0x00);
+    let state = server.handle().state().read().await.clone();
+    assert!(state.files.contains_key("PICK01.JBI"), "preset sample job is missing");
+    assert!(state.files.contains_key("WELDING_PROGRAM.JBI"));
+}
+
+// Send a robot request for `command` to `addr` and return the response
+async fn send_robot_request(
+    addr: std::net::SocketAddr,
+    command: u16,
+) -> proto::HsesResponseMessage {
+    let socket = UdpSocket::bind("127.0.0.1:0").await.expect("Failed to bind socket");
+    let message = proto::HsesRequestMessage::new(1, 0, 1, command, 1, 1, 0x0e, vec![])
+        .expect("Failed to create request message");
+    socket.send_to(&message.encode(), addr).await.expect("Failed to send data");
+    let mut buf = vec![0u8; 1024];
+    let (n, _) = tokio::time::timeout(Duration::from_secs(1), socket.recv_from(&mut buf))
+        .await
+        .expect("Timed out waiting for response")
+        .expect("Failed to receive data");
+    proto::HsesResponseMessage::decode(&buf[..n]).expect("Failed to decode response")
+}
+
+// Answers any command with its command number
+struct CommandEchoHandler;
+
+#[async_trait::async_trait]
+impl moto_hses_mock::AsyncCommandHandler for CommandEchoHandler {
+    async fn respond(
+        &self,
+        message: &proto::HsesRequestMessage,
+        _state: &moto_hses_mock::state::SharedState,
+    ) -> moto_hses_mock::HandlerResponse {
+        moto_hses_mock::HandlerResponse::ok(message.sub_header.command.to_le_bytes().to_vec())
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_unknown_command_policy() {
+    let server = MockServerBuilder::new()
+        .robot_port(0)
+        .file_port(0)
+        .with_unknown_command_policy(moto_hses_mock::UnknownCommandPolicy::Ignore)
+        .with_unsupported_command(0x72)
+        .build()
+        .await
+        .expect("Failed to build server");
+    let addr = server.local_addr().expect("Failed to get address");
+    let _handle = tokio::spawn(async move { server.run_for(Duration::from_secs(5)).await });
+
+    for _ in 0..2 {
+        let response = send_robot_request(addr, 0x9999).await;
+        assert_eq!(response.sub_header.status, 0x00);
+        assert!(response.payload.is_empty());
+    }
+    // Unsupported commands stay undefined
+    assert_eq!(send_robot_request(addr, 0x72).await.sub_header.status, 0x01);
+
+    let server = MockServerBuilder::new()
+        .robot_port(0)
+        .file_port(0)
+        .with_fallback_handler(CommandEchoHandler)
+        .build()
+        .await
+        .expect("Failed to build server");
+    let addr = server.local_addr().expect("Failed to get address");
+    let _handle = tokio::spawn(async move { server.run_for(Duration::from_secs(5)).await });
+
+    let response = send_robot_request(addr, 0x9999).await;
+    assert_eq!(response.sub_header.status, 0x00);
+    assert_eq!(response.payload, vec![0x99, 0x99]);
+    // Built-in handlers are not replaced by the fallback
+    let response = send_robot_request(addr, 0x72).await;
+    assert_eq!(response.payload.len(), 4);
+}
+
+#[tokio::test]
+async fn test_current_position_representations() {
+    let cartesian = proto::CartesianPosition::new(
+        400.0,
+        0.0,
+        300.0,
+        180.0,
+        0.0,
+        0.0,
+        1,
+        0,
+        proto::payload::position::Configuration::from_raw(0),
+        proto::payload::position::ExtendedConfiguration::from_raw(0),
+    );
+    let controller = MockServerBuilder::new()
+        .with_position(proto::Position::Pulse(proto::PulsePosition::new(vec![1, 2, 3, 4, 5, 6])))
+        .with_cartesian_position(1, cartesian.clone())
+        .with_pulse_position(11, proto::PulsePosition::new(vec![7, 8, 9, 10, 11, 12, 13, 14]))
+        .build_controller();
+
+    let request = |instance: u16, attribute: u8, service: u8| {
+        let controller = controller.clone();
+        async move {
+            let message =
+                proto::HsesRequestMessage::new(1, 0, 1, 0x75, instance, attribute, service, vec![])
+                    .expect("Failed to create request message");
+            let datagrams = controller.respond(&message).await.expect("Failed to respond");
+            proto::HsesResponseMessage::decode(&datagrams[0]).expect("Failed to decode response")
+        }
+    };
+    let read = |instance: u16| request(instance, 0, 0x01);
+    let position = |response: &proto::HsesResponseMessage| {
+        <proto::Position as proto::HsesPayload>::deserialize(
+            &response.payload,
+            proto::TextEncoding::Utf8,
+        )
+        .expect("Failed to decode position")
+    };
+
+    // Positions are 52 bytes, with unused axes sent as 0
+    let robot_pulse = read(1).await;
+    assert_eq!(robot_pulse.payload.len(), 52);
+    assert_eq!(
+        position(&robot_pulse),
+        proto::Position::Pulse(proto::PulsePosition::new(vec![1, 2, 3, 4, 5, 6, 0, 0]))
+    );
+    assert_eq!(position(&read(101).await), proto::Position::Cartesian(cartesian));
+    let base = read(11).await;
+    assert_eq!(
+        position(&base),
+        proto::Position::Pulse(proto::PulsePosition::new(vec![7, 8, 9, 10, 11, 12, 13, 14]))
+    );
+
+    // Groups without a configured position read as zero in their representation
+    assert!(matches!(position(&read(102).await), proto::Position::Cartesian(_)));
+    assert_eq!(
+        position(&read(2).await),
+        proto::Position::Pulse(proto::PulsePosition::new(vec![0; 8]))
+    );
+    assert_ne!(read(99).await.sub_header.status, 0, "Not a control group");
+
+    // Single data words: data type of the Cartesian position and 3rd axis of the pulse one
+    assert_eq!(request(101, 1, 0x0e).await.payload, 16u32.to_le_bytes());
+    assert_eq!(request(1, 8, 0x0e).await.payload, 3i32.to_le_bytes());
+    assert_ne!(request(1, 14, 0x0e).await.sub_header.status, 0, "Invalid attribute");
+    assert_ne!(request(1, 1, 0x01).await.sub_header.status, 0, "Attribute 0 for all data");
+}
+
+#[tokio::test]
+async fn test_position_variable_families() {
+    let controller = MockServerBuilder::new().build_controller();
+    let mock = controller.handle();
+    let request = |command: u16, instance: u16, attribute: u8, service: u8, payload: Vec<u8>| {
+        let controller = controller.clone();
+        async move {
+            let message = proto::HsesRequestMessage::new(
+                1, 0, 1, command, instance, attribute, service, payload,
+            )
+            .expect("Failed to create request message");
+            let datagrams = controller.respond(&message).await.expect("Failed to respond");
+            proto::HsesResponseMessage::decode(&datagrams[0]).expect("Failed to decode response")
+        }
+    };
+
+    // P (0x7f): 52 bytes, BP (0x80) and EX (0x81): 36 bytes, each variable stored on its own
+    for (command, size) in [(0x7f, 52), (0x80, 36), (0x81, 36)] {
+        let data: Vec<u8> = (1..=size).collect();
+        let response = request(command, 3, 0, 0x02, data.clone()).await;
+        assert_eq!(response.sub_header.status, 0x00, "command 0x{command:02x}");
+        assert_eq!(request(command, 3, 0, 0x01, vec![]).await.payload, data);
+        assert_eq!(request(command, 4, 0, 0x01, vec![]).await.payload, vec![0; usize::from(size)]);
+
+        // One data word by attribute
+        assert_eq!(request(command, 3, 2, 0x0e, vec![]).await.payload, vec![5, 6, 7, 8]);
+        let response = request(command, 3, 2, 0x10, vec![0xaa; 4]).await;
+        assert_eq!(response.sub_header.status, 0x00, "command 0x{command:02x}");
+        assert_eq!(request(command, 3, 0, 0x01, vec![]).await.payload[4..8], [0xaa; 4]);
+
+        // Wrong sizes, attributes and variable numbers are rejected
+        assert_ne!(request(command, 3, 0, 0x02, vec![0; 4]).await.sub_header.status, 0x00);
+        let last_word = size / 4 + 1;
+        assert_ne!(request(command, 3, last_word, 0x0e, vec![]).await.sub_header.status, 0x00);
+        assert_ne!(request(command, 128, 0, 0x01, vec![]).await.sub_header.status, 0x00);
+    }
+    for (var_type, size) in [
+        (VariableType::Position, 52),
+        (VariableType::BasePosition, 36),
+        (VariableType::ExternalAxis, 36),
+    ] {
+        let stored = mock.state().read().await.get_variable(var_type, 3).map(Vec::len);
+        assert_eq!(stored, Some(size));
+    }
+}
+
+#[tokio::test]
+async fn test_randomized_config() {
+    let config = MockConfig::randomized(42);
+    let again = MockConfig::randomized(42);
+    assert_eq!(config.variables, again.variables);
+    assert_eq!(config.io_states, again.io_states);
+    assert_eq!(config.registers, again.registers);
+    assert_eq!(config.alarms, again.alarms);
+    assert_ne!(config.variables, MockConfig::randomized(43).variables);
+
+    assert!((1..=4).contains(&config.alarms.len()));
+    assert_eq!(config.registers.len(), 1000);
+    for index in 0..100 {
+        let text = &config.variables[&(VariableType::String, index)];
+        assert_eq!(text.len(), 16);
+        assert!(text.iter().all(|byte| *byte == 0 || byte.is_ascii_graphic()));
+    }
+
+    // The state of a controller built from it holds the random content
+    let controller = MockServerBuilder::from_config(config.clone()).build_controller();
+    let state = controller.state().read().await;
+    assert_eq!(state.alarms, config.alarms);
+    assert_eq!(state.get_io_state(2701), config.io_states[&2701]);
+    assert_eq!(
+        state.get_variable(VariableType::Double, 7),
+        Some(&config.variables[&(VariableType::Double, 7)])
+    );
+    drop(state);
+}
+
+// Answers with more payload than a datagram may carry
+struct OversizeHandler;
+
+impl moto_hses_mock::CommandHandler for OversizeHandler {
+    fn handle(
+        &self,
+        _message: &proto::HsesRequestMessage,
+        _state: &mut moto_hses_mock::MockState,
+    ) -> Result<Vec<u8>, proto::ProtocolError> {
+        Ok(vec![0; proto::constants::MAX_PAYLOAD_SIZE + 1])
+    }
+}
+
+// Send a Set_Attribute_Single request with `payload` and return the response, checking
+// that it fits into one HSES datagram
+async fn write_request(
+    socket: &UdpSocket,
+    addr: std::net::SocketAddr,
+    command: u16,
+    payload: Vec<u8>,
+) -> proto::HsesResponseMessage {
+    let message = proto::HsesRequestMessage::new(1, 0, 1, command, 1, 1, 0x10, payload)
+        .expect("Failed to create request message");
+    socket.send_to(&message.encode(), addr).await.expect("Failed to send data");
+    let mut buf = vec![0u8; 4096];
+    let (n, _) = tokio::time::timeout(Duration::from_secs(1), socket.recv_from(&mut buf))
+        .await
+        .expect("Timed out waiting for response")
+        .expect("Failed to receive data");
+    assert!(n <= proto::constants::MAX_DATAGRAM_SIZE, "{n} bytes sent");
+    proto::HsesResponseMessage::decode(&buf[..n]).expect("Failed to decode response")
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_maximum_datagram_size() {
+    let server = MockServerBuilder::new()
+        .robot_port(0)
+        .file_port(0)
+        .with_handler(0x72, OversizeHandler)
+        .build()
+        .await
+        .expect("Failed to build server");
+    let addr = server.local_addr().expect("Failed to get address");
+    let _handle = tokio::spawn(async move { server.run_for(Duration::from_secs(5)).await });
+
+    let socket = UdpSocket::bind("127.0.0.1:0").await.expect("Failed to bind socket");
+
+    // Requests above the MTU are received whole and rejected with "too much data"
+    for size in [proto::constants::MAX_PAYLOAD_SIZE + 1, 3000] {
+        let response = write_request(&socket, addr, 0x7A, vec![0; size]).await;
+        assert_eq!(response.sub_header.status, 0x15, "{size} bytes");
+    }
+    let response = write_request(&socket, addr, 0x7A, vec![0; 1]).await;
+    assert_eq!(response.sub_header.status, 0x00);
+
+    // Oversize handler responses are not sent
+    let response = write_request(&socket, addr, 0x72, Vec::new()).await;
+    assert_eq!(response.sub_header.status, 0x15);
+}
+
+// Reply to a status read: the response status, or `None` if dropped
+#[derive(Debug, PartialEq, Eq)]
+struct ChaosReply {
+    status: Option<u8>,
+    datagrams: usize,
+    delay: Duration,
+}
+
+// Replies of a controller built from `config` to 2000 status reads
+async fn chaos_replies(config: MockConfig) -> (Vec<ChaosReply>, MockMetrics) {
+    let controller = MockServerBuilder::from_config(config).build_controller();
+    let mut replies = Vec::new();
+    for request in 0..2000u32 {
+        let request_id = u8::try_from(request % 256).expect("Request ID out of range");
+        let message = proto::HsesRequestMessage::new(1, 0, request_id, 0x72, 1, 0, 0x01, vec![])
+            .expect("Failed to create request message");
+        let reply = controller.exchange(&message.encode()).await;
+        let status = reply.datagrams.first().map(|datagram| {
+            proto::HsesResponseMessage::decode(datagram)
+                .expect("Failed to decode response")
+                .sub_header
+                .status
+        });
+        replies.push(ChaosReply { status, datagrams: reply.datagrams.len(), delay: reply.delay });
+    }
+    (replies, controller.handle().metrics().await)
+}
+
+// Delays of the replies of a controller built from `config` to 200 status reads
+async fn reply_delays(config: MockConfig) -> Vec<Duration> {
+    let controller = MockServerBuilder::from_config(config).build_controller();
+    let mut delays = Vec::new();
+    for request_id in 0..200u8 {
+        let message = proto::HsesRequestMessage::new(1, 0, request_id, 0x72, 1, 0, 0x01, vec![])
+            .expect("Failed to create request message");
+        delays.push(controller.exchange(&message.encode()).await.delay);
+    }
+    delays
+}
+
+#[tokio::test]
+async fn test_seeded_latency_jitter() {
+    let config = MockConfig {
+        response_latency: Duration::from_millis(2),
+        latency_jitter: Duration::from_millis(10),
+        seed: 11,
+        ..MockConfig::default()
+    };
+    let delays = reply_delays(config.clone()).await;
+    assert_eq!(delays, reply_delays(config.clone()).await);
+    assert_ne!(delays, reply_delays(MockConfig { seed: 12, ..config.clone() }).await);
+    assert!(
+        delays
+            .iter()
+            .all(|delay| (Duration::from_millis(2)..Duration::from_millis(12)).contains(delay))
+    );
+
+    // The chaos preset and the builder draw from the same seed
+    let chaos = MockConfig::chaos(5);
+    assert_eq!(chaos.seed, 5);
+    let builder = MockServerBuilder::from_config(chaos).with_seed(6).build_controller();
+    assert_eq!(builder.handle().seed().await, 6);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_job_simulation_follows_tokio_clock() {
+    let config = MockConfig {
+        job_simulation: Some(JobSimulation::new(Duration::from_secs(5))),
+        ..MockConfig::default()
+    };
+    let controller = MockServerBuilder::from_config(config).build_controller();
+    let start = proto::HsesRequestMessage::new(1, 0, 1, 0x86, 1, 1, 0x10, vec![1, 0, 0, 0])
+        .expect("Failed to create request message");
+    let status = proto::HsesRequestMessage::new(1, 0, 2, 0x72, 1, 0, 0x01, vec![])
+        .expect("Failed to create request message");
+    controller.exchange(&start.encode()).await;
+
+    // With paused time the job stops exactly after its run time, however slow the host;
+    // the simulation advances with each request
+    tokio::time::advance(Duration::from_millis(4999)).await;
+    controller.exchange(&status.encode()).await;
+    controller.handle().assert_running(true).await;
+    tokio::time::advance(Duration::from_millis(1)).await;
+    controller.exchange(&status.encode()).await;
+    controller.handle().assert_running(false).await;
+}
+
+#[tokio::test]
+async fn test_chaos_config() {
+    let config = MockConfig::chaos(7);
+    let chaos = config.chaos.expect("Chaos preset without faults");
+    assert!((0.01..=0.05).contains(&chaos.drop_rate));
+
+    // The same seed meets the same faults
+    let (replies, metrics) = chaos_replies(config.clone()).await;
+    assert_eq!(replies, chaos_replies(config.clone()).await.0);
+
+    let count = |f: fn(&ChaosReply) -> bool| {
+        u64::try_from(replies.iter().filter(|reply| f(reply)).count()).unwrap_or(u64::MAX)
+    };
+    let dropped = count(|reply| reply.status.is_none());
+    let duplicated = count(|reply| reply.datagrams == 2);
+    let errors = count(|reply| reply.status == Some(ChaosConfig::ERROR_STATUS.0));
+    assert_eq!(metrics.dropped_responses, dropped);
+    // Error responses may be dropped as well
+    assert!(metrics.chaos_errors >= errors);
+    // Roughly 20-100 drops, 10 duplicates and 10 errors
+    assert!((5..=150).contains(&dropped), "{dropped} dropped");
+    assert!((1..=40).contains(&duplicated), "{duplicated} duplicated");
+    assert!((1..=40).contains(&errors), "{errors} errors");
+    let latency = config.response_latency;
+    assert!(replies.iter().all(|reply| reply.delay <= latency + chaos.max_jitter));
+    assert!(replies.iter().any(|reply| reply.delay > latency));
+
+    // Without the faults every request is answered once
+    let controller = MockServerBuilder::from_config(config).build_controller();
+    controller.handle().set_chaos(None).await;
+    for request_id in 0..100 {
+        let message = proto::HsesRequestMessage::new(1, 0, request_id, 0x72, 1, 0, 0x01, vec![])
+            .expect("Failed to create request message");
+        let reply = controller.exchange(&message.encode()).await;
+        assert_eq!(reply.datagrams.len(), 1);
+        assert_eq!(reply.delay, latency);
+    }
+}
@@ -1,6 +1,6 @@
 //! Alarm related commands (0x70, 0x71, 0x82)
 
-use super::command_trait::Command;
+use super::command_trait::{AttributeRead, Command, Service};
 use crate::error::ProtocolError;
 
 /// Alarm attribute types
@@ -33,6 +33,21 @@ impl From<u8> for AlarmAttribute {
     }
 }
 
+impl AlarmAttribute {
+    /// Attribute of the value `value`, unlike `From<u8>` rejecting unknown values
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ProtocolError::InvalidAttribute`] for values other than 0-8
+    pub fn checked(value: u8) -> Result<Self, ProtocolError> {
+        if value <= Self::SubCodeReverse as u8 {
+            Ok(Self::from(value))
+        } else {
+            Err(ProtocolError::InvalidAttribute)
+        }
+    }
+}
+
 /// Command for reading alarm data (0x70)
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ReadAlarmData {
@@ -77,12 +92,14 @@ impl Command for ReadAlarmData {
         self.attribute as u8
     }
 
-    fn service(&self) -> u8 {
-        if self.attribute == AlarmAttribute::All {
-            0x01 // Get_Attribute_All
-        } else {
-            0x0e // Get_Attribute_Single
-        }
+    fn service(&self) -> Service {
+        if self.attribute == AlarmAttribute::All { Service::GetAll } else { Service::GetSingle }
+    }
+}
+
+impl AttributeRead for ReadAlarmData {
+    fn with_attribute(&self, attribute: u8) -> Result<Self, ProtocolError> {
+        Ok(Self::new(self.instance, AlarmAttribute::checked(attribute)?))
     }
 }
 
@@ -97,6 +114,59 @@ pub enum AlarmCategory {
     Invalid,
 }
 
+impl AlarmCategory {
+    /// Name used by the `Display` and `FromStr` implementations, e.g. "monitor-alarm"
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::MajorFailure => "major-failure",
+            Self::MonitorAlarm => "monitor-alarm",
+            Self::UserAlarmSystem => "user-alarm-system",
+            Self::UserAlarmUser => "user-alarm-user",
+            Self::OfflineAlarm => "offline-alarm",
+            Self::Invalid => "invalid",
+        }
+    }
+
+    /// Alarm history instance of the newest entry of the category
+    #[must_use]
+    pub const fn first_instance(self) -> Option<u16> {
+        match self {
+            Self::MajorFailure => Some(1),
+            Self::MonitorAlarm => Some(1001),
+            Self::UserAlarmSystem => Some(2001),
+            Self::UserAlarmUser => Some(3001),
+            Self::OfflineAlarm => Some(4001),
+            Self::Invalid => None,
+        }
+    }
+}
+
+/// Number of alarm history entries kept per category
+pub const ALARM_HISTORY_SIZE: u16 = 100;
+
+impl std::fmt::Display for AlarmCategory {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for AlarmCategory {
+    type Err = ProtocolError;
+
+    /// Parse a category name case-insensitively; "invalid" is not accepted
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        match text.trim().to_ascii_lowercase().as_str() {
+            "major-failure" => Ok(Self::MajorFailure),
+            "monitor-alarm" => Ok(Self::MonitorAlarm),
+            "user-alarm-system" => Ok(Self::UserAlarmSystem),
+            "user-alarm-user" => Ok(Self::UserAlarmUser),
+            "offline-alarm" => Ok(Self::OfflineAlarm),
+            _ => Err(ProtocolError::InvalidMessage(format!("invalid alarm category '{text}'"))),
+        }
+    }
+}
+
 /// Command for reading alarm history (0x71)
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ReadAlarmHistory {
@@ -167,12 +237,14 @@ impl Command for ReadAlarmHistory {
         self.attribute as u8
     }
 
-    fn service(&self) -> u8 {
-        if self.attribute == AlarmAttribute::All {
-            0x01 // Get_Attribute_All
-        } else {
-            0x0e // Get_Attribute_Single
-        }
+    fn service(&self) -> Service {
+        if self.attribute == AlarmAttribute::All { Service::GetAll } else { Service::GetSingle }
+    }
+}
+
+impl AttributeRead for ReadAlarmHistory {
+    fn with_attribute(&self, attribute: u8) -> Result<Self, ProtocolError> {
+        Ok(Self::new(self.instance, AlarmAttribute::checked(attribute)?))
     }
 }
 
@@ -238,8 +310,8 @@ impl Command for AlarmReset {
         1 // Fixed to 1 according to specification
     }
 
-    fn service(&self) -> u8 {
-        0x10 // Set_Attribute_Single
+    fn service(&self) -> Service {
+        Service::SetSingle
     }
 }
 
@@ -248,6 +320,22 @@ mod tests {
     use super::*;
 
     #[test]
+    #[allow(clippy::unwrap_used)]
+    fn test_alarm_category_name_round_trip() {
+        for value in [
+            AlarmCategory::MajorFailure,
+            AlarmCategory::MonitorAlarm,
+            AlarmCategory::UserAlarmSystem,
+            AlarmCategory::UserAlarmUser,
+            AlarmCategory::OfflineAlarm,
+        ] {
+            assert_eq!(value.to_string().parse::<AlarmCategory>().unwrap(), value);
+        }
+        assert!("invalid".parse::<AlarmCategory>().is_err());
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
     fn test_alarm_attribute_from_u8() {
         assert_eq!(AlarmAttribute::from(0), AlarmAttribute::All);
         assert_eq!(AlarmAttribute::from(1), AlarmAttribute::Code);
@@ -259,6 +347,19 @@ mod tests {
         assert_eq!(AlarmAttribute::from(7), AlarmAttribute::SubCodeData);
         assert_eq!(AlarmAttribute::from(8), AlarmAttribute::SubCodeReverse);
         assert_eq!(AlarmAttribute::from(99), AlarmAttribute::Code); // Default
+        assert_eq!(AlarmAttribute::checked(8).unwrap(), AlarmAttribute::SubCodeReverse);
+        assert!(matches!(AlarmAttribute::checked(9), Err(ProtocolError::InvalidAttribute)));
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn test_read_alarm_data_with_attribute() {
+        let command = ReadAlarmData::new(1001, AlarmAttribute::All);
+        assert!(command.supports_all());
+        let single = command.with_attribute(5).unwrap();
+        assert_eq!((single.instance, single.attribute), (1001, AlarmAttribute::Name));
+        assert_eq!(single.service(), Service::GetSingle);
+        assert!(ReadAlarmHistory::new(1, AlarmAttribute::All).with_attribute(9).is_err());
     }
 
     #[test]
@@ -490,8 +591,8 @@ mod tests {
         assert_eq!(cancel_cmd.attribute(), 1);
 
         // Service (Set_Attribute_Single)
-        assert_eq!(reset_cmd.service(), 0x10);
-        assert_eq!(cancel_cmd.service(), 0x10);
+        assert_eq!(reset_cmd.service(), Service::SetSingle);
+        assert_eq!(cancel_cmd.service(), Service::SetSingle);
 
         // Serialization
         let reset_payload = reset_cmd.serialize().unwrap();
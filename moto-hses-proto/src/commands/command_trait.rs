@@ -1,5 +1,6 @@
 //! Command trait and related types for HSES protocol
 
+use crate::constants;
 use crate::error::ProtocolError;
 
 /// Core trait for type-safe commands
@@ -13,7 +14,39 @@ pub trait Command {
     fn serialize(&self) -> Result<Vec<u8>, ProtocolError>;
     fn instance(&self) -> u16;
     fn attribute(&self) -> u8;
-    fn service(&self) -> u8;
+    fn service(&self) -> Service;
+    /// Whether receiving the command twice has the same effect as receiving it once
+    ///
+    /// Commands that start something, such as a job start or an incremental move, are
+    /// executed again if they are resent after only their response was lost.
+    fn is_idempotent(&self) -> bool {
+        true
+    }
+}
+
+/// Command reading a payload attribute by attribute, such as alarm data (0x70)
+///
+/// Attribute 0 reads the complete payload with `Get_Attribute_All`, other attributes read
+/// one field with `Get_Attribute_Single`; [`AttributeDeserialize`] merges single reads.
+/// Clients choose the service from the attributes wanted: one request for all
+/// attributes, or single reads when only a few fields are needed.
+///
+/// [`AttributeDeserialize`]: crate::payload::AttributeDeserialize
+pub trait AttributeRead: Command + Sized
+where
+    Self::Response: crate::payload::AttributeDeserialize,
+{
+    /// Whether the command accepts attribute 0 (`Get_Attribute_All`)
+    fn supports_all(&self) -> bool {
+        true
+    }
+
+    /// The same read for another attribute
+    ///
+    /// # Errors
+    ///
+    /// Returns `ProtocolError::InvalidAttribute` if the command has no such attribute
+    fn with_attribute(&self, attribute: u8) -> Result<Self, ProtocolError>;
 }
 
 /// Division types for HSES protocol
@@ -23,9 +56,133 @@ pub enum Division {
     File = 2,
 }
 
+impl Division {
+    /// Name used by the `Display` and `FromStr` implementations, e.g. "file"
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Robot => "robot",
+            Self::File => "file",
+        }
+    }
+}
+
+impl std::fmt::Display for Division {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for Division {
+    type Err = ProtocolError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        match text.trim().to_ascii_lowercase().as_str() {
+            "robot" => Ok(Self::Robot),
+            "file" => Ok(Self::File),
+            _ => Err(ProtocolError::InvalidMessage(format!("invalid division '{text}'"))),
+        }
+    }
+}
+
 /// Service types for HSES protocol
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+///
+/// The discriminants are the service codes in [`constants`], e.g.
+/// `Service::ReadPlural as u8 == constants::SERVICE_READ_PLURAL`. Requests carry the code
+/// as a `u8`; [`Service::try_from`] converts it back.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u8)]
 pub enum Service {
-    GetSingle = 0x0e,
-    SetSingle = 0x10,
+    GetAll = constants::SERVICE_GET_ATTRIBUTE_ALL,
+    SetAll = constants::SERVICE_SET_ATTRIBUTE_ALL,
+    GetSingle = constants::SERVICE_GET_ATTRIBUTE_SINGLE,
+    SetSingle = constants::SERVICE_SET_ATTRIBUTE_SINGLE,
+    ReadPlural = constants::SERVICE_READ_PLURAL,
+    WritePlural = constants::SERVICE_WRITE_PLURAL,
+    FileDelete = constants::SERVICE_FILE_DELETE,
+    FileSend = constants::SERVICE_FILE_SEND,
+    FileReceive = constants::SERVICE_FILE_RECEIVE,
+    FileList = constants::SERVICE_FILE_LIST,
+}
+
+impl Service {
+    /// Service code sent in the request sub-header
+    #[must_use]
+    pub const fn code(self) -> u8 {
+        self as u8
+    }
+
+    /// Whether the service writes to the controller
+    #[must_use]
+    pub const fn is_write(self) -> bool {
+        matches!(
+            self,
+            Self::SetAll | Self::SetSingle | Self::WritePlural | Self::FileDelete | Self::FileSend
+        )
+    }
+}
+
+impl From<Service> for u8 {
+    fn from(service: Service) -> Self {
+        service.code()
+    }
+}
+
+impl TryFrom<u8> for Service {
+    type Error = ProtocolError;
+
+    /// # Errors
+    ///
+    /// Returns [`ProtocolError::InvalidService`] for an unknown service code
+    fn try_from(code: u8) -> Result<Self, Self::Error> {
+        match code {
+            constants::SERVICE_GET_ATTRIBUTE_ALL => Ok(Self::GetAll),
+            constants::SERVICE_SET_ATTRIBUTE_ALL => Ok(Self::SetAll),
+            constants::SERVICE_GET_ATTRIBUTE_SINGLE => Ok(Self::GetSingle),
+            constants::SERVICE_SET_ATTRIBUTE_SINGLE => Ok(Self::SetSingle),
+            constants::SERVICE_READ_PLURAL => Ok(Self::ReadPlural),
+            constants::SERVICE_WRITE_PLURAL => Ok(Self::WritePlural),
+            constants::SERVICE_FILE_DELETE => Ok(Self::FileDelete),
+            constants::SERVICE_FILE_SEND => Ok(Self::FileSend),
+            constants::SERVICE_FILE_RECEIVE => Ok(Self::FileReceive),
+            constants::SERVICE_FILE_LIST => Ok(Self::FileList),
+            _ => Err(ProtocolError::InvalidService),
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_division_name_round_trip() {
+        for value in [Division::Robot, Division::File] {
+            assert_eq!(value.to_string().parse::<Division>().unwrap(), value);
+        }
+        assert!("2".parse::<Division>().is_err());
+    }
+
+    #[test]
+    fn test_service_codes() {
+        for service in [
+            Service::GetAll,
+            Service::SetAll,
+            Service::GetSingle,
+            Service::SetSingle,
+            Service::ReadPlural,
+            Service::WritePlural,
+            Service::FileDelete,
+            Service::FileSend,
+            Service::FileReceive,
+            Service::FileList,
+        ] {
+            assert_eq!(Service::try_from(service.code()).unwrap(), service);
+        }
+        assert_eq!(u8::from(Service::ReadPlural), 0x33);
+        assert!(Service::WritePlural.is_write());
+        assert!(!Service::GetSingle.is_write());
+        assert!(matches!(Service::try_from(0x03), Err(ProtocolError::InvalidService)));
+    }
 }
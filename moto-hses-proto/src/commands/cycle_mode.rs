@@ -1,6 +1,6 @@
 //! Cycle mode switching command (0x84)
 
-use super::command_trait::Command;
+use super::command_trait::{Command, Service};
 use crate::error::ProtocolError;
 
 /// Cycle mode switching command (0x84)
@@ -24,6 +24,37 @@ pub enum CycleMode {
     Continuous = 3,
 }
 
+impl CycleMode {
+    /// Name used by the `Display` and `FromStr` implementations, e.g. "one-cycle"
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Step => "step",
+            Self::OneCycle => "one-cycle",
+            Self::Continuous => "continuous",
+        }
+    }
+}
+
+impl std::fmt::Display for CycleMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for CycleMode {
+    type Err = ProtocolError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        match text.trim().to_ascii_lowercase().as_str() {
+            "step" => Ok(Self::Step),
+            "one-cycle" => Ok(Self::OneCycle),
+            "continuous" => Ok(Self::Continuous),
+            _ => Err(ProtocolError::InvalidMessage(format!("invalid cycle mode '{text}'"))),
+        }
+    }
+}
+
 impl Command for CycleModeSwitchingCommand {
     type Response = ();
 
@@ -43,8 +74,8 @@ impl Command for CycleModeSwitchingCommand {
         1 // Fixed according to specification
     }
 
-    fn service(&self) -> u8 {
-        0x10 // Set_Attribute_Single
+    fn service(&self) -> Service {
+        Service::SetSingle
     }
 }
 
@@ -53,6 +84,15 @@ impl Command for CycleModeSwitchingCommand {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_cycle_mode_name_round_trip() {
+        for value in [CycleMode::Step, CycleMode::OneCycle, CycleMode::Continuous] {
+            assert_eq!(value.to_string().parse::<CycleMode>().unwrap(), value);
+        }
+        assert_eq!(" One-Cycle ".parse::<CycleMode>().unwrap(), CycleMode::OneCycle);
+        assert!("one_cycle".parse::<CycleMode>().is_err());
+    }
+
     #[test]
     fn test_cycle_mode_switching_command_new() {
         let command = CycleModeSwitchingCommand::new(CycleMode::Step);
@@ -66,7 +106,7 @@ mod tests {
         assert_eq!(CycleModeSwitchingCommand::command_id(), 0x84);
         assert_eq!(command.instance(), 2);
         assert_eq!(command.attribute(), 1);
-        assert_eq!(command.service(), 0x10);
+        assert_eq!(command.service(), Service::SetSingle);
     }
 
     #[test]
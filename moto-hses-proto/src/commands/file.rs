@@ -1,6 +1,9 @@
 //! File control commands for HSES protocol
 
-use crate::commands::Command;
+use crate::commands::{Command, Service};
+use crate::constants::MAX_PAYLOAD_SIZE;
+use crate::controller::FileNameRules;
+use crate::encoding::TextEncoding;
 use crate::error::ProtocolError;
 
 /// File list request command
@@ -32,8 +35,8 @@ impl Command for ReadFileList {
         0
     }
 
-    fn service(&self) -> u8 {
-        0x32
+    fn service(&self) -> Service {
+        Service::FileList
     }
 
     fn serialize(&self) -> Result<Vec<u8>, ProtocolError> {
@@ -76,8 +79,8 @@ impl Command for SendFile {
         0
     }
 
-    fn service(&self) -> u8 {
-        0x15
+    fn service(&self) -> Service {
+        Service::FileSend
     }
 
     fn serialize(&self) -> Result<Vec<u8>, ProtocolError> {
@@ -89,6 +92,35 @@ impl Command for SendFile {
     }
 }
 
+impl SendFile {
+    /// Payloads of the datagrams carrying the file, in sending order
+    ///
+    /// A file that fits into one datagram with its name is sent as [`Command::serialize`]
+    /// encodes it. Larger files are sent as the name alone, as block 0, followed by data
+    /// blocks 1, 2, ... of at most [`MAX_PAYLOAD_SIZE`] bytes; the last block is flagged
+    /// with `header::LAST_BLOCK_FLAG`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ProtocolError::PayloadTooLarge` if the file name alone exceeds
+    /// [`MAX_PAYLOAD_SIZE`]
+    ///
+    /// [`MAX_PAYLOAD_SIZE`]: crate::constants::MAX_PAYLOAD_SIZE
+    pub fn blocks(&self) -> Result<Vec<Vec<u8>>, ProtocolError> {
+        let single = self.serialize()?;
+        if single.len() <= MAX_PAYLOAD_SIZE {
+            return Ok(vec![single]);
+        }
+        let name_len = single.len() - self.content.len();
+        if name_len > MAX_PAYLOAD_SIZE {
+            return Err(ProtocolError::PayloadTooLarge { size: name_len, max: MAX_PAYLOAD_SIZE });
+        }
+        let mut blocks = vec![single[..name_len].to_vec()];
+        blocks.extend(self.content.chunks(MAX_PAYLOAD_SIZE).map(<[u8]>::to_vec));
+        Ok(blocks)
+    }
+}
+
 /// Receive file command
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ReceiveFile {
@@ -118,8 +150,8 @@ impl Command for ReceiveFile {
         0
     }
 
-    fn service(&self) -> u8 {
-        0x16
+    fn service(&self) -> Service {
+        Service::FileReceive
     }
 
     fn serialize(&self) -> Result<Vec<u8>, ProtocolError> {
@@ -157,8 +189,8 @@ impl Command for DeleteFile {
         0
     }
 
-    fn service(&self) -> u8 {
-        0x09
+    fn service(&self) -> Service {
+        Service::FileDelete
     }
 
     fn serialize(&self) -> Result<Vec<u8>, ProtocolError> {
@@ -167,6 +199,81 @@ impl Command for DeleteFile {
     }
 }
 
+/// Characters that are never accepted in controller file names
+const FORBIDDEN_FILE_NAME_CHARS: &[char] = &['\\', '/', ':', '*', '?', '"', '<', '>', '|', ' '];
+
+/// Validate a file name against the controller's file name constraints
+///
+/// File names need a 1-3 character alphanumeric extension (e.g. `JBI`, `DAT`) and may not
+/// contain path separators, wildcards or spaces. The name before the extension may have up
+/// to 32 bytes in the configured text encoding; [`FileNameRules::Short`] restricts it to
+/// 8 ASCII letters, digits, `_` or `-`.
+///
+/// # Errors
+///
+/// Returns [`ProtocolError::InvalidFileName`] describing the violated constraint
+pub fn validate_filename(
+    filename: &str,
+    rules: FileNameRules,
+    encoding: TextEncoding,
+) -> Result<(), ProtocolError> {
+    let invalid = |reason: String| Err(ProtocolError::InvalidFileName(reason));
+
+    if filename.is_empty() {
+        return invalid("file name is empty".to_string());
+    }
+    if let Some(c) =
+        filename.chars().find(|c| c.is_control() || FORBIDDEN_FILE_NAME_CHARS.contains(c))
+    {
+        return invalid(format!("'{filename}' contains forbidden character {c:?}"));
+    }
+
+    let Some((stem, extension)) = filename.rsplit_once('.') else {
+        return invalid(format!("'{filename}' has no extension"));
+    };
+    if stem.is_empty() {
+        return invalid(format!("'{filename}' has an empty name before the extension"));
+    }
+    if stem.contains('.') {
+        return invalid(format!("'{filename}' contains more than one '.'"));
+    }
+    if extension.is_empty()
+        || extension.len() > 3
+        || !extension.chars().all(|c| c.is_ascii_alphanumeric())
+    {
+        return invalid(format!(
+            "'{filename}' has invalid extension '{extension}' (expected 1-3 letters or digits)"
+        ));
+    }
+
+    let max_stem_len = rules.max_stem_len();
+    match rules {
+        FileNameRules::Short => {
+            if !stem.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-') {
+                return invalid(format!(
+                    "'{filename}' may only contain ASCII letters, digits, '_' or '-' in an 8.3 name"
+                ));
+            }
+            if stem.len() > max_stem_len {
+                return invalid(format!(
+                    "'{filename}' exceeds the 8.3 format (name is {} characters, max {max_stem_len})",
+                    stem.len()
+                ));
+            }
+        }
+        FileNameRules::Long => {
+            let stem_len = crate::encoding_utils::encode_string(stem, encoding).len();
+            if stem_len > max_stem_len {
+                return invalid(format!(
+                    "'{filename}' is too long (name is {stem_len} bytes, max {max_stem_len})"
+                ));
+            }
+        }
+    }
+
+    Ok(())
+}
+
 /// File operation response parsers
 pub mod response {
     use super::ProtocolError;
@@ -210,6 +317,23 @@ pub mod response {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::controller::ControllerModel;
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn test_send_file_blocks() {
+        let small = SendFile::new("A.JBI".to_string(), vec![1; 10], TextEncoding::Utf8);
+        assert_eq!(small.blocks().unwrap(), vec![small.serialize().unwrap()]);
+
+        let large = SendFile::new("A.JBI".to_string(), vec![2; 1000], TextEncoding::Utf8);
+        let blocks = large.blocks().unwrap();
+        let sizes: Vec<usize> = blocks.iter().map(Vec::len).collect();
+        assert_eq!(sizes, vec![6, MAX_PAYLOAD_SIZE, MAX_PAYLOAD_SIZE, 42]);
+        assert_eq!(blocks[0], b"A.JBI\0");
+
+        let long_name = SendFile::new("A".repeat(500), vec![], TextEncoding::Utf8);
+        assert!(matches!(long_name.blocks(), Err(ProtocolError::PayloadTooLarge { .. })));
+    }
 
     #[test]
     #[allow(clippy::unwrap_used)]
@@ -265,4 +389,71 @@ mod tests {
         let expected = b"test.job".to_vec();
         assert_eq!(data, expected);
     }
+
+    #[test]
+    fn test_validate_filename_accepts_controller_names() {
+        for rules in [FileNameRules::Short, FileNameRules::Long] {
+            assert!(validate_filename("TEST.JBI", rules, TextEncoding::Utf8).is_ok());
+            assert!(validate_filename("PICK_01.JBI", rules, TextEncoding::Utf8).is_ok());
+            assert!(validate_filename("VAR.DAT", rules, TextEncoding::Utf8).is_ok());
+        }
+        let rules = FileNameRules::Long;
+        assert!(validate_filename("PALLETIZE_LAYER_01.JBI", rules, TextEncoding::Utf8).is_ok());
+        assert!(validate_filename("テスト.JBI", rules, TextEncoding::ShiftJis).is_ok());
+    }
+
+    #[test]
+    fn test_validate_filename_long_names_on_older_models() {
+        // DX100 and FS100 accept job names of up to 32 characters
+        for model in [ControllerModel::DX100, ControllerModel::FS100] {
+            let rules = model.file_name_rules();
+            assert!(validate_filename("WELDING_PROGRAM.JBI", rules, TextEncoding::Utf8).is_ok());
+            assert!(
+                validate_filename(&format!("{}.JBI", "A".repeat(32)), rules, TextEncoding::Utf8)
+                    .is_ok()
+            );
+        }
+    }
+
+    #[test]
+    fn test_validate_filename_rejects_invalid_names() {
+        let rules = FileNameRules::Long;
+        for name in ["", "TEST", "TEST.", ".JBI", "A.B.JBI", "MY JOB.JBI", "DIR/TEST.JBI", "T*.JBI"]
+        {
+            assert!(
+                matches!(
+                    validate_filename(name, rules, TextEncoding::Utf8),
+                    Err(ProtocolError::InvalidFileName(_))
+                ),
+                "'{name}' should be rejected"
+            );
+        }
+        assert!(validate_filename("TEST.JOBS", rules, TextEncoding::Utf8).is_err());
+        assert!(
+            validate_filename(&format!("{}.JBI", "A".repeat(33)), rules, TextEncoding::Utf8)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_validate_filename_short_names() {
+        let rules = FileNameRules::Short;
+        assert!(validate_filename("ABCDEFGH.JBI", rules, TextEncoding::Utf8).is_ok());
+        assert!(validate_filename("ABCDEFGHI.JBI", rules, TextEncoding::Utf8).is_err());
+        assert!(validate_filename("テスト.JBI", rules, TextEncoding::ShiftJis).is_err());
+        // 12 Shift_JIS characters are 24 bytes, within the 32 byte limit of long names
+        let rules = FileNameRules::Long;
+        assert!(
+            validate_filename("テストテストテストテスト.JBI", rules, TextEncoding::ShiftJis)
+                .is_ok()
+        );
+        assert!(
+            validate_filename(
+                "テストテストテストテストテストテスト.JBI",
+                rules,
+                TextEncoding::ShiftJis
+            )
+            .is_err()
+        );
+    }
 }
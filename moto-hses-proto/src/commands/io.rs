@@ -1,6 +1,6 @@
 //! I/O related commands (0x78)
 
-use super::command_trait::Command;
+use super::command_trait::{Command, Service};
 use crate::error::ProtocolError;
 
 /// I/O categories according to HSES protocol specification
@@ -59,6 +59,14 @@ impl IoCategory {
         Self::from_io_number(io_number).is_some()
     }
 
+    /// Check if the signals of this category can be written by a client
+    ///
+    /// Only network input signals are writable; the others are driven by the controller.
+    #[must_use]
+    pub const fn is_writable(self) -> bool {
+        matches!(self, Self::NetworkInput)
+    }
+
     /// Get the valid I/O number range as a string for error messages
     #[must_use]
     pub fn valid_range_description() -> String {
@@ -111,6 +119,59 @@ impl IoCategory {
     }
 }
 
+/// Address of a single I/O signal in the Yaskawa numbering
+///
+/// A signal is numbered by its I/O number followed by its bit, e.g. 10010 is bit 0 and
+/// 10017 bit 7 of robot user output #1001, so the last digit is always 0-7.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct IoBit {
+    pub io_number: u16,
+    /// Bit within the I/O data byte (0-7)
+    pub bit: u8,
+}
+
+impl IoBit {
+    /// Split a signal address into its I/O number and bit
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the last digit is above 7 or the I/O number is not valid
+    pub fn from_address(address: u32) -> Result<Self, ProtocolError> {
+        let invalid = |reason: &str| {
+            ProtocolError::InvalidMessage(format!("invalid I/O signal address {address}: {reason}"))
+        };
+        let bit = u8::try_from(address % 10).map_err(|_| invalid("bit out of range"))?;
+        if bit > 7 {
+            return Err(invalid("the last digit is the bit, 0-7"));
+        }
+        let io_number = u16::try_from(address / 10)
+            .ok()
+            .filter(|io_number| IoCategory::is_valid_io_number(*io_number))
+            .ok_or_else(|| {
+                invalid(&format!("valid I/O numbers are {}", IoCategory::valid_range_description()))
+            })?;
+        Ok(Self { io_number, bit })
+    }
+
+    /// Signal address, e.g. 27013 for bit 3 of I/O #2701
+    #[must_use]
+    pub const fn address(self) -> u32 {
+        self.io_number as u32 * 10 + self.bit as u32
+    }
+
+    /// Mask of the signal within its I/O data byte
+    #[must_use]
+    pub const fn mask(self) -> u8 {
+        1 << self.bit
+    }
+}
+
+impl std::fmt::Display for IoBit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "#{}", self.address())
+    }
+}
+
 /// Read I/O command (0x78)
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ReadIo {
@@ -143,8 +204,8 @@ impl Command for ReadIo {
         1 // Fixed to 1 for I/O commands
     }
 
-    fn service(&self) -> u8 {
-        0x0e // Get_Attribute_Single
+    fn service(&self) -> Service {
+        Service::GetSingle
     }
 }
 
@@ -181,9 +242,25 @@ impl Command for WriteIo {
         1 // Fixed to 1 for I/O commands
     }
 
-    fn service(&self) -> u8 {
-        0x10 // Set_Attribute_Single
+    fn service(&self) -> Service {
+        Service::SetSingle
+    }
+}
+
+/// Maximum number of I/O data bytes in one plural command
+const MAX_IO_COUNT: usize = 474;
+
+// Plural I/O commands carry 1-474 bytes, in pairs
+fn validate_io_count(count: usize) -> Result<(), ProtocolError> {
+    if count == 0 || count > MAX_IO_COUNT {
+        return Err(ProtocolError::InvalidCount { command: 0x300, got: count, max: MAX_IO_COUNT });
     }
+    if !count.is_multiple_of(2) {
+        return Err(ProtocolError::InvalidMessage(format!(
+            "I/O count must be a multiple of 2: {count}"
+        )));
+    }
+    Ok(())
 }
 
 /// Read multiple I/O data command (0x300)
@@ -201,15 +278,13 @@ impl ReadMultipleIo {
     /// Returns an error if the I/O number is invalid or count is invalid
     pub fn new(start_io_number: u16, count: u32) -> Result<Self, ProtocolError> {
         if !IoCategory::is_valid_io_number(start_io_number) {
-            return Err(ProtocolError::InvalidInstance(format!(
-                "Invalid I/O number: {start_io_number} (valid range: {})",
-                IoCategory::valid_range_description()
-            )));
-        }
-        // Validate count (max 474, must be multiple of 2)
-        if count == 0 || count > 474 || !count.is_multiple_of(2) {
-            return Err(ProtocolError::InvalidMessage("Invalid count".to_string()));
+            return Err(ProtocolError::InvalidInstance {
+                command: Self::command_id(),
+                instance: start_io_number,
+                valid: IoCategory::valid_range_description(),
+            });
         }
+        validate_io_count(count as usize)?;
         Ok(Self { start_io_number, count })
     }
 }
@@ -225,8 +300,8 @@ impl Command for ReadMultipleIo {
     fn attribute(&self) -> u8 {
         0
     } // Different from 0x78 (which uses 1)
-    fn service(&self) -> u8 {
-        0x33
+    fn service(&self) -> Service {
+        Service::ReadPlural
     } // Read plural data
     fn serialize(&self) -> Result<Vec<u8>, ProtocolError> {
         // Only send count (4 bytes, little-endian)
@@ -249,16 +324,13 @@ impl WriteMultipleIo {
     /// Returns an error if the I/O number is invalid or `io_data` is invalid
     pub fn new(start_io_number: u16, io_data: Vec<u8>) -> Result<Self, ProtocolError> {
         if !IoCategory::is_valid_io_number(start_io_number) {
-            return Err(ProtocolError::InvalidInstance(format!(
-                "Invalid I/O number: {start_io_number} (valid range: {})",
-                IoCategory::valid_range_description()
-            )));
-        }
-        let count = io_data.len();
-        // Validate count (max 474, must be multiple of 2)
-        if count == 0 || count > 474 || !count.is_multiple_of(2) {
-            return Err(ProtocolError::InvalidMessage("Invalid count".to_string()));
+            return Err(ProtocolError::InvalidInstance {
+                command: Self::command_id(),
+                instance: start_io_number,
+                valid: IoCategory::valid_range_description(),
+            });
         }
+        validate_io_count(io_data.len())?;
         Ok(Self { start_io_number, io_data })
     }
 }
@@ -274,12 +346,15 @@ impl Command for WriteMultipleIo {
     fn attribute(&self) -> u8 {
         0
     } // Different from 0x78 (which uses 1)
-    fn service(&self) -> u8 {
-        0x34
+    fn service(&self) -> Service {
+        Service::WritePlural
     } // Write plural data
     fn serialize(&self) -> Result<Vec<u8>, ProtocolError> {
-        let count = u32::try_from(self.io_data.len())
-            .map_err(|_| ProtocolError::InvalidMessage("I/O data too large".to_string()))?;
+        let count = u32::try_from(self.io_data.len()).map_err(|_| ProtocolError::InvalidCount {
+            command: Self::command_id(),
+            got: self.io_data.len(),
+            max: MAX_IO_COUNT,
+        })?;
         let mut payload = count.to_le_bytes().to_vec();
         payload.extend_from_slice(&self.io_data);
         Ok(payload)
@@ -326,6 +401,14 @@ mod tests {
         assert_eq!(IoCategory::PseudoInput.range(), (8701, 8720));
     }
 
+    #[test]
+    fn test_io_category_is_writable() {
+        assert!(IoCategory::NetworkInput.is_writable());
+        assert!(!IoCategory::RobotUserInput.is_writable());
+        assert!(!IoCategory::RobotUserOutput.is_writable());
+        assert!(!IoCategory::NetworkOutput.is_writable());
+    }
+
     #[test]
     fn test_io_ranges_consistency() {
         // Test that all ranges are properly defined
@@ -349,6 +432,23 @@ mod tests {
         }
     }
 
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn test_io_bit_address() {
+        let bit = IoBit::from_address(10010).unwrap();
+        assert_eq!(bit, IoBit { io_number: 1001, bit: 0 });
+        assert_eq!(IoBit::from_address(27017).unwrap(), IoBit { io_number: 2701, bit: 7 });
+        assert_eq!(IoBit::from_address(87207).unwrap().io_number, 8720);
+        assert_eq!(IoBit::from_address(27013).unwrap().mask(), 0b0000_1000);
+        assert_eq!(IoBit { io_number: 2701, bit: 3 }.address(), 27013);
+        assert_eq!(IoBit { io_number: 1, bit: 5 }.to_string(), "#15");
+
+        // The last digit is the bit, and the rest a valid I/O number
+        for invalid in [10018, 10019, 5, 9990, 5130, 100_000_000] {
+            assert!(IoBit::from_address(invalid).is_err(), "{invalid} should be rejected");
+        }
+    }
+
     #[test]
     #[allow(clippy::unwrap_used)]
     fn test_read_multiple_io_construction() {
@@ -377,7 +477,10 @@ mod tests {
         assert!(ReadMultipleIo::new(1, 3).is_err());
 
         // Invalid count - too large
-        assert!(ReadMultipleIo::new(1, 475).is_err());
+        assert!(matches!(
+            ReadMultipleIo::new(1, 475),
+            Err(ProtocolError::InvalidCount { command: 0x300, got: 475, max: 474 })
+        ));
         assert!(ReadMultipleIo::new(1, 1000).is_err());
     }
 
@@ -388,7 +491,7 @@ mod tests {
         assert_eq!(ReadMultipleIo::command_id(), 0x300);
         assert_eq!(cmd.instance(), 1);
         assert_eq!(cmd.attribute(), 0);
-        assert_eq!(cmd.service(), 0x33);
+        assert_eq!(cmd.service(), Service::ReadPlural);
     }
 
     #[test]
@@ -423,7 +526,10 @@ mod tests {
     #[test]
     fn test_write_multiple_io_validation() {
         // Invalid I/O number
-        assert!(WriteMultipleIo::new(0, vec![0, 0]).is_err());
+        assert!(matches!(
+            WriteMultipleIo::new(0, vec![0, 0]),
+            Err(ProtocolError::InvalidInstance { command: 0x300, instance: 0, .. })
+        ));
         assert!(WriteMultipleIo::new(65535, vec![0, 0]).is_err());
 
         // Invalid count - empty
@@ -446,7 +552,7 @@ mod tests {
         assert_eq!(WriteMultipleIo::command_id(), 0x300);
         assert_eq!(cmd.instance(), 1);
         assert_eq!(cmd.attribute(), 0);
-        assert_eq!(cmd.service(), 0x34);
+        assert_eq!(cmd.service(), Service::WritePlural);
     }
 
     #[test]
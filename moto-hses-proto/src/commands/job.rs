@@ -1,6 +1,6 @@
 //! Job related commands (0x73, 0x86, 0x87)
 
-use super::command_trait::Command;
+use super::command_trait::{AttributeRead, Command, Service};
 use crate::encoding::TextEncoding;
 use crate::error::ProtocolError;
 
@@ -16,16 +16,83 @@ pub enum TaskType {
     Invalid,
 }
 
+impl TaskType {
+    /// Name used by the `Display` and `FromStr` implementations, e.g. "sub-task-1"
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::MasterTask => "master-task",
+            Self::SubTask1 => "sub-task-1",
+            Self::SubTask2 => "sub-task-2",
+            Self::SubTask3 => "sub-task-3",
+            Self::SubTask4 => "sub-task-4",
+            Self::SubTask5 => "sub-task-5",
+            Self::Invalid => "invalid",
+        }
+    }
+}
+
+impl std::fmt::Display for TaskType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for TaskType {
+    type Err = ProtocolError;
+
+    /// Parse a task name case-insensitively; "invalid" is not accepted
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        match text.trim().to_ascii_lowercase().as_str() {
+            "master-task" => Ok(Self::MasterTask),
+            "sub-task-1" => Ok(Self::SubTask1),
+            "sub-task-2" => Ok(Self::SubTask2),
+            "sub-task-3" => Ok(Self::SubTask3),
+            "sub-task-4" => Ok(Self::SubTask4),
+            "sub-task-5" => Ok(Self::SubTask5),
+            _ => Err(ProtocolError::InvalidMessage(format!("invalid task type '{text}'"))),
+        }
+    }
+}
+
+/// Attributes of the executing job information (0x73)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JobInfoAttribute {
+    All = 0,
+    JobName = 1,
+    LineNumber = 2,
+    StepNumber = 3,
+    SpeedOverride = 4,
+}
+
+impl TryFrom<u8> for JobInfoAttribute {
+    type Error = ProtocolError;
+
+    /// # Errors
+    ///
+    /// Returns [`ProtocolError::InvalidAttribute`] for values other than 0-4
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Self::All),
+            1 => Ok(Self::JobName),
+            2 => Ok(Self::LineNumber),
+            3 => Ok(Self::StepNumber),
+            4 => Ok(Self::SpeedOverride),
+            _ => Err(ProtocolError::InvalidAttribute),
+        }
+    }
+}
+
 /// Command for reading executing job information (0x73)
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ReadExecutingJobInfo {
     pub instance: u16,
-    pub attribute: u8,
+    pub attribute: JobInfoAttribute,
 }
 
 impl ReadExecutingJobInfo {
     #[must_use]
-    pub const fn new(instance: u16, attribute: u8) -> Self {
+    pub const fn new(instance: u16, attribute: JobInfoAttribute) -> Self {
         Self { instance, attribute }
     }
 
@@ -48,12 +115,6 @@ impl ReadExecutingJobInfo {
             _ => TaskType::Invalid,
         }
     }
-
-    /// Validate attribute range for job info reading
-    #[must_use]
-    pub const fn is_valid_attribute(&self) -> bool {
-        matches!(self.attribute, 0..=4)
-    }
 }
 
 impl Command for ReadExecutingJobInfo {
@@ -74,15 +135,17 @@ impl Command for ReadExecutingJobInfo {
     }
 
     fn attribute(&self) -> u8 {
-        self.attribute
+        self.attribute as u8
     }
 
-    fn service(&self) -> u8 {
-        if self.attribute == 0 {
-            0x01 // Get_Attribute_All
-        } else {
-            0x0e // Get_Attribute_Single
-        }
+    fn service(&self) -> Service {
+        if self.attribute == JobInfoAttribute::All { Service::GetAll } else { Service::GetSingle }
+    }
+}
+
+impl AttributeRead for ReadExecutingJobInfo {
+    fn with_attribute(&self, attribute: u8) -> Result<Self, ProtocolError> {
+        Ok(Self::new(self.instance, JobInfoAttribute::try_from(attribute)?))
     }
 }
 
@@ -123,8 +186,13 @@ impl Command for JobStartCommand {
         1 // Fixed according to specification
     }
 
-    fn service(&self) -> u8 {
-        0x10 // Set_Attribute_Single
+    fn service(&self) -> Service {
+        Service::SetSingle
+    }
+
+    // A second start runs the job again once the first run has finished
+    fn is_idempotent(&self) -> bool {
+        false
     }
 }
 
@@ -179,7 +247,7 @@ impl Command for JobSelectCommand {
 
         // Validate job name length (max 32 characters)
         if job_name_bytes.len() > 32 {
-            return Err(ProtocolError::InvalidMessage("Job name exceeds 32 bytes".to_string()));
+            return Err(ProtocolError::PayloadTooLarge { size: job_name_bytes.len(), max: 32 });
         }
 
         // Create 32-byte buffer for job name (pad with zeros)
@@ -200,8 +268,8 @@ impl Command for JobSelectCommand {
         0 // Fixed to 0(All attributes)
     }
 
-    fn service(&self) -> u8 {
-        0x02 // Set_Attribute_All
+    fn service(&self) -> Service {
+        Service::SetAll
     }
 }
 
@@ -210,6 +278,37 @@ impl Command for JobSelectCommand {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_task_type_name_round_trip() {
+        for value in [
+            TaskType::MasterTask,
+            TaskType::SubTask1,
+            TaskType::SubTask2,
+            TaskType::SubTask3,
+            TaskType::SubTask4,
+            TaskType::SubTask5,
+        ] {
+            assert_eq!(value.to_string().parse::<TaskType>().unwrap(), value);
+        }
+        assert!("invalid".parse::<TaskType>().is_err());
+    }
+
+    #[test]
+    fn test_job_info_attribute_validation() {
+        assert_eq!(JobInfoAttribute::try_from(0).unwrap(), JobInfoAttribute::All);
+        assert_eq!(JobInfoAttribute::try_from(4).unwrap(), JobInfoAttribute::SpeedOverride);
+        assert!(matches!(JobInfoAttribute::try_from(5), Err(ProtocolError::InvalidAttribute)));
+
+        let command = ReadExecutingJobInfo::new(1, JobInfoAttribute::LineNumber);
+        assert_eq!(command.attribute(), 2);
+        assert_eq!(command.service(), Service::GetSingle);
+        let command = ReadExecutingJobInfo::new(1, JobInfoAttribute::All);
+        assert_eq!(command.service(), Service::GetAll);
+        let single = command.with_attribute(3).unwrap();
+        assert_eq!(single.attribute, JobInfoAttribute::StepNumber);
+        assert!(command.with_attribute(5).is_err());
+    }
+
     #[test]
     fn test_job_start_command_trait() {
         let command = JobStartCommand::new();
@@ -217,7 +316,8 @@ mod tests {
         assert_eq!(JobStartCommand::command_id(), 0x86);
         assert_eq!(command.instance(), 1);
         assert_eq!(command.attribute(), 1);
-        assert_eq!(command.service(), 0x10);
+        assert_eq!(command.service(), Service::SetSingle);
+        assert!(!command.is_idempotent());
     }
 
     #[test]
@@ -239,7 +339,7 @@ mod tests {
         assert_eq!(JobSelectCommand::command_id(), 0x87);
         assert_eq!(command.instance(), 1);
         assert_eq!(command.attribute(), 0);
-        assert_eq!(command.service(), 0x02);
+        assert_eq!(command.service(), Service::SetAll);
     }
 
     #[test]
@@ -290,7 +390,7 @@ mod tests {
         let result = command.serialize();
 
         assert!(result.is_err());
-        assert!(matches!(result.unwrap_err(), ProtocolError::InvalidMessage(_)));
+        assert!(matches!(result.unwrap_err(), ProtocolError::PayloadTooLarge { max: 32, .. }));
     }
 
     #[test]
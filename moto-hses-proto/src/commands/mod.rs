@@ -8,26 +8,39 @@ pub mod cycle_mode;
 pub mod file;
 pub mod io;
 pub mod job;
+pub mod motion;
 pub mod position;
 pub mod register;
+pub mod registry;
 pub mod servo;
 pub mod status;
+pub mod system_info;
 pub mod variable;
 
 // Re-export core traits and common types
-pub use alarm::{AlarmAttribute, AlarmReset, ReadAlarmData, ReadAlarmHistory};
-pub use command_trait::{Command, Division, Service};
+pub use alarm::{
+    ALARM_HISTORY_SIZE, AlarmAttribute, AlarmCategory, AlarmReset, ReadAlarmData, ReadAlarmHistory,
+};
+pub use command_trait::{AttributeRead, Command, Division, Service};
 pub use cycle_mode::{CycleMode, CycleModeSwitchingCommand};
 pub use file::response::{parse_file_content, parse_file_list};
-pub use file::{DeleteFile, ReadFileList, ReceiveFile, SendFile};
-pub use io::{ReadIo, ReadMultipleIo, WriteIo, WriteMultipleIo};
-pub use job::{JobSelectCommand, JobSelectType, JobStartCommand, ReadExecutingJobInfo, TaskType};
+pub use file::{DeleteFile, ReadFileList, ReceiveFile, SendFile, validate_filename};
+pub use io::{IoBit, ReadIo, ReadMultipleIo, WriteIo, WriteMultipleIo};
+pub use job::{
+    JobInfoAttribute, JobSelectCommand, JobSelectType, JobStartCommand, ReadExecutingJobInfo,
+    TaskType,
+};
+pub use motion::{MoveCartesian, MoveFrame, MovePulse, MoveSpeed, MoveType};
 pub use position::ReadCurrentPosition;
 pub use register::{ReadMultipleRegisters, ReadRegister, WriteMultipleRegisters, WriteRegister};
+pub use registry::{COMMANDS, CommandInfo, command_info};
 pub use servo::{HoldServoControl, HoldServoType, HoldServoValue};
-pub use status::{ReadStatus, ReadStatusData1, ReadStatusData2};
+pub use status::{ReadStatus, ReadStatusData1, ReadStatusData2, StatusAttribute};
+pub use system_info::ReadSystemInfo;
 pub use variable::{
-    MultipleVariableCommandId, MultipleVariableResponse, ReadMultipleVariables, ReadVariable,
-    VariableCommandId, WriteMultipleStringVariables, WriteMultipleVariables, WriteStringVar,
-    WriteVariable,
+    MultipleVariableCommandId, MultipleVariableResponse, POSITION_VARIABLE_AXES,
+    POSITION_VARIABLE_SIZE, ReadBasePositionVar, ReadMultipleVariables, ReadPositionVar,
+    ReadStationVar, ReadVariable, VariableCommandId, WriteBasePositionVar,
+    WriteMultipleStringVariables, WriteMultipleVariables, WritePositionVar, WriteStationVar,
+    WriteStringVar, WriteVariable,
 };
@@ -0,0 +1,542 @@
+//! Move instruction commands (0x8A, 0x8B)
+
+use super::command_trait::{Command, Service};
+use crate::error::ProtocolError;
+use crate::payload::position::{CartesianPosition, Configuration, ExtendedConfiguration};
+
+/// Size of the move instruction payload (26 x 4 bytes)
+const MOVE_CARTESIAN_PAYLOAD_SIZE: usize = 104;
+
+/// Size of the pulse move instruction payload (22 x 4 bytes)
+const MOVE_PULSE_PAYLOAD_SIZE: usize = 88;
+
+/// Highest tool number
+pub const MAX_TOOL_NO: u8 = 63;
+
+/// Highest user coordinate number; user coordinates are numbered from 1
+pub const MAX_USER_COORD_NO: u8 = 63;
+
+/// Number of robot axes in a pulse move instruction
+pub const MOVE_PULSE_ROBOT_AXES: usize = 8;
+
+/// Operation performed by a move instruction, selected by the instance
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MoveType {
+    /// Link (joint) motion to an absolute position (MOVJ)
+    JointAbsolute = 1,
+    /// Straight motion to an absolute position (MOVL)
+    LinearAbsolute = 2,
+    /// Straight motion by an increment (IMOV)
+    LinearIncremental = 3,
+}
+
+/// Coordinate frame the target or increment of a move instruction is given in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MoveFrame {
+    #[default]
+    Base = 16,
+    Robot = 17,
+    User = 18,
+    Tool = 19,
+}
+
+/// Speed of a move instruction
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MoveSpeed {
+    /// Percentage of the maximum joint speed [%]
+    Joint(f32),
+    /// Speed of the control point [mm/s]
+    Linear(f32),
+    /// Angular speed of the tool posture [deg/s]
+    Rotational(f32),
+}
+
+impl MoveSpeed {
+    /// Speed classification sent with the command
+    #[must_use]
+    pub const fn classification(self) -> u32 {
+        match self {
+            Self::Joint(_) => 0,
+            Self::Linear(_) => 1,
+            Self::Rotational(_) => 2,
+        }
+    }
+
+    /// Speed in protocol units (0.01%, 0.1mm/s or 0.1deg/s)
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the speed is negative, zero or not finite
+    pub fn to_raw(self) -> Result<u32, ProtocolError> {
+        let (value, scale) = match self {
+            Self::Joint(percent) => (percent, 100.0),
+            Self::Linear(value) | Self::Rotational(value) => (value, 10.0),
+        };
+        let raw = (f64::from(value) * scale).round();
+        if !raw.is_finite() || raw < 1.0 || raw > f64::from(u32::MAX) {
+            return Err(ProtocolError::InvalidMessage(format!("invalid move speed {self:?}")));
+        }
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        Ok(raw as u32)
+    }
+}
+
+/// Move instruction with a Cartesian target (0x8A)
+///
+/// For [`MoveType::LinearIncremental`] the coordinates are the increment to move by,
+/// otherwise they are the target position in `frame`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MoveCartesian {
+    pub move_type: MoveType,
+    /// Robot number (1-8)
+    pub robot: u8,
+    /// Station number (0 when no station is moved)
+    pub station: u8,
+    pub speed: MoveSpeed,
+    pub frame: MoveFrame,
+    // X, Y, Z [mm]
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+    // RX, RY, RZ [deg]
+    pub rx: f32,
+    pub ry: f32,
+    pub rz: f32,
+    pub configuration: Configuration,
+    pub extended_configuration: ExtendedConfiguration,
+    pub tool_no: u8,
+    pub user_coord_no: u8,
+    /// Base axis positions [pulse]
+    pub base_axes: [i32; 3],
+    /// Station axis positions [pulse]
+    pub station_axes: [i32; 6],
+}
+
+impl MoveCartesian {
+    /// Create a move of `robot` to or by the given coordinates, with no base or station motion
+    #[allow(clippy::too_many_arguments)]
+    #[must_use]
+    pub const fn new(
+        move_type: MoveType,
+        robot: u8,
+        speed: MoveSpeed,
+        frame: MoveFrame,
+        x: f32,
+        y: f32,
+        z: f32,
+        rx: f32,
+        ry: f32,
+        rz: f32,
+    ) -> Self {
+        Self {
+            move_type,
+            robot,
+            station: 0,
+            speed,
+            frame,
+            x,
+            y,
+            z,
+            rx,
+            ry,
+            rz,
+            configuration: Configuration::from_raw(0),
+            extended_configuration: ExtendedConfiguration::from_raw(0),
+            tool_no: 0,
+            user_coord_no: 0,
+            base_axes: [0; 3],
+            station_axes: [0; 6],
+        }
+    }
+
+    /// Set the tool number
+    #[must_use]
+    pub const fn with_tool(mut self, tool_no: u8) -> Self {
+        self.tool_no = tool_no;
+        self
+    }
+
+    /// Set the user coordinate number used with [`MoveFrame::User`]
+    #[must_use]
+    pub const fn with_user_coord(mut self, user_coord_no: u8) -> Self {
+        self.user_coord_no = user_coord_no;
+        self
+    }
+
+    /// Set the configuration (form) of the target position
+    #[must_use]
+    pub const fn with_configuration(
+        mut self,
+        configuration: Configuration,
+        extended_configuration: ExtendedConfiguration,
+    ) -> Self {
+        self.configuration = configuration;
+        self.extended_configuration = extended_configuration;
+        self
+    }
+
+    /// Take the tool number, user coordinate number and configuration from `position`,
+    /// e.g. the current position, so that the move keeps them
+    #[must_use]
+    pub const fn with_settings_of(mut self, position: &CartesianPosition) -> Self {
+        self.tool_no = position.tool_no;
+        self.user_coord_no = position.user_coord_no;
+        self.configuration = position.configuration;
+        self.extended_configuration = position.extended_configuration;
+        self
+    }
+}
+
+// Millimeters to micrometers and degrees to 0.0001 degrees, as in position data
+#[allow(clippy::cast_possible_truncation)]
+fn coordinate_to_raw(value: f32, scale: f64) -> Result<i32, ProtocolError> {
+    let raw = (f64::from(value) * scale).round();
+    if !raw.is_finite() || raw < f64::from(i32::MIN) || raw > f64::from(i32::MAX) {
+        return Err(ProtocolError::InvalidMessage(format!("coordinate {value} out of range")));
+    }
+    Ok(raw as i32)
+}
+
+fn validate_tool(tool_no: u8) -> Result<(), ProtocolError> {
+    if tool_no > MAX_TOOL_NO {
+        return Err(ProtocolError::InvalidMessage(format!(
+            "invalid tool number {tool_no}, expected 0-{MAX_TOOL_NO}"
+        )));
+    }
+    Ok(())
+}
+
+impl Command for MoveCartesian {
+    type Response = ();
+
+    fn command_id() -> u16 {
+        0x8A
+    }
+
+    fn serialize(&self) -> Result<Vec<u8>, ProtocolError> {
+        if !(1..=8).contains(&self.robot) {
+            return Err(ProtocolError::InvalidMessage(format!(
+                "invalid robot number {}",
+                self.robot
+            )));
+        }
+
+        validate_tool(self.tool_no)?;
+        if self.frame == MoveFrame::User && !(1..=MAX_USER_COORD_NO).contains(&self.user_coord_no) {
+            return Err(ProtocolError::InvalidMessage(format!(
+                "moves in the user frame need a user coordinate number 1-{MAX_USER_COORD_NO}, got {}",
+                self.user_coord_no
+            )));
+        }
+        if self.extended_configuration.reserved_bits() != 0 {
+            return Err(ProtocolError::InvalidMessage(format!(
+                "extended configuration sets reserved bits 0x{:02X} ({})",
+                self.extended_configuration.reserved_bits(),
+                self.extended_configuration
+            )));
+        }
+
+        let mut payload = Vec::with_capacity(MOVE_CARTESIAN_PAYLOAD_SIZE);
+        for value in [
+            u32::from(self.robot),
+            u32::from(self.station),
+            self.speed.classification(),
+            self.speed.to_raw()?,
+            self.frame as u32,
+        ] {
+            payload.extend_from_slice(&value.to_le_bytes());
+        }
+        for value in [self.x, self.y, self.z] {
+            payload.extend_from_slice(&coordinate_to_raw(value, 1000.0)?.to_le_bytes()); // mm to μm
+        }
+        for value in [self.rx, self.ry, self.rz] {
+            payload.extend_from_slice(&coordinate_to_raw(value, 10000.0)?.to_le_bytes()); // deg to 0.0001deg
+        }
+        for value in [
+            0, // Reserve
+            0, // Reserve
+            u32::from(self.configuration.to_raw()),
+            u32::from(self.extended_configuration.to_raw()),
+            u32::from(self.tool_no),
+            u32::from(self.user_coord_no),
+        ] {
+            payload.extend_from_slice(&value.to_le_bytes());
+        }
+        for axis in self.base_axes.iter().chain(&self.station_axes) {
+            payload.extend_from_slice(&axis.to_le_bytes());
+        }
+        Ok(payload)
+    }
+
+    fn instance(&self) -> u16 {
+        self.move_type as u16
+    }
+
+    fn attribute(&self) -> u8 {
+        1 // Fixed to 1
+    }
+
+    fn service(&self) -> Service {
+        Service::SetAll
+    }
+
+    // An absolute move to the same target again ends where the first one did
+    fn is_idempotent(&self) -> bool {
+        self.move_type != MoveType::LinearIncremental
+    }
+}
+
+/// Move instruction with a pulse target (0x8B)
+///
+/// Only absolute moves are defined for pulse targets; [`MoveType::LinearIncremental`]
+/// is rejected on serialization.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MovePulse {
+    pub move_type: MoveType,
+    /// Robot number (1-8)
+    pub robot: u8,
+    /// Station number (0 when no station is moved)
+    pub station: u8,
+    pub speed: MoveSpeed,
+    /// Robot axis positions [pulse]; unused axes are sent as 0
+    pub joints: Vec<i32>,
+    pub tool_no: u8,
+    /// Base axis positions [pulse]
+    pub base_axes: [i32; 3],
+    /// Station axis positions [pulse]
+    pub station_axes: [i32; 6],
+}
+
+impl MovePulse {
+    /// Create a move of `robot` to the given axis positions, with no base or station motion
+    #[must_use]
+    pub const fn new(move_type: MoveType, robot: u8, speed: MoveSpeed, joints: Vec<i32>) -> Self {
+        Self {
+            move_type,
+            robot,
+            station: 0,
+            speed,
+            joints,
+            tool_no: 0,
+            base_axes: [0; 3],
+            station_axes: [0; 6],
+        }
+    }
+
+    /// Set the tool number
+    #[must_use]
+    pub const fn with_tool(mut self, tool_no: u8) -> Self {
+        self.tool_no = tool_no;
+        self
+    }
+}
+
+impl Command for MovePulse {
+    type Response = ();
+
+    fn command_id() -> u16 {
+        0x8B
+    }
+
+    fn serialize(&self) -> Result<Vec<u8>, ProtocolError> {
+        if !(1..=8).contains(&self.robot) {
+            return Err(ProtocolError::InvalidMessage(format!(
+                "invalid robot number {}",
+                self.robot
+            )));
+        }
+        if self.move_type == MoveType::LinearIncremental {
+            return Err(ProtocolError::InvalidMessage(
+                "pulse moves do not support increments".to_string(),
+            ));
+        }
+        if self.joints.len() > MOVE_PULSE_ROBOT_AXES {
+            return Err(ProtocolError::InvalidMessage(format!(
+                "too many robot axes: {}",
+                self.joints.len()
+            )));
+        }
+
+        validate_tool(self.tool_no)?;
+
+        let mut payload = Vec::with_capacity(MOVE_PULSE_PAYLOAD_SIZE);
+        for value in [
+            u32::from(self.robot),
+            u32::from(self.station),
+            self.speed.classification(),
+            self.speed.to_raw()?,
+        ] {
+            payload.extend_from_slice(&value.to_le_bytes());
+        }
+        for axis in 0..MOVE_PULSE_ROBOT_AXES {
+            payload.extend_from_slice(&self.joints.get(axis).copied().unwrap_or(0).to_le_bytes());
+        }
+        payload.extend_from_slice(&u32::from(self.tool_no).to_le_bytes());
+        for axis in self.base_axes.iter().chain(&self.station_axes) {
+            payload.extend_from_slice(&axis.to_le_bytes());
+        }
+        Ok(payload)
+    }
+
+    fn instance(&self) -> u16 {
+        self.move_type as u16
+    }
+
+    fn attribute(&self) -> u8 {
+        1 // Fixed to 1
+    }
+
+    fn service(&self) -> Service {
+        Service::SetAll
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    fn word(data: &[u8], index: usize) -> i32 {
+        i32::from_le_bytes(data[index * 4..index * 4 + 4].try_into().unwrap())
+    }
+
+    #[test]
+    fn test_move_cartesian_serialization() {
+        let command = MoveCartesian::new(
+            MoveType::LinearIncremental,
+            1,
+            MoveSpeed::Linear(25.0),
+            MoveFrame::Tool,
+            1.5,
+            -2.0,
+            0.0,
+            0.0,
+            0.0,
+            -0.25,
+        )
+        .with_tool(3);
+
+        assert_eq!(MoveCartesian::command_id(), 0x8A);
+        assert_eq!(command.instance(), 3);
+        assert_eq!(command.attribute(), 1);
+        assert_eq!(command.service(), Service::SetAll);
+        assert!(!command.is_idempotent());
+
+        let data = command.serialize().unwrap();
+        assert_eq!(data.len(), MOVE_CARTESIAN_PAYLOAD_SIZE);
+        assert_eq!(word(&data, 0), 1); // Robot
+        assert_eq!(word(&data, 2), 1); // Speed classification V
+        assert_eq!(word(&data, 3), 250); // 0.1 mm/s
+        assert_eq!(word(&data, 4), 19); // Tool frame
+        assert_eq!(word(&data, 5), 1500);
+        assert_eq!(word(&data, 6), -2000);
+        assert_eq!(word(&data, 10), -2500);
+        assert_eq!(word(&data, 15), 3); // Tool number
+
+        let absolute = MoveCartesian { move_type: MoveType::LinearAbsolute, ..command };
+        assert!(absolute.is_idempotent());
+    }
+
+    #[test]
+    fn test_move_cartesian_keeps_position_settings() {
+        let position = CartesianPosition::new(
+            0.0,
+            0.0,
+            0.0,
+            0.0,
+            0.0,
+            0.0,
+            4,
+            2,
+            Configuration::from_raw(0x85),
+            ExtendedConfiguration::from_raw(0x11),
+        );
+        let command = MoveCartesian::new(
+            MoveType::LinearAbsolute,
+            1,
+            MoveSpeed::Linear(10.0),
+            MoveFrame::User,
+            1.0,
+            2.0,
+            3.0,
+            0.0,
+            0.0,
+            0.0,
+        )
+        .with_settings_of(&position);
+
+        let data = command.serialize().unwrap();
+        assert_eq!(word(&data, 13), 0x85); // Type
+        assert_eq!(word(&data, 14), 0x11); // Expanded type
+        assert_eq!(word(&data, 15), 4); // Tool number
+        assert_eq!(word(&data, 16), 2); // User coordinate number
+    }
+
+    #[test]
+    fn test_move_cartesian_rejects_invalid_values() {
+        let command = |robot, speed, x| {
+            MoveCartesian::new(
+                MoveType::LinearAbsolute,
+                robot,
+                speed,
+                MoveFrame::Base,
+                x,
+                0.0,
+                0.0,
+                0.0,
+                0.0,
+                0.0,
+            )
+        };
+
+        assert!(command(0, MoveSpeed::Joint(10.0), 0.0).serialize().is_err());
+        assert!(command(1, MoveSpeed::Joint(0.0), 0.0).serialize().is_err());
+        assert!(command(1, MoveSpeed::Linear(f32::NAN), 0.0).serialize().is_err());
+        assert!(command(1, MoveSpeed::Joint(10.0), f32::INFINITY).serialize().is_err());
+        assert!(command(1, MoveSpeed::Joint(10.0), 1.0e9).serialize().is_err());
+        let valid = command(1, MoveSpeed::Joint(10.0), 0.0);
+        assert!(valid.clone().with_tool(MAX_TOOL_NO + 1).serialize().is_err());
+        let reserved = ExtendedConfiguration::from_raw(0x20);
+        assert!(
+            valid.clone().with_configuration(valid.configuration, reserved).serialize().is_err()
+        );
+        let user_frame = MoveCartesian { frame: MoveFrame::User, ..valid };
+        assert!(user_frame.serialize().is_err());
+        assert!(user_frame.with_user_coord(1).serialize().is_ok());
+        assert_eq!(
+            command(1, MoveSpeed::Joint(12.34), 0.0).serialize().unwrap()[12..16],
+            [210, 4, 0, 0]
+        );
+    }
+
+    #[test]
+    fn test_move_pulse_serialization() {
+        let command = MovePulse::new(
+            MoveType::JointAbsolute,
+            2,
+            MoveSpeed::Joint(5.0),
+            vec![100, -200, 300, 0, 0, 600],
+        )
+        .with_tool(1);
+
+        assert_eq!(MovePulse::command_id(), 0x8B);
+        assert_eq!(command.instance(), 1);
+        assert_eq!(command.attribute(), 1);
+        assert_eq!(command.service(), Service::SetAll);
+
+        let data = command.serialize().unwrap();
+        assert_eq!(data.len(), MOVE_PULSE_PAYLOAD_SIZE);
+        assert_eq!(word(&data, 0), 2); // Robot
+        assert_eq!(word(&data, 2), 0); // Speed classification %
+        assert_eq!(word(&data, 3), 500); // 0.01%
+        assert_eq!(word(&data, 5), -200);
+        assert_eq!(word(&data, 9), 600);
+        assert_eq!(word(&data, 11), 0); // Unused 8th axis
+        assert_eq!(word(&data, 12), 1); // Tool number
+
+        let increment = MovePulse { move_type: MoveType::LinearIncremental, ..command.clone() };
+        assert!(increment.serialize().is_err());
+        let too_many_axes = MovePulse { joints: vec![0; 9], ..command };
+        assert!(too_many_axes.serialize().is_err());
+    }
+}
@@ -1,6 +1,6 @@
 //! Position related commands (0x75)
 
-use super::command_trait::Command;
+use super::command_trait::{Command, Service};
 use crate::error::ProtocolError;
 
 /// Read current position command (0x75)
@@ -35,7 +35,7 @@ impl Command for ReadCurrentPosition {
         0
     }
 
-    fn service(&self) -> u8 {
-        0x01 // Get_Attribute_All
+    fn service(&self) -> Service {
+        Service::GetAll
     }
 }
@@ -1,6 +1,6 @@
 //! Register related commands (0x79)
 
-use super::command_trait::Command;
+use super::command_trait::{Command, Service};
 use crate::error::ProtocolError;
 
 /// Read register command (0x79)
@@ -35,8 +35,8 @@ impl Command for ReadRegister {
         1 // Fixed to 1 for register commands
     }
 
-    fn service(&self) -> u8 {
-        0x0e // Get_Attribute_Single
+    fn service(&self) -> Service {
+        Service::GetSingle
     }
 }
 
@@ -74,11 +74,19 @@ impl Command for WriteRegister {
         1 // Fixed to 1 for register commands
     }
 
-    fn service(&self) -> u8 {
-        0x10 // Set_Attribute_Single
+    fn service(&self) -> Service {
+        Service::SetSingle
     }
 }
 
+/// Maximum number of registers in one plural command
+const MAX_REGISTER_COUNT: usize = 237;
+
+// Largest count of a range starting at `start` that does not pass register `last`
+fn max_register_count(start: u16, last: u16) -> usize {
+    usize::from(last.saturating_sub(start) + 1).min(MAX_REGISTER_COUNT)
+}
+
 /// Read multiple registers command (0x301)
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ReadMultipleRegisters {
@@ -95,22 +103,20 @@ impl ReadMultipleRegisters {
     pub fn new(start_register_number: u16, count: u32) -> Result<Self, ProtocolError> {
         // Validate register number (0-999)
         if start_register_number > 999 {
-            return Err(ProtocolError::InvalidInstance(format!(
-                "Invalid register number: {start_register_number} (valid range: 0-999)"
-            )));
+            return Err(ProtocolError::InvalidInstance {
+                command: Self::command_id(),
+                instance: start_register_number,
+                valid: "0-999".to_string(),
+            });
         }
-        // Validate count (max 237, must be > 0)
-        if count == 0 || count > 237 {
-            return Err(ProtocolError::InvalidMessage(format!(
-                "Invalid count: {count} (must be 1-237)"
-            )));
-        }
-        // Validate range doesn't exceed maximum register number
-        let end_register = u32::from(start_register_number) + count - 1;
-        if end_register > 999 {
-            return Err(ProtocolError::InvalidMessage(format!(
-                "Register range exceeds maximum: {start_register_number}-{end_register} (max 999)"
-            )));
+        // Validate count (max 237, must be > 0) and that the range ends at register 999
+        let max = max_register_count(start_register_number, 999);
+        if count == 0 || count as usize > max {
+            return Err(ProtocolError::InvalidCount {
+                command: Self::command_id(),
+                got: count as usize,
+                max,
+            });
         }
         Ok(Self { start_register_number, count })
     }
@@ -127,8 +133,8 @@ impl Command for ReadMultipleRegisters {
     fn attribute(&self) -> u8 {
         0
     } // Different from 0x79 (which uses 1)
-    fn service(&self) -> u8 {
-        0x33
+    fn service(&self) -> Service {
+        Service::ReadPlural
     } // Read plural data
     fn serialize(&self) -> Result<Vec<u8>, ProtocolError> {
         // Only send count (4 bytes, little-endian)
@@ -150,27 +156,22 @@ impl WriteMultipleRegisters {
     ///
     /// Returns an error if the register number is invalid, count is out of range, or range exceeds writable limit
     pub fn new(start_register_number: u16, values: Vec<i16>) -> Result<Self, ProtocolError> {
-        let count = values.len();
-        // Validate count (max 237, must be > 0)
-        if count == 0 || count > 237 {
-            return Err(ProtocolError::InvalidMessage(format!(
-                "Invalid count: {count} (must be 1-237)"
-            )));
-        }
         // Validate writable range (0-559 for writes)
         if start_register_number > 559 {
-            return Err(ProtocolError::InvalidInstance(format!(
-                "Register {start_register_number} is not writable (writable range: 0-559)"
-            )));
+            return Err(ProtocolError::InvalidInstance {
+                command: Self::command_id(),
+                instance: start_register_number,
+                valid: "0-559 (writable)".to_string(),
+            });
         }
-        let end_register = u32::from(start_register_number)
-            + u32::try_from(count)
-                .map_err(|_| ProtocolError::InvalidMessage("Count too large".to_string()))?
-            - 1;
-        if end_register > 559 {
-            return Err(ProtocolError::InvalidMessage(format!(
-                "Register range exceeds writable limit: {start_register_number}-{end_register} (max 559)"
-            )));
+        // Validate count (max 237, must be > 0) and that the range ends at register 559
+        let max = max_register_count(start_register_number, 559);
+        if values.is_empty() || values.len() > max {
+            return Err(ProtocolError::InvalidCount {
+                command: Self::command_id(),
+                got: values.len(),
+                max,
+            });
         }
         Ok(Self { start_register_number, values })
     }
@@ -187,12 +188,15 @@ impl Command for WriteMultipleRegisters {
     fn attribute(&self) -> u8 {
         0
     } // Different from 0x79 (which uses 1)
-    fn service(&self) -> u8 {
-        0x34
+    fn service(&self) -> Service {
+        Service::WritePlural
     } // Write plural data
     fn serialize(&self) -> Result<Vec<u8>, ProtocolError> {
-        let count = u32::try_from(self.values.len())
-            .map_err(|_| ProtocolError::InvalidMessage("Values count too large".to_string()))?;
+        let count = u32::try_from(self.values.len()).map_err(|_| ProtocolError::InvalidCount {
+            command: Self::command_id(),
+            got: self.values.len(),
+            max: MAX_REGISTER_COUNT,
+        })?;
         let mut payload = count.to_le_bytes().to_vec();
         for value in &self.values {
             payload.extend_from_slice(&value.to_le_bytes());
@@ -215,7 +219,10 @@ mod tests {
         assert!(ReadMultipleRegisters::new(0, 237).is_ok());
 
         // Invalid register number
-        assert!(ReadMultipleRegisters::new(1000, 1).is_err());
+        assert!(matches!(
+            ReadMultipleRegisters::new(1000, 1),
+            Err(ProtocolError::InvalidInstance { command: 0x301, instance: 1000, .. })
+        ));
 
         // Invalid count
         assert!(ReadMultipleRegisters::new(0, 0).is_err());
@@ -223,7 +230,10 @@ mod tests {
 
         // Range overflow
         assert!(ReadMultipleRegisters::new(999, 2).is_err());
-        assert!(ReadMultipleRegisters::new(500, 500).is_err());
+        assert!(matches!(
+            ReadMultipleRegisters::new(900, 200),
+            Err(ProtocolError::InvalidCount { command: 0x301, got: 200, max: 100 })
+        ));
     }
 
     #[test]
@@ -233,7 +243,7 @@ mod tests {
         assert_eq!(ReadMultipleRegisters::command_id(), 0x301);
         assert_eq!(cmd.instance(), 100);
         assert_eq!(cmd.attribute(), 0);
-        assert_eq!(cmd.service(), 0x33);
+        assert_eq!(cmd.service(), Service::ReadPlural);
     }
 
     #[test]
@@ -263,7 +273,10 @@ mod tests {
 
         // Range overflow
         assert!(WriteMultipleRegisters::new(559, vec![100, 200]).is_err());
-        assert!(WriteMultipleRegisters::new(500, vec![0; 61]).is_err()); // 500 + 61 - 1 = 560 > 559
+        assert!(matches!(
+            WriteMultipleRegisters::new(500, vec![0; 61]), // 500 + 61 - 1 = 560 > 559
+            Err(ProtocolError::InvalidCount { command: 0x301, got: 61, max: 60 })
+        ));
     }
 
     #[test]
@@ -274,7 +287,7 @@ mod tests {
         assert_eq!(WriteMultipleRegisters::command_id(), 0x301);
         assert_eq!(cmd.instance(), 100);
         assert_eq!(cmd.attribute(), 0);
-        assert_eq!(cmd.service(), 0x34);
+        assert_eq!(cmd.service(), Service::WritePlural);
     }
 
     #[test]
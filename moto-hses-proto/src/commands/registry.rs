@@ -0,0 +1,112 @@
+//! Names of the HSES commands, for traces and tools
+//!
+//! [`command_info`] names a request by its division, command and service, e.g. to label
+//! the lines of a transcript decoded from captured traffic. Robot control commands are
+//! told apart by their command ID; file control requests all use command 0 and are told
+//! apart by their service.
+//!
+//! ```
+//! use moto_hses_proto::commands::registry::command_info;
+//! use moto_hses_proto::Division;
+//!
+//! let info = command_info(Division::Robot, 0x79, 0x0E).unwrap();
+//! assert_eq!(info.name, "Register Data Reading / Writing");
+//! assert_eq!(command_info(Division::File, 0, 0x16).unwrap().name, "File Receive");
+//! ```
+
+use crate::commands::Division;
+use crate::constants::{
+    SERVICE_FILE_DELETE, SERVICE_FILE_LIST, SERVICE_FILE_RECEIVE, SERVICE_FILE_SEND,
+};
+
+/// Command known to the crate
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CommandInfo {
+    pub division: Division,
+    /// Command ID; 0 for file control requests
+    pub command: u16,
+    /// Service telling file control requests apart; `None` for robot control commands
+    pub file_service: Option<u8>,
+    pub name: &'static str,
+}
+
+const fn robot(command: u16, name: &'static str) -> CommandInfo {
+    CommandInfo { division: Division::Robot, command, file_service: None, name }
+}
+
+const fn file(service: u8, name: &'static str) -> CommandInfo {
+    CommandInfo { division: Division::File, command: 0, file_service: Some(service), name }
+}
+
+/// Commands known to the crate, in command ID order
+pub const COMMANDS: &[CommandInfo] = &[
+    robot(0x70, "Alarm Data Reading"),
+    robot(0x71, "Alarm History Reading"),
+    robot(0x72, "Status Information Reading"),
+    robot(0x73, "Executing Job Information Reading"),
+    robot(0x75, "Robot Position Data Reading"),
+    robot(0x76, "Position Error Reading"),
+    robot(0x78, "I/O Data Reading / Writing"),
+    robot(0x79, "Register Data Reading / Writing"),
+    robot(0x7A, "Byte Variable (B) Reading / Writing"),
+    robot(0x7B, "Integer Type Variable (I) Reading / Writing"),
+    robot(0x7C, "Double Precision Integer Type Variable (D) Reading / Writing"),
+    robot(0x7D, "Real Type Variable (R) Reading / Writing"),
+    robot(0x7E, "Character Type Variable (S) Reading / Writing"),
+    robot(0x7F, "Robot Position Type Variable (P) Reading / Writing"),
+    robot(0x80, "Base Position Type Variable (BP) Reading / Writing"),
+    robot(0x81, "Station Type Variable (EX) Reading / Writing"),
+    robot(0x82, "Alarm Reset / Error Cancel"),
+    robot(0x83, "Hold / Servo On/off"),
+    robot(0x84, "Step / Cycle / Continuous Switching"),
+    robot(0x86, "Start-up (Job Start)"),
+    robot(0x87, "Job Select"),
+    robot(0x88, "Management Time Acquiring"),
+    robot(0x89, "System Information Acquiring"),
+    robot(0x8A, "Move Instruction (Type Cartesian Coordinates)"),
+    robot(0x8B, "Move Instruction (Type Pulse)"),
+    robot(0x300, "Plural I/O Data Reading / Writing"),
+    robot(0x301, "Plural Register Data Reading / Writing"),
+    robot(0x302, "Plural Byte Type Variable (B) Reading / Writing"),
+    robot(0x303, "Plural Integer Type Variable (I) Reading / Writing"),
+    robot(0x304, "Plural Double Precision Integer Type Variable (D) Reading / Writing"),
+    robot(0x305, "Plural Real Type Variable (R) Reading / Writing"),
+    robot(0x306, "Plural Character Type Variable (S) Reading / Writing"),
+    file(SERVICE_FILE_DELETE, "File Delete"),
+    file(SERVICE_FILE_SEND, "File Send"),
+    file(SERVICE_FILE_RECEIVE, "File Receive"),
+    file(SERVICE_FILE_LIST, "File List Acquiring"),
+];
+
+/// Command of a request with `division`, `command` and `service`
+#[must_use]
+pub fn command_info(division: Division, command: u16, service: u8) -> Option<&'static CommandInfo> {
+    COMMANDS.iter().find(|info| {
+        info.division == division
+            && match division {
+                Division::Robot => info.command == command,
+                Division::File => info.file_service == Some(service),
+            }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_command_info_lookup() {
+        assert_eq!(
+            command_info(Division::Robot, 0x81, 0x01).map(|info| info.name),
+            Some("Station Type Variable (EX) Reading / Writing")
+        );
+        assert_eq!(
+            command_info(Division::File, 0, SERVICE_FILE_LIST).map(|info| info.name),
+            Some("File List Acquiring")
+        );
+        assert_eq!(command_info(Division::Robot, 0x74, 0x01), None);
+        assert_eq!(command_info(Division::File, 0, 0x01), None);
+        // Robot commands are not found in the file division
+        assert_eq!(command_info(Division::File, 0x79, 0x0E), None);
+    }
+}
@@ -1,6 +1,6 @@
 //! Servo related commands (0x83)
 
-use super::command_trait::Command;
+use super::command_trait::{Command, Service};
 use crate::error::ProtocolError;
 
 /// Hold/Servo On/off Command (0x83)
@@ -90,8 +90,8 @@ impl Command for HoldServoControl {
         1
     }
 
-    fn service(&self) -> u8 {
-        0x10 // Set_Attribute_Single
+    fn service(&self) -> Service {
+        Service::SetSingle
     }
 }
 
@@ -105,7 +105,7 @@ mod tests {
         let hold_on = HoldServoControl::hold_on();
         assert_eq!(hold_on.instance(), 1);
         assert_eq!(hold_on.attribute(), 1);
-        assert_eq!(hold_on.service(), 0x10);
+        assert_eq!(hold_on.service(), Service::SetSingle);
         let serialized = hold_on.serialize().unwrap();
         assert_eq!(serialized, vec![1, 0, 0, 0]);
 
@@ -1,8 +1,33 @@
 //! Status related commands (0x72)
 
-use super::command_trait::Command;
+use super::command_trait::{Command, Service};
 use crate::error::ProtocolError;
 
+/// Attributes of the status information (0x72)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatusAttribute {
+    /// Data 1 and Data 2, read with `Get_Attribute_All`
+    All = 0,
+    Data1 = 1,
+    Data2 = 2,
+}
+
+impl TryFrom<u8> for StatusAttribute {
+    type Error = ProtocolError;
+
+    /// # Errors
+    ///
+    /// Returns [`ProtocolError::InvalidAttribute`] for values other than 0-2
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Self::All),
+            1 => Ok(Self::Data1),
+            2 => Ok(Self::Data2),
+            _ => Err(ProtocolError::InvalidAttribute),
+        }
+    }
+}
+
 /// Read status command (0x72) - reads all status data
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ReadStatus;
@@ -36,11 +61,11 @@ impl Command for ReadStatus {
     }
 
     fn attribute(&self) -> u8 {
-        0 // Use 0 to get all attributes (Data 1 and Data 2) with Get_Attribute_All
+        StatusAttribute::All as u8
     }
 
-    fn service(&self) -> u8 {
-        0x01 // Get_Attribute_All
+    fn service(&self) -> Service {
+        Service::GetAll
     }
 }
 
@@ -77,11 +102,11 @@ impl Command for ReadStatusData1 {
     }
 
     fn attribute(&self) -> u8 {
-        1 // Data 1
+        StatusAttribute::Data1 as u8
     }
 
-    fn service(&self) -> u8 {
-        0x0e // Get_Attribute_Single
+    fn service(&self) -> Service {
+        Service::GetSingle
     }
 }
 
@@ -118,10 +143,10 @@ impl Command for ReadStatusData2 {
     }
 
     fn attribute(&self) -> u8 {
-        2 // Data 2
+        StatusAttribute::Data2 as u8
     }
 
-    fn service(&self) -> u8 {
-        0x0e // Get_Attribute_Single
+    fn service(&self) -> Service {
+        Service::GetSingle
     }
 }
@@ -0,0 +1,70 @@
+//! System information commands (0x89)
+
+use super::command_trait::{Command, Service};
+use crate::error::ProtocolError;
+use crate::payload::system_info::SystemInfo;
+
+/// Command for reading system information (0x89)
+///
+/// The instance selects the system: 11-18 for robots R1-R8, 21-44 for stations S1-S24
+/// and 101 for the application.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReadSystemInfo {
+    pub instance: u16,
+}
+
+impl ReadSystemInfo {
+    /// Instance of the application system information
+    pub const APPLICATION: u16 = 101;
+
+    #[must_use]
+    pub const fn new(instance: u16) -> Self {
+        Self { instance }
+    }
+
+    /// System information of robot `robot` (1-8)
+    #[must_use]
+    pub fn robot(robot: u8) -> Self {
+        Self::new(10 + u16::from(robot))
+    }
+}
+
+impl Command for ReadSystemInfo {
+    type Response = SystemInfo;
+
+    fn command_id() -> u16 {
+        0x89
+    }
+
+    fn serialize(&self) -> Result<Vec<u8>, ProtocolError> {
+        Ok(vec![])
+    }
+
+    fn instance(&self) -> u16 {
+        self.instance
+    }
+
+    fn attribute(&self) -> u8 {
+        0 // Fixed to 0(All attributes)
+    }
+
+    fn service(&self) -> Service {
+        Service::GetAll
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_system_info_command() {
+        let command = ReadSystemInfo::robot(1);
+        assert_eq!(ReadSystemInfo::command_id(), 0x89);
+        assert_eq!(command.instance(), 11);
+        assert_eq!(command.attribute(), 0);
+        assert_eq!(command.service(), Service::GetAll);
+        assert!(command.serialize().is_ok_and(|payload| payload.is_empty()));
+        assert_eq!(ReadSystemInfo::new(ReadSystemInfo::APPLICATION).instance(), 101);
+    }
+}
@@ -1,11 +1,95 @@
 //! Variable command definitions for HSES protocol
 
-use crate::{HsesPayload, commands::Command, error::ProtocolError};
+use crate::{
+    BasePosition, HsesPayload, Position, PulsePosition, StationPosition,
+    commands::{Command, Service},
+    error::ProtocolError,
+};
 use std::marker::PhantomData;
 
-/// Trait for variable command IDs
+/// Command and services used to read and write a value with [`ReadVariable`] and
+/// [`WriteVariable`]
+///
+/// Implemented for the scalar variable types (B: `u8`, I: `i16`, D: `i32`, R: `f32`,
+/// S: `String`) and for the robot, base position and station variables (P: [`Position`],
+/// BP: [`BasePosition`], EX: [`StationPosition`]). Applications can map their own [`HsesPayload`] types to a command,
+/// e.g. a struct packing consecutive variables read and written in one plural request; the
+/// default services and attribute are those of the single variable commands.
+///
+/// ```
+/// use moto_hses_proto::{
+///     HsesPayload, ProtocolError, ReadVariable, Service, TextEncoding, VariableCommandId,
+/// };
+///
+/// /// Gripper settings kept in I variables n to n+3
+/// #[derive(Debug, Clone, PartialEq)]
+/// struct GripperSettings([i16; 4]);
+///
+/// impl VariableCommandId for GripperSettings {
+///     fn command_id() -> u16 {
+///         0x303 // Plural I variables
+///     }
+///     fn read_service() -> Service {
+///         Service::ReadPlural
+///     }
+///     fn write_service() -> Service {
+///         Service::WritePlural
+///     }
+///     fn read_request() -> Vec<u8> {
+///         4u32.to_le_bytes().to_vec()
+///     }
+/// }
+///
+/// // Plural data starts with the number of variables
+/// impl HsesPayload for GripperSettings {
+///     fn serialize(&self, _: TextEncoding) -> Result<Vec<u8>, ProtocolError> {
+///         let mut data = 4u32.to_le_bytes().to_vec();
+///         data.extend(self.0.iter().flat_map(|value| value.to_le_bytes()));
+///         Ok(data)
+///     }
+///     fn deserialize(data: &[u8], _: TextEncoding) -> Result<Self, ProtocolError> {
+///         if data.len() != 12 {
+///             return Err(ProtocolError::UnexpectedLength { expected: 12, got: data.len() });
+///         }
+///         let mut values = [0; 4];
+///         for (value, bytes) in values.iter_mut().zip(data[4..].chunks_exact(2)) {
+///             *value = i16::from_le_bytes([bytes[0], bytes[1]]);
+///         }
+///         Ok(Self(values))
+///     }
+/// }
+///
+/// let read = ReadVariable::<GripperSettings>::new(10);
+/// assert_eq!(moto_hses_proto::Command::serialize(&read)?, 4u32.to_le_bytes());
+/// # Ok::<(), ProtocolError>(())
+/// ```
 pub trait VariableCommandId {
+    /// Command number
     fn command_id() -> u16;
+
+    /// Attribute of read and write requests (default: 0)
+    #[must_use]
+    fn attribute() -> u8 {
+        0
+    }
+
+    /// Service of read requests (default: `Get_Attribute_Single`)
+    #[must_use]
+    fn read_service() -> Service {
+        Service::GetSingle
+    }
+
+    /// Service of write requests (default: `Set_Attribute_Single`)
+    #[must_use]
+    fn write_service() -> Service {
+        Service::SetSingle
+    }
+
+    /// Data part of read requests, e.g. the count of a plural read (default: empty)
+    #[must_use]
+    fn read_request() -> Vec<u8> {
+        Vec::new()
+    }
 }
 
 impl VariableCommandId for u8 {
@@ -38,6 +122,51 @@ impl VariableCommandId for String {
     }
 }
 
+// The whole position variable, i.e. all data words
+impl VariableCommandId for Position {
+    fn command_id() -> u16 {
+        0x7f
+    }
+
+    fn read_service() -> Service {
+        Service::GetAll
+    }
+
+    fn write_service() -> Service {
+        Service::SetAll
+    }
+}
+
+// The whole base position variable
+impl VariableCommandId for BasePosition {
+    fn command_id() -> u16 {
+        0x80
+    }
+
+    fn read_service() -> Service {
+        Service::GetAll
+    }
+
+    fn write_service() -> Service {
+        Service::SetAll
+    }
+}
+
+// The whole station variable
+impl VariableCommandId for StationPosition {
+    fn command_id() -> u16 {
+        0x81
+    }
+
+    fn read_service() -> Service {
+        Service::GetAll
+    }
+
+    fn write_service() -> Service {
+        Service::SetAll
+    }
+}
+
 /// Command ID and validation for multiple variable operations
 pub trait MultipleVariableCommandId {
     /// Returns the command ID for multiple variable operations (0x302-0x306)
@@ -68,14 +197,11 @@ impl MultipleVariableCommandId for u8 {
     }
     fn validate_count(count: u32) -> Result<(), ProtocolError> {
         if count == 0 || count > Self::max_count() {
-            return Err(ProtocolError::InvalidMessage(format!(
-                "Invalid count: {count} (must be 1-{})",
-                Self::max_count()
-            )));
+            return Err(invalid_count::<Self>(count as usize));
         }
         if !count.is_multiple_of(2) {
             return Err(ProtocolError::InvalidMessage(format!(
-                "Count must be multiple of 2: {count}"
+                "B variable count must be a multiple of 2: {count}"
             )));
         }
         Ok(())
@@ -94,10 +220,7 @@ impl MultipleVariableCommandId for i16 {
     }
     fn validate_count(count: u32) -> Result<(), ProtocolError> {
         if count == 0 || count > Self::max_count() {
-            return Err(ProtocolError::InvalidMessage(format!(
-                "Invalid count: {count} (must be 1-{})",
-                Self::max_count()
-            )));
+            return Err(invalid_count::<Self>(count as usize));
         }
         Ok(())
     }
@@ -115,10 +238,7 @@ impl MultipleVariableCommandId for i32 {
     }
     fn validate_count(count: u32) -> Result<(), ProtocolError> {
         if count == 0 || count > Self::max_count() {
-            return Err(ProtocolError::InvalidMessage(format!(
-                "Invalid count: {count} (must be 1-{})",
-                Self::max_count()
-            )));
+            return Err(invalid_count::<Self>(count as usize));
         }
         Ok(())
     }
@@ -136,10 +256,7 @@ impl MultipleVariableCommandId for f32 {
     }
     fn validate_count(count: u32) -> Result<(), ProtocolError> {
         if count == 0 || count > Self::max_count() {
-            return Err(ProtocolError::InvalidMessage(format!(
-                "Invalid count: {count} (must be 1-{})",
-                Self::max_count()
-            )));
+            return Err(invalid_count::<Self>(count as usize));
         }
         Ok(())
     }
@@ -158,15 +275,28 @@ impl MultipleVariableCommandId for String {
     }
     fn validate_count(count: u32) -> Result<(), ProtocolError> {
         if count == 0 || count > Self::max_count() {
-            return Err(ProtocolError::InvalidMessage(format!(
-                "Invalid count: {count} (must be 1-{})",
-                Self::max_count()
-            )));
+            return Err(invalid_count::<Self>(count as usize));
         }
         Ok(())
     }
 }
 
+fn invalid_count<T: MultipleVariableCommandId>(count: usize) -> ProtocolError {
+    ProtocolError::InvalidCount {
+        command: T::multiple_command_id(),
+        got: count,
+        max: T::max_count() as usize,
+    }
+}
+
+// Count of a plural write, checked against the range of the variable type
+fn value_count<T: MultipleVariableCommandId>(len: usize) -> Result<u32, ProtocolError> {
+    match u32::try_from(len) {
+        Ok(count) if count != 0 && count <= T::max_count() => Ok(count),
+        _ => Err(invalid_count::<T>(len)),
+    }
+}
+
 /// Trait for deserializing multiple variable responses
 pub trait MultipleVariableResponse: Sized + MultipleVariableCommandId {
     /// Parse a single element from byte slice at given offset
@@ -206,10 +336,10 @@ pub trait MultipleVariableResponse: Sized + MultipleVariableCommandId {
         let element_size = Self::element_size();
         let expected_len = 4 + (expected_count as usize * element_size);
         if data.len() != expected_len {
-            return Err(ProtocolError::Deserialization(format!(
-                "Invalid response length: got {} bytes, expected {expected_len}",
-                data.len()
-            )));
+            return Err(ProtocolError::UnexpectedLength {
+                expected: expected_len,
+                got: data.len(),
+            });
         }
 
         // Parse elements
@@ -309,8 +439,8 @@ impl<T: MultipleVariableCommandId + PartialEq> Command for ReadMultipleVariables
     fn attribute(&self) -> u8 {
         0 // Fixed to 0 for plural commands
     }
-    fn service(&self) -> u8 {
-        0x33 // Read plural data
+    fn service(&self) -> Service {
+        Service::ReadPlural
     }
     fn serialize(&self) -> Result<Vec<u8>, ProtocolError> {
         Ok(self.count.to_le_bytes().to_vec())
@@ -340,9 +470,7 @@ impl<T: MultipleVariableCommandId + PartialEq + Clone + HsesPayload> WriteMultip
     /// # Errors
     /// Returns an error if count validation fails
     pub fn new(start_variable_number: u16, values: Vec<T>) -> Result<Self, ProtocolError> {
-        let count = u32::try_from(values.len()).map_err(|_| {
-            ProtocolError::InvalidMessage(format!("Values count {} exceeds u32::MAX", values.len()))
-        })?;
+        let count = u32::try_from(values.len()).map_err(|_| invalid_count::<T>(values.len()))?;
         T::validate_count(count)?;
         Ok(Self { start_variable_number, values })
     }
@@ -360,24 +488,11 @@ impl Command for WriteMultipleVariables<u8> {
     fn attribute(&self) -> u8 {
         0
     }
-    fn service(&self) -> u8 {
-        0x34
+    fn service(&self) -> Service {
+        Service::WritePlural
     }
     fn serialize(&self) -> Result<Vec<u8>, ProtocolError> {
-        let count = u32::try_from(self.values.len()).map_err(|_| {
-            ProtocolError::InvalidMessage(format!(
-                "Values count {} exceeds u32::MAX",
-                self.values.len()
-            ))
-        })?;
-
-        // Validate count using the type's max_count
-        if count == 0 || count > u8::max_count() {
-            return Err(ProtocolError::InvalidMessage(format!(
-                "Invalid count: {count} (must be 1-{})",
-                u8::max_count()
-            )));
-        }
+        let count = value_count::<u8>(self.values.len())?;
 
         let mut payload = count.to_le_bytes().to_vec();
         let serialized_values = self.values.serialize(crate::encoding::TextEncoding::Utf8)?;
@@ -397,24 +512,11 @@ impl Command for WriteMultipleVariables<i16> {
     fn attribute(&self) -> u8 {
         0
     }
-    fn service(&self) -> u8 {
-        0x34
+    fn service(&self) -> Service {
+        Service::WritePlural
     }
     fn serialize(&self) -> Result<Vec<u8>, ProtocolError> {
-        let count = u32::try_from(self.values.len()).map_err(|_| {
-            ProtocolError::InvalidMessage(format!(
-                "Values count {} exceeds u32::MAX",
-                self.values.len()
-            ))
-        })?;
-
-        // Validate count using the type's max_count
-        if count == 0 || count > i16::max_count() {
-            return Err(ProtocolError::InvalidMessage(format!(
-                "Invalid count: {count} (must be 1-{})",
-                i16::max_count()
-            )));
-        }
+        let count = value_count::<i16>(self.values.len())?;
 
         let mut payload = count.to_le_bytes().to_vec();
         let serialized_values = self.values.serialize(crate::encoding::TextEncoding::Utf8)?;
@@ -434,24 +536,11 @@ impl Command for WriteMultipleVariables<i32> {
     fn attribute(&self) -> u8 {
         0
     }
-    fn service(&self) -> u8 {
-        0x34
+    fn service(&self) -> Service {
+        Service::WritePlural
     }
     fn serialize(&self) -> Result<Vec<u8>, ProtocolError> {
-        let count = u32::try_from(self.values.len()).map_err(|_| {
-            ProtocolError::InvalidMessage(format!(
-                "Values count {} exceeds u32::MAX",
-                self.values.len()
-            ))
-        })?;
-
-        // Validate count using the type's max_count
-        if count == 0 || count > i32::max_count() {
-            return Err(ProtocolError::InvalidMessage(format!(
-                "Invalid count: {count} (must be 1-{})",
-                i32::max_count()
-            )));
-        }
+        let count = value_count::<i32>(self.values.len())?;
 
         let mut payload = count.to_le_bytes().to_vec();
         let serialized_values = self.values.serialize(crate::encoding::TextEncoding::Utf8)?;
@@ -471,24 +560,11 @@ impl Command for WriteMultipleVariables<f32> {
     fn attribute(&self) -> u8 {
         0
     }
-    fn service(&self) -> u8 {
-        0x34
+    fn service(&self) -> Service {
+        Service::WritePlural
     }
     fn serialize(&self) -> Result<Vec<u8>, ProtocolError> {
-        let count = u32::try_from(self.values.len()).map_err(|_| {
-            ProtocolError::InvalidMessage(format!(
-                "Values count {} exceeds u32::MAX",
-                self.values.len()
-            ))
-        })?;
-
-        // Validate count using the type's max_count
-        if count == 0 || count > f32::max_count() {
-            return Err(ProtocolError::InvalidMessage(format!(
-                "Invalid count: {count} (must be 1-{})",
-                f32::max_count()
-            )));
-        }
+        let count = value_count::<f32>(self.values.len())?;
 
         let mut payload = count.to_le_bytes().to_vec();
         let serialized_values = self.values.serialize(crate::encoding::TextEncoding::Utf8)?;
@@ -508,24 +584,11 @@ impl Command for WriteMultipleStringVariables {
     fn attribute(&self) -> u8 {
         0
     }
-    fn service(&self) -> u8 {
-        0x34
+    fn service(&self) -> Service {
+        Service::WritePlural
     }
     fn serialize(&self) -> Result<Vec<u8>, ProtocolError> {
-        let count = u32::try_from(self.values.len()).map_err(|_| {
-            ProtocolError::InvalidMessage(format!(
-                "Values count {} exceeds u32::MAX",
-                self.values.len()
-            ))
-        })?;
-
-        // Validate count for String variables (1-29)
-        if count == 0 || count > String::max_count() {
-            return Err(ProtocolError::InvalidMessage(format!(
-                "Invalid count: {count} (must be 1-{})",
-                String::max_count()
-            )));
-        }
+        let count = value_count::<String>(self.values.len())?;
 
         let mut payload = count.to_le_bytes().to_vec();
         let serialized_values = self.values.serialize(self.text_encoding)?;
@@ -542,6 +605,14 @@ pub struct ReadVariable<T: HsesPayload + VariableCommandId + PartialEq> {
     pub _phantom: PhantomData<T>,
 }
 
+impl<T: HsesPayload + VariableCommandId + PartialEq> ReadVariable<T> {
+    /// Read of variable `index`
+    #[must_use]
+    pub const fn new(index: u16) -> Self {
+        Self { index, _phantom: PhantomData }
+    }
+}
+
 impl<T: HsesPayload + VariableCommandId + PartialEq> Command for ReadVariable<T> {
     type Response = T;
     fn command_id() -> u16 {
@@ -551,13 +622,13 @@ impl<T: HsesPayload + VariableCommandId + PartialEq> Command for ReadVariable<T>
         self.index
     }
     fn attribute(&self) -> u8 {
-        0
+        T::attribute()
     }
-    fn service(&self) -> u8 {
-        0x0E // Get_Attribute_Single
+    fn service(&self) -> Service {
+        T::read_service()
     }
     fn serialize(&self) -> Result<Vec<u8>, ProtocolError> {
-        Ok(vec![])
+        Ok(T::read_request())
     }
 }
 
@@ -586,16 +657,54 @@ impl<T: HsesPayload + VariableCommandId + PartialEq> Command for WriteVariable<T
         self.index
     }
     fn attribute(&self) -> u8 {
-        0
+        T::attribute()
     }
-    fn service(&self) -> u8 {
-        0x10 // Set_Attribute_Single
+    fn service(&self) -> Service {
+        T::write_service()
     }
     fn serialize(&self) -> Result<Vec<u8>, ProtocolError> {
         self.value.serialize(crate::encoding::TextEncoding::Utf8)
     }
 }
 
+/// Size of the data of a robot position variable (P): 5 header words and 8 axes
+pub const POSITION_VARIABLE_SIZE: usize = 52;
+
+/// Number of axes of a robot position variable (P)
+pub const POSITION_VARIABLE_AXES: usize = 8;
+
+/// Read robot position variable command (P, 0x7F)
+pub type ReadPositionVar = ReadVariable<Position>;
+
+/// Read base position variable command (BP, 0x80)
+pub type ReadBasePositionVar = ReadVariable<BasePosition>;
+
+/// Write base position variable command (BP, 0x80)
+pub type WriteBasePositionVar = WriteVariable<BasePosition>;
+
+/// Read station variable command (EX, 0x81)
+pub type ReadStationVar = ReadVariable<StationPosition>;
+
+/// Write station variable command (EX, 0x81)
+pub type WriteStationVar = WriteVariable<StationPosition>;
+
+/// Write robot position variable command (P, 0x7F)
+///
+/// The variable always holds 8 axes, so the missing axes of a pulse position are sent as 0.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WritePositionVar {
+    pub index: u16, // Support extended variable settings (0-999)
+    pub value: Position,
+}
+
+impl WritePositionVar {
+    /// Write of `value` to variable `index`
+    #[must_use]
+    pub const fn new(index: u16, value: Position) -> Self {
+        Self { index, value }
+    }
+}
+
 impl Command for WriteStringVar {
     type Response = ();
     fn command_id() -> u16 {
@@ -607,14 +716,49 @@ impl Command for WriteStringVar {
     fn attribute(&self) -> u8 {
         0
     }
-    fn service(&self) -> u8 {
-        0x10 // Set_Attribute_Single
+    fn service(&self) -> Service {
+        Service::SetSingle
     }
     fn serialize(&self) -> Result<Vec<u8>, ProtocolError> {
         self.value.serialize(self.text_encoding)
     }
 }
 
+impl Command for WritePositionVar {
+    type Response = ();
+    fn command_id() -> u16 {
+        Position::command_id()
+    }
+    fn instance(&self) -> u16 {
+        self.index
+    }
+    fn attribute(&self) -> u8 {
+        0
+    }
+    fn service(&self) -> Service {
+        Position::write_service()
+    }
+    fn serialize(&self) -> Result<Vec<u8>, ProtocolError> {
+        let value = match &self.value {
+            Position::Pulse(pulse) if pulse.joints.len() > POSITION_VARIABLE_AXES => {
+                return Err(ProtocolError::PositionError(format!(
+                    "{} axes, at most {POSITION_VARIABLE_AXES} fit a position variable",
+                    pulse.joints.len()
+                )));
+            }
+            Position::Pulse(pulse) => {
+                let mut joints = pulse.joints.clone();
+                joints.resize(POSITION_VARIABLE_AXES, 0);
+                Position::Pulse(PulsePosition::new(joints))
+            }
+            Position::Cartesian(_) => self.value.clone(),
+        };
+        let mut data = value.serialize(crate::encoding::TextEncoding::Utf8)?;
+        data.resize(POSITION_VARIABLE_SIZE, 0);
+        Ok(data)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -641,6 +785,35 @@ mod tests {
         assert_eq!(i32::command_id(), 0x7c);
         assert_eq!(f32::command_id(), 0x7d);
         assert_eq!(String::command_id(), 0x7e);
+        assert_eq!(Position::command_id(), 0x7f);
+        assert_eq!(Position::read_service(), Service::GetAll);
+        assert_eq!(Position::write_service(), Service::SetAll);
+        assert_eq!(BasePosition::command_id(), 0x80);
+        assert_eq!(BasePosition::read_service(), Service::GetAll);
+        assert_eq!(BasePosition::write_service(), Service::SetAll);
+        assert_eq!(StationPosition::command_id(), 0x81);
+        assert_eq!(StationPosition::read_service(), Service::GetAll);
+        assert_eq!(StationPosition::write_service(), Service::SetAll);
+    }
+
+    #[allow(clippy::unwrap_used)]
+    #[test]
+    fn test_write_position_variable() {
+        let pulse = Position::Pulse(PulsePosition::new(vec![1000, -2000, 0, 0, 0, 3000]));
+        let cmd = WritePositionVar::new(3, pulse);
+        assert_eq!(WritePositionVar::command_id(), 0x7f);
+        assert_eq!((cmd.instance(), cmd.attribute(), cmd.service()), (3, 0, Service::SetAll));
+        let data = cmd.serialize().unwrap();
+        assert_eq!(data.len(), POSITION_VARIABLE_SIZE);
+        assert_eq!(&data[20..24], &1000i32.to_le_bytes());
+        assert_eq!(&data[44..52], &[0; 8]);
+
+        let read = Position::deserialize(&data, crate::encoding::TextEncoding::Utf8).unwrap();
+        let joints = vec![1000, -2000, 0, 0, 0, 3000, 0, 0];
+        assert_eq!(read, Position::Pulse(PulsePosition::new(joints)));
+
+        let nine_axes = Position::Pulse(PulsePosition::new(vec![0; 9]));
+        assert!(WritePositionVar::new(3, nine_axes).serialize().is_err());
     }
 
     #[test]
@@ -649,7 +822,7 @@ mod tests {
         assert_eq!(ReadVariable::<u8>::command_id(), 0x7a);
         assert_eq!(cmd.instance(), 5);
         assert_eq!(cmd.attribute(), 0);
-        assert_eq!(cmd.service(), 0x0E);
+        assert_eq!(cmd.service(), Service::GetSingle);
     }
 
     #[test]
@@ -658,7 +831,7 @@ mod tests {
         assert_eq!(WriteVariable::<u8>::command_id(), 0x7a);
         assert_eq!(cmd.instance(), 5);
         assert_eq!(cmd.attribute(), 0);
-        assert_eq!(cmd.service(), 0x10);
+        assert_eq!(cmd.service(), Service::SetSingle);
     }
 
     #[test]
@@ -669,6 +842,28 @@ mod tests {
         assert_eq!(serialized, vec![100]);
     }
 
+    #[test]
+    #[allow(clippy::expect_used)]
+    fn test_scalar_variable_wire_sizes() {
+        // B: 1 byte, I: 2 bytes, D: 4 bytes, R: 4 bytes
+        fn wire_size<
+            T: HsesPayload + VariableCommandId + MultipleVariableCommandId + PartialEq + Default,
+        >() -> usize {
+            let data = WriteVariable::<T> { index: 0, value: T::default() }
+                .serialize()
+                .expect("Serialization should not fail");
+            assert_eq!(data.len(), T::element_size());
+            T::deserialize(&data, crate::encoding::TextEncoding::Utf8)
+                .expect("Deserialization should not fail");
+            data.len()
+        }
+
+        assert_eq!(wire_size::<u8>(), 1);
+        assert_eq!(wire_size::<i16>(), 2);
+        assert_eq!(wire_size::<i32>(), 4);
+        assert_eq!(wire_size::<f32>(), 4);
+    }
+
     #[test]
     fn test_multiple_variable_command_ids() {
         assert_eq!(u8::multiple_command_id(), 0x302);
@@ -717,7 +912,10 @@ mod tests {
         assert!(i16::validate_count(238).is_err());
         assert!(i32::validate_count(119).is_err());
         assert!(f32::validate_count(119).is_err());
-        assert!(String::validate_count(30).is_err());
+        assert!(matches!(
+            String::validate_count(30),
+            Err(ProtocolError::InvalidCount { command: 0x306, got: 30, max: 29 })
+        ));
 
         // Invalid count for u8 - not multiple of 2
         assert!(u8::validate_count(3).is_err());
@@ -747,7 +945,7 @@ mod tests {
         assert_eq!(ReadMultipleVariables::<u8>::command_id(), 0x302);
         assert_eq!(cmd.instance(), 10);
         assert_eq!(cmd.attribute(), 0);
-        assert_eq!(cmd.service(), 0x33);
+        assert_eq!(cmd.service(), Service::ReadPlural);
     }
 
     #[test]
@@ -788,7 +986,7 @@ mod tests {
         assert_eq!(WriteMultipleVariables::<u8>::command_id(), 0x302);
         assert_eq!(cmd.instance(), 10);
         assert_eq!(cmd.attribute(), 0);
-        assert_eq!(cmd.service(), 0x34);
+        assert_eq!(cmd.service(), Service::WritePlural);
     }
 
     #[test]
@@ -874,9 +1072,10 @@ mod tests {
 
         // Test error cases
         let short_data = vec![1, 0, 0, 0]; // count = 1, but no data
-        assert!(
-            u8::deserialize_multiple(&short_data, 1, crate::encoding::TextEncoding::Utf8).is_err()
-        );
+        assert!(matches!(
+            u8::deserialize_multiple(&short_data, 1, crate::encoding::TextEncoding::Utf8),
+            Err(ProtocolError::UnexpectedLength { expected: 5, got: 4 })
+        ));
 
         let wrong_count_data = vec![2, 0, 0, 0, 1]; // count = 2, but only 1 value
         assert!(
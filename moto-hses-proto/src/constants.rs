@@ -1,7 +1,142 @@
 //! HSES protocol constants
+//!
+//! Port numbers, service codes and the layout of the 32-byte frame header, for code that
+//! builds or inspects frames without going through [`HsesRequestMessage`] and
+//! [`HsesResponseMessage`](crate::HsesResponseMessage), e.g. packet filters and mocks.
+//!
+//! ```
+//! use moto_hses_proto::constants::{self, header};
+//! use moto_hses_proto::{Division, HsesRequestMessage};
+//!
+//! let message = HsesRequestMessage::new(
+//!     Division::Robot as u8, 0, 7, 0x7A, 1, 0, constants::SERVICE_GET_ATTRIBUTE_ALL, vec![],
+//! )
+//! .unwrap();
+//! let frame = message.encode();
+//! assert_eq!(&frame[header::MAGIC_OFFSET..][..4], header::MAGIC);
+//! assert_eq!(frame[header::DIVISION_OFFSET], Division::Robot as u8);
+//! assert_eq!(frame[header::REQUEST_ID_OFFSET], 7);
+//! assert_eq!(frame[header::REQUEST_SERVICE_OFFSET], constants::SERVICE_GET_ATTRIBUTE_ALL);
+//! assert_eq!(frame.len(), header::SIZE);
+//! ```
+//!
+//! [`HsesRequestMessage`]: crate::HsesRequestMessage
+
+pub use crate::commands::{Division, Service};
 
 /// Robot control port for HSES protocol
 pub const ROBOT_CONTROL_PORT: u16 = 10040;
 
 /// File control port for HSES protocol
 pub const FILE_CONTROL_PORT: u16 = 10041;
+
+/// Largest payload of a single HSES datagram [bytes]
+///
+/// Controllers reject longer requests. File data beyond it is transferred in numbered
+/// blocks of at most this size, in both directions.
+pub const MAX_PAYLOAD_SIZE: usize = 479;
+
+/// Largest HSES datagram: the header and [`MAX_PAYLOAD_SIZE`] bytes of payload
+pub const MAX_DATAGRAM_SIZE: usize = header::SIZE + MAX_PAYLOAD_SIZE;
+
+/// `Get_Attribute_All`: read every attribute of an instance
+pub const SERVICE_GET_ATTRIBUTE_ALL: u8 = 0x01;
+
+/// `Set_Attribute_All`: write every attribute of an instance
+pub const SERVICE_SET_ATTRIBUTE_ALL: u8 = 0x02;
+
+/// `Get_Attribute_Single`: read one attribute of an instance
+pub const SERVICE_GET_ATTRIBUTE_SINGLE: u8 = 0x0E;
+
+/// `Set_Attribute_Single`: write one attribute of an instance
+pub const SERVICE_SET_ATTRIBUTE_SINGLE: u8 = 0x10;
+
+/// Read a run of consecutive instances (plural commands 0x300-0x306)
+pub const SERVICE_READ_PLURAL: u8 = 0x33;
+
+/// Write a run of consecutive instances (plural commands 0x300-0x306)
+pub const SERVICE_WRITE_PLURAL: u8 = 0x34;
+
+/// File division: delete a file
+pub const SERVICE_FILE_DELETE: u8 = 0x09;
+
+/// File division: send a file to the controller
+pub const SERVICE_FILE_SEND: u8 = 0x15;
+
+/// File division: receive a file from the controller
+pub const SERVICE_FILE_RECEIVE: u8 = 0x16;
+
+/// File division: read the list of files
+pub const SERVICE_FILE_LIST: u8 = 0x32;
+
+/// Added to the request service code in the service field of a response
+///
+/// ```
+/// use moto_hses_proto::constants::{RESPONSE_SERVICE_FLAG, SERVICE_READ_PLURAL};
+///
+/// assert_eq!(SERVICE_READ_PLURAL | RESPONSE_SERVICE_FLAG, 0xB3);
+/// ```
+pub const RESPONSE_SERVICE_FLAG: u8 = 0x80;
+
+/// Layout of the frame header: the 24-byte common header followed by the 8-byte request
+/// or response sub-header
+///
+/// Offsets are in bytes from the start of the frame; multi-byte fields are little endian.
+pub mod header {
+    /// Size of the whole header; the payload starts here
+    pub const SIZE: usize = 32;
+
+    /// Size of the common header shared by requests and responses
+    pub const COMMON_SIZE: usize = 24;
+
+    /// Identifier at the start of every frame
+    pub const MAGIC: &[u8; 4] = b"YERC";
+
+    /// Offset of [`MAGIC`]
+    pub const MAGIC_OFFSET: usize = 0;
+
+    /// Offset of the header size (u16, always 32)
+    pub const HEADER_SIZE_OFFSET: usize = 4;
+
+    /// Offset of the payload size (u16)
+    pub const PAYLOAD_SIZE_OFFSET: usize = 6;
+
+    /// Offset of the division, see [`Division`](super::Division)
+    pub const DIVISION_OFFSET: usize = 9;
+
+    /// Offset of the ACK flag: 0 in requests, 1 in responses
+    pub const ACK_OFFSET: usize = 10;
+
+    /// Offset of the request ID echoed by the response
+    pub const REQUEST_ID_OFFSET: usize = 11;
+
+    /// Offset of the block number (u32); bit 31 marks the last block
+    pub const BLOCK_NUMBER_OFFSET: usize = 12;
+
+    /// Block number bit marking the last block of a transfer
+    pub const LAST_BLOCK_FLAG: u32 = 0x8000_0000;
+
+    /// Offset of the command number (u16) in a request
+    pub const COMMAND_OFFSET: usize = 24;
+
+    /// Offset of the instance (u16) in a request
+    pub const INSTANCE_OFFSET: usize = 26;
+
+    /// Offset of the attribute in a request
+    pub const ATTRIBUTE_OFFSET: usize = 28;
+
+    /// Offset of the service code in a request
+    pub const REQUEST_SERVICE_OFFSET: usize = 29;
+
+    /// Offset of the service code in a response
+    pub const RESPONSE_SERVICE_OFFSET: usize = 24;
+
+    /// Offset of the status in a response: 0 on success
+    pub const STATUS_OFFSET: usize = 25;
+
+    /// Offset of the size of the added status in a response
+    pub const ADDED_STATUS_SIZE_OFFSET: usize = 26;
+
+    /// Offset of the added status (u16) in a response
+    pub const ADDED_STATUS_OFFSET: usize = 28;
+}
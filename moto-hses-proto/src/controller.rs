@@ -0,0 +1,70 @@
+//! Controller model definitions
+
+/// Robot controller models supporting HSES
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ControllerModel {
+    DX100,
+    FS100,
+    DX200,
+    YRC1000,
+    /// `YRC1000micro` (default, verified model)
+    #[default]
+    YRC1000micro,
+}
+
+impl ControllerModel {
+    /// File name rules of the controller
+    ///
+    /// Every supported model accepts job and data file names of up to 32 bytes, so this is
+    /// [`FileNameRules::Long`]; 8.3 names are only enforced when chosen explicitly.
+    #[must_use]
+    pub const fn file_name_rules(&self) -> FileNameRules {
+        FileNameRules::Long
+    }
+}
+
+/// File name rules checked before files are sent to or deleted on a controller
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FileNameRules {
+    /// Names of up to 32 bytes in the configured text encoding (default)
+    #[default]
+    Long,
+    /// 8.3 names of ASCII letters, digits, `_` and `-`, for setups known to be limited to them
+    Short,
+}
+
+impl FileNameRules {
+    /// Maximum length of a file name without extension in bytes
+    #[must_use]
+    pub const fn max_stem_len(self) -> usize {
+        match self {
+            Self::Long => 32,
+            Self::Short => 8,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_model() {
+        assert_eq!(ControllerModel::default(), ControllerModel::YRC1000micro);
+    }
+
+    #[test]
+    fn test_file_name_rules() {
+        for model in [
+            ControllerModel::DX100,
+            ControllerModel::FS100,
+            ControllerModel::DX200,
+            ControllerModel::YRC1000,
+            ControllerModel::YRC1000micro,
+        ] {
+            assert_eq!(model.file_name_rules(), FileNameRules::Long, "{model:?}");
+        }
+        assert_eq!(FileNameRules::Long.max_stem_len(), 32);
+        assert_eq!(FileNameRules::Short.max_stem_len(), 8);
+    }
+}
@@ -3,20 +3,15 @@
 use encoding_rs::{Encoding, SHIFT_JIS, UTF_8};
 
 /// Supported text encodings for HSES protocol
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum TextEncoding {
     /// UTF-8 encoding (default)
+    #[default]
     Utf8,
     /// `Shift_JIS` encoding (common for Japanese)
     ShiftJis,
 }
 
-impl Default for TextEncoding {
-    fn default() -> Self {
-        Self::Utf8
-    }
-}
-
 impl TextEncoding {
     /// Get the corresponding `encoding_rs::Encoding`
     #[must_use]
@@ -12,13 +12,28 @@ use crate::encoding::TextEncoding;
 /// The decoded string. If the specified encoding fails, falls back to UTF-8 lossy decoding.
 #[must_use]
 pub fn decode_string_with_fallback(bytes: &[u8], encoding: TextEncoding) -> String {
+    decode_string_with_replacements(bytes, encoding).0
+}
+
+/// Decode bytes to string with specified encoding, reporting whether the fallback was used
+///
+/// # Arguments
+/// * `bytes` - The byte slice to decode
+/// * `encoding` - The text encoding to use for decoding
+///
+/// # Returns
+/// A tuple of the decoded string and a flag that is `true` when the bytes were not valid
+/// in the specified encoding. In that case the string is the UTF-8 lossy decoding of the
+/// bytes and may contain replacement characters.
+#[must_use]
+pub fn decode_string_with_replacements(bytes: &[u8], encoding: TextEncoding) -> (String, bool) {
     let (decoded, _encoding_used, had_errors) = encoding.to_encoding().decode(bytes);
 
     if had_errors {
         // If specified encoding decoding had errors, fallback to UTF-8
-        String::from_utf8_lossy(bytes).to_string()
+        (String::from_utf8_lossy(bytes).to_string(), true)
     } else {
-        decoded.to_string()
+        (decoded.to_string(), false)
     }
 }
 
@@ -80,6 +95,25 @@ mod tests {
         assert_eq!(result, ""); // UTF-8 lossy fallback
     }
 
+    #[test]
+    fn test_decode_string_with_replacements_clean() {
+        // "テスト" in Shift_JIS encoding
+        let bytes = &[0x83, 0x65, 0x83, 0x58, 0x83, 0x67];
+        let (result, had_replacements) =
+            decode_string_with_replacements(bytes, TextEncoding::ShiftJis);
+        assert_eq!(result, "テスト");
+        assert!(!had_replacements);
+    }
+
+    #[test]
+    fn test_decode_string_with_replacements_wrong_encoding() {
+        // "テスト" in Shift_JIS encoding decoded as UTF-8
+        let bytes = &[0x83, 0x65, 0x83, 0x58, 0x83, 0x67];
+        let (result, had_replacements) = decode_string_with_replacements(bytes, TextEncoding::Utf8);
+        assert!(had_replacements);
+        assert!(result.contains('\u{FFFD}'));
+    }
+
     #[test]
     fn test_encode_string_utf8() {
         let string = "Hello World";
@@ -24,11 +24,26 @@ pub enum ProtocolError {
     PositionError(String),
     #[error("file operation error: {0}")]
     FileError(String),
+    /// File name rejected by the controller's file name constraints
+    #[error("invalid file name: {0}")]
+    InvalidFileName(String),
     #[error("system info error: {0}")]
     SystemInfoError(String),
-    /// Invalid message content or payload (e.g., invalid count, range overflow, malformed data)
+    /// Invalid message content or payload not covered by a more specific variant
     #[error("invalid message: {0}")]
     InvalidMessage(String),
+    /// Number of elements outside the range accepted by a command
+    #[error("invalid count {got} for command 0x{command:04X} (must be 1-{max})")]
+    InvalidCount { command: u16, got: usize, max: usize },
+    /// Data larger than the field or message carrying it [bytes]
+    #[error("payload too large: {size} bytes (max {max})")]
+    PayloadTooLarge { size: usize, max: usize },
+    /// Text longer than the field carrying it once encoded [bytes]
+    #[error("string too long: {encoded_len} bytes when encoded (max {limit})")]
+    StringTooLong { limit: usize, encoded_len: usize },
+    /// Data of a fixed-size field with the wrong length [bytes]
+    #[error("unexpected length: {got} bytes (expected {expected})")]
+    UnexpectedLength { expected: usize, got: usize },
     #[error("server error: {0}")]
     ServerError(String),
     /// Invalid attribute value in message header
@@ -40,7 +55,7 @@ pub enum ProtocolError {
     /// Invalid command ID
     #[error("invalid command")]
     InvalidCommand,
-    /// Invalid instance parameter (e.g., register number, I/O number out of range)
-    #[error("invalid instance: {0}")]
-    InvalidInstance(String),
+    /// Instance outside the range accepted by a command (e.g., register or I/O number)
+    #[error("invalid instance {instance} for command 0x{command:04X} (valid: {valid})")]
+    InvalidInstance { command: u16, instance: u16, valid: String },
 }
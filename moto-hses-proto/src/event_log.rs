@@ -0,0 +1,234 @@
+//! Controller event log file (`LOGDATA.DAT`)
+//!
+//! HSES has no command reading the operation history of the controller, but controllers
+//! keeping one save it as `LOGDATA.DAT`, which can be received over the file control port.
+//!
+//! The layout parsed here is assumed, not taken from controller documentation or a file
+//! captured from a controller, so it may differ from what a given controller writes: each
+//! entry is a section headed by `///INDEX n`, followed by `KEY:value` lines, among them
+//! `DATE` (`YYYY/MM/DD HH:MM[:SS]`) and `EVENT`. Parsing never fails on a file that does
+//! not follow it; entries lack the index or time they do not have, events the parser does
+//! not know are kept as [`ControllerEvent::Other`] with all their lines as fields, and the
+//! file content stays available as [`EventLog::content`].
+
+use std::collections::BTreeMap;
+
+use crate::time::ControllerDateTime;
+
+/// File holding the event log
+pub const EVENT_LOG_FILE: &str = "LOGDATA.DAT";
+
+/// Mode the controller was switched to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OperationMode {
+    Teach,
+    Play,
+    Remote,
+}
+
+/// Event of an event log entry
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ControllerEvent {
+    /// `MODE CHANGE`, with the new mode in `MODE`
+    ModeChange(OperationMode),
+    /// `JOB START`, with the job in `JOB`
+    JobStart { job: String },
+    /// `ALARM OCCUR`, with the alarm code in `CODE` and its name in `NAME`
+    Alarm { code: u32, name: String },
+    /// Any other event, or one of the above without the values it needs
+    Other(String),
+}
+
+/// Entry of the event log
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EventLogEntry {
+    /// Number of the `///INDEX` header, if it has a valid one
+    pub index: Option<u32>,
+    /// Controller time of the event, if the entry has a valid `DATE`
+    pub time: Option<ControllerDateTime>,
+    pub event: ControllerEvent,
+    /// All `KEY:value` lines of the entry, by key
+    pub fields: BTreeMap<String, String>,
+}
+
+impl EventLogEntry {
+    /// Value of the line `key` of the entry
+    #[must_use]
+    pub fn field(&self, key: &str) -> Option<&str> {
+        self.fields.get(key).map(String::as_str)
+    }
+}
+
+/// Entries of an event log file, in file order
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EventLog {
+    entries: Vec<EventLogEntry>,
+    content: String,
+}
+
+impl EventLog {
+    /// Parse the content of an event log file
+    ///
+    /// Content that does not follow the assumed layout gives entries without index, time
+    /// or typed event, or no entries at all, rather than an error.
+    #[must_use]
+    pub fn parse(content: &str) -> Self {
+        let mut entries = Vec::new();
+        // Index and fields of the entry being read
+        let mut entry: Option<(Option<u32>, BTreeMap<String, String>)> = None;
+        for line in content.lines() {
+            let line = line.trim();
+            if let Some(index) = line.strip_prefix("///INDEX") {
+                if let Some(entry) = entry.take() {
+                    entries.push(Self::entry(entry));
+                }
+                entry = Some((index.trim().parse().ok(), BTreeMap::new()));
+                continue;
+            }
+            let Some((_, fields)) = &mut entry else {
+                continue;
+            };
+            if let Some((key, value)) = line.split_once(':')
+                && !line.starts_with('/')
+            {
+                fields.insert(key.trim().to_string(), value.trim().to_string());
+            }
+        }
+        if let Some(entry) = entry {
+            entries.push(Self::entry(entry));
+        }
+        Self { entries, content: content.to_string() }
+    }
+
+    fn entry((index, fields): (Option<u32>, BTreeMap<String, String>)) -> EventLogEntry {
+        let time = fields.get("DATE").and_then(|date| ControllerDateTime::parse(date).ok());
+        let event = Self::event(&fields);
+        EventLogEntry { index, time, event, fields }
+    }
+
+    // Typed event of the fields of an entry
+    fn event(fields: &BTreeMap<String, String>) -> ControllerEvent {
+        let field = |key: &str| fields.get(key).map(String::as_str);
+        let event = field("EVENT").unwrap_or_default();
+        let typed = match event {
+            "MODE CHANGE" => field("MODE")
+                .and_then(|mode| match mode {
+                    "TEACH" => Some(OperationMode::Teach),
+                    "PLAY" => Some(OperationMode::Play),
+                    "REMOTE" => Some(OperationMode::Remote),
+                    _ => None,
+                })
+                .map(ControllerEvent::ModeChange),
+            "JOB START" => {
+                field("JOB").map(|job| ControllerEvent::JobStart { job: job.to_string() })
+            }
+            "ALARM OCCUR" => field("CODE").and_then(|code| code.parse().ok()).map(|code| {
+                ControllerEvent::Alarm { code, name: field("NAME").unwrap_or_default().to_string() }
+            }),
+            _ => None,
+        };
+        typed.unwrap_or_else(|| ControllerEvent::Other(event.to_string()))
+    }
+
+    /// Entries of the log
+    #[must_use]
+    pub fn entries(&self) -> &[EventLogEntry] {
+        &self.entries
+    }
+
+    /// Content of the file, e.g. to inspect a layout the parser does not recognize
+    #[must_use]
+    pub fn content(&self) -> &str {
+        &self.content
+    }
+
+    /// Entries with a time at or after `time`
+    pub fn since(&self, time: ControllerDateTime) -> impl Iterator<Item = &EventLogEntry> {
+        let since = time.to_unix_seconds();
+        self.entries
+            .iter()
+            .filter(move |entry| entry.time.is_some_and(|time| time.to_unix_seconds() >= since))
+    }
+
+    /// Number of entries in the log
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether the log has no entries
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    const CONTENT: &str = "//LOGDATA\r\n\
+                           ///INDEX 1\r\n\
+                           DATE:2025/03/01 08:00:05\r\n\
+                           EVENT:MODE CHANGE\r\n\
+                           MODE:PLAY\r\n\
+                           ///INDEX 2\r\n\
+                           DATE:2025/03/01 08:01:10\r\n\
+                           EVENT:JOB START\r\n\
+                           JOB:WELD-A\r\n\
+                           LINE:0\r\n\
+                           ///INDEX 3\r\n\
+                           DATE:2025/03/01 08:02\r\n\
+                           EVENT:ALARM OCCUR\r\n\
+                           CODE:4107\r\n\
+                           NAME:OUT OF RANGE(ABSO DATA)\r\n\
+                           ///INDEX 4\r\n\
+                           DATE:2025/03/01 08:03:00\r\n\
+                           EVENT:SERVO ON\r\n";
+
+    #[test]
+    fn test_parse_event_log() {
+        let log = EventLog::parse(CONTENT);
+        assert_eq!(log.len(), 4);
+        let events: Vec<_> = log.entries().iter().map(|entry| entry.event.clone()).collect();
+        assert_eq!(
+            events,
+            [
+                ControllerEvent::ModeChange(OperationMode::Play),
+                ControllerEvent::JobStart { job: "WELD-A".to_string() },
+                ControllerEvent::Alarm { code: 4107, name: "OUT OF RANGE(ABSO DATA)".to_string() },
+                ControllerEvent::Other("SERVO ON".to_string()),
+            ]
+        );
+        let job_start = &log.entries()[1];
+        assert_eq!(job_start.index, Some(2));
+        assert_eq!(job_start.time, ControllerDateTime::new(2025, 3, 1, 8, 1, 10).ok());
+        assert_eq!(job_start.field("LINE"), Some("0"));
+
+        let since = ControllerDateTime::new(2025, 3, 1, 8, 2, 0).unwrap();
+        let indexes: Vec<_> = log.since(since).filter_map(|entry| entry.index).collect();
+        assert_eq!(indexes, [3, 4]);
+        assert_eq!(log.content(), CONTENT);
+    }
+
+    #[test]
+    fn test_parse_unknown_layout() {
+        // Entries without a valid index or time are kept without them
+        let log =
+            EventLog::parse("///INDEX x\r\nEVENT:SERVO ON\r\n///INDEX 2\r\nDATE:2025/13/01\r\n");
+        let entries = log.entries();
+        assert_eq!(entries.len(), 2);
+        assert_eq!((entries[0].index, entries[0].time), (None, None));
+        assert_eq!(entries[0].event, ControllerEvent::Other("SERVO ON".to_string()));
+        assert_eq!((entries[1].index, entries[1].time), (Some(2), None));
+        let since = ControllerDateTime::new(2000, 1, 1, 0, 0, 0).unwrap();
+        assert_eq!(log.since(since).count(), 0);
+
+        // A file without entry headers gives no entries, its content is kept
+        let content = "2025/03/01 08:00:05 MODE CHANGE PLAY\r\n";
+        let log = EventLog::parse(content);
+        assert!(log.is_empty());
+        assert_eq!(log.content(), content);
+    }
+}
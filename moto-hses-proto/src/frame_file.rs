@@ -0,0 +1,152 @@
+//! Tool and user frame files (`TOOL.CND`, `UFRAME.CND`)
+//!
+//! HSES has no command reading tool or user frame data, but the condition files holding
+//! them can be received over the file control port. Each frame is a section headed by
+//! `//TOOL n` or `//UFRAME n`; its X, Y, Z [mm] and Rx, Ry, Rz [deg] are the first line of
+//! six values in the section, in user frame files given as `///USER x,y,z,rx,ry,rz`.
+//! Other lines, e.g. the tool load or the teaching points of a user frame, are skipped.
+
+use std::collections::BTreeMap;
+
+use crate::error::ProtocolError;
+
+/// File holding the tool frames
+pub const TOOL_FILE: &str = "TOOL.CND";
+
+/// File holding the user frames
+pub const USER_FRAME_FILE: &str = "UFRAME.CND";
+
+/// Pose of a frame: X, Y, Z [mm] and Rx, Ry, Rz [deg], the rotations about the fixed
+/// axes as in Cartesian positions
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct FramePose {
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+    pub rx: f64,
+    pub ry: f64,
+    pub rz: f64,
+}
+
+/// Frames of a tool or user frame file, by number
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FrameFile {
+    frames: BTreeMap<u8, FramePose>,
+}
+
+impl FrameFile {
+    /// Parse the content of a tool or user frame file
+    ///
+    /// # Errors
+    ///
+    /// Returns `ProtocolError::Deserialization` if a section header has no frame number
+    /// or a frame line has a value that is not a number
+    pub fn parse(content: &str) -> Result<Self, ProtocolError> {
+        let mut frames = BTreeMap::new();
+        let mut section = None;
+        for (line_number, line) in content.lines().enumerate() {
+            let line = line.trim();
+            let invalid = |what: &str| {
+                ProtocolError::Deserialization(format!(
+                    "frame file line {}: {what}",
+                    line_number + 1
+                ))
+            };
+            if let Some(header) = line.strip_prefix("//").filter(|header| !header.starts_with('/'))
+            {
+                section = match header.split_once(' ') {
+                    Some(("TOOL" | "UFRAME", number)) => {
+                        Some(number.trim().parse::<u8>().map_err(|_| invalid("no frame number"))?)
+                    }
+                    _ => None,
+                };
+                continue;
+            }
+            let Some(number) = section else {
+                continue;
+            };
+            let values = match line.strip_prefix("///USER") {
+                Some(values) => values,
+                None if line.starts_with('/') => continue,
+                None => line,
+            };
+            let values: Vec<&str> = values.split(',').map(str::trim).collect();
+            if values.len() != 6 {
+                continue;
+            }
+            let values = values
+                .iter()
+                .map(|value| {
+                    value.parse::<f64>().map_err(|_| invalid(&format!("invalid value '{value}'")))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            let [x, y, z, rx, ry, rz] = values[..] else {
+                continue;
+            };
+            frames.insert(number, FramePose { x, y, z, rx, ry, rz });
+            section = None;
+        }
+        Ok(Self { frames })
+    }
+
+    /// Pose of frame `number`
+    #[must_use]
+    pub fn get(&self, number: u8) -> Option<FramePose> {
+        self.frames.get(&number).copied()
+    }
+
+    /// Numbers and poses of the frames in the file
+    pub fn iter(&self) -> impl Iterator<Item = (u8, FramePose)> + '_ {
+        self.frames.iter().map(|(&number, &pose)| (number, pose))
+    }
+
+    /// Number of frames in the file
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Whether the file has no frames
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_tool_file() {
+        let content = "//TOOL 0\r\n///NAME \r\n0.000,0.000,0.000,0.0000,0.0000,0.0000\r\n\
+                       0.000,0.000,0.000\r\n0.000\r\n0.000,0.000,0.000\r\n0\r\n\
+                       //TOOL 1\r\n///NAME TORCH\r\n10.500,0.000,350.000,0.0000,-45.0000,0.0000\r\n\
+                       5.000,0.000,100.000\r\n2.500\r\n0.000,0.000,0.000\r\n0\r\n";
+        let file = FrameFile::parse(content).unwrap();
+        assert_eq!(file.len(), 2);
+        assert_eq!(
+            file.get(1),
+            Some(FramePose { x: 10.5, y: 0.0, z: 350.0, rx: 0.0, ry: -45.0, rz: 0.0 })
+        );
+        assert_eq!(file.get(0), Some(FramePose::default()));
+        assert_eq!(file.get(2), None);
+    }
+
+    #[test]
+    fn test_parse_user_frame_file() {
+        let content = "//UFRAME 1\r\n///NAME TABLE\r\n///GROUP1 RB1\r\n\
+                       ///ORG 0,0,0,0,0,0,0,0\r\n\
+                       ///USER 1000.000,-200.000,500.000,0.0000,0.0000,90.0000\r\n";
+        let file = FrameFile::parse(content).unwrap();
+        assert_eq!(
+            file.iter().collect::<Vec<_>>(),
+            [(1, FramePose { x: 1000.0, y: -200.0, z: 500.0, rx: 0.0, ry: 0.0, rz: 90.0 })]
+        );
+
+        assert!(FrameFile::parse("//TOOL x\r\n").is_err());
+        assert!(FrameFile::parse("//TOOL 1\r\n0,0,x,0,0,0\r\n").is_err());
+        assert!(FrameFile::parse("").unwrap().is_empty());
+    }
+}
@@ -0,0 +1,104 @@
+//! Job files (`*.JBI`)
+//!
+//! A job is received as text over the file control port: `//NAME` gives the job name and
+//! `//INST` starts the instructions, headed by `///` attribute lines. The instructions
+//! are numbered from line 0, the `NOP`, to the last line, the `END`; these are the line
+//! numbers of job selection and of the executing job information.
+
+use crate::error::ProtocolError;
+
+/// Extension of job files on the controller
+pub const JOB_FILE_EXTENSION: &str = "JBI";
+
+/// Name and instructions of a job file
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct JobFile {
+    /// Job name as in `//NAME`
+    pub name: String,
+    /// Instruction lines, from `NOP` to `END`
+    pub instructions: Vec<String>,
+}
+
+impl JobFile {
+    /// Parse the content of a job file
+    ///
+    /// # Errors
+    ///
+    /// Returns `ProtocolError::Deserialization` if the file has no `//NAME` line or no
+    /// instructions
+    pub fn parse(content: &str) -> Result<Self, ProtocolError> {
+        let mut name = None;
+        let mut instructions = Vec::new();
+        let mut in_instructions = false;
+        for line in content.lines().map(str::trim_end) {
+            if in_instructions {
+                if !line.starts_with("///") {
+                    instructions.push(line.to_string());
+                }
+            } else if let Some(value) = line.strip_prefix("//NAME ") {
+                name = Some(value.trim().to_string());
+            } else if line == "//INST" {
+                in_instructions = true;
+            }
+        }
+        let name = name
+            .ok_or_else(|| ProtocolError::Deserialization("job file without //NAME".to_string()))?;
+        if instructions.is_empty() {
+            return Err(ProtocolError::Deserialization(format!("job {name} has no instructions")));
+        }
+        Ok(Self { name, instructions })
+    }
+
+    /// Number of the last line, the `END`
+    #[must_use]
+    pub fn last_line(&self) -> u32 {
+        u32::try_from(self.instructions.len().saturating_sub(1)).unwrap_or(u32::MAX)
+    }
+
+    /// Whether this is the job `job_name`, as reported by job selection (`NAME.JOB`)
+    ///
+    /// Names compare without a `.JOB` or `.JBI` extension.
+    #[must_use]
+    pub fn is_job(&self, job_name: &str) -> bool {
+        job_stem(&self.name).eq_ignore_ascii_case(job_stem(job_name))
+    }
+}
+
+/// Name of the file holding the job `job_name`, e.g. `WELD.JBI` for `WELD.JOB`
+#[must_use]
+pub fn job_file_name(job_name: &str) -> String {
+    format!("{}.{JOB_FILE_EXTENSION}", job_stem(job_name))
+}
+
+// Job name without a `.JOB` or `.JBI` extension
+fn job_stem(name: &str) -> &str {
+    name.rsplit_once('.')
+        .filter(|(_, extension)| {
+            extension.eq_ignore_ascii_case("JOB") || extension.eq_ignore_ascii_case("JBI")
+        })
+        .map_or(name, |(stem, _)| stem)
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_job_file() {
+        let content = "/JOB\r\n//NAME WELD\r\n//POS\r\n///NPOS 1,0,0,0,0,0\r\nC00000=0,0,0,0,0,0\r\n\
+                       //INST\r\n///DATE 2024/04/22 10:15\r\n///ATTR SC,RW\r\n///GROUP1 RB1\r\n\
+                       NOP\r\nMOVJ C00000 VJ=10.00\r\nARCON\r\nEND\r\n";
+        let job = JobFile::parse(content).unwrap();
+        assert_eq!(job.name, "WELD");
+        assert_eq!(job.instructions, ["NOP", "MOVJ C00000 VJ=10.00", "ARCON", "END"]);
+        assert_eq!(job.last_line(), 3);
+        assert!(job.is_job("WELD.JOB"));
+        assert!(job.is_job("weld"));
+        assert!(!job.is_job("WELD2.JOB"));
+        assert_eq!(job_file_name("WELD.JOB"), "WELD.JBI");
+
+        assert!(JobFile::parse("/JOB\r\n//INST\r\nNOP\r\nEND\r\n").is_err());
+        assert!(JobFile::parse("/JOB\r\n//NAME EMPTY\r\n").is_err());
+    }
+}
@@ -1,29 +1,64 @@
 //! moto-hses-proto - HSES (High Speed Ethernet Server) protocol implementation
+//!
+//! # Wire compatibility
+//!
+//! The bytes a payload type serializes to and accepts are part of the semver contract:
+//! a plant may run clients and tools built against different releases on one controller.
+//! The wire-stable types are [`Alarm`], [`ExecutingJobInfo`], [`Status`], [`Position`]
+//! and the variable types (`u8`, `i16`, `i32`, `f32`, `String`); `tests/wire_compatibility.rs`
+//! checks them against payloads serialized by earlier releases. The one deviation so far:
+//! Cartesian positions now carry the 7th and 8th axis words, which 0.4.0 left out and
+//! ignores.
+//!
+//! Payload structs whose controller data may gain attributes ([`Alarm`],
+//! [`ExecutingJobInfo`], [`SelectedJob`], [`SystemInfo`], [`CartesianPosition`] and
+//! [`Status`]) are `#[non_exhaustive]`: build them with their constructors, so a field
+//! added in a minor release does not break the build. Types defined bit for bit by the
+//! HSES manual, such as [`StatusData1`] or [`PulsePosition`], stay exhaustive.
 
 pub mod commands;
 pub mod constants;
+pub mod controller;
 pub mod encoding;
 pub mod encoding_utils;
 pub mod error;
+pub mod event_log;
+pub mod frame_file;
+pub mod job_file;
 pub mod message;
+pub mod parameter;
 pub mod payload;
+pub mod prelude;
+pub mod time;
+pub mod trace;
 
 // Re-export commonly used items for convenience
 pub use commands::{
-    AlarmAttribute, AlarmReset, Command, CycleMode, CycleModeSwitchingCommand, DeleteFile,
-    Division, HoldServoControl, HoldServoType, HoldServoValue, ReadAlarmData, ReadAlarmHistory,
-    ReadCurrentPosition, ReadExecutingJobInfo, ReadFileList, ReadIo, ReadRegister, ReadStatus,
-    ReadStatusData1, ReadStatusData2, ReadVariable, ReceiveFile, SendFile, Service,
-    VariableCommandId, WriteIo, WriteRegister, WriteVariable,
+    ALARM_HISTORY_SIZE, AlarmAttribute, AlarmCategory, AlarmReset, AttributeRead, Command,
+    CycleMode, CycleModeSwitchingCommand, DeleteFile, Division, HoldServoControl, HoldServoType,
+    HoldServoValue, IoBit, JobInfoAttribute, JobSelectType, MoveCartesian, MoveFrame, MovePulse,
+    MoveSpeed, MoveType, MultipleVariableCommandId, ReadAlarmData, ReadAlarmHistory,
+    ReadBasePositionVar, ReadCurrentPosition, ReadExecutingJobInfo, ReadFileList, ReadIo,
+    ReadPositionVar, ReadRegister, ReadStationVar, ReadStatus, ReadStatusData1, ReadStatusData2,
+    ReadSystemInfo, ReadVariable, ReceiveFile, SendFile, Service, StatusAttribute, TaskType,
+    VariableCommandId, WriteBasePositionVar, WriteIo, WritePositionVar, WriteRegister,
+    WriteStationVar, WriteVariable,
 };
 pub use constants::{FILE_CONTROL_PORT, ROBOT_CONTROL_PORT};
+pub use controller::{ControllerModel, FileNameRules};
 pub use encoding::TextEncoding;
 pub use error::ProtocolError;
+pub use event_log::{ControllerEvent, EventLog, EventLogEntry, OperationMode};
+pub use frame_file::{FrameFile, FramePose};
+pub use job_file::JobFile;
 pub use message::{
     HsesCommonHeader, HsesRequestMessage, HsesRequestSubHeader, HsesResponseMessage,
     HsesResponseSubHeader,
 };
+pub use parameter::ParameterFile;
 pub use payload::{
-    Alarm, CartesianPosition, ExecutingJobInfo, HsesPayload, Position, PulsePosition, Status,
-    StatusData1, StatusData2,
+    Alarm, AttributeDeserialize, BasePosition, BasePositionType, CartesianPosition,
+    ExecutingJobInfo, HsesPayload, Position, PulsePosition, SelectedJob, StationPosition, Status,
+    StatusData1, StatusData2, SystemInfo,
 };
+pub use time::ControllerDateTime;
@@ -1,5 +1,6 @@
 //! HSES message structures and operations
 
+use crate::constants::{RESPONSE_SERVICE_FLAG, header};
 use crate::error::ProtocolError;
 use bytes::{Buf, BufMut, BytesMut};
 
@@ -21,14 +22,14 @@ impl HsesCommonHeader {
     #[must_use]
     pub const fn new(division: u8, ack: u8, request_id: u8, payload_size: u16) -> Self {
         Self {
-            magic: *b"YERC",
+            magic: *header::MAGIC,
             header_size: 0x20,
             payload_size,
             reserved_magic: 0x03,
             division,
             ack,
             request_id,
-            block_number: if ack == 0x01 { 0x8000_0000 } else { 0 }, // Single response: 0x8000_0000, Request: 0
+            block_number: if ack == 0x01 { header::LAST_BLOCK_FLAG } else { 0 }, // Single response: last block, Request: 0
             reserved: *b"99999999",
         }
     }
@@ -49,13 +50,13 @@ impl HsesCommonHeader {
     ///
     /// Returns an error if decoding fails
     pub fn decode(src: &mut &[u8]) -> Result<Self, ProtocolError> {
-        if src.len() < 24 {
+        if src.len() < header::COMMON_SIZE {
             return Err(ProtocolError::Underflow);
         }
 
         let mut buf = *src;
         let magic = [buf.get_u8(), buf.get_u8(), buf.get_u8(), buf.get_u8()];
-        if magic != *b"YERC" {
+        if magic != *header::MAGIC {
             return Err(ProtocolError::InvalidHeader);
         }
 
@@ -145,7 +146,7 @@ impl HsesResponseSubHeader {
     #[must_use]
     pub const fn new(service: u8, status: u8, added_status: u16) -> Self {
         Self {
-            service: service + 0x80, // Add 0x80 to service for response
+            service: service | RESPONSE_SERVICE_FLAG,
             status,
             added_status_size: 2, // 16-bit added_status
             padding1: 0,
@@ -211,7 +212,7 @@ impl HsesRequestMessage {
         payload: Vec<u8>,
     ) -> Result<Self, ProtocolError> {
         let payload_len = u16::try_from(payload.len()).map_err(|_| {
-            ProtocolError::InvalidMessage("Payload too large for protocol".to_string())
+            ProtocolError::PayloadTooLarge { size: payload.len(), max: usize::from(u16::MAX) }
         })?;
         let header = HsesCommonHeader::new(division, ack, request_id, payload_len);
         let sub_header = HsesRequestSubHeader::new(command, instance, attribute, service);
@@ -220,7 +221,7 @@ impl HsesRequestMessage {
 
     #[must_use]
     pub fn encode(&self) -> BytesMut {
-        let mut buf = BytesMut::with_capacity(32 + self.payload.len());
+        let mut buf = BytesMut::with_capacity(header::SIZE + self.payload.len());
         self.header.encode(&mut buf);
         self.sub_header.encode(&mut buf);
         buf.extend_from_slice(&self.payload);
@@ -264,7 +265,7 @@ impl HsesResponseMessage {
         payload: Vec<u8>,
     ) -> Result<Self, ProtocolError> {
         let payload_len = u16::try_from(payload.len()).map_err(|_| {
-            ProtocolError::InvalidMessage("Payload too large for protocol".to_string())
+            ProtocolError::PayloadTooLarge { size: payload.len(), max: usize::from(u16::MAX) }
         })?;
         let header = HsesCommonHeader::new(division, ack, request_id, payload_len);
         let sub_header = HsesResponseSubHeader::new(service, status, added_status);
@@ -273,7 +274,7 @@ impl HsesResponseMessage {
 
     #[must_use]
     pub fn encode(&self) -> BytesMut {
-        let mut buf = BytesMut::with_capacity(32 + self.payload.len());
+        let mut buf = BytesMut::with_capacity(header::SIZE + self.payload.len());
         self.header.encode(&mut buf);
         self.sub_header.encode(&mut buf);
         buf.extend_from_slice(&self.payload);
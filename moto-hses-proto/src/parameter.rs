@@ -0,0 +1,138 @@
+//! Controller parameter files (`*.PRM`)
+//!
+//! HSES has no command reading single parameters, but the parameter files can be received
+//! over the file control port. Parameters are listed as `S1C1G400=1000,2000,...`, each
+//! value belonging to the next parameter number; lines starting with `/` head sections
+//! and are skipped.
+
+use std::collections::BTreeMap;
+
+use crate::error::ProtocolError;
+
+/// Parameter file holding the servo parameters, including the pulse soft limits
+pub const SERVO_PARAMETER_FILE: &str = "SC.PRM";
+
+/// First parameter number of the + side pulse soft limits (`S1CxG400`-`S1CxG407`)
+pub const SOFT_LIMIT_MAX_PARAMETER: u16 = 400;
+
+/// First parameter number of the - side pulse soft limits (`S1CxG410`-`S1CxG417`)
+pub const SOFT_LIMIT_MIN_PARAMETER: u16 = 410;
+
+/// Number of axes with soft limit parameters in a control group
+pub const SOFT_LIMIT_AXES: usize = 8;
+
+/// Parameters of a parameter file, by name
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ParameterFile {
+    values: BTreeMap<String, i64>,
+}
+
+impl ParameterFile {
+    /// Parse the content of a parameter file
+    ///
+    /// # Errors
+    ///
+    /// Returns `ProtocolError::Deserialization` if a parameter line has no number in its
+    /// name or a value that is not an integer
+    pub fn parse(content: &str) -> Result<Self, ProtocolError> {
+        let mut values = BTreeMap::new();
+        for (line_number, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.starts_with('/') {
+                continue;
+            }
+            let Some((name, list)) = line.split_once('=') else {
+                continue;
+            };
+            let invalid = |what: &str| {
+                ProtocolError::Deserialization(format!(
+                    "parameter file line {}: {what}",
+                    line_number + 1
+                ))
+            };
+            let name = name.trim();
+            let digits = name.len() - name.trim_end_matches(|c: char| c.is_ascii_digit()).len();
+            let (prefix, number) = name.split_at(name.len() - digits);
+            let number: u32 =
+                number.parse().map_err(|_| invalid(&format!("no number in '{name}'")))?;
+            for (offset, value) in (0u32..).zip(list.split(',')) {
+                let value = value.trim();
+                let value: i64 =
+                    value.parse().map_err(|_| invalid(&format!("invalid value '{value}'")))?;
+                values.insert(format!("{prefix}{:0digits$}", number + offset), value);
+            }
+        }
+        Ok(Self { values })
+    }
+
+    /// Value of the parameter `name`, e.g. "S1C1G400"
+    #[must_use]
+    pub fn get(&self, name: &str) -> Option<i64> {
+        self.values.get(name).copied()
+    }
+
+    /// Number of parameters in the file
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Whether the file has no parameters
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// - and + side pulse soft limits of `axis` (0-based) of control group `control_group`
+    ///
+    /// `None` if the file lacks either parameter or a value is outside the pulse range.
+    #[must_use]
+    pub fn soft_limit(&self, control_group: u8, axis: usize) -> Option<(i32, i32)> {
+        let axis = u16::try_from(axis).ok().filter(|&axis| usize::from(axis) < SOFT_LIMIT_AXES)?;
+        let value = |first: u16| {
+            self.get(&format!("S1C{control_group}G{:03}", first + axis))
+                .and_then(|value| i32::try_from(value).ok())
+        };
+        Some((value(SOFT_LIMIT_MIN_PARAMETER)?, value(SOFT_LIMIT_MAX_PARAMETER)?))
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = "//S1CxG\r\n///R1\r\n\
+        S1C1G400=170000,90000,150000,180000,120000,250000,0,0\r\n\
+        S1C1G410=-170000,-60000,-70000,-180000,-120000,-250000,0,0\r\n\
+        S1C2G400=50000\r\n";
+
+    #[test]
+    fn test_parse_parameter_file() {
+        let file = ParameterFile::parse(SAMPLE).unwrap();
+        assert_eq!(file.len(), 17);
+        assert_eq!(file.get("S1C1G402"), Some(150_000));
+        assert_eq!(file.get("S1C1G415"), Some(-250_000));
+        assert_eq!(file.get("S1C1G418"), None);
+        assert!(ParameterFile::parse("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_parse_invalid_parameter_file() {
+        let result = ParameterFile::parse("S1C1G400=1,x");
+        assert!(
+            matches!(&result, Err(ProtocolError::Deserialization(message)) if message.contains("line 1")),
+            "{result:?}"
+        );
+        assert!(ParameterFile::parse("S1C1G=1").is_err());
+    }
+
+    #[test]
+    fn test_soft_limit() {
+        let file = ParameterFile::parse(SAMPLE).unwrap();
+        assert_eq!(file.soft_limit(1, 0), Some((-170_000, 170_000)));
+        assert_eq!(file.soft_limit(1, 7), Some((0, 0)));
+        assert_eq!(file.soft_limit(1, 8), None);
+        assert_eq!(file.soft_limit(2, 0), None);
+    }
+}
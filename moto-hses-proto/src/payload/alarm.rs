@@ -2,10 +2,12 @@
 
 use crate::encoding::TextEncoding;
 use crate::error::ProtocolError;
-use crate::payload::HsesPayload;
+use crate::payload::layout::{WireField, wire_layout};
+use crate::payload::{AttributeDeserialize, HsesPayload};
 
 /// Alarm data structure
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
 pub struct Alarm {
     pub code: u32,
     pub data: u32,
@@ -40,131 +42,95 @@ impl Alarm {
         self
     }
 
-    /// Serialize alarm data for response with specified text encoding
+    /// Serialize a single attribute (1-8) with specified text encoding
+    ///
+    /// Text attributes are null-padded, or truncated, to their field size.
+    ///
     /// # Errors
     ///
-    /// Returns an error if serialization fails
+    /// Returns `ProtocolError::InvalidAttribute` for attributes other than 1-8
     pub fn serialize(
         &self,
         attribute: u8,
         encoding: TextEncoding,
     ) -> Result<Vec<u8>, ProtocolError> {
-        let mut data = Vec::new();
-
-        match attribute {
-            1 => {
-                // Alarm code
-                data.extend_from_slice(&self.code.to_le_bytes());
-            }
-            2 => {
-                // Alarm data
-                data.extend_from_slice(&self.data.to_le_bytes());
-            }
-            3 => {
-                // Alarm type
-                data.extend_from_slice(&self.alarm_type.to_le_bytes());
-            }
-            4 => {
-                // Alarm time
-                let time_bytes = crate::encoding_utils::encode_string(&self.time, encoding);
-                let mut padded_time = vec![0u8; 16];
-                padded_time[..time_bytes.len().min(16)]
-                    .copy_from_slice(&time_bytes[..time_bytes.len().min(16)]);
-                data.extend_from_slice(&padded_time);
-            }
-            5 => {
-                // Alarm name
-                let name_bytes = crate::encoding_utils::encode_string(&self.name, encoding);
-                let mut padded_name = vec![0u8; 32];
-                padded_name[..name_bytes.len().min(32)]
-                    .copy_from_slice(&name_bytes[..name_bytes.len().min(32)]);
-                data.extend_from_slice(&padded_name);
-            }
-            6 => {
-                // Sub code info
-                let info_bytes =
-                    crate::encoding_utils::encode_string(&self.sub_code_info, encoding);
-                let mut padded_info = vec![0u8; 16];
-                padded_info[..info_bytes.len().min(16)]
-                    .copy_from_slice(&info_bytes[..info_bytes.len().min(16)]);
-                data.extend_from_slice(&padded_info);
-            }
-            7 => {
-                // Sub code data
-                let data_bytes =
-                    crate::encoding_utils::encode_string(&self.sub_code_data, encoding);
-                let mut padded_data = vec![0u8; 96];
-                padded_data[..data_bytes.len().min(96)]
-                    .copy_from_slice(&data_bytes[..data_bytes.len().min(96)]);
-                data.extend_from_slice(&padded_data);
-            }
-            8 => {
-                // Sub code reverse
-                let reverse_bytes =
-                    crate::encoding_utils::encode_string(&self.sub_code_reverse, encoding);
-                let mut padded_reverse = vec![0u8; 96];
-                padded_reverse[..reverse_bytes.len().min(96)]
-                    .copy_from_slice(&reverse_bytes[..reverse_bytes.len().min(96)]);
-                data.extend_from_slice(&padded_reverse);
-            }
-            _ => {
-                return Err(ProtocolError::InvalidAttribute);
-            }
-        }
-
-        Ok(data)
+        let field = usize::from(attribute)
+            .checked_sub(1)
+            .and_then(|index| AlarmLayout::FIELDS.get(index))
+            .ok_or(ProtocolError::InvalidAttribute)?;
+        Ok(self.serialize_complete(encoding)?[field.range()].to_vec())
     }
 
-    /// Serialize complete alarm data (all attributes)
+    /// Serialize complete alarm data (attributes 1-8 in order, 268 bytes)
     /// # Errors
     ///
     /// Returns an error if serialization fails
     pub fn serialize_complete(&self, encoding: TextEncoding) -> Result<Vec<u8>, ProtocolError> {
-        let mut data = Vec::new();
-
-        // Alarm code
-        data.extend_from_slice(&self.code.to_le_bytes());
-        // Alarm data
-        data.extend_from_slice(&self.data.to_le_bytes());
-        // Alarm type
-        data.extend_from_slice(&self.alarm_type.to_le_bytes());
-
-        // Alarm time (16 bytes)
-        let time_bytes = crate::encoding_utils::encode_string(&self.time, encoding);
-        let mut padded_time = vec![0u8; 16];
-        padded_time[..time_bytes.len().min(16)]
-            .copy_from_slice(&time_bytes[..time_bytes.len().min(16)]);
-        data.extend_from_slice(&padded_time);
-
-        // Alarm name (32 bytes)
-        let name_bytes = crate::encoding_utils::encode_string(&self.name, encoding);
-        let mut padded_name = vec![0u8; 32];
-        padded_name[..name_bytes.len().min(32)]
-            .copy_from_slice(&name_bytes[..name_bytes.len().min(32)]);
-        data.extend_from_slice(&padded_name);
-
-        // Sub code info (16 bytes)
-        let info_bytes = crate::encoding_utils::encode_string(&self.sub_code_info, encoding);
-        let mut padded_info = vec![0u8; 16];
-        padded_info[..info_bytes.len().min(16)]
-            .copy_from_slice(&info_bytes[..info_bytes.len().min(16)]);
-        data.extend_from_slice(&padded_info);
-
-        // Sub code data (96 bytes)
-        let data_bytes = crate::encoding_utils::encode_string(&self.sub_code_data, encoding);
-        let mut padded_data = vec![0u8; 96];
-        padded_data[..data_bytes.len().min(96)]
-            .copy_from_slice(&data_bytes[..data_bytes.len().min(96)]);
-        data.extend_from_slice(&padded_data);
-
-        // Sub code reverse (96 bytes)
-        let reverse_bytes = crate::encoding_utils::encode_string(&self.sub_code_reverse, encoding);
-        let mut padded_reverse = vec![0u8; 96];
-        padded_reverse[..reverse_bytes.len().min(96)]
-            .copy_from_slice(&reverse_bytes[..reverse_bytes.len().min(96)]);
-        data.extend_from_slice(&padded_reverse);
-
-        Ok(data)
+        Ok(AlarmLayout::from(self).encode(encoding))
+    }
+}
+
+wire_layout! {
+    /// Alarm data (0x70) and alarm history (0x71) as on the wire: attributes 1-8 in order
+    pub struct AlarmLayout, size = 268 {
+        code: u32,
+        data: u32,
+        alarm_type: u32,
+        time: text(16),
+        name: text(32),
+        sub_code_info: text(16),
+        sub_code_data: text(96),
+        sub_code_reverse: text(96),
+    }
+}
+
+impl From<&Alarm> for AlarmLayout {
+    fn from(alarm: &Alarm) -> Self {
+        Self {
+            code: alarm.code,
+            data: alarm.data,
+            alarm_type: alarm.alarm_type,
+            time: alarm.time.clone(),
+            name: alarm.name.clone(),
+            sub_code_info: alarm.sub_code_info.clone(),
+            sub_code_data: alarm.sub_code_data.clone(),
+            sub_code_reverse: alarm.sub_code_reverse.clone(),
+        }
+    }
+}
+
+impl From<AlarmLayout> for Alarm {
+    fn from(layout: AlarmLayout) -> Self {
+        Self {
+            code: layout.code,
+            data: layout.data,
+            alarm_type: layout.alarm_type,
+            time: layout.time,
+            name: layout.name,
+            sub_code_info: layout.sub_code_info,
+            sub_code_data: layout.sub_code_data,
+            sub_code_reverse: layout.sub_code_reverse,
+        }
+    }
+}
+
+/// Data part of an alarm data (0x70) or alarm history (0x71) response
+///
+/// Attribute 0 returns all attributes, as for `Get_Attribute_All`; attributes 1-8 return
+/// the single attribute.
+///
+/// # Errors
+///
+/// Returns `ProtocolError::InvalidAttribute` for attributes above 8
+pub fn serialize_alarm_response(
+    alarm: &Alarm,
+    attribute: u8,
+    encoding: TextEncoding,
+) -> Result<Vec<u8>, ProtocolError> {
+    if attribute == 0 {
+        alarm.serialize_complete(encoding)
+    } else {
+        alarm.serialize(attribute, encoding)
     }
 }
 
@@ -187,36 +153,33 @@ impl Alarm {
     /// Deserialize alarm data from response with specified text encoding
     /// # Errors
     ///
-    /// Returns an error if deserialization fails
+    /// Returns an error if the data is shorter than the 268 bytes of all attributes
     pub fn deserialize(data: &[u8], text_encoding: TextEncoding) -> Result<Self, ProtocolError> {
-        if data.len() < 60 {
-            return Err(ProtocolError::Deserialization("Insufficient data length".to_string()));
-        }
-
-        let code = u32::from_le_bytes([data[0], data[1], data[2], data[3]]);
-        let alarm_data = u32::from_le_bytes([data[4], data[5], data[6], data[7]]);
-        let alarm_type = u32::from_le_bytes([data[8], data[9], data[10], data[11]]);
+        AlarmLayout::decode(data, text_encoding).map(Self::from)
+    }
 
-        // Extract time (16 bytes, null-terminated) and decode with specified encoding
-        let time_end = data[12..28].iter().position(|&b| b == 0).unwrap_or(16);
-        let time_bytes = &data[12..12 + time_end];
-        let time = crate::encoding_utils::decode_string_with_fallback(time_bytes, text_encoding);
+    /// Deserialize alarm data like [`Self::deserialize`], also reporting whether a text
+    /// attribute was not valid in `text_encoding` and was decoded with replacement characters
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the data is shorter than the 268 bytes of all attributes
+    pub fn deserialize_with_replacements(
+        data: &[u8],
+        text_encoding: TextEncoding,
+    ) -> Result<(Self, bool), ProtocolError> {
+        AlarmLayout::decode_with_replacements(data, text_encoding)
+            .map(|(layout, replaced)| (Self::from(layout), replaced))
+    }
+}
 
-        // Extract name (32 bytes, null-terminated) and decode with specified encoding
-        let name_end = data[28..60].iter().position(|&b| b == 0).unwrap_or(32);
-        let name_bytes = &data[28..28 + name_end];
-        let name = crate::encoding_utils::decode_string_with_fallback(name_bytes, text_encoding);
+/// Attributes 1-8: code, data, type, time, name and the sub code information, data and
+/// reverse display flags
+impl AttributeDeserialize for Alarm {
+    const ATTRIBUTES: &'static [WireField] = AlarmLayout::FIELDS;
 
-        Ok(Self {
-            code,
-            data: alarm_data,
-            alarm_type,
-            time,
-            name,
-            sub_code_info: String::new(),
-            sub_code_data: String::new(),
-            sub_code_reverse: String::new(),
-        })
+    fn deserialize_all(data: &[u8], encoding: TextEncoding) -> Result<Self, ProtocolError> {
+        Self::deserialize(data, encoding)
     }
 }
 
@@ -379,6 +342,29 @@ mod tests {
         assert_eq!(name_str, "Test Alarm");
     }
 
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn test_serialize_alarm_response() {
+        let alarm = test_alarms::servo_error();
+        let encoding = TextEncoding::Utf8;
+
+        let all = serialize_alarm_response(&alarm, 0, encoding).unwrap();
+        assert_eq!(all, alarm.serialize_complete(encoding).unwrap());
+
+        // Each attribute is the matching slice of the complete data
+        let mut offset = 0;
+        for attribute in 1..=8 {
+            let data = serialize_alarm_response(&alarm, attribute, encoding).unwrap();
+            assert_eq!(data, all[offset..offset + data.len()], "attribute {attribute}");
+            offset += data.len();
+        }
+        assert_eq!(offset, all.len());
+        assert!(matches!(
+            serialize_alarm_response(&alarm, 9, encoding),
+            Err(ProtocolError::InvalidAttribute)
+        ));
+    }
+
     #[test]
     #[allow(clippy::unwrap_used)]
     fn test_alarm_serialize_invalid_attribute() {
@@ -415,6 +401,37 @@ mod tests {
         assert!(matches!(result.unwrap_err(), ProtocolError::Deserialization(_)));
     }
 
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn test_alarm_deserialize_attribute() {
+        let alarm =
+            Alarm::new(1001, 1, 1, "2024/01/01 12:00".to_string(), "Test Alarm".to_string())
+                .with_sub_code("[SV#1]".to_string(), String::new(), String::new());
+
+        let name = alarm.serialize(5, TextEncoding::Utf8).unwrap();
+        let read = Alarm::deserialize_attribute(&name, 5, TextEncoding::Utf8).unwrap();
+        assert_eq!((read.code, read.name.as_str(), read.time.as_str()), (0, "Test Alarm", ""));
+        let info = alarm.serialize(6, TextEncoding::Utf8).unwrap();
+        let read = Alarm::deserialize_attribute(&info, 6, TextEncoding::Utf8).unwrap();
+        assert_eq!(read.sub_code_info, "[SV#1]");
+
+        let all = alarm.serialize_complete(TextEncoding::Utf8).unwrap();
+        assert_eq!(Alarm::deserialize_attribute(&all, 0, TextEncoding::Utf8).unwrap(), alarm);
+        assert!(matches!(
+            Alarm::deserialize_attribute(&name, 9, TextEncoding::Utf8),
+            Err(ProtocolError::InvalidAttribute)
+        ));
+        assert!(matches!(
+            Alarm::deserialize_attribute(&name[..8], 5, TextEncoding::Utf8),
+            Err(ProtocolError::Deserialization(_))
+        ));
+
+        let code = alarm.serialize(1, TextEncoding::Utf8).unwrap();
+        let read =
+            Alarm::deserialize_attributes(&[(1, &code), (5, &name)], TextEncoding::Utf8).unwrap();
+        assert_eq!((read.code, read.name.as_str(), read.data), (1001, "Test Alarm", 0));
+    }
+
     #[test]
     #[allow(clippy::unwrap_used)]
     fn test_alarm_variable_type_trait() {
@@ -503,6 +520,10 @@ mod tests {
 
         // Time is ASCII, so it should be the same in both encodings
         assert_eq!(alarm_utf8.time, japanese_time);
+
+        // Only the decoding with the wrong encoding used replacement characters
+        assert!(Alarm::deserialize_with_replacements(&data, TextEncoding::Utf8).unwrap().1);
+        assert!(!Alarm::deserialize_with_replacements(&data, TextEncoding::ShiftJis).unwrap().1);
     }
 
     #[test]
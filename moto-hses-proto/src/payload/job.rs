@@ -1,10 +1,12 @@
 //! Job information data structures and operations
 
 use crate::error::ProtocolError;
-use crate::payload::HsesPayload;
+use crate::payload::layout::{WireField, wire_layout};
+use crate::payload::{AttributeDeserialize, HsesPayload};
 
 /// Executing job information data structure
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
 pub struct ExecutingJobInfo {
     pub job_name: String,
     pub line_number: u32,
@@ -27,42 +29,14 @@ impl ExecutingJobInfo {
     ///
     /// # Errors
     ///
-    /// Returns an error if serialization fails
+    /// Returns `ProtocolError::InvalidAttribute` for attributes other than 1-4
     pub fn serialize(
         &self,
         attribute: u8,
         encoding: crate::encoding::TextEncoding,
     ) -> Result<Vec<u8>, ProtocolError> {
-        let mut data = Vec::new();
-
-        match attribute {
-            1 => {
-                // Job name (32 bytes)
-                let name_bytes = crate::encoding_utils::encode_string(&self.job_name, encoding);
-                let mut padded_name = vec![0u8; 32];
-                padded_name[..name_bytes.len().min(32)]
-                    .copy_from_slice(&name_bytes[..name_bytes.len().min(32)]);
-                data.extend_from_slice(&padded_name);
-            }
-            2 => {
-                // Line number (4 bytes)
-                data.extend_from_slice(&self.line_number.to_le_bytes());
-            }
-            3 => {
-                // Step number (4 bytes)
-                data.extend_from_slice(&self.step_number.to_le_bytes());
-            }
-            4 => {
-                // Speed override value (4 bytes) - convert from % to 0.01%
-                let raw_speed_override_value = self.speed_override_value * 100;
-                data.extend_from_slice(&raw_speed_override_value.to_le_bytes());
-            }
-            _ => {
-                return Err(ProtocolError::InvalidAttribute);
-            }
-        }
-
-        Ok(data)
+        let field = attribute_field(attribute).ok_or(ProtocolError::InvalidAttribute)?;
+        Ok(ExecutingJobInfoLayout::from(self).encode(encoding)[field.range()].to_vec())
     }
 
     /// Serialize complete job info data (all attributes)
@@ -73,29 +47,49 @@ impl ExecutingJobInfo {
         &self,
         encoding: crate::encoding::TextEncoding,
     ) -> Result<Vec<u8>, ProtocolError> {
-        let mut data = Vec::new();
-
-        // Job name (32 bytes)
-        let name_bytes = crate::encoding_utils::encode_string(&self.job_name, encoding);
-        let mut padded_name = vec![0u8; 32];
-        padded_name[..name_bytes.len().min(32)]
-            .copy_from_slice(&name_bytes[..name_bytes.len().min(32)]);
-        data.extend_from_slice(&padded_name);
-
-        // Line number (4 bytes)
-        data.extend_from_slice(&self.line_number.to_le_bytes());
+        Ok(ExecutingJobInfoLayout::from(self).encode(encoding))
+    }
+}
 
-        // Step number (4 bytes)
-        data.extend_from_slice(&self.step_number.to_le_bytes());
+wire_layout! {
+    /// Executing job information (0x73) as on the wire
+    pub struct ExecutingJobInfoLayout, size = 44 {
+        job_name: text(32),
+        line_number: u32,
+        step_number: u32,
+        /// Speed override [0.01 %]
+        speed_override: u32,
+    }
+}
 
-        // Speed override value (4 bytes) - convert from % to 0.01%
-        let raw_speed_override_value = self.speed_override_value * 100;
-        data.extend_from_slice(&raw_speed_override_value.to_le_bytes());
+impl From<&ExecutingJobInfo> for ExecutingJobInfoLayout {
+    fn from(info: &ExecutingJobInfo) -> Self {
+        Self {
+            job_name: info.job_name.clone(),
+            line_number: info.line_number,
+            step_number: info.step_number,
+            speed_override: info.speed_override_value * 100,
+        }
+    }
+}
 
-        Ok(data)
+impl From<ExecutingJobInfoLayout> for ExecutingJobInfo {
+    fn from(layout: ExecutingJobInfoLayout) -> Self {
+        Self {
+            job_name: layout.job_name,
+            line_number: layout.line_number,
+            step_number: layout.step_number,
+            speed_override_value: layout.speed_override / 100,
+        }
     }
 }
 
+// Field of a single attribute (1-4) in the layout
+fn attribute_field(attribute: u8) -> Option<WireField> {
+    let index = usize::from(attribute).checked_sub(1)?;
+    ExecutingJobInfoLayout::FIELDS.get(index).copied()
+}
+
 impl Default for ExecutingJobInfo {
     fn default() -> Self {
         Self {
@@ -117,117 +111,90 @@ impl ExecutingJobInfo {
         data: &[u8],
         encoding: crate::encoding::TextEncoding,
     ) -> Result<Self, ProtocolError> {
-        if data.len() < 44 {
-            return Err(ProtocolError::Deserialization("Insufficient data length".to_string()));
-        }
+        ExecutingJobInfoLayout::decode(data, encoding).map(Self::from)
+    }
+}
+
+/// Attributes 1-4: job name, line number, step number and speed override
+impl AttributeDeserialize for ExecutingJobInfo {
+    const ATTRIBUTES: &'static [WireField] = ExecutingJobInfoLayout::FIELDS;
 
-        // Extract job name (32 bytes, null-terminated) and decode with specified encoding
-        let name_end = data[0..32].iter().position(|&b| b == 0).unwrap_or(32);
-        let name_bytes = &data[0..name_end];
-        let job_name = crate::encoding_utils::decode_string_with_fallback(name_bytes, encoding);
+    fn deserialize_all(
+        data: &[u8],
+        encoding: crate::encoding::TextEncoding,
+    ) -> Result<Self, ProtocolError> {
+        Self::deserialize(data, encoding)
+    }
+}
 
-        // Extract line number (4 bytes)
-        let line_number = u32::from_le_bytes([data[32], data[33], data[34], data[35]]);
+impl HsesPayload for ExecutingJobInfo {
+    fn serialize(&self, encoding: crate::encoding::TextEncoding) -> Result<Vec<u8>, ProtocolError> {
+        self.serialize_complete(encoding)
+    }
 
-        // Extract step number (4 bytes)
-        let step_number = u32::from_le_bytes([data[36], data[37], data[38], data[39]]);
+    fn deserialize(
+        data: &[u8],
+        encoding: crate::encoding::TextEncoding,
+    ) -> Result<Self, ProtocolError> {
+        Self::deserialize(data, encoding)
+    }
+}
 
-        // Extract speed override value (4 bytes) and convert from 0.01% to %
-        let raw_speed_override_value = u32::from_le_bytes([data[40], data[41], data[42], data[43]]);
-        let speed_override_value = raw_speed_override_value / 100;
+/// Job and line selected for execution in the master task
+///
+/// HSES has no read of the job selection itself; the job name and line number lead the
+/// executing job information (0x73, instance 1), so a complete 0x73 response decodes to
+/// the selected job.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub struct SelectedJob {
+    pub job_name: String,
+    pub line_number: u32,
+}
 
-        Ok(Self { job_name, line_number, step_number, speed_override_value })
+impl SelectedJob {
+    #[must_use]
+    pub const fn new(job_name: String, line_number: u32) -> Self {
+        Self { job_name, line_number }
     }
 
-    /// Deserialize job info data from response for specific attribute
+    /// Deserialize the selected job like [`HsesPayload::deserialize`], also reporting
+    /// whether the job name was not valid in `encoding` and was decoded with replacement
+    /// characters
     ///
     /// # Errors
     ///
-    /// Returns an error if deserialization fails
-    pub fn deserialize_attribute(
+    /// Returns an error if the data is shorter than the 36 bytes of the job name and line
+    pub fn deserialize_with_replacements(
         data: &[u8],
-        attribute: u8,
         encoding: crate::encoding::TextEncoding,
-    ) -> Result<Self, ProtocolError> {
-        match attribute {
-            1 => {
-                // Job name (32 bytes)
-                if data.len() < 32 {
-                    return Err(ProtocolError::Deserialization(
-                        "Insufficient data length for job name".to_string(),
-                    ));
-                }
-                let name_end = data[0..32].iter().position(|&b| b == 0).unwrap_or(32);
-                let name_bytes = &data[0..name_end];
-                let job_name =
-                    crate::encoding_utils::decode_string_with_fallback(name_bytes, encoding);
-                Ok(Self { job_name, line_number: 0, step_number: 0, speed_override_value: 0 })
-            }
-            2 => {
-                // Line number (4 bytes)
-                if data.len() < 4 {
-                    return Err(ProtocolError::Deserialization(
-                        "Insufficient data length for line number".to_string(),
-                    ));
-                }
-                let line_number = u32::from_le_bytes([data[0], data[1], data[2], data[3]]);
-                Ok(Self {
-                    job_name: String::new(),
-                    line_number,
-                    step_number: 0,
-                    speed_override_value: 0,
-                })
-            }
-            3 => {
-                // Step number (4 bytes)
-                if data.len() < 4 {
-                    return Err(ProtocolError::Deserialization(
-                        "Insufficient data length for step number".to_string(),
-                    ));
-                }
-                let step_number = u32::from_le_bytes([data[0], data[1], data[2], data[3]]);
-                Ok(Self {
-                    job_name: String::new(),
-                    line_number: 0,
-                    step_number,
-                    speed_override_value: 0,
-                })
-            }
-            4 => {
-                // Speed override value (4 bytes) and convert from 0.01% to %
-                if data.len() < 4 {
-                    return Err(ProtocolError::Deserialization(
-                        "Insufficient data length for speed override value".to_string(),
-                    ));
-                }
-                let raw_speed_override_value =
-                    u32::from_le_bytes([data[0], data[1], data[2], data[3]]);
-                let speed_override_value = raw_speed_override_value / 100;
-                Ok(Self {
-                    job_name: String::new(),
-                    line_number: 0,
-                    step_number: 0,
-                    speed_override_value,
-                })
-            }
-            _ => {
-                // Default to complete deserialization
-                Self::deserialize(data, encoding)
-            }
-        }
+    ) -> Result<(Self, bool), ProtocolError> {
+        SelectedJobLayout::decode_with_replacements(data, encoding).map(|(layout, replaced)| {
+            (Self { job_name: layout.job_name, line_number: layout.line_number }, replaced)
+        })
     }
 }
 
-impl HsesPayload for ExecutingJobInfo {
+wire_layout! {
+    /// Job name and line number as they lead the 0x73 data and make up the 0x87 data
+    pub struct SelectedJobLayout, size = 36 {
+        job_name: text(32),
+        line_number: u32,
+    }
+}
+
+impl HsesPayload for SelectedJob {
     fn serialize(&self, encoding: crate::encoding::TextEncoding) -> Result<Vec<u8>, ProtocolError> {
-        self.serialize_complete(encoding)
+        let layout =
+            SelectedJobLayout { job_name: self.job_name.clone(), line_number: self.line_number };
+        Ok(layout.encode(encoding))
     }
 
     fn deserialize(
         data: &[u8],
         encoding: crate::encoding::TextEncoding,
     ) -> Result<Self, ProtocolError> {
-        Self::deserialize(data, encoding)
+        Self::deserialize_with_replacements(data, encoding).map(|(job, _)| job)
     }
 }
 
@@ -247,6 +214,23 @@ mod tests {
         assert_eq!(job_info.speed_override_value, 80);
     }
 
+    #[test]
+    fn test_executing_job_info_layout() {
+        let offsets: Vec<_> = ExecutingJobInfoLayout::FIELDS
+            .iter()
+            .map(|field| (field.name, field.offset, field.size))
+            .collect();
+        assert_eq!(
+            offsets,
+            vec![
+                ("job_name", 0, 32),
+                ("line_number", 32, 4),
+                ("step_number", 36, 4),
+                ("speed_override", 40, 4)
+            ]
+        );
+    }
+
     #[test]
     #[allow(clippy::unwrap_used)]
     fn test_executing_job_info_default() {
@@ -422,4 +406,33 @@ mod tests {
         assert!(name_str.len() <= 32);
         assert!(name_str.starts_with("This is a very long job name"));
     }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn test_selected_job_round_trip() {
+        let job = SelectedJob::new("テスト.JOB".to_string(), 42);
+        let data = job.serialize(TextEncoding::ShiftJis).unwrap();
+        assert_eq!(data.len(), 36);
+        assert_eq!(&data[32..36], &[42, 0, 0, 0]);
+        assert_eq!(SelectedJob::deserialize(&data, TextEncoding::ShiftJis).unwrap(), job);
+        assert!(SelectedJob::deserialize(&data[..35], TextEncoding::ShiftJis).is_err());
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn test_selected_job_from_executing_job_info() {
+        let info = ExecutingJobInfo::new("テスト.JOB".to_string(), 7, 3, 50);
+        let data = info.serialize_complete(TextEncoding::ShiftJis).unwrap();
+
+        let (job, replaced) =
+            SelectedJob::deserialize_with_replacements(&data, TextEncoding::ShiftJis).unwrap();
+        assert_eq!(job, SelectedJob::new("テスト.JOB".to_string(), 7));
+        assert!(!replaced);
+
+        // Decoding the Shift-JIS name as UTF-8 replaces its characters
+        let (job, replaced) =
+            SelectedJob::deserialize_with_replacements(&data, TextEncoding::Utf8).unwrap();
+        assert_ne!(job.job_name, "テスト.JOB");
+        assert!(replaced);
+    }
 }
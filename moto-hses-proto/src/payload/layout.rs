@@ -0,0 +1,231 @@
+//! Declarative layouts of fixed-size payloads
+//!
+//! [`wire_layout!`] describes a payload as its fields in wire order and generates a struct
+//! holding the raw field values with `encode` and `decode`, so that offsets are computed
+//! from the field sizes rather than written out by hand. A mismatch between the sum of the
+//! field sizes and the declared size fails to compile:
+//!
+//! ```ignore
+//! wire_layout! {
+//!     /// Executing job information (0x73)
+//!     pub struct ExecutingJobInfoLayout, size = 44 {
+//!         job_name: text(32),
+//!         line_number: u32,
+//!         step_number: u32,
+//!         speed_override: u32,
+//!     }
+//! }
+//! ```
+//!
+//! Integers are little endian. Text fields are encoded with the text encoding of the
+//! connection, truncated or null-padded to their size, and decoded up to the first null;
+//! `decode_with_replacements` also reports text that was not valid in that encoding.
+//! Conversions such as units and bit fields stay with the payload type, which converts
+//! from and to its layout.
+
+/// Field of a payload layout
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WireField {
+    pub name: &'static str,
+    /// Offset from the start of the payload [bytes]
+    pub offset: usize,
+    /// Size on the wire [bytes]
+    pub size: usize,
+}
+
+impl WireField {
+    /// Byte range of the field in the payload
+    #[must_use]
+    pub const fn range(&self) -> std::ops::Range<usize> {
+        self.offset..self.offset + self.size
+    }
+}
+
+// Fields at consecutive offsets from their names and sizes
+#[doc(hidden)]
+#[must_use]
+pub const fn wire_fields<const N: usize>(fields: [(&'static str, usize); N]) -> [WireField; N] {
+    let mut result = [WireField { name: "", offset: 0, size: 0 }; N];
+    let mut offset = 0;
+    let mut index = 0;
+    while index < N {
+        let (name, size) = fields[index];
+        result[index] = WireField { name, offset, size };
+        offset += size;
+        index += 1;
+    }
+    result
+}
+
+/// Declare the layout of a fixed-size payload
+///
+/// Field kinds are `u8`, `u16`, `u32`, `i16`, `i32` and `text(size)`.
+macro_rules! wire_layout {
+    (
+        $(#[$meta:meta])*
+        $vis:vis struct $name:ident, size = $size:literal {
+            $( $(#[$field_meta:meta])* $field:ident : $kind:ident $( ($len:literal) )? ),+ $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, PartialEq, Eq, Default)]
+        $vis struct $name {
+            $( $(#[$field_meta])* pub $field: wire_layout!(@type $kind $( ($len) )?), )+
+        }
+
+        const _: () = assert!(
+            0 $( + wire_layout!(@size $kind $( ($len) )?) )+ == $size,
+            concat!("field sizes of ", stringify!($name), " do not add up to ", stringify!($size)),
+        );
+
+        impl $name {
+            /// Size on the wire [bytes]
+            pub const SIZE: usize = $size;
+
+            /// Name, offset and size of each field, in wire order
+            pub const FIELDS: &'static [$crate::payload::layout::WireField] =
+                &$crate::payload::layout::wire_fields([
+                    $( (stringify!($field), wire_layout!(@size $kind $( ($len) )?)) ),+
+                ]);
+
+            /// Encode the fields in wire order
+            #[must_use]
+            #[allow(unused_variables)] // Layouts without text fields
+            pub fn encode(&self, encoding: $crate::encoding::TextEncoding) -> Vec<u8> {
+                let mut data = Vec::with_capacity(Self::SIZE);
+                $( wire_layout!(@encode $kind $( ($len) )?, &self.$field, encoding, data); )+
+                data
+            }
+
+            /// Decode the fields from the start of `data`; bytes after the payload are ignored
+            ///
+            /// # Errors
+            ///
+            /// Returns `ProtocolError::Deserialization` if `data` is shorter than the payload
+            pub fn decode(
+                data: &[u8],
+                encoding: $crate::encoding::TextEncoding,
+            ) -> Result<Self, $crate::error::ProtocolError> {
+                Self::decode_with_replacements(data, encoding).map(|(layout, _)| layout)
+            }
+
+            /// Decode like `decode`, also reporting whether a text field was not valid in
+            /// `encoding` and was decoded with replacement characters
+            ///
+            /// # Errors
+            ///
+            /// Returns `ProtocolError::Deserialization` if `data` is shorter than the payload
+            #[allow(unused_variables, unused_mut)] // Layouts without text fields
+            pub fn decode_with_replacements(
+                data: &[u8],
+                encoding: $crate::encoding::TextEncoding,
+            ) -> Result<(Self, bool), $crate::error::ProtocolError> {
+                if data.len() < Self::SIZE {
+                    return Err($crate::error::ProtocolError::Deserialization(format!(
+                        "Insufficient data length for {}: expected {}, got {}",
+                        stringify!($name),
+                        Self::SIZE,
+                        data.len()
+                    )));
+                }
+                let mut rest = data;
+                let mut take = |size: usize| {
+                    let (field, tail) = rest.split_at(size);
+                    rest = tail;
+                    field
+                };
+                let mut replaced = false;
+                let layout = Self {
+                    $( $field: {
+                        let field = take(wire_layout!(@size $kind $( ($len) )?));
+                        wire_layout!(@decode $kind $( ($len) )?, field, encoding, replaced)
+                    }, )+
+                };
+                Ok((layout, replaced))
+            }
+        }
+    };
+
+    (@type text($len:literal)) => { String };
+    (@type $int:ident) => { $int };
+
+    (@size text($len:literal)) => { $len };
+    (@size $int:ident) => { ::std::mem::size_of::<$int>() };
+
+    (@encode text($len:literal), $value:expr, $encoding:ident, $data:ident) => {{
+        let mut field = $crate::encoding_utils::encode_string($value, $encoding);
+        field.resize($len, 0);
+        $data.extend_from_slice(&field);
+    }};
+    (@encode $int:ident, $value:expr, $encoding:ident, $data:ident) => {
+        $data.extend_from_slice(&$value.to_le_bytes())
+    };
+
+    (@decode text($len:literal), $field:ident, $encoding:ident, $replaced:ident) => {{
+        let end = $field.iter().position(|&b| b == 0).unwrap_or($len);
+        let (text, had_replacements) =
+            $crate::encoding_utils::decode_string_with_replacements(&$field[..end], $encoding);
+        $replaced |= had_replacements;
+        text
+    }};
+    (@decode $int:ident, $field:ident, $encoding:ident, $replaced:ident) => {{
+        let mut bytes = [0u8; ::std::mem::size_of::<$int>()];
+        bytes.copy_from_slice($field);
+        $int::from_le_bytes(bytes)
+    }};
+}
+
+pub(crate) use wire_layout;
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use crate::encoding::TextEncoding;
+    use crate::error::ProtocolError;
+
+    wire_layout! {
+        struct SampleLayout, size = 12 {
+            flags: u16,
+            value: i32,
+            label: text(6),
+        }
+    }
+
+    #[test]
+    fn test_layout_offsets() {
+        let offsets: Vec<_> =
+            SampleLayout::FIELDS.iter().map(|field| (field.name, field.offset)).collect();
+        assert_eq!(offsets, vec![("flags", 0), ("value", 2), ("label", 6)]);
+        assert_eq!(SampleLayout::FIELDS[2].range(), 6..12);
+    }
+
+    #[test]
+    fn test_layout_round_trip() {
+        let sample = SampleLayout { flags: 0x0102, value: -2, label: "ABCDEFGH".to_string() };
+        let data = sample.encode(TextEncoding::Utf8);
+        assert_eq!(data, [0x02, 0x01, 0xFE, 0xFF, 0xFF, 0xFF, b'A', b'B', b'C', b'D', b'E', b'F']);
+
+        let decoded = SampleLayout::decode(&data, TextEncoding::Utf8).unwrap();
+        assert_eq!(decoded.label, "ABCDEF");
+        assert_eq!(decoded.value, -2);
+        assert!(!SampleLayout::decode_with_replacements(&data, TextEncoding::Utf8).unwrap().1);
+        assert!(matches!(
+            SampleLayout::decode(&data[..11], TextEncoding::Utf8),
+            Err(ProtocolError::Deserialization(_))
+        ));
+    }
+
+    #[test]
+    fn test_layout_reports_replacements() {
+        // "テスト" in Shift_JIS encoding, not valid UTF-8
+        let data = [0, 0, 0, 0, 0, 0, 0x83, 0x65, 0x83, 0x58, 0x83, 0x67];
+        let (sample, replaced) =
+            SampleLayout::decode_with_replacements(&data, TextEncoding::Utf8).unwrap();
+        assert!(replaced);
+        assert!(sample.label.contains('\u{FFFD}'));
+        let (sample, replaced) =
+            SampleLayout::decode_with_replacements(&data, TextEncoding::ShiftJis).unwrap();
+        assert!(!replaced);
+        assert_eq!(sample.label, "テスト");
+    }
+}
@@ -2,14 +2,22 @@
 
 pub mod alarm;
 pub mod job;
+pub mod layout;
 pub mod payload_trait;
+#[cfg(any(feature = "nalgebra", feature = "glam"))]
+pub mod pose;
 pub mod position;
 pub mod status;
+pub mod system_info;
 pub mod variable;
 
 // Re-export commonly used payload types
-pub use alarm::Alarm;
-pub use job::ExecutingJobInfo;
-pub use payload_trait::HsesPayload;
-pub use position::{CartesianPosition, Position, PulsePosition};
-pub use status::{Status, StatusData1, StatusData2};
+pub use alarm::{Alarm, AlarmLayout, serialize_alarm_response};
+pub use job::{ExecutingJobInfo, ExecutingJobInfoLayout, SelectedJob, SelectedJobLayout};
+pub use layout::WireField;
+pub use payload_trait::{AttributeDeserialize, HsesPayload};
+pub use position::{
+    BasePosition, BasePositionType, CartesianPosition, Position, PulsePosition, StationPosition,
+};
+pub use status::{Status, StatusData1, StatusData2, StatusLayout};
+pub use system_info::{SystemInfo, SystemInfoLayout};
@@ -1,6 +1,7 @@
 //! Payload trait for HSES protocol data types
 
 use crate::error::ProtocolError;
+use crate::payload::layout::WireField;
 
 /// Trait for HSES protocol payload data types
 pub trait HsesPayload: Send + Sync + 'static {
@@ -20,3 +21,70 @@ pub trait HsesPayload: Send + Sync + 'static {
     where
         Self: Sized;
 }
+
+/// Payload whose attributes can also be read one at a time (`Get_Attribute_Single`)
+///
+/// The data of single attribute reads is placed at the offsets of their fields in the
+/// complete payload, so the fields of attributes not read are left empty or 0.
+pub trait AttributeDeserialize: Sized {
+    /// Fields of the complete payload, one per attribute from attribute 1
+    const ATTRIBUTES: &'static [WireField];
+
+    /// Deserialize the complete payload, as read with attribute 0
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the data is too short or invalid
+    fn deserialize_all(
+        data: &[u8],
+        encoding: crate::encoding::TextEncoding,
+    ) -> Result<Self, ProtocolError>;
+
+    /// Deserialize the data of a read of `attribute`; attribute 0 is the complete payload
+    ///
+    /// # Errors
+    ///
+    /// Returns `ProtocolError::InvalidAttribute` if the payload has no such attribute, or
+    /// an error if the data is too short or invalid
+    fn deserialize_attribute(
+        data: &[u8],
+        attribute: u8,
+        encoding: crate::encoding::TextEncoding,
+    ) -> Result<Self, ProtocolError> {
+        if attribute == 0 {
+            return Self::deserialize_all(data, encoding);
+        }
+        Self::deserialize_attributes(&[(attribute, data)], encoding)
+    }
+
+    /// Deserialize the data of single reads of several attributes, given with their
+    /// attributes
+    ///
+    /// # Errors
+    ///
+    /// Returns `ProtocolError::InvalidAttribute` if the payload has no such attribute, or
+    /// an error if the data is too short or invalid
+    fn deserialize_attributes(
+        reads: &[(u8, &[u8])],
+        encoding: crate::encoding::TextEncoding,
+    ) -> Result<Self, ProtocolError> {
+        let size = Self::ATTRIBUTES.last().map_or(0, |field| field.range().end);
+        let mut complete = vec![0u8; size];
+        for &(attribute, data) in reads {
+            let field = usize::from(attribute)
+                .checked_sub(1)
+                .and_then(|index| Self::ATTRIBUTES.get(index))
+                .ok_or(ProtocolError::InvalidAttribute)?;
+            let value = data.get(..field.size).ok_or_else(|| {
+                ProtocolError::Deserialization(format!(
+                    "Insufficient data length for {}: expected {}, got {}",
+                    field.name,
+                    field.size,
+                    data.len()
+                ))
+            })?;
+            complete[field.range()].copy_from_slice(value);
+        }
+        Self::deserialize_all(&complete, encoding)
+    }
+}
@@ -0,0 +1,158 @@
+//! Conversions between Cartesian positions and the rigid transforms of math libraries
+//!
+//! With the `nalgebra` feature, [`CartesianPosition`] converts to and from
+//! `nalgebra::Isometry3<f64>`, and with the `glam` feature to and from `glam::DAffine3`.
+//!
+//! The translation stays in mm. The angles follow the controller convention: Rx, Ry and
+//! Rz rotate about the fixed X, Y and Z axes of the coordinate system in this order, i.e.
+//! the rotation is `Rz(rz) * Ry(ry) * Rx(rx)`. Converting back yields Ry in
+//! [-90, 90] degrees and Rx and Rz in [-180, 180] degrees.
+//!
+//! A transform has no tool, user frame or configuration; positions converted from a
+//! transform have tool 0, user frame 0 and configuration 0, to be set before moving to
+//! them.
+
+use super::position::{CartesianPosition, Configuration, ExtendedConfiguration};
+
+// Position at a pose given in mm and radians, with tool, user frame and configuration 0
+#[allow(clippy::cast_possible_truncation)] // Positions are sent as f32 anyway
+fn from_pose(translation: [f64; 3], angles: (f64, f64, f64)) -> CartesianPosition {
+    let [x, y, z] = translation;
+    let [rx, ry, rz] = <[f64; 3]>::from(angles).map(f64::to_degrees);
+    CartesianPosition::new(
+        x as f32,
+        y as f32,
+        z as f32,
+        rx as f32,
+        ry as f32,
+        rz as f32,
+        0,
+        0,
+        Configuration::from_raw(0),
+        ExtendedConfiguration::from_raw(0),
+    )
+}
+
+// Translation in mm and Rx, Ry, Rz in radians
+fn pose(position: &CartesianPosition) -> ([f64; 3], [f64; 3]) {
+    let translation = [position.x, position.y, position.z].map(f64::from);
+    let angles = [position.rx, position.ry, position.rz].map(|angle| f64::from(angle).to_radians());
+    (translation, angles)
+}
+
+#[cfg(feature = "nalgebra")]
+mod nalgebra_conversions {
+    use nalgebra::{Isometry3, Translation3, UnitQuaternion};
+
+    use super::{from_pose, pose};
+    use crate::payload::position::CartesianPosition;
+
+    impl From<&CartesianPosition> for Isometry3<f64> {
+        fn from(position: &CartesianPosition) -> Self {
+            let ([x, y, z], [rx, ry, rz]) = pose(position);
+            // Roll, pitch and yaw of nalgebra are Rx, Ry and Rz about the fixed axes
+            Self::from_parts(
+                Translation3::new(x, y, z),
+                UnitQuaternion::from_euler_angles(rx, ry, rz),
+            )
+        }
+    }
+
+    impl From<CartesianPosition> for Isometry3<f64> {
+        fn from(position: CartesianPosition) -> Self {
+            Self::from(&position)
+        }
+    }
+
+    impl From<Isometry3<f64>> for CartesianPosition {
+        fn from(isometry: Isometry3<f64>) -> Self {
+            let translation = isometry.translation.vector;
+            from_pose(translation.into(), isometry.rotation.euler_angles())
+        }
+    }
+}
+
+#[cfg(feature = "glam")]
+mod glam_conversions {
+    use glam::{DAffine3, DQuat, DVec3, EulerRot};
+
+    use super::{from_pose, pose};
+    use crate::payload::position::CartesianPosition;
+
+    impl From<&CartesianPosition> for DAffine3 {
+        fn from(position: &CartesianPosition) -> Self {
+            let (translation, [rx, ry, rz]) = pose(position);
+            // Intrinsic Z, Y', X'' is the same rotation as extrinsic X, Y, Z
+            let rotation = DQuat::from_euler(EulerRot::ZYX, rz, ry, rx);
+            Self::from_rotation_translation(rotation, DVec3::from_array(translation))
+        }
+    }
+
+    impl From<CartesianPosition> for DAffine3 {
+        fn from(position: CartesianPosition) -> Self {
+            Self::from(&position)
+        }
+    }
+
+    /// The transform is taken to be rigid; a scale is dropped
+    impl From<DAffine3> for CartesianPosition {
+        fn from(affine: DAffine3) -> Self {
+            let (_, rotation, translation) = affine.to_scale_rotation_translation();
+            let (rz, ry, rx) = rotation.to_euler(EulerRot::ZYX);
+            from_pose(translation.to_array(), (rx, ry, rz))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn position() -> CartesianPosition {
+        CartesianPosition::new(
+            350.5,
+            -120.25,
+            410.0,
+            90.0,
+            0.0,
+            90.0,
+            0,
+            0,
+            Configuration::from_raw(0),
+            ExtendedConfiguration::from_raw(0),
+        )
+    }
+
+    fn assert_close(actual: &CartesianPosition, expected: &CartesianPosition) {
+        let values = |p: &CartesianPosition| [p.x, p.y, p.z, p.rx, p.ry, p.rz];
+        for (a, e) in values(actual).into_iter().zip(values(expected)) {
+            assert!((a - e).abs() < 1e-3, "{actual:?} != {expected:?}");
+        }
+    }
+
+    #[cfg(feature = "nalgebra")]
+    #[test]
+    fn test_nalgebra_conversion() {
+        use nalgebra::{Isometry3, Point3, Vector3};
+
+        let isometry = Isometry3::from(position());
+        // Rx(90) turns Y into Z, which Rz(90) keeps; the other order would give -X
+        let y = isometry.rotation * Vector3::y();
+        assert!((y - Vector3::z()).norm() < 1e-9, "{y}");
+        let origin = isometry * Point3::origin();
+        assert!((origin - Point3::new(350.5, -120.25, 410.0)).norm() < 1e-4);
+        assert_close(&CartesianPosition::from(isometry), &position());
+    }
+
+    #[cfg(feature = "glam")]
+    #[test]
+    fn test_glam_conversion() {
+        use glam::{DAffine3, DVec3};
+
+        let affine = DAffine3::from(position());
+        let y = affine.transform_vector3(DVec3::Y);
+        assert!(y.abs_diff_eq(DVec3::Z, 1e-9), "{y}");
+        assert!(affine.translation.abs_diff_eq(DVec3::new(350.5, -120.25, 410.0), 1e-4));
+        assert_close(&CartesianPosition::from(affine), &position());
+    }
+}
@@ -126,6 +126,9 @@ pub struct Configuration {
     pub ik_solution_basis: IkSolutionBasis,
 }
 
+/// Bits 5-7 of the extended configuration, reserved
+pub const EXTENDED_CONFIGURATION_RESERVED_BITS: u8 = 0xE0;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct ExtendedConfiguration {
     pub bit0: LAxisTurnNum,
@@ -170,6 +173,24 @@ impl Configuration {
         }
     }
 
+    /// Whether the S axis faces the front
+    #[must_use]
+    pub const fn front(self) -> bool {
+        matches!(self.s_placement, SAxisPlacement::Front)
+    }
+
+    /// Whether the upper arm is above the lower arm (elbow up)
+    #[must_use]
+    pub const fn up(self) -> bool {
+        matches!(self.u_placement, UAxisPlacement::Up)
+    }
+
+    /// Whether the wrist is flipped
+    #[must_use]
+    pub const fn flip(self) -> bool {
+        matches!(self.b_placement, BAxisPlacement::Flip)
+    }
+
     /// Convert configuration to raw u8 value
     #[must_use]
     pub const fn to_raw(self) -> u8 {
@@ -218,6 +239,12 @@ impl ExtendedConfiguration {
         }
     }
 
+    /// Reserved bits that are set; 0 for a valid extended configuration
+    #[must_use]
+    pub const fn reserved_bits(self) -> u8 {
+        self.to_raw() & EXTENDED_CONFIGURATION_RESERVED_BITS
+    }
+
     /// Convert `ExtendedConfiguration` to raw u8 value
     #[must_use]
     pub const fn to_raw(self) -> u8 {
@@ -250,6 +277,57 @@ impl ExtendedConfiguration {
     }
 }
 
+// Turn number of an axis, as shown on the pendant
+const fn turn(double: bool) -> &'static str {
+    if double { "≥180" } else { "<180" }
+}
+
+/// Arm configuration, e.g. "Front, Up, Flip, R<180, T<180, S<180"
+///
+/// A back redundant S axis and the configuration taking priority over the previous step
+/// are added when set.
+impl std::fmt::Display for Configuration {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}, {}, {}, R{}, T{}, S{}",
+            if self.front() { "Front" } else { "Back" },
+            if self.up() { "Up" } else { "Down" },
+            if self.flip() { "Flip" } else { "No-flip" },
+            turn(matches!(self.r_turn_num, RAxisTurnNum::Double)),
+            turn(matches!(self.t_turn_num, TAxisTurnNum::Double)),
+            turn(matches!(self.s_turn_num, SAxisTurnNum::Double)),
+        )?;
+        if matches!(self.redundant_s_placement, RedundantSAxisPlacement::Back) {
+            f.write_str(", redundant S Back")?;
+        }
+        if matches!(self.ik_solution_basis, IkSolutionBasis::Configuration) {
+            f.write_str(", configuration priority")?;
+        }
+        Ok(())
+    }
+}
+
+/// Turn numbers, e.g. "L<180, U<180, B<180, E<180, W<180", followed by any reserved
+/// bits that are set
+impl std::fmt::Display for ExtendedConfiguration {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "L{}, U{}, B{}, E{}, W{}",
+            turn(matches!(self.bit0, LAxisTurnNum::Double)),
+            turn(matches!(self.bit1, UAxisTurnNum::Double)),
+            turn(matches!(self.bit2, BAxisTurnNum::Double)),
+            turn(matches!(self.bit3, EAxisTurnNum::Double)),
+            turn(matches!(self.bit4, WAxisTurnNum::Double)),
+        )?;
+        if self.reserved_bits() != 0 {
+            write!(f, ", reserved bits 0x{:02X}", self.reserved_bits())?;
+        }
+        Ok(())
+    }
+}
+
 // Position data structures
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct PulsePosition {
@@ -265,6 +343,7 @@ impl PulsePosition {
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
 pub struct CartesianPosition {
     // X [mm]
     pub x: f32,
@@ -286,6 +365,9 @@ pub struct CartesianPosition {
     pub configuration: Configuration,
     // Extended configuration
     pub extended_configuration: ExtendedConfiguration,
+    /// 7th and 8th axis data as sent by the controller, e.g. the elbow angle of a 7-axis
+    /// robot [0.0001deg]; written back unchanged
+    pub extra_axes: [i32; 2],
 }
 
 impl CartesianPosition {
@@ -303,7 +385,19 @@ impl CartesianPosition {
         configuration: Configuration,
         extended_configuration: ExtendedConfiguration,
     ) -> Self {
-        Self { x, y, z, rx, ry, rz, tool_no, user_coord_no, configuration, extended_configuration }
+        Self {
+            x,
+            y,
+            z,
+            rx,
+            ry,
+            rz,
+            tool_no,
+            user_coord_no,
+            configuration,
+            extended_configuration,
+            extra_axes: [0; 2],
+        }
     }
 }
 
@@ -313,12 +407,11 @@ pub enum Position {
     Cartesian(CartesianPosition),
 }
 
-impl Position {
-    /// Serialize position to byte data
-    ///
-    /// # Errors
-    /// Returns `ProtocolError::PositionError` if serialization fails
-    pub fn serialize(&self) -> Result<Vec<u8>, ProtocolError> {
+impl HsesPayload for Position {
+    fn serialize(
+        &self,
+        _encoding: crate::encoding::TextEncoding,
+    ) -> Result<Vec<u8>, ProtocolError> {
         let mut data = Vec::new();
 
         match self {
@@ -354,18 +447,18 @@ impl Position {
                     data.extend_from_slice(&((cart.ry * 10000.0) as i32).to_le_bytes()); // deg to 0.0001deg
                     data.extend_from_slice(&((cart.rz * 10000.0) as i32).to_le_bytes()); // deg to 0.0001deg
                 }
+                for axis in cart.extra_axes {
+                    data.extend_from_slice(&axis.to_le_bytes());
+                }
             }
         }
 
         Ok(data)
     }
 
-    /// Deserialize position from byte data
-    ///
-    /// # Errors
-    /// Returns `ProtocolError::Underflow` if data is insufficient
-    /// Returns `ProtocolError::PositionError` if data format is invalid
-    pub fn deserialize(
+    /// Returns `ProtocolError::Underflow` if data is insufficient and
+    /// `ProtocolError::PositionError` if the position type is unknown
+    fn deserialize(
         data: &[u8],
         _encoding: crate::encoding::TextEncoding,
     ) -> Result<Self, ProtocolError> {
@@ -448,18 +541,30 @@ impl Position {
                 #[allow(clippy::cast_precision_loss)]
                 let rz = coord_buf.get_i32_le() as f32 / 10000.0; // 0.0001deg to deg
 
-                Ok(Self::Cartesian(CartesianPosition::new(
-                    x,
-                    y,
-                    z,
-                    rx,
-                    ry,
-                    rz,
-                    tool_no,
-                    user_coord_no,
-                    configuration,
-                    extended_configuration,
-                )))
+                // 7th and 8th axis data, missing from the 44-byte form
+                let mut extra_buf = &data[44..];
+                let mut extra_axes = [0; 2];
+                for axis in &mut extra_axes {
+                    if extra_buf.remaining() >= 4 {
+                        *axis = extra_buf.get_i32_le();
+                    }
+                }
+
+                Ok(Self::Cartesian(CartesianPosition {
+                    extra_axes,
+                    ..CartesianPosition::new(
+                        x,
+                        y,
+                        z,
+                        rx,
+                        ry,
+                        rz,
+                        tool_no,
+                        user_coord_no,
+                        configuration,
+                        extended_configuration,
+                    )
+                }))
             }
             _ => {
                 Err(ProtocolError::PositionError(format!("Unknown position type: {position_type}")))
@@ -468,18 +573,187 @@ impl Position {
     }
 }
 
-impl HsesPayload for Position {
+// Pulse and Cartesian positions use the position data layout, tagged with their own type;
+// data of the other type is rejected
+impl HsesPayload for PulsePosition {
+    fn serialize(&self, encoding: crate::encoding::TextEncoding) -> Result<Vec<u8>, ProtocolError> {
+        Position::Pulse(self.clone()).serialize(encoding)
+    }
+
+    fn deserialize(
+        data: &[u8],
+        encoding: crate::encoding::TextEncoding,
+    ) -> Result<Self, ProtocolError> {
+        match Position::deserialize(data, encoding)? {
+            Position::Pulse(pulse) => Ok(pulse),
+            Position::Cartesian(_) => Err(ProtocolError::PositionError(
+                "expected a pulse position, got a Cartesian position".to_string(),
+            )),
+        }
+    }
+}
+
+impl HsesPayload for CartesianPosition {
+    fn serialize(&self, encoding: crate::encoding::TextEncoding) -> Result<Vec<u8>, ProtocolError> {
+        Position::Cartesian(self.clone()).serialize(encoding)
+    }
+
+    fn deserialize(
+        data: &[u8],
+        encoding: crate::encoding::TextEncoding,
+    ) -> Result<Self, ProtocolError> {
+        match Position::deserialize(data, encoding)? {
+            Position::Cartesian(cart) => Ok(cart),
+            Position::Pulse(_) => Err(ProtocolError::PositionError(
+                "expected a Cartesian position, got a pulse position".to_string(),
+            )),
+        }
+    }
+}
+
+/// Data type of a base position variable (BP)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum BasePositionType {
+    /// Base axis pulses
+    #[default]
+    Pulse,
+    /// Base axis positions in the base coordinate system [μm]
+    Base,
+}
+
+/// Number of axes of a base position variable (BP)
+pub const BASE_POSITION_AXES: usize = 8;
+
+/// Size of the data of a base position variable (BP): the data type and 8 axes
+pub const BASE_POSITION_SIZE: usize = 36;
+
+/// Base position variable (BP): the positions of the base axes of a control group, e.g. a
+/// traveling axis
+///
+/// Unlike a robot position, the data has no configuration, tool or user coordinate words,
+/// only the data type followed by the 8 axes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BasePosition {
+    pub data_type: BasePositionType,
+    /// Axis data, in pulses or μm by `data_type`; unused axes are 0
+    pub axes: [i32; BASE_POSITION_AXES],
+}
+
+impl BasePosition {
+    /// Base position of axis pulses
+    #[must_use]
+    pub const fn pulse(axes: [i32; BASE_POSITION_AXES]) -> Self {
+        Self { data_type: BasePositionType::Pulse, axes }
+    }
+
+    /// Base position in the base coordinate system [μm]
+    #[must_use]
+    pub const fn base(axes: [i32; BASE_POSITION_AXES]) -> Self {
+        Self { data_type: BasePositionType::Base, axes }
+    }
+}
+
+impl HsesPayload for BasePosition {
     fn serialize(
         &self,
         _encoding: crate::encoding::TextEncoding,
     ) -> Result<Vec<u8>, ProtocolError> {
-        self.serialize()
+        let data_type: u32 = match self.data_type {
+            BasePositionType::Pulse => 0,
+            BasePositionType::Base => 16,
+        };
+        let mut data = Vec::with_capacity(BASE_POSITION_SIZE);
+        data.extend_from_slice(&data_type.to_le_bytes());
+        for axis in self.axes {
+            data.extend_from_slice(&axis.to_le_bytes());
+        }
+        Ok(data)
     }
+
+    /// Returns `ProtocolError::Underflow` if data is shorter than 36 bytes and
+    /// `ProtocolError::PositionError` if the data type is unknown
     fn deserialize(
         data: &[u8],
-        encoding: crate::encoding::TextEncoding,
+        _encoding: crate::encoding::TextEncoding,
     ) -> Result<Self, ProtocolError> {
-        Self::deserialize(data, encoding)
+        if data.len() < BASE_POSITION_SIZE {
+            return Err(ProtocolError::Underflow);
+        }
+        let mut buf = data;
+        let data_type = match buf.get_u32_le() {
+            0 => BasePositionType::Pulse,
+            16 => BasePositionType::Base,
+            other => {
+                return Err(ProtocolError::PositionError(format!(
+                    "Unknown base position type: {other}"
+                )));
+            }
+        };
+        let mut axes = [0; BASE_POSITION_AXES];
+        for axis in &mut axes {
+            *axis = buf.get_i32_le();
+        }
+        Ok(Self { data_type, axes })
+    }
+}
+
+/// Number of axes of a station variable (EX)
+pub const STATION_POSITION_AXES: usize = 8;
+
+/// Size of the data of a station variable (EX): the data type and 8 axes
+pub const STATION_POSITION_SIZE: usize = 36;
+
+/// Station variable (EX): the positions of the axes of a station, e.g. a positioner
+///
+/// Stations have no Cartesian representation, so the data type word is always 0 (pulse)
+/// and the axes are in pulses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct StationPosition {
+    /// Axis pulses; unused axes are 0
+    pub axes: [i32; STATION_POSITION_AXES],
+}
+
+impl StationPosition {
+    #[must_use]
+    pub const fn new(axes: [i32; STATION_POSITION_AXES]) -> Self {
+        Self { axes }
+    }
+}
+
+impl HsesPayload for StationPosition {
+    fn serialize(
+        &self,
+        _encoding: crate::encoding::TextEncoding,
+    ) -> Result<Vec<u8>, ProtocolError> {
+        let mut data = Vec::with_capacity(STATION_POSITION_SIZE);
+        data.extend_from_slice(&0u32.to_le_bytes());
+        for axis in self.axes {
+            data.extend_from_slice(&axis.to_le_bytes());
+        }
+        Ok(data)
+    }
+
+    /// Returns `ProtocolError::Underflow` if data is shorter than 36 bytes and
+    /// `ProtocolError::PositionError` if the data type is not pulse
+    fn deserialize(
+        data: &[u8],
+        _encoding: crate::encoding::TextEncoding,
+    ) -> Result<Self, ProtocolError> {
+        if data.len() < STATION_POSITION_SIZE {
+            return Err(ProtocolError::Underflow);
+        }
+        let mut buf = data;
+        let data_type = buf.get_u32_le();
+        if data_type != 0 {
+            return Err(ProtocolError::PositionError(format!(
+                "Unknown station position type: {data_type}"
+            )));
+        }
+        let mut axes = [0; STATION_POSITION_AXES];
+        for axis in &mut axes {
+            *axis = buf.get_i32_le();
+        }
+        Ok(Self { axes })
     }
 }
 
@@ -487,6 +761,45 @@ impl HsesPayload for Position {
 mod tests {
     use super::*;
 
+    #[allow(clippy::unwrap_used)]
+    #[test]
+    fn test_base_position_round_trip() {
+        let encoding = crate::encoding::TextEncoding::Utf8;
+        let position = BasePosition::base([1_500_000, -250, 0, 0, 0, 0, 0, 7]);
+        let data = position.serialize(encoding).unwrap();
+        assert_eq!(data.len(), BASE_POSITION_SIZE);
+        assert_eq!(&data[..8], &[16, 0, 0, 0, 0x60, 0xe3, 0x16, 0x00]);
+        assert_eq!(BasePosition::deserialize(&data, encoding).unwrap(), position);
+
+        let pulse = BasePosition::pulse([100, 0, 0, 0, 0, 0, 0, 0]);
+        let data = pulse.serialize(encoding).unwrap();
+        assert_eq!(
+            BasePosition::deserialize(&data, encoding).unwrap().data_type,
+            BasePositionType::Pulse
+        );
+
+        assert!(BasePosition::deserialize(&data[..32], encoding).is_err());
+        let mut unknown = data;
+        unknown[0] = 1;
+        assert!(BasePosition::deserialize(&unknown, encoding).is_err());
+    }
+
+    #[allow(clippy::unwrap_used)]
+    #[test]
+    fn test_station_position_round_trip() {
+        let encoding = crate::encoding::TextEncoding::Utf8;
+        let position = StationPosition::new([90_000, -45_000, 0, 0, 0, 0, 0, 0]);
+        let data = position.serialize(encoding).unwrap();
+        assert_eq!(data.len(), STATION_POSITION_SIZE);
+        assert_eq!(&data[..8], &[0, 0, 0, 0, 0x90, 0x5f, 0x01, 0x00]);
+        assert_eq!(StationPosition::deserialize(&data, encoding).unwrap(), position);
+
+        assert!(StationPosition::deserialize(&data[..32], encoding).is_err());
+        let mut unknown = data;
+        unknown[0] = 16;
+        assert!(StationPosition::deserialize(&unknown, encoding).is_err());
+    }
+
     #[test]
     fn test_pulse_position_creation() {
         let joints = vec![1000, 2000, 3000, 0, 0, 0, 0, 0];
@@ -522,7 +835,7 @@ mod tests {
     #[allow(clippy::unwrap_used)]
     fn test_position_serialization() {
         let position = Position::Pulse(PulsePosition::new(vec![1000, 2000, 3000, 0, 0, 0, 0, 0]));
-        let serialized = position.serialize().unwrap();
+        let serialized = position.serialize(crate::encoding::TextEncoding::Utf8).unwrap();
         let deserialized =
             Position::deserialize(&serialized, crate::encoding::TextEncoding::Utf8).unwrap();
         assert_eq!(position, deserialized);
@@ -545,7 +858,7 @@ mod tests {
             configuration,
             extended_configuration,
         ));
-        let serialized = position.serialize().unwrap();
+        let serialized = position.serialize(crate::encoding::TextEncoding::Utf8).unwrap();
         let deserialized =
             Position::deserialize(&serialized, crate::encoding::TextEncoding::Utf8).unwrap();
         assert_eq!(position, deserialized);
@@ -553,13 +866,32 @@ mod tests {
 
     #[test]
     #[allow(clippy::unwrap_used)]
-    fn test_position_variable_type_trait() {
-        let position = Position::Pulse(PulsePosition::new(vec![1000, 2000, 3000, 0, 0, 0, 0, 0]));
+    fn test_pulse_and_cartesian_position_payloads() {
+        let encoding = crate::encoding::TextEncoding::Utf8;
+        let pulse = PulsePosition::new(vec![1000, 2000, 3000, 0, 0, 0]);
+        let serialized = pulse.serialize(encoding).unwrap();
+        assert_eq!(serialized, Position::Pulse(pulse.clone()).serialize(encoding).unwrap());
+        assert_eq!(PulsePosition::deserialize(&serialized, encoding).unwrap(), pulse);
+        assert!(matches!(
+            CartesianPosition::deserialize(&serialized, encoding),
+            Err(ProtocolError::PositionError(_))
+        ));
 
-        let serialized = position.serialize().unwrap();
-        let deserialized =
-            Position::deserialize(&serialized, crate::encoding::TextEncoding::Utf8).unwrap();
-        assert_eq!(position, deserialized);
+        let cartesian = CartesianPosition::new(
+            100.0,
+            -50.0,
+            300.0,
+            180.0,
+            0.0,
+            90.0,
+            2,
+            1,
+            Configuration::from_raw(0),
+            ExtendedConfiguration::from_raw(0),
+        );
+        let serialized = cartesian.serialize(encoding).unwrap();
+        assert_eq!(CartesianPosition::deserialize(&serialized, encoding).unwrap(), cartesian);
+        assert!(PulsePosition::deserialize(&serialized, encoding).is_err());
     }
 
     #[test]
@@ -578,6 +910,52 @@ mod tests {
         assert_eq!(raw, 0x55);
     }
 
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn test_cartesian_position_wire_round_trip() {
+        // Type, form, tool, user coordinate, extended form, X..RZ, 7th and 8th axis
+        let words: [i32; 13] =
+            [16, 0xA5, 5, 3, 0x23, 100_000, -200_000, 300_000, 1_800_000, 0, -900_000, 123_456, -7];
+        let data: Vec<u8> = words.iter().flat_map(|word| word.to_le_bytes()).collect();
+
+        let position =
+            CartesianPosition::deserialize(&data, crate::encoding::TextEncoding::Utf8).unwrap();
+        assert_eq!(position.tool_no, 5);
+        assert_eq!(position.user_coord_no, 3);
+        assert_eq!(position.configuration.to_raw(), 0xA5);
+        assert_eq!(position.extended_configuration.to_raw(), 0x23);
+        assert_eq!(position.extra_axes, [123_456, -7]);
+        assert_eq!(position.serialize(crate::encoding::TextEncoding::Utf8).unwrap(), data);
+
+        // The 44-byte form without the 7th and 8th axis is still accepted
+        let short =
+            CartesianPosition::deserialize(&data[..44], crate::encoding::TextEncoding::Utf8)
+                .unwrap();
+        assert_eq!(short.extra_axes, [0, 0]);
+    }
+
+    #[test]
+    fn test_configuration_display() {
+        let configuration = Configuration::from_raw(0x00);
+        assert!(configuration.front() && configuration.up() && configuration.flip());
+        assert_eq!(configuration.to_string(), "Front, Up, Flip, R<180, T<180, S<180");
+
+        let configuration = Configuration::from_raw(0xFF);
+        assert!(!configuration.front() && !configuration.up() && !configuration.flip());
+        assert_eq!(
+            configuration.to_string(),
+            "Back, Down, No-flip, R≥180, T≥180, S≥180, redundant S Back, configuration priority"
+        );
+
+        assert_eq!(
+            ExtendedConfiguration::from_raw(0x03).to_string(),
+            "L≥180, U≥180, B<180, E<180, W<180"
+        );
+        let extended = ExtendedConfiguration::from_raw(0x41);
+        assert_eq!(extended.reserved_bits(), 0x40);
+        assert_eq!(extended.to_string(), "L≥180, U<180, B<180, E<180, W<180, reserved bits 0x40");
+    }
+
     #[test]
     fn test_extended_configuration_serialization() {
         let extended_configuration = ExtendedConfiguration::from_raw(0x1F); // 00011111
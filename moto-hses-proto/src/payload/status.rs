@@ -1,11 +1,13 @@
 //! Status data structures and operations
 
 use crate::error::ProtocolError;
-use crate::payload::HsesPayload;
+use crate::payload::layout::{WireField, wire_layout};
+use crate::payload::{AttributeDeserialize, HsesPayload};
 use bytes::Buf;
 
 // Enhanced status structure
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
 pub struct Status {
     pub data1: StatusData1,
     pub data2: StatusData2,
@@ -18,14 +20,15 @@ impl Status {
     /// Returns `ProtocolError::Underflow` if data is insufficient
     /// Returns `ProtocolError::Deserialization` if data format is invalid
     pub fn from_bytes(data: &[u8]) -> Result<Self, ProtocolError> {
-        if data.len() < 8 {
+        if data.len() < StatusLayout::SIZE {
             return Err(ProtocolError::Underflow);
         }
 
-        let data1 = StatusData1::from_bytes(&data[0..4])?;
-        let data2 = StatusData2::from_bytes(&data[4..8])?;
-
-        Ok(Self { data1, data2 })
+        let layout = StatusLayout::decode(data, crate::encoding::TextEncoding::Utf8)?;
+        Ok(Self {
+            data1: StatusData1::from_word(layout.data1)?,
+            data2: StatusData2::from_word(layout.data2)?,
+        })
     }
 
     /// Create Status from Data 1 and Data 2 instances
@@ -69,10 +72,8 @@ impl HsesPayload for Status {
         &self,
         _encoding: crate::encoding::TextEncoding,
     ) -> Result<Vec<u8>, ProtocolError> {
-        let mut data = Vec::new();
-        data.extend(self.data1.serialize(crate::encoding::TextEncoding::Utf8)?);
-        data.extend(self.data2.serialize(crate::encoding::TextEncoding::Utf8)?);
-        Ok(data)
+        let layout = StatusLayout { data1: self.data1.word(), data2: self.data2.word() };
+        Ok(layout.encode(crate::encoding::TextEncoding::Utf8))
     }
     fn deserialize(
         data: &[u8],
@@ -82,6 +83,26 @@ impl HsesPayload for Status {
     }
 }
 
+/// Attributes 1 and 2: Data 1 and Data 2
+impl AttributeDeserialize for Status {
+    const ATTRIBUTES: &'static [WireField] = StatusLayout::FIELDS;
+
+    fn deserialize_all(
+        data: &[u8],
+        _encoding: crate::encoding::TextEncoding,
+    ) -> Result<Self, ProtocolError> {
+        Self::from_bytes(data)
+    }
+}
+
+wire_layout! {
+    /// Status (0x72) as on the wire: the status words of Data 1 and Data 2
+    pub struct StatusLayout, size = 8 {
+        data1: u32,
+        data2: u32,
+    }
+}
+
 // Attribute-specific status structures
 #[derive(Debug, Clone, PartialEq, Eq)]
 #[allow(clippy::struct_excessive_bools)]
@@ -119,7 +140,44 @@ impl StatusData1 {
         }
 
         let mut buf = data;
-        let status_word = u16::try_from(buf.get_u32_le())
+        Self::from_word(buf.get_u32_le())
+    }
+
+    // Status word with the flags set
+    const fn word(&self) -> u32 {
+        let mut status_word = 0u32;
+
+        if self.step {
+            status_word |= 0x0001;
+        }
+        if self.one_cycle {
+            status_word |= 0x0002;
+        }
+        if self.continuous {
+            status_word |= 0x0004;
+        }
+        if self.running {
+            status_word |= 0x0008;
+        }
+        if self.speed_limited {
+            status_word |= 0x0010;
+        }
+        if self.teach {
+            status_word |= 0x0020;
+        }
+        if self.play {
+            status_word |= 0x0040;
+        }
+        if self.remote {
+            status_word |= 0x0080;
+        }
+
+        status_word
+    }
+
+    // Flags of a status word; only the low 16 bits may be set
+    fn from_word(word: u32) -> Result<Self, ProtocolError> {
+        let status_word = u16::try_from(word)
             .map_err(|_| ProtocolError::Deserialization("Invalid status word value".to_string()))?;
 
         Ok(Self {
@@ -147,7 +205,38 @@ impl StatusData2 {
         }
 
         let mut buf = data;
-        let status_word = u16::try_from(buf.get_u32_le())
+        Self::from_word(buf.get_u32_le())
+    }
+
+    // Status word with the flags set
+    const fn word(&self) -> u32 {
+        let mut status_word = 0u32;
+
+        if self.teach_pendant_hold {
+            status_word |= 0x0002;
+        }
+        if self.external_hold {
+            status_word |= 0x0004;
+        }
+        if self.command_hold {
+            status_word |= 0x0008;
+        }
+        if self.alarm {
+            status_word |= 0x0010;
+        }
+        if self.error {
+            status_word |= 0x0020;
+        }
+        if self.servo_on {
+            status_word |= 0x0040;
+        }
+
+        status_word
+    }
+
+    // Flags of a status word; only the low 16 bits may be set
+    fn from_word(word: u32) -> Result<Self, ProtocolError> {
+        let status_word = u16::try_from(word)
             .map_err(|_| ProtocolError::Deserialization("Invalid status word value".to_string()))?;
 
         Ok(Self {
@@ -166,36 +255,7 @@ impl HsesPayload for StatusData1 {
         &self,
         _encoding: crate::encoding::TextEncoding,
     ) -> Result<Vec<u8>, ProtocolError> {
-        let mut data = Vec::new();
-        let mut status_word = 0u32;
-
-        if self.step {
-            status_word |= 0x0001;
-        }
-        if self.one_cycle {
-            status_word |= 0x0002;
-        }
-        if self.continuous {
-            status_word |= 0x0004;
-        }
-        if self.running {
-            status_word |= 0x0008;
-        }
-        if self.speed_limited {
-            status_word |= 0x0010;
-        }
-        if self.teach {
-            status_word |= 0x0020;
-        }
-        if self.play {
-            status_word |= 0x0040;
-        }
-        if self.remote {
-            status_word |= 0x0080;
-        }
-
-        data.extend_from_slice(&status_word.to_le_bytes());
-        Ok(data)
+        Ok(self.word().to_le_bytes().to_vec())
     }
     fn deserialize(
         data: &[u8],
@@ -210,30 +270,7 @@ impl HsesPayload for StatusData2 {
         &self,
         _encoding: crate::encoding::TextEncoding,
     ) -> Result<Vec<u8>, ProtocolError> {
-        let mut data = Vec::new();
-        let mut status_word = 0u32;
-
-        if self.teach_pendant_hold {
-            status_word |= 0x0002;
-        }
-        if self.external_hold {
-            status_word |= 0x0004;
-        }
-        if self.command_hold {
-            status_word |= 0x0008;
-        }
-        if self.alarm {
-            status_word |= 0x0010;
-        }
-        if self.error {
-            status_word |= 0x0020;
-        }
-        if self.servo_on {
-            status_word |= 0x0040;
-        }
-
-        data.extend_from_slice(&status_word.to_le_bytes());
-        Ok(data)
+        Ok(self.word().to_le_bytes().to_vec())
     }
     fn deserialize(
         data: &[u8],
@@ -252,6 +289,11 @@ mod tests {
     fn test_status_from_bytes() {
         let data = vec![0x01, 0x00, 0x00, 0x00, 0x40, 0x00, 0x00, 0x00];
         let status = Status::from_bytes(&data).unwrap();
+        let data2 =
+            Status::deserialize_attribute(&data[4..], 2, crate::encoding::TextEncoding::Utf8)
+                .unwrap();
+        assert_eq!(data2.data2, status.data2);
+        assert!(!data2.data1.step);
         assert!(status.data1.step);
         assert!(status.data2.servo_on);
         assert!(!status.data1.running);
@@ -0,0 +1,93 @@
+//! System information data structures
+
+use crate::error::ProtocolError;
+use crate::payload::layout::{WireField, wire_layout};
+use crate::payload::{AttributeDeserialize, HsesPayload};
+
+/// Size of the complete system information
+pub const SYSTEM_INFO_SIZE: usize = SystemInfoLayout::SIZE;
+
+wire_layout! {
+    /// System information (0x89) as on the wire
+    pub struct SystemInfoLayout, size = 48 {
+        software_version: text(24),
+        /// Model name of a robot or station, or the application of the system
+        model: text(16),
+        parameter_version: text(8),
+    }
+}
+
+/// System information reported by the controller (0x89)
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub struct SystemInfo {
+    /// System software version, e.g. "YAS4.10.00A-00"
+    pub software_version: String,
+    /// Model name of a robot or station, or the application of the system
+    pub model: String,
+    pub parameter_version: String,
+}
+
+impl SystemInfo {
+    #[must_use]
+    pub const fn new(software_version: String, model: String, parameter_version: String) -> Self {
+        Self { software_version, model, parameter_version }
+    }
+}
+
+impl HsesPayload for SystemInfo {
+    fn serialize(&self, encoding: crate::encoding::TextEncoding) -> Result<Vec<u8>, ProtocolError> {
+        let layout = SystemInfoLayout {
+            software_version: self.software_version.clone(),
+            model: self.model.clone(),
+            parameter_version: self.parameter_version.clone(),
+        };
+        Ok(layout.encode(encoding))
+    }
+
+    fn deserialize(
+        data: &[u8],
+        encoding: crate::encoding::TextEncoding,
+    ) -> Result<Self, ProtocolError> {
+        let layout = SystemInfoLayout::decode(data, encoding)?;
+        Ok(Self {
+            software_version: layout.software_version,
+            model: layout.model,
+            parameter_version: layout.parameter_version,
+        })
+    }
+}
+
+/// Attributes 1-3: software version, model and parameter version
+impl AttributeDeserialize for SystemInfo {
+    const ATTRIBUTES: &'static [WireField] = SystemInfoLayout::FIELDS;
+
+    fn deserialize_all(
+        data: &[u8],
+        encoding: crate::encoding::TextEncoding,
+    ) -> Result<Self, ProtocolError> {
+        Self::deserialize(data, encoding)
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use crate::encoding::TextEncoding;
+
+    #[test]
+    fn test_system_info_round_trip() {
+        let info = SystemInfo {
+            software_version: "YAS4.10.00A-00".to_string(),
+            model: "GP8".to_string(),
+            parameter_version: "1.10".to_string(),
+        };
+        let data = info.serialize(TextEncoding::Utf8).unwrap();
+        assert_eq!(data.len(), SYSTEM_INFO_SIZE);
+        assert_eq!(&data[24..27], b"GP8");
+        assert_eq!(SystemInfo::deserialize(&data, TextEncoding::Utf8).unwrap(), info);
+
+        assert!(SystemInfo::deserialize(&data[..47], TextEncoding::Utf8).is_err());
+    }
+}
@@ -83,22 +83,43 @@ impl HsesPayload for f32 {
     }
 }
 
-impl HsesPayload for String {
-    fn serialize(&self, encoding: crate::encoding::TextEncoding) -> Result<Vec<u8>, ProtocolError> {
-        // S variables are 16 bytes (4 × 32-bit integers)
-        let encoded_bytes = crate::encoding_utils::encode_string(self, encoding);
+/// Size of a character variable (S) [bytes]
+pub const STRING_VARIABLE_SIZE: usize = 16;
 
-        if encoded_bytes.len() > 16 {
-            return Err(ProtocolError::InvalidMessage(format!(
-                "String exceeds 16 bytes when encoded: {} bytes",
-                encoded_bytes.len()
-            )));
-        }
+/// Size of a character variable (S) on controllers with extended strings [bytes]
+pub const EXTENDED_STRING_VARIABLE_SIZE: usize = 32;
 
-        // Pad to 16 bytes with null terminator
-        let mut result = vec![0u8; 16];
-        result[..encoded_bytes.len()].copy_from_slice(&encoded_bytes);
-        Ok(result)
+/// S variable data: the encoded text padded with nulls to `size` bytes
+///
+/// Text that does not fit or has characters the encoding cannot represent is rejected
+/// rather than cut or replaced.
+///
+/// # Errors
+///
+/// Returns [`ProtocolError::StringTooLong`] if the encoded text exceeds `size` bytes, or
+/// [`ProtocolError::Serialization`] if it cannot be encoded
+pub fn encode_string_variable(
+    string: &str,
+    encoding: crate::encoding::TextEncoding,
+    size: usize,
+) -> Result<Vec<u8>, ProtocolError> {
+    let (encoded, _, had_errors) = encoding.to_encoding().encode(string);
+    if had_errors {
+        return Err(ProtocolError::Serialization(format!(
+            "{string:?} has characters that cannot be encoded as {encoding:?}"
+        )));
+    }
+    if encoded.len() > size {
+        return Err(ProtocolError::StringTooLong { limit: size, encoded_len: encoded.len() });
+    }
+    let mut data = vec![0u8; size];
+    data[..encoded.len()].copy_from_slice(&encoded);
+    Ok(data)
+}
+
+impl HsesPayload for String {
+    fn serialize(&self, encoding: crate::encoding::TextEncoding) -> Result<Vec<u8>, ProtocolError> {
+        encode_string_variable(self, encoding, STRING_VARIABLE_SIZE)
     }
 
     fn deserialize(
@@ -244,21 +265,13 @@ impl HsesPayload for Vec<f32> {
 impl HsesPayload for Vec<String> {
     fn serialize(&self, encoding: crate::encoding::TextEncoding) -> Result<Vec<u8>, ProtocolError> {
         // Multiple S variables: serialize as byte array (each element is 16 bytes)
-        let mut result = Vec::with_capacity(self.len() * 16);
+        let mut result = Vec::with_capacity(self.len() * STRING_VARIABLE_SIZE);
         for string in self {
-            let encoded_bytes = crate::encoding_utils::encode_string(string, encoding);
-
-            if encoded_bytes.len() > 16 {
-                return Err(ProtocolError::InvalidMessage(format!(
-                    "String exceeds 16 bytes when encoded: {} bytes",
-                    encoded_bytes.len()
-                )));
-            }
-
-            // Pad to 16 bytes with null terminator
-            let mut byte_array = vec![0u8; 16];
-            byte_array[..encoded_bytes.len()].copy_from_slice(&encoded_bytes);
-            result.extend_from_slice(&byte_array);
+            result.extend_from_slice(&encode_string_variable(
+                string,
+                encoding,
+                STRING_VARIABLE_SIZE,
+            )?);
         }
         Ok(result)
     }
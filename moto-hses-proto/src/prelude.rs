@@ -0,0 +1,26 @@
+//! Commonly needed traits and types
+//!
+//! The traits must be in scope to serialize commands and deserialize payloads, e.g.
+//! `command_id()` or `Position::deserialize`:
+//!
+//! ```
+//! use moto_hses_proto::prelude::*;
+//!
+//! let command = ReadVariable::<i32>::new(5);
+//! assert_eq!(ReadVariable::<i32>::command_id(), 0x7c);
+//! assert!(command.serialize()?.is_empty());
+//! # Ok::<(), ProtocolError>(())
+//! ```
+
+pub use crate::commands::{
+    AlarmAttribute, AlarmCategory, AttributeRead, Command, CycleMode, Division, IoBit, MoveFrame,
+    MoveSpeed, MultipleVariableCommandId, ReadVariable, Service, VariableCommandId, WriteVariable,
+};
+pub use crate::constants::{FILE_CONTROL_PORT, ROBOT_CONTROL_PORT};
+pub use crate::controller::ControllerModel;
+pub use crate::encoding::TextEncoding;
+pub use crate::error::ProtocolError;
+pub use crate::payload::{
+    Alarm, AttributeDeserialize, CartesianPosition, ExecutingJobInfo, HsesPayload, Position,
+    PulsePosition, Status,
+};
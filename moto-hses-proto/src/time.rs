@@ -0,0 +1,196 @@
+//! Timestamps written by the controller
+
+use crate::error::ProtocolError;
+
+/// Date and time as the controller writes it, e.g. in alarm data or its event log
+///
+/// HSES cannot read the controller clock, and the timestamps carry no time zone, so they
+/// are only ordered and compared with each other. Conversions to and from Unix seconds
+/// treat them as UTC.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ControllerDateTime {
+    pub year: u16,
+    pub month: u8,
+    pub day: u8,
+    pub hour: u8,
+    pub minute: u8,
+    pub second: u8,
+}
+
+impl ControllerDateTime {
+    /// Create a date/time, validating each field
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a field is out of range (e.g. month 13 or February 30)
+    pub fn new(
+        year: u16,
+        month: u8,
+        day: u8,
+        hour: u8,
+        minute: u8,
+        second: u8,
+    ) -> Result<Self, ProtocolError> {
+        if !(1970..=9999).contains(&year)
+            || !(1..=12).contains(&month)
+            || day == 0
+            || day > days_in_month(year, month)
+            || hour > 23
+            || minute > 59
+            || second > 59
+        {
+            return Err(ProtocolError::InvalidMessage(format!(
+                "invalid date/time {year:04}/{month:02}/{day:02} {hour:02}:{minute:02}:{second:02}"
+            )));
+        }
+        Ok(Self { year, month, day, hour, minute, second })
+    }
+
+    /// Parse `YYYY/MM/DD HH:MM:SS`, or `YYYY/MM/DD HH:MM` as used in alarm times
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the text is not a valid date/time in either format
+    pub fn parse(text: &str) -> Result<Self, ProtocolError> {
+        let invalid = || ProtocolError::InvalidMessage(format!("invalid date/time '{text}'"));
+        let (date, time) = text.trim().split_once(' ').ok_or_else(invalid)?;
+        let date: Vec<&str> = date.split('/').collect();
+        let time: Vec<&str> = time.trim().split(':').collect();
+        if date.len() != 3 || !(2..=3).contains(&time.len()) {
+            return Err(invalid());
+        }
+        let field = |value: &str| value.parse::<u8>().map_err(|_| invalid());
+
+        Self::new(
+            date[0].parse().map_err(|_| invalid())?,
+            field(date[1])?,
+            field(date[2])?,
+            field(time[0])?,
+            field(time[1])?,
+            time.get(2).map_or(Ok(0), |second| field(second))?,
+        )
+    }
+
+    /// Seconds since the Unix epoch
+    #[must_use]
+    pub fn to_unix_seconds(&self) -> i64 {
+        let days = days_from_civil(i64::from(self.year), self.month, self.day);
+        days * 86_400
+            + i64::from(self.hour) * 3_600
+            + i64::from(self.minute) * 60
+            + i64::from(self.second)
+    }
+
+    /// Date/time from seconds since the Unix epoch
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the time is outside the years 1970-9999
+    pub fn from_unix_seconds(seconds: i64) -> Result<Self, ProtocolError> {
+        let out_of_range = || ProtocolError::InvalidMessage(format!("time {seconds} out of range"));
+        let (year, month, day) = civil_from_days(seconds.div_euclid(86_400));
+        let second_of_day = seconds.rem_euclid(86_400);
+        let narrow = |value: i64| u8::try_from(value).map_err(|_| out_of_range());
+
+        Self::new(
+            u16::try_from(year).map_err(|_| out_of_range())?,
+            month,
+            day,
+            narrow(second_of_day / 3_600)?,
+            narrow(second_of_day / 60 % 60)?,
+            narrow(second_of_day % 60)?,
+        )
+    }
+}
+
+impl Default for ControllerDateTime {
+    /// 1970/01/01 00:00:00
+    fn default() -> Self {
+        Self { year: 1970, month: 1, day: 1, hour: 0, minute: 0, second: 0 }
+    }
+}
+
+impl std::fmt::Display for ControllerDateTime {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{:04}/{:02}/{:02} {:02}:{:02}:{:02}",
+            self.year, self.month, self.day, self.hour, self.minute, self.second
+        )
+    }
+}
+
+const fn is_leap_year(year: u16) -> bool {
+    (year.is_multiple_of(4) && !year.is_multiple_of(100)) || year.is_multiple_of(400)
+}
+
+const fn days_in_month(year: u16, month: u8) -> u8 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+// Days since 1970-01-01 of a proleptic Gregorian date
+fn days_from_civil(year: i64, month: u8, day: u8) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month = i64::from(month);
+    let day_of_year =
+        (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + i64::from(day) - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+// Proleptic Gregorian date of a number of days since 1970-01-01
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn civil_from_days(days: i64) -> (i64, u8, u8) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    // Both values are small and non-negative by construction
+    let day = (day_of_year - (153 * shifted_month + 2) / 5 + 1) as u8;
+    let month = (if shifted_month < 10 { shifted_month + 3 } else { shifted_month - 9 }) as u8;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_display() {
+        let time = ControllerDateTime::parse("2024/02/29 23:59:58").unwrap();
+        assert_eq!(time, ControllerDateTime::new(2024, 2, 29, 23, 59, 58).unwrap());
+        assert_eq!(time.to_string(), "2024/02/29 23:59:58");
+
+        // Alarm times have minute resolution
+        let alarm_time = ControllerDateTime::parse("2024/01/01 12:00").unwrap();
+        assert_eq!(alarm_time.second, 0);
+
+        for invalid in
+            ["", "2024/01/01", "2023/02/29 00:00", "2024/13/01 00:00", "2024/01/01 24:00"]
+        {
+            assert!(ControllerDateTime::parse(invalid).is_err(), "'{invalid}' should be rejected");
+        }
+    }
+
+    #[test]
+    fn test_unix_seconds_round_trip() {
+        let epoch = ControllerDateTime::new(1970, 1, 1, 0, 0, 0).unwrap();
+        assert_eq!(epoch.to_unix_seconds(), 0);
+
+        let time = ControllerDateTime::new(2024, 3, 1, 12, 34, 56).unwrap();
+        assert_eq!(time.to_unix_seconds(), 1_709_296_496);
+        assert_eq!(ControllerDateTime::from_unix_seconds(1_709_296_496).unwrap(), time);
+    }
+}
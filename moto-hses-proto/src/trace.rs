@@ -0,0 +1,194 @@
+//! Human-readable dumps of HSES frames
+//!
+//! [`FrameDump`] formats a raw frame as its decoded header fields followed by the payload
+//! in hex, for debug logs, documentation examples and debugging sessions:
+//!
+//! ```
+//! use moto_hses_proto::trace::FrameDump;
+//! use moto_hses_proto::{Command, Division, HsesRequestMessage, ReadRegister};
+//!
+//! let command = ReadRegister::new(5);
+//! let message = HsesRequestMessage::new(
+//!     Division::Robot as u8,
+//!     0,
+//!     1,
+//!     ReadRegister::command_id(),
+//!     command.instance(),
+//!     command.attribute(),
+//!     command.service().code(),
+//!     vec![],
+//! )
+//! .unwrap();
+//! let frame = message.encode();
+//! assert_eq!(
+//!     FrameDump::new(&frame).to_string(),
+//!     "HSES request, 32 bytes\n\
+//!      \x20 header:     division=robot ack=0 request_id=1 block=0x00000000 payload_size=0\n\
+//!      \x20 sub-header: command=0x0079 instance=5 attribute=1 service=0x0E (GetSingle)\n\
+//!      \x20 payload:    (empty)"
+//! );
+//! ```
+
+use std::fmt;
+
+use crate::commands::Service;
+use crate::constants::{RESPONSE_SERVICE_FLAG, header};
+
+// Bytes per line of the payload hex dump
+const BYTES_PER_LINE: usize = 16;
+
+// Indentation of the payload lines after the first
+const PAYLOAD_INDENT: &str = "              ";
+
+/// Display adapter printing a raw frame with its decoded header fields
+///
+/// Frames shorter than the header or without the "YERC" magic are printed as hex only.
+#[derive(Debug, Clone, Copy)]
+pub struct FrameDump<'a> {
+    frame: &'a [u8],
+}
+
+impl<'a> FrameDump<'a> {
+    #[must_use]
+    pub const fn new(frame: &'a [u8]) -> Self {
+        Self { frame }
+    }
+}
+
+impl fmt::Display for FrameDump<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let frame = self.frame;
+        if frame.len() < header::SIZE || &frame[..header::MAGIC.len()] != header::MAGIC {
+            write!(f, "invalid HSES frame, {} bytes\n  data:       ", frame.len())?;
+            return write_hex(f, frame);
+        }
+
+        let u16_at = |offset: usize| u16::from_le_bytes([frame[offset], frame[offset + 1]]);
+        let ack = frame[header::ACK_OFFSET];
+        let block_number = u32::from_le_bytes([
+            frame[header::BLOCK_NUMBER_OFFSET],
+            frame[header::BLOCK_NUMBER_OFFSET + 1],
+            frame[header::BLOCK_NUMBER_OFFSET + 2],
+            frame[header::BLOCK_NUMBER_OFFSET + 3],
+        ]);
+
+        writeln!(
+            f,
+            "HSES {}, {} bytes",
+            if ack == 0 { "request" } else { "response" },
+            frame.len()
+        )?;
+        writeln!(
+            f,
+            "  header:     division={} ack={ack} request_id={} block=0x{block_number:08X} payload_size={}",
+            division_name(frame[header::DIVISION_OFFSET]),
+            frame[header::REQUEST_ID_OFFSET],
+            u16_at(header::PAYLOAD_SIZE_OFFSET),
+        )?;
+        if ack == 0 {
+            writeln!(
+                f,
+                "  sub-header: command=0x{:04X} instance={} attribute={} service={}",
+                u16_at(header::COMMAND_OFFSET),
+                u16_at(header::INSTANCE_OFFSET),
+                frame[header::ATTRIBUTE_OFFSET],
+                ServiceCode(frame[header::REQUEST_SERVICE_OFFSET]),
+            )?;
+        } else {
+            writeln!(
+                f,
+                "  sub-header: service={} status=0x{:02X} added_status=0x{:04X}",
+                ServiceCode(frame[header::RESPONSE_SERVICE_OFFSET]),
+                frame[header::STATUS_OFFSET],
+                u16_at(header::ADDED_STATUS_OFFSET),
+            )?;
+        }
+        write!(f, "  payload:    ")?;
+        write_hex(f, &frame[header::SIZE..])
+    }
+}
+
+// Name of a division byte, e.g. "robot"
+fn division_name(division: u8) -> String {
+    match division {
+        1 => "robot".to_string(),
+        2 => "file".to_string(),
+        _ => format!("0x{division:02X}"),
+    }
+}
+
+// Service code with the name of the service, e.g. "0x8E (GetSingle)" in a response
+struct ServiceCode(u8);
+
+impl fmt::Display for ServiceCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "0x{:02X}", self.0)?;
+        Service::try_from(self.0 & !RESPONSE_SERVICE_FLAG)
+            .map_or(Ok(()), |service| write!(f, " ({service:?})"))
+    }
+}
+
+// Offset, hex bytes and printable ASCII of `data`, 16 bytes per line
+fn write_hex(f: &mut fmt::Formatter<'_>, data: &[u8]) -> fmt::Result {
+    if data.is_empty() {
+        return f.write_str("(empty)");
+    }
+    for (line, chunk) in data.chunks(BYTES_PER_LINE).enumerate() {
+        if line > 0 {
+            write!(f, "\n{PAYLOAD_INDENT}")?;
+        }
+        write!(f, "{:04X} ", line * BYTES_PER_LINE)?;
+        for byte in chunk {
+            write!(f, " {byte:02X}")?;
+        }
+        let padding = (BYTES_PER_LINE - chunk.len()) * 3;
+        write!(f, "{:padding$}  ", "")?;
+        for &byte in chunk {
+            let shown =
+                if byte.is_ascii_graphic() || byte == b' ' { char::from(byte) } else { '.' };
+            write!(f, "{shown}")?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use crate::HsesResponseMessage;
+
+    #[test]
+    fn test_response_dump() {
+        let message =
+            HsesResponseMessage::new(1, 1, 9, 0x33, 0x00, 0, b"ABCDEFGHIJKLMNOPQR".to_vec())
+                .unwrap();
+        let dump = FrameDump::new(&message.encode()).to_string();
+        let lines: Vec<&str> = dump.lines().collect();
+        assert_eq!(lines[0], "HSES response, 50 bytes");
+        assert_eq!(
+            lines[1],
+            "  header:     division=robot ack=1 request_id=9 block=0x80000000 payload_size=18"
+        );
+        assert_eq!(
+            lines[2],
+            "  sub-header: service=0xB3 (ReadPlural) status=0x00 added_status=0x0000"
+        );
+        assert_eq!(
+            lines[3],
+            "  payload:    0000  41 42 43 44 45 46 47 48 49 4A 4B 4C 4D 4E 4F 50  ABCDEFGHIJKLMNOP"
+        );
+        assert_eq!(
+            lines[4],
+            "              0010  51 52                                            QR"
+        );
+    }
+
+    #[test]
+    fn test_invalid_frame_dump() {
+        assert_eq!(
+            FrameDump::new(&[0x59, 0x45, 0x00]).to_string(),
+            "invalid HSES frame, 3 bytes\n  data:       0000  59 45 00                                         YE."
+        );
+    }
+}
@@ -0,0 +1,122 @@
+#![allow(clippy::unwrap_used)]
+// Wire compatibility with payloads produced by earlier published versions
+//
+// The fixtures in `tests/fixtures/v<version>/` were serialized by that release of the
+// crate, as hex dumps of 16 bytes per line. Each must still deserialize to the same value
+// and serialize back to the same bytes: a change failing here changes the wire format of a
+// type documented as wire-stable, and with it the traffic of plants mixing versions.
+
+use moto_hses_proto::payload::position::{Configuration, ExtendedConfiguration};
+use moto_hses_proto::{
+    Alarm, CartesianPosition, ExecutingJobInfo, HsesPayload, Position, PulsePosition, Status,
+    StatusData1, StatusData2, TextEncoding,
+};
+
+fn fixture(hex: &str) -> Vec<u8> {
+    hex.split_whitespace().map(|byte| u8::from_str_radix(byte, 16).unwrap()).collect()
+}
+
+// Deserialize a fixture, compare it with `expected` and serialize it back
+fn assert_round_trip<T: HsesPayload + PartialEq + std::fmt::Debug>(
+    hex: &str,
+    encoding: TextEncoding,
+    expected: &T,
+) {
+    let data = fixture(hex);
+    let value = T::deserialize(&data, encoding).unwrap();
+    assert_eq!(&value, expected);
+    assert_eq!(value.serialize(encoding).unwrap(), data);
+}
+
+#[test]
+fn test_v0_4_0_alarms() {
+    let alarm =
+        Alarm::new(4100, 1, 0, "2024/04/22 10:31".to_string(), "OVERRUN(ROBOT)".to_string())
+            .with_sub_code("[1]".to_string(), "1 2".to_string(), "0".to_string());
+    assert_round_trip(include_str!("fixtures/v0.4.0/alarm_utf8.hex"), TextEncoding::Utf8, &alarm);
+
+    let alarm = Alarm::new(1010, 0, 0, "2024/04/22 10:31".to_string(), "非常停止中".to_string());
+    assert_round_trip(
+        include_str!("fixtures/v0.4.0/alarm_shift_jis.hex"),
+        TextEncoding::ShiftJis,
+        &alarm,
+    );
+}
+
+#[test]
+fn test_v0_4_0_job_and_status() {
+    let job = ExecutingJobInfo::new("PALLET_MAIN.JOB".to_string(), 12, 5, 100);
+    assert_round_trip(
+        include_str!("fixtures/v0.4.0/executing_job_info.hex"),
+        TextEncoding::Utf8,
+        &job,
+    );
+
+    let status = Status::new(
+        StatusData1 {
+            step: false,
+            one_cycle: false,
+            continuous: true,
+            running: true,
+            speed_limited: false,
+            teach: false,
+            play: true,
+            remote: true,
+        },
+        StatusData2 {
+            teach_pendant_hold: false,
+            external_hold: true,
+            command_hold: false,
+            alarm: true,
+            error: false,
+            servo_on: true,
+        },
+    );
+    assert_round_trip(include_str!("fixtures/v0.4.0/status.hex"), TextEncoding::Utf8, &status);
+}
+
+#[test]
+fn test_v0_4_0_positions() {
+    let pulse =
+        Position::Pulse(PulsePosition::new(vec![1000, -2000, 3000, -4000, 5000, -6000, 0, 0]));
+    assert_round_trip(
+        include_str!("fixtures/v0.4.0/position_pulse.hex"),
+        TextEncoding::Utf8,
+        &pulse,
+    );
+
+    let cartesian = Position::Cartesian(CartesianPosition::new(
+        350.5,
+        -120.25,
+        410.0,
+        180.0,
+        -0.5,
+        90.0,
+        2,
+        1,
+        Configuration::from_raw(0b0010_0101),
+        ExtendedConfiguration::from_raw(0b0000_0011),
+    ));
+    // 0.4.0 left out the 7th and 8th axis words of Cartesian positions; they are now sent
+    // as zeros, which 0.4.0 ignores
+    let data = fixture(include_str!("fixtures/v0.4.0/position_cartesian.hex"));
+    let value = Position::deserialize(&data, TextEncoding::Utf8).unwrap();
+    assert_eq!(value, cartesian);
+    let serialized = value.serialize(TextEncoding::Utf8).unwrap();
+    assert_eq!(serialized[..data.len()], data);
+    assert_eq!(serialized[data.len()..], [0; 8]);
+}
+
+#[test]
+fn test_v0_4_0_variables() {
+    assert_round_trip(
+        include_str!("fixtures/v0.4.0/variable_string_shift_jis.hex"),
+        TextEncoding::ShiftJis,
+        &"溶接ライン".to_string(),
+    );
+    assert_round_trip(
+        include_str!("fixtures/v0.4.0/variable_f32.hex"),
+        TextEncoding::Utf8,
+        &2.5f32,
+    );
+}